@@ -2,6 +2,8 @@
 
 #if ZE_BACKEND_D3D12
 #define ZE_PUSH_CONSTANT
+#elif ZE_BACKEND_VULKAN
+#define ZE_PUSH_CONSTANT [[vk::push_constant]]
 #else
 #error "Backend doesn't support push constants"
 #endif
@@ -9,7 +11,7 @@
 typedef uint ResourceHandle;
 
 // Bindless support
-#if ZE_BACKEND_D3D12
+#if ZE_BACKEND_D3D12 || ZE_BACKEND_VULKAN
 inline Texture2D get_texture(ResourceHandle handle)
 {
     return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
@@ -20,6 +22,21 @@ inline TextureCube get_texture_cube(ResourceHandle handle)
     return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
 }
 
+inline Texture3D get_texture_3d(ResourceHandle handle)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
+}
+
+inline RWTexture2D<float4> get_rw_texture(ResourceHandle handle)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
+}
+
+inline RWTexture3D<float4> get_rw_texture_3d(ResourceHandle handle)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
+}
+
 inline SamplerState get_sampler(ResourceHandle handle)
 {
     return SamplerDescriptorHeap[NonUniformResourceIndex(handle)];
@@ -31,6 +48,12 @@ inline StructuredBuffer<StructType> get_structured_buffer(ResourceHandle handle)
     return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
 }
 
+template<typename StructType>
+inline RWStructuredBuffer<StructType> get_rw_structured_buffer(ResourceHandle handle)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
+}
+
 inline ByteAddressBuffer get_byte_address_buffer(ResourceHandle handle)
 {
     return ResourceDescriptorHeap[NonUniformResourceIndex(handle)];
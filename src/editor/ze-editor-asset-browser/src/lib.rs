@@ -0,0 +1,620 @@
+use cfg_if::cfg_if;
+use enumflags2::make_bitflags;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::process::Command;
+use std::sync::Arc;
+use ze_asset_editor::AssetEditorManager;
+use ze_asset_server::AssetServer;
+use ze_core::type_uuid::Uuid;
+use ze_core::{ze_error, ze_info};
+use ze_editor_selection::{SelectionItem, SelectionService};
+use ze_filesystem::path::Path;
+use ze_filesystem::{DirEntryType, FileSystem, IterDirFlagBits, IterDirFlags};
+use ze_gfx::backend::ShaderResourceView;
+use ze_imgui::ze_imgui_sys::*;
+use ze_imgui::*;
+use ze_platform::MouseButton;
+
+pub const ASSET_BROWSER_ID: &str = "Asset Browser";
+
+/// Drag and drop payload type used when dragging assets, the payload data is the asset UUID bytes
+pub const ASSET_UUID_PAYLOAD: &str = "ZE_ASSET_UUID";
+
+const DELETE_POPUP_ID: &str = "Delete##AssetBrowserDelete";
+const ENTRY_WIDTH: f32 = 90.0;
+const ENTRY_HEIGHT: f32 = 150.0;
+const THUMBNAIL_SIZE: f32 = 64.0;
+
+/// Provide thumbnails for assets of a specific type
+pub trait AssetThumbnailProvider {
+    fn thumbnail(&self, asset: Uuid) -> Option<Arc<ShaderResourceView>>;
+}
+
+struct TypeFilter {
+    name: String,
+    type_uuid: Uuid,
+    enabled: bool,
+}
+
+struct Entry {
+    ty: DirEntryType,
+    path: Path,
+    name: String,
+    asset_uuid: Option<Uuid>,
+    asset_type_uuid: Option<Uuid>,
+}
+
+/// Dockable panel listing the asset directories as a folder tree and a thumbnail grid
+pub struct AssetBrowser {
+    asset_server: Arc<AssetServer>,
+    filesystem: Arc<FileSystem>,
+    asset_editor_manager: Arc<AssetEditorManager>,
+    selection: Arc<SelectionService>,
+    root_directory: Path,
+    current_directory: Path,
+    directory_tree: TreeView<Path>,
+    directory_icon: Option<Arc<ShaderResourceView>>,
+    file_icon: Option<Arc<ShaderResourceView>>,
+    thumbnail_providers: HashMap<Uuid, Box<dyn AssetThumbnailProvider>>,
+    type_filters: Vec<TypeFilter>,
+    search: String,
+    entries: Vec<Entry>,
+    entries_dirty: bool,
+    pending_delete: Option<Path>,
+    open_delete_popup: bool,
+}
+
+impl AssetBrowser {
+    pub fn new(
+        asset_server: Arc<AssetServer>,
+        filesystem: Arc<FileSystem>,
+        asset_editor_manager: Arc<AssetEditorManager>,
+        selection: Arc<SelectionService>,
+        root_directory: Path,
+        directory_icon: Option<Arc<ShaderResourceView>>,
+        file_icon: Option<Arc<ShaderResourceView>>,
+    ) -> Self {
+        Self {
+            asset_server,
+            filesystem,
+            asset_editor_manager,
+            selection,
+            current_directory: root_directory.clone(),
+            directory_tree: TreeView::new(false),
+            root_directory,
+            directory_icon,
+            file_icon,
+            thumbnail_providers: Default::default(),
+            type_filters: vec![],
+            search: String::new(),
+            entries: vec![],
+            entries_dirty: true,
+            pending_delete: None,
+            open_delete_popup: false,
+        }
+    }
+
+    pub fn add_thumbnail_provider<P: AssetThumbnailProvider + 'static>(
+        &mut self,
+        type_uuid: Uuid,
+        provider: P,
+    ) {
+        self.thumbnail_providers
+            .insert(type_uuid, Box::new(provider));
+    }
+
+    /// Add a type that can be used to filter the displayed assets
+    pub fn add_type_filter(&mut self, name: &str, type_uuid: Uuid) {
+        self.type_filters.push(TypeFilter {
+            name: name.to_string(),
+            type_uuid,
+            enabled: false,
+        });
+    }
+
+    /// Copy files from outside of the asset directories to the current directory and import them
+    pub fn import_files(&mut self, files: &[std::path::PathBuf]) {
+        for file in files {
+            let file_name = match file.file_name().and_then(|name| name.to_str()) {
+                Some(file_name) => file_name,
+                None => continue,
+            };
+
+            let extension = file
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .unwrap_or_default();
+            if !self.asset_server.is_extension_importable(extension) {
+                ze_error!("No importer for {}", file.display());
+                continue;
+            }
+
+            let path = self.current_directory.join(file_name);
+            if self.filesystem.exists(&path) {
+                ze_error!("Can't import {}, {} already exists", file.display(), path);
+                continue;
+            }
+
+            let result = File::open(file)
+                .map_err(|error| error.to_string())
+                .and_then(|mut source| {
+                    let mut destination = self
+                        .filesystem
+                        .write(&path)
+                        .map_err(|error| error.to_string())?;
+                    io::copy(&mut source, &mut destination).map_err(|error| error.to_string())
+                });
+
+            if let Err(error) = result {
+                ze_error!("Failed to copy {} to {}: {}", file.display(), path, error);
+                continue;
+            }
+
+            if !self.asset_server.import_source_asset(&path) {
+                ze_error!("Failed to import {}", path);
+            }
+        }
+
+        self.entries_dirty = true;
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+        if self.entries_dirty {
+            self.refresh_entries();
+        }
+
+        imgui.begin_window(
+            ASSET_BROWSER_ID,
+            make_bitflags! { WindowFlagBits::{NoScrollbar | NoScrollWithMouse}},
+        );
+
+        self.draw_toolbar(imgui);
+
+        if imgui.begin_table(
+            "MainTable",
+            2,
+            make_bitflags! { TableFlagBits::{Resizable | NoBordersInBodyUntilResize} },
+            imgui.available_content_region(),
+        ) {
+            imgui.table_setup_column(
+                "Directory Hierarchy",
+                0.1,
+                TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+            );
+            imgui.table_next_row();
+            imgui.table_next_column();
+            self.draw_directory_hierarchy(imgui);
+
+            imgui.table_next_column();
+            self.draw_entries(imgui);
+            imgui.end_table();
+        }
+
+        self.draw_delete_popup(imgui);
+        imgui.end_window();
+    }
+
+    fn draw_toolbar(&mut self, imgui: &mut Context) {
+        if imgui.input_text_with_hint(
+            "##Search",
+            "Search assets",
+            &mut self.search,
+            InputTextFlags::empty(),
+        ) {
+            self.entries_dirty = true;
+        }
+
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Filters", ImVec2::default()) {
+            imgui.open_popup("##Filters");
+        }
+
+        if imgui.begin_popup("##Filters", WindowFlags::empty()) {
+            if self.type_filters.is_empty() {
+                imgui.text("No asset types registered");
+            }
+
+            for filter in &mut self.type_filters {
+                if imgui.checkbox(&filter.name, &mut filter.enabled) {
+                    self.entries_dirty = true;
+                }
+            }
+            imgui.end_popup();
+        }
+
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Refresh", ImVec2::default()) {
+            self.entries_dirty = true;
+        }
+    }
+
+    fn draw_entries(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+        imgui.begin_child(
+            "Directory List",
+            imgui.available_content_region(),
+            false,
+            WindowFlags::empty(),
+        );
+
+        imgui.dummy(ImVec2::new(0.0, 5.0));
+        imgui.dummy(ImVec2::new(10.0, 0.0));
+        imgui.same_line(0.0, -1.0);
+
+        let column_count = (imgui.available_content_region().x / ENTRY_WIDTH).clamp(1.0, 15.0);
+        imgui.begin_table(
+            "DirectoryListTable",
+            column_count as u32,
+            TableFlags::empty(),
+            ImVec2::default(),
+        );
+
+        imgui.table_next_row();
+
+        let mut new_directory = None;
+        let mut entry_to_delete = None;
+        for entry in &self.entries {
+            imgui.table_next_column();
+            imgui.begin_child(
+                entry.path.as_str(),
+                ImVec2::new(ENTRY_WIDTH, ENTRY_HEIGHT),
+                false,
+                make_bitflags! { WindowFlagBits::{NoScrollbar | NoScrollWithMouse} },
+            );
+
+            let cursor_pos = imgui.cursor_pos();
+            let cursor_screen_pos = imgui.cursor_screen_pos();
+            let size = imgui.available_content_region();
+            imgui.invisible_button("##Entry", size);
+
+            let hovered = imgui.is_item_hovered();
+            let selected = matches!(entry.asset_uuid, Some(uuid) if self.selection.is_selected(SelectionItem::Asset(uuid)));
+            if let Some(uuid) = entry.asset_uuid {
+                if imgui.is_item_clicked(MouseButton::Left) {
+                    if imgui.is_key_down(Key::LeftCtrl) {
+                        self.selection.toggle(SelectionItem::Asset(uuid));
+                    } else {
+                        self.selection.select(SelectionItem::Asset(uuid));
+                    }
+                }
+            }
+            if hovered && imgui.is_mouse_double_clicked(MouseButton::Left) {
+                if entry.ty == DirEntryType::Directory {
+                    new_directory = Some(entry.path.clone());
+                } else {
+                    self.open_asset(entry);
+                }
+            }
+
+            if let Some(uuid) = entry.asset_uuid {
+                if imgui.begin_drag_drop_source(DragDropFlags::empty()) {
+                    imgui.set_drag_drop_payload(ASSET_UUID_PAYLOAD, uuid.as_bytes());
+                    imgui.text(&entry.name);
+                    imgui.end_drag_drop_source();
+                }
+            }
+
+            if imgui.begin_popup_context_item("##EntryContext") {
+                if self.draw_entry_context_menu(imgui, entry) {
+                    entry_to_delete = Some(entry.path.clone());
+                }
+                imgui.end_popup();
+            }
+
+            if hovered && entry.ty == DirEntryType::File {
+                imgui.push_style_var_vec2f32(StyleVar::WindowPadding, ImVec2::new(5.0, 5.0));
+                imgui.begin_tooltip();
+                imgui.pop_style_var(1);
+                imgui.text(entry.path.path());
+                imgui.end_tooltip();
+            }
+
+            imgui.window_add_rect_filled(
+                cursor_screen_pos,
+                cursor_screen_pos + size,
+                if hovered {
+                    unsafe { (*igGetStyle()).Colors[ImGuiCol__ImGuiCol_HeaderHovered as usize] }
+                } else if selected {
+                    unsafe { (*igGetStyle()).Colors[ImGuiCol__ImGuiCol_Header as usize] }
+                } else {
+                    ImVec4::from(0.115)
+                },
+            );
+
+            imgui.set_cursor_pos(cursor_pos);
+            imgui.dummy(ImVec2::new(0.0, 15.0));
+
+            if let Some(icon) = self.entry_icon(entry) {
+                let icon = imgui.frame_texture(icon);
+                imgui.image_centered_x(icon, ImVec2::new(THUMBNAIL_SIZE, THUMBNAIL_SIZE));
+            }
+
+            imgui.dummy(ImVec2::new(0.0, 5.0));
+            imgui.text_centered_wrapped(&entry.name, 8);
+            imgui.end_child();
+        }
+
+        imgui.end_table();
+        imgui.end_child();
+
+        if let Some(directory) = new_directory {
+            self.set_current_directory(directory);
+        }
+
+        if entry_to_delete.is_some() {
+            self.pending_delete = entry_to_delete;
+            self.open_delete_popup = true;
+        }
+    }
+
+    /// Draw the entry context menu items, returns true if the entry should be deleted
+    fn draw_entry_context_menu(&self, imgui: &mut Context, entry: &Entry) -> bool {
+        let is_file = entry.ty == DirEntryType::File;
+
+        if imgui.menu_item("Open", None, false, is_file && entry.asset_uuid.is_some()) {
+            self.open_asset(entry);
+        }
+
+        if imgui.menu_item("Reimport", None, false, is_file)
+            && !self.asset_server.import_source_asset(&entry.path)
+        {
+            ze_error!("Failed to reimport {}", entry.path);
+        }
+
+        if imgui.menu_item("Show in Explorer", None, false, true) {
+            match self.filesystem.to_underlying_path(&entry.path) {
+                Ok(path) => show_in_file_explorer(&path),
+                Err(error) => ze_error!("Cannot show {} in explorer: {}", entry.path, error),
+            }
+        }
+
+        imgui.separator();
+        imgui.menu_item("Delete", None, false, true)
+    }
+
+    fn draw_delete_popup(&mut self, imgui: &mut Context) {
+        // Context menus are drawn inside entries so we need to open the popup from here
+        if self.open_delete_popup {
+            self.open_delete_popup = false;
+            imgui.open_dialog(DELETE_POPUP_ID);
+        }
+
+        if let Some(path) = self.pending_delete.clone() {
+            match imgui.confirm_dialog(
+                DELETE_POPUP_ID,
+                &format!("Are you sure you want to delete {}?", path.path()),
+                "Delete",
+            ) {
+                DialogResult::Confirmed(()) => {
+                    self.delete(&path);
+                    self.pending_delete = None;
+                }
+                DialogResult::Cancelled => self.pending_delete = None,
+                DialogResult::Pending => {}
+            }
+        }
+    }
+
+    fn draw_directory_hierarchy(&mut self, imgui: &mut Context) {
+        // TODO: Cache hierarchy
+
+        puffin::profile_function!();
+        imgui.begin_child(
+            "Directory Hierarchy",
+            imgui.available_content_region(),
+            false,
+            WindowFlags::empty(),
+        );
+        if self.directory_tree.selection() != [self.current_directory.clone()] {
+            self.directory_tree
+                .set_selection(vec![self.current_directory.clone()]);
+        }
+
+        let root_directory = self.root_directory.clone();
+        self.draw_directory_entry(imgui, &root_directory);
+        imgui.end_child();
+
+        let mut selected_directory = None;
+        self.directory_tree.for_each_event(|event| {
+            if let TreeViewEvent::SelectionChanged(mut directories) = event {
+                selected_directory = directories.pop();
+            }
+        });
+        if let Some(directory) = selected_directory {
+            self.set_current_directory(directory);
+        }
+    }
+
+    fn draw_directory_entry(&mut self, imgui: &mut Context, path: &Path) {
+        puffin::profile_function!(path.path());
+
+        let flags = make_bitflags! { TreeNodeFlagBits::{DefaultOpen | OpenOnArrow | OpenOnDoubleClick | SpanFullWidth } };
+
+        let name = path
+            .path_segments()
+            .next_back()
+            .unwrap_or_default()
+            .to_string();
+        if self
+            .directory_tree
+            .node(imgui, path.clone(), &format!("##{}", name), flags)
+        {
+            imgui.same_line(0.0, -1.0);
+            if let Some(icon) = &self.directory_icon {
+                let icon = imgui.frame_texture(icon.clone());
+                imgui.image(icon, ImVec2::new(16.0, 16.0));
+                imgui.same_line(0.0, -1.0);
+            }
+            imgui.text(&name);
+
+            let filesystem = self.filesystem.clone();
+            filesystem
+                .iter_dir(path, IterDirFlags::empty(), |entry| {
+                    if entry.ty == DirEntryType::Directory {
+                        self.draw_directory_entry(imgui, &entry.path);
+                    }
+                })
+                .unwrap_or_else(|_| ze_error!("Failed to iterate directory {}", path));
+            imgui.tree_pop();
+        }
+    }
+
+    fn set_current_directory(&mut self, path: Path) {
+        self.current_directory = path;
+        self.entries_dirty = true;
+    }
+
+    fn entry_icon(&self, entry: &Entry) -> Option<Arc<ShaderResourceView>> {
+        if entry.ty == DirEntryType::Directory {
+            return self.directory_icon.clone();
+        }
+
+        if let (Some(uuid), Some(type_uuid)) = (entry.asset_uuid, entry.asset_type_uuid) {
+            if let Some(thumbnail) = self
+                .thumbnail_providers
+                .get(&type_uuid)
+                .and_then(|provider| provider.thumbnail(uuid))
+            {
+                return Some(thumbnail);
+            }
+        }
+
+        self.file_icon.clone()
+    }
+
+    fn open_asset(&self, entry: &Entry) {
+        if let (Some(uuid), Some(type_uuid)) = (entry.asset_uuid, entry.asset_type_uuid) {
+            self.asset_editor_manager
+                .open_asset(type_uuid, uuid, &entry.path);
+        }
+    }
+
+    fn delete(&mut self, path: &Path) {
+        let result = if matches!(self.filesystem.to_underlying_path(path), Ok(path) if path.is_dir())
+        {
+            self.filesystem
+                .remove(path)
+                .map_err(|error| error.to_string())
+        } else {
+            self.asset_server
+                .remove_source_asset(path)
+                .map_err(|error| error.to_string())
+        };
+
+        match result {
+            Ok(_) => ze_info!("Deleted {}", path),
+            Err(error) => ze_error!("Failed to delete {}: {}", path, error),
+        }
+
+        if self.current_directory.is_within(path) {
+            self.current_directory = self.root_directory.clone();
+        }
+        self.entries_dirty = true;
+    }
+
+    /// Collect the entries to display, searching recursively from the root directory when a search
+    /// or a type filter is active
+    fn refresh_entries(&mut self) {
+        puffin::profile_function!();
+        self.entries_dirty = false;
+        self.entries.clear();
+
+        let search = self.search.to_lowercase();
+        let enabled_types = self
+            .type_filters
+            .iter()
+            .filter(|filter| filter.enabled)
+            .map(|filter| filter.type_uuid)
+            .collect::<Vec<_>>();
+        let filtering = !search.is_empty() || !enabled_types.is_empty();
+
+        let (directory, flags) = if filtering {
+            (
+                &self.root_directory,
+                IterDirFlags::from_flag(IterDirFlagBits::Recursive),
+            )
+        } else {
+            (&self.current_directory, IterDirFlags::empty())
+        };
+
+        let asset_server = &self.asset_server;
+        let entries = &mut self.entries;
+        self.filesystem
+            .iter_dir(directory, flags, |entry| {
+                let file_name = entry.path.path_segments().next_back().unwrap_or_default();
+                let mut file_name_and_extension = file_name.split('.');
+                let name = file_name_and_extension.next().unwrap_or_default();
+
+                let (asset_uuid, asset_type_uuid) = if entry.ty == DirEntryType::File {
+                    let extension = file_name_and_extension.next_back().unwrap_or_default();
+                    if !asset_server.is_extension_importable(extension) {
+                        return;
+                    }
+
+                    let asset_uuid = asset_server.asset_uuid_from_path(&entry.path);
+                    let asset_type_uuid =
+                        asset_uuid.and_then(|uuid| asset_server.asset_type_uuid(uuid));
+                    (asset_uuid, asset_type_uuid)
+                } else if filtering {
+                    return;
+                } else {
+                    (None, None)
+                };
+
+                if !search.is_empty() && !name.to_lowercase().contains(&search) {
+                    return;
+                }
+
+                if !enabled_types.is_empty()
+                    && asset_type_uuid
+                        .filter(|ty| enabled_types.contains(ty))
+                        .is_none()
+                {
+                    return;
+                }
+
+                entries.push(Entry {
+                    ty: entry.ty,
+                    path: entry.path.clone(),
+                    name: name.to_string(),
+                    asset_uuid,
+                    asset_type_uuid,
+                });
+            })
+            .unwrap_or_else(|_| ze_error!("Failed to iterate directory {}", directory));
+
+        self.entries.sort_by(|a, b| {
+            if a.ty != b.ty {
+                if a.ty == DirEntryType::Directory {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            } else {
+                a.name.cmp(&b.name)
+            }
+        });
+    }
+}
+
+fn show_in_file_explorer(path: &std::path::Path) {
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            let result = Command::new("explorer").arg("/select,").arg(path).spawn();
+        } else if #[cfg(target_os = "macos")] {
+            let result = Command::new("open").arg("-R").arg(path).spawn();
+        } else {
+            let result = Command::new("xdg-open")
+                .arg(path.parent().unwrap_or(path))
+                .spawn();
+        }
+    };
+
+    if let Err(error) = result {
+        ze_error!("Failed to open file explorer: {}", error);
+    }
+}
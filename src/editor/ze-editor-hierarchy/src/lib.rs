@@ -0,0 +1,265 @@
+use enumflags2::make_bitflags;
+use std::sync::Arc;
+use ze_ecs::entity::Entity;
+use ze_ecs::hierarchy::Name;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_editor_selection::{SelectionItem, SelectionService};
+use ze_imgui::ze_imgui_sys::ImVec2;
+use ze_imgui::{
+    Context, DragDropFlags, InputTextFlags, TableColumnFlagBits, TableColumnFlags, TableFlagBits,
+    TableFlags, TreeNodeFlagBits, TreeView, TreeViewEvent, WindowFlags,
+};
+use ze_platform::MouseButton;
+use ze_scene::transform::Transform;
+
+pub const HIERARCHY_ID: &str = "Hierarchy";
+pub const ENTITY_PAYLOAD: &str = "ZE_ENTITY";
+
+/// Marker for entities hidden in the editor viewports
+#[derive(Component)]
+pub struct Hidden;
+
+/// Modifications requested while drawing, applied once the world is no longer borrowed
+enum Command {
+    Spawn(Option<Entity>),
+    Destroy(Entity),
+    SetParent(Entity, Option<Entity>),
+    SetVisible(Entity, bool),
+}
+
+/// Panel listing the world entities
+pub struct Hierarchy {
+    selection: Arc<SelectionService>,
+
+    /// Generation of the selection service when the tree selection was last synced
+    selection_generation: u64,
+    tree: TreeView<Entity>,
+    search: String,
+    commands: Vec<Command>,
+}
+
+impl Hierarchy {
+    pub fn new(selection: Arc<SelectionService>) -> Self {
+        Self {
+            selection,
+            selection_generation: u64::MAX,
+            tree: TreeView::new(true),
+            search: String::default(),
+            commands: vec![],
+        }
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context, world: &mut World) {
+        puffin::profile_function!();
+
+        // Selection may have been changed by other panels
+        let generation = self.selection.generation();
+        if generation != self.selection_generation {
+            self.selection_generation = generation;
+            self.tree.set_selection(
+                self.selection
+                    .items()
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        SelectionItem::Entity(entity) => Some(entity),
+                        _ => None,
+                    })
+                    .collect(),
+            );
+        }
+
+        imgui.begin_window(HIERARCHY_ID, WindowFlags::empty());
+        imgui.input_text_with_hint(
+            "##Search",
+            "Search",
+            &mut self.search,
+            InputTextFlags::empty(),
+        );
+        imgui.separator();
+
+        imgui.begin_table(
+            "HierarchyTable",
+            2,
+            TableFlags::from_flag(TableFlagBits::RowBg),
+            ImVec2::default(),
+        );
+        imgui.table_setup_column(
+            "Name",
+            0.0,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Visible",
+            24.0,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthFixed),
+        );
+
+        let mut entities = vec![];
+        world
+            .query::<Entity>()
+            .for_each(world, |entity| entities.push(entity));
+        entities.sort();
+
+        if self.search.is_empty() {
+            for entity in entities {
+                if world.parent(entity).is_none() {
+                    self.draw_entity(imgui, world, entity, true);
+                }
+            }
+        } else {
+            // Matching entities are displayed as a flat list
+            let search = self.search.to_lowercase();
+            for entity in entities {
+                if entity_name(world, entity).to_lowercase().contains(&search) {
+                    self.draw_entity(imgui, world, entity, false);
+                }
+            }
+        }
+
+        imgui.end_table();
+
+        let selection = &self.selection;
+        self.tree.for_each_event(|event| {
+            if let TreeViewEvent::SelectionChanged(entities) = event {
+                selection.set(entities.into_iter().map(SelectionItem::Entity).collect());
+            }
+        });
+
+        // Remaining space is used to detach entities and to spawn new ones
+        let available_region = imgui.available_content_region();
+        imgui.dummy(ImVec2::new(
+            available_region.x,
+            available_region.y.max(20.0),
+        ));
+        if imgui.begin_drag_drop_target() {
+            if let Some(entity) = accept_entity_payload(imgui, world) {
+                self.commands.push(Command::SetParent(entity, None));
+            }
+            imgui.end_drag_drop_target();
+        }
+
+        if imgui.is_item_clicked(MouseButton::Left) {
+            self.selection.clear();
+        }
+
+        if imgui.begin_popup_context_window("HierarchyContextMenu") {
+            if imgui.menu_item("Create Entity", None, false, true) {
+                self.commands.push(Command::Spawn(None));
+            }
+            imgui.end_popup();
+        }
+
+        imgui.end_window();
+
+        self.apply_commands(world);
+    }
+
+    fn draw_entity(&mut self, imgui: &mut Context, world: &World, entity: Entity, recursive: bool) {
+        let name = entity_name(world, entity);
+        let children = world.children(entity);
+
+        imgui.table_next_row();
+        imgui.table_next_column();
+        imgui.push_id_i32(entity.id() as i32);
+
+        let mut flags = make_bitflags!(TreeNodeFlagBits::{OpenOnArrow | SpanFullWidth});
+        if !recursive || children.is_empty() {
+            flags |= TreeNodeFlagBits::Leaf;
+        }
+
+        let open = self.tree.node(imgui, entity, &name, flags);
+
+        if imgui.begin_drag_drop_source(DragDropFlags::empty()) {
+            imgui.set_drag_drop_payload(ENTITY_PAYLOAD, &entity.id().to_le_bytes());
+            imgui.text(&name);
+            imgui.end_drag_drop_source();
+        }
+
+        if imgui.begin_drag_drop_target() {
+            if let Some(dragged) = accept_entity_payload(imgui, world) {
+                if dragged != entity && !world.is_ancestor_of(dragged, entity) {
+                    self.commands
+                        .push(Command::SetParent(dragged, Some(entity)));
+                }
+            }
+            imgui.end_drag_drop_target();
+        }
+
+        if imgui.begin_popup_context_item("EntityContextMenu") {
+            if imgui.menu_item("Create Child", None, false, true) {
+                self.commands.push(Command::Spawn(Some(entity)));
+            }
+            if imgui.menu_item("Delete", None, false, true) {
+                self.commands.push(Command::Destroy(entity));
+            }
+            imgui.end_popup();
+        }
+
+        imgui.table_next_column();
+        let mut visible = world.query::<&Hidden>().get(world, entity).is_none();
+        if imgui.checkbox("##Visible", &mut visible) {
+            self.commands.push(Command::SetVisible(entity, visible));
+        }
+
+        if open {
+            if recursive {
+                for child in children {
+                    self.draw_entity(imgui, world, child, true);
+                }
+            }
+            imgui.tree_pop();
+        }
+
+        imgui.pop_id();
+    }
+
+    fn apply_commands(&mut self, world: &mut World) {
+        for command in self.commands.drain(..) {
+            match command {
+                Command::Spawn(parent) => {
+                    let entity = world.spawn();
+                    world.add(entity, Name("Entity".to_string()));
+                    world.add(entity, Transform::default());
+                    if parent.is_some() {
+                        world.set_parent(entity, parent);
+                    }
+                    self.selection.select(SelectionItem::Entity(entity));
+                }
+                Command::Destroy(entity) => {
+                    if world.is_valid(entity) {
+                        self.selection.remove(SelectionItem::Entity(entity));
+                        world.destroy_recursive(entity);
+                    }
+                }
+                Command::SetParent(entity, parent) => {
+                    if world.parent(entity) != parent {
+                        world.set_parent(entity, parent);
+                    }
+                }
+                Command::SetVisible(entity, visible) => {
+                    let hidden = world.query::<&Hidden>().get(world, entity).is_some();
+                    if visible && hidden {
+                        world.remove::<Hidden>(entity);
+                    } else if !visible && !hidden {
+                        world.add(entity, Hidden);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn entity_name(world: &World, entity: Entity) -> String {
+    world
+        .query::<&Name>()
+        .get(world, entity)
+        .map_or_else(|| format!("Entity {}", entity.id()), |name| name.0.clone())
+}
+
+fn accept_entity_payload(imgui: &mut Context, world: &World) -> Option<Entity> {
+    imgui
+        .accept_drag_drop_payload(ENTITY_PAYLOAD, DragDropFlags::empty())
+        .and_then(|payload| payload.try_into().ok())
+        .map(|id| world.entity(u32::from_le_bytes(id)))
+}
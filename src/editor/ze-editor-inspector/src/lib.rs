@@ -0,0 +1,188 @@
+use parking_lot::{Mutex, MutexGuard};
+use std::sync::Arc;
+use ze_editor_selection::{SelectionItem, SelectionService};
+use ze_imgui::ze_imgui_sys::ImVec2;
+use ze_imgui::{Context, TreeNodeFlagBits, TreeNodeFlags, WindowFlags};
+use ze_property_editor::draw_multi_property_editor;
+use ze_reflection::{Reflectable, TypeDescription};
+
+pub const INSPECTOR_ID: &str = "Inspector";
+
+/// Locked access to the data of an inspected object
+pub trait InspectableGuard {
+    fn data_ptr(&mut self) -> *mut u8;
+}
+
+impl<T> InspectableGuard for MutexGuard<'_, T> {
+    fn data_ptr(&mut self) -> *mut u8 {
+        &mut **self as *mut T as *mut u8
+    }
+}
+
+/// An object that can be edited by the inspector
+pub trait Inspectable {
+    fn name(&self) -> String;
+
+    /// Type of the data returned by `lock`
+    fn type_desc(&self) -> Arc<TypeDescription>;
+    fn lock(&self) -> Box<dyn InspectableGuard + '_>;
+
+    /// Pointer to a default instance of the object, used to revert fields
+    fn default_ptr(&self) -> Option<*const u8> {
+        None
+    }
+
+    /// Called after the inspector modified the object
+    fn on_modified(&self) {}
+}
+
+/// Basic `Inspectable` implementation over a shared object
+pub struct InspectableObject<T: Reflectable + Default> {
+    name: String,
+    object: Arc<Mutex<T>>,
+    default: T,
+}
+
+impl<T: Reflectable + Default> InspectableObject<T> {
+    pub fn new(name: &str, object: Arc<Mutex<T>>) -> Self {
+        Self {
+            name: name.to_string(),
+            object,
+            default: T::default(),
+        }
+    }
+}
+
+impl<T: Reflectable + Default> Inspectable for InspectableObject<T> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn type_desc(&self) -> Arc<TypeDescription> {
+        T::type_desc()
+    }
+
+    fn lock(&self) -> Box<dyn InspectableGuard + '_> {
+        Box::new(self.object.lock())
+    }
+
+    fn default_ptr(&self) -> Option<*const u8> {
+        Some(&self.default as *const T as *const u8)
+    }
+}
+
+/// Provide the objects to inspect for a selected item
+pub trait InspectorProvider {
+    /// Returns an empty list if the provider doesn't handle `item`
+    fn inspect(&self, item: &SelectionItem) -> Vec<Arc<dyn Inspectable>>;
+}
+
+/// Objects sharing the same type
+type ObjectGroup<'a> = (Arc<TypeDescription>, Vec<&'a Arc<dyn Inspectable>>);
+
+/// Panel displaying the selected objects using the property editor
+///
+/// Selected objects of the same type are edited together
+pub struct Inspector {
+    selection: Arc<SelectionService>,
+    providers: Vec<Box<dyn InspectorProvider>>,
+    objects: Vec<Arc<dyn Inspectable>>,
+    selection_generation: Option<u64>,
+
+    /// When locked, the inspector keeps displaying the same objects when the selection changes
+    locked: bool,
+}
+
+impl Inspector {
+    pub fn new(selection: Arc<SelectionService>) -> Self {
+        Self {
+            selection,
+            providers: vec![],
+            objects: vec![],
+            selection_generation: None,
+            locked: false,
+        }
+    }
+
+    pub fn add_provider<P: InspectorProvider + 'static>(&mut self, provider: P) {
+        self.providers.push(Box::new(provider));
+        self.selection_generation = None;
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+        if !self.locked && self.selection_generation != Some(self.selection.generation()) {
+            self.refresh_objects();
+        }
+
+        imgui.begin_window(INSPECTOR_ID, WindowFlags::empty());
+        imgui.checkbox("Lock", &mut self.locked);
+        imgui.separator();
+
+        if self.objects.is_empty() {
+            imgui.text("Nothing selected");
+        }
+
+        // Group objects by type so they can be edited together
+        let mut groups: Vec<ObjectGroup> = vec![];
+        for object in &self.objects {
+            let type_desc = object.type_desc();
+            match groups
+                .iter_mut()
+                .find(|(ty, _)| Arc::ptr_eq(ty, &type_desc))
+            {
+                Some((_, objects)) => objects.push(object),
+                None => groups.push((type_desc, vec![object])),
+            }
+        }
+
+        for (index, (type_desc, objects)) in groups.iter().enumerate() {
+            let header = if objects.len() == 1 {
+                objects[0].name()
+            } else {
+                format!("{} ({} objects)", type_desc.name(), objects.len())
+            };
+
+            imgui.push_id_i32(index as i32);
+            if imgui.collapsing_header(
+                &header,
+                TreeNodeFlags::from_flag(TreeNodeFlagBits::DefaultOpen),
+            ) {
+                let mut guards = objects
+                    .iter()
+                    .map(|object| object.lock())
+                    .collect::<Vec<_>>();
+                let ptrs = guards
+                    .iter_mut()
+                    .map(|guard| guard.data_ptr())
+                    .collect::<Vec<_>>();
+
+                // SAFETY: Objects are locked and described by `type_desc`
+                let modified = unsafe {
+                    draw_multi_property_editor(imgui, type_desc, &ptrs, objects[0].default_ptr())
+                };
+                drop(guards);
+
+                if modified {
+                    for object in objects {
+                        object.on_modified();
+                    }
+                }
+                imgui.dummy(ImVec2::new(0.0, 5.0));
+            }
+            imgui.pop_id();
+        }
+
+        imgui.end_window();
+    }
+
+    fn refresh_objects(&mut self) {
+        self.selection_generation = Some(self.selection.generation());
+        self.objects.clear();
+        for item in self.selection.items() {
+            for provider in &self.providers {
+                self.objects.append(&mut provider.inspect(&item));
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use ze_core::{ze_error, ze_info};
+use ze_filesystem::path::Path;
+use ze_filesystem::{DirEntryType, FileSystem, IterDirFlags};
+use ze_imgui::ze_imgui_sys::{ImGuiID, ImVec2};
+use ze_imgui::{Context, Dir, InputTextFlags};
+
+pub const DEFAULT_LAYOUT: &str = "Default";
+pub const LAYOUT_EXTENSION: &str = ".layout";
+
+#[derive(Debug)]
+pub enum Error {
+    FileSystem(ze_filesystem::Error),
+    InvalidYaml(serde_yaml::Error),
+    UnknownLayout,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl From<ze_filesystem::Error> for Error {
+    fn from(error: ze_filesystem::Error) -> Self {
+        Self::FileSystem(error)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(error: serde_yaml::Error) -> Self {
+        Self::InvalidYaml(error)
+    }
+}
+
+/// Layout as saved on disk
+#[derive(Serialize, Deserialize, Default)]
+struct Layout {
+    /// ImGui windows & docking settings
+    imgui_settings: String,
+    open_panels: Vec<String>,
+    panel_states: BTreeMap<String, serde_yaml::Value>,
+}
+
+struct Panel {
+    name: String,
+    open: bool,
+}
+
+/// Area of the main dock space where a preset docks a panel
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DockArea {
+    Left,
+    Center,
+    Right,
+    Bottom,
+}
+
+/// Layout used when no layout with the same name has been saved
+struct Preset {
+    name: String,
+    panels: Vec<(String, DockArea)>,
+}
+
+/// Save and restore the editor workspace: dock layout, open panels and panel states
+///
+/// Layouts are saved per project in `directory`
+pub struct LayoutManager {
+    filesystem: Arc<FileSystem>,
+    directory: Path,
+    panels: Vec<Panel>,
+    panel_states: BTreeMap<String, serde_yaml::Value>,
+    presets: Vec<Preset>,
+    current: String,
+
+    /// Set when a layout has been loaded and must be applied before the next frame
+    pending_imgui_settings: Option<String>,
+    pending_dock: Option<Vec<(String, DockArea)>>,
+    pending_load: bool,
+    new_layout_name: String,
+}
+
+impl LayoutManager {
+    pub fn new(filesystem: Arc<FileSystem>, directory: Path) -> Self {
+        Self {
+            filesystem,
+            directory,
+            panels: vec![],
+            panel_states: BTreeMap::default(),
+            presets: vec![],
+            current: DEFAULT_LAYOUT.to_string(),
+            pending_imgui_settings: None,
+            pending_dock: None,
+            pending_load: false,
+            new_layout_name: String::default(),
+        }
+    }
+
+    /// Register a panel that can be opened from the window menu
+    pub fn add_panel(&mut self, name: &str) {
+        self.panels.push(Panel {
+            name: name.to_string(),
+            open: true,
+        });
+    }
+
+    /// Add a preset opening `panels`, docked in their area of the main dock space
+    pub fn add_preset(&mut self, name: &str, panels: &[(&str, DockArea)]) {
+        self.presets.push(Preset {
+            name: name.to_string(),
+            panels: panels
+                .iter()
+                .map(|(panel, area)| (panel.to_string(), *area))
+                .collect(),
+        });
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    pub fn is_open(&self, panel: &str) -> bool {
+        self.panels
+            .iter()
+            .any(|registered| registered.name == panel && registered.open)
+    }
+
+    pub fn set_open(&mut self, panel: &str, open: bool) {
+        if let Some(registered) = self.panels.iter_mut().find(|p| p.name == panel) {
+            registered.open = open;
+        }
+    }
+
+    /// State of `panel` in the last loaded layout
+    pub fn panel_state<T: DeserializeOwned>(&self, panel: &str) -> Option<T> {
+        self.panel_states
+            .get(panel)
+            .and_then(|state| serde_yaml::from_value(state.clone()).ok())
+    }
+
+    /// Set the state of `panel` saved with the next layouts
+    pub fn set_panel_state<T: serde::Serialize>(&mut self, panel: &str, state: &T) {
+        match serde_yaml::to_value(state) {
+            Ok(state) => {
+                self.panel_states.insert(panel.to_string(), state);
+            }
+            Err(error) => ze_error!("Failed to serialize {} state: {}", panel, error),
+        }
+    }
+
+    /// Names of the presets and saved layouts
+    pub fn layouts(&self) -> Vec<String> {
+        let mut layouts = self
+            .presets
+            .iter()
+            .map(|preset| preset.name.clone())
+            .collect::<Vec<_>>();
+
+        let _ = self
+            .filesystem
+            .iter_dir(&self.directory, IterDirFlags::empty(), |entry| {
+                if entry.ty != DirEntryType::File {
+                    return;
+                }
+
+                let file_name = entry.path.path_segments().next_back().unwrap_or_default();
+                if let Some(name) = file_name.strip_suffix(LAYOUT_EXTENSION) {
+                    if !layouts.iter().any(|layout| layout == name) {
+                        layouts.push(name.to_string());
+                    }
+                }
+            });
+
+        layouts
+    }
+
+    /// Load a saved layout, or the preset with the same name if it has never been saved
+    ///
+    /// The layout is applied by the next `apply_pending` call, presets are docked by the next
+    /// `build_pending_dock` call
+    pub fn load(&mut self, name: &str) -> Result<(), Error> {
+        let path = self.layout_path(name);
+        let layout = if self.filesystem.exists(&path) {
+            self.pending_dock = None;
+            serde_yaml::from_reader::<_, Layout>(self.filesystem.read(&path)?)?
+        } else {
+            let preset = self
+                .presets
+                .iter()
+                .find(|preset| preset.name == name)
+                .ok_or(Error::UnknownLayout)?;
+
+            self.pending_dock = Some(preset.panels.clone());
+            Layout {
+                imgui_settings: String::default(),
+                open_panels: preset
+                    .panels
+                    .iter()
+                    .map(|(panel, _)| panel.clone())
+                    .collect(),
+                panel_states: self.panel_states.clone(),
+            }
+        };
+
+        for panel in &mut self.panels {
+            panel.open = layout.open_panels.contains(&panel.name);
+        }
+
+        self.panel_states = layout.panel_states;
+        self.pending_imgui_settings =
+            Some(layout.imgui_settings).filter(|settings| !settings.is_empty());
+        self.pending_load = true;
+        self.current = name.to_string();
+        Ok(())
+    }
+
+    /// Apply the last loaded layout, must be called outside of an ImGui frame
+    ///
+    /// Returns true if a layout has been loaded, panels should then restore their state
+    pub fn apply_pending(&mut self, imgui: &mut Context) -> bool {
+        if let Some(settings) = self.pending_imgui_settings.take() {
+            imgui.load_ini_settings(&settings);
+        }
+
+        std::mem::take(&mut self.pending_load)
+    }
+
+    /// Dock the panels of the last loaded preset, must be called once the main dock space has
+    /// been submitted
+    pub fn build_pending_dock(&mut self, imgui: &mut Context, dockspace_id: ImGuiID) {
+        let panels = match self.pending_dock.take() {
+            Some(panels) => panels,
+            None => return,
+        };
+
+        let size = imgui.main_viewport().work_size();
+        imgui.dock_builder_reset(dockspace_id, size);
+
+        // Side areas are only split from the center when used
+        let mut center = dockspace_id;
+        let mut nodes = vec![];
+        for (area, dir, ratio) in [
+            (DockArea::Left, Dir::Left, 0.2),
+            (DockArea::Right, Dir::Right, 0.25),
+            (DockArea::Bottom, Dir::Down, 0.3),
+        ] {
+            if panels.iter().any(|(_, panel_area)| *panel_area == area) {
+                let (node, remaining) = imgui.dock_builder_split_node(center, dir, ratio);
+                nodes.push((area, node));
+                center = remaining;
+            }
+        }
+
+        for (panel, area) in &panels {
+            let node = nodes
+                .iter()
+                .find(|(node_area, _)| node_area == area)
+                .map_or(center, |(_, node)| *node);
+            imgui.dock_builder_dock_window(panel, node);
+        }
+
+        imgui.dock_builder_finish(dockspace_id);
+    }
+
+    /// Save the current layout as `name`
+    pub fn save(&mut self, name: &str, imgui: &Context) -> Result<(), Error> {
+        let layout = Layout {
+            imgui_settings: imgui.save_ini_settings(),
+            open_panels: self
+                .panels
+                .iter()
+                .filter(|panel| panel.open)
+                .map(|panel| panel.name.clone())
+                .collect(),
+            panel_states: self.panel_states.clone(),
+        };
+
+        self.filesystem.create_dir_all(&self.directory)?;
+        let file = self.filesystem.write(&self.layout_path(name))?;
+        serde_yaml::to_writer(file, &layout)?;
+        self.current = name.to_string();
+        ze_info!("Saved layout \"{}\"", name);
+        Ok(())
+    }
+
+    /// Draw the window menu, must be called inside a menu bar
+    ///
+    /// Returns the name of the layout to save, panel states should be updated before saving
+    pub fn draw_menu(&mut self, imgui: &mut Context) -> Option<String> {
+        if !imgui.begin_menu("Window", true) {
+            return None;
+        }
+
+        for panel in &mut self.panels {
+            if imgui.menu_item(&panel.name, None, panel.open, true) {
+                panel.open = !panel.open;
+            }
+        }
+
+        imgui.separator();
+        for layout in self.layouts() {
+            let selected = layout == self.current;
+            if imgui.menu_item(&format!("Layout: {}", layout), None, selected, true) {
+                if let Err(error) = self.load(&layout) {
+                    ze_error!("Failed to load layout \"{}\": {}", layout, error);
+                }
+            }
+        }
+
+        imgui.separator();
+        let mut save = None;
+        if imgui.menu_item(
+            &format!("Save layout \"{}\"", self.current),
+            None,
+            false,
+            true,
+        ) {
+            save = Some(self.current.clone());
+        }
+
+        imgui.input_text_with_hint(
+            "##NewLayout",
+            "New layout name",
+            &mut self.new_layout_name,
+            InputTextFlags::empty(),
+        );
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Save as", ImVec2::default()) && !self.new_layout_name.is_empty() {
+            save = Some(std::mem::take(&mut self.new_layout_name));
+        }
+
+        imgui.end_menu();
+        save
+    }
+
+    fn layout_path(&self, name: &str) -> Path {
+        self.directory.join(format!("{}{}", name, LAYOUT_EXTENSION))
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, HashSet};
+use std::process::Command;
+use std::sync::Arc;
+use ze_core::console::{CVar, CVarValue};
+use ze_core::logger::{Message, RingBufferSink, Severity, SourceLocation};
+use ze_core::{console, ze_error};
+use ze_imgui::ze_imgui_sys::{ImVec2, ImVec4};
+use ze_imgui::{Context, InputTextFlags, WindowFlagBits, WindowFlags};
+use ze_platform::MouseButton;
+
+pub const LOG_VIEWER_ID: &str = "Log";
+
+const SEVERITIES: [Severity; 5] = [
+    Severity::Verbose,
+    Severity::Info,
+    Severity::Warn,
+    Severity::Error,
+    Severity::Fatal,
+];
+
+pub fn severity_color(severity: Severity) -> ImVec4 {
+    match severity {
+        Severity::Verbose => ImVec4::new(0.5, 0.8, 0.8, 1.0),
+        Severity::Info => ImVec4::new(1.0, 1.0, 1.0, 1.0),
+        Severity::Warn => ImVec4::new(1.0, 0.85, 0.2, 1.0),
+        Severity::Error => ImVec4::new(1.0, 0.3, 0.3, 1.0),
+        Severity::Fatal => ImVec4::new(1.0, 0.06, 0.06, 1.0),
+    }
+}
+
+/// Panel displaying the log with filtering options
+pub struct LogViewer {
+    log: Arc<RingBufferSink>,
+    search: String,
+    hidden_severities: HashSet<Severity>,
+
+    /// Known categories (crate names) and if they are displayed
+    categories: BTreeMap<String, bool>,
+    auto_scroll: bool,
+    first_message_index: usize,
+
+    /// Command used to open a source file, `{file}` and `{line}` are replaced
+    source_editor_command: Arc<CVar>,
+}
+
+impl LogViewer {
+    pub fn new(log: Arc<RingBufferSink>) -> Self {
+        Self {
+            log,
+            search: String::default(),
+            hidden_severities: HashSet::default(),
+            categories: BTreeMap::default(),
+            auto_scroll: true,
+            first_message_index: 0,
+            source_editor_command: console::register_cvar(
+                "editor.source_editor_command",
+                "Command used to open a source file from the log viewer",
+                CVarValue::String("code --goto {file}:{line}".to_string()),
+            ),
+        }
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+
+        let mut clicked_location = None;
+        imgui.window(LOG_VIEWER_ID).build(|imgui| {
+            self.draw_toolbar(imgui);
+            imgui.separator();
+
+            imgui
+                .child("Messages")
+                .flags(WindowFlags::from_flag(WindowFlagBits::HorizontalScrollbar))
+                .build(|imgui| clicked_location = self.draw_messages(imgui));
+        });
+
+        if let Some(location) = clicked_location {
+            self.open_source_location(location);
+        }
+    }
+
+    /// Return the source location of the message double clicked this frame
+    fn draw_messages(&mut self, imgui: &mut Context) -> Option<SourceLocation> {
+        // Only follow new messages when the view is pinned at the bottom
+        let pinned = self.auto_scroll && imgui.scroll_y() >= imgui.scroll_max_y();
+        let search = self.search.to_lowercase();
+        let mut clicked_location = None;
+        let mut new_categories = vec![];
+
+        self.log.for_each(|index, message| {
+            match self.categories.get(&message.crate_name) {
+                None => new_categories.push(message.crate_name.clone()),
+                Some(false) => return,
+                _ => {}
+            }
+
+            if index < self.first_message_index
+                || self.hidden_severities.contains(&message.severity)
+                || (!search.is_empty() && !message.message.to_lowercase().contains(&search))
+            {
+                return;
+            }
+
+            imgui.text_colored(severity_color(message.severity), &format_message(message));
+            if let Some(location) = message.location {
+                if imgui.is_item_hovered() {
+                    imgui.begin_tooltip();
+                    imgui.text(&format!("{}:{}", location.file, location.line));
+                    imgui.end_tooltip();
+
+                    if imgui.is_mouse_double_clicked(MouseButton::Left) {
+                        clicked_location = Some(location);
+                    }
+                }
+            }
+        });
+
+        for category in new_categories {
+            self.categories.insert(category, true);
+        }
+
+        if pinned {
+            imgui.set_scroll_y(imgui.scroll_max_y());
+        }
+
+        clicked_location
+    }
+
+    fn draw_toolbar(&mut self, imgui: &mut Context) {
+        if imgui.button("Clear", ImVec2::default()) {
+            self.first_message_index = self.log.received_count();
+        }
+
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Categories", ImVec2::default()) {
+            imgui.open_popup("Categories");
+        }
+
+        imgui.popup("Categories").build(|imgui| {
+            for (category, visible) in self.categories.iter_mut() {
+                imgui.checkbox(category, visible);
+            }
+        });
+
+        for severity in SEVERITIES {
+            let mut visible = !self.hidden_severities.contains(&severity);
+            imgui.same_line(0.0, -1.0);
+            if imgui.checkbox(&severity.to_string(), &mut visible) {
+                if visible {
+                    self.hidden_severities.remove(&severity);
+                } else {
+                    self.hidden_severities.insert(severity);
+                }
+            }
+        }
+
+        imgui.same_line(0.0, -1.0);
+        imgui.checkbox("Auto-scroll", &mut self.auto_scroll);
+        imgui.same_line(0.0, -1.0);
+        imgui.input_text_with_hint(
+            "##Search",
+            "Search",
+            &mut self.search,
+            InputTextFlags::empty(),
+        );
+    }
+
+    fn open_source_location(&self, location: SourceLocation) {
+        let command = self
+            .source_editor_command
+            .as_string()
+            .unwrap_or_default()
+            .replace("{file}", location.file)
+            .replace("{line}", &location.line.to_string());
+
+        let mut args = command.split_whitespace();
+        if let Some(program) = args.next() {
+            if let Err(error) = Command::new(program).args(args).spawn() {
+                ze_error!("Failed to open {}: {}", location.file, error);
+            }
+        }
+    }
+}
+
+fn format_message(message: &Message) -> String {
+    format!(
+        "[{}] [{}] ({}) {}",
+        message.time.format("%H:%M:%S"),
+        message.severity,
+        message.crate_name,
+        message.message
+    )
+}
@@ -0,0 +1,442 @@
+use enumflags2::make_bitflags;
+use puffin::{FrameData, FrameView, GlobalFrameView, NanoSecond, Reader, Scope, Stream};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
+use ze_core::{ze_error, ze_info};
+use ze_imgui::ze_imgui_sys::{ImVec2, ImVec4};
+use ze_imgui::{
+    Context, InputTextFlags, Key, TableColumnFlagBits, TableColumnFlags, TableFlagBits,
+    TreeNodeFlagBits, TreeNodeFlags, WindowFlagBits, WindowFlags,
+};
+
+pub const PROFILER_ID: &str = "Profiler";
+
+/// Amount of frames kept in the history
+const FRAME_HISTORY: usize = 300;
+const FRAME_HISTORY_HEIGHT: f32 = 60.0;
+
+/// Frame duration used as the minimum scale of the frame history (60 FPS)
+const TARGET_FRAME_NS: NanoSecond = 16_666_667;
+const SCOPE_HEIGHT: f32 = 18.0;
+const MAX_ZOOM: f32 = 1000.0;
+
+/// Time spent by a render graph pass on the GPU
+#[derive(Clone)]
+pub struct GpuPassTiming {
+    pub name: String,
+    pub duration_ms: f32,
+}
+
+/// Panel visualizing the CPU profiling captures and the GPU pass timings
+///
+/// CPU captures are recorded by puffin, every thread using puffin scopes is displayed
+pub struct Profiler {
+    frames: GlobalFrameView,
+
+    /// Frames displayed while paused
+    paused_frames: Option<Vec<Arc<FrameData>>>,
+
+    /// Frame displayed in the timeline, latest frame if `None`
+    selected_frame: Option<u64>,
+
+    /// Horizontal zoom of the timeline, 1.0 fits the whole frame
+    zoom: f32,
+    gpu_frames: VecDeque<Vec<GpuPassTiming>>,
+    export_path: String,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        let frames = GlobalFrameView::default();
+        frames.lock().set_max_recent(FRAME_HISTORY);
+
+        Self {
+            frames,
+            paused_frames: None,
+            selected_frame: None,
+            zoom: 1.0,
+            gpu_frames: VecDeque::with_capacity(FRAME_HISTORY),
+            export_path: "capture.puffin".to_string(),
+        }
+    }
+
+    /// Add the GPU pass timings of a frame
+    /// Ignored while the profiler is paused
+    pub fn push_gpu_frame(&mut self, passes: Vec<GpuPassTiming>) {
+        if self.paused_frames.is_some() {
+            return;
+        }
+
+        if self.gpu_frames.len() == FRAME_HISTORY {
+            self.gpu_frames.pop_front();
+        }
+        self.gpu_frames.push_back(passes);
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+
+        let frames = match &self.paused_frames {
+            Some(frames) => frames.clone(),
+            None => self.frames.lock().recent_frames().cloned().collect(),
+        };
+
+        imgui.begin_window(PROFILER_ID, WindowFlags::empty());
+        self.draw_toolbar(imgui, &frames);
+        imgui.separator();
+        self.draw_frame_history(imgui, &frames);
+
+        if imgui.collapsing_header(
+            "GPU",
+            TreeNodeFlags::from_flag(TreeNodeFlagBits::DefaultOpen),
+        ) {
+            self.draw_gpu_passes(imgui);
+        }
+
+        if imgui.collapsing_header(
+            "CPU",
+            TreeNodeFlags::from_flag(TreeNodeFlagBits::DefaultOpen),
+        ) {
+            let frame = self
+                .selected_frame
+                .and_then(|index| frames.iter().find(|frame| frame.frame_index() == index))
+                .or_else(|| frames.last());
+
+            match frame {
+                Some(frame) => self.draw_timeline(imgui, frame),
+                None => imgui.text("No frame captured"),
+            }
+        }
+
+        imgui.end_window();
+    }
+
+    fn draw_toolbar(&mut self, imgui: &mut Context, frames: &[Arc<FrameData>]) {
+        let mut paused = self.paused_frames.is_some();
+        if imgui.checkbox("Pause", &mut paused) {
+            self.paused_frames = if paused { Some(frames.to_vec()) } else { None };
+        }
+
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Latest frame", ImVec2::default()) {
+            self.selected_frame = None;
+        }
+
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Export", ImVec2::default()) {
+            self.export(frames);
+        }
+
+        imgui.same_line(0.0, -1.0);
+        imgui.input_text_with_hint(
+            "##ExportPath",
+            "Capture path",
+            &mut self.export_path,
+            InputTextFlags::empty(),
+        );
+    }
+
+    /// Save `frames` as a `.puffin` capture that can be opened with puffin_viewer
+    fn export(&self, frames: &[Arc<FrameData>]) {
+        let mut view = FrameView::default();
+        view.set_max_recent(frames.len());
+        for frame in frames {
+            view.add_frame(frame.clone());
+        }
+
+        match view.save_to_path(Path::new(&self.export_path)) {
+            Ok(()) => ze_info!("Exported {} frames to {}", frames.len(), self.export_path),
+            Err(error) => ze_error!(
+                "Failed to export capture to {}: {}",
+                self.export_path,
+                error
+            ),
+        }
+    }
+
+    fn draw_frame_history(&mut self, imgui: &mut Context, frames: &[Arc<FrameData>]) {
+        let width = imgui.available_content_region().x;
+        let origin = imgui.cursor_screen_pos();
+        let clicked =
+            imgui.invisible_button("FrameHistory", ImVec2::new(width, FRAME_HISTORY_HEIGHT));
+        let hovered = imgui.is_item_hovered();
+        let mouse_pos = imgui.mouse_pos();
+
+        imgui.window_add_rect_filled(
+            origin,
+            origin + ImVec2::new(width, FRAME_HISTORY_HEIGHT),
+            ImVec4::new(0.1, 0.1, 0.1, 1.0),
+        );
+
+        let max_duration = frames
+            .iter()
+            .map(|frame| frame.duration_ns())
+            .max()
+            .unwrap_or_default()
+            .max(TARGET_FRAME_NS);
+        let ns_to_px = FRAME_HISTORY_HEIGHT / max_duration as f32;
+        let bar_width = (width / FRAME_HISTORY as f32).max(1.0);
+
+        // Latest frame is on the right
+        for (i, frame) in frames.iter().rev().enumerate() {
+            let max_x = origin.x + width - i as f32 * bar_width;
+            let min_x = max_x - bar_width;
+            if min_x < origin.x {
+                break;
+            }
+
+            let height = frame.duration_ns() as f32 * ns_to_px;
+            let bar_hovered = hovered && mouse_pos.x >= min_x && mouse_pos.x < max_x;
+            let color = if bar_hovered || self.selected_frame == Some(frame.frame_index()) {
+                ImVec4::new(1.0, 0.8, 0.3, 1.0)
+            } else {
+                ImVec4::new(0.3, 0.6, 0.9, 1.0)
+            };
+
+            imgui.window_add_rect_filled(
+                ImVec2::new(min_x, origin.y + FRAME_HISTORY_HEIGHT - height),
+                ImVec2::new(
+                    (max_x - 1.0).max(min_x + 1.0),
+                    origin.y + FRAME_HISTORY_HEIGHT,
+                ),
+                color,
+            );
+
+            if bar_hovered {
+                imgui.begin_tooltip();
+                imgui.text(&format!(
+                    "Frame {}: {:.2} ms",
+                    frame.frame_index(),
+                    ns_to_ms(frame.duration_ns())
+                ));
+                imgui.end_tooltip();
+
+                if clicked {
+                    self.selected_frame = Some(frame.frame_index());
+                }
+            }
+        }
+
+        let target_y = origin.y + FRAME_HISTORY_HEIGHT - TARGET_FRAME_NS as f32 * ns_to_px;
+        imgui.window_add_line(
+            ImVec2::new(origin.x, target_y),
+            ImVec2::new(origin.x + width, target_y),
+            ImVec4::new(0.9, 0.3, 0.3, 0.6),
+            1.0,
+        );
+    }
+
+    fn draw_gpu_passes(&mut self, imgui: &mut Context) {
+        let passes = match self.gpu_frames.back() {
+            Some(passes) => passes,
+            None => {
+                imgui.text("No GPU timings available");
+                return;
+            }
+        };
+
+        imgui.text(&format!(
+            "Total: {:.3} ms",
+            passes.iter().map(|pass| pass.duration_ms).sum::<f32>()
+        ));
+
+        imgui.begin_table(
+            "GpuPasses",
+            3,
+            make_bitflags! { TableFlagBits::{Resizable | RowBg} },
+            ImVec2::default(),
+        );
+        imgui.table_setup_column(
+            "Pass",
+            0.6,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Time (ms)",
+            0.2,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Average (ms)",
+            0.2,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_headers_row();
+
+        for pass in passes {
+            let (sum, count) = self
+                .gpu_frames
+                .iter()
+                .flat_map(|frame| frame.iter().filter(|other| other.name == pass.name))
+                .fold((0.0, 0), |(sum, count), other| {
+                    (sum + other.duration_ms, count + 1)
+                });
+
+            imgui.table_next_row();
+            imgui.table_next_column();
+            imgui.text(&pass.name);
+            imgui.table_next_column();
+            imgui.text(&format!("{:.3}", pass.duration_ms));
+            imgui.table_next_column();
+            imgui.text(&format!("{:.3}", sum / count as f32));
+        }
+
+        imgui.end_table();
+    }
+
+    fn draw_timeline(&mut self, imgui: &mut Context, frame: &FrameData) {
+        let frame_data = match frame.unpacked() {
+            Ok(frame_data) => frame_data,
+            Err(_) => {
+                imgui.text("Failed to unpack frame");
+                return;
+            }
+        };
+
+        imgui.text(&format!(
+            "Frame {}: {:.2} ms (Ctrl + Wheel to zoom)",
+            frame.frame_index(),
+            ns_to_ms(frame.duration_ns())
+        ));
+
+        imgui.begin_child(
+            "Timeline",
+            ImVec2::default(),
+            false,
+            WindowFlags::from_flag(WindowFlagBits::HorizontalScrollbar),
+        );
+
+        if imgui.is_window_hovered() && imgui.is_key_down(Key::ModCtrl) {
+            let wheel = imgui.mouse_wheel();
+            if wheel != 0.0 {
+                self.zoom = (self.zoom * (1.0 + wheel * 0.1)).clamp(1.0, MAX_ZOOM);
+            }
+        }
+
+        let (min_ns, max_ns) = frame.range_ns();
+        let width = imgui.available_content_region().x * self.zoom;
+        let mut hovered_scope = None;
+
+        for (thread, stream_info) in &frame_data.thread_streams {
+            imgui.text(&thread.name);
+
+            let origin = imgui.cursor_screen_pos();
+            let height = (stream_info.depth as f32 * SCOPE_HEIGHT).max(1.0);
+            imgui.invisible_button(&thread.name, ImVec2::new(width, height));
+
+            let layout = TimelineLayout {
+                origin,
+                min_ns,
+                ns_to_px: width / (max_ns - min_ns).max(1) as f32,
+                mouse_pos: imgui.is_item_hovered().then(|| imgui.mouse_pos()),
+            };
+
+            if let Ok(scopes) = Reader::from_start(&stream_info.stream).read_top_scopes() {
+                draw_scopes(
+                    imgui,
+                    &layout,
+                    &stream_info.stream,
+                    &scopes,
+                    0,
+                    &mut hovered_scope,
+                );
+            }
+        }
+
+        if let Some(tooltip) = hovered_scope {
+            imgui.begin_tooltip();
+            imgui.text(&tooltip);
+            imgui.end_tooltip();
+        }
+
+        imgui.end_child();
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct TimelineLayout {
+    origin: ImVec2,
+    min_ns: NanoSecond,
+    ns_to_px: f32,
+
+    /// Mouse position if hovering the thread
+    mouse_pos: Option<ImVec2>,
+}
+
+/// Draw `scopes` and their children, `hovered_scope` is set to the description of the hovered scope
+fn draw_scopes(
+    imgui: &mut Context,
+    layout: &TimelineLayout,
+    stream: &Stream,
+    scopes: &[Scope],
+    depth: usize,
+    hovered_scope: &mut Option<String>,
+) {
+    for scope in scopes {
+        let record = &scope.record;
+        let min = ImVec2::new(
+            layout.origin.x + (record.start_ns - layout.min_ns) as f32 * layout.ns_to_px,
+            layout.origin.y + depth as f32 * SCOPE_HEIGHT,
+        );
+        let max = ImVec2::new(
+            min.x + (record.duration_ns as f32 * layout.ns_to_px).max(1.0),
+            min.y + SCOPE_HEIGHT - 1.0,
+        );
+
+        imgui.window_add_rect_filled(min, max, scope_color(record.id));
+
+        // Only draw labels that can be at least partially read
+        if max.x - min.x > 8.0 {
+            imgui.window_push_clip_rect(min, max);
+            imgui.window_add_text(
+                ImVec2::new(min.x + 2.0, min.y + 1.0),
+                ImVec4::new(0.0, 0.0, 0.0, 1.0),
+                record.id,
+            );
+            imgui.window_pop_clip_rect();
+        }
+
+        if let Some(mouse_pos) = layout.mouse_pos {
+            if mouse_pos.x >= min.x
+                && mouse_pos.x < max.x
+                && mouse_pos.y >= min.y
+                && mouse_pos.y < max.y
+            {
+                *hovered_scope = Some(format!(
+                    "{} {}\n{}\n{:.3} ms",
+                    record.id,
+                    record.data,
+                    record.location,
+                    ns_to_ms(record.duration_ns)
+                ));
+            }
+        }
+
+        if let Ok(reader) = Reader::with_offset(stream, scope.child_begin_position) {
+            if let Ok(children) = reader.read_top_scopes() {
+                draw_scopes(imgui, layout, stream, &children, depth + 1, hovered_scope);
+            }
+        }
+    }
+}
+
+/// Stable color for a scope id
+fn scope_color(id: &str) -> ImVec4 {
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+    let hash = hasher.finish();
+    let channel = |shift: u64| 0.45 + ((hash >> shift) & 0xFF) as f32 / 255.0 * 0.45;
+    ImVec4::new(channel(0), channel(8), channel(16), 1.0)
+}
+
+fn ns_to_ms(ns: NanoSecond) -> f32 {
+    ns as f32 / 1_000_000.0
+}
@@ -0,0 +1,85 @@
+use parking_lot::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use ze_core::type_uuid::Uuid;
+use ze_ecs::entity::Entity;
+
+/// An object that can be selected in the editor
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SelectionItem {
+    Entity(Entity),
+    Asset(Uuid),
+    Settings(&'static str),
+}
+
+/// Selection shared by every editor panel
+///
+/// The last selected item is considered as the primary selection
+#[derive(Default)]
+pub struct SelectionService {
+    items: RwLock<Vec<SelectionItem>>,
+
+    /// Incremented everytime the selection changes
+    generation: AtomicU64,
+}
+
+impl SelectionService {
+    /// Replace the current selection by `item`
+    pub fn select(&self, item: SelectionItem) {
+        self.set(vec![item]);
+    }
+
+    pub fn set(&self, items: Vec<SelectionItem>) {
+        *self.items.write() = items;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn add(&self, item: SelectionItem) {
+        let mut items = self.items.write();
+        items.retain(|selected| *selected != item);
+        items.push(item);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn remove(&self, item: SelectionItem) {
+        let mut items = self.items.write();
+        let len = items.len();
+        items.retain(|selected| *selected != item);
+        if items.len() != len {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Add `item` if not selected, remove it otherwise
+    pub fn toggle(&self, item: SelectionItem) {
+        if self.is_selected(item) {
+            self.remove(item);
+        } else {
+            self.add(item);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.set(vec![]);
+    }
+
+    pub fn is_selected(&self, item: SelectionItem) -> bool {
+        self.items.read().contains(&item)
+    }
+
+    pub fn items(&self) -> Vec<SelectionItem> {
+        self.items.read().clone()
+    }
+
+    pub fn primary(&self) -> Option<SelectionItem> {
+        self.items.read().last().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.read().is_empty()
+    }
+
+    /// Generation of the selection, can be used to detect selection changes
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,246 @@
+use enumflags2::make_bitflags;
+use std::sync::Arc;
+use ze_imgui::ze_imgui_sys::{ImVec2, ImVec4};
+use ze_imgui::{
+    Context, InputTextFlags, SelectableFlagBits, SelectableFlags, TableColumnFlagBits,
+    TableColumnFlags, TableFlagBits, WindowFlags,
+};
+use ze_shader_compiler::diagnostic::{DiagnosticSeverity, ShaderDiagnostic};
+use ze_shader_system::{ShaderInfo, ShaderManager, ShaderStatus};
+
+pub const SHADER_INSPECTOR_ID: &str = "Shaders";
+
+/// Number of source lines displayed before and after a diagnostic line
+const SOURCE_CONTEXT_LINES: usize = 2;
+
+fn status_color(status: ShaderStatus) -> ImVec4 {
+    match status {
+        ShaderStatus::NotCompiled => ImVec4::new(0.6, 0.6, 0.6, 1.0),
+        ShaderStatus::Compiling => ImVec4::new(1.0, 0.85, 0.2, 1.0),
+        ShaderStatus::Compiled => ImVec4::new(0.4, 0.9, 0.4, 1.0),
+        ShaderStatus::Failed => ImVec4::new(1.0, 0.3, 0.3, 1.0),
+    }
+}
+
+fn severity_color(severity: DiagnosticSeverity) -> ImVec4 {
+    match severity {
+        DiagnosticSeverity::Error => ImVec4::new(1.0, 0.3, 0.3, 1.0),
+        DiagnosticSeverity::Warning => ImVec4::new(1.0, 0.85, 0.2, 1.0),
+        DiagnosticSeverity::Note => ImVec4::new(0.5, 0.8, 0.8, 1.0),
+    }
+}
+
+/// Panel listing the shaders of the shader manager with their compilation status and errors
+pub struct ShaderInspector {
+    shader_manager: Arc<ShaderManager>,
+    search: String,
+
+    /// Shader and pass whose diagnostics are displayed
+    selected: Option<(String, String)>,
+}
+
+impl ShaderInspector {
+    pub fn new(shader_manager: Arc<ShaderManager>) -> Self {
+        Self {
+            shader_manager,
+            search: String::default(),
+            selected: None,
+        }
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+
+        imgui.begin_window(SHADER_INSPECTOR_ID, WindowFlags::empty());
+        let shaders = self.shader_manager.shaders();
+
+        imgui.input_text_with_hint(
+            "##Search",
+            "Search",
+            &mut self.search,
+            InputTextFlags::empty(),
+        );
+        imgui.same_line(0.0, -1.0);
+        if imgui.button("Recompile all", ImVec2::default()) {
+            for shader in &shaders {
+                self.shader_manager.recompile(&shader.name);
+            }
+        }
+
+        let search = self.search.to_lowercase();
+        let shaders = shaders
+            .into_iter()
+            .filter(|shader| shader.name.to_lowercase().contains(&search))
+            .collect::<Vec<_>>();
+
+        self.draw_shader_table(imgui, &shaders);
+
+        imgui.separator();
+        match &self.selected {
+            Some((shader, pass)) => {
+                let shader = shaders.iter().find(|info| &info.name == shader);
+                match shader {
+                    Some(shader) => self.draw_diagnostics(imgui, shader, pass),
+                    None => imgui.text("Selected shader is not loaded"),
+                }
+            }
+            None => imgui.text("Select a shader pass to display its diagnostics"),
+        }
+
+        imgui.end_window();
+    }
+
+    fn draw_shader_table(&mut self, imgui: &mut Context, shaders: &[ShaderInfo]) {
+        imgui.begin_table(
+            "ShaderTable",
+            5,
+            make_bitflags! { TableFlagBits::{Resizable | RowBg} },
+            ImVec2::default(),
+        );
+        imgui.table_setup_column(
+            "Shader",
+            0.4,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Pass",
+            0.2,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Status",
+            0.15,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "Permutations",
+            0.1,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_setup_column(
+            "",
+            0.15,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
+        );
+        imgui.table_headers_row();
+
+        for shader in shaders {
+            for pass in &shader.passes {
+                let id = format!("{}##{}", shader.name, pass.name);
+                imgui.table_next_row();
+                imgui.table_next_column();
+                let selected = matches!(
+                    &self.selected,
+                    Some((shader_name, pass_name))
+                        if *shader_name == shader.name && *pass_name == pass.name
+                );
+                if imgui.selectable_ex(
+                    &id,
+                    selected,
+                    SelectableFlags::from_flag(SelectableFlagBits::SpanAllColumns),
+                    ImVec2::default(),
+                ) {
+                    self.selected = Some((shader.name.clone(), pass.name.clone()));
+                }
+                imgui.table_next_column();
+                imgui.text(pass_display_name(&pass.name));
+                imgui.table_next_column();
+                imgui.text_colored(status_color(pass.status), &format!("{:?}", pass.status));
+                imgui.table_next_column();
+                imgui.text(&pass.permutation_count.to_string());
+                imgui.table_next_column();
+                if imgui.button(&format!("Recompile##{}", id), ImVec2::default()) {
+                    self.shader_manager.recompile(&shader.name);
+                }
+            }
+        }
+
+        imgui.end_table();
+    }
+
+    fn draw_diagnostics(&self, imgui: &mut Context, shader: &ShaderInfo, pass_name: &str) {
+        let pass = match shader.passes.iter().find(|pass| pass.name == pass_name) {
+            Some(pass) => pass,
+            None => return,
+        };
+
+        imgui.text(&format!(
+            "{} / {}",
+            shader.name,
+            pass_display_name(&pass.name)
+        ));
+
+        if pass.stages.iter().all(|stage| stage.diagnostics.is_empty()) {
+            imgui.text("No diagnostics");
+            return;
+        }
+
+        for stage in &pass.stages {
+            if stage.diagnostics.is_empty() {
+                continue;
+            }
+
+            imgui.text(&format!("{:?} stage", stage.stage));
+            let source = self
+                .shader_manager
+                .stage_source(&shader.name, &pass.name, stage.stage);
+            for diagnostic in &stage.diagnostics {
+                draw_diagnostic(imgui, diagnostic, source.as_deref());
+            }
+        }
+    }
+}
+
+fn pass_display_name(name: &str) -> &str {
+    if name.is_empty() {
+        "<default>"
+    } else {
+        name
+    }
+}
+
+fn draw_diagnostic(imgui: &mut Context, diagnostic: &ShaderDiagnostic, source: Option<&str>) {
+    let location = match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => format!("{}:{}: ", line, column),
+        (Some(line), None) => format!("{}: ", line),
+        _ => String::default(),
+    };
+
+    let color = severity_color(diagnostic.severity);
+    imgui.text_colored(
+        color,
+        &format!(
+            "{}{:?}: {}",
+            location, diagnostic.severity, diagnostic.message
+        ),
+    );
+
+    // Display the lines around the diagnostic, the source is the one given to the compiler
+    // so lines match the diagnostic ones
+    let (line, source) = match (diagnostic.line, source) {
+        (Some(line), Some(source)) if line > 0 => (line as usize, source),
+        _ => return,
+    };
+
+    let first_line = line.saturating_sub(SOURCE_CONTEXT_LINES).max(1);
+    for (index, text) in source
+        .lines()
+        .enumerate()
+        .skip(first_line - 1)
+        .take(line - first_line + SOURCE_CONTEXT_LINES + 1)
+    {
+        let current_line = index + 1;
+        let text = format!("{:>5} | {}", current_line, text);
+        if current_line == line {
+            imgui.text_colored(color, &text);
+            if let Some(column) = diagnostic.column {
+                imgui.text_colored(
+                    color,
+                    &format!("      | {}^", " ".repeat(column.saturating_sub(1) as usize)),
+                );
+            }
+        } else {
+            imgui.text(&text);
+        }
+    }
+}
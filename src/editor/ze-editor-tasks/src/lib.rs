@@ -0,0 +1,175 @@
+use enumflags2::make_bitflags;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ze_imgui::ze_imgui_sys::ImVec2;
+use ze_imgui::{Cond, Context, WindowFlagBits};
+use ze_jobsystem::progress::Progress;
+use ze_jobsystem::JobSystem;
+
+/// Time a toast stays visible once its task is finished
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/// Distance between the toasts and the main viewport borders
+const TOAST_MARGIN: f32 = 10.0;
+
+const PROGRESS_BAR_WIDTH: f32 = 250.0;
+
+struct Task {
+    progress: Arc<Progress>,
+    finished_at: Option<Instant>,
+}
+
+impl Task {
+    fn status(&self) -> String {
+        let progress = &self.progress;
+        if progress.is_finished() {
+            if progress.is_cancelled() {
+                format!("Cancelled ({}/{})", progress.completed(), progress.total())
+            } else {
+                "Done".to_string()
+            }
+        } else if progress.is_cancelled() {
+            "Cancelling...".to_string()
+        } else {
+            progress.current_item()
+        }
+    }
+}
+
+/// Track background operations reported through the jobsystem (asset imports, shader
+/// compilation...) and display them in a status bar and toast notifications
+pub struct TaskService {
+    started: Arc<Mutex<Vec<Arc<Progress>>>>,
+    tasks: Vec<Task>,
+}
+
+impl TaskService {
+    pub fn new(jobsystem: &JobSystem) -> Self {
+        let started: Arc<Mutex<Vec<Arc<Progress>>>> = Default::default();
+        {
+            let started = started.clone();
+            jobsystem
+                .on_progress_started
+                .connect(move |progress| started.lock().push(progress));
+        }
+
+        Self {
+            started,
+            tasks: vec![],
+        }
+    }
+
+    /// Tasks that are not finished yet
+    fn running(&self) -> impl Iterator<Item = &Arc<Progress>> {
+        self.tasks
+            .iter()
+            .map(|task| &task.progress)
+            .filter(|progress| !progress.is_finished())
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
+
+        self.update();
+        self.draw_status_bar(imgui);
+        self.draw_toasts(imgui);
+    }
+
+    fn update(&mut self) {
+        let now = Instant::now();
+        self.tasks
+            .extend(self.started.lock().drain(..).map(|progress| Task {
+                progress,
+                finished_at: None,
+            }));
+
+        for task in &mut self.tasks {
+            if task.finished_at.is_none() && task.progress.is_finished() {
+                task.finished_at = Some(now);
+            }
+        }
+
+        // Tasks without any item are not worth a notification
+        self.tasks.retain(|task| match task.finished_at {
+            Some(finished_at) => {
+                task.progress.total() > 0 && now.duration_since(finished_at) < TOAST_DURATION
+            }
+            None => true,
+        });
+    }
+
+    fn draw_status_bar(&self, imgui: &mut Context) {
+        if !imgui.begin_status_bar() {
+            return;
+        }
+
+        let running = self.running().collect::<Vec<_>>();
+        match running.first() {
+            Some(progress) => {
+                imgui.text(&format!(
+                    "{} ({}/{})",
+                    progress.name(),
+                    progress.completed(),
+                    progress.total()
+                ));
+                imgui.progress_bar(
+                    progress.fraction(),
+                    ImVec2::new(PROGRESS_BAR_WIDTH, 0.0),
+                    None,
+                );
+                imgui.text(&progress.current_item());
+                if progress.is_cancellable()
+                    && !progress.is_cancelled()
+                    && imgui.button("Cancel", ImVec2::default())
+                {
+                    progress.cancel();
+                }
+                if running.len() > 1 {
+                    imgui.text(&format!("+{} more", running.len() - 1));
+                }
+            }
+            None => imgui.text("Ready"),
+        }
+
+        imgui.end_status_bar();
+    }
+
+    fn draw_toasts(&self, imgui: &mut Context) {
+        let viewport = imgui.main_viewport();
+        let work_pos = viewport.work_pos();
+        let work_size = viewport.work_size();
+        let mut position = ImVec2::new(
+            work_pos.x + work_size.x - TOAST_MARGIN,
+            work_pos.y + work_size.y - TOAST_MARGIN,
+        );
+
+        for task in &self.tasks {
+            let progress = &task.progress;
+            let flags = make_bitflags!(WindowFlagBits::{
+                NoTitleBar | NoResize | NoMove | NoCollapse | NoScrollbar | AlwaysAutoResize
+                | NoSavedSettings | NoFocusOnAppearing | NoNavFocus | NoDocking
+            });
+
+            imgui.set_next_window_viewport(imgui.main_viewport());
+            imgui.set_next_window_pos(position, Cond::Always, ImVec2::new(1.0, 1.0));
+            imgui.begin_window(&format!("##Toast{:p}", Arc::as_ptr(progress)), flags);
+            imgui.text(progress.name());
+            imgui.progress_bar(
+                progress.fraction(),
+                ImVec2::new(PROGRESS_BAR_WIDTH, 0.0),
+                Some(&format!("{}/{}", progress.completed(), progress.total())),
+            );
+            imgui.text(&task.status());
+            if progress.is_cancellable() && !progress.is_finished() && !progress.is_cancelled() {
+                imgui.same_line(0.0, -1.0);
+                if imgui.button("Cancel", ImVec2::default()) {
+                    progress.cancel();
+                }
+            }
+
+            position.y -= imgui.window_size().y + TOAST_MARGIN;
+            imgui.end_window();
+        }
+    }
+}
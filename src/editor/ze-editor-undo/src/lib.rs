@@ -0,0 +1,115 @@
+use parking_lot::Mutex;
+use ze_ecs::component::Component;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+
+/// Maximum number of commands kept in the undo history
+const MAX_HISTORY: usize = 256;
+
+/// A reversible modification
+///
+/// Commands are pushed once already applied, `redo` must apply them again
+pub trait UndoCommand: Send + Sync {
+    fn name(&self) -> String;
+    fn undo(&mut self, world: &mut World);
+    fn redo(&mut self, world: &mut World);
+}
+
+/// Undo history shared by every editor panel
+#[derive(Default)]
+pub struct UndoService {
+    undo_stack: Mutex<Vec<Box<dyn UndoCommand>>>,
+    redo_stack: Mutex<Vec<Box<dyn UndoCommand>>>,
+}
+
+impl UndoService {
+    /// Push an already applied command, clearing the redo history
+    pub fn push<C: UndoCommand + 'static>(&self, command: C) {
+        let mut undo_stack = self.undo_stack.lock();
+        if undo_stack.len() == MAX_HISTORY {
+            undo_stack.remove(0);
+        }
+        undo_stack.push(Box::new(command));
+        self.redo_stack.lock().clear();
+    }
+
+    /// Undo the last command, returns false if there is nothing to undo
+    pub fn undo(&self, world: &mut World) -> bool {
+        let command = self.undo_stack.lock().pop();
+        match command {
+            Some(mut command) => {
+                command.undo(world);
+                self.redo_stack.lock().push(command);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone command, returns false if there is nothing to redo
+    pub fn redo(&self, world: &mut World) -> bool {
+        let command = self.redo_stack.lock().pop();
+        match command {
+            Some(mut command) => {
+                command.redo(world);
+                self.undo_stack.lock().push(command);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Name of the command undone by the next `undo` call
+    pub fn undo_name(&self) -> Option<String> {
+        self.undo_stack.lock().last().map(|command| command.name())
+    }
+
+    /// Name of the command redone by the next `redo` call
+    pub fn redo_name(&self) -> Option<String> {
+        self.redo_stack.lock().last().map(|command| command.name())
+    }
+
+    pub fn clear(&self) {
+        self.undo_stack.lock().clear();
+        self.redo_stack.lock().clear();
+    }
+}
+
+/// Replace the value of a component
+pub struct SetComponent<T: Component + Clone> {
+    name: String,
+    entity: Entity,
+    before: T,
+    after: T,
+}
+
+impl<T: Component + Clone> SetComponent<T> {
+    pub fn new(name: &str, entity: Entity, before: T, after: T) -> Self {
+        Self {
+            name: name.to_string(),
+            entity,
+            before,
+            after,
+        }
+    }
+
+    fn set(world: &World, entity: Entity, value: &T) {
+        if let Some(component) = world.query::<&mut T>().get(world, entity) {
+            *component = value.clone();
+        }
+    }
+}
+
+impl<T: Component + Clone> UndoCommand for SetComponent<T> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn undo(&mut self, world: &mut World) {
+        Self::set(world, self.entity, &self.before);
+    }
+
+    fn redo(&mut self, world: &mut World) {
+        Self::set(world, self.entity, &self.after);
+    }
+}
@@ -0,0 +1,70 @@
+use nalgebra_glm as glm;
+use std::f32::consts::FRAC_PI_2;
+
+/// Pitch is clamped to avoid flipping when looking straight up or down
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Fly camera controlled from the viewport
+/// Uses a left-handed coordinate system with Y up
+pub struct ViewportCamera {
+    pub position: glm::Vec3,
+
+    /// Rotation around the Y axis in radians
+    pub yaw: f32,
+
+    /// Rotation around the X axis in radians
+    pub pitch: f32,
+
+    /// Vertical field of view in radians
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+
+    /// Movement speed in units per second
+    pub speed: f32,
+}
+
+impl ViewportCamera {
+    pub fn forward(&self) -> glm::Vec3 {
+        glm::vec3(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+
+    pub fn right(&self) -> glm::Vec3 {
+        glm::vec3(self.yaw.cos(), 0.0, -self.yaw.sin())
+    }
+
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    pub fn view(&self) -> glm::Mat4 {
+        glm::look_at_lh(
+            &self.position,
+            &(self.position + self.forward()),
+            &glm::Vec3::y(),
+        )
+    }
+
+    pub fn projection(&self, aspect_ratio: f32) -> glm::Mat4 {
+        glm::perspective_lh_zo(aspect_ratio, self.fov_y, self.near, self.far)
+    }
+}
+
+impl Default for ViewportCamera {
+    fn default() -> Self {
+        Self {
+            position: glm::vec3(0.0, 1.0, -5.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            fov_y: 60.0_f32.to_radians(),
+            near: 0.1,
+            far: 1000.0,
+            speed: 5.0,
+        }
+    }
+}
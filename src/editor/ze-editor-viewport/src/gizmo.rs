@@ -0,0 +1,475 @@
+use crate::camera::ViewportCamera;
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use ze_imgui::ze_imgui_sys::{ImVec2, ImVec4};
+use ze_imgui::Context;
+use ze_platform::MouseButton;
+use ze_scene::transform::Transform;
+
+/// Length of the gizmo axes in pixels
+const GIZMO_SIZE: f32 = 100.0;
+
+/// Maximum distance in pixels between the mouse and a handle to hover it
+const HOVER_DISTANCE: f32 = 8.0;
+
+const RING_SEGMENTS: usize = 64;
+const LINE_THICKNESS: f32 = 3.0;
+const ARROW_SIZE: f32 = 8.0;
+const SCALE_HANDLE_SIZE: f32 = 5.0;
+
+const AXIS_COLORS: [[f32; 4]; 3] = [
+    [0.9, 0.2, 0.2, 1.0],
+    [0.2, 0.9, 0.2, 1.0],
+    [0.2, 0.4, 0.9, 1.0],
+];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// Space the gizmo axes are expressed in
+/// For child entities, the world space is the space of their parent
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GizmoSpace {
+    Local,
+    #[default]
+    World,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct GizmoSnapping {
+    pub enabled: bool,
+    pub translation: f32,
+
+    /// Rotation step in degrees
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Default for GizmoSnapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            translation: 0.5,
+            rotation: 15.0,
+            scale: 0.1,
+        }
+    }
+}
+
+impl GizmoSnapping {
+    fn snap(&self, value: f32, step: f32) -> f32 {
+        if self.enabled && step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    }
+}
+
+pub enum GizmoResult {
+    None,
+    Hovered,
+    Dragging,
+
+    /// The drag ended this frame, contains the transform before the drag
+    Finished(Transform),
+}
+
+struct Drag {
+    axis: usize,
+    direction: glm::Vec3,
+    start: Transform,
+
+    /// Position along the axis for translation/scale, direction from the center for rotation
+    start_value: glm::Vec3,
+}
+
+/// Maps positions in the gizmo space to the viewport screen space
+pub(crate) struct ScreenProjection {
+    view_proj: glm::Mat4,
+    inverse_view_proj: glm::Mat4,
+    origin: glm::Vec2,
+    size: glm::Vec2,
+}
+
+impl ScreenProjection {
+    /// Projection of the points in the space of `model` to the viewport image at `origin`
+    pub(crate) fn new(
+        camera: &ViewportCamera,
+        model: &glm::Mat4,
+        origin: ImVec2,
+        size: ImVec2,
+    ) -> Self {
+        let view_proj = camera.projection(size.x / size.y) * camera.view() * model;
+        Self {
+            view_proj,
+            inverse_view_proj: glm::inverse(&view_proj),
+            origin: glm::vec2(origin.x, origin.y),
+            size: glm::vec2(size.x, size.y),
+        }
+    }
+
+    pub(crate) fn to_screen(&self, point: &glm::Vec3) -> Option<glm::Vec2> {
+        let clip = self.view_proj * glm::vec4(point.x, point.y, point.z, 1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+
+        let ndc = clip.xy() / clip.w;
+        Some(glm::vec2(
+            self.origin.x + (ndc.x + 1.0) * 0.5 * self.size.x,
+            self.origin.y + (1.0 - ndc.y) * 0.5 * self.size.y,
+        ))
+    }
+
+    /// Ray going through `screen_pos`, as an origin and a direction
+    fn ray(&self, screen_pos: &glm::Vec2) -> (glm::Vec3, glm::Vec3) {
+        let ndc = glm::vec2(
+            (screen_pos.x - self.origin.x) / self.size.x * 2.0 - 1.0,
+            1.0 - (screen_pos.y - self.origin.y) / self.size.y * 2.0,
+        );
+        let unproject = |depth: f32| {
+            let point = self.inverse_view_proj * glm::vec4(ndc.x, ndc.y, depth, 1.0);
+            point.xyz() / point.w
+        };
+
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        (near, (far - near).normalize())
+    }
+}
+
+/// Translate/rotate/scale manipulator drawn over the viewport
+#[derive(Default)]
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    pub snapping: GizmoSnapping,
+    drag: Option<Drag>,
+}
+
+impl Gizmo {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Draw the gizmo and edit `transform`, must be called right after drawing the viewport image
+    ///
+    /// `parent` is the local to world matrix of the parent of the transform
+    pub fn manipulate(
+        &mut self,
+        imgui: &mut Context,
+        camera: &ViewportCamera,
+        parent: &glm::Mat4,
+        origin: ImVec2,
+        size: ImVec2,
+        transform: &mut Transform,
+    ) -> GizmoResult {
+        let projection = ScreenProjection::new(camera, parent, origin, size);
+
+        // Keep the same size on screen whatever the distance to the camera is
+        let center = transform.translation;
+        let world_center = parent * glm::vec4(center.x, center.y, center.z, 1.0);
+        let distance = glm::distance(&world_center.xyz(), &camera.position);
+        let parent_scale = (parent * glm::vec4(1.0, 0.0, 0.0, 0.0)).xyz().norm();
+        let length = GIZMO_SIZE * 2.0 * distance * (camera.fov_y * 0.5).tan()
+            / size.y
+            / parent_scale.max(f32::EPSILON);
+
+        let mouse_pos = imgui.mouse_pos();
+        let mouse_pos = glm::vec2(mouse_pos.x, mouse_pos.y);
+
+        if let Some(drag) = &self.drag {
+            if !imgui.is_mouse_down(MouseButton::Left) {
+                let start = drag.start;
+                self.drag = None;
+                return GizmoResult::Finished(start);
+            }
+
+            let (ray_origin, ray_direction) = projection.ray(&mouse_pos);
+            self.update_drag(drag, &ray_origin, &ray_direction, transform);
+
+            let axes = self.axes(&drag.start);
+            self.draw(
+                imgui,
+                &projection,
+                transform,
+                &axes,
+                length,
+                Some(drag.axis),
+            );
+            return GizmoResult::Dragging;
+        }
+
+        let axes = self.axes(transform);
+        let hovered_axis = self.hovered_axis(&projection, transform, &axes, length, &mouse_pos);
+        self.draw(imgui, &projection, transform, &axes, length, hovered_axis);
+
+        match hovered_axis {
+            Some(axis) if imgui.is_item_clicked(MouseButton::Left) => {
+                let (ray_origin, ray_direction) = projection.ray(&mouse_pos);
+                let direction = axes[axis];
+                let start_value = match self.mode {
+                    GizmoMode::Translate | GizmoMode::Scale => {
+                        closest_point_on_axis(&center, &direction, &ray_origin, &ray_direction)
+                    }
+                    GizmoMode::Rotate => {
+                        direction_on_plane(&center, &direction, &ray_origin, &ray_direction)
+                    }
+                };
+
+                match start_value {
+                    Some(start_value) => {
+                        self.drag = Some(Drag {
+                            axis,
+                            direction,
+                            start: *transform,
+                            start_value,
+                        });
+                        GizmoResult::Dragging
+                    }
+                    None => GizmoResult::Hovered,
+                }
+            }
+            Some(_) => GizmoResult::Hovered,
+            None => GizmoResult::None,
+        }
+    }
+
+    fn axes(&self, transform: &Transform) -> [glm::Vec3; 3] {
+        let axes = [glm::Vec3::x(), glm::Vec3::y(), glm::Vec3::z()];
+        if self.mode == GizmoMode::Scale || self.space == GizmoSpace::Local {
+            axes.map(|axis| glm::quat_rotate_vec3(&transform.rotation, &axis))
+        } else {
+            axes
+        }
+    }
+
+    fn update_drag(
+        &self,
+        drag: &Drag,
+        ray_origin: &glm::Vec3,
+        ray_direction: &glm::Vec3,
+        transform: &mut Transform,
+    ) {
+        let center = drag.start.translation;
+        match self.mode {
+            GizmoMode::Translate => {
+                if let Some(point) =
+                    closest_point_on_axis(&center, &drag.direction, ray_origin, ray_direction)
+                {
+                    let offset = (point - drag.start_value).dot(&drag.direction);
+                    let offset = self.snapping.snap(offset, self.snapping.translation);
+                    transform.translation = center + drag.direction * offset;
+                }
+            }
+            GizmoMode::Rotate => {
+                if let Some(direction) =
+                    direction_on_plane(&center, &drag.direction, ray_origin, ray_direction)
+                {
+                    let angle = drag
+                        .start_value
+                        .cross(&direction)
+                        .dot(&drag.direction)
+                        .atan2(drag.start_value.dot(&direction));
+                    let angle = self
+                        .snapping
+                        .snap(angle.to_degrees(), self.snapping.rotation)
+                        .to_radians();
+                    transform.rotation =
+                        glm::quat_angle_axis(angle, &drag.direction) * drag.start.rotation;
+                }
+            }
+            GizmoMode::Scale => {
+                if let Some(point) =
+                    closest_point_on_axis(&center, &drag.direction, ray_origin, ray_direction)
+                {
+                    let start = (drag.start_value - center).dot(&drag.direction);
+                    if start.abs() > f32::EPSILON {
+                        let factor = (point - center).dot(&drag.direction) / start;
+                        let scale = drag.start.scale[drag.axis] * factor;
+                        transform.scale[drag.axis] = self.snapping.snap(scale, self.snapping.scale);
+                    }
+                }
+            }
+        }
+    }
+
+    fn hovered_axis(
+        &self,
+        projection: &ScreenProjection,
+        transform: &Transform,
+        axes: &[glm::Vec3; 3],
+        length: f32,
+        mouse_pos: &glm::Vec2,
+    ) -> Option<usize> {
+        let center = transform.translation;
+        let mut hovered = None;
+        let mut min_distance = HOVER_DISTANCE;
+        for (axis, direction) in axes.iter().enumerate() {
+            let points = match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    vec![center, center + direction * length]
+                }
+                GizmoMode::Rotate => ring_points(&center, axes, axis, length),
+            };
+
+            let screen_points = points
+                .iter()
+                .map(|point| projection.to_screen(point))
+                .collect::<Vec<_>>();
+            for segment in screen_points.windows(2) {
+                if let [Some(a), Some(b)] = segment {
+                    let distance = distance_to_segment(mouse_pos, a, b);
+                    if distance < min_distance {
+                        min_distance = distance;
+                        hovered = Some(axis);
+                    }
+                }
+            }
+        }
+
+        hovered
+    }
+
+    fn draw(
+        &self,
+        imgui: &mut Context,
+        projection: &ScreenProjection,
+        transform: &Transform,
+        axes: &[glm::Vec3; 3],
+        length: f32,
+        active_axis: Option<usize>,
+    ) {
+        let center = transform.translation;
+        for (axis, direction) in axes.iter().enumerate() {
+            let [r, g, b, a] = if active_axis == Some(axis) {
+                ACTIVE_COLOR
+            } else {
+                AXIS_COLORS[axis]
+            };
+            let color = ImVec4::new(r, g, b, a);
+
+            match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let (start, end) = match (
+                        projection.to_screen(&center),
+                        projection.to_screen(&(center + direction * length)),
+                    ) {
+                        (Some(start), Some(end)) => (start, end),
+                        _ => continue,
+                    };
+
+                    imgui.window_add_line(
+                        to_imvec2(&start),
+                        to_imvec2(&end),
+                        color,
+                        LINE_THICKNESS,
+                    );
+                    if self.mode == GizmoMode::Translate {
+                        let forward = (end - start).try_normalize(f32::EPSILON);
+                        if let Some(forward) = forward {
+                            let side = glm::vec2(-forward.y, forward.x) * ARROW_SIZE * 0.5;
+                            let base = end - forward * ARROW_SIZE;
+                            imgui.window_add_triangle_filled(
+                                to_imvec2(&(end + forward * ARROW_SIZE)),
+                                to_imvec2(&(base + side)),
+                                to_imvec2(&(base - side)),
+                                color,
+                            );
+                        }
+                    } else {
+                        imgui.window_add_rect_filled(
+                            to_imvec2(&(end - glm::Vec2::repeat(SCALE_HANDLE_SIZE))),
+                            to_imvec2(&(end + glm::Vec2::repeat(SCALE_HANDLE_SIZE))),
+                            color,
+                        );
+                    }
+                }
+                GizmoMode::Rotate => {
+                    let points = ring_points(&center, axes, axis, length)
+                        .iter()
+                        .filter_map(|point| projection.to_screen(point))
+                        .map(|point| to_imvec2(&point))
+                        .collect::<Vec<_>>();
+                    imgui.window_add_polyline(&points, color, LINE_THICKNESS);
+                }
+            }
+        }
+    }
+}
+
+/// Points of the rotation ring around `axes[axis]`
+fn ring_points(
+    center: &glm::Vec3,
+    axes: &[glm::Vec3; 3],
+    axis: usize,
+    radius: f32,
+) -> Vec<glm::Vec3> {
+    let u = axes[(axis + 1) % 3];
+    let v = axes[(axis + 2) % 3];
+    (0..=RING_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Point of the axis line closest to the ray
+fn closest_point_on_axis(
+    center: &glm::Vec3,
+    direction: &glm::Vec3,
+    ray_origin: &glm::Vec3,
+    ray_direction: &glm::Vec3,
+) -> Option<glm::Vec3> {
+    let b = direction.dot(ray_direction);
+    let denominator = 1.0 - b * b;
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+
+    let w = center - ray_origin;
+    let t = (b * ray_direction.dot(&w) - direction.dot(&w)) / denominator;
+    Some(center + direction * t)
+}
+
+/// Normalized direction from `center` to the intersection of the ray and the plane of normal `normal`
+fn direction_on_plane(
+    center: &glm::Vec3,
+    normal: &glm::Vec3,
+    ray_origin: &glm::Vec3,
+    ray_direction: &glm::Vec3,
+) -> Option<glm::Vec3> {
+    let denominator = normal.dot(ray_direction);
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+
+    let t = normal.dot(&(center - ray_origin)) / denominator;
+    if t < 0.0 {
+        return None;
+    }
+
+    (ray_origin + ray_direction * t - center).try_normalize(f32::EPSILON)
+}
+
+fn distance_to_segment(point: &glm::Vec2, a: &glm::Vec2, b: &glm::Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.norm_squared() > f32::EPSILON {
+        ((point - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    glm::distance(point, &(a + ab * t))
+}
+
+pub(crate) fn to_imvec2(vec: &glm::Vec2) -> ImVec2 {
+    ImVec2::new(vec.x, vec.y)
+}
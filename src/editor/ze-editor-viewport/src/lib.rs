@@ -0,0 +1,615 @@
+use crate::camera::ViewportCamera;
+use crate::gizmo::{
+    to_imvec2, Gizmo, GizmoMode, GizmoResult, GizmoSnapping, GizmoSpace, ScreenProjection,
+};
+use enumflags2::make_bitflags;
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use ze_core::maths::Vector3;
+use ze_core::ze_error;
+use ze_ecs::world::World;
+use ze_editor_selection::{SelectionItem, SelectionService};
+use ze_editor_undo::{SetComponent, UndoService};
+use ze_gfx::backend::*;
+use ze_gfx::PixelFormat;
+use ze_imgui::ze_imgui_sys::{ImVec2, ImVec4};
+use ze_imgui::{Context, Key, StyleVar, WindowFlags};
+use ze_platform::MouseButton;
+use ze_scene::debug_draw::DebugDraw;
+use ze_scene::transform::{global_matrix, Transform};
+
+pub mod camera;
+pub mod gizmo;
+
+pub const VIEWPORT_ID: &str = "Viewport";
+
+const COLOR_FORMAT: PixelFormat = PixelFormat::R8G8B8A8Unorm;
+const CLEAR_COLOR: [f32; 4] = [0.1, 0.1, 0.1, 1.0];
+
+/// Entity id target format, see [`ViewportTargets::ids`]
+const ID_FORMAT: PixelFormat = PixelFormat::R32Uint;
+
+/// Frames to wait before reading a picking result
+/// Must be greater than the amount of frames in flight of the device
+const PICKING_LATENCY: u32 = 3;
+
+/// Row pitch of the picking readback buffer, buffer rows must be aligned to 256 bytes
+const PICKING_ROW_PITCH: u32 = 256;
+
+const MOUSE_SENSITIVITY: f32 = 0.005;
+
+const DEBUG_LINE_THICKNESS: f32 = 1.0;
+
+/// Viewport settings saved in the editor layouts
+#[derive(Serialize, Deserialize)]
+pub struct ViewportState {
+    pub camera_position: [f32; 3],
+    pub camera_yaw: f32,
+    pub camera_pitch: f32,
+    pub camera_speed: f32,
+    pub gizmo_mode: GizmoMode,
+    pub gizmo_space: GizmoSpace,
+    pub gizmo_snapping: GizmoSnapping,
+}
+
+/// Render the scene displayed by a [`SceneViewport`]
+pub trait ViewportRenderer {
+    /// Render the scene to `targets`
+    ///
+    /// Targets are cleared and in the [`ResourceState::RenderTargetWrite`] state,
+    /// they can be imported in a render graph as external textures
+    fn render(
+        &mut self,
+        cmd_list: &mut CommandList,
+        targets: &ViewportTargets,
+        camera: &ViewportCamera,
+    );
+}
+
+/// Textures a viewport renders to
+pub struct ViewportTargets {
+    pub width: u32,
+    pub height: u32,
+    pub color: Arc<Texture>,
+    pub color_rtv: RenderTargetView,
+
+    /// Id of the entity covering each pixel, as `entity.id() + 1`
+    /// 0 means that no entity is covering the pixel
+    pub ids: Arc<Texture>,
+    pub ids_rtv: RenderTargetView,
+    color_srv: Arc<ShaderResourceView>,
+    color_state: ResourceState,
+    ids_state: ResourceState,
+}
+
+impl ViewportTargets {
+    fn new(device: &Arc<dyn Device>, width: u32, height: u32) -> Result<Self, DeviceError> {
+        let create_texture = |format, name| -> Result<Arc<Texture>, DeviceError> {
+            Ok(Arc::new(device.create_texture(
+                &TextureDesc {
+                    width,
+                    height,
+                    depth: 1,
+                    array_size: 1,
+                    mip_levels: 1,
+                    format,
+                    sample_desc: Default::default(),
+                    usage_flags: make_bitflags!(TextureUsageFlagBits::{RenderTarget | Sampled}),
+                    memory_desc: MemoryDesc {
+                        memory_location: MemoryLocation::GpuOnly,
+                        memory_flags: Default::default(),
+                    },
+                },
+                None,
+                name,
+            )?))
+        };
+
+        let create_rtv = |texture: &Arc<Texture>| {
+            device.create_render_target_view(&RenderTargetViewDesc {
+                resource: texture.clone(),
+                format: texture.desc.format,
+                ty: RenderTargetViewType::Texture2D(Texture2DRTV { mip_level: 0 }),
+            })
+        };
+
+        let color = create_texture(COLOR_FORMAT, "Viewport Color")?;
+        let ids = create_texture(ID_FORMAT, "Viewport Entity Ids")?;
+        let color_srv = Arc::new(device.create_shader_resource_view(
+            &ShaderResourceViewDesc::Texture2D(Texture2DSRV {
+                texture: color.clone(),
+                format: COLOR_FORMAT,
+                min_mip_level: 0,
+                mip_levels: 1,
+            }),
+        )?);
+
+        Ok(Self {
+            width,
+            height,
+            color_rtv: create_rtv(&color)?,
+            color,
+            ids_rtv: create_rtv(&ids)?,
+            ids,
+            color_srv,
+            color_state: ResourceState::Common,
+            ids_state: ResourceState::Common,
+        })
+    }
+}
+
+/// Pixel to pick at the next render
+struct PendingPick {
+    x: u32,
+    y: u32,
+
+    /// Toggle the picked entity selection instead of replacing the selection
+    toggle: bool,
+}
+
+/// Entity id being copied to the CPU
+struct PickReadback {
+    buffer: Buffer,
+    frames_left: u32,
+    toggle: bool,
+}
+
+/// Panel displaying the scene from a fly camera
+///
+/// Clicking on the scene selects the entity under the cursor using the entity id target
+pub struct SceneViewport {
+    device: Arc<dyn Device>,
+    selection: Arc<SelectionService>,
+    undo: Arc<UndoService>,
+    renderer: Option<Box<dyn ViewportRenderer>>,
+    camera: ViewportCamera,
+    gizmo: Gizmo,
+    targets: Option<ViewportTargets>,
+    controlling_camera: bool,
+    pending_pick: Option<PendingPick>,
+    pick_readbacks: Vec<PickReadback>,
+    debug_draw: DebugDraw,
+
+    /// Raw mouse motion received since the last camera update, if the platform supports it
+    raw_mouse_motion: Option<glm::Vec2>,
+}
+
+impl SceneViewport {
+    pub fn new(
+        device: Arc<dyn Device>,
+        selection: Arc<SelectionService>,
+        undo: Arc<UndoService>,
+    ) -> Self {
+        Self {
+            device,
+            selection,
+            undo,
+            renderer: None,
+            camera: ViewportCamera::default(),
+            gizmo: Gizmo::default(),
+            targets: None,
+            controlling_camera: false,
+            pending_pick: None,
+            pick_readbacks: vec![],
+            debug_draw: DebugDraw::default(),
+            raw_mouse_motion: None,
+        }
+    }
+
+    pub fn set_renderer<R: ViewportRenderer + 'static>(&mut self, renderer: R) {
+        self.renderer = Some(Box::new(renderer));
+    }
+
+    pub fn camera(&self) -> &ViewportCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut ViewportCamera {
+        &mut self.camera
+    }
+
+    pub fn gizmo_mut(&mut self) -> &mut Gizmo {
+        &mut self.gizmo
+    }
+
+    /// Lines drawn over the scene, kept until cleared
+    pub fn debug_draw_mut(&mut self) -> &mut DebugDraw {
+        &mut self.debug_draw
+    }
+
+    /// Raw motion is used to rotate the camera instead of the cursor motion, which is
+    /// accelerated and stops at the screen edges
+    pub fn add_raw_mouse_motion(&mut self, delta_x: f32, delta_y: f32) {
+        *self.raw_mouse_motion.get_or_insert_with(glm::Vec2::zeros) += glm::vec2(delta_x, delta_y);
+    }
+
+    pub fn state(&self) -> ViewportState {
+        ViewportState {
+            camera_position: self.camera.position.into(),
+            camera_yaw: self.camera.yaw,
+            camera_pitch: self.camera.pitch,
+            camera_speed: self.camera.speed,
+            gizmo_mode: self.gizmo.mode,
+            gizmo_space: self.gizmo.space,
+            gizmo_snapping: self.gizmo.snapping,
+        }
+    }
+
+    pub fn set_state(&mut self, state: &ViewportState) {
+        self.camera.position = state.camera_position.into();
+        self.camera.yaw = state.camera_yaw;
+        self.camera.pitch = state.camera_pitch;
+        self.camera.speed = state.camera_speed;
+        self.gizmo.mode = state.gizmo_mode;
+        self.gizmo.space = state.gizmo_space;
+        self.gizmo.snapping = state.gizmo_snapping;
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context, world: &World) {
+        puffin::profile_function!();
+        self.resolve_picks(world);
+
+        imgui.push_style_var_vec2f32(StyleVar::WindowPadding, ImVec2::from(0.0));
+        imgui.begin_window(VIEWPORT_ID, WindowFlags::empty());
+        imgui.pop_style_var(1);
+        self.draw_toolbar(imgui);
+
+        let size = imgui.available_content_region();
+        let width = size.x.max(1.0) as u32;
+        let height = size.y.max(1.0) as u32;
+        if !matches!(&self.targets, Some(targets) if targets.width == width && targets.height == height)
+        {
+            self.pending_pick = None;
+            self.targets = match ViewportTargets::new(&self.device, width, height) {
+                Ok(targets) => Some(targets),
+                Err(error) => {
+                    ze_error!("Failed to create viewport targets: {:?}", error);
+                    None
+                }
+            };
+        }
+
+        if let Some(targets) = &self.targets {
+            let origin = imgui.cursor_screen_pos();
+            let color = imgui.frame_texture(targets.color_srv.clone());
+            imgui.image(color, size);
+            let hovered = imgui.is_item_hovered();
+            self.draw_debug_lines(imgui, origin, size);
+            let gizmo_result = self.draw_gizmo(imgui, world, origin, size);
+            let gizmo_used = !matches!(gizmo_result, GizmoResult::None);
+
+            if hovered && !gizmo_used && imgui.is_item_clicked(MouseButton::Left) {
+                let mouse_pos = imgui.mouse_pos();
+                let x = mouse_pos.x - origin.x;
+                let y = mouse_pos.y - origin.y;
+                if x >= 0.0 && y >= 0.0 && (x as u32) < width && (y as u32) < height {
+                    self.pending_pick = Some(PendingPick {
+                        x: x as u32,
+                        y: y as u32,
+                        toggle: imgui.is_key_down(Key::LeftCtrl),
+                    });
+                }
+            }
+
+            self.update_camera(imgui, hovered);
+        }
+
+        imgui.end_window();
+    }
+
+    fn draw_toolbar(&mut self, imgui: &mut Context) {
+        for (mode, label) in [
+            (GizmoMode::Translate, "Translate (W)"),
+            (GizmoMode::Rotate, "Rotate (E)"),
+            (GizmoMode::Scale, "Scale (R)"),
+        ] {
+            let mut active = self.gizmo.mode == mode;
+            if imgui.toggle_button(label, &mut active, ImVec2::default()) {
+                self.gizmo.mode = mode;
+            }
+            imgui.same_line(0.0, -1.0);
+        }
+
+        let space_label = match self.gizmo.space {
+            GizmoSpace::Local => "Local",
+            GizmoSpace::World => "World",
+        };
+        if imgui.button(space_label, ImVec2::default()) {
+            self.gizmo.space = match self.gizmo.space {
+                GizmoSpace::Local => GizmoSpace::World,
+                GizmoSpace::World => GizmoSpace::Local,
+            };
+        }
+        imgui.same_line(0.0, -1.0);
+        imgui.toggle_button("Snap", &mut self.gizmo.snapping.enabled, ImVec2::default());
+
+        // Shortcuts are only available when the camera is not moving as WASD is used to move it
+        if imgui.is_window_hovered() && !self.controlling_camera && !self.gizmo.is_dragging() {
+            for (key, mode) in [
+                (Key::W, GizmoMode::Translate),
+                (Key::E, GizmoMode::Rotate),
+                (Key::R, GizmoMode::Scale),
+            ] {
+                if imgui.is_key_pressed(key, false) {
+                    self.gizmo.mode = mode;
+                }
+            }
+        }
+    }
+
+    fn draw_debug_lines(&self, imgui: &mut Context, origin: ImVec2, size: ImVec2) {
+        let projection = ScreenProjection::new(&self.camera, &glm::Mat4::identity(), origin, size);
+        for line in self.debug_draw.lines() {
+            if let (Some(start), Some(end)) = (
+                projection.to_screen(&line.start),
+                projection.to_screen(&line.end),
+            ) {
+                let [r, g, b, a] = line.color;
+                imgui.window_add_line(
+                    to_imvec2(&start),
+                    to_imvec2(&end),
+                    ImVec4::new(r, g, b, a),
+                    DEBUG_LINE_THICKNESS,
+                );
+            }
+        }
+    }
+
+    /// Manipulate the primary selected entity transform
+    fn draw_gizmo(
+        &mut self,
+        imgui: &mut Context,
+        world: &World,
+        origin: ImVec2,
+        size: ImVec2,
+    ) -> GizmoResult {
+        let entity = match self.selection.primary() {
+            Some(SelectionItem::Entity(entity)) if world.is_valid(entity) => entity,
+            _ => return GizmoResult::None,
+        };
+
+        let parent = world
+            .parent(entity)
+            .map_or(glm::Mat4::identity(), |parent| global_matrix(world, parent));
+
+        let transform = match world.query::<&mut Transform>().get(world, entity) {
+            Some(transform) => transform,
+            None => return GizmoResult::None,
+        };
+
+        let result = self
+            .gizmo
+            .manipulate(imgui, &self.camera, &parent, origin, size, transform);
+        if let GizmoResult::Finished(before) = &result {
+            if before != transform {
+                self.undo
+                    .push(SetComponent::new("Transform", entity, *before, *transform));
+            }
+        }
+
+        result
+    }
+
+    /// Render the scene to the viewport targets, must be called before the UI is rendered
+    pub fn render(&mut self, cmd_list: &mut CommandList) {
+        puffin::profile_function!();
+
+        let targets = match &mut self.targets {
+            Some(targets) => targets,
+            None => return,
+        };
+
+        let mut barriers = vec![];
+        if targets.color_state != ResourceState::RenderTargetWrite {
+            barriers.push(transition(
+                &targets.color,
+                targets.color_state,
+                ResourceState::RenderTargetWrite,
+            ));
+        }
+
+        if targets.ids_state != ResourceState::RenderTargetWrite {
+            barriers.push(transition(
+                &targets.ids,
+                targets.ids_state,
+                ResourceState::RenderTargetWrite,
+            ));
+        }
+
+        if !barriers.is_empty() {
+            self.device.cmd_resource_barrier(cmd_list, &barriers);
+        }
+
+        self.device.cmd_begin_render_pass(
+            cmd_list,
+            &RenderPassDesc {
+                render_targets: &[
+                    RenderPassRenderTarget {
+                        render_target_view: &targets.color_rtv,
+                        load_mode: RenderPassTextureLoadMode::Clear,
+                        store_mode: RenderPassTextureStoreMode::Preserve,
+                        clear_value: ClearValue::Color(CLEAR_COLOR),
+                    },
+                    RenderPassRenderTarget {
+                        render_target_view: &targets.ids_rtv,
+                        load_mode: RenderPassTextureLoadMode::Clear,
+                        store_mode: RenderPassTextureStoreMode::Preserve,
+                        clear_value: ClearValue::Color([0.0; 4]),
+                    },
+                ],
+                depth_stencil: None,
+            },
+        );
+        self.device.cmd_end_render_pass(cmd_list);
+
+        if let Some(renderer) = &mut self.renderer {
+            renderer.render(cmd_list, targets, &self.camera);
+        }
+
+        let mut barriers = vec![transition(
+            &targets.color,
+            ResourceState::RenderTargetWrite,
+            ResourceState::ShaderRead,
+        )];
+        targets.color_state = ResourceState::ShaderRead;
+        targets.ids_state = ResourceState::RenderTargetWrite;
+
+        let readback =
+            self.pending_pick
+                .take()
+                .and_then(|pick| match create_readback_buffer(&self.device) {
+                    Ok(buffer) => Some((pick, buffer)),
+                    Err(error) => {
+                        ze_error!("Failed to create picking readback buffer: {:?}", error);
+                        None
+                    }
+                });
+
+        if readback.is_some() {
+            barriers.push(transition(
+                &targets.ids,
+                ResourceState::RenderTargetWrite,
+                ResourceState::CopyRead,
+            ));
+            targets.ids_state = ResourceState::CopyRead;
+        }
+
+        self.device.cmd_resource_barrier(cmd_list, &barriers);
+
+        if let Some((pick, buffer)) = readback {
+            self.device.cmd_copy_texture_to_buffer_regions(
+                cmd_list,
+                &targets.ids,
+                &buffer,
+                &[BufferToTextureCopyRegion {
+                    buffer_offset_in_bytes: 0,
+                    buffer_texture_width: 1,
+                    buffer_texture_height: 1,
+                    buffer_texture_depth: 1,
+                    buffer_texture_row_pitch_in_bytes: PICKING_ROW_PITCH,
+                    texture_subresource_index: 0,
+                    texture_subresource_layout: self
+                        .device
+                        .texture_subresource_layout(&targets.ids, 0),
+                    texture_subresource_width: 1,
+                    texture_subresource_height: 1,
+                    texture_subresource_depth: 1,
+                    texture_subresource_offset: Vector3::new(pick.x as i32, pick.y as i32, 0),
+                }],
+            );
+
+            self.pick_readbacks.push(PickReadback {
+                buffer,
+                frames_left: PICKING_LATENCY,
+                toggle: pick.toggle,
+            });
+        }
+    }
+
+    /// Move the camera while the right mouse button is held, the drag must start in the viewport
+    fn update_camera(&mut self, imgui: &Context, hovered: bool) {
+        let raw_mouse_motion = self.raw_mouse_motion.take();
+
+        if hovered && imgui.is_item_clicked(MouseButton::Right) {
+            self.controlling_camera = true;
+        }
+
+        if !imgui.is_mouse_down(MouseButton::Right) {
+            self.controlling_camera = false;
+        }
+
+        if !self.controlling_camera {
+            return;
+        }
+
+        let delta = raw_mouse_motion.unwrap_or_else(|| {
+            let delta = imgui.mouse_delta();
+            glm::vec2(delta.x, delta.y)
+        });
+        self.camera
+            .rotate(delta.x * MOUSE_SENSITIVITY, -delta.y * MOUSE_SENSITIVITY);
+
+        let wheel = imgui.mouse_wheel();
+        if wheel != 0.0 {
+            self.camera.speed = (self.camera.speed * (1.0 + wheel * 0.1)).clamp(0.1, 1000.0);
+        }
+
+        let mut direction = glm::Vec3::zeros();
+        for (key, axis) in [
+            (Key::W, self.camera.forward()),
+            (Key::S, -self.camera.forward()),
+            (Key::D, self.camera.right()),
+            (Key::A, -self.camera.right()),
+            (Key::E, glm::Vec3::y()),
+            (Key::Q, -glm::Vec3::y()),
+        ] {
+            if imgui.is_key_down(key) {
+                direction += axis;
+            }
+        }
+
+        if direction != glm::Vec3::zeros() {
+            self.camera.position += direction.normalize() * self.camera.speed * imgui.delta_time();
+        }
+    }
+
+    /// Select the entities of the finished picking readbacks
+    fn resolve_picks(&mut self, world: &World) {
+        for readback in &mut self.pick_readbacks {
+            readback.frames_left = readback.frames_left.saturating_sub(1);
+        }
+
+        let (finished, pending) = std::mem::take(&mut self.pick_readbacks)
+            .into_iter()
+            .partition::<Vec<_>, _>(|readback| readback.frames_left == 0);
+        self.pick_readbacks = pending;
+
+        for readback in finished {
+            let id = match self.device.buffer_mapped_ptr(&readback.buffer) {
+                // SAFETY: The buffer is at least 4 bytes and the GPU finished writing to it
+                Some(ptr) => unsafe { (ptr as *const u32).read_unaligned() },
+                None => continue,
+            };
+
+            let entity = id
+                .checked_sub(1)
+                .and_then(|entity_id| world.try_entity(entity_id));
+
+            match (entity, readback.toggle) {
+                (Some(entity), true) => self.selection.toggle(SelectionItem::Entity(entity)),
+                (Some(entity), false) => self.selection.select(SelectionItem::Entity(entity)),
+                (None, true) => {}
+                (None, false) => self.selection.clear(),
+            }
+        }
+    }
+}
+
+fn transition(
+    texture: &Texture,
+    source_state: ResourceState,
+    dest_state: ResourceState,
+) -> ResourceBarrier<'_> {
+    ResourceBarrier::Transition(ResourceTransitionBarrier {
+        resource: ResourceTransitionBarrierResource::Texture(texture),
+        source_state,
+        dest_state,
+    })
+}
+
+fn create_readback_buffer(device: &Arc<dyn Device>) -> Result<Buffer, DeviceError> {
+    device.create_buffer(
+        &BufferDesc {
+            size_bytes: PICKING_ROW_PITCH as u64,
+            usage: BufferUsageFlags::default(),
+            memory_desc: MemoryDesc {
+                memory_location: MemoryLocation::GpuToCpu,
+                memory_flags: Default::default(),
+            },
+            default_resource_state: ResourceState::CopyWrite,
+        },
+        None,
+        "Viewport Picking Readback",
+    )
+}
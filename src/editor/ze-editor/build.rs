@@ -1,11 +1,13 @@
-﻿use std::path::Path;
+﻿use std::env;
+use std::path::Path;
 
 fn main() {
-    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
-
-    #[cfg(target_os = "windows")]
-    println!(
-        "cargo:rustc-link-arg=/DEF:{}\\agility.def",
-        manifest_dir.to_str().unwrap()
-    );
+    // Build scripts are compiled for the host, the target is only known at runtime
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        println!(
+            "cargo:rustc-link-arg=/DEF:{}\\agility.def",
+            manifest_dir.to_str().unwrap()
+        );
+    }
 }
@@ -1,51 +1,200 @@
-﻿use parking_lot::Mutex;
+use enumflags2::make_bitflags;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use ze_core::logger;
-use ze_core::logger::{Message, Sink};
+use ze_core::logger::RingBufferSink;
+use ze_core::{console, ze_error, ze_info};
+use ze_editor_log::severity_color;
 use ze_imgui::ze_imgui_sys::ImVec2;
-use ze_imgui::{Context, StyleVar, WindowFlagBits, WindowFlags};
+use ze_imgui::{
+    Context, Font, InputTextCallbackData, InputTextFlagBits, Key, StyleVar, WindowFlagBits,
+    WindowFlags,
+};
+
+pub const CONSOLE_ID: &str = "Console";
 
 pub struct Console {
-    messages: Mutex<Vec<Message>>,
+    log: Arc<RingBufferSink>,
+    input: String,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    font: Font,
+
+    /// Messages before this index are not displayed, set by the `clear` command
+    first_message_index: Arc<AtomicUsize>,
+    last_received_count: usize,
 }
 
 impl Console {
-    pub fn new() -> Arc<Self> {
-        let me = Arc::new(Self {
-            messages: Default::default(),
+    pub fn new(log: Arc<RingBufferSink>, font: Font) -> Self {
+        let first_message_index = Arc::new(AtomicUsize::new(0));
+        {
+            let log = log.clone();
+            let first_message_index = first_message_index.clone();
+            console::register_command("clear", "Clear the console output", move |_| {
+                first_message_index.store(log.received_count(), Ordering::SeqCst);
+                Ok(())
+            });
+        }
+
+        console::register_command("help", "List commands and console variables", |_| {
+            for command in console::commands() {
+                ze_info!("{} - {}", command.name(), command.description());
+            }
+
+            for cvar in console::cvars() {
+                ze_info!(
+                    "{} = {} - {}",
+                    cvar.name(),
+                    cvar.value(),
+                    cvar.description()
+                );
+            }
+            Ok(())
         });
-        logger::register_sink_weak(Arc::downgrade(&me));
-        me
+
+        Self {
+            log,
+            input: String::default(),
+            history: vec![],
+            history_index: None,
+            font,
+            first_message_index,
+            last_received_count: 0,
+        }
     }
 
-    pub fn draw(&self, imgui: &mut Context) {
-        let messages = self.messages.lock();
+    pub fn draw(&mut self, imgui: &mut Context) {
+        puffin::profile_function!();
 
         imgui.push_style_var_vec2f32(StyleVar::WindowPadding, ImVec2::from(0.0));
-        imgui.begin_window("Console", WindowFlags::empty());
+        imgui.begin_window(CONSOLE_ID, WindowFlags::empty());
         imgui.push_style_var_vec2f32(StyleVar::WindowPadding, ImVec2::from(5.0));
+        let footer_height = imgui.frame_height_with_spacing();
         imgui.begin_child(
             "ScrollingRegion",
-            imgui.available_content_region(),
+            ImVec2::new(0.0, -footer_height),
             false,
             WindowFlags::from_flag(WindowFlagBits::AlwaysUseWindowPadding),
         );
         imgui.pop_style_var(2);
 
-        for message in messages.iter() {
-            let message = format!("({}) {}", message.crate_name, message.message);
-            imgui.text_wrapped(&message);
-        }
+        // Only the visible messages are submitted, the sink can hold a lot of them
+        let received_count = self.log.received_count();
+        let first_stored_index = received_count.saturating_sub(self.log.capacity());
+        let first_message_index = self
+            .first_message_index
+            .load(Ordering::SeqCst)
+            .max(first_stored_index);
+        let log = &self.log;
+        imgui.push_font(self.font);
+        imgui.list_clipper(
+            received_count.saturating_sub(first_message_index),
+            None,
+            |imgui, range| {
+                let range = first_message_index + range.start..first_message_index + range.end;
+                log.for_each_in(range, |_, message| {
+                    let text = format!("({}) {}", message.crate_name, message.message);
+                    imgui.text_colored(severity_color(message.severity), &text);
+                });
+            },
+        );
+        imgui.pop_font();
 
-        imgui.set_scroll_y(99999.0);
+        // Scroll to the newest message
+        if received_count != self.last_received_count {
+            self.last_received_count = received_count;
+            imgui.set_scroll_y(99999.0);
+        }
 
         imgui.end_child();
+        imgui.separator();
+
+        let history = &self.history;
+        let history_index = &mut self.history_index;
+        let submitted = imgui.input_text_with_callback(
+            "##Input",
+            &mut self.input,
+            make_bitflags!(InputTextFlagBits::{EnterReturnsTrue | CallbackCompletion | CallbackHistory}),
+            |data| match data.event_flag() {
+                Some(InputTextFlagBits::CallbackCompletion) => complete(data),
+                Some(InputTextFlagBits::CallbackHistory) => {
+                    browse_history(data, history, history_index)
+                }
+                _ => {}
+            },
+        );
+
+        if submitted {
+            self.submit();
+            imgui.set_keyboard_focus_here(-1);
+        }
+
         imgui.end_window();
     }
+
+    fn submit(&mut self) {
+        let line = self.input.trim().to_string();
+        self.input.clear();
+        self.history_index = None;
+        if line.is_empty() {
+            return;
+        }
+
+        ze_info!("> {}", line);
+        self.history.retain(|entry| *entry != line);
+        self.history.push(line.clone());
+        if let Err(error) = console::execute(&line) {
+            ze_error!("{}: {}", line, error);
+        }
+    }
 }
 
-impl Sink for Console {
-    fn log(&self, message: &Message) {
-        self.messages.lock().push(message.clone());
+/// Complete the current text with command & cvar names, listing candidates if ambiguous
+fn complete(data: &mut InputTextCallbackData) {
+    let text = data.text().to_string();
+    let candidates = console::complete(text.trim_start());
+    match candidates.len() {
+        0 => {}
+        1 => data.set_text(&format!("{} ", candidates[0])),
+        _ => {
+            let mut common_prefix = candidates[0].clone();
+            for candidate in &candidates[1..] {
+                while !candidate.starts_with(&common_prefix) {
+                    common_prefix.pop();
+                }
+            }
+
+            data.set_text(&common_prefix);
+            ze_info!("Possible matches: {}", candidates.join(", "));
+        }
+    }
+}
+
+fn browse_history(
+    data: &mut InputTextCallbackData,
+    history: &[String],
+    history_index: &mut Option<usize>,
+) {
+    if history.is_empty() {
+        return;
+    }
+
+    *history_index = if data.is_event_key(Key::UpArrow) {
+        match *history_index {
+            None => Some(history.len() - 1),
+            Some(index) => Some(index.saturating_sub(1)),
+        }
+    } else if data.is_event_key(Key::DownArrow) {
+        match *history_index {
+            Some(index) if index + 1 < history.len() => Some(index + 1),
+            _ => None,
+        }
+    } else {
+        *history_index
+    };
+
+    match history_index {
+        Some(index) => data.set_text(&history[*index]),
+        None => data.set_text(""),
     }
 }
@@ -1,27 +1,43 @@
-﻿use crate::asset_explorer::AssetExplorer;
-use crate::console::Console;
+use crate::console::{Console, CONSOLE_ID};
 use crate::icon_manager::IconManager;
 use cfg_if::cfg_if;
 use enumflags2::make_bitflags;
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
-use std::str::FromStr;
 use std::sync::{Arc, Weak};
-use std::time::Instant;
-use url::Url;
+use std::time::{Duration, Instant};
 use ze_asset_server::{AssetServer, AssetServerProvider};
 use ze_asset_system::AssetManager;
-use ze_core::type_uuid::{TypeUuid, Uuid};
-use ze_core::ze_info;
+use ze_core::logger::RingBufferSink;
+use ze_core::type_uuid::TypeUuid;
+use ze_core::{ze_error, ze_info};
+use ze_ecs::world::World;
+use ze_editor_asset_browser::{AssetBrowser, ASSET_BROWSER_ID};
+use ze_editor_hierarchy::{Hierarchy, HIERARCHY_ID};
+use ze_editor_inspector::{Inspector, INSPECTOR_ID};
+use ze_editor_layout::{DockArea, LayoutManager, DEFAULT_LAYOUT};
+use ze_editor_log::{LogViewer, LOG_VIEWER_ID};
+use ze_editor_profiler::{Profiler, PROFILER_ID};
+use ze_editor_selection::SelectionService;
+use ze_editor_shader_inspector::{ShaderInspector, SHADER_INSPECTOR_ID};
+use ze_editor_tasks::TaskService;
+use ze_editor_undo::UndoService;
+use ze_editor_viewport::{SceneViewport, ViewportState, VIEWPORT_ID};
 use ze_filesystem::mount_points::StdMountPoint;
 use ze_filesystem::FileSystem;
 use ze_gfx::backend::*;
-use ze_gfx::{utils, PixelFormat};
-use ze_imgui::Context;
+use ze_gfx::null::NullBackend;
+use ze_gfx::PixelFormat;
+use ze_imgui::{Context, Font, FontRasterizer, Key, Theme};
 use ze_jobsystem::JobSystem;
-use ze_platform::{Message, Platform, Window, WindowFlagBits};
-use ze_render_graph::registry::PhysicalResourceTextureView;
-use ze_render_graph::{RenderGraph, TextureInfo};
+use ze_null_platform::NullPlatform;
+use ze_physics::PhysicsWorld;
+use ze_platform::{FileFilter, Message, Platform, PowerSource, Window, WindowFlagBits};
+use ze_render_graph::render_pass::RenderPassType;
+use ze_render_graph::FrameGraph;
+use ze_scene::scene::{SceneData, SceneSerializer};
+use ze_scene::transform::Transform;
 use ze_shader_compiler::ShaderCompiler;
 use ze_shader_system::ShaderManager;
 use ze_texture_asset::importer::TextureImporter;
@@ -39,17 +55,58 @@ use ze_d3d12_backend::backend::D3D12Backend;
 #[cfg(target_os = "macos")]
 use ze_macos_platform::MacOSPlatform;
 
+#[cfg(target_os = "linux")]
+use ze_vulkan_shader_compiler::VulkanShaderCompiler;
+
+#[cfg(target_os = "linux")]
+use ze_vulkan_backend::backend::VulkanBackend;
+
+#[cfg(feature = "sdl")]
+use ze_sdl_platform::SdlPlatform;
+
 #[cfg(target_os = "macos")]
 use ze_metal_backend::MetalBackend;
 
 #[cfg(target_os = "macos")]
 use ze_metal_shader_compiler::MetalShaderCompiler;
 
+/// Run without a display nor a GPU, using the null platform and the null graphics backend
+const HEADLESS_ARG: &str = "--headless";
+
+/// Use the SDL platform instead of the native one, to compare their behavior
+#[cfg(feature = "sdl")]
+const SDL_ARG: &str = "--sdl";
+
+/// Frames drawn after the last message before waiting for the next one, ImGui needs a few
+/// frames to settle hover states and layouts
+const IDLE_FRAME_COUNT: u32 = 3;
+
+/// Longest wait for a message while idle, keeps text cursors blinking and task progress updated
+const IDLE_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Longer wait used while on battery or when power saving is enabled
+const POWER_SAVING_IDLE_FRAME_DURATION: Duration = Duration::from_millis(500);
+
+/// Delay between two queries of the power status
+const POWER_STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+const TEXTURE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+const IMPORT_FILTERS: &[FileFilter] = &[FileFilter {
+    name: "Textures",
+    extensions: TEXTURE_EXTENSIONS,
+}];
+
+const SCENE_FILTERS: &[FileFilter] = &[FileFilter {
+    name: "Scenes",
+    extensions: &["scene"],
+}];
+
 pub struct EditorApplication {
     platform: Arc<dyn Platform>,
     backend: Arc<dyn Backend>,
     device: Arc<dyn Device>,
-    _jobsystem: Arc<JobSystem>,
+    jobsystem: Arc<JobSystem>,
     filesystem: Arc<FileSystem>,
     _shader_compiler: Arc<dyn ShaderCompiler>,
     shader_manager: Arc<ShaderManager>,
@@ -57,19 +114,19 @@ pub struct EditorApplication {
     main_window_swapchain: Option<Arc<SwapChain>>,
     main_window_swapchain_rtvs: Vec<Arc<RenderTargetView>>,
     imgui: Box<Context>,
+    monospace_font: Font,
     icon_manager: Arc<IconManager>,
+    log: Arc<RingBufferSink>,
 }
 
 impl EditorApplication {
-    pub fn new() -> Self {
-        cfg_if! {
-            if #[cfg(target_os = "windows")] {
-                let platform = WindowsPlatform::new();
-            } else if #[cfg(target_os = "macos")] {
-                let platform = MacOSPlatform::new();
-            } else {
-                panic!("unsupported platform")
-            }
+    pub fn new(log: Arc<RingBufferSink>) -> Self {
+        let headless = env::args().any(|arg| arg == HEADLESS_ARG);
+        let platform: Arc<dyn Platform> = if headless {
+            ze_info!("Running headless");
+            NullPlatform::new()
+        } else {
+            native_platform()
         };
 
         let jobsystem = JobSystem::new(JobSystem::cpu_thread_count());
@@ -80,14 +137,10 @@ impl EditorApplication {
             Path::new(&env::current_dir().unwrap()),
         ));
 
-        cfg_if! {
-            if #[cfg(target_os = "windows")] {
-                let backend = D3D12Backend::new().expect("Failed to create graphics backend");
-            } else if #[cfg(target_os = "macos")] {
-                let backend = MetalBackend::new().expect("Failed to create graphics backend");
-            } else {
-                panic!("unsupported platform")
-            }
+        let backend: Arc<dyn Backend> = if headless {
+            Arc::new(NullBackend)
+        } else {
+            native_backend()
         };
 
         let device = backend
@@ -99,6 +152,8 @@ impl EditorApplication {
                 let shader_compiler = D3D12ShaderCompiler::new(filesystem.clone());
             } else if #[cfg(target_os = "macos")] {
                 let shader_compiler = MetalShaderCompiler::new();
+            } else if #[cfg(target_os = "linux")] {
+                let shader_compiler = VulkanShaderCompiler::new(filesystem.clone());
             } else {
                 panic!("unsupported platform")
             }
@@ -108,7 +163,7 @@ impl EditorApplication {
             ShaderManager::new(device.clone(), jobsystem.clone(), shader_compiler.clone());
         shader_manager.search_shaders(
             &filesystem,
-            &Url::from_str("vfs:///assets/shaders").unwrap(),
+            &ze_filesystem::path::Path::parse("/main/assets/shaders").unwrap(),
         );
 
         let screen_0_bounds = platform.monitor(0).bounds;
@@ -123,18 +178,43 @@ impl EditorApplication {
             )
             .unwrap();
 
-        let imgui = Context::new(
+        let mut imgui = Context::new(
             device.clone(),
             shader_manager.clone(),
             platform.clone(),
             main_window.clone(),
+            FontRasterizer::default(),
+        );
+
+        if let Err(error) = imgui.add_font_from_file(
+            &filesystem,
+            &ze_filesystem::path::Path::parse("/main/assets/Inter-SemiBold.ttf").unwrap(),
+            16.0,
+        ) {
+            ze_error!("Failed to load editor font: {}", error);
+        }
+        let monospace_font = imgui.add_embedded_font();
+
+        // Projects can override the default editor theme, which follows the system one
+        imgui.follow_system_theme();
+        let theme_path = ze_filesystem::path::Path::parse("/main/editor.theme").unwrap();
+        if filesystem.exists(&theme_path) {
+            match Theme::load(&filesystem, &theme_path) {
+                Ok(theme) => imgui.set_theme(&theme),
+                Err(error) => ze_error!("Failed to load editor theme: {}", error),
+            }
+        }
+
+        imgui.set_ini_settings_directory(
+            filesystem.clone(),
+            ze_filesystem::path::Path::parse("/main/asset-cache/editor").unwrap(),
         );
 
         Self {
             platform,
             backend,
             device: device.clone(),
-            _jobsystem: jobsystem,
+            jobsystem,
             filesystem: filesystem.clone(),
             _shader_compiler: shader_compiler,
             shader_manager,
@@ -142,32 +222,40 @@ impl EditorApplication {
             main_window_swapchain: None,
             main_window_swapchain_rtvs: vec![],
             imgui,
+            monospace_font,
             icon_manager: Arc::new(IconManager::new(
                 device,
                 filesystem,
-                Url::from_str("vfs://main/assets/textures/editor/icons/").unwrap(),
+                ze_filesystem::path::Path::parse("/main/assets/textures/editor/icons/").unwrap(),
             )),
+            log,
         }
     }
 
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn run(&mut self) {
         self.update_main_window_swapchain();
 
         let mut running = true;
         let mut previous = Instant::now();
+        let mut idle_frames = 0;
+        let mut saving_power = is_saving_power(self.platform.as_ref());
+        let mut power_status_time = Instant::now();
 
-        let mut main_registry = ze_render_graph::registry::PhysicalResourceRegistry::new();
+        // Created first so progress of the initial asset import is reported
+        let mut tasks = TaskService::new(&self.jobsystem);
 
         let asset_server = Arc::new(
             AssetServer::new(
                 self.filesystem.clone(),
-                vec![Url::from_str("vfs://main/assets").unwrap()],
-                Url::from_str("vfs://main/asset-cache").unwrap(),
+                self.jobsystem.clone(),
+                vec![ze_filesystem::path::Path::parse("/main/assets").unwrap()],
+                ze_filesystem::path::Path::parse("/main/asset-cache").unwrap(),
             )
             .unwrap(),
         );
 
-        asset_server.add_importer(&["png", "jpg", "jpeg"], TextureImporter::default());
+        asset_server.add_importer(TEXTURE_EXTENSIONS, TextureImporter::default());
 
         let asset_manager = Arc::new(AssetManager::default());
         asset_manager.add_provider(AssetServerProvider::new(asset_server.clone()), 1);
@@ -185,40 +273,164 @@ impl EditorApplication {
             ze_texture_editor::EditorFactory::new(asset_manager.clone()),
         );
 
-        let mut asset_explorer = AssetExplorer::new(
+        let selection = Arc::new(SelectionService::default());
+        let undo = Arc::new(UndoService::default());
+
+        let mut asset_browser = AssetBrowser::new(
             asset_server.clone(),
-            self.icon_manager.clone(),
             self.filesystem.clone(),
             asset_editor_manager.clone(),
+            selection.clone(),
+            ze_filesystem::path::Path::parse("/main/assets").unwrap(),
+            self.icon_manager
+                .icon("icons8-folder-64")
+                .map(|icon| icon.srv.clone()),
+            self.icon_manager
+                .icon("icons8-file-64")
+                .map(|icon| icon.srv.clone()),
+        );
+        asset_browser.add_type_filter("Texture", ze_texture_asset::Texture::type_uuid());
+
+        let mut hierarchy = Hierarchy::new(selection.clone());
+        let mut inspector = Inspector::new(selection.clone());
+        let mut world = World::default();
+        let mut scene_serializer = SceneSerializer::default();
+        scene_serializer.register::<Transform>();
+        let mut physics = PhysicsWorld::default();
+        let mut console = Console::new(self.log.clone(), self.monospace_font);
+        let mut log_viewer = LogViewer::new(self.log.clone());
+        let mut profiler = Profiler::new();
+        let mut shader_inspector = ShaderInspector::new(self.shader_manager.clone());
+        let mut viewport = SceneViewport::new(self.device.clone(), selection.clone(), undo.clone());
+
+        let mut layout = LayoutManager::new(
+            self.filesystem.clone(),
+            ze_filesystem::path::Path::parse("/main/layouts").unwrap(),
         );
+        for panel in [
+            VIEWPORT_ID,
+            HIERARCHY_ID,
+            INSPECTOR_ID,
+            ASSET_BROWSER_ID,
+            CONSOLE_ID,
+            LOG_VIEWER_ID,
+            PROFILER_ID,
+            SHADER_INSPECTOR_ID,
+        ] {
+            layout.add_panel(panel);
+        }
+        layout.add_preset(
+            DEFAULT_LAYOUT,
+            &[
+                (VIEWPORT_ID, DockArea::Center),
+                (HIERARCHY_ID, DockArea::Left),
+                (INSPECTOR_ID, DockArea::Right),
+                (ASSET_BROWSER_ID, DockArea::Bottom),
+                (CONSOLE_ID, DockArea::Bottom),
+            ],
+        );
+        layout.add_preset(
+            "Animation",
+            &[
+                (VIEWPORT_ID, DockArea::Center),
+                (HIERARCHY_ID, DockArea::Left),
+                (INSPECTOR_ID, DockArea::Right),
+                (ASSET_BROWSER_ID, DockArea::Bottom),
+            ],
+        );
+        layout.add_preset(
+            "Rendering",
+            &[
+                (VIEWPORT_ID, DockArea::Center),
+                (SHADER_INSPECTOR_ID, DockArea::Left),
+                (INSPECTOR_ID, DockArea::Right),
+                (LOG_VIEWER_ID, DockArea::Bottom),
+                (PROFILER_ID, DockArea::Bottom),
+                (CONSOLE_ID, DockArea::Bottom),
+            ],
+        );
+        if let Err(error) = layout.load(DEFAULT_LAYOUT) {
+            ze_error!("Failed to load the default layout: {}", error);
+        }
 
-        let console = Console::new();
+        // File dialogs waiting for the user
+        let mut import_dialog = None;
+        let mut save_scene_dialog = None;
 
         while running {
             puffin::GlobalProfiler::lock().new_frame();
             puffin::profile_scope!("Main loop");
 
+            if power_status_time.elapsed() >= POWER_STATUS_INTERVAL {
+                saving_power = is_saving_power(self.platform.as_ref());
+                power_status_time = Instant::now();
+            }
+
+            // Nothing is visible while minimized and nothing animates without inputs, wait for
+            // the next message instead of drawing the same frame again
+            let mut next_message = if self.main_window.is_minimized() {
+                self.platform.wait_event(None)
+            } else if idle_frames >= IDLE_FRAME_COUNT {
+                self.platform.wait_event(Some(if saving_power {
+                    POWER_SAVING_IDLE_FRAME_DURATION
+                } else {
+                    IDLE_FRAME_DURATION
+                }))
+            } else {
+                self.platform.poll_event()
+            };
+
             let delta_time = previous.elapsed().as_secs_f32();
             previous = Instant::now();
 
-            while let Some(message) = self.platform.poll_event() {
+            while let Some(message) = next_message.take().or_else(|| self.platform.poll_event()) {
+                idle_frames = 0;
                 self.imgui.send_platform_message(&message);
                 match message {
-                    Message::WindowClosed(event_window) => {
-                        if Weak::ptr_eq(&event_window, &Arc::downgrade(&self.main_window)) {
-                            running = false;
-                        }
+                    Message::WindowCloseRequested(event_window)
+                        if Weak::ptr_eq(&event_window, &Arc::downgrade(&self.main_window)) =>
+                    {
+                        self.main_window.close();
+                        running = false;
+                    }
+                    Message::WindowResized(event_window, _, _)
+                        if Weak::ptr_eq(&event_window, &Arc::downgrade(&self.main_window)) =>
+                    {
+                        self.update_main_window_swapchain();
                     }
-                    Message::WindowResized(event_window, _, _) => {
-                        if Weak::ptr_eq(&event_window, &Arc::downgrade(&self.main_window)) {
-                            let _ = main_registry.remove_resource("backbuffer");
-                            self.update_main_window_swapchain();
+                    Message::FilesDropped(_, files, _) => asset_browser.import_files(&files),
+                    Message::FileDialogClosed(id, paths) => {
+                        if import_dialog == Some(id) {
+                            import_dialog = None;
+                            asset_browser.import_files(&paths);
+                        } else if save_scene_dialog == Some(id) {
+                            save_scene_dialog = None;
+                            if let Some(path) = paths.first() {
+                                save_scene(&scene_serializer, &world, path);
+                            }
                         }
                     }
+                    Message::MouseMotionRaw(delta_x, delta_y) => {
+                        viewport.add_raw_mouse_motion(delta_x, delta_y)
+                    }
                     _ => {}
                 }
             }
 
+            if self.main_window.is_minimized() {
+                continue;
+            }
+
+            if !self.imgui.is_any_input_down() {
+                idle_frames += 1;
+            }
+
+            if layout.apply_pending(&mut self.imgui) {
+                if let Some(state) = layout.panel_state::<ViewportState>(VIEWPORT_ID) {
+                    viewport.set_state(&state);
+                }
+            }
+
             self.device.begin_frame();
 
             self.imgui.begin_frame(
@@ -230,8 +442,78 @@ impl EditorApplication {
             let main_dockspace_id = self
                 .imgui
                 .dock_space_over_viewport(self.imgui.main_viewport());
+            layout.build_pending_dock(&mut self.imgui, main_dockspace_id);
+
+            if self.imgui.is_key_down(Key::LeftCtrl) {
+                if self.imgui.is_key_pressed(Key::Z, true) {
+                    undo.undo(&mut world);
+                } else if self.imgui.is_key_pressed(Key::Y, true) {
+                    undo.redo(&mut world);
+                }
+            }
 
             if self.imgui.begin_main_menu_bar() {
+                if self.imgui.begin_menu("File", true) {
+                    if self
+                        .imgui
+                        .menu_item("Import Asset...", None, false, import_dialog.is_none())
+                    {
+                        import_dialog = Some(self.platform.open_file_dialog(IMPORT_FILTERS, true));
+                    }
+                    if self.imgui.menu_item(
+                        "Save Scene As...",
+                        None,
+                        false,
+                        save_scene_dialog.is_none(),
+                    ) {
+                        save_scene_dialog = Some(
+                            self.platform
+                                .save_file_dialog(SCENE_FILTERS, "Untitled.scene"),
+                        );
+                    }
+                    self.imgui.end_menu();
+                }
+
+                if self.imgui.begin_menu("Edit", true) {
+                    let undo_name = undo.undo_name();
+                    let redo_name = undo.redo_name();
+                    if self.imgui.menu_item(
+                        &format!("Undo {}", undo_name.as_deref().unwrap_or_default()),
+                        Some("Ctrl+Z"),
+                        false,
+                        undo_name.is_some(),
+                    ) {
+                        undo.undo(&mut world);
+                    }
+                    if self.imgui.menu_item(
+                        &format!("Redo {}", redo_name.as_deref().unwrap_or_default()),
+                        Some("Ctrl+Y"),
+                        false,
+                        redo_name.is_some(),
+                    ) {
+                        undo.redo(&mut world);
+                    }
+                    self.imgui.end_menu();
+                }
+
+                if self.imgui.begin_menu("Debug", true) {
+                    let overlay_enabled = self.imgui.is_performance_overlay_enabled();
+                    if self
+                        .imgui
+                        .menu_item("Performance Overlay", None, overlay_enabled, true)
+                    {
+                        self.imgui.set_performance_overlay_enabled(!overlay_enabled);
+                    }
+                    self.imgui.end_menu();
+                }
+
+                if let Some(name) = layout.draw_menu(&mut self.imgui) {
+                    layout.set_panel_state(VIEWPORT_ID, &viewport.state());
+                    if let Err(error) = layout.save(&name, &self.imgui) {
+                        ze_error!("Failed to save layout \"{}\": {}", name, error);
+                    }
+                }
+
                 self.imgui.text(&format!(
                     "{} | FPS: {}",
                     self.backend.name(),
@@ -242,11 +524,61 @@ impl EditorApplication {
 
             {
                 puffin::profile_scope!("Draw editor");
-                asset_explorer.draw(&mut self.imgui);
+                if layout.is_open(ASSET_BROWSER_ID) {
+                    asset_browser.draw(&mut self.imgui);
+                }
+                if layout.is_open(HIERARCHY_ID) {
+                    hierarchy.draw(&mut self.imgui, &mut world);
+                }
+                if layout.is_open(INSPECTOR_ID) {
+                    inspector.draw(&mut self.imgui);
+                }
                 asset_editor_manager.draw_editors(&mut self.imgui, main_dockspace_id);
-                console.draw(&mut self.imgui);
+                if layout.is_open(CONSOLE_ID) {
+                    console.draw(&mut self.imgui);
+                }
+                if layout.is_open(LOG_VIEWER_ID) {
+                    log_viewer.draw(&mut self.imgui);
+                }
+                if layout.is_open(PROFILER_ID) {
+                    profiler.draw(&mut self.imgui);
+                }
+                if layout.is_open(SHADER_INSPECTOR_ID) {
+                    shader_inspector.draw(&mut self.imgui);
+                }
+                if layout.is_open(VIEWPORT_ID) {
+                    // The simulation doesn't run in the editor, physics is only synchronized to
+                    // display the colliders
+                    physics.sync(&world);
+                    let debug_draw = viewport.debug_draw_mut();
+                    debug_draw.clear();
+                    physics.debug_draw(debug_draw);
+                    viewport.draw(&mut self.imgui, &world);
+                }
+                tasks.draw(&mut self.imgui);
             }
 
+            self.imgui
+                .add_performance_stat("Job workers", self.jobsystem.worker_count());
+            self.imgui
+                .add_performance_stat("Pending jobs", self.jobsystem.pending_job_count());
+            if self.imgui.is_performance_overlay_enabled() {
+                let memory_stats = self.device.memory_stats();
+                for (name, segment) in [
+                    ("GPU local memory", memory_stats.local),
+                    ("GPU non-local memory", memory_stats.non_local),
+                ] {
+                    self.imgui.add_performance_stat(
+                        name,
+                        format!(
+                            "{} / {} MiB ({} allocations)",
+                            segment.usage_bytes / (1024 * 1024),
+                            segment.budget_bytes / (1024 * 1024),
+                            segment.allocation_count
+                        ),
+                    );
+                }
+            }
             self.imgui.end_frame();
             // Render
 
@@ -258,46 +590,39 @@ impl EditorApplication {
                 .swapchain_backbuffer(swapchain, backbuffer_index)
                 .unwrap();
 
-            main_registry.insert_or_update_existing_texture(
-                "backbuffer",
-                backbuffer.clone(),
-                PhysicalResourceTextureView::RTV(
-                    self.main_window_swapchain_rtvs[backbuffer_index as usize].clone(),
-                ),
-            );
-
             let mut main_cmd_list = self
                 .device
                 .create_command_list(QueueType::Graphics)
                 .unwrap();
 
-            let mut render_graph = RenderGraph::new(self.device.clone(), &mut main_registry);
-            render_graph.add_graphics_pass(
-                "ui",
-                |render_graph, render_pass| {
-                    render_graph.add_pass_color_output(
-                        render_pass,
-                        "backbuffer",
-                        TextureInfo::default(),
-                    );
+            if layout.is_open(VIEWPORT_ID) {
+                viewport.render(&mut main_cmd_list);
+            }
+
+            let mut render_graph = FrameGraph::new(self.device.clone());
+            let backbuffer = render_graph.import_external_texture(backbuffer, "Backbuffer");
+            render_graph.add_pass(
+                "UI",
+                RenderPassType::Graphics,
+                |builder| {
+                    let _ = builder.write(backbuffer);
                 },
-                |_, cmd_list| {
+                |_, _, cmd_list| {
                     self.imgui
                         .draw_viewport(cmd_list, self.imgui.main_viewport_mut());
                 },
             );
 
             {
-                puffin::profile_scope!("Render Graph compilation");
-                render_graph.compile("backbuffer");
-            }
+                let mut render_graph = {
+                    puffin::profile_scope!("Render Graph compilation");
+                    render_graph.compile(backbuffer)
+                };
 
-            {
                 puffin::profile_scope!("Render Graph execution");
                 render_graph.execute(&mut main_cmd_list);
             }
 
-            self.imgui.draw_non_main_viewports(&mut main_cmd_list);
             {
                 puffin::profile_scope!("Submit");
                 self.device
@@ -306,14 +631,21 @@ impl EditorApplication {
 
             {
                 puffin::profile_scope!("Present");
+                self.imgui.render_platform_windows();
                 self.device.present(swapchain);
-                self.imgui.present();
             }
 
             self.device.end_frame();
         }
+
+        layout.set_panel_state(VIEWPORT_ID, &viewport.state());
+        let current_layout = layout.current().to_string();
+        if let Err(error) = layout.save(&current_layout, &self.imgui) {
+            ze_error!("Failed to save layout \"{}\": {}", current_layout, error);
+        }
     }
 
+    #[allow(clippy::arc_with_non_send_sync)]
     fn update_main_window_swapchain(&mut self) {
         self.device.wait_idle();
         self.main_window_swapchain_rtvs.clear();
@@ -335,6 +667,7 @@ impl EditorApplication {
                             TextureUsageFlagBits::RenderTarget,
                         ),
                         window_handle: self.main_window.handle(),
+                        display_handle: self.main_window.display_handle(),
                     },
                     old_swapchain,
                 )
@@ -358,3 +691,60 @@ impl EditorApplication {
         self.main_window_swapchain = Some(swapchain);
     }
 }
+
+/// Write the entities of `world` as a YAML scene, the editor has no prefab yet
+/// Frames are drawn less often while on battery or when the user asked to save power
+fn is_saving_power(platform: &dyn Platform) -> bool {
+    let status = platform.power_status();
+    status.source == PowerSource::Battery || status.power_saver
+}
+
+fn save_scene(serializer: &SceneSerializer, world: &World, path: &Path) {
+    let result = serializer
+        .serialize(world, &HashMap::<String, SceneData>::new())
+        .and_then(|scene| scene.to_yaml())
+        .map_err(|error| error.to_string())
+        .and_then(|yaml| std::fs::write(path, yaml).map_err(|error| error.to_string()));
+
+    match result {
+        Ok(()) => ze_info!("Saved scene {}", path.display()),
+        Err(error) => ze_error!("Failed to save scene {}: {}", path.display(), error),
+    }
+}
+
+fn native_platform() -> Arc<dyn Platform> {
+    #[cfg(feature = "sdl")]
+    if env::args().any(|arg| arg == SDL_ARG) {
+        ze_info!("Using the SDL platform");
+        return SdlPlatform::new().expect("Failed to initialize SDL");
+    }
+
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            WindowsPlatform::new()
+        } else if #[cfg(target_os = "macos")] {
+            MacOSPlatform::new()
+        } else if #[cfg(target_os = "linux")] {
+            ze_linux_platform::new_platform()
+                .expect("Failed to connect to the X server or the Wayland compositor")
+        } else if #[cfg(feature = "sdl")] {
+            SdlPlatform::new().expect("Failed to initialize SDL")
+        } else {
+            panic!("unsupported platform")
+        }
+    }
+}
+
+fn native_backend() -> Arc<dyn Backend> {
+    cfg_if! {
+        if #[cfg(target_os = "windows")] {
+            D3D12Backend::new().expect("Failed to create graphics backend")
+        } else if #[cfg(target_os = "macos")] {
+            MetalBackend::new().expect("Failed to create graphics backend")
+        } else if #[cfg(target_os = "linux")] {
+            VulkanBackend::new().expect("Failed to create graphics backend")
+        } else {
+            panic!("unsupported platform")
+        }
+    }
+}
@@ -9,7 +9,7 @@ use ze_gfx::utils::copy_data_to_texture;
 use ze_gfx::PixelFormat;
 
 pub struct Icon {
-    pub texture: Arc<Texture>,
+    _texture: Arc<Texture>,
     pub srv: Arc<ShaderResourceView>,
 }
 
@@ -37,7 +37,7 @@ impl IconManager {
         } else {
             drop(icons);
             let path = self.icon_root_dir.clone();
-            let path = path.join(&format!("{}{}", name, ".png"));
+            let path = path.join(format!("{}{}", name, ".png"));
             if let Ok(mut file) = self.filesystem.read(&path) {
                 let mut data = vec![];
                 file.read_to_end(&mut data).unwrap();
@@ -50,6 +50,7 @@ impl IconManager {
                                 width: image.width(),
                                 height: image.height(),
                                 depth: 1,
+                                array_size: 1,
                                 mip_levels: 1,
                                 format: PixelFormat::R8G8B8A8Unorm,
                                 sample_desc: Default::default(),
@@ -83,7 +84,7 @@ impl IconManager {
                     image.as_bytes(),
                     texture.desc.width,
                     texture.desc.height,
-                    4,
+                    texture.desc.width as usize * 4,
                     &texture,
                     ResourceState::Common,
                 )
@@ -92,7 +93,7 @@ impl IconManager {
                 let mut icons = self.icons.write();
 
                 let icon = Arc::new(Icon {
-                    texture,
+                    _texture: texture,
                     srv: texture_srv,
                 });
                 icons.insert(name.to_string(), icon.clone());
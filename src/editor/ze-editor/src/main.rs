@@ -3,7 +3,7 @@ extern crate core;
 use crate::editor::EditorApplication;
 use std::fs;
 use std::fs::OpenOptions;
-use ze_core::logger::{FileSink, StdoutSink};
+use ze_core::logger::{FileSink, RingBufferSink, StdoutSink};
 use ze_core::{logger, thread};
 
 #[cfg(target_os = "windows")]
@@ -18,17 +18,18 @@ fn main() {
         OpenOptions::new()
             .create(true)
             .write(true)
+            .truncate(true)
             .open("logs/editor.log")
             .unwrap(),
     ));
 
-    let _server = puffin_http::Server::new("127.0.0.1:8585").unwrap();
+    let log = RingBufferSink::new(4096);
+    logger::register_sink(log.clone());
 
-    let mut editor = EditorApplication::new();
+    let mut editor = EditorApplication::new(log);
     editor.run();
 }
 
-mod asset_explorer;
 mod console;
 mod editor;
 mod icon_manager;
@@ -1,19 +1,47 @@
 use enumflags2::make_bitflags;
 use std::sync::Arc;
 use ze_imgui::ze_imgui_sys::ImVec2;
-use ze_imgui::{Context, TableColumnFlagBits, TableColumnFlags, TableFlagBits};
+use ze_imgui::{
+    Context, InputTextFlags, ItemFlagBits, SelectableFlags, TableColumnFlagBits, TableColumnFlags,
+    TableFlagBits,
+};
 use ze_reflection::{
     MetaAttributeValue, PrimitiveType, Reflectable, TypeDataDescription, TypeDescription,
 };
 
 /// Draw a property editor using reflection
+/// Fields with the `read_only` attribute are displayed but can't be edited
 /// Returns whether or not something has changed
 pub fn draw_property_editor<T: Reflectable>(imgui: &mut Context, object: &mut T) -> bool {
+    // SAFETY: `object` is a valid `T`
+    unsafe {
+        draw_multi_property_editor(imgui, &T::type_desc(), &[object as *mut _ as *mut u8], None)
+    }
+}
+
+/// Draw a property editor editing multiple objects sharing the same type
+/// Fields with different values are displayed as mixed, editing them set the value of every object
+/// If `default` is specified, fields that differs from it can be reverted to their default value
+/// Returns whether or not something has changed
+///
+/// # Safety
+///
+/// `objects` and `default` must point to valid objects of the type described by `type_desc`
+pub unsafe fn draw_multi_property_editor(
+    imgui: &mut Context,
+    type_desc: &Arc<TypeDescription>,
+    objects: &[*mut u8],
+    default: Option<*const u8>,
+) -> bool {
+    if objects.is_empty() {
+        return false;
+    }
+
     imgui.begin_table(
         "PropertiesTable",
-        2,
+        if default.is_some() { 3 } else { 2 },
         make_bitflags! { TableFlagBits::{Resizable | NoBordersInBodyUntilResize} },
-        imgui.available_content_region(),
+        ImVec2::default(),
     );
 
     imgui.table_setup_column(
@@ -28,30 +56,70 @@ pub fn draw_property_editor<T: Reflectable>(imgui: &mut Context, object: &mut T)
         TableColumnFlags::from_flag(TableColumnFlagBits::WidthStretch),
     );
 
-    let modified =
-        draw_property_editor_internal(imgui, &T::type_desc(), object as *mut _ as *mut u8, "obj");
+    if default.is_some() {
+        imgui.table_setup_column(
+            "Revert",
+            60.0,
+            TableColumnFlags::from_flag(TableColumnFlagBits::WidthFixed),
+        );
+    }
+
+    let modified = draw_property_editor_internal(imgui, type_desc, objects, default, "obj");
 
     imgui.end_table();
 
     modified
 }
 
-fn draw_property_editor_internal(
+unsafe fn draw_property_editor_internal(
     imgui: &mut Context,
     type_desc: &Arc<TypeDescription>,
-    value: *mut u8,
+    values: &[*mut u8],
+    default: Option<*const u8>,
     label: &str,
 ) -> bool {
+    let mixed = values[1..]
+        .iter()
+        .any(|value| !values_equal(type_desc, values[0], *value));
+
     match type_desc.data() {
-        TypeDataDescription::Primitive(primitive_type) => match primitive_type {
-            PrimitiveType::Bool => {
-                let value = unsafe { (value as *mut bool).as_mut().unwrap_unchecked() };
-                imgui.checkbox(label, value)
+        TypeDataDescription::Primitive(primitive_type) => {
+            if mixed {
+                imgui.push_item_flag(ItemFlagBits::MixedValue, true);
             }
-            _ => {
-                todo!()
+
+            let modified = match primitive_type {
+                PrimitiveType::Bool => {
+                    let value = (values[0] as *mut bool).as_mut().unwrap_unchecked();
+                    imgui.checkbox(label, value)
+                }
+                PrimitiveType::I32 => {
+                    let value = (values[0] as *mut i32).as_mut().unwrap_unchecked();
+                    imgui.input_int(label, value, 1, None, InputTextFlags::empty())
+                }
+                PrimitiveType::F32 => {
+                    let value = (values[0] as *mut f32).as_mut().unwrap_unchecked();
+                    imgui.input_float(label, value, 0.0, None, "%.3f", InputTextFlags::empty())
+                }
+                _ => {
+                    // TODO: Use input widgets for the remaining primitives
+                    imgui.text(&primitive_to_string(primitive_type, values[0]));
+                    false
+                }
+            };
+
+            if mixed {
+                imgui.pop_item_flag();
+            }
+
+            if modified {
+                for value in &values[1..] {
+                    copy_value(type_desc, values[0], *value);
+                }
             }
-        },
+
+            modified
+        }
         TypeDataDescription::Struct(struct_desc) => {
             let mut field_modified = false;
 
@@ -72,11 +140,39 @@ fn draw_property_editor_internal(
                 );
 
                 imgui.text(&display_name);
-                imgui.table_next_column();
+
+                let field_values = values
+                    .iter()
+                    .map(|value| value.add(field.offset_in_bytes()))
+                    .collect::<Vec<_>>();
+                let field_default = default.map(|default| default.add(field.offset_in_bytes()));
+                let read_only = field.attributes().has_attribute("read_only");
+
+                // Revert button is drawn first as the field may span multiple rows
+                if let Some(field_default) = field_default.filter(|_| !read_only) {
+                    imgui.table_set_column_index(2);
+                    if field_values
+                        .iter()
+                        .any(|value| !values_equal(field.ty(), field_default, *value))
+                    {
+                        imgui.push_id_str(field.name());
+                        if imgui.button("Revert", ImVec2::default()) {
+                            for value in &field_values {
+                                copy_value(field.ty(), field_default, *value);
+                            }
+                            field_modified = true;
+                        }
+                        imgui.pop_id();
+                    }
+                }
+
+                imgui.table_set_column_index(1);
+                let _disabled = imgui.disabled_scope(read_only);
                 if draw_property_editor_internal(
                     imgui,
                     field.ty(),
-                    unsafe { value.add(field.offset_in_bytes()) },
+                    &field_values,
+                    field_default,
                     &format!("##{}", field.name()),
                 ) {
                     field_modified = true;
@@ -86,20 +182,103 @@ fn draw_property_editor_internal(
             field_modified
         }
         TypeDataDescription::Enum(enum_desc) => {
-            let current_variant = enum_desc.variant_of_ptr(value).unwrap();
-            let mut modified = false;
-            if imgui.begin_combo(label, current_variant.name()) {
-                for variant in enum_desc.variants() {
-                    if imgui.selectable(variant.name(), ImVec2::default()) {
-                        enum_desc.set_variant_of_ptr(value, variant.discriminant());
-                        modified = true;
+            let current_variant = enum_desc.variant_of_ptr(values[0]).unwrap();
+            let preview_value = if mixed {
+                "Mixed"
+            } else {
+                current_variant.name()
+            };
+
+            imgui
+                .combo_items(label, preview_value, |imgui| {
+                    let mut modified = false;
+                    for variant in enum_desc.variants() {
+                        let selected =
+                            !mixed && variant.discriminant() == current_variant.discriminant();
+                        if imgui.selectable_ex(
+                            variant.name(),
+                            selected,
+                            SelectableFlags::empty(),
+                            ImVec2::default(),
+                        ) {
+                            for value in values {
+                                enum_desc.set_variant_of_ptr(*value, variant.discriminant());
+                            }
+                            modified = true;
+                        }
                     }
-                }
+                    modified
+                })
+                .unwrap_or(false)
+        }
+    }
+}
 
-                imgui.end_combo();
-            }
+/// Compare two values using their reflection data
+unsafe fn values_equal(type_desc: &Arc<TypeDescription>, a: *const u8, b: *const u8) -> bool {
+    match type_desc.data() {
+        TypeDataDescription::Primitive(_) => {
+            let size = type_desc.size_in_bytes();
+            std::slice::from_raw_parts(a, size) == std::slice::from_raw_parts(b, size)
+        }
+        TypeDataDescription::Struct(struct_desc) => struct_desc.fields().iter().all(|field| {
+            values_equal(
+                field.ty(),
+                a.add(field.offset_in_bytes()),
+                b.add(field.offset_in_bytes()),
+            )
+        }),
+        TypeDataDescription::Enum(enum_desc) => {
+            enum_desc
+                .variant_of_ptr(a)
+                .map(|variant| variant.discriminant())
+                == enum_desc
+                    .variant_of_ptr(b)
+                    .map(|variant| variant.discriminant())
+        }
+    }
+}
 
-            modified
+/// Copy `src` to `dst` using their reflection data
+unsafe fn copy_value(type_desc: &Arc<TypeDescription>, src: *const u8, dst: *mut u8) {
+    match type_desc.data() {
+        TypeDataDescription::Primitive(_) => {
+            std::ptr::copy(src, dst, type_desc.size_in_bytes());
+        }
+        TypeDataDescription::Struct(struct_desc) => {
+            for field in struct_desc.fields() {
+                copy_value(
+                    field.ty(),
+                    src.add(field.offset_in_bytes()),
+                    dst.add(field.offset_in_bytes()),
+                );
+            }
+        }
+        TypeDataDescription::Enum(enum_desc) => {
+            if let Some(variant) = enum_desc.variant_of_ptr(src) {
+                enum_desc.set_variant_of_ptr(dst, variant.discriminant());
+            }
         }
     }
 }
+
+unsafe fn primitive_to_string(primitive_type: &PrimitiveType, value: *const u8) -> String {
+    match primitive_type {
+        PrimitiveType::Char => (*(value as *const char)).to_string(),
+        PrimitiveType::Bool => (*(value as *const bool)).to_string(),
+        PrimitiveType::U8 => (*value).to_string(),
+        PrimitiveType::U16 => (*(value as *const u16)).to_string(),
+        PrimitiveType::U32 => (*(value as *const u32)).to_string(),
+        PrimitiveType::U64 => (*(value as *const u64)).to_string(),
+        PrimitiveType::U128 => (*(value as *const u128)).to_string(),
+        PrimitiveType::USize => (*(value as *const usize)).to_string(),
+        PrimitiveType::I8 => (*(value as *const i8)).to_string(),
+        PrimitiveType::I16 => (*(value as *const i16)).to_string(),
+        PrimitiveType::I32 => (*(value as *const i32)).to_string(),
+        PrimitiveType::I64 => (*(value as *const i64)).to_string(),
+        PrimitiveType::I128 => (*(value as *const i128)).to_string(),
+        PrimitiveType::ISize => (*(value as *const isize)).to_string(),
+        PrimitiveType::F32 => (*(value as *const f32)).to_string(),
+        PrimitiveType::F64 => (*(value as *const f64)).to_string(),
+    }
+}
@@ -93,6 +93,7 @@ impl AssetEditor for Editor {
                 }
 
                 if let Some(default_srv) = texture.default_srv() {
+                    let default_srv = imgui.frame_texture(default_srv.clone());
                     imgui.image_centered(
                         default_srv,
                         ImVec2::new(texture.width() as f32, texture.height() as f32),
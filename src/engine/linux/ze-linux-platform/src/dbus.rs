@@ -0,0 +1,290 @@
+//! Bindings to the few libdbus functions used to talk to the desktop portal, loaded at runtime
+//! as the library may not be installed
+
+#![allow(non_camel_case_types)]
+
+use dlib::dlopen_external_library;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::ptr::{null, null_mut};
+
+pub type dbus_bool_t = c_uint;
+
+pub enum DBusConnection {}
+pub enum DBusMessage {}
+
+#[repr(C)]
+pub struct DBusError {
+    pub name: *const c_char,
+    pub message: *const c_char,
+    dummy: c_uint,
+    padding: *mut c_void,
+}
+
+impl Default for DBusError {
+    fn default() -> Self {
+        Self {
+            name: null(),
+            message: null(),
+            dummy: 0,
+            padding: null_mut(),
+        }
+    }
+}
+
+/// Only used on the stack, larger than the C struct on all platforms
+#[repr(C)]
+#[derive(Default)]
+pub struct DBusMessageIter {
+    _private: [usize; 16],
+}
+
+pub const DBUS_BUS_SESSION: c_int = 0;
+pub const DBUS_TYPE_ARRAY: c_int = b'a' as c_int;
+pub const DBUS_TYPE_BOOLEAN: c_int = b'b' as c_int;
+pub const DBUS_TYPE_DICT_ENTRY: c_int = b'e' as c_int;
+pub const DBUS_TYPE_DOUBLE: c_int = b'd' as c_int;
+pub const DBUS_TYPE_OBJECT_PATH: c_int = b'o' as c_int;
+pub const DBUS_TYPE_STRING: c_int = b's' as c_int;
+pub const DBUS_TYPE_STRUCT: c_int = b'r' as c_int;
+pub const DBUS_TYPE_UINT32: c_int = b'u' as c_int;
+pub const DBUS_TYPE_VARIANT: c_int = b'v' as c_int;
+pub const DBUS_TIMEOUT_USE_DEFAULT: c_int = -1;
+pub const DBUS_TIMEOUT_INFINITE: c_int = 0x7fffffff;
+
+pub const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+pub const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+dlopen_external_library!(DBus,
+functions:
+    fn dbus_error_init(*mut DBusError) -> (),
+    fn dbus_error_free(*mut DBusError) -> (),
+    fn dbus_bus_get_private(c_int, *mut DBusError) -> *mut DBusConnection,
+    fn dbus_bus_add_match(*mut DBusConnection, *const c_char, *mut DBusError) -> (),
+    fn dbus_bus_get_unique_name(*mut DBusConnection) -> *const c_char,
+    fn dbus_connection_close(*mut DBusConnection) -> (),
+    fn dbus_connection_unref(*mut DBusConnection) -> (),
+    fn dbus_connection_set_exit_on_disconnect(*mut DBusConnection, dbus_bool_t) -> (),
+    fn dbus_connection_send_with_reply_and_block(
+        *mut DBusConnection,
+        *mut DBusMessage,
+        c_int,
+        *mut DBusError
+    ) -> *mut DBusMessage,
+    fn dbus_connection_send(*mut DBusConnection, *mut DBusMessage, *mut c_uint) -> dbus_bool_t,
+    fn dbus_connection_flush(*mut DBusConnection) -> (),
+    fn dbus_connection_read_write(*mut DBusConnection, c_int) -> dbus_bool_t,
+    fn dbus_connection_pop_message(*mut DBusConnection) -> *mut DBusMessage,
+    fn dbus_message_new_method_call(
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char
+    ) -> *mut DBusMessage,
+    fn dbus_message_new_signal(*const c_char, *const c_char, *const c_char) -> *mut DBusMessage,
+    fn dbus_message_unref(*mut DBusMessage) -> (),
+    fn dbus_message_is_signal(*mut DBusMessage, *const c_char, *const c_char) -> dbus_bool_t,
+    fn dbus_message_get_path(*mut DBusMessage) -> *const c_char,
+    fn dbus_message_iter_init(*mut DBusMessage, *mut DBusMessageIter) -> dbus_bool_t,
+    fn dbus_message_iter_init_append(*mut DBusMessage, *mut DBusMessageIter) -> (),
+    fn dbus_message_iter_append_basic(*mut DBusMessageIter, c_int, *const c_void) -> dbus_bool_t,
+    fn dbus_message_iter_open_container(
+        *mut DBusMessageIter,
+        c_int,
+        *const c_char,
+        *mut DBusMessageIter
+    ) -> dbus_bool_t,
+    fn dbus_message_iter_close_container(*mut DBusMessageIter, *mut DBusMessageIter) -> dbus_bool_t,
+    fn dbus_message_iter_next(*mut DBusMessageIter) -> dbus_bool_t,
+    fn dbus_message_iter_get_arg_type(*mut DBusMessageIter) -> c_int,
+    fn dbus_message_iter_recurse(*mut DBusMessageIter, *mut DBusMessageIter) -> (),
+    fn dbus_message_iter_get_basic(*mut DBusMessageIter, *mut c_void) -> (),
+);
+
+impl DBus {
+    pub unsafe fn append_string(&self, iter: &mut DBusMessageIter, value: &str) {
+        // Strings with a nul character can't be sent
+        let value = CString::new(value).unwrap_or_default();
+        let pointer = value.as_ptr();
+        (self.dbus_message_iter_append_basic)(
+            iter,
+            DBUS_TYPE_STRING,
+            &pointer as *const _ as *const c_void,
+        );
+    }
+
+    pub unsafe fn append_u32(&self, iter: &mut DBusMessageIter, value: u32) {
+        (self.dbus_message_iter_append_basic)(
+            iter,
+            DBUS_TYPE_UINT32,
+            &value as *const u32 as *const c_void,
+        );
+    }
+
+    pub unsafe fn append_bool(&self, iter: &mut DBusMessageIter, value: bool) {
+        let value = dbus_bool_t::from(value);
+        (self.dbus_message_iter_append_basic)(
+            iter,
+            DBUS_TYPE_BOOLEAN,
+            &value as *const dbus_bool_t as *const c_void,
+        );
+    }
+
+    pub unsafe fn append_f64(&self, iter: &mut DBusMessageIter, value: f64) {
+        (self.dbus_message_iter_append_basic)(
+            iter,
+            DBUS_TYPE_DOUBLE,
+            &value as *const f64 as *const c_void,
+        );
+    }
+
+    /// Append a container, filled by `append` before being closed. `signature` is the type of
+    /// the content of arrays and variants, and is ignored for the other containers
+    pub unsafe fn append_container(
+        &self,
+        iter: &mut DBusMessageIter,
+        container_type: c_int,
+        signature: &str,
+        append: impl FnOnce(&mut DBusMessageIter),
+    ) {
+        let signature = CString::new(signature).unwrap();
+        let signature = match container_type {
+            DBUS_TYPE_ARRAY | DBUS_TYPE_VARIANT => signature.as_ptr(),
+            _ => null(),
+        };
+
+        let mut container = DBusMessageIter::default();
+        (self.dbus_message_iter_open_container)(iter, container_type, signature, &mut container);
+        append(&mut container);
+        (self.dbus_message_iter_close_container)(iter, &mut container);
+    }
+
+    /// Append an entry of an `a{sv}` dictionary, `append` appends the value of the variant
+    pub unsafe fn append_option(
+        &self,
+        options: &mut DBusMessageIter,
+        key: &str,
+        signature: &str,
+        append: impl FnOnce(&mut DBusMessageIter),
+    ) {
+        self.append_container(options, DBUS_TYPE_DICT_ENTRY, "", |entry| {
+            self.append_string(entry, key);
+            self.append_container(entry, DBUS_TYPE_VARIANT, signature, append);
+        });
+    }
+
+    /// String at the position of `iter`, `None` if the argument isn't a string or an object path
+    pub unsafe fn read_string(&self, iter: &mut DBusMessageIter) -> Option<String> {
+        match (self.dbus_message_iter_get_arg_type)(iter) {
+            DBUS_TYPE_STRING | DBUS_TYPE_OBJECT_PATH => {
+                let mut value: *const c_char = null();
+                (self.dbus_message_iter_get_basic)(
+                    iter,
+                    &mut value as *mut *const c_char as *mut c_void,
+                );
+                Some(CStr::from_ptr(value).to_string_lossy().into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+pub fn open_dbus() -> Option<DBus> {
+    unsafe { DBus::open("libdbus-1.so.3").ok() }
+}
+
+/// Private connection to the session bus, closed when dropped
+pub struct SessionBus {
+    pub dbus: DBus,
+    pub connection: *mut DBusConnection,
+}
+
+// Only used by one thread at a time, connections are shared by the platform and its threads
+unsafe impl Send for SessionBus {}
+
+impl SessionBus {
+    /// `None` when libdbus or the session bus are missing
+    pub fn connect() -> Option<Self> {
+        let dbus = open_dbus()?;
+        unsafe {
+            let mut error = new_error(&dbus);
+            let connection = (dbus.dbus_bus_get_private)(DBUS_BUS_SESSION, &mut error);
+            (dbus.dbus_error_free)(&mut error);
+            if connection.is_null() {
+                return None;
+            }
+
+            // libdbus exits the process when the bus disconnects by default
+            (dbus.dbus_connection_set_exit_on_disconnect)(connection, 0);
+            Some(Self { dbus, connection })
+        }
+    }
+
+    /// Call `method` of a portal interface, `append` appends the arguments.
+    /// Returns the reply to unref, `None` when there is no portal or the call failed
+    pub unsafe fn call_portal(
+        &self,
+        interface: &str,
+        method: &str,
+        append: impl FnOnce(&mut DBusMessageIter),
+    ) -> Option<*mut DBusMessage> {
+        let destination = CString::new(PORTAL_DESTINATION).unwrap();
+        let path = CString::new(PORTAL_PATH).unwrap();
+        let interface = CString::new(interface).unwrap();
+        let method = CString::new(method).unwrap();
+
+        let dbus = &self.dbus;
+        let message = (dbus.dbus_message_new_method_call)(
+            destination.as_ptr(),
+            path.as_ptr(),
+            interface.as_ptr(),
+            method.as_ptr(),
+        );
+        if message.is_null() {
+            return None;
+        }
+
+        let mut iter = DBusMessageIter::default();
+        (dbus.dbus_message_iter_init_append)(message, &mut iter);
+        append(&mut iter);
+
+        let mut error = new_error(dbus);
+        let reply = (dbus.dbus_connection_send_with_reply_and_block)(
+            self.connection,
+            message,
+            DBUS_TIMEOUT_USE_DEFAULT,
+            &mut error,
+        );
+        (dbus.dbus_message_unref)(message);
+        (dbus.dbus_error_free)(&mut error);
+        (!reply.is_null()).then_some(reply)
+    }
+
+    /// Receive the signals matching `rule`, returns false if the rule is invalid
+    pub fn add_match(&self, rule: &str) -> bool {
+        let rule = CString::new(rule).unwrap();
+        let dbus = &self.dbus;
+        unsafe {
+            let mut error = new_error(dbus);
+            (dbus.dbus_bus_add_match)(self.connection, rule.as_ptr(), &mut error);
+            let failed = !error.name.is_null();
+            (dbus.dbus_error_free)(&mut error);
+            !failed
+        }
+    }
+}
+
+impl Drop for SessionBus {
+    fn drop(&mut self) {
+        unsafe {
+            (self.dbus.dbus_connection_close)(self.connection);
+            (self.dbus.dbus_connection_unref)(self.connection);
+        }
+    }
+}
+
+unsafe fn new_error(dbus: &DBus) -> DBusError {
+    let mut error = DBusError::default();
+    (dbus.dbus_error_init)(&mut error);
+    error
+}
@@ -0,0 +1,286 @@
+//! Open and save dialogs of the XDG desktop portal, shown by the desktop on behalf of the
+//! application for both X11 and Wayland
+
+use crate::dbus::*;
+use crate::uri_list::parse_uri_list;
+use crate::waker::Waker;
+use parking_lot::Mutex;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_void;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use ze_core::ze_error;
+use ze_platform::{FileDialogId, FileFilter, Message};
+
+const FILE_CHOOSER_INTERFACE: &str = "org.freedesktop.portal.FileChooser";
+const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+/// Kind of the patterns of the portal filters, the other one is MIME types
+const GLOB_PATTERN: u32 = 0;
+
+/// Response code of a request when the user picked files
+const RESPONSE_SUCCESS: u32 = 0;
+
+type ClosedDialogs = Arc<Mutex<Vec<(FileDialogId, Vec<PathBuf>)>>>;
+
+/// Dialogs are requested from a thread each, as the portal answers once the user closed them
+///
+/// Dialogs are reported as cancelled when libdbus, the session bus or the portal are missing.
+pub struct FileDialogs {
+    waker: Arc<Waker>,
+    closed: ClosedDialogs,
+}
+
+struct Request {
+    method: &'static str,
+    title: &'static str,
+    filters: Vec<(String, Vec<String>)>,
+    multiple: bool,
+    current_name: Option<String>,
+}
+
+impl FileDialogs {
+    /// `waker` is woken when a dialog is closed, so the result is reported by the event loop
+    pub fn new(waker: Arc<Waker>) -> Self {
+        Self {
+            waker,
+            closed: Default::default(),
+        }
+    }
+
+    pub fn open(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        self.request(Request {
+            method: "OpenFile",
+            title: if multi_select {
+                "Open Files"
+            } else {
+                "Open File"
+            },
+            filters: convert_filters(filters),
+            multiple: multi_select,
+            current_name: None,
+        })
+    }
+
+    pub fn save(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        self.request(Request {
+            method: "SaveFile",
+            title: "Save File",
+            filters: convert_filters(filters),
+            multiple: false,
+            current_name: Some(default_name.to_string()),
+        })
+    }
+
+    /// Push the messages of the dialogs closed since the last call
+    pub fn send_closed(&self, message_queue: &mut impl Extend<Message>) {
+        message_queue.extend(
+            self.closed
+                .lock()
+                .drain(..)
+                .map(|(id, paths)| Message::FileDialogClosed(id, paths)),
+        );
+    }
+
+    fn request(&self, request: Request) -> FileDialogId {
+        let id = FileDialogId::new();
+        let closed = self.closed.clone();
+        let waker = self.waker.clone();
+        let result = thread::Builder::new()
+            .name("File Dialog".to_string())
+            .spawn(move || {
+                let paths = SessionBus::connect()
+                    .and_then(|bus| request.run(&bus))
+                    .unwrap_or_default();
+                closed.lock().push((id, paths));
+                waker.wake();
+            });
+
+        if let Err(error) = result {
+            ze_error!("Failed to start the file dialog thread: {}", error);
+            self.closed.lock().push((id, vec![]));
+        }
+
+        id
+    }
+}
+
+impl Request {
+    /// Ask the portal for the dialog and wait for its response, `None` if it couldn't be shown
+    fn run(&self, bus: &SessionBus) -> Option<Vec<PathBuf>> {
+        let dbus = &bus.dbus;
+        unsafe {
+            // The response is sent to the handle returned by the call. Subscribing before the
+            // call ensures it isn't missed if the dialog is closed before the reply is read
+            if !bus.add_match(&format!(
+                "type='signal',interface='{}',member='Response'",
+                REQUEST_INTERFACE
+            )) {
+                return None;
+            }
+
+            let reply = bus.call_portal(FILE_CHOOSER_INTERFACE, self.method, |iter| {
+                self.append_arguments(dbus, iter)
+            })?;
+
+            let mut iter = DBusMessageIter::default();
+            let handle = if (dbus.dbus_message_iter_init)(reply, &mut iter) != 0 {
+                dbus.read_string(&mut iter)
+            } else {
+                None
+            };
+            (dbus.dbus_message_unref)(reply);
+            let handle = handle?;
+
+            let interface = CString::new(REQUEST_INTERFACE).unwrap();
+            let member = CString::new("Response").unwrap();
+
+            // The response may already have been received while waiting for the reply
+            loop {
+                let message = (dbus.dbus_connection_pop_message)(bus.connection);
+                if message.is_null() {
+                    if (dbus.dbus_connection_read_write)(bus.connection, DBUS_TIMEOUT_INFINITE) == 0
+                    {
+                        return None;
+                    }
+                    continue;
+                }
+
+                let mut paths = None;
+                if (dbus.dbus_message_is_signal)(message, interface.as_ptr(), member.as_ptr()) != 0
+                {
+                    let path = (dbus.dbus_message_get_path)(message);
+                    if !path.is_null() && CStr::from_ptr(path).to_bytes() == handle.as_bytes() {
+                        paths = Some(read_response(dbus, message));
+                    }
+                }
+                (dbus.dbus_message_unref)(message);
+
+                if paths.is_some() {
+                    return paths;
+                }
+            }
+        }
+    }
+
+    /// Parent window, title and options
+    unsafe fn append_arguments(&self, dbus: &DBus, iter: &mut DBusMessageIter) {
+        dbus.append_string(iter, "");
+        dbus.append_string(iter, self.title);
+        dbus.append_container(iter, DBUS_TYPE_ARRAY, "{sv}", |options| {
+            dbus.append_option(options, "multiple", "b", |value| {
+                dbus.append_bool(value, self.multiple)
+            });
+
+            if let Some(current_name) = &self.current_name {
+                dbus.append_option(options, "current_name", "s", |value| {
+                    dbus.append_string(value, current_name)
+                });
+            }
+
+            if !self.filters.is_empty() {
+                dbus.append_option(options, "filters", "a(sa(us))", |value| {
+                    append_filters(dbus, value, &self.filters)
+                });
+            }
+        });
+    }
+}
+
+/// Filters are `(name, [(kind, pattern)])` structs
+unsafe fn append_filters(
+    dbus: &DBus,
+    iter: &mut DBusMessageIter,
+    filters: &[(String, Vec<String>)],
+) {
+    dbus.append_container(iter, DBUS_TYPE_ARRAY, "(sa(us))", |array| {
+        for (name, patterns) in filters {
+            dbus.append_container(array, DBUS_TYPE_STRUCT, "", |filter| {
+                dbus.append_string(filter, name);
+                dbus.append_container(filter, DBUS_TYPE_ARRAY, "(us)", |array| {
+                    for pattern in patterns {
+                        dbus.append_container(array, DBUS_TYPE_STRUCT, "", |entry| {
+                            dbus.append_u32(entry, GLOB_PATTERN);
+                            dbus.append_string(entry, pattern);
+                        });
+                    }
+                });
+            });
+        }
+    });
+}
+
+/// Paths of the `uris` result of a successful response, empty if the dialog was cancelled
+unsafe fn read_response(dbus: &DBus, message: *mut DBusMessage) -> Vec<PathBuf> {
+    let mut iter = DBusMessageIter::default();
+    if (dbus.dbus_message_iter_init)(message, &mut iter) == 0
+        || (dbus.dbus_message_iter_get_arg_type)(&mut iter) != DBUS_TYPE_UINT32
+    {
+        return vec![];
+    }
+
+    let mut response = 0u32;
+    (dbus.dbus_message_iter_get_basic)(&mut iter, &mut response as *mut u32 as *mut c_void);
+    if response != RESPONSE_SUCCESS
+        || (dbus.dbus_message_iter_next)(&mut iter) == 0
+        || (dbus.dbus_message_iter_get_arg_type)(&mut iter) != DBUS_TYPE_ARRAY
+    {
+        return vec![];
+    }
+
+    // Results are a dictionary of variants, only the file URIs are used
+    let mut uri_list = String::new();
+    let mut results = DBusMessageIter::default();
+    (dbus.dbus_message_iter_recurse)(&mut iter, &mut results);
+    while (dbus.dbus_message_iter_get_arg_type)(&mut results) == DBUS_TYPE_DICT_ENTRY {
+        let mut entry = DBusMessageIter::default();
+        (dbus.dbus_message_iter_recurse)(&mut results, &mut entry);
+        if dbus.read_string(&mut entry).as_deref() == Some("uris")
+            && (dbus.dbus_message_iter_next)(&mut entry) != 0
+        {
+            let mut variant = DBusMessageIter::default();
+            (dbus.dbus_message_iter_recurse)(&mut entry, &mut variant);
+            if (dbus.dbus_message_iter_get_arg_type)(&mut variant) == DBUS_TYPE_ARRAY {
+                let mut uris = DBusMessageIter::default();
+                (dbus.dbus_message_iter_recurse)(&mut variant, &mut uris);
+                while let Some(uri) = dbus.read_string(&mut uris) {
+                    uri_list.push_str(&uri);
+                    uri_list.push('\n');
+                    (dbus.dbus_message_iter_next)(&mut uris);
+                }
+            }
+        }
+        (dbus.dbus_message_iter_next)(&mut results);
+    }
+
+    parse_uri_list(uri_list.as_bytes())
+}
+
+/// Glob patterns are case sensitive, each letter matches both cases
+fn convert_filters(filters: &[FileFilter]) -> Vec<(String, Vec<String>)> {
+    filters
+        .iter()
+        .map(|filter| {
+            let patterns = filter
+                .extensions
+                .iter()
+                .map(|extension| {
+                    let mut pattern = "*.".to_string();
+                    for character in extension.chars() {
+                        if character.is_alphabetic() {
+                            pattern.push('[');
+                            pattern.extend(character.to_lowercase());
+                            pattern.extend(character.to_uppercase());
+                            pattern.push(']');
+                        } else {
+                            pattern.push(character);
+                        }
+                    }
+                    pattern
+                })
+                .collect();
+            (filter.name.to_string(), patterns)
+        })
+        .collect()
+}
@@ -0,0 +1,190 @@
+//! Keysym conversions shared by the backends, xkbcommon keysyms are the X11 ones
+
+use std::os::raw::c_uint;
+use x11_dl::keysym::*;
+use ze_core::ze_verbose;
+use ze_platform::KeyCode;
+
+/// Unshifted character of a key, like `MapVirtualKey` on Windows
+pub fn keysym_character_code(keysym: c_uint) -> u32 {
+    match keysym {
+        0x20..=0x7e => (keysym as u8).to_ascii_uppercase() as u32,
+        _ => 0,
+    }
+}
+
+#[allow(non_upper_case_globals)]
+pub fn convert_key_code(keysym: c_uint) -> KeyCode {
+    match keysym {
+        XK_Escape => KeyCode::Escape,
+        XK_space => KeyCode::Space,
+        XK_BackSpace => KeyCode::Backspace,
+        XK_a => KeyCode::A,
+        XK_b => KeyCode::B,
+        XK_c => KeyCode::C,
+        XK_d => KeyCode::D,
+        XK_e => KeyCode::E,
+        XK_f => KeyCode::F,
+        XK_g => KeyCode::G,
+        XK_h => KeyCode::H,
+        XK_i => KeyCode::I,
+        XK_j => KeyCode::J,
+        XK_k => KeyCode::K,
+        XK_l => KeyCode::L,
+        XK_m => KeyCode::M,
+        XK_n => KeyCode::N,
+        XK_o => KeyCode::O,
+        XK_p => KeyCode::P,
+        XK_q => KeyCode::Q,
+        XK_r => KeyCode::R,
+        XK_s => KeyCode::S,
+        XK_t => KeyCode::T,
+        XK_u => KeyCode::U,
+        XK_v => KeyCode::V,
+        XK_w => KeyCode::W,
+        XK_x => KeyCode::X,
+        XK_y => KeyCode::Y,
+        XK_z => KeyCode::Z,
+        // The first keysym of keypad keys is the one without num lock
+        XK_KP_Insert | XK_KP_0 => KeyCode::Numpad0,
+        XK_KP_End | XK_KP_1 => KeyCode::Numpad1,
+        XK_KP_Down | XK_KP_2 => KeyCode::Numpad2,
+        XK_KP_Next | XK_KP_3 => KeyCode::Numpad3,
+        XK_KP_Left | XK_KP_4 => KeyCode::Numpad4,
+        XK_KP_Begin | XK_KP_5 => KeyCode::Numpad5,
+        XK_KP_Right | XK_KP_6 => KeyCode::Numpad6,
+        XK_KP_Home | XK_KP_7 => KeyCode::Numpad7,
+        XK_KP_Up | XK_KP_8 => KeyCode::Numpad8,
+        XK_KP_Prior | XK_KP_9 => KeyCode::Numpad9,
+        XK_0 => KeyCode::Num0,
+        XK_1 => KeyCode::Num1,
+        XK_2 => KeyCode::Num2,
+        XK_3 => KeyCode::Num3,
+        XK_4 => KeyCode::Num4,
+        XK_5 => KeyCode::Num5,
+        XK_6 => KeyCode::Num6,
+        XK_7 => KeyCode::Num7,
+        XK_8 => KeyCode::Num8,
+        XK_9 => KeyCode::Num9,
+        XK_Control_L => KeyCode::LeftControl,
+        XK_Control_R => KeyCode::RightControl,
+        XK_Shift_L => KeyCode::LeftShift,
+        XK_Shift_R => KeyCode::RightShift,
+        XK_Alt_L => KeyCode::LeftAlt,
+        XK_Alt_R => KeyCode::RightAlt,
+        XK_F1 => KeyCode::F1,
+        XK_F2 => KeyCode::F2,
+        XK_F3 => KeyCode::F3,
+        XK_F4 => KeyCode::F4,
+        XK_F5 => KeyCode::F5,
+        XK_F6 => KeyCode::F6,
+        XK_F7 => KeyCode::F7,
+        XK_F8 => KeyCode::F8,
+        XK_F9 => KeyCode::F9,
+        XK_F10 => KeyCode::F10,
+        XK_F11 => KeyCode::F11,
+        XK_F12 => KeyCode::F12,
+        XK_F13 => KeyCode::F13,
+        XK_F14 => KeyCode::F14,
+        XK_F15 => KeyCode::F15,
+        XK_F16 => KeyCode::F16,
+        XK_F17 => KeyCode::F17,
+        XK_F18 => KeyCode::F18,
+        XK_F19 => KeyCode::F19,
+        XK_F20 => KeyCode::F20,
+        XK_F21 => KeyCode::F21,
+        XK_F22 => KeyCode::F22,
+        XK_F23 => KeyCode::F23,
+        XK_F24 => KeyCode::F24,
+        _ => {
+            ze_verbose!("Key {} not handled", keysym);
+            KeyCode::None
+        }
+    }
+}
+
+/// Inverse of `convert_key_code`, the keypad keys are the ones with num lock
+pub fn key_code_keysym(key: KeyCode) -> Option<c_uint> {
+    Some(match key {
+        KeyCode::None => return None,
+        KeyCode::Escape => XK_Escape,
+        KeyCode::Space => XK_space,
+        KeyCode::Backspace => XK_BackSpace,
+        KeyCode::A => XK_a,
+        KeyCode::B => XK_b,
+        KeyCode::C => XK_c,
+        KeyCode::D => XK_d,
+        KeyCode::E => XK_e,
+        KeyCode::F => XK_f,
+        KeyCode::G => XK_g,
+        KeyCode::H => XK_h,
+        KeyCode::I => XK_i,
+        KeyCode::J => XK_j,
+        KeyCode::K => XK_k,
+        KeyCode::L => XK_l,
+        KeyCode::M => XK_m,
+        KeyCode::N => XK_n,
+        KeyCode::O => XK_o,
+        KeyCode::P => XK_p,
+        KeyCode::Q => XK_q,
+        KeyCode::R => XK_r,
+        KeyCode::S => XK_s,
+        KeyCode::T => XK_t,
+        KeyCode::U => XK_u,
+        KeyCode::V => XK_v,
+        KeyCode::W => XK_w,
+        KeyCode::X => XK_x,
+        KeyCode::Y => XK_y,
+        KeyCode::Z => XK_z,
+        KeyCode::Numpad0 => XK_KP_0,
+        KeyCode::Numpad1 => XK_KP_1,
+        KeyCode::Numpad2 => XK_KP_2,
+        KeyCode::Numpad3 => XK_KP_3,
+        KeyCode::Numpad4 => XK_KP_4,
+        KeyCode::Numpad5 => XK_KP_5,
+        KeyCode::Numpad6 => XK_KP_6,
+        KeyCode::Numpad7 => XK_KP_7,
+        KeyCode::Numpad8 => XK_KP_8,
+        KeyCode::Numpad9 => XK_KP_9,
+        KeyCode::Num0 => XK_0,
+        KeyCode::Num1 => XK_1,
+        KeyCode::Num2 => XK_2,
+        KeyCode::Num3 => XK_3,
+        KeyCode::Num4 => XK_4,
+        KeyCode::Num5 => XK_5,
+        KeyCode::Num6 => XK_6,
+        KeyCode::Num7 => XK_7,
+        KeyCode::Num8 => XK_8,
+        KeyCode::Num9 => XK_9,
+        KeyCode::LeftControl => XK_Control_L,
+        KeyCode::RightControl => XK_Control_R,
+        KeyCode::LeftShift => XK_Shift_L,
+        KeyCode::RightShift => XK_Shift_R,
+        KeyCode::LeftAlt => XK_Alt_L,
+        KeyCode::RightAlt => XK_Alt_R,
+        KeyCode::F1 => XK_F1,
+        KeyCode::F2 => XK_F2,
+        KeyCode::F3 => XK_F3,
+        KeyCode::F4 => XK_F4,
+        KeyCode::F5 => XK_F5,
+        KeyCode::F6 => XK_F6,
+        KeyCode::F7 => XK_F7,
+        KeyCode::F8 => XK_F8,
+        KeyCode::F9 => XK_F9,
+        KeyCode::F10 => XK_F10,
+        KeyCode::F11 => XK_F11,
+        KeyCode::F12 => XK_F12,
+        KeyCode::F13 => XK_F13,
+        KeyCode::F14 => XK_F14,
+        KeyCode::F15 => XK_F15,
+        KeyCode::F16 => XK_F16,
+        KeyCode::F17 => XK_F17,
+        KeyCode::F18 => XK_F18,
+        KeyCode::F19 => XK_F19,
+        KeyCode::F20 => XK_F20,
+        KeyCode::F21 => XK_F21,
+        KeyCode::F22 => XK_F22,
+        KeyCode::F23 => XK_F23,
+        KeyCode::F24 => XK_F24,
+    })
+}
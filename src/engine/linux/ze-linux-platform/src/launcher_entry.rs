@@ -0,0 +1,84 @@
+//! Progress shown on the launcher icon of the application with the Unity LauncherEntry API,
+//! supported by the docks and task managers of most desktops for both X11 and Wayland
+
+use crate::dbus::*;
+use parking_lot::Mutex;
+use std::env;
+use std::ffi::CString;
+use std::ptr::null_mut;
+use ze_platform::TaskbarProgressState;
+
+const LAUNCHER_ENTRY_INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+/// Any path works, the docks only look at the application URI of the signal
+const LAUNCHER_ENTRY_PATH: &str = "/com/zinoengine/LauncherEntry";
+
+/// Launcher entry of the application, shared by all of its windows as the docks show one icon
+/// per application
+///
+/// Nothing is shown when libdbus or the session bus are missing, or when the application has no
+/// desktop file named after its executable.
+pub struct LauncherEntry {
+    bus: Option<Mutex<SessionBus>>,
+
+    /// `application://` URI of the desktop file of the application
+    app_uri: String,
+}
+
+impl LauncherEntry {
+    pub fn new() -> Self {
+        let desktop_id = env::current_exe()
+            .ok()
+            .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        Self {
+            bus: SessionBus::connect().map(Mutex::new),
+            app_uri: format!("application://{}.desktop", desktop_id),
+        }
+    }
+
+    /// Docks have no state for paused or failed progress, they are shown as normal progress.
+    /// Indeterminate progress is shown as empty
+    pub fn set_progress(&self, state: TaskbarProgressState, fraction: f32) {
+        let bus = match &self.bus {
+            Some(bus) => bus.lock(),
+            None => return,
+        };
+
+        let visible = state != TaskbarProgressState::None;
+        let progress = match state {
+            TaskbarProgressState::None | TaskbarProgressState::Indeterminate => 0.0,
+            _ => fraction.clamp(0.0, 1.0) as f64,
+        };
+
+        let path = CString::new(LAUNCHER_ENTRY_PATH).unwrap();
+        let interface = CString::new(LAUNCHER_ENTRY_INTERFACE).unwrap();
+        let member = CString::new("Update").unwrap();
+
+        let dbus = &bus.dbus;
+        unsafe {
+            let message =
+                (dbus.dbus_message_new_signal)(path.as_ptr(), interface.as_ptr(), member.as_ptr());
+            if message.is_null() {
+                return;
+            }
+
+            let mut iter = DBusMessageIter::default();
+            (dbus.dbus_message_iter_init_append)(message, &mut iter);
+            dbus.append_string(&mut iter, &self.app_uri);
+            dbus.append_container(&mut iter, DBUS_TYPE_ARRAY, "{sv}", |properties| {
+                dbus.append_option(properties, "progress", "d", |value| {
+                    dbus.append_f64(value, progress)
+                });
+                dbus.append_option(properties, "progress-visible", "b", |value| {
+                    dbus.append_bool(value, visible)
+                });
+            });
+
+            (dbus.dbus_connection_send)(bus.connection, message, null_mut());
+            (dbus.dbus_connection_flush)(bus.connection);
+            (dbus.dbus_message_unref)(message);
+        }
+    }
+}
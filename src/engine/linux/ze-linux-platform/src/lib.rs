@@ -0,0 +1,31 @@
+use std::sync::Arc;
+use ze_core::ze_info;
+use ze_platform::{Error, Platform};
+
+pub use wayland::WaylandPlatform;
+pub use x11::X11Platform;
+
+mod dbus;
+mod file_dialog;
+mod keysym;
+mod launcher_entry;
+mod pixels;
+mod power;
+mod scancode;
+mod theme;
+mod uri_list;
+mod waker;
+mod wayland;
+mod x11;
+
+/// Create the platform of the running desktop, X11 is preferred and Wayland is used when
+/// there is no X server
+pub fn new_platform() -> Result<Arc<dyn Platform>, Error> {
+    match X11Platform::new() {
+        Ok(platform) => Ok(platform),
+        Err(_) => {
+            ze_info!("X11 isn't available, falling back to Wayland");
+            Ok(WaylandPlatform::new()?)
+        }
+    }
+}
@@ -0,0 +1,13 @@
+/// Convert 8-bit RGBA pixels to the premultiplied ARGB of X11 and Wayland cursors
+pub fn premultiplied_argb(rgba: &[u8]) -> Vec<u32> {
+    rgba.chunks_exact(4)
+        .map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+            (alpha << 24)
+                | (premultiply(pixel[0]) << 16)
+                | (premultiply(pixel[1]) << 8)
+                | premultiply(pixel[2])
+        })
+        .collect()
+}
@@ -0,0 +1,111 @@
+//! Power status read from the power supplies of sysfs, and power saving from the power profile
+//! monitor of the XDG desktop portal
+
+use crate::dbus::*;
+use parking_lot::Mutex;
+use std::fs;
+use std::os::raw::c_void;
+use std::path::Path;
+use ze_platform::{PowerSource, PowerStatus};
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+const POWER_PROFILE_MONITOR_INTERFACE: &str = "org.freedesktop.portal.PowerProfileMonitor";
+
+/// Power saving is reported as disabled when libdbus, the session bus or the portal are missing
+pub struct PowerMonitor {
+    bus: Option<Mutex<SessionBus>>,
+}
+
+impl PowerMonitor {
+    pub fn new() -> Self {
+        Self {
+            bus: SessionBus::connect().map(Mutex::new),
+        }
+    }
+
+    pub fn status(&self) -> PowerStatus {
+        let mut status = read_power_supplies(Path::new(POWER_SUPPLY_PATH));
+        status.power_saver = self.read_power_saver().unwrap_or(false);
+        status
+    }
+
+    fn read_power_saver(&self) -> Option<bool> {
+        let bus = self.bus.as_ref()?.lock();
+        let dbus = &bus.dbus;
+        unsafe {
+            let reply = bus.call_portal(PROPERTIES_INTERFACE, "Get", |iter| {
+                dbus.append_string(iter, POWER_PROFILE_MONITOR_INTERFACE);
+                dbus.append_string(iter, "power-saver-enabled");
+            })?;
+
+            // The property is a boolean in a variant
+            let mut power_saver = None;
+            let mut iter = DBusMessageIter::default();
+            if (dbus.dbus_message_iter_init)(reply, &mut iter) != 0
+                && (dbus.dbus_message_iter_get_arg_type)(&mut iter) == DBUS_TYPE_VARIANT
+            {
+                let mut variant = DBusMessageIter::default();
+                (dbus.dbus_message_iter_recurse)(&mut iter, &mut variant);
+                if (dbus.dbus_message_iter_get_arg_type)(&mut variant) == DBUS_TYPE_BOOLEAN {
+                    let mut value: dbus_bool_t = 0;
+                    (dbus.dbus_message_iter_get_basic)(
+                        &mut variant,
+                        &mut value as *mut dbus_bool_t as *mut c_void,
+                    );
+                    power_saver = Some(value != 0);
+                }
+            }
+            (dbus.dbus_message_unref)(reply);
+            power_saver
+        }
+    }
+}
+
+/// Source and charge of the batteries of the system, batteries of devices like mice are ignored
+fn read_power_supplies(path: &Path) -> PowerStatus {
+    let mut mains_online = false;
+    let mut discharging = false;
+    let mut batteries = 0;
+    let mut capacity = 0;
+
+    let supplies = match fs::read_dir(path) {
+        Ok(supplies) => supplies,
+        Err(_) => return PowerStatus::default(),
+    };
+
+    for supply in supplies.flatten() {
+        let supply = supply.path();
+        let read = |name: &str| {
+            fs::read_to_string(supply.join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        match read("type").as_str() {
+            "Mains" => mains_online |= read("online") == "1",
+            "Battery" if read("scope") != "Device" => {
+                discharging |= read("status") == "Discharging";
+                if let Ok(battery_capacity) = read("capacity").parse::<u32>() {
+                    batteries += 1;
+                    capacity += battery_capacity.min(100);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let source = if discharging {
+        PowerSource::Battery
+    } else if mains_online || batteries > 0 {
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    };
+
+    PowerStatus {
+        source,
+        battery_percentage: (batteries > 0).then(|| (capacity / batteries) as u8),
+        power_saver: false,
+    }
+}
@@ -0,0 +1,93 @@
+//! Physical keys from the keycodes, X11 and xkb keycodes are the Linux input event codes
+//! of the keys offset by 8
+
+use ze_platform::KeyCode;
+
+/// Offset between Linux input event codes and xkb key codes
+pub const XKB_KEYCODE_OFFSET: u32 = 8;
+
+/// Key at the position of the keycode on a US QWERTY keyboard, whatever the keyboard layout
+pub fn convert_scan_code(keycode: u32) -> KeyCode {
+    match keycode.wrapping_sub(XKB_KEYCODE_OFFSET) {
+        1 => KeyCode::Escape,
+        2 => KeyCode::Num1,
+        3 => KeyCode::Num2,
+        4 => KeyCode::Num3,
+        5 => KeyCode::Num4,
+        6 => KeyCode::Num5,
+        7 => KeyCode::Num6,
+        8 => KeyCode::Num7,
+        9 => KeyCode::Num8,
+        10 => KeyCode::Num9,
+        11 => KeyCode::Num0,
+        14 => KeyCode::Backspace,
+        16 => KeyCode::Q,
+        17 => KeyCode::W,
+        18 => KeyCode::E,
+        19 => KeyCode::R,
+        20 => KeyCode::T,
+        21 => KeyCode::Y,
+        22 => KeyCode::U,
+        23 => KeyCode::I,
+        24 => KeyCode::O,
+        25 => KeyCode::P,
+        29 => KeyCode::LeftControl,
+        30 => KeyCode::A,
+        31 => KeyCode::S,
+        32 => KeyCode::D,
+        33 => KeyCode::F,
+        34 => KeyCode::G,
+        35 => KeyCode::H,
+        36 => KeyCode::J,
+        37 => KeyCode::K,
+        38 => KeyCode::L,
+        42 => KeyCode::LeftShift,
+        44 => KeyCode::Z,
+        45 => KeyCode::X,
+        46 => KeyCode::C,
+        47 => KeyCode::V,
+        48 => KeyCode::B,
+        49 => KeyCode::N,
+        50 => KeyCode::M,
+        54 => KeyCode::RightShift,
+        56 => KeyCode::LeftAlt,
+        57 => KeyCode::Space,
+        59 => KeyCode::F1,
+        60 => KeyCode::F2,
+        61 => KeyCode::F3,
+        62 => KeyCode::F4,
+        63 => KeyCode::F5,
+        64 => KeyCode::F6,
+        65 => KeyCode::F7,
+        66 => KeyCode::F8,
+        67 => KeyCode::F9,
+        68 => KeyCode::F10,
+        71 => KeyCode::Numpad7,
+        72 => KeyCode::Numpad8,
+        73 => KeyCode::Numpad9,
+        75 => KeyCode::Numpad4,
+        76 => KeyCode::Numpad5,
+        77 => KeyCode::Numpad6,
+        79 => KeyCode::Numpad1,
+        80 => KeyCode::Numpad2,
+        81 => KeyCode::Numpad3,
+        82 => KeyCode::Numpad0,
+        87 => KeyCode::F11,
+        88 => KeyCode::F12,
+        97 => KeyCode::RightControl,
+        100 => KeyCode::RightAlt,
+        183 => KeyCode::F13,
+        184 => KeyCode::F14,
+        185 => KeyCode::F15,
+        186 => KeyCode::F16,
+        187 => KeyCode::F17,
+        188 => KeyCode::F18,
+        189 => KeyCode::F19,
+        190 => KeyCode::F20,
+        191 => KeyCode::F21,
+        192 => KeyCode::F22,
+        193 => KeyCode::F23,
+        194 => KeyCode::F24,
+        _ => KeyCode::None,
+    }
+}
@@ -0,0 +1,189 @@
+//! Color scheme preferred by the user, read from the settings of the XDG desktop portal which
+//! is the same for X11 and Wayland desktops
+
+use crate::dbus::*;
+use crate::waker::Waker;
+use parking_lot::Mutex;
+use std::ffi::CString;
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use ze_core::ze_error;
+use ze_platform::SystemTheme;
+
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+/// Value of the color scheme setting when the user prefers dark applications,
+/// 0 is no preference and 2 prefers light
+const COLOR_SCHEME_PREFER_DARK: u32 = 1;
+
+/// Longest wait for a signal, the thread only notices the platform was dropped after it
+const WATCH_TIMEOUT_MS: i32 = 1000;
+
+struct Shared {
+    theme: Mutex<SystemTheme>,
+    changed: AtomicBool,
+}
+
+/// Keeps the system theme updated from a thread listening to the portal settings changes
+///
+/// The theme stays light when libdbus, the session bus or the portal are missing.
+pub struct ThemeWatcher {
+    shared: Arc<Shared>,
+}
+
+impl ThemeWatcher {
+    /// `waker` is woken when the theme changes, so the change is reported by the event loop
+    pub fn new(waker: Arc<Waker>) -> Self {
+        let shared = Arc::new(Shared {
+            theme: Mutex::new(SystemTheme::Light),
+            changed: AtomicBool::new(false),
+        });
+
+        if let Some(portal) = Portal::connect() {
+            if let Some(theme) = portal.read_theme() {
+                *shared.theme.lock() = theme;
+            }
+
+            if portal.watch_changes() {
+                let shared = Arc::downgrade(&shared);
+                if let Err(error) = thread::Builder::new()
+                    .name("Theme Watcher".to_string())
+                    .spawn(move || portal.run(shared, waker))
+                {
+                    ze_error!("Failed to start the theme watcher thread: {}", error);
+                }
+            }
+        }
+
+        Self { shared }
+    }
+
+    pub fn theme(&self) -> SystemTheme {
+        *self.shared.theme.lock()
+    }
+
+    /// The new theme if it changed since the last call
+    pub fn take_change(&self) -> Option<SystemTheme> {
+        self.shared
+            .changed
+            .swap(false, Ordering::SeqCst)
+            .then(|| self.theme())
+    }
+}
+
+/// Connection used to read the settings of the portal
+struct Portal {
+    bus: SessionBus,
+}
+
+impl Portal {
+    fn connect() -> Option<Self> {
+        Some(Self {
+            bus: SessionBus::connect()?,
+        })
+    }
+
+    fn read_theme(&self) -> Option<SystemTheme> {
+        let dbus = &self.bus.dbus;
+        unsafe {
+            // Fails when there is no portal or it doesn't know the setting
+            let reply = self.bus.call_portal(SETTINGS_INTERFACE, "Read", |iter| {
+                dbus.append_string(iter, APPEARANCE_NAMESPACE);
+                dbus.append_string(iter, COLOR_SCHEME_KEY);
+            })?;
+
+            let mut iter = DBusMessageIter::default();
+            let theme = if (dbus.dbus_message_iter_init)(reply, &mut iter) != 0 {
+                self.read_color_scheme(iter)
+            } else {
+                None
+            };
+            (dbus.dbus_message_unref)(reply);
+            theme
+        }
+    }
+
+    /// Subscribe to the changes of the color scheme setting
+    fn watch_changes(&self) -> bool {
+        self.bus.add_match(&format!(
+            "type='signal',interface='{}',member='SettingChanged',arg0='{}',arg1='{}'",
+            SETTINGS_INTERFACE, APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY
+        ))
+    }
+
+    /// Receive the setting changes until the bus disconnects or the watcher is dropped
+    fn run(self, shared: Weak<Shared>, waker: Arc<Waker>) {
+        let interface = CString::new(SETTINGS_INTERFACE).unwrap();
+        let member = CString::new("SettingChanged").unwrap();
+
+        let dbus = &self.bus.dbus;
+        let connection = self.bus.connection;
+        unsafe {
+            while (dbus.dbus_connection_read_write)(connection, WATCH_TIMEOUT_MS) != 0 {
+                let shared = match shared.upgrade() {
+                    Some(shared) => shared,
+                    None => break,
+                };
+
+                loop {
+                    let message = (dbus.dbus_connection_pop_message)(connection);
+                    if message.is_null() {
+                        break;
+                    }
+
+                    if (dbus.dbus_message_is_signal)(message, interface.as_ptr(), member.as_ptr())
+                        != 0
+                    {
+                        // The namespace and the key are filtered by the match rule
+                        let mut iter = DBusMessageIter::default();
+                        if (dbus.dbus_message_iter_init)(message, &mut iter) != 0
+                            && (dbus.dbus_message_iter_next)(&mut iter) != 0
+                            && (dbus.dbus_message_iter_next)(&mut iter) != 0
+                        {
+                            if let Some(theme) = self.read_color_scheme(iter) {
+                                let mut current_theme = shared.theme.lock();
+                                if *current_theme != theme {
+                                    *current_theme = theme;
+                                    shared.changed.store(true, Ordering::SeqCst);
+                                    waker.wake();
+                                }
+                            }
+                        }
+                    }
+                    (dbus.dbus_message_unref)(message);
+                }
+            }
+        }
+    }
+
+    /// The setting is an unsigned integer, wrapped in one or more variants
+    unsafe fn read_color_scheme(&self, mut iter: DBusMessageIter) -> Option<SystemTheme> {
+        let dbus = &self.bus.dbus;
+        loop {
+            match (dbus.dbus_message_iter_get_arg_type)(&mut iter) {
+                DBUS_TYPE_VARIANT => {
+                    let mut variant = DBusMessageIter::default();
+                    (dbus.dbus_message_iter_recurse)(&mut iter, &mut variant);
+                    iter = variant;
+                }
+                DBUS_TYPE_UINT32 => {
+                    let mut value = 0u32;
+                    (dbus.dbus_message_iter_get_basic)(
+                        &mut iter,
+                        &mut value as *mut u32 as *mut c_void,
+                    );
+                    return Some(if value == COLOR_SCHEME_PREFER_DARK {
+                        SystemTheme::Dark
+                    } else {
+                        SystemTheme::Light
+                    });
+                }
+                _ => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+/// MIME type of the dropped files lists of both X11 and Wayland
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// Paths of the `file://` URIs of a `text/uri-list`, other URIs and comments are skipped
+pub fn parse_uri_list(data: &[u8]) -> Vec<PathBuf> {
+    data.split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty() && !line.starts_with(b"#"))
+        .filter_map(|uri| uri.strip_prefix(b"file://"))
+        .filter_map(|uri| {
+            // Skip the host, usually empty or localhost
+            let path = &uri[uri.iter().position(|&byte| byte == b'/')?..];
+            Some(PathBuf::from(OsString::from_vec(percent_decode(path))))
+        })
+        .collect()
+}
+
+fn percent_decode(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let escaped = data
+            .get(i + 1..i + 3)
+            .filter(|_| data[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(data[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
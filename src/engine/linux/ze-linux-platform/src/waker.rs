@@ -0,0 +1,52 @@
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::raw::c_int;
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+/// Interrupts the wait for the display server events of `wait_event` from any thread
+pub struct Waker {
+    reader: UnixStream,
+    writer: UnixStream,
+}
+
+impl Waker {
+    pub fn new() -> io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        writer.set_nonblocking(true)?;
+        Ok(Self { reader, writer })
+    }
+
+    /// A full socket already wakes the waiting thread, the write error can be ignored
+    pub fn wake(&self) {
+        let _ = (&self.writer).write(&[0]);
+    }
+
+    /// Block until `fd` can be read, `wake` is called or `timeout` elapsed
+    pub fn wait(&self, fd: RawFd, timeout: Option<Duration>) {
+        let mut fds = [
+            libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: self.reader.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+
+        // Rounded up so short timeouts don't turn into busy loops
+        let timeout = timeout.map_or(-1, |timeout| {
+            timeout.as_micros().div_ceil(1000).min(c_int::MAX as u128) as c_int
+        });
+        unsafe {
+            libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout);
+        }
+
+        let mut buffer = [0; 64];
+        while matches!((&self.reader).read(&mut buffer), Ok(read) if read > 0) {}
+    }
+}
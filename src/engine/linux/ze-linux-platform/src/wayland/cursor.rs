@@ -0,0 +1,126 @@
+use crate::pixels::premultiplied_argb;
+use crate::wayland::state::State;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::fd::AsFd;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::{env, fs, io, process};
+use wayland_client::protocol::wl_buffer::WlBuffer;
+use wayland_client::protocol::wl_shm;
+use wayland_client::protocol::wl_shm::WlShm;
+use wayland_client::QueueHandle;
+use ze_core::maths::Point2;
+use ze_platform::Cursor;
+
+#[derive(Clone)]
+pub enum CursorShape {
+    /// Names of the cursor in the theme, by order of preference
+    Theme(&'static [&'static str]),
+    Image(Arc<CursorImage>),
+}
+
+impl PartialEq for CursorShape {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CursorShape::Theme(names), CursorShape::Theme(other_names)) => names == other_names,
+            (CursorShape::Image(image), CursorShape::Image(other_image)) => {
+                Arc::ptr_eq(image, other_image)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Cursor of the theme, loaded by the platform when it is set, or created from pixels
+pub struct WaylandCursor {
+    pub shape: CursorShape,
+}
+
+impl WaylandCursor {
+    pub fn new(shape: CursorShape) -> Self {
+        Self { shape }
+    }
+}
+
+impl Cursor for WaylandCursor {}
+
+/// Shared memory buffer of a cursor created from pixels
+pub struct CursorImage {
+    pub buffer: WlBuffer,
+    pub width: u32,
+    pub height: u32,
+    pub hotspot: Point2<u32>,
+}
+
+impl CursorImage {
+    pub fn new(
+        shm: &WlShm,
+        queue_handle: &QueueHandle<State>,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> io::Result<Self> {
+        let pixels = premultiplied_argb(&rgba[..(width * height * 4) as usize]);
+        let bytes: Vec<u8> = pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes())
+            .collect();
+
+        let mut file = create_shm_file()?;
+        file.write_all(&bytes)?;
+
+        let pool = shm.create_pool(file.as_fd(), bytes.len() as i32, queue_handle, ());
+        let buffer = pool.create_buffer(
+            0,
+            width as i32,
+            height as i32,
+            width as i32 * 4,
+            wl_shm::Format::Argb8888,
+            queue_handle,
+            (),
+        );
+
+        // The buffer keeps the memory mapped by the compositor alive
+        pool.destroy();
+
+        Ok(Self {
+            buffer,
+            width,
+            height,
+            hotspot,
+        })
+    }
+}
+
+impl Drop for CursorImage {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+    }
+}
+
+/// Create a file only shared with the compositor, removed from the runtime directory
+/// as soon as it is opened
+fn create_shm_file() -> io::Result<File> {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let mut path = PathBuf::from(
+        env::var_os("XDG_RUNTIME_DIR")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR not set"))?,
+    );
+    path.push(format!(
+        "ze-cursor-{}-{}",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    fs::remove_file(&path)?;
+    Ok(file)
+}
@@ -0,0 +1,130 @@
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr::null;
+use xkbcommon_dl::{
+    xkb_context, xkb_context_flags, xkb_keycode_t, xkb_keymap, xkb_keymap_compile_flags,
+    xkb_keymap_format, xkb_keysym_t, xkb_state, xkb_state_component, XkbCommon, XKB_MOD_NAME_ALT,
+    XKB_MOD_NAME_CTRL, XKB_MOD_NAME_LOGO, XKB_MOD_NAME_SHIFT,
+};
+use ze_platform::{ModifierBits, Modifiers};
+
+/// Keyboard layout sent by the compositor, translating key codes to keysyms and text
+pub struct Keymap {
+    xkb: &'static XkbCommon,
+    context: *mut xkb_context,
+    keymap: *mut xkb_keymap,
+    state: *mut xkb_state,
+}
+
+// The keymap is only used while holding the platform state lock
+unsafe impl Send for Keymap {}
+
+impl Keymap {
+    pub fn new(xkb: &'static XkbCommon, keymap: &str) -> Option<Keymap> {
+        let keymap = CString::new(keymap).ok()?;
+        unsafe {
+            let context = (xkb.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS);
+            if context.is_null() {
+                return None;
+            }
+
+            let keymap = (xkb.xkb_keymap_new_from_string)(
+                context,
+                keymap.as_ptr(),
+                xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            );
+            if keymap.is_null() {
+                (xkb.xkb_context_unref)(context);
+                return None;
+            }
+
+            let state = (xkb.xkb_state_new)(keymap);
+            if state.is_null() {
+                (xkb.xkb_keymap_unref)(keymap);
+                (xkb.xkb_context_unref)(context);
+                return None;
+            }
+
+            Some(Keymap {
+                xkb,
+                context,
+                keymap,
+                state,
+            })
+        }
+    }
+
+    pub fn update_modifiers(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        unsafe {
+            (self.xkb.xkb_state_update_mask)(self.state, depressed, latched, locked, 0, 0, group);
+        }
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        for (name, modifier) in [
+            (XKB_MOD_NAME_SHIFT, ModifierBits::Shift),
+            (XKB_MOD_NAME_CTRL, ModifierBits::Control),
+            (XKB_MOD_NAME_ALT, ModifierBits::Alt),
+            (XKB_MOD_NAME_LOGO, ModifierBits::Super),
+        ] {
+            let active = unsafe {
+                (self.xkb.xkb_state_mod_name_is_active)(
+                    self.state,
+                    name.as_ptr() as *const c_char,
+                    xkb_state_component::XKB_STATE_MODS_EFFECTIVE,
+                )
+            };
+            if active > 0 {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    }
+
+    /// Keysym of the key without any modifier, like `XLookupKeysym` with index 0
+    pub fn keysym(&self, key: xkb_keycode_t) -> xkb_keysym_t {
+        let mut keysyms: *const xkb_keysym_t = null();
+        unsafe {
+            let count =
+                (self.xkb.xkb_keymap_key_get_syms_by_level)(self.keymap, key, 0, 0, &mut keysyms);
+            if count > 0 {
+                *keysyms
+            } else {
+                0
+            }
+        }
+    }
+
+    /// Text typed by the key with the current modifiers
+    pub fn text(&self, key: xkb_keycode_t) -> String {
+        let mut buffer = [0u8; 64];
+        let length = unsafe {
+            (self.xkb.xkb_state_key_get_utf8)(
+                self.state,
+                key,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len(),
+            )
+        };
+
+        // The length doesn't include the null terminator and may exceed the buffer size
+        let length = (length.max(0) as usize).min(buffer.len() - 1);
+        String::from_utf8_lossy(&buffer[..length]).into_owned()
+    }
+
+    pub fn repeats(&self, key: xkb_keycode_t) -> bool {
+        unsafe { (self.xkb.xkb_keymap_key_repeats)(self.keymap, key) != 0 }
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xkb.xkb_state_unref)(self.state);
+            (self.xkb.xkb_keymap_unref)(self.keymap);
+            (self.xkb.xkb_context_unref)(self.context);
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use crate::file_dialog::FileDialogs;
+use crate::launcher_entry::LauncherEntry;
+use crate::power::PowerMonitor;
+use crate::theme::ThemeWatcher;
+use crate::waker::Waker;
+use crate::wayland::cursor::{CursorImage, CursorShape, WaylandCursor};
+use crate::wayland::state::{receive_offer, State, TEXT_MIME_TYPES};
+use crate::wayland::window::WaylandWindow;
+use parking_lot::Mutex;
+use std::os::fd::AsRawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wayland_client::globals::{registry_queue_init, BindError};
+use wayland_client::protocol::wl_output::WlOutput;
+use wayland_client::{Connection, EventQueue, Proxy};
+use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1;
+use xkbcommon_dl::xkbcommon_option;
+use ze_core::maths::Point2;
+use ze_core::{ze_error, ze_warn};
+use ze_platform::{
+    Cursor, Error, FileDialogId, FileFilter, KeyCode, Message, Modifiers, Monitor, Platform,
+    PowerStatus, SystemCursor, SystemTheme, Window, WindowFlagBits, WindowFlags,
+};
+
+struct Inner {
+    event_queue: EventQueue<State>,
+    state: State,
+}
+
+/// Platform implemented over the Wayland client library, loaded at runtime like Xlib
+///
+/// Windows are xdg-shell toplevels, Wayland doesn't expose nor allow setting window positions
+/// so the ones reported are the ones requested by the application.
+pub struct WaylandPlatform {
+    connection: Connection,
+    inner: Mutex<Inner>,
+    waker: Arc<Waker>,
+    theme_watcher: ThemeWatcher,
+    file_dialogs: FileDialogs,
+    launcher_entry: Arc<LauncherEntry>,
+    power_monitor: PowerMonitor,
+}
+
+impl WaylandPlatform {
+    /// Connect to the compositor, fails if libwayland is missing or if there is no compositor
+    pub fn new() -> Result<Arc<WaylandPlatform>, Error> {
+        let connection = Connection::connect_to_env().map_err(|error| {
+            ze_error!("Failed to connect to the Wayland compositor: {}", error);
+            Error::Unknown
+        })?;
+
+        let waker = Arc::new(Waker::new().map_err(|error| {
+            ze_error!("Failed to create the event loop waker: {}", error);
+            Error::Unknown
+        })?);
+
+        let xkb = xkbcommon_option().ok_or_else(|| {
+            ze_error!("Failed to load libxkbcommon");
+            Error::Unknown
+        })?;
+
+        let (globals, mut event_queue) =
+            registry_queue_init::<State>(&connection).map_err(|error| {
+                ze_error!("Failed to get the Wayland globals: {}", error);
+                Error::Unknown
+            })?;
+        let queue_handle = event_queue.handle();
+
+        // Buffer scales are supported since version 3 of the compositor
+        let compositor = globals
+            .bind(&queue_handle, 3..=4, ())
+            .map_err(missing_global)?;
+        let shm = globals
+            .bind(&queue_handle, 1..=1, ())
+            .map_err(missing_global)?;
+        let wm_base = globals
+            .bind(&queue_handle, 1..=2, ())
+            .map_err(missing_global)?;
+        let decoration_manager = globals.bind(&queue_handle, 1..=1, ()).ok();
+        let data_device_manager = globals.bind(&queue_handle, 1..=3, ()).ok();
+
+        let mut state = State::new(
+            &connection,
+            queue_handle.clone(),
+            xkb,
+            compositor,
+            shm,
+            wm_base,
+            decoration_manager,
+            data_device_manager,
+        );
+
+        // Bound before the seat so the relative pointer is created with the pointer
+        state.relative_pointer_manager = globals.bind(&queue_handle, 1..=1, ()).ok();
+        state.text_input_manager = globals.bind(&queue_handle, 1..=1, ()).ok();
+        state.activation = globals.bind(&queue_handle, 1..=1, ()).ok();
+
+        // Key repeat information is sent since version 4
+        state.seat = globals.bind(&queue_handle, 1..=5, ()).ok();
+        match (&state.seat, &state.data_device_manager) {
+            (Some(seat), Some(data_device_manager)) => {
+                state.data_device =
+                    Some(data_device_manager.get_data_device(seat, &queue_handle, ()));
+            }
+            (None, _) => ze_warn!("No Wayland seat available, input is disabled"),
+            _ => {}
+        }
+
+        if let (Some(seat), Some(text_input_manager)) = (&state.seat, &state.text_input_manager) {
+            state.text_input = Some(text_input_manager.get_text_input(seat, &queue_handle, ()));
+        }
+
+        // Outputs added later are bound when the registry announces them
+        globals.contents().with_list(|list| {
+            for global in list {
+                if global.interface == WlOutput::interface().name {
+                    state.add_output(
+                        globals.registry(),
+                        global.name,
+                        global.version,
+                        &queue_handle,
+                    );
+                }
+            }
+        });
+
+        // The first roundtrip gets the seat capabilities and the output properties,
+        // the second one the keymap of the keyboard created from the capabilities
+        for _ in 0..2 {
+            event_queue.roundtrip(&mut state).map_err(|error| {
+                ze_error!("Failed to dispatch Wayland events: {}", error);
+                Error::Unknown
+            })?;
+        }
+
+        Ok(Arc::new(WaylandPlatform {
+            connection,
+            inner: Mutex::new(Inner { event_queue, state }),
+            theme_watcher: ThemeWatcher::new(waker.clone()),
+            file_dialogs: FileDialogs::new(waker.clone()),
+            launcher_entry: Arc::new(LauncherEntry::new()),
+            power_monitor: PowerMonitor::new(),
+            waker,
+        }))
+    }
+}
+
+fn missing_global(error: BindError) -> Error {
+    ze_error!("Missing required Wayland global: {}", error);
+    Error::Unknown
+}
+
+impl Platform for WaylandPlatform {
+    fn poll_event(&self) -> Option<Message> {
+        let mut inner = self.inner.lock();
+        let Inner { event_queue, state } = &mut *inner;
+        if let Some(message) = state.message_queue.pop_front() {
+            return Some(message);
+        }
+
+        let _ = event_queue.flush();
+
+        // Reading fails with WouldBlock when there is nothing to read,
+        // protocol errors are reported when dispatching
+        if let Some(guard) = event_queue.prepare_read() {
+            let _ = guard.read();
+        }
+
+        if let Err(error) = event_queue.dispatch_pending(state) {
+            ze_error!("Failed to dispatch Wayland events: {}", error);
+        }
+
+        state.repeat_keys();
+        if let Some(theme) = self.theme_watcher.take_change() {
+            state
+                .message_queue
+                .push_back(Message::SystemThemeChanged(theme));
+        }
+        self.file_dialogs.send_closed(&mut state.message_queue);
+        state.message_queue.pop_front()
+    }
+
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        {
+            let mut inner = self.inner.lock();
+            let Inner { event_queue, state } = &mut *inner;
+            if let Some(message) = state.message_queue.pop_front() {
+                return Some(message);
+            }
+
+            // Key repeats are sent without any event from the compositor
+            let timeout = match state.next_key_repeat() {
+                Some(next) => {
+                    let repeat_timeout = next.saturating_duration_since(Instant::now());
+                    Some(timeout.map_or(repeat_timeout, |timeout| timeout.min(repeat_timeout)))
+                }
+                None => timeout,
+            };
+
+            // No read can be prepared while events are queued, they are dispatched right away
+            let _ = event_queue.flush();
+            if let Some(guard) = event_queue.prepare_read() {
+                self.waker.wait(guard.connection_fd().as_raw_fd(), timeout);
+            }
+        }
+
+        self.poll_event()
+    }
+
+    fn wake(&self) {
+        self.waker.wake();
+    }
+
+    fn create_window(
+        &self,
+        name: &str,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        flags: WindowFlags,
+    ) -> Result<Arc<dyn Window>, Error> {
+        let mut inner = self.inner.lock();
+        let Inner { event_queue, state } = &mut *inner;
+        let queue_handle = state.queue_handle.clone();
+
+        let surface = state.compositor.create_surface(&queue_handle, ());
+        let xdg_surface = state
+            .wm_base
+            .get_xdg_surface(&surface, &queue_handle, surface.id());
+        let toplevel = xdg_surface.get_toplevel(&queue_handle, surface.id());
+
+        if flags.contains(WindowFlagBits::Maximized) {
+            toplevel.set_maximized();
+        }
+
+        // Without the decoration protocol, the compositor decides whether it draws decorations
+        let decoration = state.decoration_manager.as_ref().map(|decoration_manager| {
+            let decoration =
+                decoration_manager.get_toplevel_decoration(&toplevel, &queue_handle, ());
+            decoration.set_mode(if flags.contains(WindowFlagBits::Borderless) {
+                zxdg_toplevel_decoration_v1::Mode::ClientSide
+            } else {
+                zxdg_toplevel_decoration_v1::Mode::ServerSide
+            });
+            decoration
+        });
+
+        let window = WaylandWindow::new(
+            self.connection.clone(),
+            queue_handle,
+            surface,
+            xdg_surface,
+            toplevel,
+            decoration,
+            state.activation.clone(),
+            self.launcher_entry.clone(),
+            flags.contains(WindowFlagBits::Resizable),
+            width,
+            height,
+            x,
+            y,
+        );
+        window.set_title(name);
+
+        state
+            .windows
+            .insert(window.surface_id(), Arc::downgrade(&window));
+
+        // The compositor sends the first configure once the surface is committed without buffer
+        window.show();
+        event_queue.roundtrip(state).map_err(|error| {
+            ze_error!("Failed to configure window: {}", error);
+            Error::Unknown
+        })?;
+
+        Ok(window)
+    }
+
+    fn create_system_cursor(&self, cursor: SystemCursor) -> Box<dyn Cursor> {
+        // Themes may only have the CSS names or the legacy X11 ones
+        let names: &'static [&'static str] = match cursor {
+            SystemCursor::No => &["not-allowed", "crossed_circle"],
+            SystemCursor::Crosshair => &["crosshair"],
+            SystemCursor::Ibeam => &["text", "xterm"],
+            SystemCursor::Arrow => &["default", "left_ptr"],
+            SystemCursor::Hand => &["pointer", "hand2"],
+            SystemCursor::SizeAll => &["all-scroll", "fleur"],
+            SystemCursor::SizeNorthEastSouthWest => &["nesw-resize", "bottom_left_corner"],
+            SystemCursor::SizeNorthSouth => &["ns-resize", "sb_v_double_arrow"],
+            SystemCursor::SizeNorthWestSouthEast => &["nwse-resize", "bottom_right_corner"],
+            SystemCursor::SizeWestEast => &["ew-resize", "sb_h_double_arrow"],
+            SystemCursor::Wait => &["wait", "watch"],
+            SystemCursor::WaitArrow => &["progress", "left_ptr_watch"],
+        };
+
+        Box::new(WaylandCursor::new(CursorShape::Theme(names)))
+    }
+
+    fn create_cursor_from_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        let inner = self.inner.lock();
+        let image = CursorImage::new(
+            &inner.state.shm,
+            &inner.state.queue_handle,
+            rgba,
+            width,
+            height,
+            hotspot,
+        )
+        .map_err(|error| {
+            ze_error!("Failed to create a {}x{} cursor: {}", width, height, error);
+            Error::Unknown
+        })?;
+
+        Ok(Box::new(WaylandCursor::new(CursorShape::Image(Arc::new(
+            image,
+        )))))
+    }
+
+    fn set_cursor(&self, cursor: Option<&dyn Cursor>) {
+        let shape = cursor.map(|cursor| {
+            cursor
+                .downcast_ref::<WaylandCursor>()
+                .unwrap()
+                .shape
+                .clone()
+        });
+
+        let mut inner = self.inner.lock();
+        if inner.state.current_cursor == shape {
+            return;
+        }
+
+        inner.state.current_cursor = shape;
+        inner.state.update_cursor();
+        let _ = self.connection.flush();
+    }
+
+    fn show_cursor(&self, show: bool) {
+        let mut inner = self.inner.lock();
+        if inner.state.cursor_shown == show {
+            return;
+        }
+
+        inner.state.cursor_shown = show;
+        inner.state.update_cursor();
+        let _ = self.connection.flush();
+    }
+
+    /// Compositors keep sending the pointer events to the window while a button is held,
+    /// Wayland has no other way to grab the pointer
+    fn capture_mouse(&self, _: &dyn Window) {}
+
+    fn release_mouse(&self) {}
+
+    fn mouse_position(&self) -> Point2<i32> {
+        self.inner.lock().state.mouse_position()
+    }
+
+    /// Wayland has no way to query the keyboard, the keys are the ones of our key events
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.inner.lock().state.is_key_down(key)
+    }
+
+    fn keyboard_modifiers(&self) -> Modifiers {
+        self.inner.lock().state.keyboard_modifiers()
+    }
+
+    fn monitor_count(&self) -> usize {
+        self.inner
+            .lock()
+            .state
+            .outputs
+            .iter()
+            .filter(|output| output.done)
+            .count()
+    }
+
+    fn monitor(&self, index: usize) -> Monitor {
+        self.inner
+            .lock()
+            .state
+            .outputs
+            .iter()
+            .filter(|output| output.done)
+            .nth(index)
+            .unwrap()
+            .monitor()
+    }
+
+    fn system_theme(&self) -> SystemTheme {
+        self.theme_watcher.theme()
+    }
+
+    fn power_status(&self) -> PowerStatus {
+        self.power_monitor.status()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        let inner = self.inner.lock();
+        if let Some((_, text)) = &inner.state.clipboard_source {
+            return Some(text.clone());
+        }
+
+        let offer = inner.state.selection_offer.as_ref()?;
+        let mime_type = {
+            let offered_mime_types = offer.data::<Mutex<Vec<String>>>()?.lock();
+            TEXT_MIME_TYPES
+                .iter()
+                .find(|mime_type| {
+                    offered_mime_types
+                        .iter()
+                        .any(|offered| offered == *mime_type)
+                })?
+                .to_string()
+        };
+
+        let text = receive_offer(&self.connection, offer, mime_type)?;
+        Some(String::from_utf8_lossy(&text).into_owned())
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        let mut inner = self.inner.lock();
+        let state = &mut inner.state;
+        let (data_device_manager, data_device) =
+            match (&state.data_device_manager, &state.data_device) {
+                (Some(data_device_manager), Some(data_device)) => {
+                    (data_device_manager, data_device)
+                }
+                _ => {
+                    ze_warn!("The compositor doesn't support the clipboard");
+                    return;
+                }
+            };
+
+        let source = data_device_manager.create_data_source(&state.queue_handle, ());
+        for mime_type in TEXT_MIME_TYPES {
+            source.offer(mime_type.to_string());
+        }
+
+        // Compositors only accept selections set in response to an input event
+        data_device.set_selection(Some(&source), state.last_serial);
+        if let Some((previous_source, _)) =
+            state.clipboard_source.replace((source, text.to_string()))
+        {
+            previous_source.destroy();
+        }
+        let _ = self.connection.flush();
+    }
+
+    fn open_file_dialog(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        self.file_dialogs.open(filters, multi_select)
+    }
+
+    fn save_file_dialog(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        self.file_dialogs.save(filters, default_name)
+    }
+}
+
+mod cursor;
+mod keymap;
+mod state;
+mod window;
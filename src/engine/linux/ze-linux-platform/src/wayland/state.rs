@@ -0,0 +1,1237 @@
+use crate::keysym::{convert_key_code, keysym_character_code};
+use crate::scancode::{convert_scan_code, XKB_KEYCODE_OFFSET};
+use crate::uri_list::{parse_uri_list, URI_LIST_MIME_TYPE};
+use crate::wayland::cursor::CursorShape;
+use crate::wayland::keymap::Keymap;
+use crate::wayland::window::WaylandWindow;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::AsFd;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::GlobalListContents;
+use wayland_client::protocol::wl_buffer::WlBuffer;
+use wayland_client::protocol::wl_compositor::WlCompositor;
+use wayland_client::protocol::wl_data_device::WlDataDevice;
+use wayland_client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use wayland_client::protocol::wl_data_offer::WlDataOffer;
+use wayland_client::protocol::wl_data_source::WlDataSource;
+use wayland_client::protocol::wl_keyboard::WlKeyboard;
+use wayland_client::protocol::wl_output::WlOutput;
+use wayland_client::protocol::wl_pointer::WlPointer;
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::protocol::wl_shm::WlShm;
+use wayland_client::protocol::wl_shm_pool::WlShmPool;
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::protocol::{
+    wl_data_device, wl_data_device_manager, wl_data_offer, wl_data_source, wl_keyboard, wl_output,
+    wl_pointer, wl_registry, wl_seat, wl_surface,
+};
+use wayland_client::{
+    delegate_noop, event_created_child, Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_cursor::CursorTheme;
+use wayland_protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
+use wayland_protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1;
+use wayland_protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3;
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
+use wayland_protocols::xdg::activation::v1::client::xdg_activation_token_v1;
+use wayland_protocols::xdg::activation::v1::client::xdg_activation_token_v1::XdgActivationTokenV1;
+use wayland_protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1;
+use wayland_protocols::xdg::decoration::zv1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
+use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1;
+use wayland_protocols::xdg::shell::client::xdg_surface::XdgSurface;
+use wayland_protocols::xdg::shell::client::xdg_toplevel::XdgToplevel;
+use wayland_protocols::xdg::shell::client::xdg_wm_base::XdgWmBase;
+use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use xkbcommon_dl::XkbCommon;
+use ze_core::maths::{Point2, RectI32};
+use ze_core::ze_warn;
+use ze_platform::{KeyCode, Message, Modifiers, Monitor, MouseButton, Window};
+
+/// DPI of an output with a scale of 1
+pub const DEFAULT_DPI: f32 = 96.0;
+
+/// Wayland doesn't report double clicks, two clicks closer than this are one
+const DOUBLE_CLICK_TIME: u32 = 500;
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Scroll distance sent by compositors for a step of a mouse wheel
+const AXIS_STEP: f64 = 10.0;
+
+/// Linux input event codes of the mouse buttons
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
+/// Maximum time waiting for the owner of an offer to send its content
+const OFFER_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Drag and drop actions are supported since version 3 of the data device manager
+const DRAG_ACTIONS_VERSION: u32 = 3;
+
+/// Cursor shown until the application sets one
+const DEFAULT_CURSOR: &[&str] = &["default", "left_ptr"];
+
+/// Mime types of text in the clipboard, by order of preference
+pub const TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+
+pub struct Output {
+    pub output: WlOutput,
+
+    /// Name of the registry global, used to know when the output is removed
+    pub name: u32,
+    pub bounds: RectI32,
+    pub scale: i32,
+
+    /// The output is usable once the compositor sent all its properties
+    pub done: bool,
+}
+
+impl Output {
+    pub fn monitor(&self) -> Monitor {
+        // Wayland doesn't expose the area not covered by panels
+        Monitor {
+            bounds: self.bounds,
+            work_bounds: self.bounds,
+            dpi: DEFAULT_DPI * self.scale as f32,
+        }
+    }
+}
+
+/// Key held down, Wayland leaves key repeat to clients
+struct KeyRepeat {
+    key: u32,
+    window: Weak<WaylandWindow>,
+    next: Instant,
+}
+
+/// Files dragged over one of our windows
+struct Drag {
+    offer: WlDataOffer,
+    window: Weak<WaylandWindow>,
+    position: Point2<i32>,
+}
+
+/// Everything modified by the Wayland events, owned by the event queue user
+pub struct State {
+    pub queue_handle: QueueHandle<State>,
+    pub xkb: &'static XkbCommon,
+    pub compositor: WlCompositor,
+
+    /// Shared memory of the cursors created from pixels
+    pub shm: WlShm,
+    pub wm_base: XdgWmBase,
+    pub decoration_manager: Option<ZxdgDecorationManagerV1>,
+    pub data_device_manager: Option<WlDataDeviceManager>,
+    pub seat: Option<WlSeat>,
+    pointer: Option<WlPointer>,
+
+    /// Optional, used to get the raw mouse motion
+    pub relative_pointer_manager: Option<ZwpRelativePointerManagerV1>,
+    relative_pointer: Option<ZwpRelativePointerV1>,
+    keyboard: Option<WlKeyboard>,
+    pub data_device: Option<WlDataDevice>,
+
+    /// Optional, used to compose text with input methods
+    pub text_input_manager: Option<ZwpTextInputManagerV3>,
+    pub text_input: Option<ZwpTextInputV3>,
+
+    /// Optional, used to request the attention of the user
+    pub activation: Option<XdgActivationV1>,
+
+    pub outputs: Vec<Output>,
+    pub windows: HashMap<ObjectId, Weak<WaylandWindow>>,
+    pub message_queue: VecDeque<Message>,
+
+    /// Serial of the last input event, needed to set the clipboard
+    pub last_serial: u32,
+
+    pointer_focus: Option<Weak<WaylandWindow>>,
+    pointer_position: Point2<i32>,
+    pointer_enter_serial: u32,
+    cursor_theme: Option<CursorTheme>,
+    cursor_surface: WlSurface,
+
+    /// Cursor shown over our windows, `None` when hidden
+    pub current_cursor: Option<CursorShape>,
+
+    /// Visibility set with `show_cursor`, independent of the current cursor
+    pub cursor_shown: bool,
+
+    /// Button, time and position of the last click
+    last_click: Option<(u32, u32, Point2<i32>)>,
+
+    keymap: Option<Keymap>,
+    keyboard_focus: Option<Weak<WaylandWindow>>,
+
+    /// Keys held while one of our windows has the keyboard focus
+    keys_down: HashSet<KeyCode>,
+
+    /// Keys repeated per second, 0 disables repeat
+    repeat_rate: i32,
+    repeat_delay: Duration,
+    key_repeat: Option<KeyRepeat>,
+
+    /// Source of the clipboard while we own it, with its text
+    pub clipboard_source: Option<(WlDataSource, String)>,
+
+    /// Clipboard content offered by another client
+    pub selection_offer: Option<WlDataOffer>,
+
+    drag: Option<Drag>,
+
+    text_input_focus: Option<Weak<WaylandWindow>>,
+
+    /// Text input state applied by the next done event
+    pending_preedit: Option<String>,
+    pending_commit: Option<String>,
+
+    /// A preedit text was sent and not cleared yet
+    composing: bool,
+}
+
+impl State {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        connection: &Connection,
+        queue_handle: QueueHandle<State>,
+        xkb: &'static XkbCommon,
+        compositor: WlCompositor,
+        shm: WlShm,
+        wm_base: XdgWmBase,
+        decoration_manager: Option<ZxdgDecorationManagerV1>,
+        data_device_manager: Option<WlDataDeviceManager>,
+    ) -> Self {
+        // The size is overridden by XCURSOR_SIZE when set
+        let cursor_theme = match CursorTheme::load(connection, shm.clone(), 24) {
+            Ok(theme) => Some(theme),
+            Err(_) => {
+                ze_warn!("Failed to load the cursor theme");
+                None
+            }
+        };
+        let cursor_surface = compositor.create_surface(&queue_handle, ());
+
+        Self {
+            queue_handle,
+            xkb,
+            compositor,
+            shm,
+            wm_base,
+            decoration_manager,
+            data_device_manager,
+            seat: None,
+            pointer: None,
+            relative_pointer_manager: None,
+            relative_pointer: None,
+            keyboard: None,
+            data_device: None,
+            text_input_manager: None,
+            text_input: None,
+            activation: None,
+            outputs: vec![],
+            windows: HashMap::new(),
+            message_queue: VecDeque::new(),
+            last_serial: 0,
+            pointer_focus: None,
+            pointer_position: Point2::new(0, 0),
+            pointer_enter_serial: 0,
+            cursor_theme,
+            cursor_surface,
+            current_cursor: Some(CursorShape::Theme(DEFAULT_CURSOR)),
+            cursor_shown: true,
+            last_click: None,
+            keymap: None,
+            keyboard_focus: None,
+            keys_down: HashSet::new(),
+            repeat_rate: 0,
+            repeat_delay: Duration::ZERO,
+            key_repeat: None,
+            clipboard_source: None,
+            selection_offer: None,
+            drag: None,
+            text_input_focus: None,
+            pending_preedit: None,
+            pending_commit: None,
+            composing: false,
+        }
+    }
+
+    fn window(&self, surface: &ObjectId) -> Option<(Arc<WaylandWindow>, Weak<WaylandWindow>)> {
+        let handle = self.windows.get(surface)?;
+        handle.upgrade().map(|window| (window, handle.clone()))
+    }
+
+    fn output(&self, output: &ObjectId) -> Option<&Output> {
+        self.outputs
+            .iter()
+            .find(|candidate| candidate.output.id() == *output)
+    }
+
+    pub fn add_output(
+        &mut self,
+        registry: &WlRegistry,
+        name: u32,
+        version: u32,
+        queue_handle: &QueueHandle<State>,
+    ) {
+        self.outputs.push(Output {
+            output: registry.bind(name, version.min(3), queue_handle, name),
+            name,
+            bounds: RectI32::new(0, 0, 0, 0),
+            scale: 1,
+            done: false,
+        });
+    }
+
+    /// Pointer position over the window it is on, Wayland doesn't report it elsewhere
+    pub fn mouse_position(&self) -> Point2<i32> {
+        match self.pointer_focus.as_ref().and_then(Weak::upgrade) {
+            Some(window) => screen_position(&window, self.pointer_position),
+            None => self.pointer_position,
+        }
+    }
+
+    /// Set the cursor of the pointer, Wayland cursors are per pointer focus
+    pub fn update_cursor(&mut self) {
+        let pointer = match &self.pointer {
+            Some(pointer) if self.pointer_focus.is_some() => pointer,
+            _ => return,
+        };
+
+        let cursor = self.current_cursor.as_ref().filter(|_| self.cursor_shown);
+        let names = match cursor {
+            Some(CursorShape::Theme(names)) => *names,
+            Some(CursorShape::Image(image)) => {
+                self.cursor_surface.attach(Some(&image.buffer), 0, 0);
+                self.cursor_surface
+                    .damage_buffer(0, 0, image.width as i32, image.height as i32);
+                self.cursor_surface.commit();
+                pointer.set_cursor(
+                    self.pointer_enter_serial,
+                    Some(&self.cursor_surface),
+                    image.hotspot.x as i32,
+                    image.hotspot.y as i32,
+                );
+                return;
+            }
+            None => {
+                pointer.set_cursor(self.pointer_enter_serial, None, 0, 0);
+                return;
+            }
+        };
+
+        let theme = match &mut self.cursor_theme {
+            Some(theme) => theme,
+            None => return,
+        };
+
+        let name = match names.iter().find(|name| theme.get_cursor(name).is_some()) {
+            Some(name) => name,
+            None => {
+                ze_warn!("Cursor {} missing in the cursor theme", names[0]);
+                return;
+            }
+        };
+        let cursor = theme.get_cursor(name).unwrap();
+
+        let image = &cursor[0];
+        let (width, height) = image.dimensions();
+        let (hotspot_x, hotspot_y) = image.hotspot();
+        self.cursor_surface.attach(Some(image), 0, 0);
+        self.cursor_surface
+            .damage_buffer(0, 0, width as i32, height as i32);
+        self.cursor_surface.commit();
+        pointer.set_cursor(
+            self.pointer_enter_serial,
+            Some(&self.cursor_surface),
+            hotspot_x as i32,
+            hotspot_y as i32,
+        );
+    }
+
+    /// Send the text committed by the input method, then the new preedit text
+    fn apply_text_input(&mut self) {
+        let window = match self.text_input_focus.clone() {
+            Some(window) => window,
+            None => return,
+        };
+
+        if let Some(text) = self.pending_commit.take() {
+            for character in text.chars().filter(|character| !character.is_control()) {
+                self.message_queue
+                    .push_back(Message::TextInput(window.clone(), character));
+            }
+        }
+
+        // The preedit text is cleared by done events without preedit
+        let preedit = self.pending_preedit.take().unwrap_or_default();
+        if self.composing || !preedit.is_empty() {
+            self.composing = !preedit.is_empty();
+            self.message_queue
+                .push_back(Message::TextComposition(window, preedit));
+        }
+    }
+
+    /// Time of the next key repeat, `None` if no key is repeated
+    pub fn next_key_repeat(&self) -> Option<Instant> {
+        self.key_repeat
+            .as_ref()
+            .filter(|_| self.repeat_rate > 0)
+            .map(|key_repeat| key_repeat.next)
+    }
+
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    pub fn keyboard_modifiers(&self) -> Modifiers {
+        self.keymap
+            .as_ref()
+            .map_or(Modifiers::empty(), |keymap| keymap.modifiers())
+    }
+
+    /// Send the key down events of the held key when its repeat delay elapsed
+    pub fn repeat_keys(&mut self) {
+        let now = Instant::now();
+        while let Some(key_repeat) = &mut self.key_repeat {
+            if key_repeat.next > now || self.repeat_rate <= 0 {
+                break;
+            }
+
+            key_repeat.next += Duration::from_secs(1) / self.repeat_rate as u32;
+            let (key, window) = (key_repeat.key, key_repeat.window.clone());
+            self.send_key_down(window, key, true);
+        }
+    }
+
+    fn send_key_down(&mut self, window: Weak<WaylandWindow>, key: u32, repeat: bool) {
+        let keymap = match &self.keymap {
+            Some(keymap) => keymap,
+            None => return,
+        };
+
+        let keysym = keymap.keysym(key);
+        let key_code = convert_key_code(keysym);
+        self.keys_down.insert(key_code);
+        self.message_queue.push_back(Message::KeyDown(
+            window.clone(),
+            key_code,
+            convert_scan_code(key),
+            keysym_character_code(keysym),
+            repeat,
+        ));
+
+        for character in keymap.text(key).chars() {
+            if !character.is_control() {
+                self.message_queue
+                    .push_back(Message::TextInput(window.clone(), character));
+            }
+        }
+    }
+
+    fn send_button_event(&mut self, serial: u32, time: u32, button: u32, pressed: bool) {
+        let (window, handle) = match self.pointer_focus.clone() {
+            Some(handle) => match handle.upgrade() {
+                Some(window) => (window, handle),
+                None => return,
+            },
+            None => return,
+        };
+
+        let button_code = button;
+        let button = match button {
+            BTN_LEFT => MouseButton::Left,
+            BTN_MIDDLE => MouseButton::Middle,
+            BTN_RIGHT => MouseButton::Right,
+            _ => return,
+        };
+
+        let local_position = self.pointer_position;
+        let position = screen_position(&window, local_position);
+        let message = if !pressed {
+            Message::MouseButtonUp(handle, button, position)
+        } else if button_code == BTN_LEFT
+            && self
+                .seat
+                .as_ref()
+                .is_some_and(|seat| window.hit_test(seat, serial, local_position))
+        {
+            return;
+        } else {
+            let double_click = match self.last_click {
+                Some((last_button, last_time, last_position)) => {
+                    last_button == button_code
+                        && time.wrapping_sub(last_time) < DOUBLE_CLICK_TIME
+                        && (position.x - last_position.x).abs() <= DOUBLE_CLICK_DISTANCE
+                        && (position.y - last_position.y).abs() <= DOUBLE_CLICK_DISTANCE
+                }
+                None => false,
+            };
+
+            if double_click {
+                self.last_click = None;
+                Message::MouseButtonDoubleClick(handle, button, position)
+            } else {
+                self.last_click = Some((button_code, time, position));
+                Message::MouseButtonDown(handle, button, position)
+            }
+        };
+
+        self.message_queue.push_back(message);
+    }
+
+    /// Apply the scale of the output to the windows on it
+    fn update_window_scales(&mut self, output: &ObjectId) {
+        let scale = match self.output(output) {
+            Some(output) => output.scale,
+            None => return,
+        };
+
+        for handle in self.windows.values() {
+            if let Some(window) = handle.upgrade() {
+                if window.output().as_ref() == Some(output) && window.set_scale(scale) {
+                    self.message_queue.push_back(Message::WindowDpiChanged(
+                        handle.clone(),
+                        DEFAULT_DPI * scale as f32,
+                    ));
+                    self.message_queue.push_back(Message::WindowResized(
+                        handle.clone(),
+                        window.width(),
+                        window.height(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Read the content of an offer, the owner writes it to the file descriptor we send until it
+/// closes it
+pub fn receive_offer(
+    connection: &Connection,
+    offer: &WlDataOffer,
+    mime_type: String,
+) -> Option<Vec<u8>> {
+    let (mut reader, writer) = UnixStream::pair().ok()?;
+    offer.receive(mime_type, writer.as_fd());
+    let _ = connection.flush();
+    drop(writer);
+
+    let mut data = vec![];
+    reader.set_read_timeout(Some(OFFER_TIMEOUT)).ok()?;
+    if let Err(error) = reader.read_to_end(&mut data) {
+        ze_warn!("Offer owner didn't answer: {}", error);
+        return None;
+    }
+
+    Some(data)
+}
+
+/// Position of a point of the window relative to the desktop, as far as we know it
+pub fn screen_position(window: &WaylandWindow, position: Point2<i32>) -> Point2<i32> {
+    let window_position = window.position();
+    Point2::new(
+        window_position.x + position.x,
+        window_position.y + position.y,
+    )
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                state.add_output(registry, name, version, queue_handle);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(index) = state.outputs.iter().position(|output| output.name == name) {
+                    let output = state.outputs.remove(index);
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+                    if output.done {
+                        state.message_queue.push_back(Message::MonitorsChanged);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for State {
+    fn event(
+        state: &mut Self,
+        proxy: &WlOutput,
+        event: wl_output::Event,
+        _: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = match state
+            .outputs
+            .iter_mut()
+            .find(|output| output.output == *proxy)
+        {
+            Some(output) => output,
+            None => return,
+        };
+
+        match event {
+            wl_output::Event::Geometry { x, y, .. } => {
+                output.bounds.x = x;
+                output.bounds.y = y;
+            }
+            wl_output::Event::Mode {
+                flags: WEnum::Value(flags),
+                width,
+                height,
+                ..
+            } if flags.contains(wl_output::Mode::Current) => {
+                output.bounds.width = width;
+                output.bounds.height = height;
+            }
+            wl_output::Event::Scale { factor } => output.scale = factor.max(1),
+            // Sent once all the properties of a new or changed output were sent
+            wl_output::Event::Done => {
+                output.done = true;
+                state.update_window_scales(&proxy.id());
+                state.message_queue.push_back(Message::MonitorsChanged);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for State {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        let capabilities = match event {
+            wl_seat::Event::Capabilities {
+                capabilities: WEnum::Value(capabilities),
+            } => capabilities,
+            _ => return,
+        };
+
+        let has_pointer = capabilities.contains(wl_seat::Capability::Pointer);
+        if has_pointer && state.pointer.is_none() {
+            let pointer = seat.get_pointer(queue_handle, ());
+            state.relative_pointer = state
+                .relative_pointer_manager
+                .as_ref()
+                .map(|manager| manager.get_relative_pointer(&pointer, queue_handle, ()));
+            state.pointer = Some(pointer);
+        } else if !has_pointer {
+            if let Some(relative_pointer) = state.relative_pointer.take() {
+                relative_pointer.destroy();
+            }
+            if let Some(pointer) = state.pointer.take() {
+                pointer.release();
+            }
+            state.pointer_focus = None;
+        }
+
+        let has_keyboard = capabilities.contains(wl_seat::Capability::Keyboard);
+        if has_keyboard && state.keyboard.is_none() {
+            state.keyboard = Some(seat.get_keyboard(queue_handle, ()));
+        } else if !has_keyboard {
+            if let Some(keyboard) = state.keyboard.take() {
+                keyboard.release();
+            }
+            state.keyboard_focus = None;
+            state.key_repeat = None;
+        }
+    }
+}
+
+/// Relative motion is only sent while the pointer is over one of our surfaces
+impl Dispatch<ZwpRelativePointerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwpRelativePointerV1,
+        event: zwp_relative_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            dx_unaccel,
+            dy_unaccel,
+            ..
+        } = event
+        {
+            state.message_queue.push_back(Message::MouseMotionRaw(
+                dx_unaccel as f32,
+                dy_unaccel as f32,
+            ));
+        }
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, ()> for State {
+    fn event(
+        state: &mut Self,
+        text_input: &ZwpTextInputV3,
+        event: zwp_text_input_v3::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_text_input_v3::Event::Enter { surface } => {
+                if let Some((window, handle)) = state.window(&surface.id()) {
+                    window.set_text_input(Some(text_input.clone()));
+                    state.text_input_focus = Some(handle);
+                }
+            }
+            zwp_text_input_v3::Event::Leave { .. } => {
+                let window = match state.text_input_focus.clone() {
+                    Some(window) => window,
+                    None => return,
+                };
+
+                if let Some(window) = window.upgrade() {
+                    window.set_text_input(None);
+                }
+
+                // Unfinished compositions are dropped
+                state.pending_preedit = None;
+                state.pending_commit = None;
+                state.apply_text_input();
+                state.text_input_focus = None;
+            }
+            zwp_text_input_v3::Event::PreeditString { text, .. } => {
+                state.pending_preedit = text;
+            }
+            zwp_text_input_v3::Event::CommitString { text } => {
+                state.pending_commit = text;
+            }
+            zwp_text_input_v3::Event::Done { .. } => state.apply_text_input(),
+            _ => {}
+        }
+    }
+}
+
+/// Tokens are requested to activate the surface they are created for
+impl Dispatch<XdgActivationTokenV1, WlSurface> for State {
+    fn event(
+        state: &mut Self,
+        token: &XdgActivationTokenV1,
+        event: xdg_activation_token_v1::Event,
+        surface: &WlSurface,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_activation_token_v1::Event::Done { token: name } = event {
+            if let Some(activation) = &state.activation {
+                activation.activate(name, surface);
+            }
+            token.destroy();
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_pointer::Event::Enter {
+                serial,
+                surface,
+                surface_x,
+                surface_y,
+            } => {
+                if let Some((window, handle)) = state.window(&surface.id()) {
+                    state.pointer_focus = Some(handle.clone());
+                    state.pointer_position = window.surface_to_window(surface_x, surface_y);
+                    state.pointer_enter_serial = serial;
+                    window.set_cursor_position(state.pointer_position);
+                    state.update_cursor();
+                    state.message_queue.push_back(Message::MouseEntered(handle));
+                }
+            }
+            wl_pointer::Event::Leave { .. } => {
+                if let Some(handle) = state.pointer_focus.take() {
+                    state.message_queue.push_back(Message::MouseLeft(handle));
+                }
+            }
+            wl_pointer::Event::Motion {
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                if let Some(window) = state.pointer_focus.as_ref().and_then(Weak::upgrade) {
+                    state.pointer_position = window.surface_to_window(surface_x, surface_y);
+                    window.set_cursor_position(state.pointer_position);
+                }
+            }
+            wl_pointer::Event::Button {
+                serial,
+                time,
+                button,
+                state: WEnum::Value(button_state),
+            } => {
+                state.last_serial = serial;
+                state.send_button_event(
+                    serial,
+                    time,
+                    button,
+                    button_state == wl_pointer::ButtonState::Pressed,
+                );
+            }
+            wl_pointer::Event::Axis {
+                axis: WEnum::Value(axis),
+                value,
+                ..
+            } => {
+                let (window, handle) = match state.pointer_focus.clone() {
+                    Some(handle) => match handle.upgrade() {
+                        Some(window) => (window, handle),
+                        None => return,
+                    },
+                    None => return,
+                };
+
+                let position = screen_position(&window, state.pointer_position);
+                let delta = (value / AXIS_STEP) as f32;
+
+                // Wayland scroll values are positive when scrolling down or right
+                state.message_queue.push_back(match axis {
+                    wl_pointer::Axis::VerticalScroll => {
+                        Message::MouseWheel(handle, -delta, position)
+                    }
+                    wl_pointer::Axis::HorizontalScroll => {
+                        Message::MouseWheelH(handle, delta, position)
+                    }
+                    _ => return,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Keymap {
+                format: WEnum::Value(wl_keyboard::KeymapFormat::XkbV1),
+                fd,
+                size,
+            } => {
+                let mut keymap = String::new();
+                if let Err(error) = File::from(fd).take(size as u64).read_to_string(&mut keymap) {
+                    ze_warn!("Failed to read the keymap: {}", error);
+                    return;
+                }
+
+                // The keymap is null terminated
+                state.keymap = Keymap::new(state.xkb, keymap.trim_end_matches('\0'));
+                if state.keymap.is_none() {
+                    ze_warn!("Failed to compile the keymap");
+                }
+            }
+            wl_keyboard::Event::Enter {
+                serial,
+                surface,
+                keys,
+            } => {
+                state.last_serial = serial;
+
+                // Keys already held when the focus is gained
+                if let Some(keymap) = &state.keymap {
+                    for key in keys.chunks_exact(4) {
+                        let key = u32::from_ne_bytes([key[0], key[1], key[2], key[3]]);
+                        let keysym = keymap.keysym(key + XKB_KEYCODE_OFFSET);
+                        state.keys_down.insert(convert_key_code(keysym));
+                    }
+                }
+
+                state.keyboard_focus = match state.window(&surface.id()) {
+                    Some((window, handle)) => {
+                        if window.set_focused(true) {
+                            state
+                                .message_queue
+                                .push_back(Message::WindowFocusGained(handle.clone()));
+                        }
+                        Some(handle)
+                    }
+                    None => None,
+                };
+            }
+            wl_keyboard::Event::Leave { surface, .. } => {
+                if let Some((window, handle)) = state.window(&surface.id()) {
+                    if window.set_focused(false) {
+                        state
+                            .message_queue
+                            .push_back(Message::WindowFocusLost(handle));
+                    }
+                }
+                state.keyboard_focus = None;
+                state.keys_down.clear();
+                state.key_repeat = None;
+            }
+            wl_keyboard::Event::Key {
+                serial,
+                key,
+                state: WEnum::Value(key_state),
+                ..
+            } => {
+                state.last_serial = serial;
+                let window = match state.keyboard_focus.clone() {
+                    Some(window) => window,
+                    None => return,
+                };
+
+                let key = key + XKB_KEYCODE_OFFSET;
+                if key_state == wl_keyboard::KeyState::Pressed {
+                    state.send_key_down(window.clone(), key, false);
+                    if state
+                        .keymap
+                        .as_ref()
+                        .is_some_and(|keymap| keymap.repeats(key))
+                    {
+                        state.key_repeat = Some(KeyRepeat {
+                            key,
+                            window,
+                            next: Instant::now() + state.repeat_delay,
+                        });
+                    }
+                } else {
+                    if let Some(keymap) = &state.keymap {
+                        let keysym = keymap.keysym(key);
+                        let key_code = convert_key_code(keysym);
+                        state.keys_down.remove(&key_code);
+                        state.message_queue.push_back(Message::KeyUp(
+                            window,
+                            key_code,
+                            convert_scan_code(key),
+                            keysym_character_code(keysym),
+                            false,
+                        ));
+                    }
+
+                    if matches!(&state.key_repeat, Some(key_repeat) if key_repeat.key == key) {
+                        state.key_repeat = None;
+                    }
+                }
+            }
+            wl_keyboard::Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                if let Some(keymap) = &mut state.keymap {
+                    keymap.update_modifiers(mods_depressed, mods_latched, mods_locked, group);
+                }
+            }
+            wl_keyboard::Event::RepeatInfo { rate, delay } => {
+                state.repeat_rate = rate;
+                state.repeat_delay = Duration::from_millis(delay.max(0) as u64);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSurface, ()> for State {
+    fn event(
+        state: &mut Self,
+        surface: &WlSurface,
+        event: wl_surface::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let output = match event {
+            wl_surface::Event::Enter { output } => output.id(),
+            _ => return,
+        };
+
+        let scale = match state.output(&output) {
+            Some(output) => output.scale,
+            None => return,
+        };
+
+        if let Some((window, handle)) = state.window(&surface.id()) {
+            if window.enter_output(output, scale) {
+                state.message_queue.push_back(Message::WindowDpiChanged(
+                    handle.clone(),
+                    DEFAULT_DPI * scale as f32,
+                ));
+                state.message_queue.push_back(Message::WindowResized(
+                    handle,
+                    window.width(),
+                    window.height(),
+                ));
+            }
+        }
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for State {
+    fn event(
+        _: &mut Self,
+        wm_base: &XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+/// The user data of the xdg objects is the id of their surface
+impl Dispatch<XdgSurface, ObjectId> for State {
+    fn event(
+        state: &mut Self,
+        _: &XdgSurface,
+        event: xdg_surface::Event,
+        surface: &ObjectId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let serial = match event {
+            xdg_surface::Event::Configure { serial } => serial,
+            _ => return,
+        };
+
+        if let Some((window, handle)) = state.window(surface) {
+            if window.send_surface_configure(serial) {
+                state.message_queue.push_back(Message::WindowResized(
+                    handle,
+                    window.width(),
+                    window.height(),
+                ));
+            }
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, ObjectId> for State {
+    fn event(
+        state: &mut Self,
+        _: &XdgToplevel,
+        event: xdg_toplevel::Event,
+        surface: &ObjectId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let (window, handle) = match state.window(surface) {
+            Some(window) => window,
+            None => return,
+        };
+
+        match event {
+            xdg_toplevel::Event::Configure { width, height, .. } => {
+                window.send_toplevel_configure(width, height)
+            }
+            xdg_toplevel::Event::Close => state
+                .message_queue
+                .push_back(Message::WindowCloseRequested(handle)),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlDataDevice, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &WlDataDevice,
+        event: wl_data_device::Event,
+        _: &(),
+        connection: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_data_device::Event::Selection { id } => {
+                if let Some(offer) = state.selection_offer.take() {
+                    offer.destroy();
+                }
+                state.selection_offer = id;
+            }
+            wl_data_device::Event::Enter {
+                serial,
+                surface,
+                x,
+                y,
+                id: Some(offer),
+            } => {
+                let (window, handle) = match state.window(&surface.id()) {
+                    Some(window) => window,
+                    None => {
+                        offer.destroy();
+                        return;
+                    }
+                };
+
+                // Only lists of files are accepted
+                let accepted = offer
+                    .data::<Mutex<Vec<String>>>()
+                    .is_some_and(|mime_types| {
+                        mime_types
+                            .lock()
+                            .iter()
+                            .any(|mime_type| mime_type == URI_LIST_MIME_TYPE)
+                    });
+                offer.accept(serial, accepted.then(|| URI_LIST_MIME_TYPE.to_string()));
+                if accepted && offer.version() >= DRAG_ACTIONS_VERSION {
+                    let copy = wl_data_device_manager::DndAction::Copy;
+                    offer.set_actions(copy, copy);
+                }
+
+                if let Some(drag) = state.drag.take() {
+                    drag.offer.destroy();
+                }
+                state.drag = Some(Drag {
+                    offer,
+                    window: handle,
+                    position: screen_position(&window, window.surface_to_window(x, y)),
+                });
+            }
+            wl_data_device::Event::Motion { x, y, .. } => {
+                if let Some(drag) = &mut state.drag {
+                    if let Some(window) = drag.window.upgrade() {
+                        drag.position = screen_position(&window, window.surface_to_window(x, y));
+                    }
+                }
+            }
+            wl_data_device::Event::Leave => {
+                if let Some(drag) = state.drag.take() {
+                    drag.offer.destroy();
+                }
+            }
+            wl_data_device::Event::Drop => {
+                let drag = match state.drag.take() {
+                    Some(drag) => drag,
+                    None => return,
+                };
+
+                let files = receive_offer(connection, &drag.offer, URI_LIST_MIME_TYPE.to_string())
+                    .map(|data| parse_uri_list(&data))
+                    .unwrap_or_default();
+                if drag.offer.version() >= DRAG_ACTIONS_VERSION {
+                    drag.offer.finish();
+                }
+                drag.offer.destroy();
+
+                if !files.is_empty() {
+                    state.message_queue.push_back(Message::FilesDropped(
+                        drag.window,
+                        files,
+                        drag.position,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, WlDataDevice, [
+        wl_data_device::EVT_DATA_OFFER_OPCODE => (WlDataOffer, Mutex::new(vec![])),
+    ]);
+}
+
+/// The user data of offers are the mime types they are available in
+impl Dispatch<WlDataOffer, Mutex<Vec<String>>> for State {
+    fn event(
+        _: &mut Self,
+        _: &WlDataOffer,
+        event: wl_data_offer::Event,
+        mime_types: &Mutex<Vec<String>>,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_data_offer::Event::Offer { mime_type } = event {
+            mime_types.lock().push(mime_type);
+        }
+    }
+}
+
+impl Dispatch<WlDataSource, ()> for State {
+    fn event(
+        state: &mut Self,
+        source: &WlDataSource,
+        event: wl_data_source::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let (clipboard_source, text) = match &state.clipboard_source {
+            Some((clipboard_source, text)) if clipboard_source == source => {
+                (clipboard_source, text)
+            }
+            _ => {
+                source.destroy();
+                return;
+            }
+        };
+
+        match event {
+            wl_data_source::Event::Send { fd, .. } => {
+                if let Err(error) = File::from(fd).write_all(text.as_bytes()) {
+                    ze_warn!("Failed to send the clipboard content: {}", error);
+                }
+            }
+            wl_data_source::Event::Cancelled => {
+                clipboard_source.destroy();
+                state.clipboard_source = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(State: WlCompositor);
+delegate_noop!(State: ignore WlShm);
+delegate_noop!(State: WlShmPool);
+delegate_noop!(State: ignore WlBuffer);
+delegate_noop!(State: WlDataDeviceManager);
+delegate_noop!(State: ZxdgDecorationManagerV1);
+delegate_noop!(State: ZwpRelativePointerManagerV1);
+delegate_noop!(State: ZwpTextInputManagerV3);
+delegate_noop!(State: XdgActivationV1);
+delegate_noop!(State: ignore ZxdgToplevelDecorationV1);
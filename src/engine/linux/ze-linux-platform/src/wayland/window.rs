@@ -0,0 +1,420 @@
+use crate::launcher_entry::LauncherEntry;
+use crate::wayland::state::State;
+use parking_lot::Mutex;
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::Arc;
+use wayland_client::backend::ObjectId;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::{Connection, Proxy, QueueHandle};
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
+use wayland_protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1;
+use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1;
+use wayland_protocols::xdg::shell::client::xdg_surface::XdgSurface;
+use wayland_protocols::xdg::shell::client::xdg_toplevel::{ResizeEdge, XdgToplevel};
+use ze_core::maths::Point2;
+use ze_platform::{HitTest, HitTestCallback, TaskbarProgressState, Window};
+
+/// Window backed by a xdg-shell toplevel
+///
+/// Sizes are in pixels, the surface is scaled by the scale of the output it is on.
+/// Wayland doesn't let clients know or choose where their windows are, the position is only
+/// the one requested by the application.
+pub struct WaylandWindow {
+    connection: Connection,
+    queue_handle: QueueHandle<State>,
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    toplevel: XdgToplevel,
+    decoration: Option<ZxdgToplevelDecorationV1>,
+    activation: Option<XdgActivationV1>,
+    launcher_entry: Arc<LauncherEntry>,
+    resizable: bool,
+    width: AtomicU32,
+    height: AtomicU32,
+    x: AtomicI32,
+    y: AtomicI32,
+    scale: AtomicI32,
+
+    /// The window has the keyboard focus
+    focused: AtomicBool,
+
+    /// Output the surface entered last, its scale is the one used by the window
+    output: Mutex<Option<ObjectId>>,
+
+    /// Size sent by the last toplevel configure, applied when the surface configure is acked
+    pending_size: Mutex<(i32, i32)>,
+    hit_test_callback: Mutex<Option<HitTestCallback>>,
+
+    /// Text input of the seat while the window has the text input focus
+    text_input: Mutex<Option<ZwpTextInputV3>>,
+
+    /// Last position given to `set_ime_position`, sent again when the text input is enabled
+    ime_position: Mutex<Option<Point2<i32>>>,
+
+    /// Last pointer position over the surface, Wayland doesn't report it outside of our surfaces
+    cursor_position: Mutex<Point2<i32>>,
+    min_size: Mutex<Option<(u32, u32)>>,
+    max_size: Mutex<Option<(u32, u32)>>,
+}
+
+impl WaylandWindow {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        connection: Connection,
+        queue_handle: QueueHandle<State>,
+        surface: WlSurface,
+        xdg_surface: XdgSurface,
+        toplevel: XdgToplevel,
+        decoration: Option<ZxdgToplevelDecorationV1>,
+        activation: Option<XdgActivationV1>,
+        launcher_entry: Arc<LauncherEntry>,
+        resizable: bool,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+    ) -> Arc<WaylandWindow> {
+        let window = Arc::new(WaylandWindow {
+            connection,
+            queue_handle,
+            surface,
+            xdg_surface,
+            toplevel,
+            decoration,
+            activation,
+            launcher_entry,
+            resizable,
+            width: AtomicU32::new(width),
+            height: AtomicU32::new(height),
+            x: AtomicI32::new(x),
+            y: AtomicI32::new(y),
+            scale: AtomicI32::new(1),
+            focused: AtomicBool::new(false),
+            output: Mutex::new(None),
+            pending_size: Mutex::new((0, 0)),
+            hit_test_callback: Mutex::new(None),
+            text_input: Mutex::new(None),
+            ime_position: Mutex::new(None),
+            cursor_position: Mutex::new(Point2::new(0, 0)),
+            min_size: Mutex::new(None),
+            max_size: Mutex::new(None),
+        });
+        window.update_geometry();
+        window
+    }
+
+    pub fn surface_id(&self) -> ObjectId {
+        self.surface.id()
+    }
+
+    pub fn scale(&self) -> i32 {
+        self.scale.load(Ordering::SeqCst)
+    }
+
+    pub fn output(&self) -> Option<ObjectId> {
+        self.output.lock().clone()
+    }
+
+    /// Convert a position relative to the surface to pixels relative to the window
+    pub fn surface_to_window(&self, x: f64, y: f64) -> Point2<i32> {
+        let scale = self.scale() as f64;
+        Point2::new((x * scale) as i32, (y * scale) as i32)
+    }
+
+    pub fn set_cursor_position(&self, position: Point2<i32>) {
+        *self.cursor_position.lock() = position;
+    }
+
+    pub fn send_toplevel_configure(&self, width: i32, height: i32) {
+        *self.pending_size.lock() = (width, height);
+    }
+
+    /// Ack a surface configure, returns true if the compositor changed the window size
+    pub fn send_surface_configure(&self, serial: u32) -> bool {
+        self.xdg_surface.ack_configure(serial);
+
+        // A null size lets us choose it
+        let (width, height) = *self.pending_size.lock();
+        if width <= 0 || height <= 0 {
+            return false;
+        }
+
+        let scale = self.scale();
+        self.set_pixel_size((width * scale) as u32, (height * scale) as u32)
+    }
+
+    /// Set the output the surface is on, returns true if its scale changed
+    pub fn enter_output(&self, output: ObjectId, scale: i32) -> bool {
+        *self.output.lock() = Some(output);
+        self.set_scale(scale)
+    }
+
+    /// Change the buffer scale, the surface size is kept so the size in pixels changes
+    pub fn set_scale(&self, scale: i32) -> bool {
+        let old_scale = self.scale.swap(scale, Ordering::SeqCst);
+        if old_scale == scale {
+            return false;
+        }
+
+        let width = self.width() as i32 / old_scale * scale;
+        let height = self.height() as i32 / old_scale * scale;
+        self.width.store(width as u32, Ordering::SeqCst);
+        self.height.store(height as u32, Ordering::SeqCst);
+        self.surface.set_buffer_scale(scale);
+        self.update_geometry();
+        true
+    }
+
+    fn set_pixel_size(&self, width: u32, height: u32) -> bool {
+        let old_width = self.width.swap(width, Ordering::SeqCst);
+        let old_height = self.height.swap(height, Ordering::SeqCst);
+        old_width != width || old_height != height
+    }
+
+    /// Returns true if the focus changed
+    pub fn set_focused(&self, focused: bool) -> bool {
+        self.focused.swap(focused, Ordering::SeqCst) != focused
+    }
+
+    /// Send the window size to the compositor, in surface coordinates
+    fn update_geometry(&self) {
+        let scale = self.scale();
+        let width = (self.width() as i32 / scale).max(1);
+        let height = (self.height() as i32 / scale).max(1);
+        self.xdg_surface.set_window_geometry(0, 0, width, height);
+        if self.resizable {
+            // A null size removes the limit, the minimum is rounded up so it is never smaller
+            let (min_width, min_height) = self.min_size.lock().map_or((0, 0), |(w, h)| {
+                (
+                    w.div_ceil(scale as u32) as i32,
+                    h.div_ceil(scale as u32) as i32,
+                )
+            });
+            let (max_width, max_height) = self.max_size.lock().map_or((0, 0), |(w, h)| {
+                ((w as i32 / scale).max(1), (h as i32 / scale).max(1))
+            });
+            self.toplevel.set_min_size(min_width, min_height);
+            self.toplevel.set_max_size(max_width, max_height);
+        } else {
+            self.toplevel.set_min_size(width, height);
+            self.toplevel.set_max_size(width, height);
+        }
+        let _ = self.connection.flush();
+    }
+
+    /// Enable the input method when the window gets the text input focus,
+    /// disable it when the window loses it
+    pub fn set_text_input(&self, text_input: Option<ZwpTextInputV3>) {
+        let mut current_text_input = self.text_input.lock();
+        if let Some(text_input) = current_text_input.take() {
+            text_input.disable();
+            text_input.commit();
+        }
+
+        if let Some(text_input) = &text_input {
+            text_input.enable();
+            if let Some(position) = *self.ime_position.lock() {
+                self.set_cursor_rectangle(text_input, position);
+            }
+            text_input.commit();
+        }
+
+        *current_text_input = text_input;
+    }
+
+    fn set_cursor_rectangle(&self, text_input: &ZwpTextInputV3, position: Point2<i32>) {
+        let scale = self.scale();
+        text_input.set_cursor_rectangle(position.x / scale, position.y / scale, 1, 1);
+    }
+
+    /// Ask the compositor to move or resize the window if the hit test callback returns
+    /// a decoration, returns false if the click must be handled by the application
+    pub fn hit_test(&self, seat: &WlSeat, serial: u32, position: Point2<i32>) -> bool {
+        let hit_test_callback = self.hit_test_callback.lock();
+        let callback = match hit_test_callback.as_ref() {
+            Some(callback) => callback,
+            None => return false,
+        };
+
+        let edge = match callback(position) {
+            HitTest::Client | HitTest::MaximizeButton => return false,
+            HitTest::Caption => {
+                self.toplevel._move(seat, serial);
+                let _ = self.connection.flush();
+                return true;
+            }
+            HitTest::TopLeft => ResizeEdge::TopLeft,
+            HitTest::Top => ResizeEdge::Top,
+            HitTest::TopRight => ResizeEdge::TopRight,
+            HitTest::Right => ResizeEdge::Right,
+            HitTest::BottomRight => ResizeEdge::BottomRight,
+            HitTest::Bottom => ResizeEdge::Bottom,
+            HitTest::BottomLeft => ResizeEdge::BottomLeft,
+            HitTest::Left => ResizeEdge::Left,
+        };
+
+        self.toplevel.resize(seat, serial, edge);
+        let _ = self.connection.flush();
+        true
+    }
+}
+
+impl Drop for WaylandWindow {
+    fn drop(&mut self) {
+        if let Some(decoration) = &self.decoration {
+            decoration.destroy();
+        }
+        self.toplevel.destroy();
+        self.xdg_surface.destroy();
+        self.surface.destroy();
+        let _ = self.connection.flush();
+    }
+}
+
+impl Window for WaylandWindow {
+    fn set_position(&self, position: Point2<i32>) {
+        self.x.store(position.x, Ordering::SeqCst);
+        self.y.store(position.y, Ordering::SeqCst);
+    }
+
+    fn set_size(&self, width: u32, height: u32) {
+        self.set_pixel_size(width, height);
+        self.update_geometry();
+    }
+
+    /// Applied with the next commit of the surface, like the rest of its state
+    fn set_min_size(&self, size: Option<(u32, u32)>) {
+        *self.min_size.lock() = size;
+        self.update_geometry();
+    }
+
+    fn set_max_size(&self, size: Option<(u32, u32)>) {
+        *self.max_size.lock() = size;
+        self.update_geometry();
+    }
+
+    fn set_title(&self, title: &str) {
+        self.toplevel.set_title(title.to_string());
+        let _ = self.connection.flush();
+    }
+
+    /// Wayland has no icon protocol, compositors show the icon of the application desktop entry
+    fn set_icon(&self, _: u32, _: u32, _: &[u8]) {}
+
+    /// Surfaces are mapped when the renderer attaches their first buffer
+    fn show(&self) {
+        self.surface.commit();
+        let _ = self.connection.flush();
+    }
+
+    /// Committing without a buffer unmaps the surface
+    fn close(&self) {
+        self.surface.attach(None, 0, 0);
+        self.surface.commit();
+        let _ = self.connection.flush();
+    }
+
+    fn minimize(&self) {
+        self.toplevel.set_minimized();
+        let _ = self.connection.flush();
+    }
+
+    fn maximize(&self) {
+        self.toplevel.set_maximized();
+        let _ = self.connection.flush();
+    }
+
+    /// Only maximized windows can be restored, minimized ones are restored by the user
+    fn restore(&self) {
+        self.toplevel.unset_maximized();
+        let _ = self.connection.flush();
+    }
+
+    /// Clients can't take the focus on Wayland, the compositor decides which window has it
+    fn focus(&self) {}
+
+    /// Wayland has no protocol to change the opacity of a window, the compositor blends it
+    /// using the alpha of its buffers
+    fn set_opacity(&self, _: f32) {}
+
+    /// Stacking is decided by the compositor, xdg-shell can't keep a window on top
+    fn set_always_on_top(&self, _: bool) {}
+
+    /// The progress is shown on the launcher icon of the application, shared by its windows
+    fn set_taskbar_progress(&self, state: TaskbarProgressState, fraction: f32) {
+        self.launcher_entry.set_progress(state, fraction);
+    }
+
+    /// Compositors mark the window as demanding attention when activated with a token not
+    /// created from an input event, without the activation protocol nothing is done
+    fn request_attention(&self) {
+        let activation = match &self.activation {
+            Some(activation) if !self.is_focused() => activation,
+            _ => return,
+        };
+
+        let token = activation.get_activation_token(&self.queue_handle, self.surface.clone());
+        token.set_surface(&self.surface);
+        token.commit();
+        let _ = self.connection.flush();
+    }
+
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>) {
+        *self.hit_test_callback.lock() = callback;
+    }
+
+    fn set_ime_position(&self, position: Point2<i32>) {
+        *self.ime_position.lock() = Some(position);
+        if let Some(text_input) = self.text_input.lock().as_ref() {
+            self.set_cursor_rectangle(text_input, position);
+            text_input.commit();
+            let _ = self.connection.flush();
+        }
+    }
+
+    fn handle(&self) -> RawWindowHandle {
+        let mut handle = WaylandWindowHandle::empty();
+        handle.surface = self.surface.id().as_ptr() as *mut _;
+        RawWindowHandle::Wayland(handle)
+    }
+
+    fn display_handle(&self) -> RawDisplayHandle {
+        let mut handle = WaylandDisplayHandle::empty();
+        handle.display = self.connection.backend().display_ptr() as *mut _;
+        RawDisplayHandle::Wayland(handle)
+    }
+
+    fn width(&self) -> u32 {
+        self.width.load(Ordering::SeqCst)
+    }
+
+    fn height(&self) -> u32 {
+        self.height.load(Ordering::SeqCst)
+    }
+
+    fn position(&self) -> Point2<i32> {
+        Point2::<i32>::new(self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
+    }
+
+    fn cursor_position(&self) -> Point2<i32> {
+        *self.cursor_position.lock()
+    }
+
+    /// The compositor doesn't tell clients if they are minimized
+    fn is_minimized(&self) -> bool {
+        false
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+
+    /// Wayland output scales are integers
+    fn dpi_scale(&self) -> f32 {
+        self.scale() as f32
+    }
+}
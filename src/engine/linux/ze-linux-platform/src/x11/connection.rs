@@ -0,0 +1,117 @@
+use crate::uri_list::URI_LIST_MIME_TYPE;
+use std::ffi::CString;
+use x11_dl::xlib::{Atom, Display, False, Xlib};
+
+/// Connection to the X server shared by the platform, its windows and cursors
+pub struct Connection {
+    pub xlib: Xlib,
+    pub display: *mut Display,
+    pub atoms: Atoms,
+}
+
+// Xlib is initialized with XInitThreads before the display is opened
+unsafe impl Send for Connection {}
+unsafe impl Sync for Connection {}
+
+impl Connection {
+    pub fn new(xlib: Xlib, display: *mut Display) -> Self {
+        let atoms = Atoms::new(&xlib, display);
+        Self {
+            xlib,
+            display,
+            atoms,
+        }
+    }
+
+    pub fn flush(&self) {
+        unsafe {
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+pub struct Atoms {
+    pub WM_PROTOCOLS: Atom,
+    pub WM_DELETE_WINDOW: Atom,
+    pub UTF8_STRING: Atom,
+    pub CLIPBOARD: Atom,
+    pub TARGETS: Atom,
+    pub RESOURCE_MANAGER: Atom,
+
+    /// Property of our windows receiving the clipboard content
+    pub ZE_CLIPBOARD: Atom,
+    pub _NET_WM_NAME: Atom,
+    pub _NET_WM_ICON: Atom,
+    pub _NET_WM_STATE: Atom,
+    pub _NET_WM_STATE_MAXIMIZED_HORZ: Atom,
+    pub _NET_WM_STATE_MAXIMIZED_VERT: Atom,
+    pub _NET_WM_STATE_ABOVE: Atom,
+    pub _NET_WM_STATE_DEMANDS_ATTENTION: Atom,
+    pub _NET_WM_WINDOW_OPACITY: Atom,
+    pub _NET_WM_MOVERESIZE: Atom,
+    pub _NET_ACTIVE_WINDOW: Atom,
+    pub _NET_WORKAREA: Atom,
+    pub _MOTIF_WM_HINTS: Atom,
+    pub XdndAware: Atom,
+    pub XdndPosition: Atom,
+    pub XdndStatus: Atom,
+    pub XdndLeave: Atom,
+    pub XdndDrop: Atom,
+    pub XdndFinished: Atom,
+    pub XdndSelection: Atom,
+    pub XdndActionCopy: Atom,
+    pub TEXT_URI_LIST: Atom,
+
+    /// Property of our windows receiving the list of dropped files
+    pub ZE_DROP: Atom,
+}
+
+impl Atoms {
+    fn new(xlib: &Xlib, display: *mut Display) -> Self {
+        let intern = |name: &str| {
+            let name = CString::new(name).unwrap();
+            unsafe { (xlib.XInternAtom)(display, name.as_ptr(), False) }
+        };
+
+        Self {
+            WM_PROTOCOLS: intern("WM_PROTOCOLS"),
+            WM_DELETE_WINDOW: intern("WM_DELETE_WINDOW"),
+            UTF8_STRING: intern("UTF8_STRING"),
+            CLIPBOARD: intern("CLIPBOARD"),
+            TARGETS: intern("TARGETS"),
+            RESOURCE_MANAGER: intern("RESOURCE_MANAGER"),
+            ZE_CLIPBOARD: intern("ZE_CLIPBOARD"),
+            _NET_WM_NAME: intern("_NET_WM_NAME"),
+            _NET_WM_ICON: intern("_NET_WM_ICON"),
+            _NET_WM_STATE: intern("_NET_WM_STATE"),
+            _NET_WM_STATE_MAXIMIZED_HORZ: intern("_NET_WM_STATE_MAXIMIZED_HORZ"),
+            _NET_WM_STATE_MAXIMIZED_VERT: intern("_NET_WM_STATE_MAXIMIZED_VERT"),
+            _NET_WM_STATE_ABOVE: intern("_NET_WM_STATE_ABOVE"),
+            _NET_WM_STATE_DEMANDS_ATTENTION: intern("_NET_WM_STATE_DEMANDS_ATTENTION"),
+            _NET_WM_WINDOW_OPACITY: intern("_NET_WM_WINDOW_OPACITY"),
+            _NET_WM_MOVERESIZE: intern("_NET_WM_MOVERESIZE"),
+            _NET_ACTIVE_WINDOW: intern("_NET_ACTIVE_WINDOW"),
+            _NET_WORKAREA: intern("_NET_WORKAREA"),
+            _MOTIF_WM_HINTS: intern("_MOTIF_WM_HINTS"),
+            XdndAware: intern("XdndAware"),
+            XdndPosition: intern("XdndPosition"),
+            XdndStatus: intern("XdndStatus"),
+            XdndLeave: intern("XdndLeave"),
+            XdndDrop: intern("XdndDrop"),
+            XdndFinished: intern("XdndFinished"),
+            XdndSelection: intern("XdndSelection"),
+            XdndActionCopy: intern("XdndActionCopy"),
+            TEXT_URI_LIST: intern(URI_LIST_MIME_TYPE),
+            ZE_DROP: intern("ZE_DROP"),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::x11::connection::Connection;
+use std::sync::Arc;
+use x11_dl::xlib;
+use ze_platform::Cursor;
+
+pub struct X11Cursor {
+    connection: Arc<Connection>,
+    pub cursor: xlib::Cursor,
+}
+
+impl X11Cursor {
+    pub fn new(connection: Arc<Connection>, cursor: xlib::Cursor) -> Self {
+        Self { connection, cursor }
+    }
+}
+
+impl Drop for X11Cursor {
+    fn drop(&mut self) {
+        unsafe {
+            (self.connection.xlib.XFreeCursor)(self.connection.display, self.cursor);
+        }
+    }
+}
+
+impl Cursor for X11Cursor {}
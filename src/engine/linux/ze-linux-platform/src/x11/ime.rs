@@ -0,0 +1,189 @@
+use crate::x11::connection::Connection;
+use parking_lot::Mutex;
+use std::ffi::{c_void, CStr};
+use std::mem;
+use std::os::raw::{c_short, c_ulong};
+use std::ptr::null_mut;
+use x11_dl::xlib;
+use ze_core::maths::Point2;
+
+/// Text being composed with the input method, edited by the preedit callbacks
+#[derive(Default)]
+pub struct Preedit {
+    text: Vec<char>,
+
+    /// The text changed since it was last sent to the application
+    changed: bool,
+}
+
+impl Preedit {
+    /// Text to send to the application, `None` if it didn't change
+    pub fn take_changes(&mut self) -> Option<String> {
+        if !mem::take(&mut self.changed) {
+            return None;
+        }
+
+        Some(self.text.iter().collect())
+    }
+}
+
+/// Create an input context letting us display the preedit text if the input method supports it,
+/// otherwise the input method displays it in its own window
+///
+/// The preedit must outlive the input context.
+pub unsafe fn create_input_context(
+    connection: &Connection,
+    input_method: xlib::XIM,
+    window: xlib::Window,
+    preedit: &Mutex<Preedit>,
+) -> xlib::XIC {
+    let xlib = &connection.xlib;
+    let client_data = preedit as *const Mutex<Preedit> as xlib::XPointer;
+    let callback = |callback: unsafe extern "C" fn(
+        xlib::XIC,
+        xlib::XPointer,
+        xlib::XPointer,
+    ) -> xlib::Bool| xlib::XICCallback {
+        client_data,
+        callback: Some(callback),
+    };
+
+    // Callbacks are copied by Xlib
+    let start = callback(preedit_start);
+    let done = callback(preedit_done);
+    let draw = callback(preedit_draw);
+    let caret = callback(preedit_caret);
+    let preedit_attributes = (xlib.XVaCreateNestedList)(
+        0,
+        xlib::XNPreeditStartCallback_0.as_ptr(),
+        &start as *const xlib::XICCallback,
+        xlib::XNPreeditDoneCallback_0.as_ptr(),
+        &done as *const xlib::XICCallback,
+        xlib::XNPreeditDrawCallback_0.as_ptr(),
+        &draw as *const xlib::XICCallback,
+        xlib::XNPreeditCaretCallback_0.as_ptr(),
+        &caret as *const xlib::XICCallback,
+        null_mut::<c_void>(),
+    );
+
+    // Variadic arguments must have the C types, XIMStyle is an unsigned long
+    let input_context = (xlib.XCreateIC)(
+        input_method,
+        xlib::XNInputStyle_0.as_ptr(),
+        (xlib::XIMPreeditCallbacks | xlib::XIMStatusNothing) as c_ulong,
+        xlib::XNClientWindow_0.as_ptr(),
+        window,
+        xlib::XNFocusWindow_0.as_ptr(),
+        window,
+        xlib::XNPreeditAttributes_0.as_ptr(),
+        preedit_attributes,
+        null_mut::<c_void>(),
+    );
+    (xlib.XFree)(preedit_attributes);
+
+    if !input_context.is_null() {
+        return input_context;
+    }
+
+    (xlib.XCreateIC)(
+        input_method,
+        xlib::XNInputStyle_0.as_ptr(),
+        (xlib::XIMPreeditNothing | xlib::XIMStatusNothing) as c_ulong,
+        xlib::XNClientWindow_0.as_ptr(),
+        window,
+        xlib::XNFocusWindow_0.as_ptr(),
+        window,
+        null_mut::<c_void>(),
+    )
+}
+
+/// Move the candidates of the input method, the position is relative to the focus window
+pub unsafe fn set_spot_location(
+    connection: &Connection,
+    input_context: xlib::XIC,
+    position: Point2<i32>,
+) {
+    let xlib = &connection.xlib;
+    let spot = xlib::XPoint {
+        x: position.x as c_short,
+        y: position.y as c_short,
+    };
+
+    let preedit_attributes = (xlib.XVaCreateNestedList)(
+        0,
+        xlib::XNSpotLocation_0.as_ptr(),
+        &spot as *const xlib::XPoint,
+        null_mut::<c_void>(),
+    );
+    (xlib.XSetICValues)(
+        input_context,
+        xlib::XNPreeditAttributes_0.as_ptr(),
+        preedit_attributes,
+        null_mut::<c_void>(),
+    );
+    (xlib.XFree)(preedit_attributes);
+}
+
+unsafe fn preedit<'a>(client_data: xlib::XPointer) -> &'a Mutex<Preedit> {
+    &*(client_data as *const Mutex<Preedit>)
+}
+
+/// Returns the maximum length of the preedit text, -1 for no limit
+unsafe extern "C" fn preedit_start(
+    _: xlib::XIC,
+    client_data: xlib::XPointer,
+    _: xlib::XPointer,
+) -> xlib::Bool {
+    let mut preedit = preedit(client_data).lock();
+    preedit.text.clear();
+    preedit.changed = true;
+    -1
+}
+
+unsafe extern "C" fn preedit_done(
+    _: xlib::XIC,
+    client_data: xlib::XPointer,
+    _: xlib::XPointer,
+) -> xlib::Bool {
+    let mut preedit = preedit(client_data).lock();
+    preedit.text.clear();
+    preedit.changed = true;
+    0
+}
+
+/// Replace a range of the preedit text
+unsafe extern "C" fn preedit_draw(
+    _: xlib::XIC,
+    client_data: xlib::XPointer,
+    call_data: xlib::XPointer,
+) -> xlib::Bool {
+    let draw = &*(call_data as *const xlib::XIMPreeditDrawCallbackStruct);
+    let text = match draw.text.as_ref() {
+        Some(text) if text.encoding_is_wchar != 0 => return 0,
+        // Only the feedback of the range changed
+        Some(text) if text.string.multi_byte.is_null() => return 0,
+        // Multi-byte strings are in the locale encoding, expected to be UTF-8
+        Some(text) => CStr::from_ptr(text.string.multi_byte)
+            .to_string_lossy()
+            .chars()
+            .take(text.length as usize)
+            .collect(),
+        None => vec![],
+    };
+
+    let mut preedit = preedit(client_data).lock();
+    let first = (draw.chg_first.max(0) as usize).min(preedit.text.len());
+    let last = (first + draw.chg_length.max(0) as usize).min(preedit.text.len());
+    preedit.text.splice(first..last, text);
+    preedit.changed = true;
+    0
+}
+
+/// The caret isn't displayed
+unsafe extern "C" fn preedit_caret(
+    _: xlib::XIC,
+    _: xlib::XPointer,
+    _: xlib::XPointer,
+) -> xlib::Bool {
+    0
+}
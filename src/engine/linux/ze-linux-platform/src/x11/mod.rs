@@ -0,0 +1,1452 @@
+use crate::file_dialog::FileDialogs;
+use crate::keysym::{convert_key_code, key_code_keysym, keysym_character_code};
+use crate::launcher_entry::LauncherEntry;
+use crate::pixels::premultiplied_argb;
+use crate::power::PowerMonitor;
+use crate::scancode::convert_scan_code;
+use crate::theme::ThemeWatcher;
+use crate::uri_list::parse_uri_list;
+use crate::waker::Waker;
+use crate::x11::connection::Connection;
+use crate::x11::cursor::X11Cursor;
+use crate::x11::ime::{create_input_context, Preedit};
+use crate::x11::window::X11Window;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::{c_void, CString};
+use std::mem;
+use std::os::raw::{c_char, c_int, c_long, c_uint};
+use std::ptr::{self, null, null_mut};
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use x11_dl::xcursor::Xcursor;
+use x11_dl::xinput2;
+use x11_dl::xinput2::XInput2;
+use x11_dl::xlib;
+use x11_dl::xlib::Xlib;
+use x11_dl::xrandr;
+use x11_dl::xrandr::Xrandr;
+use ze_core::maths::{Point2, RectI32};
+use ze_core::{ze_error, ze_warn};
+use ze_platform::{
+    Cursor, Error, FileDialogId, FileFilter, KeyCode, Message, ModifierBits, Modifiers, Monitor,
+    MouseButton, Platform, PowerStatus, SystemCursor, SystemTheme, Window, WindowFlagBits,
+    WindowFlags,
+};
+
+/// DPI used when the desktop environment doesn't set `Xft.dpi`
+const DEFAULT_DPI: f32 = 96.0;
+
+/// X11 doesn't report double clicks, two clicks closer than this are one
+const DOUBLE_CLICK_TIME: xlib::Time = 500;
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Maximum time waiting for the clipboard owner to send its content
+const CLIPBOARD_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// `_MOTIF_WM_HINTS` flag telling the decorations field is set
+const MWM_HINTS_DECORATIONS: c_long = 1 << 1;
+
+/// Version of the drag and drop protocol we implement
+const XDND_VERSION: c_long = 5;
+
+// Cursor font shapes, used when Xcursor or the cursor theme is missing
+const XC_X_CURSOR: c_uint = 0;
+const XC_BOTTOM_LEFT_CORNER: c_uint = 12;
+const XC_BOTTOM_RIGHT_CORNER: c_uint = 14;
+const XC_CROSSHAIR: c_uint = 34;
+const XC_FLEUR: c_uint = 52;
+const XC_HAND2: c_uint = 60;
+const XC_LEFT_PTR: c_uint = 68;
+const XC_SB_H_DOUBLE_ARROW: c_uint = 108;
+const XC_SB_V_DOUBLE_ARROW: c_uint = 116;
+const XC_WATCH: c_uint = 150;
+const XC_XTERM: c_uint = 152;
+
+/// Drop in progress on one of our windows
+struct PendingDrop {
+    source: xlib::Window,
+    position: Point2<i32>,
+}
+
+/// Platform implemented over Xlib, loaded at runtime so the engine starts without X11
+pub struct X11Platform {
+    connection: Arc<Connection>,
+
+    /// RandR and its first event type, used to get the monitors and be notified of their changes
+    xrandr: Option<(Xrandr, c_int)>,
+    xcursor: Option<Xcursor>,
+
+    /// XInput2 and its extension opcode, used to get raw mouse motion
+    xinput2: Option<(XInput2, c_int)>,
+    root: xlib::Window,
+
+    /// Input method used to translate key events to text, null if none is available
+    input_method: xlib::XIM,
+
+    /// Hidden window owning the clipboard selection and receiving the content of other ones
+    clipboard_window: xlib::Window,
+    invisible_cursor: xlib::Cursor,
+    current_cursor: Mutex<xlib::Cursor>,
+
+    /// Visibility set with `show_cursor`, windows use the invisible cursor while hidden
+    cursor_shown: AtomicBool,
+
+    window_map: Mutex<HashMap<xlib::Window, Weak<X11Window>>>,
+    message_queue: Mutex<VecDeque<Message>>,
+    monitors: Mutex<Vec<Monitor>>,
+
+    /// Text of the clipboard while we own it, sent to the clients requesting it
+    clipboard_text: Mutex<Option<String>>,
+
+    /// Keys held down, X11 reports auto-repeats as new key presses
+    pressed_keys: Mutex<HashSet<c_uint>>,
+
+    /// Button, time and position of the last click
+    last_click: Mutex<Option<(c_uint, xlib::Time, Point2<i32>)>>,
+
+    pending_drop: Mutex<Option<PendingDrop>>,
+    waker: Arc<Waker>,
+    theme_watcher: ThemeWatcher,
+    file_dialogs: FileDialogs,
+    launcher_entry: Arc<LauncherEntry>,
+    power_monitor: PowerMonitor,
+}
+
+// The input method is only used by the thread polling events
+unsafe impl Send for X11Platform {}
+unsafe impl Sync for X11Platform {}
+
+impl X11Platform {
+    /// Connect to the X server, fails if Xlib is missing or if there is no display
+    pub fn new() -> Result<Arc<X11Platform>, Error> {
+        let xlib = Xlib::open().map_err(|error| {
+            ze_error!("Failed to load Xlib: {}", error);
+            Error::Unknown
+        })?;
+
+        let waker = Arc::new(Waker::new().map_err(|error| {
+            ze_error!("Failed to create the event loop waker: {}", error);
+            Error::Unknown
+        })?);
+
+        unsafe {
+            (xlib.XInitThreads)();
+            let display = (xlib.XOpenDisplay)(null());
+            if display.is_null() {
+                ze_error!("Failed to open X11 display");
+                return Err(Error::Unknown);
+            }
+
+            let connection = Arc::new(Connection::new(xlib, display));
+            let xlib = &connection.xlib;
+            let root = (xlib.XDefaultRootWindow)(display);
+
+            // Only report key releases when keys are actually released
+            (xlib.XkbSetDetectableAutoRepeat)(display, xlib::True, null_mut());
+
+            // DPI changes are notified through the resource manager property of the root window
+            (xlib.XSelectInput)(display, root, xlib::PropertyChangeMask);
+            (xlib.XrmInitialize)();
+
+            (xlib.XSetLocaleModifiers)(CString::default().as_ptr());
+            let input_method = (xlib.XOpenIM)(display, null_mut(), null_mut(), null_mut());
+            if input_method.is_null() {
+                ze_warn!("No X input method available, text input is limited to Latin-1");
+            }
+
+            let clipboard_window = (xlib.XCreateSimpleWindow)(display, root, 0, 0, 1, 1, 0, 0, 0);
+
+            let invisible_cursor = {
+                let data = [0 as c_char; 8];
+                let pixmap = (xlib.XCreateBitmapFromData)(display, root, data.as_ptr(), 8, 8);
+                let mut color = mem::zeroed();
+                let cursor = (xlib.XCreatePixmapCursor)(
+                    display, pixmap, pixmap, &mut color, &mut color, 0, 0,
+                );
+                (xlib.XFreePixmap)(display, pixmap);
+                cursor
+            };
+
+            let platform = Arc::new(X11Platform {
+                connection: connection.clone(),
+                xrandr: open_xrandr(&connection, root),
+                xcursor: Xcursor::open().ok(),
+                xinput2: open_xinput2(&connection, root),
+                root,
+                input_method,
+                clipboard_window,
+                invisible_cursor,
+                current_cursor: Default::default(),
+                cursor_shown: AtomicBool::new(true),
+                window_map: Default::default(),
+                message_queue: Mutex::new(VecDeque::new()),
+                monitors: Default::default(),
+                clipboard_text: Default::default(),
+                pressed_keys: Default::default(),
+                last_click: Default::default(),
+                pending_drop: Default::default(),
+                theme_watcher: ThemeWatcher::new(waker.clone()),
+                file_dialogs: FileDialogs::new(waker.clone()),
+                launcher_entry: Arc::new(LauncherEntry::new()),
+                power_monitor: PowerMonitor::new(),
+                waker,
+            });
+
+            platform.update_monitors();
+            Ok(platform)
+        }
+    }
+
+    /// Query the monitors again, returns true if they changed
+    /// Mouse position on the root window and state of the modifier keys and mouse buttons
+    fn query_pointer(&self) -> (Point2<i32>, c_uint) {
+        let mut root = 0;
+        let mut child = 0;
+        let mut x = 0;
+        let mut y = 0;
+        let mut window_x = 0;
+        let mut window_y = 0;
+        let mut mask = 0;
+        unsafe {
+            (self.connection.xlib.XQueryPointer)(
+                self.connection.display,
+                self.root,
+                &mut root,
+                &mut child,
+                &mut x,
+                &mut y,
+                &mut window_x,
+                &mut window_y,
+                &mut mask,
+            );
+        }
+        (Point2::<i32>::new(x, y), mask)
+    }
+
+    fn update_monitors(&self) -> bool {
+        let mut bounds = vec![];
+        let display = self.connection.display;
+        unsafe {
+            if let Some((xrandr, _)) = &self.xrandr {
+                let mut count = 0;
+                let infos = (xrandr.XRRGetMonitors)(display, self.root, xlib::True, &mut count);
+                if !infos.is_null() {
+                    for info in slice::from_raw_parts(infos, count as usize) {
+                        bounds.push(RectI32::new(info.x, info.y, info.width, info.height));
+                    }
+                    (xrandr.XRRFreeMonitors)(infos);
+                }
+            }
+
+            // Without RandR the whole screen is a single monitor
+            if bounds.is_empty() {
+                let xlib = &self.connection.xlib;
+                let screen = (xlib.XDefaultScreen)(display);
+                bounds.push(RectI32::new(
+                    0,
+                    0,
+                    (xlib.XDisplayWidth)(display, screen),
+                    (xlib.XDisplayHeight)(display, screen),
+                ));
+            }
+        }
+
+        let dpi = self.dpi();
+        let work_area = self.work_area();
+        let monitors: Vec<Monitor> = bounds
+            .into_iter()
+            .map(|bounds| Monitor {
+                bounds,
+                work_bounds: work_area
+                    .map(|work_area| intersect(bounds, work_area))
+                    .unwrap_or(bounds),
+                dpi,
+            })
+            .collect();
+
+        let mut current_monitors = self.monitors.lock();
+        let changed = *current_monitors != monitors;
+        *current_monitors = monitors;
+        changed
+    }
+
+    /// X11 has no per-monitor DPI, desktop environments set a global `Xft.dpi` resource
+    fn dpi(&self) -> f32 {
+        let resources = match unsafe {
+            self.read_property::<u8>(self.root, self.atoms().RESOURCE_MANAGER, xlib::XA_STRING)
+        } {
+            Some(resources) => resources,
+            None => return DEFAULT_DPI,
+        };
+
+        let xlib = &self.connection.xlib;
+        let mut dpi = DEFAULT_DPI;
+        unsafe {
+            let resources = CString::new(resources).unwrap_or_default();
+            let database = (xlib.XrmGetStringDatabase)(resources.as_ptr());
+            if database.is_null() {
+                return dpi;
+            }
+
+            let name = CString::new("Xft.dpi").unwrap();
+            let class = CString::new("Xft.Dpi").unwrap();
+            let mut ty = null_mut();
+            let mut value: xlib::XrmValue = mem::zeroed();
+            if (xlib.XrmGetResource)(database, name.as_ptr(), class.as_ptr(), &mut ty, &mut value)
+                != 0
+                && !value.addr.is_null()
+            {
+                let value = std::ffi::CStr::from_ptr(value.addr).to_string_lossy();
+                dpi = value.trim().parse().unwrap_or(DEFAULT_DPI);
+            }
+
+            (xlib.XrmDestroyDatabase)(database);
+        }
+
+        dpi
+    }
+
+    /// Desktop area not covered by panels, spanning all monitors
+    fn work_area(&self) -> Option<RectI32> {
+        let work_area = unsafe {
+            self.read_property::<c_long>(self.root, self.atoms()._NET_WORKAREA, xlib::XA_CARDINAL)?
+        };
+
+        // One area per virtual desktop, they are usually the same
+        match work_area[..] {
+            [x, y, width, height, ..] => Some(RectI32::new(
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+            )),
+            _ => None,
+        }
+    }
+
+    fn atoms(&self) -> &connection::Atoms {
+        &self.connection.atoms
+    }
+
+    /// Cursor defined on the windows, the invisible one while hidden by `show_cursor`
+    fn window_cursor(&self) -> xlib::Cursor {
+        if self.cursor_shown.load(Ordering::SeqCst) {
+            *self.current_cursor.lock()
+        } else {
+            self.invisible_cursor
+        }
+    }
+
+    /// X11 cursors are per window
+    fn define_cursor(&self, cursor: xlib::Cursor) {
+        for window in self.window_map.lock().values().filter_map(Weak::upgrade) {
+            unsafe {
+                (self.connection.xlib.XDefineCursor)(self.connection.display, window.id(), cursor);
+            }
+        }
+        self.connection.flush();
+    }
+
+    /// Read a property of type `ty`, `T` must match the property format
+    /// (`u8` for 8, `c_long` for 32)
+    unsafe fn read_property<T: Copy>(
+        &self,
+        window: xlib::Window,
+        property: xlib::Atom,
+        ty: xlib::Atom,
+    ) -> Option<Vec<T>> {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut item_count = 0;
+        let mut bytes_after = 0;
+        let mut data = null_mut();
+
+        let xlib = &self.connection.xlib;
+        let result = (xlib.XGetWindowProperty)(
+            self.connection.display,
+            window,
+            property,
+            0,
+            c_long::MAX,
+            xlib::False,
+            ty,
+            &mut actual_type,
+            &mut actual_format,
+            &mut item_count,
+            &mut bytes_after,
+            &mut data,
+        );
+
+        if result != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+
+        let items = if actual_type == ty {
+            Some(slice::from_raw_parts(data as *const T, item_count as usize).to_vec())
+        } else {
+            None
+        };
+
+        (xlib.XFree)(data as *mut c_void);
+        items
+    }
+
+    fn process_event(&self, event: &mut xlib::XEvent) {
+        let ty = event.get_type();
+        if let Some((xrandr, first_event)) = &self.xrandr {
+            if ty == first_event + xrandr::RRScreenChangeNotify {
+                unsafe { (xrandr.XRRUpdateConfiguration)(event) };
+                self.send_monitors_changed();
+                return;
+            }
+        }
+
+        unsafe {
+            match ty {
+                xlib::GenericEvent => {
+                    return self.process_generic_event(&mut event.generic_event_cookie)
+                }
+                xlib::SelectionRequest => return self.send_selection(&event.selection_request),
+                xlib::SelectionClear => {
+                    *self.clipboard_text.lock() = None;
+                    return;
+                }
+                xlib::PropertyNotify if event.property.window == self.root => {
+                    // The work area changes when panels are moved or resized
+                    if event.property.atom == self.atoms()._NET_WORKAREA {
+                        self.send_monitors_changed();
+                    } else if event.property.atom == self.atoms().RESOURCE_MANAGER {
+                        self.update_monitors();
+                        let dpi = self.monitors.lock()[0].dpi;
+                        let mut message_queue = self.message_queue.lock();
+                        for handle in self.window_map.lock().values() {
+                            if let Some(window) = handle.upgrade() {
+                                window.set_dpi_scale(dpi / DEFAULT_DPI);
+                            }
+                            message_queue.push_back(Message::WindowDpiChanged(handle.clone(), dpi));
+                        }
+                    }
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let (window, handle) = {
+            let window_map = self.window_map.lock();
+            match window_map.get(&unsafe { event.any.window }) {
+                Some(handle) => match handle.upgrade() {
+                    Some(window) => (window, handle.clone()),
+                    None => return,
+                },
+                None => return,
+            }
+        };
+
+        unsafe {
+            match ty {
+                xlib::ClientMessage => {
+                    self.process_client_message(&window, handle, &event.client_message)
+                }
+                xlib::SelectionNotify
+                    if event.selection.selection == self.atoms().XdndSelection =>
+                {
+                    self.finish_drop(&window, handle, &event.selection)
+                }
+                xlib::ConfigureNotify if window.send_configure_event(&event.configure) => {
+                    self.message_queue.lock().push_back(Message::WindowResized(
+                        handle,
+                        window.width(),
+                        window.height(),
+                    ));
+                }
+                xlib::ButtonPress => self.send_button_event(&window, handle, &event.button, true),
+                xlib::ButtonRelease => {
+                    self.send_button_event(&window, handle, &event.button, false)
+                }
+                // Grabs, e.g while capturing the mouse, report crossings the mouse didn't make
+                xlib::EnterNotify | xlib::LeaveNotify
+                    if event.crossing.mode == xlib::NotifyNormal =>
+                {
+                    self.message_queue
+                        .lock()
+                        .push_back(if ty == xlib::EnterNotify {
+                            Message::MouseEntered(handle)
+                        } else {
+                            Message::MouseLeft(handle)
+                        });
+                }
+                xlib::KeyPress => self.send_key_event(&window, handle, &mut event.key, true),
+                xlib::KeyRelease => self.send_key_event(&window, handle, &mut event.key, false),
+                xlib::FocusIn | xlib::FocusOut => {
+                    self.send_focus_event(&window, handle, &event.focus_change)
+                }
+                xlib::MapNotify => window.set_minimized(false),
+                xlib::UnmapNotify => window.set_minimized(true),
+                _ => (),
+            }
+        }
+    }
+
+    unsafe fn send_focus_event(
+        &self,
+        window: &X11Window,
+        handle: Weak<X11Window>,
+        event: &xlib::XFocusChangeEvent,
+    ) {
+        let focused = event.type_ == xlib::FocusIn;
+        if !window.input_context().is_null() {
+            if focused {
+                (self.connection.xlib.XSetICFocus)(window.input_context());
+            } else {
+                (self.connection.xlib.XUnsetICFocus)(window.input_context());
+            }
+        }
+
+        // Keyboard grabs, e.g by the window manager while switching windows, don't move the focus
+        if event.mode == xlib::NotifyGrab || event.mode == xlib::NotifyUngrab {
+            return;
+        }
+
+        if window.set_focused(focused) {
+            self.message_queue.lock().push_back(if focused {
+                Message::WindowFocusGained(handle)
+            } else {
+                Message::WindowFocusLost(handle)
+            });
+        }
+    }
+
+    fn send_monitors_changed(&self) {
+        if self.update_monitors() {
+            self.message_queue
+                .lock()
+                .push_back(Message::MonitorsChanged);
+        }
+    }
+
+    /// Handle the XInput2 raw motion events, sent for the whole screen
+    unsafe fn process_generic_event(&self, cookie: &mut xlib::XGenericEventCookie) {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        match &self.xinput2 {
+            Some((_, opcode)) if cookie.extension == *opcode => {}
+            _ => return,
+        }
+
+        if (xlib.XGetEventData)(display, cookie) == 0 {
+            return;
+        }
+
+        if cookie.evtype == xinput2::XI_RawMotion {
+            let event = &*(cookie.data as *const xinput2::XIRawEvent);
+            let (dx, dy) = raw_motion(event);
+            if dx != 0.0 || dy != 0.0 {
+                self.message_queue
+                    .lock()
+                    .push_back(Message::MouseMotionRaw(dx as f32, dy as f32));
+            }
+        }
+
+        (xlib.XFreeEventData)(display, cookie);
+    }
+
+    /// Handle the window manager and drag and drop messages
+    unsafe fn process_client_message(
+        &self,
+        window: &X11Window,
+        handle: Weak<X11Window>,
+        message: &xlib::XClientMessageEvent,
+    ) {
+        let atoms = self.atoms();
+        let source = message.data.get_long(0) as xlib::Window;
+        if message.message_type == atoms.WM_PROTOCOLS {
+            if source == atoms.WM_DELETE_WINDOW {
+                self.message_queue
+                    .lock()
+                    .push_back(Message::WindowCloseRequested(handle));
+            }
+        } else if message.message_type == atoms.XdndPosition {
+            // Root coordinates of the pointer, packed in a single long
+            let position = message.data.get_long(2);
+            *self.pending_drop.lock() = Some(PendingDrop {
+                source,
+                position: Point2::new((position >> 16) as i16 as i32, position as i16 as i32),
+            });
+
+            self.send_xdnd_message(
+                source,
+                atoms.XdndStatus,
+                [
+                    window.id() as c_long,
+                    1,
+                    0,
+                    0,
+                    atoms.XdndActionCopy as c_long,
+                ],
+            );
+        } else if message.message_type == atoms.XdndLeave {
+            *self.pending_drop.lock() = None;
+        } else if message.message_type == atoms.XdndDrop {
+            // The list of files is sent as the content of the XDND selection
+            (self.connection.xlib.XConvertSelection)(
+                self.connection.display,
+                atoms.XdndSelection,
+                atoms.TEXT_URI_LIST,
+                atoms.ZE_DROP,
+                window.id(),
+                message.data.get_long(2) as xlib::Time,
+            );
+            self.connection.flush();
+        }
+    }
+
+    /// Send the files of the XDND selection and tell the source the drop is done
+    unsafe fn finish_drop(
+        &self,
+        window: &X11Window,
+        handle: Weak<X11Window>,
+        event: &xlib::XSelectionEvent,
+    ) {
+        let pending_drop = match self.pending_drop.lock().take() {
+            Some(pending_drop) => pending_drop,
+            None => return,
+        };
+
+        let atoms = self.atoms();
+        let files = if event.property != 0 {
+            let data = self.read_property::<u8>(window.id(), atoms.ZE_DROP, atoms.TEXT_URI_LIST);
+            (self.connection.xlib.XDeleteProperty)(
+                self.connection.display,
+                window.id(),
+                atoms.ZE_DROP,
+            );
+            data.map(|data| parse_uri_list(&data)).unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        let accepted = !files.is_empty();
+        self.send_xdnd_message(
+            pending_drop.source,
+            atoms.XdndFinished,
+            [
+                window.id() as c_long,
+                accepted as c_long,
+                if accepted {
+                    atoms.XdndActionCopy as c_long
+                } else {
+                    0
+                },
+                0,
+                0,
+            ],
+        );
+
+        if accepted {
+            self.message_queue.lock().push_back(Message::FilesDropped(
+                handle,
+                files,
+                pending_drop.position,
+            ));
+        }
+    }
+
+    unsafe fn send_xdnd_message(
+        &self,
+        target: xlib::Window,
+        message_type: xlib::Atom,
+        data: [c_long; 5],
+    ) {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+
+        let mut message_data = xlib::ClientMessageData::new();
+        for (i, value) in data.into_iter().enumerate() {
+            message_data.set_long(i, value);
+        }
+
+        let mut message: xlib::XEvent = mem::zeroed();
+        message.client_message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display,
+            window: target,
+            message_type,
+            format: 32,
+            data: message_data,
+        };
+        (xlib.XSendEvent)(display, target, xlib::False, 0, &mut message);
+        self.connection.flush();
+    }
+
+    fn send_button_event(
+        &self,
+        window: &X11Window,
+        handle: Weak<X11Window>,
+        event: &xlib::XButtonEvent,
+        pressed: bool,
+    ) {
+        let position = Point2::new(event.x_root, event.y_root);
+        let button = match event.button {
+            xlib::Button1 => MouseButton::Left,
+            xlib::Button2 => MouseButton::Middle,
+            xlib::Button3 => MouseButton::Right,
+
+            // Wheels are reported as buttons 4 to 7, without release
+            4 | 5 if pressed => {
+                let delta = if event.button == 4 { 1.0 } else { -1.0 };
+                self.message_queue
+                    .lock()
+                    .push_back(Message::MouseWheel(handle, delta, position));
+                return;
+            }
+            6 | 7 if pressed => {
+                let delta = if event.button == 7 { 1.0 } else { -1.0 };
+                self.message_queue
+                    .lock()
+                    .push_back(Message::MouseWheelH(handle, delta, position));
+                return;
+            }
+            _ => return,
+        };
+
+        let message = if !pressed {
+            Message::MouseButtonUp(handle, button, position)
+        } else if event.button == xlib::Button1 && window.hit_test(event) {
+            return;
+        } else {
+            let mut last_click = self.last_click.lock();
+            let double_click = match *last_click {
+                Some((last_button, time, last_position)) => {
+                    last_button == event.button
+                        && event.time.wrapping_sub(time) < DOUBLE_CLICK_TIME
+                        && (position.x - last_position.x).abs() <= DOUBLE_CLICK_DISTANCE
+                        && (position.y - last_position.y).abs() <= DOUBLE_CLICK_DISTANCE
+                }
+                None => false,
+            };
+
+            if double_click {
+                *last_click = None;
+                Message::MouseButtonDoubleClick(handle, button, position)
+            } else {
+                *last_click = Some((event.button, event.time, position));
+                Message::MouseButtonDown(handle, button, position)
+            }
+        };
+
+        self.message_queue.lock().push_back(message);
+    }
+
+    fn send_key_event(
+        &self,
+        window: &X11Window,
+        handle: Weak<X11Window>,
+        event: &mut xlib::XKeyEvent,
+        pressed: bool,
+    ) {
+        let keysym = unsafe { (self.connection.xlib.XLookupKeysym)(event, 0) } as c_uint;
+        let key_code = convert_key_code(keysym);
+        let scan_code = convert_scan_code(event.keycode);
+        let character_code = keysym_character_code(keysym);
+
+        if !pressed {
+            self.pressed_keys.lock().remove(&event.keycode);
+            self.message_queue.lock().push_back(Message::KeyUp(
+                handle,
+                key_code,
+                scan_code,
+                character_code,
+                false,
+            ));
+            return;
+        }
+
+        let repeat = !self.pressed_keys.lock().insert(event.keycode);
+        let text = self.lookup_text(window, event);
+        let mut message_queue = self.message_queue.lock();
+        message_queue.push_back(Message::KeyDown(
+            handle.clone(),
+            key_code,
+            scan_code,
+            character_code,
+            repeat,
+        ));
+
+        for character in text.chars() {
+            if !character.is_control() {
+                message_queue.push_back(Message::TextInput(handle.clone(), character));
+            }
+        }
+    }
+
+    /// Send the preedit texts changed by the input method callbacks
+    fn send_preedit_changes(&self) {
+        let window_map = self.window_map.lock();
+        let mut message_queue = self.message_queue.lock();
+        for handle in window_map.values() {
+            if let Some(text) = handle
+                .upgrade()
+                .and_then(|window| window.take_preedit_changes())
+            {
+                message_queue.push_back(Message::TextComposition(handle.clone(), text));
+            }
+        }
+    }
+
+    /// Text typed by a key press, composed by the input method when available
+    fn lookup_text(&self, window: &X11Window, event: &mut xlib::XKeyEvent) -> String {
+        let xlib = &self.connection.xlib;
+        let mut buffer = [0u8; 64];
+        unsafe {
+            if window.input_context().is_null() {
+                let length = (xlib.XLookupString)(
+                    event,
+                    buffer.as_mut_ptr() as *mut c_char,
+                    buffer.len() as c_int,
+                    null_mut(),
+                    null_mut(),
+                );
+
+                // Latin-1 code points are the first Unicode ones
+                return buffer[..length.max(0) as usize]
+                    .iter()
+                    .map(|&byte| byte as char)
+                    .collect();
+            }
+
+            let mut keysym = 0;
+            let mut status = 0;
+            let length = (xlib.Xutf8LookupString)(
+                window.input_context(),
+                event,
+                buffer.as_mut_ptr() as *mut c_char,
+                buffer.len() as c_int,
+                &mut keysym,
+                &mut status,
+            );
+
+            if status == xlib::XLookupChars || status == xlib::XLookupBoth {
+                String::from_utf8_lossy(&buffer[..length as usize]).into_owned()
+            } else {
+                String::new()
+            }
+        }
+    }
+
+    /// Answer another client requesting the content of the clipboard we own
+    unsafe fn send_selection(&self, request: &xlib::XSelectionRequestEvent) {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        let atoms = self.atoms();
+
+        // Obsolete clients don't set the property to store the content in
+        let mut property = if request.property != 0 {
+            request.property
+        } else {
+            request.target
+        };
+
+        let clipboard_text = self.clipboard_text.lock();
+        match clipboard_text.as_ref() {
+            Some(_) if request.target == atoms.TARGETS => {
+                let targets = [atoms.TARGETS, atoms.UTF8_STRING, xlib::XA_STRING];
+                (xlib.XChangeProperty)(
+                    display,
+                    request.requestor,
+                    property,
+                    xlib::XA_ATOM,
+                    32,
+                    xlib::PropModeReplace,
+                    targets.as_ptr() as *const u8,
+                    targets.len() as c_int,
+                );
+            }
+            Some(text)
+                if request.target == atoms.UTF8_STRING || request.target == xlib::XA_STRING =>
+            {
+                (xlib.XChangeProperty)(
+                    display,
+                    request.requestor,
+                    property,
+                    request.target,
+                    8,
+                    xlib::PropModeReplace,
+                    text.as_ptr(),
+                    text.len() as c_int,
+                );
+            }
+            _ => property = 0,
+        }
+
+        let mut event: xlib::XEvent = mem::zeroed();
+        event.selection = xlib::XSelectionEvent {
+            type_: xlib::SelectionNotify,
+            serial: 0,
+            send_event: xlib::True,
+            display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property,
+            time: request.time,
+        };
+        (xlib.XSendEvent)(display, request.requestor, xlib::False, 0, &mut event);
+        self.connection.flush();
+    }
+}
+
+/// Load RandR and listen to the screen changes, `None` if the server doesn't support it
+unsafe fn open_xrandr(connection: &Connection, root: xlib::Window) -> Option<(Xrandr, c_int)> {
+    let xrandr = Xrandr::open().ok()?;
+    let mut first_event = 0;
+    let mut first_error = 0;
+    if (xrandr.XRRQueryExtension)(connection.display, &mut first_event, &mut first_error) == 0 {
+        return None;
+    }
+
+    (xrandr.XRRSelectInput)(connection.display, root, xrandr::RRScreenChangeNotifyMask);
+    Some((xrandr, first_event))
+}
+
+/// Load XInput2 and listen to the raw motion of all pointers, `None` if the server doesn't
+/// support XInput 2.0
+unsafe fn open_xinput2(connection: &Connection, root: xlib::Window) -> Option<(XInput2, c_int)> {
+    let xlib = &connection.xlib;
+    let display = connection.display;
+
+    let name = CString::new("XInputExtension").unwrap();
+    let mut opcode = 0;
+    let mut first_event = 0;
+    let mut first_error = 0;
+    if (xlib.XQueryExtension)(
+        display,
+        name.as_ptr(),
+        &mut opcode,
+        &mut first_event,
+        &mut first_error,
+    ) == 0
+    {
+        return None;
+    }
+
+    let xinput2 = XInput2::open().ok()?;
+    let mut major = 2;
+    let mut minor = 0;
+    if (xinput2.XIQueryVersion)(display, &mut major, &mut minor) != xlib::Success as c_int {
+        return None;
+    }
+
+    let mut mask = [0; 4];
+    xinput2::XISetMask(&mut mask, xinput2::XI_RawMotion);
+    let mut event_mask = xinput2::XIEventMask {
+        deviceid: xinput2::XIAllMasterDevices,
+        mask_len: mask.len() as c_int,
+        mask: mask.as_mut_ptr(),
+    };
+    (xinput2.XISelectEvents)(display, root, &mut event_mask, 1);
+
+    Some((xinput2, opcode))
+}
+
+/// Motion on the X and Y valuators of a raw event, raw values are only sent for the valuators
+/// set in the mask
+unsafe fn raw_motion(event: &xinput2::XIRawEvent) -> (f64, f64) {
+    let mask = slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize);
+    let mut values = event.raw_values;
+    let mut motion = [0.0; 2];
+    for (valuator, value) in motion.iter_mut().enumerate() {
+        if valuator < mask.len() * 8 && xinput2::XIMaskIsSet(mask, valuator as i32) {
+            *value = *values;
+            values = values.add(1);
+        }
+    }
+    (motion[0], motion[1])
+}
+
+fn intersect(a: RectI32, b: RectI32) -> RectI32 {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let width = ((a.x + a.width).min(b.x + b.width) - x).max(0);
+    let height = ((a.y + a.height).min(b.y + b.height) - y).max(0);
+    RectI32::new(x, y, width, height)
+}
+
+impl Drop for X11Platform {
+    fn drop(&mut self) {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        unsafe {
+            (xlib.XFreeCursor)(display, self.invisible_cursor);
+            (xlib.XDestroyWindow)(display, self.clipboard_window);
+            if !self.input_method.is_null() {
+                (xlib.XCloseIM)(self.input_method);
+            }
+        }
+    }
+}
+
+impl Platform for X11Platform {
+    fn poll_event(&self) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        unsafe {
+            while (xlib.XPending)(display) > 0 {
+                let mut event = mem::zeroed();
+                (xlib.XNextEvent)(display, &mut event);
+
+                // Input methods consume the key events used to compose characters
+                if (xlib.XFilterEvent)(&mut event, 0) != 0 {
+                    self.send_preedit_changes();
+                    continue;
+                }
+
+                self.process_event(&mut event);
+            }
+        }
+
+        if let Some(theme) = self.theme_watcher.take_change() {
+            self.message_queue
+                .lock()
+                .push_back(Message::SystemThemeChanged(theme));
+        }
+        self.file_dialogs
+            .send_closed(&mut *self.message_queue.lock());
+
+        self.message_queue.lock().pop_front()
+    }
+
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        // Xlib may already have read events from the connection, nothing would wake us up
+        let display = self.connection.display;
+        unsafe {
+            if (self.connection.xlib.XPending)(display) == 0 {
+                self.waker
+                    .wait((self.connection.xlib.XConnectionNumber)(display), timeout);
+            }
+        }
+
+        self.poll_event()
+    }
+
+    fn wake(&self) {
+        self.waker.wake();
+    }
+
+    fn create_window(
+        &self,
+        name: &str,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        flags: WindowFlags,
+    ) -> Result<Arc<dyn Window>, Error> {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        let atoms = self.atoms();
+
+        unsafe {
+            let mut attributes: xlib::XSetWindowAttributes = mem::zeroed();
+            attributes.background_pixel =
+                (xlib.XBlackPixel)(display, (xlib.XDefaultScreen)(display));
+            attributes.event_mask = xlib::StructureNotifyMask
+                | xlib::KeyPressMask
+                | xlib::KeyReleaseMask
+                | xlib::ButtonPressMask
+                | xlib::ButtonReleaseMask
+                | xlib::EnterWindowMask
+                | xlib::LeaveWindowMask
+                | xlib::FocusChangeMask;
+
+            let window = (xlib.XCreateWindow)(
+                display,
+                self.root,
+                x,
+                y,
+                width,
+                height,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                null_mut(),
+                xlib::CWBackPixel | xlib::CWEventMask,
+                &mut attributes,
+            );
+
+            if window == 0 {
+                ze_error!("Failed to create window");
+                return Err(Error::Unknown);
+            }
+
+            // Closing the window sends a message instead of killing the connection
+            let mut protocols = [atoms.WM_DELETE_WINDOW];
+            (xlib.XSetWMProtocols)(display, window, protocols.as_mut_ptr(), 1);
+
+            // Accept files dragged from file managers
+            let xdnd_version = [XDND_VERSION];
+            (xlib.XChangeProperty)(
+                display,
+                window,
+                atoms.XdndAware,
+                xlib::XA_ATOM,
+                32,
+                xlib::PropModeReplace,
+                xdnd_version.as_ptr() as *const u8,
+                1,
+            );
+
+            // Window managers ignore the creation position unless it was requested by the user
+            let size_hints = (xlib.XAllocSizeHints)();
+            (*size_hints).flags = xlib::PPosition | xlib::USPosition;
+            (*size_hints).x = x;
+            (*size_hints).y = y;
+            if !flags.contains(WindowFlagBits::Resizable) {
+                (*size_hints).flags |= xlib::PMinSize | xlib::PMaxSize;
+                (*size_hints).min_width = width as c_int;
+                (*size_hints).min_height = height as c_int;
+                (*size_hints).max_width = width as c_int;
+                (*size_hints).max_height = height as c_int;
+            }
+            (xlib.XSetWMNormalHints)(display, window, size_hints);
+            (xlib.XFree)(size_hints as *mut c_void);
+
+            if flags.contains(WindowFlagBits::Borderless) {
+                let hints: [c_long; 5] = [MWM_HINTS_DECORATIONS, 0, 0, 0, 0];
+                (xlib.XChangeProperty)(
+                    display,
+                    window,
+                    atoms._MOTIF_WM_HINTS,
+                    atoms._MOTIF_WM_HINTS,
+                    32,
+                    xlib::PropModeReplace,
+                    hints.as_ptr() as *const u8,
+                    hints.len() as c_int,
+                );
+            }
+
+            if flags.contains(WindowFlagBits::Maximized) {
+                let states = [
+                    atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+                    atoms._NET_WM_STATE_MAXIMIZED_VERT,
+                ];
+                (xlib.XChangeProperty)(
+                    display,
+                    window,
+                    atoms._NET_WM_STATE,
+                    xlib::XA_ATOM,
+                    32,
+                    xlib::PropModeReplace,
+                    states.as_ptr() as *const u8,
+                    states.len() as c_int,
+                );
+            }
+
+            let cursor = self.window_cursor();
+            if cursor != 0 {
+                (xlib.XDefineCursor)(display, window, cursor);
+            }
+
+            let preedit = Box::<Mutex<Preedit>>::default();
+            let input_context = if self.input_method.is_null() {
+                null_mut()
+            } else {
+                create_input_context(&self.connection, self.input_method, window, &preedit)
+            };
+
+            let window = X11Window::new(
+                self.connection.clone(),
+                window,
+                input_context,
+                preedit,
+                self.launcher_entry.clone(),
+                width,
+                height,
+                x,
+                y,
+                flags.contains(WindowFlagBits::Resizable),
+            );
+            window.set_title(name);
+            window.set_dpi_scale(self.monitors.lock()[0].dpi / DEFAULT_DPI);
+            window.show();
+
+            self.window_map
+                .lock()
+                .insert(window.id(), Arc::downgrade(&window));
+
+            Ok(window)
+        }
+    }
+
+    fn create_system_cursor(&self, cursor: SystemCursor) -> Box<dyn Cursor> {
+        let (name, shape) = match cursor {
+            SystemCursor::No => ("not-allowed", XC_X_CURSOR),
+            SystemCursor::Crosshair => ("crosshair", XC_CROSSHAIR),
+            SystemCursor::Ibeam => ("xterm", XC_XTERM),
+            SystemCursor::Arrow => ("left_ptr", XC_LEFT_PTR),
+            SystemCursor::Hand => ("hand2", XC_HAND2),
+            SystemCursor::SizeAll => ("fleur", XC_FLEUR),
+            SystemCursor::SizeNorthEastSouthWest => ("bottom_left_corner", XC_BOTTOM_LEFT_CORNER),
+            SystemCursor::SizeNorthSouth => ("sb_v_double_arrow", XC_SB_V_DOUBLE_ARROW),
+            SystemCursor::SizeNorthWestSouthEast => ("bottom_right_corner", XC_BOTTOM_RIGHT_CORNER),
+            SystemCursor::SizeWestEast => ("sb_h_double_arrow", XC_SB_H_DOUBLE_ARROW),
+            SystemCursor::Wait => ("watch", XC_WATCH),
+            SystemCursor::WaitArrow => ("left_ptr_watch", XC_WATCH),
+        };
+
+        let display = self.connection.display;
+        let cursor = unsafe {
+            // Themed cursors are loaded through Xcursor, font cursors are the fallback
+            let name = CString::new(name).unwrap();
+            let cursor = match &self.xcursor {
+                Some(xcursor) => (xcursor.XcursorLibraryLoadCursor)(display, name.as_ptr()),
+                None => 0,
+            };
+
+            if cursor != 0 {
+                cursor
+            } else {
+                (self.connection.xlib.XCreateFontCursor)(display, shape)
+            }
+        };
+
+        Box::new(X11Cursor::new(self.connection.clone(), cursor))
+    }
+
+    fn create_cursor_from_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        let xcursor = self.xcursor.as_ref().ok_or_else(|| {
+            ze_error!("Xcursor is required to create cursors from images");
+            Error::Unknown
+        })?;
+
+        let pixels = premultiplied_argb(&rgba[..(width * height * 4) as usize]);
+        let cursor = unsafe {
+            let image = (xcursor.XcursorImageCreate)(width as c_int, height as c_int);
+            if image.is_null() {
+                return Err(Error::Unknown);
+            }
+
+            (*image).xhot = hotspot.x;
+            (*image).yhot = hotspot.y;
+            ptr::copy_nonoverlapping(pixels.as_ptr(), (*image).pixels, pixels.len());
+            let cursor = (xcursor.XcursorImageLoadCursor)(self.connection.display, image);
+            (xcursor.XcursorImageDestroy)(image);
+            cursor
+        };
+
+        if cursor == 0 {
+            ze_error!("Failed to create a {}x{} cursor", width, height);
+            return Err(Error::Unknown);
+        }
+
+        Ok(Box::new(X11Cursor::new(self.connection.clone(), cursor)))
+    }
+
+    fn set_cursor(&self, cursor: Option<&dyn Cursor>) {
+        let cursor = match cursor {
+            Some(cursor) => cursor.downcast_ref::<X11Cursor>().unwrap().cursor,
+            None => self.invisible_cursor,
+        };
+
+        let mut current_cursor = self.current_cursor.lock();
+        if *current_cursor == cursor {
+            return;
+        }
+        *current_cursor = cursor;
+        drop(current_cursor);
+
+        if self.cursor_shown.load(Ordering::SeqCst) {
+            self.define_cursor(cursor);
+        }
+    }
+
+    fn show_cursor(&self, show: bool) {
+        if self.cursor_shown.swap(show, Ordering::SeqCst) != show {
+            self.define_cursor(self.window_cursor());
+        }
+    }
+
+    fn capture_mouse(&self, window: &dyn Window) {
+        let window = window.downcast_ref::<X11Window>().unwrap();
+        unsafe {
+            // Events over our other windows are still reported to them
+            (self.connection.xlib.XGrabPointer)(
+                self.connection.display,
+                window.id(),
+                xlib::True,
+                (xlib::ButtonPressMask | xlib::ButtonReleaseMask) as c_uint,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+                0,
+                0,
+                xlib::CurrentTime,
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn release_mouse(&self) {
+        unsafe {
+            (self.connection.xlib.XUngrabPointer)(self.connection.display, xlib::CurrentTime);
+        }
+        self.connection.flush();
+    }
+
+    fn mouse_position(&self) -> Point2<i32> {
+        self.query_pointer().0
+    }
+
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        let keysym = match key_code_keysym(key) {
+            Some(keysym) => keysym,
+            None => return false,
+        };
+
+        let xlib = &self.connection.xlib;
+        let mut keys: [c_char; 32] = [0; 32];
+        unsafe {
+            let keycode = (xlib.XKeysymToKeycode)(self.connection.display, keysym.into()) as usize;
+            if keycode == 0 {
+                return false;
+            }
+
+            (xlib.XQueryKeymap)(self.connection.display, keys.as_mut_ptr());
+            keys[keycode / 8] as u8 & (1 << (keycode % 8)) != 0
+        }
+    }
+
+    fn keyboard_modifiers(&self) -> Modifiers {
+        let mask = self.query_pointer().1;
+        let mut modifiers = Modifiers::empty();
+        for (modifier_mask, modifier) in [
+            (xlib::ShiftMask, ModifierBits::Shift),
+            (xlib::ControlMask, ModifierBits::Control),
+            (xlib::Mod1Mask, ModifierBits::Alt),
+            (xlib::Mod4Mask, ModifierBits::Super),
+        ] {
+            if mask & modifier_mask != 0 {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    }
+
+    fn monitor_count(&self) -> usize {
+        self.monitors.lock().len()
+    }
+
+    fn monitor(&self, index: usize) -> Monitor {
+        self.monitors.lock()[index]
+    }
+
+    fn system_theme(&self) -> SystemTheme {
+        self.theme_watcher.theme()
+    }
+
+    fn power_status(&self) -> PowerStatus {
+        self.power_monitor.status()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        let atoms = self.atoms();
+
+        unsafe {
+            match (xlib.XGetSelectionOwner)(display, atoms.CLIPBOARD) {
+                0 => return None,
+                owner if owner == self.clipboard_window => {
+                    return self.clipboard_text.lock().clone();
+                }
+                _ => {}
+            }
+
+            (xlib.XConvertSelection)(
+                display,
+                atoms.CLIPBOARD,
+                atoms.UTF8_STRING,
+                atoms.ZE_CLIPBOARD,
+                self.clipboard_window,
+                xlib::CurrentTime,
+            );
+            self.connection.flush();
+
+            // The owner sends its content asynchronously
+            let start = Instant::now();
+            let mut event: xlib::XEvent = mem::zeroed();
+            while (xlib.XCheckTypedWindowEvent)(
+                display,
+                self.clipboard_window,
+                xlib::SelectionNotify,
+                &mut event,
+            ) == 0
+            {
+                if start.elapsed() > CLIPBOARD_TIMEOUT {
+                    ze_warn!("Clipboard owner didn't answer");
+                    return None;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            // The owner can't convert its content to text
+            if event.selection.property == 0 {
+                return None;
+            }
+
+            let text = self.read_property::<u8>(
+                self.clipboard_window,
+                atoms.ZE_CLIPBOARD,
+                atoms.UTF8_STRING,
+            );
+            (xlib.XDeleteProperty)(display, self.clipboard_window, atoms.ZE_CLIPBOARD);
+            text.map(|text| String::from_utf8_lossy(&text).into_owned())
+        }
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        *self.clipboard_text.lock() = Some(text.to_string());
+
+        unsafe {
+            (self.connection.xlib.XSetSelectionOwner)(
+                self.connection.display,
+                self.atoms().CLIPBOARD,
+                self.clipboard_window,
+                xlib::CurrentTime,
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn open_file_dialog(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        self.file_dialogs.open(filters, multi_select)
+    }
+
+    fn save_file_dialog(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        self.file_dialogs.save(filters, default_name)
+    }
+}
+
+mod connection;
+mod cursor;
+mod ime;
+mod window;
@@ -0,0 +1,577 @@
+use crate::launcher_entry::LauncherEntry;
+use crate::x11::connection::Connection;
+use crate::x11::ime::{set_spot_location, Preedit};
+use parking_lot::Mutex;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, XlibWindowHandle};
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_int, c_long, c_void};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::Arc;
+use x11_dl::xlib;
+use ze_core::maths::Point2;
+use ze_platform::{HitTest, HitTestCallback, TaskbarProgressState, Window};
+
+/// `_NET_WM_MOVERESIZE` direction moving the window instead of resizing it
+const NET_WM_MOVERESIZE_MOVE: c_long = 8;
+
+/// `_NET_WM_STATE` actions
+const NET_WM_STATE_REMOVE: c_long = 0;
+const NET_WM_STATE_ADD: c_long = 1;
+
+/// `_NET_ACTIVE_WINDOW` source indication of requests from applications
+const NET_ACTIVE_WINDOW_SOURCE_APPLICATION: c_long = 1;
+
+pub struct X11Window {
+    connection: Arc<Connection>,
+    window: xlib::Window,
+
+    /// Input context translating key events to text, null if no input method is available
+    input_context: xlib::XIC,
+
+    /// Boxed as its address is given to the input context callbacks
+    preedit: Box<Mutex<Preedit>>,
+    launcher_entry: Arc<LauncherEntry>,
+    width: AtomicU32,
+    height: AtomicU32,
+    x: AtomicI32,
+    y: AtomicI32,
+    hit_test_callback: Mutex<Option<HitTestCallback>>,
+
+    /// The size of windows that aren't resizable is fixed by their size hints
+    resizable: bool,
+    min_size: Mutex<Option<(u32, u32)>>,
+    max_size: Mutex<Option<(u32, u32)>>,
+
+    /// Iconified windows are unmapped by the window manager
+    minimized: AtomicBool,
+    focused: AtomicBool,
+
+    /// Same for all windows, X11 only has a global DPI
+    dpi_scale: Mutex<f32>,
+}
+
+// The input context is only used by the thread polling events
+unsafe impl Send for X11Window {}
+unsafe impl Sync for X11Window {}
+
+impl X11Window {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        connection: Arc<Connection>,
+        window: xlib::Window,
+        input_context: xlib::XIC,
+        preedit: Box<Mutex<Preedit>>,
+        launcher_entry: Arc<LauncherEntry>,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        resizable: bool,
+    ) -> Arc<X11Window> {
+        Arc::new(X11Window {
+            connection,
+            window,
+            input_context,
+            preedit,
+            launcher_entry,
+            width: AtomicU32::new(width),
+            height: AtomicU32::new(height),
+            x: AtomicI32::new(x),
+            y: AtomicI32::new(y),
+            hit_test_callback: Mutex::new(None),
+            resizable,
+            min_size: Mutex::new(None),
+            max_size: Mutex::new(None),
+            minimized: AtomicBool::new(false),
+            focused: AtomicBool::new(false),
+            dpi_scale: Mutex::new(1.0),
+        })
+    }
+
+    pub fn id(&self) -> xlib::Window {
+        self.window
+    }
+
+    pub fn input_context(&self) -> xlib::XIC {
+        self.input_context
+    }
+
+    pub fn set_dpi_scale(&self, dpi_scale: f32) {
+        *self.dpi_scale.lock() = dpi_scale;
+    }
+
+    pub fn set_minimized(&self, minimized: bool) {
+        self.minimized.store(minimized, Ordering::SeqCst);
+    }
+
+    /// Returns true if the focus changed
+    pub fn set_focused(&self, focused: bool) -> bool {
+        self.focused.swap(focused, Ordering::SeqCst) != focused
+    }
+
+    /// Replace the size limits of the `WM_NORMAL_HINTS`, keeping the other hints
+    fn update_size_hints(&self) {
+        if !self.resizable {
+            return;
+        }
+
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        unsafe {
+            let size_hints = (xlib.XAllocSizeHints)();
+            let mut supplied = 0;
+            (xlib.XGetWMNormalHints)(display, self.window, size_hints, &mut supplied);
+            (*size_hints).flags &= !(xlib::PMinSize | xlib::PMaxSize);
+            if let Some((width, height)) = *self.min_size.lock() {
+                (*size_hints).flags |= xlib::PMinSize;
+                (*size_hints).min_width = width as c_int;
+                (*size_hints).min_height = height as c_int;
+            }
+            if let Some((width, height)) = *self.max_size.lock() {
+                (*size_hints).flags |= xlib::PMaxSize;
+                (*size_hints).max_width = width.min(c_int::MAX as u32) as c_int;
+                (*size_hints).max_height = height.min(c_int::MAX as u32) as c_int;
+            }
+            (xlib.XSetWMNormalHints)(display, self.window, size_hints);
+            (xlib.XFree)(size_hints as *mut c_void);
+        }
+        self.connection.flush();
+    }
+
+    /// Text composed with the input method, `None` if it didn't change since the last call
+    pub fn take_preedit_changes(&self) -> Option<String> {
+        self.preedit.lock().take_changes()
+    }
+
+    /// Update the window geometry from a `ConfigureNotify`, returns true if the size changed
+    pub fn send_configure_event(&self, event: &xlib::XConfigureEvent) -> bool {
+        // Positions of real events are relative to the window manager frame, only the ones
+        // synthesized by the window manager are in root coordinates
+        let (x, y) = if event.send_event != 0 {
+            (event.x, event.y)
+        } else {
+            let mut x = 0;
+            let mut y = 0;
+            let mut child = 0;
+            unsafe {
+                let display = self.connection.display;
+                (self.connection.xlib.XTranslateCoordinates)(
+                    display,
+                    self.window,
+                    (self.connection.xlib.XDefaultRootWindow)(display),
+                    0,
+                    0,
+                    &mut x,
+                    &mut y,
+                    &mut child,
+                );
+            }
+            (x, y)
+        };
+        self.x.store(x, Ordering::SeqCst);
+        self.y.store(y, Ordering::SeqCst);
+
+        let width = self.width.swap(event.width as u32, Ordering::SeqCst);
+        let height = self.height.swap(event.height as u32, Ordering::SeqCst);
+        width != event.width as u32 || height != event.height as u32
+    }
+
+    /// Ask the window manager to move or resize the window if the hit test callback returns
+    /// a decoration, returns false if the click must be handled by the application
+    pub fn hit_test(&self, event: &xlib::XButtonEvent) -> bool {
+        let hit_test_callback = self.hit_test_callback.lock();
+        let callback = match hit_test_callback.as_ref() {
+            Some(callback) => callback,
+            None => return false,
+        };
+
+        let direction = match callback(Point2::new(event.x, event.y)) {
+            HitTest::Client | HitTest::MaximizeButton => return false,
+            HitTest::Caption => NET_WM_MOVERESIZE_MOVE,
+            HitTest::TopLeft => 0,
+            HitTest::Top => 1,
+            HitTest::TopRight => 2,
+            HitTest::Right => 3,
+            HitTest::BottomRight => 4,
+            HitTest::Bottom => 5,
+            HitTest::BottomLeft => 6,
+            HitTest::Left => 7,
+        };
+
+        unsafe {
+            // The window manager can't grab the pointer while we have it
+            (self.connection.xlib.XUngrabPointer)(self.connection.display, xlib::CurrentTime);
+            self.send_window_manager_message(
+                self.connection.atoms._NET_WM_MOVERESIZE,
+                [
+                    event.x_root as c_long,
+                    event.y_root as c_long,
+                    direction,
+                    event.button as c_long,
+                    1,
+                ],
+            );
+        }
+        self.connection.flush();
+
+        true
+    }
+
+    /// Send an EWMH request about this window to the window manager
+    unsafe fn send_window_manager_message(&self, message_type: xlib::Atom, data: [c_long; 5]) {
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+
+        let mut message_data = xlib::ClientMessageData::new();
+        for (i, value) in data.into_iter().enumerate() {
+            message_data.set_long(i, value);
+        }
+
+        let mut message: xlib::XEvent = mem::zeroed();
+        message.client_message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display,
+            window: self.window,
+            message_type,
+            format: 32,
+            data: message_data,
+        };
+
+        (xlib.XSendEvent)(
+            display,
+            (xlib.XDefaultRootWindow)(display),
+            xlib::False,
+            xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+            &mut message,
+        );
+    }
+
+    unsafe fn set_maximized(&self, maximized: bool) {
+        let atoms = &self.connection.atoms;
+        self.send_window_manager_message(
+            atoms._NET_WM_STATE,
+            [
+                if maximized {
+                    NET_WM_STATE_ADD
+                } else {
+                    NET_WM_STATE_REMOVE
+                },
+                atoms._NET_WM_STATE_MAXIMIZED_HORZ as c_long,
+                atoms._NET_WM_STATE_MAXIMIZED_VERT as c_long,
+                NET_ACTIVE_WINDOW_SOURCE_APPLICATION,
+                0,
+            ],
+        );
+    }
+}
+
+impl Drop for X11Window {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.input_context.is_null() {
+                (self.connection.xlib.XDestroyIC)(self.input_context);
+            }
+            (self.connection.xlib.XDestroyWindow)(self.connection.display, self.window);
+        }
+        self.connection.flush();
+    }
+}
+
+impl Window for X11Window {
+    fn set_position(&self, position: Point2<i32>) {
+        unsafe {
+            (self.connection.xlib.XMoveWindow)(
+                self.connection.display,
+                self.window,
+                position.x,
+                position.y,
+            );
+        }
+        self.connection.flush();
+        self.x.store(position.x, Ordering::SeqCst);
+        self.y.store(position.y, Ordering::SeqCst);
+    }
+
+    fn set_size(&self, width: u32, height: u32) {
+        unsafe {
+            (self.connection.xlib.XResizeWindow)(
+                self.connection.display,
+                self.window,
+                width,
+                height,
+            );
+        }
+        self.connection.flush();
+        self.width.store(width, Ordering::SeqCst);
+        self.height.store(height, Ordering::SeqCst);
+    }
+
+    fn set_min_size(&self, size: Option<(u32, u32)>) {
+        *self.min_size.lock() = size;
+        self.update_size_hints();
+    }
+
+    fn set_max_size(&self, size: Option<(u32, u32)>) {
+        *self.max_size.lock() = size;
+        self.update_size_hints();
+    }
+
+    fn set_title(&self, title: &str) {
+        let title = CString::new(title).unwrap_or_default();
+        let xlib = &self.connection.xlib;
+        let display = self.connection.display;
+        unsafe {
+            (xlib.XStoreName)(display, self.window, title.as_ptr());
+
+            // WM_NAME is Latin-1, window managers use the UTF-8 property when set
+            (xlib.XChangeProperty)(
+                display,
+                self.window,
+                self.connection.atoms._NET_WM_NAME,
+                self.connection.atoms.UTF8_STRING,
+                8,
+                xlib::PropModeReplace,
+                title.as_ptr() as *const u8,
+                title.as_bytes().len() as i32,
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn set_icon(&self, width: u32, height: u32, rgba: &[u8]) {
+        // The size followed by ARGB pixels, items of 32-bit properties are longs even on 64-bit
+        let data: Vec<c_long> = [width as c_long, height as c_long]
+            .into_iter()
+            .chain(
+                rgba[..(width * height * 4) as usize]
+                    .chunks_exact(4)
+                    .map(|pixel| {
+                        let [r, g, b, a] =
+                            [pixel[0], pixel[1], pixel[2], pixel[3]].map(c_long::from);
+                        (a << 24) | (r << 16) | (g << 8) | b
+                    }),
+            )
+            .collect();
+
+        unsafe {
+            (self.connection.xlib.XChangeProperty)(
+                self.connection.display,
+                self.window,
+                self.connection.atoms._NET_WM_ICON,
+                xlib::XA_CARDINAL,
+                32,
+                xlib::PropModeReplace,
+                data.as_ptr() as *const u8,
+                data.len() as c_int,
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn show(&self) {
+        unsafe {
+            (self.connection.xlib.XMapRaised)(self.connection.display, self.window);
+        }
+        self.connection.flush();
+    }
+
+    fn close(&self) {
+        unsafe {
+            (self.connection.xlib.XWithdrawWindow)(
+                self.connection.display,
+                self.window,
+                (self.connection.xlib.XDefaultScreen)(self.connection.display),
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn minimize(&self) {
+        unsafe {
+            let display = self.connection.display;
+            (self.connection.xlib.XIconifyWindow)(
+                display,
+                self.window,
+                (self.connection.xlib.XDefaultScreen)(display),
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn maximize(&self) {
+        unsafe { self.set_maximized(true) };
+        self.connection.flush();
+    }
+
+    /// Like on Windows, a minimized window is restored to its state before being minimized
+    fn restore(&self) {
+        unsafe {
+            if self.is_minimized() {
+                (self.connection.xlib.XMapWindow)(self.connection.display, self.window);
+            } else {
+                self.set_maximized(false);
+            }
+        }
+        self.connection.flush();
+    }
+
+    /// Window managers may decide to only mark the window as demanding attention
+    fn focus(&self) {
+        unsafe {
+            self.send_window_manager_message(
+                self.connection.atoms._NET_ACTIVE_WINDOW,
+                [
+                    NET_ACTIVE_WINDOW_SOURCE_APPLICATION,
+                    xlib::CurrentTime as c_long,
+                    0,
+                    0,
+                    0,
+                ],
+            );
+        }
+        self.connection.flush();
+    }
+
+    /// Applied by the compositor, windows stay opaque without one
+    fn set_opacity(&self, opacity: f32) {
+        // Items of 32-bit properties are longs even on 64-bit
+        let opacity = (opacity.clamp(0.0, 1.0) as f64 * u32::MAX as f64).round() as c_long;
+        unsafe {
+            (self.connection.xlib.XChangeProperty)(
+                self.connection.display,
+                self.window,
+                self.connection.atoms._NET_WM_WINDOW_OPACITY,
+                xlib::XA_CARDINAL,
+                32,
+                xlib::PropModeReplace,
+                &opacity as *const c_long as *const u8,
+                1,
+            );
+        }
+        self.connection.flush();
+    }
+
+    /// Window managers only handle this request once the window is shown
+    fn set_always_on_top(&self, always_on_top: bool) {
+        unsafe {
+            self.send_window_manager_message(
+                self.connection.atoms._NET_WM_STATE,
+                [
+                    if always_on_top {
+                        NET_WM_STATE_ADD
+                    } else {
+                        NET_WM_STATE_REMOVE
+                    },
+                    self.connection.atoms._NET_WM_STATE_ABOVE as c_long,
+                    0,
+                    NET_ACTIVE_WINDOW_SOURCE_APPLICATION,
+                    0,
+                ],
+            );
+        }
+        self.connection.flush();
+    }
+
+    /// The progress is shown on the launcher icon of the application, shared by its windows
+    fn set_taskbar_progress(&self, state: TaskbarProgressState, fraction: f32) {
+        self.launcher_entry.set_progress(state, fraction);
+    }
+
+    /// Window managers remove the state once the window is activated
+    fn request_attention(&self) {
+        if self.is_focused() {
+            return;
+        }
+
+        unsafe {
+            self.send_window_manager_message(
+                self.connection.atoms._NET_WM_STATE,
+                [
+                    NET_WM_STATE_ADD,
+                    self.connection.atoms._NET_WM_STATE_DEMANDS_ATTENTION as c_long,
+                    0,
+                    NET_ACTIVE_WINDOW_SOURCE_APPLICATION,
+                    0,
+                ],
+            );
+        }
+        self.connection.flush();
+    }
+
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>) {
+        *self.hit_test_callback.lock() = callback;
+    }
+
+    fn set_ime_position(&self, position: Point2<i32>) {
+        if self.input_context.is_null() {
+            return;
+        }
+
+        unsafe { set_spot_location(&self.connection, self.input_context, position) };
+        self.connection.flush();
+    }
+
+    fn handle(&self) -> RawWindowHandle {
+        let mut handle = XlibWindowHandle::empty();
+        handle.window = self.window;
+        RawWindowHandle::Xlib(handle)
+    }
+
+    fn display_handle(&self) -> RawDisplayHandle {
+        let mut handle = XlibDisplayHandle::empty();
+        handle.display = self.connection.display as *mut c_void;
+        handle.screen = unsafe { (self.connection.xlib.XDefaultScreen)(self.connection.display) };
+        RawDisplayHandle::Xlib(handle)
+    }
+
+    fn width(&self) -> u32 {
+        self.width.load(Ordering::SeqCst)
+    }
+
+    fn height(&self) -> u32 {
+        self.height.load(Ordering::SeqCst)
+    }
+
+    fn position(&self) -> Point2<i32> {
+        Point2::<i32>::new(self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
+    }
+
+    fn cursor_position(&self) -> Point2<i32> {
+        let mut root = 0;
+        let mut child = 0;
+        let mut root_x = 0;
+        let mut root_y = 0;
+        let mut x = 0;
+        let mut y = 0;
+        let mut mask = 0;
+        unsafe {
+            (self.connection.xlib.XQueryPointer)(
+                self.connection.display,
+                self.window,
+                &mut root,
+                &mut child,
+                &mut root_x,
+                &mut root_y,
+                &mut x,
+                &mut y,
+                &mut mask,
+            );
+        }
+        Point2::new(x, y)
+    }
+
+    fn is_minimized(&self) -> bool {
+        self.minimized.load(Ordering::SeqCst)
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        *self.dpi_scale.lock()
+    }
+}
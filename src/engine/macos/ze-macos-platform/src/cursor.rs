@@ -0,0 +1,27 @@
+use cocoa::base::id;
+use objc::{msg_send, sel, sel_impl};
+use ze_platform::Cursor;
+
+pub struct MacOSCursor {
+    pub cursor: id,
+}
+
+impl MacOSCursor {
+    /// Retain a `NSCursor`
+    pub fn new(cursor: id) -> Self {
+        unsafe {
+            let _: id = msg_send![cursor, retain];
+        }
+        Self { cursor }
+    }
+}
+
+impl Drop for MacOSCursor {
+    fn drop(&mut self) {
+        unsafe {
+            let _: () = msg_send![self.cursor, release];
+        }
+    }
+}
+
+impl Cursor for MacOSCursor {}
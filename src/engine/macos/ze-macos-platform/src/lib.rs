@@ -1,61 +1,660 @@
-use cocoa::appkit::NSScreen;
-use cocoa::base::*;
-use cocoa::foundation::NSArray;
-use core_graphics::display::{
-    CGDirectDisplayID, CGDisplayBounds, CGDisplayCopyDisplayMode, CGDisplayModeGetPixelHeight,
-    CGDisplayModeGetPixelWidth, CGDisplayModeRelease, CGDisplayScreenSize, CGGetActiveDisplayList,
+use crate::cursor::MacOSCursor;
+use crate::utils::{
+    autoreleasepool, from_ns_string, ns_image_from_rgba, ns_string, point_to_pixels,
+    primary_screen_geometry, rect_to_pixels,
 };
+use crate::window::{window_class, MacOSWindow};
+use block::ConcreteBlock;
+use cocoa::appkit::{
+    NSApplication, NSApplicationActivationPolicy, NSBackingStoreType, NSEvent, NSEventMask,
+    NSEventModifierFlags, NSEventSubtype, NSEventType, NSModalResponse, NSOpenPanel, NSPasteboard,
+    NSPasteboardTypeString, NSSavePanel, NSScreen, NSWindow, NSWindowStyleMask,
+};
+use cocoa::base::{id, nil, NO, YES};
+use cocoa::foundation::{
+    NSArray, NSDate, NSDefaultRunLoopMode, NSInteger, NSPoint, NSRect, NSSize, NSUInteger,
+};
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel, BOOL};
+use objc::{class, msg_send, sel, sel_impl};
 use parking_lot::Mutex;
-use std::sync::Arc;
-use ze_platform::{Monitor, Platform};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::path::PathBuf;
+use std::sync::{Arc, Once, Weak};
+use std::time::Duration;
+use ze_core::maths::Point2;
+use ze_core::{ze_error, ze_verbose};
+use ze_platform::{
+    Cursor, Error, FileDialogId, FileFilter, KeyCode, Message, ModifierBits, Modifiers, Monitor,
+    MouseButton, Platform, PowerSource, PowerStatus, SystemCursor, SystemTheme, Window,
+    WindowFlagBits, WindowFlags,
+};
+
+/// DPI of a screen with a backing scale factor of 1, the engine uses the Windows reference
+pub(crate) const DEFAULT_DPI: f32 = 96.0;
+
+const MESSAGE_QUEUE_IVAR: &str = "zeMessageQueue";
+
+/// Trackpads report scrolling in points, this many points are one wheel step
+const PRECISE_SCROLL_STEP: f64 = 10.0;
+
+/// State of the keys combining all the event sources of the session, like `GetAsyncKeyState`
+const CG_EVENT_SOURCE_STATE_COMBINED_SESSION_STATE: i32 = 0;
+
+#[cfg_attr(target_os = "macos", link(name = "CoreGraphics", kind = "framework"))]
+extern "C" {
+    fn CGEventSourceKeyState(state_id: i32, key: u16) -> bool;
+}
+
+// Power sources of IOKit, the returned CoreFoundation objects are bridged to Foundation ones
+#[cfg_attr(target_os = "macos", link(name = "IOKit", kind = "framework"))]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> id;
+    fn IOPSCopyPowerSourcesList(blob: id) -> id;
+    fn IOPSGetPowerSourceDescription(blob: id, power_source: id) -> id;
+    fn IOPSGetProvidingPowerSourceType(blob: id) -> id;
+}
+
+// Device dependent modifier flags, telling which of the left or right modifier is held
+const NX_DEVICELCTLKEYMASK: u64 = 0x0000_0001;
+const NX_DEVICELSHIFTKEYMASK: u64 = 0x0000_0002;
+const NX_DEVICERSHIFTKEYMASK: u64 = 0x0000_0004;
+const NX_DEVICELALTKEYMASK: u64 = 0x0000_0020;
+const NX_DEVICERALTKEYMASK: u64 = 0x0000_0040;
+const NX_DEVICERCTLKEYMASK: u64 = 0x0000_2000;
+
+// Virtual key codes of the modifiers, from Carbon's Events.h
+const KVK_SHIFT: u16 = 0x38;
+const KVK_OPTION: u16 = 0x3A;
+const KVK_CONTROL: u16 = 0x3B;
+const KVK_RIGHT_SHIFT: u16 = 0x3C;
+const KVK_RIGHT_OPTION: u16 = 0x3D;
+const KVK_RIGHT_CONTROL: u16 = 0x3E;
 
+/// Platform implemented over AppKit, must be created and used from the main thread
 pub struct MacOSPlatform {
+    app: id,
+    window_map: Mutex<HashMap<usize, Weak<MacOSWindow>>>,
+    message_queue: Arc<Mutex<VecDeque<Message>>>,
     monitors: Mutex<Vec<Monitor>>,
+    current_cursor: Mutex<Option<id>>,
+
+    /// Visibility set with `show_cursor`, the cursor is also hidden while it is set to `None`
+    cursor_shown: Mutex<bool>,
+    cursor_hidden: Mutex<bool>,
+
+    /// Notified when screens are connected, disconnected or change resolution,
+    /// and when the user switches between the light and dark modes
+    system_observer: id,
 }
 
+// AppKit objects are only used from the main thread, which owns the platform
+unsafe impl Send for MacOSPlatform {}
+unsafe impl Sync for MacOSPlatform {}
+
 impl MacOSPlatform {
     pub fn new() -> Arc<Self> {
+        let app = unsafe {
+            let app = NSApplication::sharedApplication(nil);
+
+            // Applications launched from a terminal aren't regular ones, they have no dock icon
+            // and their windows can't get the focus
+            app.setActivationPolicy_(
+                NSApplicationActivationPolicy::NSApplicationActivationPolicyRegular,
+            );
+            app.finishLaunching();
+            app.activateIgnoringOtherApps_(YES);
+            app
+        };
+
+        let message_queue: Arc<Mutex<VecDeque<Message>>> = Default::default();
+        let system_observer = unsafe {
+            let observer: id = msg_send![system_observer_class(), new];
+            (*observer).set_ivar::<*mut c_void>(
+                MESSAGE_QUEUE_IVAR,
+                Arc::as_ptr(&message_queue) as *mut c_void,
+            );
+
+            autoreleasepool(|| {
+                let notification_center: id =
+                    msg_send![class!(NSNotificationCenter), defaultCenter];
+                let _: () = msg_send![notification_center,
+                    addObserver: observer
+                    selector: sel!(screenParametersChanged:)
+                    name: ns_string("NSApplicationDidChangeScreenParametersNotification")
+                    object: nil];
+
+                // Sent by the system to all applications, not documented but used by all of them
+                let distributed_center: id =
+                    msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+                let _: () = msg_send![distributed_center,
+                    addObserver: observer
+                    selector: sel!(interfaceThemeChanged:)
+                    name: ns_string("AppleInterfaceThemeChangedNotification")
+                    object: nil];
+            });
+            observer
+        };
+
         let platform = Self {
+            app,
+            window_map: Default::default(),
+            message_queue,
             monitors: Default::default(),
+            current_cursor: Mutex::new(Some(unsafe { msg_send![class!(NSCursor), arrowCursor] })),
+            cursor_shown: Mutex::new(true),
+            cursor_hidden: Mutex::new(false),
+            system_observer,
         };
         platform.update_monitors();
         Arc::new(platform)
     }
 
+    /// Hiding the cursor is counted, it must be shown as many times
+    unsafe fn set_cursor_visible(&self, visible: bool) {
+        let mut cursor_hidden = self.cursor_hidden.lock();
+        if *cursor_hidden != visible {
+            return;
+        }
+
+        if visible {
+            let _: () = msg_send![class!(NSCursor), unhide];
+        } else {
+            let _: () = msg_send![class!(NSCursor), hide];
+        }
+        *cursor_hidden = !visible;
+    }
+
     fn update_monitors(&self) {
+        let mut monitors = self.monitors.lock();
+        monitors.clear();
+
         unsafe {
-            let mut monitors = self.monitors.lock();
-            monitors.clear();
-
-            let mut active_displays = [CGDirectDisplayID::default(); 256];
-            let mut active_display_count = 0;
-            CGGetActiveDisplayList(
-                active_displays.len() as u32,
-                active_displays.as_mut().as_mut_ptr(),
-                &mut active_display_count,
-            );
+            let screens = NSScreen::screens(nil);
+            for i in 0..screens.count() {
+                let screen = screens.objectAtIndex(i);
+                monitors.push(Monitor {
+                    bounds: rect_to_pixels(NSScreen::frame(screen)),
+                    work_bounds: rect_to_pixels(NSScreen::visibleFrame(screen)),
+                    dpi: DEFAULT_DPI * NSScreen::backingScaleFactor(screen) as f32,
+                });
+            }
+        }
+    }
+
+    fn window(&self, window: id) -> Option<(Arc<MacOSWindow>, Weak<MacOSWindow>)> {
+        let handle = self.window_map.lock().get(&(window as usize))?.clone();
+        handle.upgrade().map(|window| (window, handle))
+    }
+
+    /// Translate an event of one of our windows, returns false if AppKit must not receive it
+    unsafe fn process_event(&self, event: id) -> bool {
+        let (window, handle) = match self.window(event.window()) {
+            Some(window) => window,
+            None => return true,
+        };
+
+        let event_type = event.eventType();
+        match event_type {
+            NSEventType::NSLeftMouseDown
+            | NSEventType::NSRightMouseDown
+            | NSEventType::NSOtherMouseDown => {
+                let button = match event_button(event) {
+                    Some(button) => button,
+                    None => return true,
+                };
+
+                if matches!(event_type, NSEventType::NSLeftMouseDown)
+                    && window.hit_test(event, window.window_to_client(event.locationInWindow()))
+                {
+                    return false;
+                }
+
+                let message = if event.clickCount() == 2 {
+                    Message::MouseButtonDoubleClick(handle, button, self.mouse_position())
+                } else {
+                    Message::MouseButtonDown(handle, button, self.mouse_position())
+                };
+                self.message_queue.lock().push_back(message);
+            }
+            NSEventType::NSLeftMouseUp
+            | NSEventType::NSRightMouseUp
+            | NSEventType::NSOtherMouseUp => {
+                if let Some(button) = event_button(event) {
+                    self.message_queue.lock().push_back(Message::MouseButtonUp(
+                        handle,
+                        button,
+                        self.mouse_position(),
+                    ));
+                }
+            }
+            NSEventType::NSMouseMoved
+            | NSEventType::NSLeftMouseDragged
+            | NSEventType::NSRightMouseDragged
+            | NSEventType::NSOtherMouseDragged => {
+                // AppKit sets its own cursors over the window edges
+                if let Some(cursor) = *self.current_cursor.lock() {
+                    let _: () = msg_send![cursor, set];
+                }
+
+                // Deltas are still reported when the cursor is stuck at a screen edge
+                self.message_queue.lock().push_back(Message::MouseMotionRaw(
+                    event.deltaX() as f32,
+                    event.deltaY() as f32,
+                ));
+            }
+            // Sent by the tracking area of the content view
+            NSEventType::NSMouseEntered => {
+                self.message_queue
+                    .lock()
+                    .push_back(Message::MouseEntered(handle));
+            }
+            NSEventType::NSMouseExited => {
+                self.message_queue
+                    .lock()
+                    .push_back(Message::MouseLeft(handle));
+            }
+            NSEventType::NSScrollWheel => {
+                let mut delta_x = event.scrollingDeltaX();
+                let mut delta_y = event.scrollingDeltaY();
+                if event.hasPreciseScrollingDeltas() == YES {
+                    delta_x /= PRECISE_SCROLL_STEP;
+                    delta_y /= PRECISE_SCROLL_STEP;
+                }
+
+                let position = self.mouse_position();
+                let mut message_queue = self.message_queue.lock();
+                if delta_y != 0.0 {
+                    message_queue.push_back(Message::MouseWheel(
+                        handle.clone(),
+                        delta_y as f32,
+                        position,
+                    ));
+                }
+
+                // AppKit horizontal deltas are positive when scrolling to the left
+                if delta_x != 0.0 {
+                    message_queue.push_back(Message::MouseWheelH(
+                        handle,
+                        -delta_x as f32,
+                        position,
+                    ));
+                }
+            }
 
-            for i in 0..active_display_count {
-                let display = active_displays[i as usize];
-                let bounds = CGDisplayBounds(display);
-                let display_mode = CGDisplayCopyDisplayMode(display);
-                let width = CGDisplayModeGetPixelWidth(display_mode);
-                let height = CGDisplayModeGetPixelHeight(display_mode);
-                let test = CGDisplayScreenSize(display);
-                CGDisplayModeRelease(display_mode);
-                /*monitors.push(Monitor {
-                    bounds: (),
-                    work_bounds: (),
-                    dpi: (),
-                });*/
+            // AppKit beeps when no responder handles a key, so key events are kept
+            NSEventType::NSKeyDown => {
+                self.message_queue.lock().push_back(Message::KeyDown(
+                    handle,
+                    layout_key_code(event),
+                    convert_key_code(event.keyCode()),
+                    character_code(event),
+                    event.isARepeat() == YES,
+                ));
+
+                window.interpret_key_event(event);
+                return false;
+            }
+            NSEventType::NSKeyUp => {
+                self.message_queue.lock().push_back(Message::KeyUp(
+                    handle,
+                    layout_key_code(event),
+                    convert_key_code(event.keyCode()),
+                    character_code(event),
+                    false,
+                ));
+                return false;
             }
+
+            // Modifiers don't send key events, only the new state of all modifiers
+            NSEventType::NSFlagsChanged => {
+                let key_code = event.keyCode();
+                let mask = match key_code {
+                    KVK_SHIFT => NX_DEVICELSHIFTKEYMASK,
+                    KVK_RIGHT_SHIFT => NX_DEVICERSHIFTKEYMASK,
+                    KVK_CONTROL => NX_DEVICELCTLKEYMASK,
+                    KVK_RIGHT_CONTROL => NX_DEVICERCTLKEYMASK,
+                    KVK_OPTION => NX_DEVICELALTKEYMASK,
+                    KVK_RIGHT_OPTION => NX_DEVICERALTKEYMASK,
+                    _ => return true,
+                };
+
+                let key_code = convert_key_code(key_code);
+                let message = if event.modifierFlags().bits() & mask != 0 {
+                    Message::KeyDown(handle, key_code, key_code, 0, false)
+                } else {
+                    Message::KeyUp(handle, key_code, key_code, 0, false)
+                };
+                self.message_queue.lock().push_back(message);
+            }
+            _ => {}
+        }
+
+        true
+    }
+}
+
+fn event_button(event: id) -> Option<MouseButton> {
+    unsafe {
+        match event.eventType() {
+            NSEventType::NSLeftMouseDown | NSEventType::NSLeftMouseUp => Some(MouseButton::Left),
+            NSEventType::NSRightMouseDown | NSEventType::NSRightMouseUp => Some(MouseButton::Right),
+            NSEventType::NSOtherMouseDown | NSEventType::NSOtherMouseUp
+                if event.buttonNumber() == 2 =>
+            {
+                Some(MouseButton::Middle)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Letter keys by alphabetical order
+const LETTER_KEY_CODES: [KeyCode; 26] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+];
+
+/// Virtual key codes are positions on an ANSI keyboard, the letter keys are translated with
+/// the keyboard layout from the character they type
+fn layout_key_code(event: id) -> KeyCode {
+    match char::from_u32(character_code(event)) {
+        Some(letter @ 'A'..='Z') => LETTER_KEY_CODES[(letter as u8 - b'A') as usize],
+        _ => convert_key_code(unsafe { event.keyCode() }),
+    }
+}
+
+/// Unshifted character of a key, like `MapVirtualKey` on Windows
+fn character_code(event: id) -> u32 {
+    let characters = unsafe { from_ns_string(event.charactersIgnoringModifiers()) };
+    match characters.chars().next() {
+        Some(character) if character.is_ascii_graphic() || character == ' ' => {
+            character.to_ascii_uppercase() as u32
+        }
+        _ => 0,
+    }
+}
+
+/// Inverse of `convert_key_code`
+fn virtual_key_code(key: KeyCode) -> Option<u16> {
+    Some(match key {
+        KeyCode::Escape => 0x35,
+        KeyCode::Space => 0x31,
+        KeyCode::Backspace => 0x33,
+        KeyCode::A => 0x00,
+        KeyCode::B => 0x0B,
+        KeyCode::C => 0x08,
+        KeyCode::D => 0x02,
+        KeyCode::E => 0x0E,
+        KeyCode::F => 0x03,
+        KeyCode::G => 0x05,
+        KeyCode::H => 0x04,
+        KeyCode::I => 0x22,
+        KeyCode::J => 0x26,
+        KeyCode::K => 0x28,
+        KeyCode::L => 0x25,
+        KeyCode::M => 0x2E,
+        KeyCode::N => 0x2D,
+        KeyCode::O => 0x1F,
+        KeyCode::P => 0x23,
+        KeyCode::Q => 0x0C,
+        KeyCode::R => 0x0F,
+        KeyCode::S => 0x01,
+        KeyCode::T => 0x11,
+        KeyCode::U => 0x20,
+        KeyCode::V => 0x09,
+        KeyCode::W => 0x0D,
+        KeyCode::X => 0x07,
+        KeyCode::Y => 0x10,
+        KeyCode::Z => 0x06,
+        KeyCode::Numpad0 => 0x52,
+        KeyCode::Numpad1 => 0x53,
+        KeyCode::Numpad2 => 0x54,
+        KeyCode::Numpad3 => 0x55,
+        KeyCode::Numpad4 => 0x56,
+        KeyCode::Numpad5 => 0x57,
+        KeyCode::Numpad6 => 0x58,
+        KeyCode::Numpad7 => 0x59,
+        KeyCode::Numpad8 => 0x5B,
+        KeyCode::Numpad9 => 0x5C,
+        KeyCode::Num0 => 0x1D,
+        KeyCode::Num1 => 0x12,
+        KeyCode::Num2 => 0x13,
+        KeyCode::Num3 => 0x14,
+        KeyCode::Num4 => 0x15,
+        KeyCode::Num5 => 0x17,
+        KeyCode::Num6 => 0x16,
+        KeyCode::Num7 => 0x1A,
+        KeyCode::Num8 => 0x1C,
+        KeyCode::Num9 => 0x19,
+        KeyCode::LeftControl => KVK_CONTROL,
+        KeyCode::RightControl => KVK_RIGHT_CONTROL,
+        KeyCode::LeftShift => KVK_SHIFT,
+        KeyCode::RightShift => KVK_RIGHT_SHIFT,
+        KeyCode::LeftAlt => KVK_OPTION,
+        KeyCode::RightAlt => KVK_RIGHT_OPTION,
+        KeyCode::F1 => 0x7A,
+        KeyCode::F2 => 0x78,
+        KeyCode::F3 => 0x63,
+        KeyCode::F4 => 0x76,
+        KeyCode::F5 => 0x60,
+        KeyCode::F6 => 0x61,
+        KeyCode::F7 => 0x62,
+        KeyCode::F8 => 0x64,
+        KeyCode::F9 => 0x65,
+        KeyCode::F10 => 0x6D,
+        KeyCode::F11 => 0x67,
+        KeyCode::F12 => 0x6F,
+        KeyCode::F13 => 0x69,
+        KeyCode::F14 => 0x6B,
+        KeyCode::F15 => 0x71,
+        KeyCode::F16 => 0x6A,
+        KeyCode::F17 => 0x40,
+        KeyCode::F18 => 0x4F,
+        KeyCode::F19 => 0x50,
+        KeyCode::F20 => 0x5A,
+        _ => return None,
+    })
+}
+
+/// Convert a virtual key code from Carbon's Events.h
+fn convert_key_code(key_code: u16) -> KeyCode {
+    match key_code {
+        0x35 => KeyCode::Escape,
+        0x31 => KeyCode::Space,
+        0x33 => KeyCode::Backspace,
+        0x00 => KeyCode::A,
+        0x0B => KeyCode::B,
+        0x08 => KeyCode::C,
+        0x02 => KeyCode::D,
+        0x0E => KeyCode::E,
+        0x03 => KeyCode::F,
+        0x05 => KeyCode::G,
+        0x04 => KeyCode::H,
+        0x22 => KeyCode::I,
+        0x26 => KeyCode::J,
+        0x28 => KeyCode::K,
+        0x25 => KeyCode::L,
+        0x2E => KeyCode::M,
+        0x2D => KeyCode::N,
+        0x1F => KeyCode::O,
+        0x23 => KeyCode::P,
+        0x0C => KeyCode::Q,
+        0x0F => KeyCode::R,
+        0x01 => KeyCode::S,
+        0x11 => KeyCode::T,
+        0x20 => KeyCode::U,
+        0x09 => KeyCode::V,
+        0x0D => KeyCode::W,
+        0x07 => KeyCode::X,
+        0x10 => KeyCode::Y,
+        0x06 => KeyCode::Z,
+        0x52 => KeyCode::Numpad0,
+        0x53 => KeyCode::Numpad1,
+        0x54 => KeyCode::Numpad2,
+        0x55 => KeyCode::Numpad3,
+        0x56 => KeyCode::Numpad4,
+        0x57 => KeyCode::Numpad5,
+        0x58 => KeyCode::Numpad6,
+        0x59 => KeyCode::Numpad7,
+        0x5B => KeyCode::Numpad8,
+        0x5C => KeyCode::Numpad9,
+        0x1D => KeyCode::Num0,
+        0x12 => KeyCode::Num1,
+        0x13 => KeyCode::Num2,
+        0x14 => KeyCode::Num3,
+        0x15 => KeyCode::Num4,
+        0x17 => KeyCode::Num5,
+        0x16 => KeyCode::Num6,
+        0x1A => KeyCode::Num7,
+        0x1C => KeyCode::Num8,
+        0x19 => KeyCode::Num9,
+        KVK_CONTROL => KeyCode::LeftControl,
+        KVK_RIGHT_CONTROL => KeyCode::RightControl,
+        KVK_SHIFT => KeyCode::LeftShift,
+        KVK_RIGHT_SHIFT => KeyCode::RightShift,
+        KVK_OPTION => KeyCode::LeftAlt,
+        KVK_RIGHT_OPTION => KeyCode::RightAlt,
+        0x7A => KeyCode::F1,
+        0x78 => KeyCode::F2,
+        0x63 => KeyCode::F3,
+        0x76 => KeyCode::F4,
+        0x60 => KeyCode::F5,
+        0x61 => KeyCode::F6,
+        0x62 => KeyCode::F7,
+        0x64 => KeyCode::F8,
+        0x65 => KeyCode::F9,
+        0x6D => KeyCode::F10,
+        0x67 => KeyCode::F11,
+        0x6F => KeyCode::F12,
+        0x69 => KeyCode::F13,
+        0x6B => KeyCode::F14,
+        0x71 => KeyCode::F15,
+        0x6A => KeyCode::F16,
+        0x40 => KeyCode::F17,
+        0x4F => KeyCode::F18,
+        0x50 => KeyCode::F19,
+        0x5A => KeyCode::F20,
+        _ => {
+            ze_verbose!("Key {} not handled", key_code);
+            KeyCode::None
+        }
+    }
+}
+
+impl Drop for MacOSPlatform {
+    fn drop(&mut self) {
+        unsafe {
+            let notification_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let _: () = msg_send![notification_center, removeObserver: self.system_observer];
+            let distributed_center: id =
+                msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+            let _: () = msg_send![distributed_center, removeObserver: self.system_observer];
+            let _: () = msg_send![self.system_observer, release];
         }
     }
 }
 
 impl Platform for MacOSPlatform {
-    fn poll_event(&self) -> Option<ze_platform::Message> {
-        todo!()
+    fn poll_event(&self) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        unsafe {
+            autoreleasepool(|| loop {
+                let event = self.app.nextEventMatchingMask_untilDate_inMode_dequeue_(
+                    NSEventMask::NSAnyEventMask.bits(),
+                    NSDate::distantPast(nil),
+                    NSDefaultRunLoopMode,
+                    YES,
+                );
+                if event == nil {
+                    break;
+                }
+
+                if self.process_event(event) {
+                    self.app.sendEvent_(event);
+                }
+            });
+        }
+
+        self.message_queue.lock().pop_front()
+    }
+
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        unsafe {
+            autoreleasepool(|| {
+                let until: id = match timeout {
+                    Some(timeout) => msg_send![
+                        class!(NSDate),
+                        dateWithTimeIntervalSinceNow: timeout.as_secs_f64()
+                    ],
+                    None => NSDate::distantFuture(nil),
+                };
+                let event = self.app.nextEventMatchingMask_untilDate_inMode_dequeue_(
+                    NSEventMask::NSAnyEventMask.bits(),
+                    until,
+                    NSDefaultRunLoopMode,
+                    YES,
+                );
+                if event != nil && self.process_event(event) {
+                    self.app.sendEvent_(event);
+                }
+            });
+        }
+
+        self.poll_event()
+    }
+
+    /// Posts an application defined event, ignored as it has no window
+    fn wake(&self) {
+        unsafe {
+            autoreleasepool(|| {
+                let event = NSEvent::otherEventWithType_location_modifierFlags_timestamp_windowNumber_context_subtype_data1_data2_(
+                    nil,
+                    NSEventType::NSApplicationDefined,
+                    NSPoint::new(0.0, 0.0),
+                    NSEventModifierFlags::empty(),
+                    0.0,
+                    0,
+                    nil,
+                    NSEventSubtype::NSWindowExposedEventType,
+                    0,
+                    0,
+                );
+                self.app.postEvent_atStart_(event, NO);
+            });
+        }
     }
 
     fn create_window(
@@ -65,31 +664,426 @@ impl Platform for MacOSPlatform {
         height: u32,
         x: i32,
         y: i32,
-        flags: ze_platform::WindowFlags,
-    ) -> Result<Arc<dyn ze_platform::Window>, ze_platform::Error> {
-        todo!()
+        flags: WindowFlags,
+    ) -> Result<Arc<dyn Window>, Error> {
+        let mut style_mask = if flags.contains(WindowFlagBits::Borderless) {
+            NSWindowStyleMask::NSBorderlessWindowMask
+        } else {
+            NSWindowStyleMask::NSTitledWindowMask
+                | NSWindowStyleMask::NSClosableWindowMask
+                | NSWindowStyleMask::NSMiniaturizableWindowMask
+        };
+        if flags.contains(WindowFlagBits::Resizable) {
+            style_mask |= NSWindowStyleMask::NSResizableWindowMask;
+        }
+
+        let window = unsafe {
+            // The window isn't on a screen yet, its size is in points of the primary screen
+            let (_, scale) = primary_screen_geometry();
+            let content_rect = NSRect::new(
+                NSPoint::new(0.0, 0.0),
+                NSSize::new(width as f64 / scale, height as f64 / scale),
+            );
+
+            let window: id = msg_send![window_class(), alloc];
+            let window = window.initWithContentRect_styleMask_backing_defer_(
+                content_rect,
+                style_mask,
+                NSBackingStoreType::NSBackingStoreBuffered,
+                NO,
+            );
+
+            if window == nil {
+                ze_error!("Failed to create window");
+                return Err(Error::Unknown);
+            }
+
+            // The window is released when dropped
+            window.setReleasedWhenClosed_(NO);
+            window.setAcceptsMouseMovedEvents_(YES);
+            MacOSWindow::new(window, self.message_queue.clone())
+        };
+
+        window.set_position(Point2::new(x, y));
+        window.set_title(name);
+
+        if flags.contains(WindowFlagBits::Maximized) {
+            unsafe {
+                window.id().zoom_(nil);
+            }
+        }
+
+        window.show();
+
+        self.window_map
+            .lock()
+            .insert(window.id() as usize, Arc::downgrade(&window));
+
+        Ok(window)
     }
 
-    fn create_system_cursor(
+    fn create_system_cursor(&self, cursor: SystemCursor) -> Box<dyn Cursor> {
+        // Cursors without a public constructor use the private ones AppKit uses itself
+        let (selector, fallback) = match cursor {
+            SystemCursor::No => (sel!(operationNotAllowedCursor), sel!(arrowCursor)),
+            SystemCursor::Crosshair => (sel!(crosshairCursor), sel!(arrowCursor)),
+            SystemCursor::Ibeam => (sel!(IBeamCursor), sel!(arrowCursor)),
+            SystemCursor::Arrow => (sel!(arrowCursor), sel!(arrowCursor)),
+            SystemCursor::Hand => (sel!(pointingHandCursor), sel!(arrowCursor)),
+            SystemCursor::SizeAll => (sel!(_moveCursor), sel!(openHandCursor)),
+            SystemCursor::SizeNorthEastSouthWest => (
+                sel!(_windowResizeNorthEastSouthWestCursor),
+                sel!(closedHandCursor),
+            ),
+            SystemCursor::SizeNorthSouth => (sel!(resizeUpDownCursor), sel!(arrowCursor)),
+            SystemCursor::SizeNorthWestSouthEast => (
+                sel!(_windowResizeNorthWestSouthEastCursor),
+                sel!(closedHandCursor),
+            ),
+            SystemCursor::SizeWestEast => (sel!(resizeLeftRightCursor), sel!(arrowCursor)),
+            SystemCursor::Wait | SystemCursor::WaitArrow => (sel!(_waitCursor), sel!(arrowCursor)),
+        };
+
+        unsafe {
+            let class = class!(NSCursor);
+            let responds: BOOL = msg_send![class, respondsToSelector: selector];
+            let selector: Sel = if responds == YES { selector } else { fallback };
+            let cursor: id = msg_send![class, performSelector: selector];
+            Box::new(MacOSCursor::new(cursor))
+        }
+    }
+
+    fn create_cursor_from_rgba(
         &self,
-        cursor: ze_platform::SystemCursor,
-    ) -> Box<dyn ze_platform::Cursor> {
-        todo!()
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        unsafe {
+            autoreleasepool(|| {
+                // Pixels are the ones of the primary screen, like the other positions
+                let (_, scale) = primary_screen_geometry();
+                let image = ns_image_from_rgba(
+                    rgba,
+                    width,
+                    height,
+                    NSSize::new(width as f64 / scale, height as f64 / scale),
+                );
+                if image == nil {
+                    ze_error!("Failed to create a {}x{} cursor", width, height);
+                    return Err(Error::Unknown);
+                }
+
+                let hotspot = NSPoint::new(hotspot.x as f64 / scale, hotspot.y as f64 / scale);
+                let cursor: id = msg_send![class!(NSCursor), alloc];
+                let cursor: id = msg_send![cursor, initWithImage: image hotSpot: hotspot];
+                let _: () = msg_send![image, release];
+
+                // The cursor wrapper retains it again
+                let wrapper = MacOSCursor::new(cursor);
+                let _: () = msg_send![cursor, release];
+                Ok(Box::new(wrapper) as Box<dyn Cursor>)
+            })
+        }
     }
 
-    fn set_cursor(&self, cursor: Option<&dyn ze_platform::Cursor>) {
-        todo!()
+    fn set_cursor(&self, cursor: Option<&dyn Cursor>) {
+        let cursor = cursor.map(|cursor| cursor.downcast_ref::<MacOSCursor>().unwrap().cursor);
+
+        let mut current_cursor = self.current_cursor.lock();
+        if *current_cursor == cursor {
+            return;
+        }
+        *current_cursor = cursor;
+
+        unsafe {
+            if let Some(cursor) = cursor {
+                let _: () = msg_send![cursor, set];
+            }
+            self.set_cursor_visible(cursor.is_some() && *self.cursor_shown.lock());
+        }
     }
 
-    fn mouse_position(&self) -> ze_core::maths::Vec2i32 {
-        todo!()
+    fn show_cursor(&self, show: bool) {
+        *self.cursor_shown.lock() = show;
+        let has_cursor = self.current_cursor.lock().is_some();
+        unsafe { self.set_cursor_visible(show && has_cursor) };
     }
 
+    /// AppKit keeps sending the mouse events to the window while a button is held
+    fn capture_mouse(&self, _: &dyn Window) {}
+
+    fn release_mouse(&self) {}
+
+    fn mouse_position(&self) -> Point2<i32> {
+        unsafe { point_to_pixels(NSEvent::mouseLocation(nil)) }
+    }
+
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        virtual_key_code(key).is_some_and(|key_code| unsafe {
+            CGEventSourceKeyState(CG_EVENT_SOURCE_STATE_COMBINED_SESSION_STATE, key_code)
+        })
+    }
+
+    fn keyboard_modifiers(&self) -> Modifiers {
+        let flags = unsafe {
+            let flags: NSUInteger = msg_send![class!(NSEvent), modifierFlags];
+            NSEventModifierFlags::from_bits_truncate(flags)
+        };
+
+        let mut modifiers = Modifiers::empty();
+        for (flag, modifier) in [
+            (NSEventModifierFlags::NSShiftKeyMask, ModifierBits::Shift),
+            (
+                NSEventModifierFlags::NSControlKeyMask,
+                ModifierBits::Control,
+            ),
+            (NSEventModifierFlags::NSAlternateKeyMask, ModifierBits::Alt),
+            (NSEventModifierFlags::NSCommandKeyMask, ModifierBits::Super),
+        ] {
+            if flags.contains(flag) {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    }
+
+    /// AppKit only notifies screen changes through the notification center, screens are cheap
+    /// to query so they are updated every time
     fn monitor_count(&self) -> usize {
-        todo!()
+        self.update_monitors();
+        self.monitors.lock().len()
+    }
+
+    fn monitor(&self, index: usize) -> Monitor {
+        self.monitors.lock()[index]
     }
 
-    fn monitor(&self, index: usize) -> ze_platform::Monitor {
-        todo!()
+    fn system_theme(&self) -> SystemTheme {
+        read_system_theme()
+    }
+
+    fn power_status(&self) -> PowerStatus {
+        read_power_status()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        unsafe {
+            autoreleasepool(|| {
+                let pasteboard = NSPasteboard::generalPasteboard(nil);
+                let text = pasteboard.stringForType(NSPasteboardTypeString);
+                if text == nil {
+                    None
+                } else {
+                    Some(from_ns_string(text))
+                }
+            })
+        }
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        unsafe {
+            autoreleasepool(|| {
+                let pasteboard = NSPasteboard::generalPasteboard(nil);
+                pasteboard.clearContents();
+                pasteboard.setString_forType(ns_string(text), NSPasteboardTypeString);
+            });
+        }
+    }
+
+    fn open_file_dialog(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        unsafe {
+            autoreleasepool(|| {
+                let panel = NSOpenPanel::openPanel(nil);
+                panel.setCanChooseFiles_(YES);
+                panel.setCanChooseDirectories_(NO);
+                panel.setAllowsMultipleSelection_(if multi_select { YES } else { NO });
+                self.show_file_dialog(panel, filters, true)
+            })
+        }
+    }
+
+    fn save_file_dialog(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        unsafe {
+            autoreleasepool(|| {
+                let panel = NSSavePanel::savePanel(nil);
+                panel.setCanCreateDirectories(YES);
+                let _: () = msg_send![panel, setNameFieldStringValue: ns_string(default_name)];
+                self.show_file_dialog(panel, filters, false)
+            })
+        }
+    }
+}
+
+impl MacOSPlatform {
+    /// Show the panel without blocking, its completion handler is called by the event loop
+    ///
+    /// Panels have no filter selector, they show the files matching any of the filters.
+    unsafe fn show_file_dialog(
+        &self,
+        panel: id,
+        filters: &[FileFilter],
+        is_open_panel: bool,
+    ) -> FileDialogId {
+        let dialog_id = FileDialogId::new();
+        if !filters.is_empty() {
+            let extensions: Vec<id> = filters
+                .iter()
+                .flat_map(|filter| filter.extensions.iter())
+                .map(|extension| ns_string(extension))
+                .collect();
+            let _: () = msg_send![panel,
+                setAllowedFileTypes: NSArray::arrayWithObjects(nil, &extensions)];
+        }
+
+        // Released once the panel is closed
+        let panel: id = msg_send![panel, retain];
+        let message_queue = self.message_queue.clone();
+        let handler = ConcreteBlock::new(move |response: NSInteger| {
+            let mut paths = vec![];
+            if response == NSModalResponse::NSModalResponseOk as NSInteger {
+                let urls = if is_open_panel {
+                    panel.URLs()
+                } else {
+                    NSArray::arrayWithObject(nil, panel.URL())
+                };
+                for index in 0..urls.count() {
+                    let path: id = msg_send![urls.objectAtIndex(index), path];
+                    paths.push(PathBuf::from(from_ns_string(path)));
+                }
+            }
+
+            message_queue
+                .lock()
+                .push_back(Message::FileDialogClosed(dialog_id, paths));
+            let _: () = msg_send![panel, release];
+        })
+        .copy();
+        let _: () = msg_send![panel, beginWithCompletionHandler: &*handler];
+        dialog_id
+    }
+}
+
+fn system_observer_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let mut decl = ClassDecl::new("ZESystemObserver", class!(NSObject)).unwrap();
+        decl.add_ivar::<*mut c_void>(MESSAGE_QUEUE_IVAR);
+        decl.add_method(
+            sel!(screenParametersChanged:),
+            screen_parameters_changed as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(interfaceThemeChanged:),
+            interface_theme_changed as extern "C" fn(&Object, Sel, id),
+        );
+        decl.register();
+    });
+
+    Class::get("ZESystemObserver").unwrap()
+}
+
+/// The monitors are enumerated again by `monitor_count`
+extern "C" fn screen_parameters_changed(this: &Object, _: Sel, _: id) {
+    unsafe {
+        let message_queue =
+            *this.get_ivar::<*mut c_void>(MESSAGE_QUEUE_IVAR) as *const Mutex<VecDeque<Message>>;
+        if let Some(message_queue) = message_queue.as_ref() {
+            message_queue.lock().push_back(Message::MonitorsChanged);
+        }
+    }
+}
+
+extern "C" fn interface_theme_changed(this: &Object, _: Sel, _: id) {
+    unsafe {
+        let message_queue =
+            *this.get_ivar::<*mut c_void>(MESSAGE_QUEUE_IVAR) as *const Mutex<VecDeque<Message>>;
+        if let Some(message_queue) = message_queue.as_ref() {
+            message_queue
+                .lock()
+                .push_back(Message::SystemThemeChanged(read_system_theme()));
+        }
     }
 }
+
+/// The interface style default only exists, set to "Dark", in dark mode
+fn read_system_theme() -> SystemTheme {
+    unsafe {
+        autoreleasepool(|| {
+            let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+            let style: id = msg_send![defaults, stringForKey: ns_string("AppleInterfaceStyle")];
+            if from_ns_string(style) == "Dark" {
+                SystemTheme::Dark
+            } else {
+                SystemTheme::Light
+            }
+        })
+    }
+}
+
+/// Low power mode is only available since macOS 12
+fn read_power_status() -> PowerStatus {
+    unsafe {
+        autoreleasepool(|| {
+            let mut status = PowerStatus::default();
+
+            let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+            let responds: BOOL =
+                msg_send![process_info, respondsToSelector: sel!(isLowPowerModeEnabled)];
+            if responds == YES {
+                let low_power: BOOL = msg_send![process_info, isLowPowerModeEnabled];
+                status.power_saver = low_power == YES;
+            }
+
+            let blob = IOPSCopyPowerSourcesInfo();
+            if blob == nil {
+                return status;
+            }
+
+            status.source = match from_ns_string(IOPSGetProvidingPowerSourceType(blob)).as_str() {
+                "AC Power" => PowerSource::Ac,
+                "Battery Power" | "UPS Power" => PowerSource::Battery,
+                _ => PowerSource::Unknown,
+            };
+
+            // Capacities are percentages on most batteries, but may be in mAh
+            let sources = IOPSCopyPowerSourcesList(blob);
+            if sources != nil {
+                for index in 0..sources.count() {
+                    let description =
+                        IOPSGetPowerSourceDescription(blob, sources.objectAtIndex(index));
+                    if description == nil {
+                        continue;
+                    }
+
+                    let source_type: id = msg_send![description, objectForKey: ns_string("Type")];
+                    if from_ns_string(source_type) != "InternalBattery" {
+                        continue;
+                    }
+
+                    let current: id =
+                        msg_send![description, objectForKey: ns_string("Current Capacity")];
+                    let max: id = msg_send![description, objectForKey: ns_string("Max Capacity")];
+                    if current != nil && max != nil {
+                        let current: f64 = msg_send![current, doubleValue];
+                        let max: f64 = msg_send![max, doubleValue];
+                        if max > 0.0 {
+                            status.battery_percentage =
+                                Some((current / max * 100.0).round().clamp(0.0, 100.0) as u8);
+                            break;
+                        }
+                    }
+                }
+                let _: () = msg_send![sources, release];
+            }
+            let _: () = msg_send![blob, release];
+
+            status
+        })
+    }
+}
+
+mod cursor;
+mod utils;
+mod window;
@@ -0,0 +1,111 @@
+use cocoa::appkit::NSScreen;
+use cocoa::base::{id, nil, NO, YES};
+use cocoa::foundation::{
+    NSArray, NSAutoreleasePool, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger,
+};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::CStr;
+use std::ptr;
+use ze_core::maths::{Point2, RectI32};
+
+/// `NSBitmapFormatAlphaNonpremultiplied`
+const BITMAP_FORMAT_ALPHA_NONPREMULTIPLIED: NSUInteger = 1 << 1;
+
+/// Height and scale of the primary screen, the one with the menu bar
+///
+/// Cocoa screen coordinates are in points from the bottom left of the primary screen, positions
+/// exposed by the platform are in pixels of the primary screen from its top left.
+pub unsafe fn primary_screen_geometry() -> (f64, f64) {
+    let screens = NSScreen::screens(nil);
+    if screens.count() == 0 {
+        return (0.0, 1.0);
+    }
+
+    let screen = screens.objectAtIndex(0);
+    (
+        NSScreen::frame(screen).size.height,
+        NSScreen::backingScaleFactor(screen),
+    )
+}
+
+pub unsafe fn point_to_pixels(point: NSPoint) -> Point2<i32> {
+    let (height, scale) = primary_screen_geometry();
+    Point2::new(
+        (point.x * scale).round() as i32,
+        ((height - point.y) * scale).round() as i32,
+    )
+}
+
+pub unsafe fn pixels_to_point(point: Point2<i32>) -> NSPoint {
+    let (height, scale) = primary_screen_geometry();
+    NSPoint::new(point.x as f64 / scale, height - point.y as f64 / scale)
+}
+
+pub unsafe fn rect_to_pixels(rect: NSRect) -> RectI32 {
+    let (_, scale) = primary_screen_geometry();
+    let top_left = point_to_pixels(NSPoint::new(
+        rect.origin.x,
+        rect.origin.y + rect.size.height,
+    ));
+    RectI32::new(
+        top_left.x,
+        top_left.y,
+        (rect.size.width * scale).round() as i32,
+        (rect.size.height * scale).round() as i32,
+    )
+}
+
+/// Create an autoreleased `NSString`
+pub unsafe fn ns_string(string: &str) -> id {
+    NSString::alloc(nil).init_str(string).autorelease()
+}
+
+pub unsafe fn from_ns_string(string: id) -> String {
+    if string == nil {
+        return String::new();
+    }
+
+    CStr::from_ptr(string.UTF8String())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Drain the objects autoreleased by `f`
+pub unsafe fn autoreleasepool<R>(f: impl FnOnce() -> R) -> R {
+    let pool = NSAutoreleasePool::new(nil);
+    let result = f();
+    pool.drain();
+    result
+}
+
+/// Create a `NSImage` of `size` points from `width * height` 8-bit RGBA pixels, the caller
+/// owns it. Returns nil if the bitmap can't be allocated
+pub unsafe fn ns_image_from_rgba(rgba: &[u8], width: u32, height: u32, size: NSSize) -> id {
+    let bitmap: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let bitmap: id = msg_send![bitmap,
+        initWithBitmapDataPlanes: ptr::null_mut::<*mut u8>()
+        pixelsWide: width as NSInteger
+        pixelsHigh: height as NSInteger
+        bitsPerSample: 8 as NSInteger
+        samplesPerPixel: 4 as NSInteger
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: ns_string("NSDeviceRGBColorSpace")
+        bitmapFormat: BITMAP_FORMAT_ALPHA_NONPREMULTIPLIED
+        bytesPerRow: (width * 4) as NSInteger
+        bitsPerPixel: 32 as NSInteger];
+    if bitmap == nil {
+        return nil;
+    }
+
+    // The bitmap allocated its own buffer as no planes were given
+    let byte_count = (width * height * 4) as usize;
+    let data: *mut u8 = msg_send![bitmap, bitmapData];
+    ptr::copy_nonoverlapping(rgba[..byte_count].as_ptr(), data, byte_count);
+
+    let image: id = msg_send![class!(NSImage), alloc];
+    let image: id = msg_send![image, initWithSize: size];
+    let _: () = msg_send![image, addRepresentation: bitmap];
+    let _: () = msg_send![bitmap, release];
+    image
+}
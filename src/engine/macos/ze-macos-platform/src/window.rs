@@ -0,0 +1,790 @@
+use crate::utils::{
+    autoreleasepool, from_ns_string, ns_image_from_rgba, ns_string, pixels_to_point,
+    point_to_pixels,
+};
+use crate::DEFAULT_DPI;
+use cocoa::appkit::{NSApplication, NSEvent, NSFilenamesPboardType, NSView, NSWindow};
+use cocoa::base::{id, nil, BOOL, NO, YES};
+use cocoa::foundation::{NSArray, NSInteger, NSNotFound, NSPoint, NSRect, NSSize, NSUInteger};
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Protocol, Sel};
+use objc::{class, msg_send, sel, sel_impl, Encode, Encoding};
+use parking_lot::Mutex;
+use raw_window_handle::{
+    AppKitDisplayHandle, AppKitWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::path::PathBuf;
+use std::ptr::null_mut;
+use std::sync::{Arc, Once, Weak};
+use ze_core::maths::Point2;
+use ze_platform::{HitTest, HitTestCallback, Message, TaskbarProgressState, Window};
+
+const DELEGATE_STATE_IVAR: &str = "zeState";
+
+/// `NSDragOperationCopy`, dropped files are never moved
+const DRAG_OPERATION_COPY: NSUInteger = 1;
+
+/// `NSNormalWindowLevel` and `NSFloatingWindowLevel`
+const NORMAL_WINDOW_LEVEL: NSInteger = 0;
+const FLOATING_WINDOW_LEVEL: NSInteger = 3;
+
+/// `NSCriticalRequest`, the dock icon bounces until the application is activated
+const CRITICAL_REQUEST: NSInteger = 0;
+
+/// Height in points of the progress bar drawn over the dock icon
+const DOCK_PROGRESS_HEIGHT: f64 = 20.0;
+
+/// `NSTrackingAreaOptions` of the tracking area sending the mouse entered and exited events
+const TRACKING_MOUSE_ENTERED_AND_EXITED: NSUInteger = 0x01;
+const TRACKING_ACTIVE_ALWAYS: NSUInteger = 0x80;
+const TRACKING_IN_VISIBLE_RECT: NSUInteger = 0x200;
+
+/// `NSRange`, cocoa doesn't implement `Encode` for it so it can't be used by methods
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Range {
+    location: NSUInteger,
+    length: NSUInteger,
+}
+
+impl Range {
+    const NOT_FOUND: Range = Range {
+        location: NSNotFound as NSUInteger,
+        length: 0,
+    };
+}
+
+unsafe impl Encode for Range {
+    fn encode() -> Encoding {
+        let encoding = format!(
+            "{{_NSRange={}{}}}",
+            NSUInteger::encode().as_str(),
+            NSUInteger::encode().as_str()
+        );
+        unsafe { Encoding::from_str(&encoding) }
+    }
+}
+
+/// State shared with the window delegate and content view, used to send the window messages
+struct DelegateState {
+    window: Weak<MacOSWindow>,
+    message_queue: Arc<Mutex<VecDeque<Message>>>,
+}
+
+pub struct MacOSWindow {
+    window: id,
+    view: id,
+    delegate: id,
+    delegate_state: *mut DelegateState,
+    hit_test_callback: Mutex<Option<HitTestCallback>>,
+
+    /// Text being composed with the input method
+    marked_text: Mutex<String>,
+    ime_position: Mutex<Point2<i32>>,
+}
+
+// AppKit objects are only used from the main thread, which owns the platform
+unsafe impl Send for MacOSWindow {}
+unsafe impl Sync for MacOSWindow {}
+
+impl MacOSWindow {
+    /// Take ownership of a window created with the class returned by [`window_class`]
+    pub fn new(window: id, message_queue: Arc<Mutex<VecDeque<Message>>>) -> Arc<MacOSWindow> {
+        Arc::new_cyclic(|handle| unsafe {
+            let delegate_state = Box::into_raw(Box::new(DelegateState {
+                window: handle.clone(),
+                message_queue,
+            }));
+
+            let delegate: id = msg_send![delegate_class(), new];
+            (*delegate).set_ivar(DELEGATE_STATE_IVAR, delegate_state as *mut c_void);
+            window.setDelegate_(delegate);
+
+            // Dragging destination messages are forwarded to the delegate
+            let dragged_types = NSArray::arrayWithObject(nil, NSFilenamesPboardType);
+            let _: () = msg_send![window, registerForDraggedTypes: dragged_types];
+
+            // The content view receives the text of the input method
+            let view: id = msg_send![view_class(), alloc];
+            let view = view.initWithFrame_(NSView::frame(window.contentView()));
+            (*view).set_ivar(DELEGATE_STATE_IVAR, delegate_state as *mut c_void);
+            window.setContentView_(view);
+            window.makeFirstResponder_(view);
+
+            // Covers the visible part of the view whatever its size, so the rect is ignored
+            let options = TRACKING_MOUSE_ENTERED_AND_EXITED
+                | TRACKING_ACTIVE_ALWAYS
+                | TRACKING_IN_VISIBLE_RECT;
+            let rect = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0));
+            let tracking_area: id = msg_send![class!(NSTrackingArea), alloc];
+            let tracking_area: id = msg_send![
+                tracking_area,
+                initWithRect: rect
+                options: options
+                owner: view
+                userInfo: nil
+            ];
+            let _: () = msg_send![view, addTrackingArea: tracking_area];
+            let _: () = msg_send![tracking_area, release];
+
+            MacOSWindow {
+                window,
+                view,
+                delegate,
+                delegate_state,
+                hit_test_callback: Mutex::new(None),
+                marked_text: Mutex::new(String::new()),
+                ime_position: Mutex::new(Point2::new(0, 0)),
+            }
+        })
+    }
+
+    pub fn id(&self) -> id {
+        self.window
+    }
+
+    pub fn scale(&self) -> f64 {
+        unsafe { NSWindow::backingScaleFactor(self.window) }
+    }
+
+    /// Convert a position in the window from an event to pixels from the top left of the client area
+    pub fn window_to_client(&self, point: NSPoint) -> Point2<i32> {
+        let scale = self.scale();
+        let height = unsafe { NSView::frame(self.window.contentView()).size.height };
+        Point2::new(
+            (point.x * scale).round() as i32,
+            ((height - point.y) * scale).round() as i32,
+        )
+    }
+
+    /// Start dragging the window if the hit test callback returns a caption,
+    /// returns false if the click must be handled by the application
+    ///
+    /// AppKit resizes resizable windows from their edges itself, even when borderless.
+    pub fn hit_test(&self, event: id, position: Point2<i32>) -> bool {
+        let hit_test_callback = self.hit_test_callback.lock();
+        let callback = match hit_test_callback.as_ref() {
+            Some(callback) => callback,
+            None => return false,
+        };
+
+        match callback(position) {
+            HitTest::Caption => {
+                unsafe {
+                    let _: () = msg_send![self.window, performWindowDragWithEvent: event];
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Let the input method handle a key down event, the typed text is sent by the content view
+    pub fn interpret_key_event(&self, event: id) {
+        unsafe {
+            let events = NSArray::arrayWithObject(nil, event);
+            let _: () = msg_send![self.view, interpretKeyEvents: events];
+        }
+    }
+
+    fn content_rect(&self) -> NSRect {
+        unsafe {
+            self.window
+                .contentRectForFrameRect_(NSWindow::frame(self.window))
+        }
+    }
+}
+
+impl Drop for MacOSWindow {
+    fn drop(&mut self) {
+        unsafe {
+            self.window.setDelegate_(nil);
+            (*self.view).set_ivar(DELEGATE_STATE_IVAR, null_mut::<c_void>());
+            self.window.close();
+            let _: () = msg_send![self.window, release];
+            let _: () = msg_send![self.view, release];
+            let _: () = msg_send![self.delegate, release];
+            drop(Box::from_raw(self.delegate_state));
+        }
+    }
+}
+
+impl Window for MacOSWindow {
+    fn set_position(&self, position: Point2<i32>) {
+        unsafe {
+            // The position is the one of the client area, the frame includes the title bar
+            let frame = NSWindow::frame(self.window);
+            let content_rect = self.content_rect();
+            let mut top_left = pixels_to_point(position);
+            top_left.y += frame.size.height - content_rect.size.height;
+            self.window.setFrameTopLeftPoint_(top_left);
+        }
+    }
+
+    fn set_size(&self, width: u32, height: u32) {
+        let scale = self.scale();
+        unsafe {
+            self.window
+                .setContentSize_(NSSize::new(width as f64 / scale, height as f64 / scale));
+        }
+    }
+
+    /// AppKit keeps the limits in points, they follow the scale of the screen it was set on
+    fn set_min_size(&self, size: Option<(u32, u32)>) {
+        let scale = self.scale();
+        let (width, height) = size.map_or((0.0, 0.0), |(width, height)| {
+            (width as f64 / scale, height as f64 / scale)
+        });
+        unsafe {
+            self.window.setContentMinSize_(NSSize::new(width, height));
+        }
+    }
+
+    fn set_max_size(&self, size: Option<(u32, u32)>) {
+        let scale = self.scale();
+        let (width, height) = size.map_or((f32::MAX as f64, f32::MAX as f64), |(width, height)| {
+            (width as f64 / scale, height as f64 / scale)
+        });
+        unsafe {
+            self.window.setContentMaxSize_(NSSize::new(width, height));
+        }
+    }
+
+    fn set_title(&self, title: &str) {
+        unsafe {
+            autoreleasepool(|| self.window.setTitle_(ns_string(title)));
+        }
+    }
+
+    /// Windows have no icon on macOS, the application icon shown in the Dock is changed instead
+    fn set_icon(&self, width: u32, height: u32, rgba: &[u8]) {
+        unsafe {
+            autoreleasepool(|| {
+                let image = ns_image_from_rgba(
+                    rgba,
+                    width,
+                    height,
+                    NSSize::new(width as f64, height as f64),
+                );
+                if image == nil {
+                    return;
+                }
+
+                let app = NSApplication::sharedApplication(nil);
+                let _: () = msg_send![app, setApplicationIconImage: image];
+                let _: () = msg_send![image, release];
+            });
+        }
+    }
+
+    fn show(&self) {
+        unsafe {
+            self.window.makeKeyAndOrderFront_(nil);
+        }
+    }
+
+    fn close(&self) {
+        unsafe {
+            self.window.orderOut_(nil);
+        }
+    }
+
+    fn minimize(&self) {
+        unsafe {
+            self.window.miniaturize_(nil);
+        }
+    }
+
+    /// Zooming toggles between the user size and the largest size fitting the screen
+    fn maximize(&self) {
+        unsafe {
+            let zoomed: BOOL = msg_send![self.window, isZoomed];
+            if zoomed == NO {
+                self.window.zoom_(nil);
+            }
+        }
+    }
+
+    fn restore(&self) {
+        unsafe {
+            let zoomed: BOOL = msg_send![self.window, isZoomed];
+            if self.is_minimized() {
+                self.window.deminiaturize_(nil);
+            } else if zoomed == YES {
+                self.window.zoom_(nil);
+            }
+        }
+    }
+
+    fn focus(&self) {
+        unsafe {
+            NSApplication::sharedApplication(nil).activateIgnoringOtherApps_(YES);
+            self.window.makeKeyAndOrderFront_(nil);
+        }
+    }
+
+    fn set_opacity(&self, opacity: f32) {
+        unsafe {
+            self.window.setAlphaValue_(opacity.clamp(0.0, 1.0) as f64);
+        }
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) {
+        unsafe {
+            self.window.setLevel_(if always_on_top {
+                FLOATING_WINDOW_LEVEL
+            } else {
+                NORMAL_WINDOW_LEVEL
+            });
+        }
+    }
+
+    /// The progress is drawn over the dock icon of the application, shared by its windows
+    fn set_taskbar_progress(&self, state: TaskbarProgressState, fraction: f32) {
+        unsafe { set_dock_progress(state, fraction) };
+    }
+
+    fn request_attention(&self) {
+        if self.is_focused() {
+            return;
+        }
+
+        unsafe {
+            let _: NSInteger = msg_send![
+                NSApplication::sharedApplication(nil),
+                requestUserAttention: CRITICAL_REQUEST
+            ];
+        }
+    }
+
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>) {
+        *self.hit_test_callback.lock() = callback;
+    }
+
+    /// Read by the input method with `firstRectForCharacterRange:actualRange:`
+    fn set_ime_position(&self, position: Point2<i32>) {
+        *self.ime_position.lock() = position;
+    }
+
+    fn handle(&self) -> RawWindowHandle {
+        let mut handle = AppKitWindowHandle::empty();
+        handle.ns_window = self.window as *mut c_void;
+        handle.ns_view = unsafe { self.window.contentView() } as *mut c_void;
+        RawWindowHandle::AppKit(handle)
+    }
+
+    fn display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
+    }
+
+    fn width(&self) -> u32 {
+        (self.content_rect().size.width * self.scale()).round() as u32
+    }
+
+    fn height(&self) -> u32 {
+        (self.content_rect().size.height * self.scale()).round() as u32
+    }
+
+    fn position(&self) -> Point2<i32> {
+        let content_rect = self.content_rect();
+        unsafe {
+            point_to_pixels(NSPoint::new(
+                content_rect.origin.x,
+                content_rect.origin.y + content_rect.size.height,
+            ))
+        }
+    }
+
+    fn is_minimized(&self) -> bool {
+        unsafe {
+            let miniaturized: BOOL = msg_send![self.window, isMiniaturized];
+            miniaturized == YES
+        }
+    }
+
+    fn is_focused(&self) -> bool {
+        unsafe { self.window.isKeyWindow() == YES }
+    }
+
+    fn cursor_position(&self) -> Point2<i32> {
+        self.window_to_client(unsafe { self.window.mouseLocationOutsideOfEventStream() })
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        self.scale() as f32
+    }
+}
+
+/// Window class accepting the keyboard focus when borderless
+pub fn window_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let mut decl = ClassDecl::new("ZEWindow", class!(NSWindow)).unwrap();
+        decl.add_method(
+            sel!(canBecomeKeyWindow),
+            can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(canBecomeMainWindow),
+            can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.register();
+    });
+
+    Class::get("ZEWindow").unwrap()
+}
+
+extern "C" fn can_become_key_window(_: &Object, _: Sel) -> BOOL {
+    YES
+}
+
+fn delegate_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let mut decl = ClassDecl::new("ZEWindowDelegate", class!(NSObject)).unwrap();
+        decl.add_ivar::<*mut c_void>(DELEGATE_STATE_IVAR);
+        decl.add_method(
+            sel!(windowShouldClose:),
+            window_should_close as extern "C" fn(&Object, Sel, id) -> BOOL,
+        );
+        decl.add_method(
+            sel!(windowDidResize:),
+            window_did_resize as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(windowDidChangeBackingProperties:),
+            window_did_change_backing_properties as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(windowDidBecomeKey:),
+            window_did_become_key as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(windowDidResignKey:),
+            window_did_resign_key as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(draggingEntered:),
+            dragging_entered as extern "C" fn(&Object, Sel, id) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(performDragOperation:),
+            perform_drag_operation as extern "C" fn(&Object, Sel, id) -> BOOL,
+        );
+        decl.register();
+    });
+
+    Class::get("ZEWindowDelegate").unwrap()
+}
+
+/// View implementing `NSTextInputClient` so input methods can compose text
+fn view_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe {
+        let mut decl = ClassDecl::new("ZEView", class!(NSView)).unwrap();
+        decl.add_ivar::<*mut c_void>(DELEGATE_STATE_IVAR);
+        decl.add_protocol(Protocol::get("NSTextInputClient").unwrap());
+        decl.add_method(
+            sel!(acceptsFirstResponder),
+            can_become_key_window as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(hasMarkedText),
+            has_marked_text as extern "C" fn(&Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(markedRange),
+            marked_range as extern "C" fn(&Object, Sel) -> Range,
+        );
+        decl.add_method(
+            sel!(selectedRange),
+            selected_range as extern "C" fn(&Object, Sel) -> Range,
+        );
+        decl.add_method(
+            sel!(setMarkedText:selectedRange:replacementRange:),
+            set_marked_text as extern "C" fn(&Object, Sel, id, Range, Range),
+        );
+        decl.add_method(sel!(unmarkText), unmark_text as extern "C" fn(&Object, Sel));
+        decl.add_method(
+            sel!(validAttributesForMarkedText),
+            valid_attributes_for_marked_text as extern "C" fn(&Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(attributedSubstringForProposedRange:actualRange:),
+            attributed_substring_for_proposed_range
+                as extern "C" fn(&Object, Sel, Range, *mut c_void) -> id,
+        );
+        decl.add_method(
+            sel!(insertText:replacementRange:),
+            insert_text as extern "C" fn(&Object, Sel, id, Range),
+        );
+        decl.add_method(
+            sel!(characterIndexForPoint:),
+            character_index_for_point as extern "C" fn(&Object, Sel, NSPoint) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(firstRectForCharacterRange:actualRange:),
+            first_rect_for_character_range
+                as extern "C" fn(&Object, Sel, Range, *mut c_void) -> NSRect,
+        );
+        decl.add_method(
+            sel!(doCommandBySelector:),
+            do_command_by_selector as extern "C" fn(&Object, Sel, Sel),
+        );
+        decl.register();
+    });
+
+    Class::get("ZEView").unwrap()
+}
+
+/// State of a delegate or view, null once the window is dropped
+fn delegate_state(object: &Object) -> Option<&DelegateState> {
+    unsafe {
+        let state = *object.get_ivar::<*mut c_void>(DELEGATE_STATE_IVAR) as *const DelegateState;
+        state.as_ref()
+    }
+}
+
+/// Send a message built from the window of the delegate or view, if it is still alive
+fn send_message(object: &Object, f: impl FnOnce(&MacOSWindow, Weak<MacOSWindow>) -> Message) {
+    if let Some(state) = delegate_state(object) {
+        if let Some(window) = state.window.upgrade() {
+            let message = f(&window, state.window.clone());
+            state.message_queue.lock().push_back(message);
+        }
+    }
+}
+
+fn window(object: &Object) -> Option<Arc<MacOSWindow>> {
+    delegate_state(object)?.window.upgrade()
+}
+
+/// Closing is left to the application
+extern "C" fn window_should_close(this: &Object, _: Sel, _: id) -> BOOL {
+    send_message(this, |_, handle| Message::WindowCloseRequested(handle));
+    NO
+}
+
+extern "C" fn window_did_resize(this: &Object, _: Sel, _: id) {
+    send_message(this, |window, handle| {
+        Message::WindowResized(handle, window.width(), window.height())
+    });
+}
+
+/// Sent when the window moves to a screen with a different scale
+extern "C" fn window_did_change_backing_properties(this: &Object, _: Sel, _: id) {
+    send_message(this, |window, handle| {
+        Message::WindowDpiChanged(handle, DEFAULT_DPI * window.scale() as f32)
+    });
+
+    // The size in pixels changed with the scale
+    send_message(this, |window, handle| {
+        Message::WindowResized(handle, window.width(), window.height())
+    });
+}
+
+extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) {
+    send_message(this, |_, handle| Message::WindowFocusGained(handle));
+}
+
+extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
+    send_message(this, |_, handle| Message::WindowFocusLost(handle));
+}
+
+extern "C" fn dragging_entered(_: &Object, _: Sel, _: id) -> NSUInteger {
+    DRAG_OPERATION_COPY
+}
+
+extern "C" fn perform_drag_operation(this: &Object, _: Sel, dragging_info: id) -> BOOL {
+    let files = unsafe {
+        let pasteboard: id = msg_send![dragging_info, draggingPasteboard];
+        let paths: id = msg_send![pasteboard, propertyListForType: NSFilenamesPboardType];
+        if paths == nil {
+            return NO;
+        }
+
+        (0..paths.count())
+            .map(|index| PathBuf::from(from_ns_string(paths.objectAtIndex(index))))
+            .collect::<Vec<_>>()
+    };
+
+    let position = unsafe { point_to_pixels(NSEvent::mouseLocation(nil)) };
+    send_message(this, |_, handle| {
+        Message::FilesDropped(handle, files, position)
+    });
+    YES
+}
+
+extern "C" fn has_marked_text(this: &Object, _: Sel) -> BOOL {
+    match window(this) {
+        Some(window) if !window.marked_text.lock().is_empty() => YES,
+        _ => NO,
+    }
+}
+
+extern "C" fn marked_range(this: &Object, _: Sel) -> Range {
+    match window(this) {
+        Some(window) if !window.marked_text.lock().is_empty() => Range {
+            location: 0,
+            length: window.marked_text.lock().encode_utf16().count() as NSUInteger,
+        },
+        _ => Range::NOT_FOUND,
+    }
+}
+
+/// The text of the application isn't exposed to the input method
+extern "C" fn selected_range(_: &Object, _: Sel) -> Range {
+    Range::NOT_FOUND
+}
+
+extern "C" fn set_marked_text(this: &Object, _: Sel, text: id, _: Range, _: Range) {
+    let text = unsafe { input_string(text) };
+    send_message(this, |window, handle| {
+        *window.marked_text.lock() = text.clone();
+        Message::TextComposition(handle, text)
+    });
+}
+
+extern "C" fn unmark_text(this: &Object, _: Sel) {
+    end_composition(this);
+}
+
+extern "C" fn valid_attributes_for_marked_text(_: &Object, _: Sel) -> id {
+    unsafe { NSArray::array(nil) }
+}
+
+extern "C" fn attributed_substring_for_proposed_range(
+    _: &Object,
+    _: Sel,
+    _: Range,
+    _: *mut c_void,
+) -> id {
+    nil
+}
+
+/// Text typed or committed by the input method
+extern "C" fn insert_text(this: &Object, _: Sel, text: id, _: Range) {
+    end_composition(this);
+
+    // Function keys are sent as characters of the private use area
+    let text = unsafe { input_string(text) };
+    for character in text.chars() {
+        if !character.is_control() && !('\u{f700}'..='\u{f8ff}').contains(&character) {
+            send_message(this, |_, handle| Message::TextInput(handle, character));
+        }
+    }
+}
+
+extern "C" fn character_index_for_point(_: &Object, _: Sel, _: NSPoint) -> NSUInteger {
+    NSNotFound as NSUInteger
+}
+
+/// Screen rect the input method shows its candidates below
+extern "C" fn first_rect_for_character_range(
+    this: &Object,
+    _: Sel,
+    _: Range,
+    _: *mut c_void,
+) -> NSRect {
+    let window = match window(this) {
+        Some(window) => window,
+        None => return NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(0.0, 0.0)),
+    };
+
+    let position = *window.ime_position.lock();
+    let scale = window.scale();
+    unsafe {
+        let height = NSView::frame(window.view).size.height;
+        let rect = NSRect::new(
+            NSPoint::new(
+                position.x as f64 / scale,
+                height - position.y as f64 / scale,
+            ),
+            NSSize::new(0.0, 0.0),
+        );
+        window.window.convertRectToScreen_(rect)
+    }
+}
+
+/// Commands like moving the cursor are already sent as key events
+extern "C" fn do_command_by_selector(_: &Object, _: Sel, _: Sel) {}
+
+fn end_composition(object: &Object) {
+    if let Some(window) = window(object) {
+        if window.marked_text.lock().is_empty() {
+            return;
+        }
+
+        window.marked_text.lock().clear();
+        send_message(object, |_, handle| {
+            Message::TextComposition(handle, String::new())
+        });
+    }
+}
+
+/// Text given to the view, either a `NSString` or a `NSAttributedString`
+unsafe fn input_string(text: id) -> String {
+    let attributed: BOOL = msg_send![text, isKindOfClass: class!(NSAttributedString)];
+    if attributed == YES {
+        from_ns_string(msg_send![text, string])
+    } else {
+        from_ns_string(text)
+    }
+}
+
+/// Show a progress bar over the application icon in the dock. The dock has no state for
+/// paused or failed progress, they are shown as normal progress
+unsafe fn set_dock_progress(state: TaskbarProgressState, fraction: f32) {
+    autoreleasepool(|| {
+        let app = NSApplication::sharedApplication(nil);
+        let dock_tile: id = msg_send![app, dockTile];
+        if state == TaskbarProgressState::None {
+            let _: () = msg_send![dock_tile, setContentView: nil];
+            let _: () = msg_send![dock_tile, display];
+            return;
+        }
+
+        // The content view replaces the icon, it is drawn by an image view under the bar
+        let mut content_view: id = msg_send![dock_tile, contentView];
+        if content_view == nil {
+            let size: NSSize = msg_send![dock_tile, size];
+            let image_view: id = msg_send![class!(NSImageView), alloc];
+            let image_view: id = msg_send![
+                image_view,
+                initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), size)
+            ];
+            let icon: id = msg_send![app, applicationIconImage];
+            let _: () = msg_send![image_view, setImage: icon];
+
+            let indicator: id = msg_send![class!(NSProgressIndicator), alloc];
+            let indicator: id = msg_send![
+                indicator,
+                initWithFrame: NSRect::new(
+                    NSPoint::new(0.0, 0.0),
+                    NSSize::new(size.width, DOCK_PROGRESS_HEIGHT)
+                )
+            ];
+            let _: () = msg_send![indicator, setMinValue: 0.0f64];
+            let _: () = msg_send![indicator, setMaxValue: 1.0f64];
+            let _: () = msg_send![image_view, addSubview: indicator];
+            let _: () = msg_send![indicator, release];
+
+            let _: () = msg_send![dock_tile, setContentView: image_view];
+            let _: () = msg_send![image_view, release];
+            content_view = image_view;
+        }
+
+        let subviews: id = msg_send![content_view, subviews];
+        let indicator = subviews.objectAtIndex(0);
+        let indeterminate = if state == TaskbarProgressState::Indeterminate {
+            YES
+        } else {
+            NO
+        };
+        let _: () = msg_send![indicator, setIndeterminate: indeterminate];
+        let _: () = msg_send![indicator, setDoubleValue: fraction.clamp(0.0, 1.0) as f64];
+        let _: () = msg_send![dock_tile, display];
+    });
+}
@@ -137,6 +137,16 @@ impl Device for MetalDevice {
         todo!()
     }
 
+    fn cmd_copy_texture_to_buffer_regions(
+        &self,
+        cmd_list: &mut ze_gfx::backend::CommandList,
+        src_texture: &ze_gfx::backend::Texture,
+        dst_buffer: &ze_gfx::backend::Buffer,
+        regions: &[ze_gfx::backend::BufferToTextureCopyRegion],
+    ) {
+        todo!()
+    }
+
     fn cmd_debug_begin_event(
         &self,
         cmd_list: &mut ze_gfx::backend::CommandList,
@@ -1,4 +1,5 @@
 ﻿use crate::device::cmd_list::D3D12CommandList;
+use crate::device::fence::D3D12Fence;
 use crate::device::D3D12Device;
 use crate::utils;
 use crate::utils::SendableIUnknown;
@@ -135,10 +136,19 @@ impl CommandQueue {
         wait_fences: &[&Fence],
         signal_fences: &[&Fence],
     ) {
-        for _ in wait_fences {
-            todo!()
-            //let current_value = fence.
-            //self.queue.Wait();
+        for fence in wait_fences {
+            let fence = unsafe {
+                fence
+                    .backend_data
+                    .downcast_ref::<D3D12Fence>()
+                    .unwrap_unchecked()
+            };
+
+            unsafe {
+                self.queue
+                    .Wait(fence.fence.deref(), fence.value.load(Ordering::SeqCst))
+                    .unwrap_unchecked();
+            }
         }
 
         let command_lists = {
@@ -166,14 +176,26 @@ impl CommandQueue {
             lists
         };
 
-        unsafe {
-            self.queue.ExecuteCommandLists(&command_lists);
+        if !command_lists.is_empty() {
+            unsafe {
+                self.queue.ExecuteCommandLists(&command_lists);
+            }
         }
 
-        for _ in signal_fences {
-            todo!()
-            //let current_value = fence.
-            //self.queue.Wait();
+        for fence in signal_fences {
+            let fence = unsafe {
+                fence
+                    .backend_data
+                    .downcast_ref::<D3D12Fence>()
+                    .unwrap_unchecked()
+            };
+
+            let value = fence.value.fetch_add(1, Ordering::SeqCst) + 1;
+            unsafe {
+                self.queue
+                    .Signal(fence.fence.deref(), value)
+                    .unwrap_unchecked();
+            }
         }
     }
 
@@ -7,7 +7,7 @@ use ze_gfx::backend::MAX_RENDER_PASS_RENDER_TARGET_COUNT;
 pub(crate) enum D3D12CommandListPipelineType {
     None,
     Graphics(GraphicsPipelineStateDesc),
-    Compute(D3D12_COMPUTE_PIPELINE_STATE_DESC),
+    Compute(D3D12_SHADER_BYTECODE),
 }
 
 unsafe impl Send for D3D12CommandListPipelineType {}
@@ -27,6 +27,7 @@ pub(crate) struct D3D12CommandList {
     pub render_pass_rt_count: u32,
     pub render_pass_rtv_formats: [DXGI_FORMAT; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
     pub render_pass_dsv_format: DXGI_FORMAT,
+    pub render_pass_sample_desc: DXGI_SAMPLE_DESC,
     pub pipeline_state_dirty: bool,
 }
 
@@ -38,6 +39,10 @@ impl D3D12CommandList {
             render_pass_rt_count: 0,
             render_pass_rtv_formats: [DXGI_FORMAT_UNKNOWN; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
             render_pass_dsv_format: DXGI_FORMAT_UNKNOWN,
+            render_pass_sample_desc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
             pipeline_state_dirty: true,
         }
     }
@@ -0,0 +1,23 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use crate::utils::SendableIUnknown;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use windows::Win32::Graphics::Direct3D12::*;
+
+pub(crate) struct D3D12Fence {
+    pub frame_manager: Arc<FrameManager>,
+    pub fence: SendableIUnknown<ID3D12Fence>,
+
+    /// Last value a queue has been asked to signal
+    pub value: AtomicU64,
+}
+
+impl Drop for D3D12Fence {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::Fence(self.fence.clone()));
+    }
+}
@@ -1,6 +1,8 @@
 pub(crate) mod buffer;
 pub(crate) mod cmd_list;
+pub(crate) mod fence;
 mod memory_pool;
+pub(crate) mod query_heap;
 pub(crate) mod sampler;
 pub(crate) mod shader;
 pub(crate) mod swapchain;
@@ -10,11 +12,15 @@ pub(crate) mod views;
 use crate::descriptor_manager::DescriptorManager;
 use crate::device::buffer::D3D12Buffer;
 use crate::device::cmd_list::{D3D12CommandList, D3D12CommandListPipelineType};
+use crate::device::fence::D3D12Fence;
+use crate::device::query_heap::D3D12QueryHeap;
 use crate::device::sampler::D3D12Sampler;
 use crate::device::shader::D3D12ShaderModule;
 use crate::device::swapchain::D3D12SwapChain;
 use crate::device::texture::D3D12Texture;
-use crate::device::views::{D3D12DepthStencilView, D3D12RenderTargetView, D3D12ShaderResourceView};
+use crate::device::views::{
+    D3D12DepthStencilView, D3D12RenderTargetView, D3D12ShaderResourceView, D3D12UnorderedAccessView,
+};
 use crate::frame_manager::FrameManager;
 use crate::pipeline_manager::{GraphicsPipelineStateDesc, PipelineManager};
 #[cfg(feature = "pix")]
@@ -28,18 +34,20 @@ use std::ops::Deref;
 use std::slice;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use windows::core::Interface;
-use windows::Win32::Foundation::{BOOL, HWND, RECT};
+use windows::core::{Interface, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, BOOL, HWND, RECT};
 use windows::Win32::Graphics::Direct3D::{ID3DBlob, D3D_PRIMITIVE_TOPOLOGY_TRIANGLELIST};
 use windows::Win32::Graphics::Direct3D12::*;
 use windows::Win32::Graphics::Dxgi::Common::*;
 use windows::Win32::Graphics::Dxgi::*;
+use windows::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+use windows::Win32::System::WindowsProgramming::INFINITE;
 
 use crate::device::memory_pool::D3D12MemoryPool;
 use ze_core::color::Color4f32;
 use ze_core::maths::RectI32;
 use ze_d3dmemoryallocator::{
-    AllocationDesc, Allocator, AllocatorDesc, PoolDesc, PoolFlagBits, PoolFlags,
+    AllocationDesc, Allocator, AllocatorDesc, Budget, PoolDesc, PoolFlagBits, PoolFlags,
 };
 use ze_gfx::backend::*;
 use ze_gfx::ShaderStageFlagBits;
@@ -200,6 +208,9 @@ impl D3D12Device {
                         NumRenderTargets: command_list.render_pass_rt_count,
                     };
                     *desc.dsv_format = command_list.render_pass_dsv_format;
+                    *desc.sample_desc = command_list.render_pass_sample_desc;
+                    desc.rasterizer_state.MultisampleEnable =
+                        BOOL::from(command_list.render_pass_sample_desc.Count > 1);
 
                     let pipeline = self
                         .pipeline_manager
@@ -208,7 +219,16 @@ impl D3D12Device {
                         command_list.cmd_list.SetPipelineState(&pipeline);
                     }
                 }
-                D3D12CommandListPipelineType::Compute(_) => todo!(),
+                D3D12CommandListPipelineType::Compute(compute_shader) => {
+                    let pipeline = self.pipeline_manager.get_or_create_compute_pipeline(
+                        &self.device,
+                        &self.default_root_signature,
+                        compute_shader,
+                    );
+                    unsafe {
+                        command_list.cmd_list.SetPipelineState(&pipeline);
+                    }
+                }
                 _ => {}
             }
 
@@ -216,6 +236,21 @@ impl D3D12Device {
         }
     }
 
+    fn set_compute_shader(&self, command_list: &mut D3D12CommandList, module: &ShaderModule) {
+        let module = unsafe {
+            module
+                .backend_data
+                .downcast_ref::<D3D12ShaderModule>()
+                .unwrap_unchecked()
+        };
+
+        command_list.pipeline = D3D12CommandListPipelineType::Compute(D3D12_SHADER_BYTECODE {
+            pShaderBytecode: module.bytecode.as_ptr() as *const c_void,
+            BytecodeLength: module.bytecode.len(),
+        });
+        command_list.pipeline_state_dirty = true;
+    }
+
     pub fn device(&self) -> &SendableIUnknown<ID3D12Device2> {
         &self.device
     }
@@ -283,26 +318,36 @@ impl Device for D3D12Device {
             },
         };
 
+        // Readback heaps resources must start in the copy destination state
+        let initial_state = match info.memory_desc.memory_location {
+            MemoryLocation::GpuToCpu => D3D12_RESOURCE_STATE_COPY_DEST,
+            _ => D3D12_RESOURCE_STATE_COMMON,
+        };
+
         match self
             .allocator
-            .create_resource(&allocation_desc, &buffer_desc)
+            .create_resource(&allocation_desc, &buffer_desc, initial_state)
         {
             Ok(allocation) => {
                 let resource = allocation.resource().unwrap();
-                let mapped_ptr = {
-                    if info.memory_desc.memory_location == MemoryLocation::CpuToGpu {
-                        unsafe {
-                            let mut mapped_ptr = std::ptr::null_mut();
-                            let range = D3D12_RANGE { Begin: 0, End: 0 };
-                            resource
-                                .Map(0, Some(&range), Some(&mut mapped_ptr))
-                                .unwrap();
-                            let mapped_ptr = mapped_ptr.cast::<u8>();
-                            Some(mapped_ptr)
-                        }
-                    } else {
-                        None
-                    }
+                let mapped_ptr = match info.memory_desc.memory_location {
+                    MemoryLocation::CpuToGpu => unsafe {
+                        let mut mapped_ptr = std::ptr::null_mut();
+                        let range = D3D12_RANGE { Begin: 0, End: 0 };
+                        resource
+                            .Map(0, Some(&range), Some(&mut mapped_ptr))
+                            .unwrap();
+                        let mapped_ptr = mapped_ptr.cast::<u8>();
+                        Some(mapped_ptr)
+                    },
+                    // Readback buffers stay mapped, the whole buffer may be read by the CPU
+                    MemoryLocation::GpuToCpu => unsafe {
+                        let mut mapped_ptr = std::ptr::null_mut();
+                        resource.Map(0, None, Some(&mut mapped_ptr)).unwrap();
+                        let mapped_ptr = mapped_ptr.cast::<u8>();
+                        Some(mapped_ptr)
+                    },
+                    MemoryLocation::GpuOnly => None,
                 };
 
                 let gpu_virtual_address = unsafe { resource.GetGPUVirtualAddress() };
@@ -370,9 +415,13 @@ impl Device for D3D12Device {
             Alignment: 0,
             Width: info.width as u64,
             Height: info.height,
-            DepthOrArraySize: info.depth as u16,
+            DepthOrArraySize: if info.depth > 1 {
+                info.depth as u16
+            } else {
+                info.array_size as u16
+            },
             MipLevels: info.mip_levels as u16,
-            Format: get_dxgi_format_from_ze_format(info.format),
+            Format: get_dxgi_resource_format_from_ze_format(info.format),
             SampleDesc: get_dxgi_sample_desc_from_ze_sample_desc(info.sample_desc),
             Layout: D3D12_TEXTURE_LAYOUT_UNKNOWN,
             Flags: flags,
@@ -397,7 +446,7 @@ impl Device for D3D12Device {
 
         match self
             .allocator
-            .create_resource(&allocation_desc, &texture_desc)
+            .create_resource(&allocation_desc, &texture_desc, D3D12_RESOURCE_STATE_COMMON)
         {
             Ok(allocation) => {
                 let resource = allocation.resource().unwrap();
@@ -471,7 +520,7 @@ impl Device for D3D12Device {
             }
             ShaderResourceViewDesc::Texture2D(texture) => {
                 let d3d_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
-                    Format: get_dxgi_format_from_ze_format(texture.format),
+                    Format: get_dxgi_srv_format_from_ze_format(texture.format),
                     Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
                     ViewDimension: D3D12_SRV_DIMENSION_TEXTURE2D,
                     Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
@@ -484,6 +533,111 @@ impl Device for D3D12Device {
                     },
                 };
 
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            ShaderResourceViewDesc::Texture2DArray(texture) => {
+                let d3d_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
+                    Format: get_dxgi_srv_format_from_ze_format(texture.format),
+                    Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+                    ViewDimension: D3D12_SRV_DIMENSION_TEXTURE2DARRAY,
+                    Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                        Texture2DArray: D3D12_TEX2D_ARRAY_SRV {
+                            MostDetailedMip: texture.min_mip_level,
+                            MipLevels: texture.mip_levels,
+                            FirstArraySlice: texture.first_array_slice,
+                            ArraySize: texture.array_size,
+                            PlaneSlice: 0,
+                            ResourceMinLODClamp: 0.0,
+                        },
+                    },
+                };
+
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            ShaderResourceViewDesc::TextureCube(texture) => {
+                let d3d_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
+                    Format: get_dxgi_srv_format_from_ze_format(texture.format),
+                    Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+                    ViewDimension: D3D12_SRV_DIMENSION_TEXTURECUBE,
+                    Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                        TextureCube: D3D12_TEXCUBE_SRV {
+                            MostDetailedMip: texture.min_mip_level,
+                            MipLevels: texture.mip_levels,
+                            ResourceMinLODClamp: 0.0,
+                        },
+                    },
+                };
+
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            ShaderResourceViewDesc::TextureCubeArray(texture) => {
+                let d3d_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
+                    Format: get_dxgi_srv_format_from_ze_format(texture.format),
+                    Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+                    ViewDimension: D3D12_SRV_DIMENSION_TEXTURECUBEARRAY,
+                    Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                        TextureCubeArray: D3D12_TEXCUBE_ARRAY_SRV {
+                            MostDetailedMip: texture.min_mip_level,
+                            MipLevels: texture.mip_levels,
+                            First2DArrayFace: texture.first_array_slice,
+                            NumCubes: texture.cube_count,
+                            ResourceMinLODClamp: 0.0,
+                        },
+                    },
+                };
+
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            ShaderResourceViewDesc::Texture3D(texture) => {
+                let d3d_desc = D3D12_SHADER_RESOURCE_VIEW_DESC {
+                    Format: get_dxgi_srv_format_from_ze_format(texture.format),
+                    Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+                    ViewDimension: D3D12_SRV_DIMENSION_TEXTURE3D,
+                    Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                        Texture3D: D3D12_TEX3D_SRV {
+                            MostDetailedMip: texture.min_mip_level,
+                            MipLevels: texture.mip_levels,
+                            ResourceMinLODClamp: 0.0,
+                        },
+                    },
+                };
+
                 (
                     texture
                         .texture
@@ -509,6 +663,138 @@ impl Device for D3D12Device {
             desc.clone(),
             Box::new(D3D12ShaderResourceView {
                 descriptor_manager: self.descriptor_manager.clone(),
+                frame_manager: self.frame_manager.clone(),
+                handle,
+            }),
+        ))
+    }
+
+    fn create_unordered_access_view(
+        &self,
+        desc: &UnorderedAccessViewDesc,
+    ) -> Result<UnorderedAccessView, DeviceError> {
+        let (resource, d3d_desc) = match desc {
+            UnorderedAccessViewDesc::Buffer(buffer) => {
+                let buffer_size = buffer.buffer.info.size_bytes;
+                let (format, d3d_buffer_uav) = match &buffer.ty {
+                    BufferSRVType::Raw(raw) => (
+                        DXGI_FORMAT_R32_TYPELESS,
+                        D3D12_BUFFER_UAV {
+                            FirstElement: raw.offset_in_bytes / 4,
+                            NumElements: ((buffer_size - raw.offset_in_bytes) / 4) as u32,
+                            StructureByteStride: 0,
+                            CounterOffsetInBytes: 0,
+                            Flags: D3D12_BUFFER_UAV_FLAG_RAW,
+                        },
+                    ),
+                    BufferSRVType::Structured(structured) => (
+                        DXGI_FORMAT_UNKNOWN,
+                        D3D12_BUFFER_UAV {
+                            FirstElement: structured.offset_in_bytes
+                                / structured.stride_in_bytes as u64,
+                            NumElements: ((buffer_size - structured.offset_in_bytes)
+                                / structured.stride_in_bytes as u64)
+                                as u32,
+                            StructureByteStride: structured.stride_in_bytes,
+                            CounterOffsetInBytes: 0,
+                            Flags: D3D12_BUFFER_UAV_FLAG_NONE,
+                        },
+                    ),
+                };
+
+                let d3d_desc = D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                    Format: format,
+                    ViewDimension: D3D12_UAV_DIMENSION_BUFFER,
+                    Anonymous: D3D12_UNORDERED_ACCESS_VIEW_DESC_0 {
+                        Buffer: d3d_buffer_uav,
+                    },
+                };
+
+                (
+                    buffer
+                        .buffer
+                        .backend_data
+                        .downcast_ref::<D3D12Buffer>()
+                        .unwrap()
+                        .resource
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            UnorderedAccessViewDesc::Texture2D(texture) => {
+                debug_assert!(texture
+                    .texture
+                    .desc
+                    .usage_flags
+                    .contains(TextureUsageFlagBits::UnorderedAccess));
+
+                let d3d_desc = D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                    Format: get_dxgi_format_from_ze_format(texture.format),
+                    ViewDimension: D3D12_UAV_DIMENSION_TEXTURE2D,
+                    Anonymous: D3D12_UNORDERED_ACCESS_VIEW_DESC_0 {
+                        Texture2D: D3D12_TEX2D_UAV {
+                            MipSlice: texture.mip_level,
+                            PlaneSlice: 0,
+                        },
+                    },
+                };
+
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+            UnorderedAccessViewDesc::Texture3D(texture) => {
+                debug_assert!(texture
+                    .texture
+                    .desc
+                    .usage_flags
+                    .contains(TextureUsageFlagBits::UnorderedAccess));
+
+                let d3d_desc = D3D12_UNORDERED_ACCESS_VIEW_DESC {
+                    Format: get_dxgi_format_from_ze_format(texture.format),
+                    ViewDimension: D3D12_UAV_DIMENSION_TEXTURE3D,
+                    Anonymous: D3D12_UNORDERED_ACCESS_VIEW_DESC_0 {
+                        Texture3D: D3D12_TEX3D_UAV {
+                            MipSlice: texture.mip_level,
+                            FirstWSlice: 0,
+                            WSize: u32::MAX,
+                        },
+                    },
+                };
+
+                (
+                    texture
+                        .texture
+                        .backend_data
+                        .downcast_ref::<D3D12Texture>()
+                        .unwrap()
+                        .texture
+                        .deref(),
+                    d3d_desc,
+                )
+            }
+        };
+
+        let handle = self
+            .descriptor_manager
+            .allocate_cbv_srv_uav_descriptor_handle();
+        unsafe {
+            self.device
+                .CreateUnorderedAccessView(resource, None, Some(&d3d_desc), handle.0)
+        }
+
+        Ok(UnorderedAccessView::new(
+            desc.clone(),
+            Box::new(D3D12UnorderedAccessView {
+                descriptor_manager: self.descriptor_manager.clone(),
+                frame_manager: self.frame_manager.clone(),
                 handle,
             }),
         ))
@@ -533,6 +819,12 @@ impl Device for D3D12Device {
             .deref();
 
         match &desc.ty {
+            RenderTargetViewType::Texture2D(_) if desc.resource.desc.sample_desc.count > 1 => {
+                d3d_desc.ViewDimension = D3D12_RTV_DIMENSION_TEXTURE2DMS;
+                d3d_desc.Anonymous = D3D12_RENDER_TARGET_VIEW_DESC_0 {
+                    Texture2DMS: D3D12_TEX2DMS_RTV::default(),
+                };
+            }
             RenderTargetViewType::Texture2D(info) => {
                 d3d_desc.ViewDimension = D3D12_RTV_DIMENSION_TEXTURE2D;
                 d3d_desc.Anonymous = D3D12_RENDER_TARGET_VIEW_DESC_0 {
@@ -542,6 +834,27 @@ impl Device for D3D12Device {
                     },
                 };
             }
+            RenderTargetViewType::Texture2DArray(info) => {
+                d3d_desc.ViewDimension = D3D12_RTV_DIMENSION_TEXTURE2DARRAY;
+                d3d_desc.Anonymous = D3D12_RENDER_TARGET_VIEW_DESC_0 {
+                    Texture2DArray: D3D12_TEX2D_ARRAY_RTV {
+                        MipSlice: info.mip_level,
+                        FirstArraySlice: info.first_array_slice,
+                        ArraySize: info.array_size,
+                        PlaneSlice: 0,
+                    },
+                };
+            }
+            RenderTargetViewType::Texture3D(info) => {
+                d3d_desc.ViewDimension = D3D12_RTV_DIMENSION_TEXTURE3D;
+                d3d_desc.Anonymous = D3D12_RENDER_TARGET_VIEW_DESC_0 {
+                    Texture3D: D3D12_TEX3D_RTV {
+                        MipSlice: info.mip_level,
+                        FirstWSlice: info.first_depth_slice,
+                        WSize: info.depth_size,
+                    },
+                };
+            }
         }
 
         let handle = self.descriptor_manager.allocate_rtv_descriptor_handle();
@@ -579,6 +892,12 @@ impl Device for D3D12Device {
             .deref();
 
         match &desc.ty {
+            DepthStencilViewType::Texture2D(_) if desc.resource.desc.sample_desc.count > 1 => {
+                d3d_desc.ViewDimension = D3D12_DSV_DIMENSION_TEXTURE2DMS;
+                d3d_desc.Anonymous = D3D12_DEPTH_STENCIL_VIEW_DESC_0 {
+                    Texture2DMS: D3D12_TEX2DMS_DSV::default(),
+                };
+            }
             DepthStencilViewType::Texture2D(info) => {
                 d3d_desc.ViewDimension = D3D12_DSV_DIMENSION_TEXTURE2D;
                 d3d_desc.Anonymous = D3D12_DEPTH_STENCIL_VIEW_DESC_0 {
@@ -587,6 +906,16 @@ impl Device for D3D12Device {
                     },
                 };
             }
+            DepthStencilViewType::Texture2DArray(info) => {
+                d3d_desc.ViewDimension = D3D12_DSV_DIMENSION_TEXTURE2DARRAY;
+                d3d_desc.Anonymous = D3D12_DEPTH_STENCIL_VIEW_DESC_0 {
+                    Texture2DArray: D3D12_TEX2D_ARRAY_DSV {
+                        MipSlice: info.mip_level,
+                        FirstArraySlice: info.first_array_slice,
+                        ArraySize: info.array_size,
+                    },
+                };
+            }
         }
 
         let handle = self.descriptor_manager.allocate_dsv_descriptor_handle();
@@ -645,6 +974,7 @@ impl Device for D3D12Device {
                     width: d3d_desc.Width as u32,
                     height: d3d_desc.Height as u32,
                     depth: d3d_desc.DepthOrArraySize as u32,
+                    array_size: 1,
                     mip_levels: d3d_desc.MipLevels as u32,
                     format: get_ze_format_from_dxgi_format(d3d_desc.Format),
                     sample_desc: get_ze_sample_desc_from_dxgi_sample_desc(d3d_desc.SampleDesc),
@@ -712,6 +1042,7 @@ impl Device for D3D12Device {
                             width: d3d_desc.Width as u32,
                             height: d3d_desc.Height as u32,
                             depth: d3d_desc.DepthOrArraySize as u32,
+                            array_size: 1,
                             mip_levels: d3d_desc.MipLevels as u32,
                             format: get_ze_format_from_dxgi_format(d3d_desc.Format),
                             sample_desc: get_ze_sample_desc_from_dxgi_sample_desc(
@@ -823,6 +1154,50 @@ impl Device for D3D12Device {
         ))
     }
 
+    fn create_query_heap(
+        &self,
+        desc: &QueryHeapDesc,
+        name: &str,
+    ) -> Result<QueryHeap, DeviceError> {
+        let mut heap: Option<ID3D12QueryHeap> = None;
+        unsafe {
+            self.device.CreateQueryHeap(
+                &D3D12_QUERY_HEAP_DESC {
+                    Type: match desc.ty {
+                        QueryType::Timestamp => D3D12_QUERY_HEAP_TYPE_TIMESTAMP,
+                    },
+                    Count: desc.count,
+                    NodeMask: 0,
+                },
+                &mut heap,
+            )
+        }
+        .map_err(convert_d3d_error_to_ze_device_error)?;
+
+        let heap = heap.unwrap();
+        set_resource_name(&heap.clone().into(), name);
+
+        Ok(QueryHeap::new(
+            *desc,
+            Box::new(D3D12QueryHeap {
+                frame_manager: self.frame_manager.clone(),
+                heap: SendableIUnknown(heap),
+            }),
+        ))
+    }
+
+    fn create_fence(&self, name: &str) -> Result<Fence, DeviceError> {
+        let fence: ID3D12Fence = unsafe { self.device.CreateFence(0, D3D12_FENCE_FLAG_NONE) }
+            .map_err(convert_d3d_error_to_ze_device_error)?;
+        set_resource_name(&fence.clone().into(), name);
+
+        Ok(Fence::new(Box::new(D3D12Fence {
+            frame_manager: self.frame_manager.clone(),
+            fence: SendableIUnknown(fence),
+            value: AtomicU64::new(0),
+        })))
+    }
+
     fn buffer_mapped_ptr(&self, buffer: &Buffer) -> Option<*mut u8> {
         let buffer = unsafe {
             buffer
@@ -834,6 +1209,40 @@ impl Device for D3D12Device {
         buffer.mapped_ptr
     }
 
+    fn is_fence_signaled(&self, fence: &Fence) -> bool {
+        let fence = unsafe {
+            fence
+                .backend_data
+                .downcast_ref::<D3D12Fence>()
+                .unwrap_unchecked()
+        };
+
+        unsafe { fence.fence.GetCompletedValue() >= fence.value.load(Ordering::SeqCst) }
+    }
+
+    fn wait_for_fence(&self, fence: &Fence) {
+        let fence = unsafe {
+            fence
+                .backend_data
+                .downcast_ref::<D3D12Fence>()
+                .unwrap_unchecked()
+        };
+
+        let value = fence.value.load(Ordering::SeqCst);
+        unsafe {
+            if fence.fence.GetCompletedValue() >= value {
+                return;
+            }
+
+            // Sleep until the fence sets the event, each wait has its own event so several
+            // threads can wait for the same fence
+            let event = CreateEventW(None, false, false, PCWSTR::null()).unwrap();
+            fence.fence.SetEventOnCompletion(value, event).unwrap();
+            WaitForSingleObject(event, INFINITE);
+            CloseHandle(event);
+        }
+    }
+
     fn texture_subresource_layout(
         &self,
         texture: &Texture,
@@ -849,8 +1258,8 @@ impl Device for D3D12Device {
         let mut footprint = D3D12_PLACED_SUBRESOURCE_FOOTPRINT::default();
 
         let mut total_bytes = 0;
+        let mut num_rows = 0;
         unsafe {
-            let mut num_rows = 0;
             let mut row_size_in_bytes = 0;
 
             self.device.GetCopyableFootprints(
@@ -868,6 +1277,7 @@ impl Device for D3D12Device {
         TextureSubresourceLayout {
             offset_in_bytes: footprint.Offset,
             row_pitch_in_bytes: footprint.Footprint.RowPitch as u64,
+            depth_pitch_in_bytes: footprint.Footprint.RowPitch as u64 * num_rows as u64,
             size_in_bytes: total_bytes,
         }
     }
@@ -933,6 +1343,25 @@ impl Device for D3D12Device {
         &self.transient_memory_pool
     }
 
+    fn memory_stats(&self) -> MemoryStats {
+        let (local, non_local) = self.allocator.budget();
+        let convert = |budget: Budget| MemorySegmentStats {
+            budget_bytes: budget.budget_bytes,
+            usage_bytes: budget.usage_bytes,
+            allocation_count: budget.stats.allocation_count,
+            allocation_bytes: budget.stats.allocation_bytes,
+        };
+
+        MemoryStats {
+            local: convert(local),
+            non_local: convert(non_local),
+        }
+    }
+
+    fn timestamp_frequency(&self) -> u64 {
+        unsafe { self.graphics_queue.GetTimestampFrequency() }.unwrap_or(1)
+    }
+
     fn cmd_copy_buffer_regions(
         &self,
         cmd_list: &mut CommandList,
@@ -1041,6 +1470,124 @@ impl Device for D3D12Device {
         }
     }
 
+    fn cmd_copy_texture_to_buffer_regions(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_buffer: &Buffer,
+        regions: &[BufferToTextureCopyRegion],
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        let d3d_src_texture = unsafe {
+            src_texture
+                .backend_data
+                .downcast_ref::<D3D12Texture>()
+                .unwrap_unchecked()
+        };
+
+        let d3d_dst_buffer = unsafe {
+            dst_buffer
+                .backend_data
+                .downcast_ref::<D3D12Buffer>()
+                .unwrap_unchecked()
+        };
+
+        for region in regions {
+            let src_location = D3D12_TEXTURE_COPY_LOCATION {
+                pResource: Some(d3d_src_texture.texture.deref().clone()),
+                Type: D3D12_TEXTURE_COPY_TYPE_SUBRESOURCE_INDEX,
+                Anonymous: D3D12_TEXTURE_COPY_LOCATION_0 {
+                    SubresourceIndex: region.texture_subresource_index,
+                },
+            };
+
+            let dst_location = D3D12_TEXTURE_COPY_LOCATION {
+                pResource: Some(d3d_dst_buffer.resource.deref().clone()),
+                Type: D3D12_TEXTURE_COPY_TYPE_PLACED_FOOTPRINT,
+                Anonymous: D3D12_TEXTURE_COPY_LOCATION_0 {
+                    PlacedFootprint: D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
+                        Offset: region.buffer_offset_in_bytes,
+                        Footprint: D3D12_SUBRESOURCE_FOOTPRINT {
+                            Format: get_dxgi_format_from_ze_format(src_texture.desc.format),
+                            Width: region.buffer_texture_width,
+                            Height: region.buffer_texture_height,
+                            Depth: region.buffer_texture_depth,
+                            RowPitch: region.buffer_texture_row_pitch_in_bytes,
+                        },
+                    },
+                },
+            };
+
+            let offset = region.texture_subresource_offset;
+            let src_box = D3D12_BOX {
+                left: offset.x as u32,
+                top: offset.y as u32,
+                front: offset.z as u32,
+                right: offset.x as u32 + region.texture_subresource_width,
+                bottom: offset.y as u32 + region.texture_subresource_height,
+                back: offset.z as u32 + region.texture_subresource_depth,
+            };
+
+            unsafe {
+                cmd_list.cmd_list.CopyTextureRegion(
+                    &dst_location,
+                    0,
+                    0,
+                    0,
+                    &src_location,
+                    Some(&src_box),
+                )
+            };
+        }
+    }
+
+    fn cmd_resolve_texture(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_texture: &Texture,
+    ) {
+        debug_assert!(src_texture.desc.sample_desc.count > 1);
+        debug_assert_eq!(dst_texture.desc.sample_desc.count, 1);
+
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        let src_texture_resource = unsafe {
+            src_texture
+                .backend_data
+                .downcast_ref::<D3D12Texture>()
+                .unwrap_unchecked()
+        };
+
+        let dst_texture_resource = unsafe {
+            dst_texture
+                .backend_data
+                .downcast_ref::<D3D12Texture>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            cmd_list.cmd_list.ResolveSubresource(
+                dst_texture_resource.texture.deref(),
+                0,
+                src_texture_resource.texture.deref(),
+                0,
+                get_dxgi_format_from_ze_format(dst_texture.desc.format),
+            );
+        }
+    }
+
     #[cfg(feature = "pix")]
     fn cmd_debug_begin_event(&self, cmd_list: &mut CommandList, name: &str, color: Color4f32) {
         use ze_core::color::Color4u8;
@@ -1090,6 +1637,74 @@ impl Device for D3D12Device {
     #[cfg(not(feature = "pix"))]
     fn cmd_debug_end_event(&self, _: &mut CommandList) {}
 
+    fn cmd_write_timestamp(&self, cmd_list: &mut CommandList, query_heap: &QueryHeap, index: u32) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        let query_heap = unsafe {
+            query_heap
+                .backend_data
+                .downcast_ref::<D3D12QueryHeap>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            cmd_list
+                .cmd_list
+                .EndQuery(query_heap.heap.deref(), D3D12_QUERY_TYPE_TIMESTAMP, index);
+        }
+    }
+
+    fn cmd_resolve_query_data(
+        &self,
+        cmd_list: &mut CommandList,
+        query_heap: &QueryHeap,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &Buffer,
+        dst_offset_in_bytes: u64,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        let query_type = match query_heap.desc.ty {
+            QueryType::Timestamp => D3D12_QUERY_TYPE_TIMESTAMP,
+        };
+
+        let query_heap = unsafe {
+            query_heap
+                .backend_data
+                .downcast_ref::<D3D12QueryHeap>()
+                .unwrap_unchecked()
+        };
+
+        let dst_buffer = unsafe {
+            dst_buffer
+                .backend_data
+                .downcast_ref::<D3D12Buffer>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            cmd_list.cmd_list.ResolveQueryData(
+                query_heap.heap.deref(),
+                query_type,
+                first_query,
+                query_count,
+                dst_buffer.resource.deref(),
+                dst_offset_in_bytes,
+            );
+        }
+    }
+
     fn cmd_begin_render_pass(&self, cmd_list: &mut CommandList, desc: &RenderPassDesc) {
         let mut cmd_list = unsafe {
             cmd_list
@@ -1100,6 +1715,8 @@ impl Device for D3D12Device {
 
         let mut render_target_descs = vec![];
 
+        cmd_list.render_pass_sample_desc =
+            get_dxgi_sample_desc_from_ze_sample_desc(desc.sample_desc());
         cmd_list.render_pass_rt_count = desc.render_targets.len() as u32;
         for (i, desc) in desc.render_targets.iter().enumerate() {
             let rtv = unsafe {
@@ -1241,19 +1858,37 @@ impl Device for D3D12Device {
         for barrier in barriers {
             match barrier {
                 ResourceBarrier::Transition(transition) => {
-                    let resource = match transition.resource {
-                        ResourceTransitionBarrierResource::Buffer(buffer) => buffer
-                            .backend_data
-                            .downcast_ref::<D3D12Buffer>()
-                            .unwrap()
-                            .resource
-                            .deref(),
-                        ResourceTransitionBarrierResource::Texture(texture) => texture
-                            .backend_data
-                            .downcast_ref::<D3D12Texture>()
-                            .unwrap()
-                            .texture
-                            .deref(),
+                    let (resource, subresource) = match transition.resource {
+                        ResourceTransitionBarrierResource::Buffer(buffer) => (
+                            buffer
+                                .backend_data
+                                .downcast_ref::<D3D12Buffer>()
+                                .unwrap()
+                                .resource
+                                .deref(),
+                            D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
+                        ),
+                        ResourceTransitionBarrierResource::Texture(texture) => (
+                            texture
+                                .backend_data
+                                .downcast_ref::<D3D12Texture>()
+                                .unwrap()
+                                .texture
+                                .deref(),
+                            D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
+                        ),
+                        ResourceTransitionBarrierResource::TextureSubresource(
+                            texture,
+                            subresource,
+                        ) => (
+                            texture
+                                .backend_data
+                                .downcast_ref::<D3D12Texture>()
+                                .unwrap()
+                                .texture
+                                .deref(),
+                            subresource,
+                        ),
                     };
 
                     resource_barriers.push(D3D12_RESOURCE_BARRIER {
@@ -1262,7 +1897,7 @@ impl Device for D3D12Device {
                         Anonymous: D3D12_RESOURCE_BARRIER_0 {
                             Transition: ManuallyDrop::new(D3D12_RESOURCE_TRANSITION_BARRIER {
                                 pResource: Some(resource.clone()),
-                                Subresource: D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
+                                Subresource: subresource,
                                 StateBefore: get_d3d_resource_stats_from_ze_resource_state(
                                     transition.source_state,
                                 ),
@@ -1273,6 +1908,35 @@ impl Device for D3D12Device {
                         },
                     });
                 }
+                ResourceBarrier::UnorderedAccess(resource) => {
+                    let resource = match resource {
+                        ResourceTransitionBarrierResource::Buffer(buffer) => buffer
+                            .backend_data
+                            .downcast_ref::<D3D12Buffer>()
+                            .unwrap()
+                            .resource
+                            .deref(),
+                        ResourceTransitionBarrierResource::Texture(texture)
+                        | ResourceTransitionBarrierResource::TextureSubresource(texture, _) => {
+                            texture
+                                .backend_data
+                                .downcast_ref::<D3D12Texture>()
+                                .unwrap()
+                                .texture
+                                .deref()
+                        }
+                    };
+
+                    resource_barriers.push(D3D12_RESOURCE_BARRIER {
+                        Type: D3D12_RESOURCE_BARRIER_TYPE_UAV,
+                        Flags: D3D12_RESOURCE_BARRIER_FLAG_NONE,
+                        Anonymous: D3D12_RESOURCE_BARRIER_0 {
+                            UAV: ManuallyDrop::new(D3D12_RESOURCE_UAV_BARRIER {
+                                pResource: Some(resource.clone()),
+                            }),
+                        },
+                    });
+                }
             }
         }
 
@@ -1287,6 +1951,10 @@ impl Device for D3D12Device {
                     let transition_barrier = unsafe { barrier.Anonymous.Transition };
                     drop(ManuallyDrop::into_inner(transition_barrier));
                 }
+                D3D12_RESOURCE_BARRIER_TYPE_UAV => {
+                    let uav_barrier = unsafe { barrier.Anonymous.UAV };
+                    drop(ManuallyDrop::into_inner(uav_barrier));
+                }
                 _ => todo!(),
             }
         }
@@ -1351,10 +2019,7 @@ impl Device for D3D12Device {
         cmd_list.pipeline_state_dirty = true;
 
         if stages.len() == 1 && stages[0].stage == ShaderStageFlagBits::Compute {
-            let desc = D3D12_COMPUTE_PIPELINE_STATE_DESC::default();
-            //desc.CS = D3D12_SHADER_BYTECODE {};
-            cmd_list.pipeline = D3D12CommandListPipelineType::Compute(desc);
-            todo!();
+            self.set_compute_shader(cmd_list, stages[0].module);
         } else {
             let desc = match &mut cmd_list.pipeline {
                 D3D12CommandListPipelineType::Graphics(graphics) => graphics,
@@ -1398,6 +2063,17 @@ impl Device for D3D12Device {
         }
     }
 
+    fn cmd_set_compute_shader(&self, cmd_list: &mut CommandList, module: &ShaderModule) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.set_compute_shader(cmd_list, module);
+    }
+
     fn cmd_set_input_assembly_state(
         &self,
         cmd_list: &mut CommandList,
@@ -1549,13 +2225,24 @@ impl Device for D3D12Device {
 
         debug_assert!(data.len() <= 32 * 4);
 
+        let count = (data.len() / size_of::<u32>()) as u32;
+        let offset = offset_in_bytes / size_of::<u32>() as u32;
         unsafe {
-            cmd_list.cmd_list.SetGraphicsRoot32BitConstants(
-                0,
-                (data.len() / size_of::<u32>()) as u32,
-                data.as_ptr() as *const c_void,
-                offset_in_bytes / size_of::<u32>() as u32,
-            );
+            if let D3D12CommandListPipelineType::Compute(_) = cmd_list.pipeline {
+                cmd_list.cmd_list.SetComputeRoot32BitConstants(
+                    0,
+                    count,
+                    data.as_ptr() as *const c_void,
+                    offset,
+                );
+            } else {
+                cmd_list.cmd_list.SetGraphicsRoot32BitConstants(
+                    0,
+                    count,
+                    data.as_ptr() as *const c_void,
+                    offset,
+                );
+            }
         }
     }
 
@@ -1612,6 +2299,28 @@ impl Device for D3D12Device {
         };
     }
 
+    fn cmd_dispatch(
+        &self,
+        cmd_list: &mut CommandList,
+        thread_group_x: u32,
+        thread_group_y: u32,
+        thread_group_z: u32,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<D3D12CommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.flush_pipeline_state(cmd_list);
+        unsafe {
+            cmd_list
+                .cmd_list
+                .Dispatch(thread_group_x, thread_group_y, thread_group_z);
+        }
+    }
+
     fn cmd_dispatch_mesh(
         &self,
         cmd_list: &mut CommandList,
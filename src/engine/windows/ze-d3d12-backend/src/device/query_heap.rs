@@ -0,0 +1,19 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use crate::utils::SendableIUnknown;
+use std::sync::Arc;
+use windows::Win32::Graphics::Direct3D12::*;
+
+pub(crate) struct D3D12QueryHeap {
+    pub frame_manager: Arc<FrameManager>,
+    pub heap: SendableIUnknown<ID3D12QueryHeap>,
+}
+
+impl Drop for D3D12QueryHeap {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::QueryHeap(self.heap.clone()));
+    }
+}
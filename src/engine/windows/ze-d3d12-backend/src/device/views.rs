@@ -1,4 +1,6 @@
 use crate::descriptor_manager::DescriptorManager;
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
 use std::sync::Arc;
 use windows::Win32::Graphics::Direct3D12::D3D12_CPU_DESCRIPTOR_HANDLE;
 use ze_gfx::backend::ShaderVisibleResource;
@@ -29,13 +31,19 @@ impl Drop for D3D12DepthStencilView {
 
 pub struct D3D12ShaderResourceView {
     pub descriptor_manager: Arc<DescriptorManager>,
+    pub frame_manager: Arc<FrameManager>,
     pub handle: (D3D12_CPU_DESCRIPTOR_HANDLE, u32),
 }
 
 impl Drop for D3D12ShaderResourceView {
     fn drop(&mut self) {
-        self.descriptor_manager
-            .free_cbv_srv_uav_descriptor_handle(self.handle);
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::CbvSrvUavDescriptor(
+                self.descriptor_manager.clone(),
+                self.handle,
+            ));
     }
 }
 
@@ -44,3 +52,27 @@ impl ShaderVisibleResource for D3D12ShaderResourceView {
         self.handle.1
     }
 }
+
+pub struct D3D12UnorderedAccessView {
+    pub descriptor_manager: Arc<DescriptorManager>,
+    pub frame_manager: Arc<FrameManager>,
+    pub handle: (D3D12_CPU_DESCRIPTOR_HANDLE, u32),
+}
+
+impl Drop for D3D12UnorderedAccessView {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::CbvSrvUavDescriptor(
+                self.descriptor_manager.clone(),
+                self.handle,
+            ));
+    }
+}
+
+impl ShaderVisibleResource for D3D12UnorderedAccessView {
+    fn descriptor_index(&self) -> u32 {
+        self.handle.1
+    }
+}
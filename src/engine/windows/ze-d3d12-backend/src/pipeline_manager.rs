@@ -94,6 +94,13 @@ struct MeshGraphicsPipelineStateDescStream {
     pub flags: PipelineTypedField<D3D12_PIPELINE_STATE_FLAGS>,
 }
 
+#[repr(C)]
+#[derive(Clone, PartialEq, Eq)]
+struct ComputePipelineStateDescStream {
+    pub root_signature: PipelineTypedField<ID3D12RootSignature>,
+    pub compute_shader: PipelineTypedField<D3D12_SHADER_BYTECODE>,
+}
+
 // TODO: rework
 #[derive(Clone, PartialEq, Eq)]
 pub struct GraphicsPipelineStateDesc {
@@ -315,6 +322,9 @@ impl From<&GraphicsPipelineStateDesc> for GraphicsPipelineEntry {
 pub struct PipelineManager {
     graphics_pipelines:
         RwLock<HashMap<GraphicsPipelineEntry, SendableIUnknown<ID3D12PipelineState>>>,
+
+    /// Compute pipelines, keyed by the address and length of their shader bytecode
+    compute_pipelines: RwLock<HashMap<(usize, usize), SendableIUnknown<ID3D12PipelineState>>>,
 }
 
 impl PipelineManager {
@@ -395,4 +405,43 @@ impl PipelineManager {
             pipeline
         }
     }
+
+    pub fn get_or_create_compute_pipeline(
+        &self,
+        device: &ID3D12Device2,
+        root_signature: &ID3D12RootSignature,
+        compute_shader: &D3D12_SHADER_BYTECODE,
+    ) -> ID3D12PipelineState {
+        let key = (
+            compute_shader.pShaderBytecode as usize,
+            compute_shader.BytecodeLength,
+        );
+
+        if let Some(pipeline) = self.compute_pipelines.read().get(&key) {
+            return pipeline.deref().clone();
+        }
+
+        let stream = ComputePipelineStateDescStream {
+            root_signature: PipelineTypedField::new(
+                D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_ROOT_SIGNATURE,
+                root_signature.clone(),
+            ),
+            compute_shader: PipelineTypedField::new(
+                D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_CS,
+                *compute_shader,
+            ),
+        };
+
+        let stream_desc = D3D12_PIPELINE_STATE_STREAM_DESC {
+            pPipelineStateSubobjectStream: &stream as *const _ as *mut _,
+            SizeInBytes: size_of_val(&stream),
+        };
+
+        let pipeline: ID3D12PipelineState =
+            unsafe { device.CreatePipelineState(&stream_desc) }.unwrap();
+        self.compute_pipelines
+            .write()
+            .insert(key, pipeline.clone().into());
+        pipeline
+    }
 }
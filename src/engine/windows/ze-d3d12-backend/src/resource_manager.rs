@@ -1,11 +1,20 @@
-﻿use crate::utils::SendableIUnknown;
+﻿use crate::descriptor_manager::DescriptorManager;
+use crate::utils::SendableIUnknown;
 use parking_lot::Mutex;
-use windows::Win32::Graphics::Direct3D12::ID3D12Resource;
+use std::sync::Arc;
+use windows::Win32::Graphics::Direct3D12::{
+    ID3D12Fence, ID3D12QueryHeap, ID3D12Resource, D3D12_CPU_DESCRIPTOR_HANDLE,
+};
 use ze_d3dmemoryallocator::Allocation;
 
 pub enum Entry {
     Resource(SendableIUnknown<ID3D12Resource>),
     Allocation(Allocation),
+    QueryHeap(SendableIUnknown<ID3D12QueryHeap>),
+    Fence(SendableIUnknown<ID3D12Fence>),
+
+    /// Shader visible descriptors may still be read by in-flight frames
+    CbvSrvUavDescriptor(Arc<DescriptorManager>, (D3D12_CPU_DESCRIPTOR_HANDLE, u32)),
 }
 
 /// This object manage resource lifetimes in a elegant way
@@ -21,6 +30,12 @@ impl DeferredResourceQueue {
     }
 
     pub fn flush(&self) {
-        self.queue.lock().clear();
+        let entries: Vec<Entry> = self.queue.lock().drain(..).collect();
+        for entry in entries {
+            // Other entries are reference counted and released when dropped
+            if let Entry::CbvSrvUavDescriptor(descriptor_manager, handle) = entry {
+                descriptor_manager.free_cbv_srv_uav_descriptor_handle(handle);
+            }
+        }
     }
 }
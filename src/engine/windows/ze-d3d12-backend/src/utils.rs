@@ -42,6 +42,7 @@ pub fn get_heap_type_from_memory_location(memory_location: MemoryLocation) -> D3
     match memory_location {
         MemoryLocation::CpuToGpu => D3D12_HEAP_TYPE_UPLOAD,
         MemoryLocation::GpuOnly => D3D12_HEAP_TYPE_DEFAULT,
+        MemoryLocation::GpuToCpu => D3D12_HEAP_TYPE_READBACK,
     }
 }
 
@@ -51,22 +52,68 @@ pub fn get_dxgi_format_from_ze_format(format: PixelFormat) -> DXGI_FORMAT {
         PixelFormat::B8G8R8A8UnormSrgb => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
         PixelFormat::B8G8R8A8Unorm => DXGI_FORMAT_B8G8R8A8_UNORM,
         PixelFormat::R8Unorm => DXGI_FORMAT_R8_UNORM,
+        PixelFormat::R32Uint => DXGI_FORMAT_R32_UINT,
         PixelFormat::R8G8B8A8Unorm => DXGI_FORMAT_R8G8B8A8_UNORM,
         PixelFormat::D24UnormS8Uint => DXGI_FORMAT_D24_UNORM_S8_UINT,
         PixelFormat::R16G16B16A16Sfloat => DXGI_FORMAT_R16G16B16A16_FLOAT,
+        PixelFormat::Bc1Unorm => DXGI_FORMAT_BC1_UNORM,
+        PixelFormat::Bc1UnormSrgb => DXGI_FORMAT_BC1_UNORM_SRGB,
+        PixelFormat::Bc2Unorm => DXGI_FORMAT_BC2_UNORM,
+        PixelFormat::Bc2UnormSrgb => DXGI_FORMAT_BC2_UNORM_SRGB,
+        PixelFormat::Bc3Unorm => DXGI_FORMAT_BC3_UNORM,
+        PixelFormat::Bc3UnormSrgb => DXGI_FORMAT_BC3_UNORM_SRGB,
+        PixelFormat::Bc4Unorm => DXGI_FORMAT_BC4_UNORM,
+        PixelFormat::Bc4Snorm => DXGI_FORMAT_BC4_SNORM,
+        PixelFormat::Bc5Unorm => DXGI_FORMAT_BC5_UNORM,
+        PixelFormat::Bc5Snorm => DXGI_FORMAT_BC5_SNORM,
+        PixelFormat::Bc6hUfloat => DXGI_FORMAT_BC6H_UF16,
+        PixelFormat::Bc6hSfloat => DXGI_FORMAT_BC6H_SF16,
+        PixelFormat::Bc7Unorm => DXGI_FORMAT_BC7_UNORM,
+        PixelFormat::Bc7UnormSrgb => DXGI_FORMAT_BC7_UNORM_SRGB,
         _ => todo!(),
     }
 }
 
+/// Format of a texture resource, depth formats are typeless so they can also be sampled
+pub fn get_dxgi_resource_format_from_ze_format(format: PixelFormat) -> DXGI_FORMAT {
+    match format {
+        PixelFormat::D24UnormS8Uint => DXGI_FORMAT_R24G8_TYPELESS,
+        _ => get_dxgi_format_from_ze_format(format),
+    }
+}
+
+/// Format of a texture SRV, depth formats are read through their depth component
+pub fn get_dxgi_srv_format_from_ze_format(format: PixelFormat) -> DXGI_FORMAT {
+    match format {
+        PixelFormat::D24UnormS8Uint => DXGI_FORMAT_R24_UNORM_X8_TYPELESS,
+        _ => get_dxgi_format_from_ze_format(format),
+    }
+}
+
 pub fn get_ze_format_from_dxgi_format(format: DXGI_FORMAT) -> PixelFormat {
     match format {
         DXGI_FORMAT_UNKNOWN => PixelFormat::Unknown,
         DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => PixelFormat::B8G8R8A8UnormSrgb,
         DXGI_FORMAT_B8G8R8A8_UNORM => PixelFormat::B8G8R8A8Unorm,
         DXGI_FORMAT_R8_UNORM => PixelFormat::R8Unorm,
+        DXGI_FORMAT_R32_UINT => PixelFormat::R32Uint,
         DXGI_FORMAT_R8G8B8A8_UNORM => PixelFormat::R8G8B8A8Unorm,
         DXGI_FORMAT_D24_UNORM_S8_UINT => PixelFormat::D24UnormS8Uint,
         DXGI_FORMAT_R16G16B16A16_FLOAT => PixelFormat::R16G16B16A16Sfloat,
+        DXGI_FORMAT_BC1_UNORM => PixelFormat::Bc1Unorm,
+        DXGI_FORMAT_BC1_UNORM_SRGB => PixelFormat::Bc1UnormSrgb,
+        DXGI_FORMAT_BC2_UNORM => PixelFormat::Bc2Unorm,
+        DXGI_FORMAT_BC2_UNORM_SRGB => PixelFormat::Bc2UnormSrgb,
+        DXGI_FORMAT_BC3_UNORM => PixelFormat::Bc3Unorm,
+        DXGI_FORMAT_BC3_UNORM_SRGB => PixelFormat::Bc3UnormSrgb,
+        DXGI_FORMAT_BC4_UNORM => PixelFormat::Bc4Unorm,
+        DXGI_FORMAT_BC4_SNORM => PixelFormat::Bc4Snorm,
+        DXGI_FORMAT_BC5_UNORM => PixelFormat::Bc5Unorm,
+        DXGI_FORMAT_BC5_SNORM => PixelFormat::Bc5Snorm,
+        DXGI_FORMAT_BC6H_UF16 => PixelFormat::Bc6hUfloat,
+        DXGI_FORMAT_BC6H_SF16 => PixelFormat::Bc6hSfloat,
+        DXGI_FORMAT_BC7_UNORM => PixelFormat::Bc7Unorm,
+        DXGI_FORMAT_BC7_UNORM_SRGB => PixelFormat::Bc7UnormSrgb,
         _ => todo!(),
     }
 }
@@ -123,9 +170,14 @@ pub fn get_d3d_resource_stats_from_ze_resource_state(
         ResourceState::RenderTargetWrite => D3D12_RESOURCE_STATE_RENDER_TARGET,
         ResourceState::DepthRead => D3D12_RESOURCE_STATE_DEPTH_READ,
         ResourceState::DepthWrite => D3D12_RESOURCE_STATE_DEPTH_WRITE,
-        ResourceState::ShaderRead => D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE,
+        ResourceState::ShaderRead => {
+            D3D12_RESOURCE_STATE_PIXEL_SHADER_RESOURCE
+                | D3D12_RESOURCE_STATE_NON_PIXEL_SHADER_RESOURCE
+        }
         ResourceState::CopyRead => D3D12_RESOURCE_STATE_COPY_SOURCE,
         ResourceState::CopyWrite => D3D12_RESOURCE_STATE_COPY_DEST,
+        ResourceState::ResolveRead => D3D12_RESOURCE_STATE_RESOLVE_SOURCE,
+        ResourceState::ResolveWrite => D3D12_RESOURCE_STATE_RESOLVE_DEST,
         ResourceState::Present => D3D12_RESOURCE_STATE_PRESENT,
     }
 }
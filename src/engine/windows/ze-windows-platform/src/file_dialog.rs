@@ -0,0 +1,207 @@
+//! Open and save dialogs, shown from a thread each as they run a modal loop until closed
+
+use crate::utils::utf8_to_utf16;
+use parking_lot::Mutex;
+use std::ffi::{c_void, OsString};
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use windows::core::{Result, PCWSTR};
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_INPROC_SERVER,
+    COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE,
+};
+use windows::Win32::UI::Shell::Common::COMDLG_FILTERSPEC;
+use windows::Win32::UI::Shell::{
+    FileOpenDialog, FileSaveDialog, IFileOpenDialog, IFileSaveDialog, IShellItem,
+    FOS_ALLOWMULTISELECT, FOS_FORCEFILESYSTEM, FOS_OVERWRITEPROMPT, SIGDN_FILESYSPATH,
+};
+use windows::Win32::UI::WindowsAndMessaging::{PostThreadMessageW, WM_NULL};
+use ze_core::ze_error;
+use ze_platform::{FileDialogId, FileFilter, Message};
+
+type ClosedDialogs = Arc<Mutex<Vec<(FileDialogId, Vec<PathBuf>)>>>;
+
+pub struct FileDialogs {
+    /// Thread polling the messages, woken up when a dialog is closed
+    thread_id: u32,
+    closed: ClosedDialogs,
+}
+
+/// UTF-16 strings of a request, moved to the dialog thread
+struct Request {
+    /// Name and `*.ext;*.ext` patterns of each filter
+    filters: Vec<(Vec<u16>, Vec<u16>)>,
+    kind: RequestKind,
+}
+
+enum RequestKind {
+    Open {
+        multi_select: bool,
+    },
+    Save {
+        default_name: Vec<u16>,
+
+        /// Appended when the user types a name without extension
+        default_extension: Option<Vec<u16>>,
+    },
+}
+
+impl FileDialogs {
+    pub fn new(thread_id: u32) -> Self {
+        Self {
+            thread_id,
+            closed: Default::default(),
+        }
+    }
+
+    pub fn open(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        self.request(Request {
+            filters: convert_filters(filters),
+            kind: RequestKind::Open { multi_select },
+        })
+    }
+
+    pub fn save(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        let default_extension = filters
+            .first()
+            .and_then(|filter| filter.extensions.first())
+            .map(|extension| utf8_to_utf16(extension));
+
+        self.request(Request {
+            filters: convert_filters(filters),
+            kind: RequestKind::Save {
+                default_name: utf8_to_utf16(default_name),
+                default_extension,
+            },
+        })
+    }
+
+    /// Push the messages of the dialogs closed since the last call
+    pub fn send_closed(&self, message_queue: &mut impl Extend<Message>) {
+        message_queue.extend(
+            self.closed
+                .lock()
+                .drain(..)
+                .map(|(id, paths)| Message::FileDialogClosed(id, paths)),
+        );
+    }
+
+    fn request(&self, request: Request) -> FileDialogId {
+        let id = FileDialogId::new();
+        let closed = self.closed.clone();
+        let thread_id = self.thread_id;
+        let result = thread::Builder::new()
+            .name("File Dialog".to_string())
+            .spawn(move || {
+                let paths = request.run();
+                closed.lock().push((id, paths));
+                unsafe {
+                    PostThreadMessageW(thread_id, WM_NULL, WPARAM(0), LPARAM(0));
+                }
+            });
+
+        if let Err(error) = result {
+            ze_error!("Failed to start the file dialog thread: {}", error);
+            self.closed.lock().push((id, vec![]));
+        }
+
+        id
+    }
+}
+
+impl Request {
+    /// Show the dialog from the current thread, no path if it was cancelled or couldn't be shown
+    fn run(&self) -> Vec<PathBuf> {
+        unsafe {
+            // The dialogs need a single-threaded apartment
+            if let Err(error) =
+                CoInitializeEx(None, COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE)
+            {
+                ze_error!("Failed to initialize COM for the file dialog: {}", error);
+                return vec![];
+            }
+
+            // Fails with ERROR_CANCELLED when the user closes the dialog
+            let paths = self.show().unwrap_or_default();
+            CoUninitialize();
+            paths
+        }
+    }
+
+    /// Show the dialog and wait until it is closed
+    unsafe fn show(&self) -> Result<Vec<PathBuf>> {
+        let filter_specs: Vec<COMDLG_FILTERSPEC> = self
+            .filters
+            .iter()
+            .map(|(name, spec)| COMDLG_FILTERSPEC {
+                pszName: PCWSTR(name.as_ptr()),
+                pszSpec: PCWSTR(spec.as_ptr()),
+            })
+            .collect();
+
+        match &self.kind {
+            RequestKind::Open { multi_select } => {
+                let dialog: IFileOpenDialog =
+                    CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER)?;
+                let mut options = dialog.GetOptions()? | FOS_FORCEFILESYSTEM;
+                if *multi_select {
+                    options |= FOS_ALLOWMULTISELECT;
+                }
+                dialog.SetOptions(options)?;
+                if !filter_specs.is_empty() {
+                    dialog.SetFileTypes(&filter_specs)?;
+                }
+
+                dialog.Show(HWND::default())?;
+                let items = dialog.GetResults()?;
+                (0..items.GetCount()?)
+                    .map(|index| items.GetItemAt(index).and_then(|item| item_path(&item)))
+                    .collect()
+            }
+            RequestKind::Save {
+                default_name,
+                default_extension,
+            } => {
+                let dialog: IFileSaveDialog =
+                    CoCreateInstance(&FileSaveDialog, None, CLSCTX_INPROC_SERVER)?;
+                dialog
+                    .SetOptions(dialog.GetOptions()? | FOS_FORCEFILESYSTEM | FOS_OVERWRITEPROMPT)?;
+                if !filter_specs.is_empty() {
+                    dialog.SetFileTypes(&filter_specs)?;
+                }
+                if let Some(default_extension) = default_extension {
+                    dialog.SetDefaultExtension(PCWSTR(default_extension.as_ptr()))?;
+                }
+                dialog.SetFileName(PCWSTR(default_name.as_ptr()))?;
+
+                dialog.Show(HWND::default())?;
+                Ok(vec![item_path(&dialog.GetResult()?)?])
+            }
+        }
+    }
+}
+
+unsafe fn item_path(item: &IShellItem) -> Result<PathBuf> {
+    let name = item.GetDisplayName(SIGDN_FILESYSPATH)?;
+    let path = PathBuf::from(OsString::from_wide(name.as_wide()));
+    CoTaskMemFree(Some(name.0 as *const c_void));
+    Ok(path)
+}
+
+fn convert_filters(filters: &[FileFilter]) -> Vec<(Vec<u16>, Vec<u16>)> {
+    filters
+        .iter()
+        .map(|filter| {
+            let patterns = filter
+                .extensions
+                .iter()
+                .map(|extension| format!("*.{}", extension))
+                .collect::<Vec<_>>()
+                .join(";");
+            (utf8_to_utf16(filter.name), utf8_to_utf16(&patterns))
+        })
+        .collect()
+}
@@ -0,0 +1,53 @@
+use std::ffi::c_void;
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::Graphics::Gdi::{CreateBitmap, DeleteObject};
+use windows::Win32::UI::WindowsAndMessaging::{CreateIconIndirect, HICON, ICONINFO};
+use ze_core::maths::Point2;
+
+/// Create an icon from `width * height` 8-bit RGBA pixels, or a cursor if a hotspot is given
+pub fn create_icon(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    hotspot: Option<Point2<u32>>,
+) -> Option<HICON> {
+    // GDI bitmaps are BGRA
+    let bgra: Vec<u8> = rgba[..(width * height * 4) as usize]
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+        .collect();
+
+    // Transparency comes from the alpha channel, the mask is only required by the API.
+    // Its rows are aligned to 16 bits
+    let mask = vec![0u8; ((width as usize + 15) / 16) * 2 * height as usize];
+
+    unsafe {
+        let color_bitmap = CreateBitmap(
+            width as i32,
+            height as i32,
+            1,
+            32,
+            Some(bgra.as_ptr() as *const c_void),
+        );
+        let mask_bitmap = CreateBitmap(
+            width as i32,
+            height as i32,
+            1,
+            1,
+            Some(mask.as_ptr() as *const c_void),
+        );
+
+        let icon = CreateIconIndirect(&ICONINFO {
+            fIcon: BOOL::from(hotspot.is_none()),
+            xHotspot: hotspot.map_or(0, |hotspot| hotspot.x),
+            yHotspot: hotspot.map_or(0, |hotspot| hotspot.y),
+            hbmMask: mask_bitmap,
+            hbmColor: color_bitmap,
+        });
+
+        // The icon has its own copy of the bitmaps
+        DeleteObject(color_bitmap);
+        DeleteObject(mask_bitmap);
+        icon.ok()
+    }
+}
@@ -1,28 +1,62 @@
 use crate::cursor::WindowsCursor;
-use crate::utils::utf8_to_utf16;
-use crate::window::WindowsWindow;
+use crate::file_dialog::FileDialogs;
+use crate::icon::create_icon;
+use crate::utils::{utf16_to_utf8, utf8_to_utf16};
+use crate::window::{adjust_window_rect, is_borderless_resizable, WindowsWindow};
 use parking_lot::Mutex;
 use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_void, OsString};
 use std::hash::{Hash, Hasher};
 use std::mem::size_of;
 use std::os::raw::c_short;
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use windows::core::PCWSTR;
 use windows::Win32::Foundation::{
-    GetLastError, BOOL, COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, NO_ERROR, POINT, RECT, WPARAM,
+    GetLastError, BOOL, COLORREF, HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, NO_ERROR, POINT, RECT,
+    WPARAM,
 };
 use windows::Win32::Graphics::Gdi::{
     ClientToScreen, EnumDisplayMonitors, GetMonitorInfoW, GetStockObject, BLACK_BRUSH, HBRUSH, HDC,
     HMONITOR, MONITORINFO,
 };
 use windows::Win32::Media::{timeBeginPeriod, timeEndPeriod};
-use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, SetClipboardData,
+};
+use windows::Win32::System::Memory::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+};
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+use windows::Win32::System::SystemServices::CF_UNICODETEXT;
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::HiDpi::{
+    GetDpiForMonitor, SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    MDT_EFFECTIVE_DPI,
+};
+use windows::Win32::UI::Input::Ime::{
+    ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext, GCS_COMPSTR,
+    ISC_SHOWUICOMPOSITIONWINDOW,
+};
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::Input::{
+    GetRawInputData, RegisterRawInputDevices, HRAWINPUT, RAWINPUT, RAWINPUTDEVICE, RAWINPUTHEADER,
+    RID_INPUT, RIM_TYPEMOUSE,
+};
+use windows::Win32::UI::Shell::{
+    DragAcceptFiles, DragFinish, DragQueryFileW, DragQueryPoint, HDROP,
+};
 use windows::Win32::UI::WindowsAndMessaging::*;
 use ze_core::maths::{Point2, RectI32};
 use ze_core::{ze_error, ze_verbose};
 use ze_platform::{
-    Cursor, Error, KeyCode, Message, Monitor, MouseButton, Platform, SystemCursor, Window,
+    Cursor, Error, FileDialogId, FileFilter, KeyCode, Message, ModifierBits, Modifiers, Monitor,
+    MouseButton, Platform, PowerSource, PowerStatus, SystemCursor, SystemTheme, Window,
     WindowFlagBits, WindowFlags,
 };
 
@@ -66,6 +100,21 @@ pub struct WindowsPlatform {
     window_map: Mutex<HashMap<HashableHWND, Weak<WindowsWindow>>>,
     message_queue: Mutex<VecDeque<Message>>,
     monitors: Mutex<Vec<Monitor>>,
+    system_theme: Mutex<SystemTheme>,
+
+    /// First half of a surrogate pair received by WM_CHAR
+    high_surrogate: Mutex<Option<u16>>,
+
+    /// `ShowCursor` is counted, only called when the visibility changes
+    cursor_shown: AtomicBool,
+
+    /// Thread polling the messages, woken up by posting it a message
+    thread_id: u32,
+    file_dialogs: FileDialogs,
+
+    /// COM is used by the taskbar progress, it fails if the application initialized it with
+    /// another threading model
+    com_initialized: bool,
 }
 
 impl WindowsPlatform {
@@ -73,6 +122,17 @@ impl WindowsPlatform {
         unsafe {
             timeBeginPeriod(1);
 
+            // Required to get the actual monitor DPIs and WM_DPICHANGED messages
+            SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+
+            let com_initialized = match CoInitializeEx(None, COINIT_APARTMENTTHREADED) {
+                Ok(()) => true,
+                Err(error) => {
+                    ze_error!("Failed to initialize COM: {}", error);
+                    false
+                }
+            };
+
             let class_name = utf8_to_utf16(WIN_CLASS_NAME);
             let win_class = WNDCLASSEXW {
                 cbSize: size_of::<WNDCLASSEXW>() as u32,
@@ -94,6 +154,12 @@ impl WindowsPlatform {
                 window_map: Default::default(),
                 message_queue: Mutex::new(VecDeque::new()),
                 monitors: Default::default(),
+                system_theme: Mutex::new(SystemTheme::Light),
+                high_surrogate: Default::default(),
+                cursor_shown: AtomicBool::new(true),
+                thread_id: GetCurrentThreadId(),
+                file_dialogs: FileDialogs::new(GetCurrentThreadId()),
+                com_initialized,
             });
 
             // Create dummy window to set platform pointer into the WNDCLASS
@@ -123,23 +189,62 @@ impl WindowsPlatform {
             }
 
             platform.update_monitors();
+            platform.update_system_theme();
+
+            // Raw mouse motion is sent as WM_INPUT to the focused window,
+            // the mouse is the usage 2 of the generic desktop usage page
+            let raw_mouse = RAWINPUTDEVICE {
+                usUsagePage: 1,
+                usUsage: 2,
+                dwFlags: Default::default(),
+                hwndTarget: HWND::default(),
+            };
+            if !RegisterRawInputDevices(&[raw_mouse], size_of::<RAWINPUTDEVICE>() as u32).as_bool()
+            {
+                ze_error!("Failed to register raw mouse input: {}", GetLastError().0);
+            }
 
             platform
         }
     }
 
-    fn update_monitors(&self) {
-        let mut monitors = self.monitors.lock();
-        monitors.clear();
-
+    /// Enumerate the monitors again, returns true if they changed
+    fn update_monitors(&self) -> bool {
+        let mut monitors = vec![];
         unsafe {
             EnumDisplayMonitors(
                 HDC::default(),
                 None,
                 Some(enum_display_monitors_callback),
-                LPARAM((&*monitors as *const _) as isize),
+                LPARAM((&mut monitors as *mut Vec<Monitor>) as isize),
             );
         }
+
+        let mut current_monitors = self.monitors.lock();
+        let changed = *current_monitors != monitors;
+        *current_monitors = monitors;
+        changed
+    }
+
+    /// Read the theme from the registry again, returns true if it changed
+    fn update_system_theme(&self) -> bool {
+        let theme = read_system_theme();
+        let mut current_theme = self.system_theme.lock();
+        let changed = *current_theme != theme;
+        *current_theme = theme;
+        changed
+    }
+
+    /// Window lock is released before invoking the hit test callback, as it may query the platform
+    fn hit_test(&self, hwnd: HWND, lparam: LPARAM) -> Option<LRESULT> {
+        let window = self.window_map.lock().get(&hwnd.into())?.upgrade()?;
+        window.hit_test(lparam)
+    }
+
+    fn min_max_info(&self, hwnd: HWND, info: &mut MINMAXINFO) -> Option<LRESULT> {
+        let window = self.window_map.lock().get(&hwnd.into())?.upgrade()?;
+        window.min_max_info(info);
+        Some(LRESULT(0))
     }
 
     fn send_window_message(&self, hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) {
@@ -151,7 +256,7 @@ impl WindowsPlatform {
             }
             match msg {
                 WM_CLOSE => {
-                    message_queue.push_back(Message::WindowClosed(window.clone()));
+                    message_queue.push_back(Message::WindowCloseRequested(window.clone()));
                 }
                 WM_SIZE => {
                     message_queue.push_back(Message::WindowResized(
@@ -160,6 +265,33 @@ impl WindowsPlatform {
                         ze_win_hiword!(lparam.0) as u32,
                     ));
                 }
+                WM_DPICHANGED => {
+                    // Monitor DPIs may have changed too
+                    self.update_monitors();
+                    message_queue.push_back(Message::WindowDpiChanged(
+                        window.clone(),
+                        ze_win_hiword!(wparam.0) as f32,
+                    ));
+                }
+                // Sent for any device, the monitors are only reported if they changed
+                WM_DISPLAYCHANGE | WM_DEVICECHANGE => {
+                    if self.update_monitors() {
+                        message_queue.push_back(Message::MonitorsChanged);
+                    }
+                }
+                // Broadcast to all top-level windows, the theme is only reported if it changed
+                WM_SETTINGCHANGE => {
+                    if self.update_system_theme() {
+                        message_queue
+                            .push_back(Message::SystemThemeChanged(*self.system_theme.lock()));
+                    }
+                }
+                WM_SETFOCUS => {
+                    message_queue.push_back(Message::WindowFocusGained(window.clone()));
+                }
+                WM_KILLFOCUS => {
+                    message_queue.push_back(Message::WindowFocusLost(window.clone()));
+                }
                 WM_LBUTTONDOWN => {
                     message_queue.push_back(Message::MouseButtonDown(
                         window.clone(),
@@ -232,6 +364,13 @@ impl WindowsPlatform {
                         self.mouse_position(),
                     ));
                 }
+                WM_MOUSEHWHEEL => {
+                    message_queue.push_back(Message::MouseWheelH(
+                        window.clone(),
+                        (ze_win_hiword!(wparam.0) as c_short as f32) / (WHEEL_DELTA as f32),
+                        self.mouse_position(),
+                    ));
+                }
                 WM_SYSKEYDOWN | WM_KEYDOWN => {
                     let key_code = VIRTUAL_KEY(wparam.0 as u16);
                     let repeat = (lparam.0 & 0x40000000) != 0;
@@ -240,6 +379,7 @@ impl WindowsPlatform {
                     message_queue.push_back(Message::KeyDown(
                         window.clone(),
                         convert_key_code(key_code),
+                        convert_scan_code(lparam),
                         character_code,
                         repeat,
                     ));
@@ -252,16 +392,219 @@ impl WindowsPlatform {
                     message_queue.push_back(Message::KeyUp(
                         window.clone(),
                         convert_key_code(key_code),
+                        convert_scan_code(lparam),
                         character_code,
                         repeat,
                     ));
                 }
+                WM_MOUSEMOVE => {
+                    if window
+                        .upgrade()
+                        .is_some_and(|window| window.track_mouse_leave())
+                    {
+                        message_queue.push_back(Message::MouseEntered(window.clone()));
+                    }
+                }
+                WM_MOUSELEAVE => {
+                    message_queue.push_back(Message::MouseLeft(window.clone()));
+                }
+                WM_INPUT => {
+                    if let Some((x, y)) = unsafe { raw_mouse_motion(HRAWINPUT(lparam.0)) } {
+                        message_queue.push_back(Message::MouseMotionRaw(x as f32, y as f32));
+                    }
+                }
+                WM_DROPFILES => {
+                    let (files, position) =
+                        unsafe { dropped_files(hwnd, HDROP(wparam.0 as isize)) };
+                    message_queue.push_back(Message::FilesDropped(window.clone(), files, position));
+                }
+                WM_IME_COMPOSITION if lparam.0 as u32 & GCS_COMPSTR.0 != 0 => {
+                    let text = unsafe { composition_string(hwnd) };
+                    message_queue.push_back(Message::TextComposition(window.clone(), text));
+                }
+                WM_IME_ENDCOMPOSITION => {
+                    message_queue
+                        .push_back(Message::TextComposition(window.clone(), String::new()));
+                }
+                WM_CHAR => {
+                    let code_unit = wparam.0 as u16;
+                    let mut high_surrogate = self.high_surrogate.lock();
+                    if (0xD800..0xDC00).contains(&code_unit) {
+                        *high_surrogate = Some(code_unit);
+                        return;
+                    }
+
+                    let code_units = high_surrogate.take().into_iter().chain(Some(code_unit));
+                    for character in char::decode_utf16(code_units).flatten() {
+                        if !character.is_control() {
+                            message_queue.push_back(Message::TextInput(window.clone(), character));
+                        }
+                    }
+                }
                 _ => (),
             }
         }
     }
 }
 
+/// Paths of the files of a WM_DROPFILES message and the screen position they were dropped at
+unsafe fn dropped_files(hwnd: HWND, hdrop: HDROP) -> (Vec<PathBuf>, Point2<i32>) {
+    let count = DragQueryFileW(hdrop, u32::MAX, None);
+    let files = (0..count)
+        .map(|index| {
+            // The length doesn't include the null terminator
+            let mut path = vec![0; DragQueryFileW(hdrop, index, None) as usize + 1];
+            let len = DragQueryFileW(hdrop, index, Some(&mut path)) as usize;
+            PathBuf::from(OsString::from_wide(&path[..len]))
+        })
+        .collect();
+
+    let mut position = POINT::default();
+    DragQueryPoint(hdrop, &mut position);
+    ClientToScreen(hwnd, &mut position);
+    DragFinish(hdrop);
+
+    (files, Point2::new(position.x, position.y))
+}
+
+/// Text being composed by the input method of a window, the result is sent as WM_CHAR messages
+unsafe fn composition_string(hwnd: HWND) -> String {
+    let context = ImmGetContext(hwnd);
+    if context.0 == 0 {
+        return String::new();
+    }
+
+    // The size is in bytes
+    let size = ImmGetCompositionStringW(context, GCS_COMPSTR, None, 0).max(0);
+    let mut text = vec![0u16; size as usize / size_of::<u16>()];
+    if !text.is_empty() {
+        ImmGetCompositionStringW(
+            context,
+            GCS_COMPSTR,
+            Some(text.as_mut_ptr() as *mut c_void),
+            size as u32,
+        );
+    }
+
+    ImmReleaseContext(hwnd, context);
+    String::from_utf16_lossy(&text)
+}
+
+/// Relative motion of a WM_INPUT message, absolute devices like tablets are ignored
+unsafe fn raw_mouse_motion(handle: HRAWINPUT) -> Option<(i32, i32)> {
+    let mut raw_input = RAWINPUT::default();
+    let mut size = size_of::<RAWINPUT>() as u32;
+    let result = GetRawInputData(
+        handle,
+        RID_INPUT,
+        Some(&mut raw_input as *mut RAWINPUT as *mut c_void),
+        &mut size,
+        size_of::<RAWINPUTHEADER>() as u32,
+    );
+
+    if result == u32::MAX || raw_input.header.dwType != RIM_TYPEMOUSE.0 {
+        return None;
+    }
+
+    let mouse = raw_input.data.mouse;
+    if mouse.usFlags & MOUSE_MOVE_ABSOLUTE as u16 != 0 {
+        return None;
+    }
+
+    Some((mouse.lLastX, mouse.lLastY))
+}
+
+/// Inverse of `convert_key_code`, telling apart the left and right modifier keys
+fn virtual_key(key: KeyCode) -> Option<VIRTUAL_KEY> {
+    Some(match key {
+        KeyCode::None => return None,
+        KeyCode::Escape => VK_ESCAPE,
+        KeyCode::Space => VK_SPACE,
+        KeyCode::Backspace => VK_BACK,
+        KeyCode::A => VK_A,
+        KeyCode::B => VK_B,
+        KeyCode::C => VK_C,
+        KeyCode::D => VK_D,
+        KeyCode::E => VK_E,
+        KeyCode::F => VK_F,
+        KeyCode::G => VK_G,
+        KeyCode::H => VK_H,
+        KeyCode::I => VK_I,
+        KeyCode::J => VK_J,
+        KeyCode::K => VK_K,
+        KeyCode::L => VK_L,
+        KeyCode::M => VK_M,
+        KeyCode::N => VK_N,
+        KeyCode::O => VK_O,
+        KeyCode::P => VK_P,
+        KeyCode::Q => VK_Q,
+        KeyCode::R => VK_R,
+        KeyCode::S => VK_S,
+        KeyCode::T => VK_T,
+        KeyCode::U => VK_U,
+        KeyCode::V => VK_V,
+        KeyCode::W => VK_W,
+        KeyCode::X => VK_X,
+        KeyCode::Y => VK_Y,
+        KeyCode::Z => VK_Z,
+        KeyCode::Numpad0 => VK_NUMPAD0,
+        KeyCode::Numpad1 => VK_NUMPAD1,
+        KeyCode::Numpad2 => VK_NUMPAD2,
+        KeyCode::Numpad3 => VK_NUMPAD3,
+        KeyCode::Numpad4 => VK_NUMPAD4,
+        KeyCode::Numpad5 => VK_NUMPAD5,
+        KeyCode::Numpad6 => VK_NUMPAD6,
+        KeyCode::Numpad7 => VK_NUMPAD7,
+        KeyCode::Numpad8 => VK_NUMPAD8,
+        KeyCode::Numpad9 => VK_NUMPAD9,
+        KeyCode::Num0 => VK_0,
+        KeyCode::Num1 => VK_1,
+        KeyCode::Num2 => VK_2,
+        KeyCode::Num3 => VK_3,
+        KeyCode::Num4 => VK_4,
+        KeyCode::Num5 => VK_5,
+        KeyCode::Num6 => VK_6,
+        KeyCode::Num7 => VK_7,
+        KeyCode::Num8 => VK_8,
+        KeyCode::Num9 => VK_9,
+        KeyCode::LeftControl => VK_LCONTROL,
+        KeyCode::LeftShift => VK_LSHIFT,
+        KeyCode::LeftAlt => VK_LMENU,
+        KeyCode::RightControl => VK_RCONTROL,
+        KeyCode::RightShift => VK_RSHIFT,
+        KeyCode::RightAlt => VK_RMENU,
+        KeyCode::F1 => VK_F1,
+        KeyCode::F2 => VK_F2,
+        KeyCode::F3 => VK_F3,
+        KeyCode::F4 => VK_F4,
+        KeyCode::F5 => VK_F5,
+        KeyCode::F6 => VK_F6,
+        KeyCode::F7 => VK_F7,
+        KeyCode::F8 => VK_F8,
+        KeyCode::F9 => VK_F9,
+        KeyCode::F10 => VK_F10,
+        KeyCode::F11 => VK_F11,
+        KeyCode::F12 => VK_F12,
+        KeyCode::F13 => VK_F13,
+        KeyCode::F14 => VK_F14,
+        KeyCode::F15 => VK_F15,
+        KeyCode::F16 => VK_F16,
+        KeyCode::F17 => VK_F17,
+        KeyCode::F18 => VK_F18,
+        KeyCode::F19 => VK_F19,
+        KeyCode::F20 => VK_F20,
+        KeyCode::F21 => VK_F21,
+        KeyCode::F22 => VK_F22,
+        KeyCode::F23 => VK_F23,
+        KeyCode::F24 => VK_F24,
+    })
+}
+
+/// Physical state of the key, even if the message telling it changed wasn't processed yet
+fn is_virtual_key_down(key: VIRTUAL_KEY) -> bool {
+    unsafe { GetAsyncKeyState(key.0 as i32) as u16 & 0x8000 != 0 }
+}
+
 fn convert_key_code(key: VIRTUAL_KEY) -> KeyCode {
     match key {
         VK_ESCAPE => KeyCode::Escape,
@@ -349,6 +692,159 @@ fn convert_key_code(key: VIRTUAL_KEY) -> KeyCode {
     }
 }
 
+/// Physical key of a key message from the set 1 scancode in its `lparam`, extended keys
+/// are prefixed with 0xE0 like in the scancode set
+fn convert_scan_code(lparam: LPARAM) -> KeyCode {
+    let mut scan_code = (lparam.0 >> 16) & 0xFF;
+    if (lparam.0 >> 24) & 1 != 0 {
+        scan_code |= 0xE000;
+    }
+    match scan_code {
+        0x01 => KeyCode::Escape,
+        0x02 => KeyCode::Num1,
+        0x03 => KeyCode::Num2,
+        0x04 => KeyCode::Num3,
+        0x05 => KeyCode::Num4,
+        0x06 => KeyCode::Num5,
+        0x07 => KeyCode::Num6,
+        0x08 => KeyCode::Num7,
+        0x09 => KeyCode::Num8,
+        0x0A => KeyCode::Num9,
+        0x0B => KeyCode::Num0,
+        0x0E => KeyCode::Backspace,
+        0x10 => KeyCode::Q,
+        0x11 => KeyCode::W,
+        0x12 => KeyCode::E,
+        0x13 => KeyCode::R,
+        0x14 => KeyCode::T,
+        0x15 => KeyCode::Y,
+        0x16 => KeyCode::U,
+        0x17 => KeyCode::I,
+        0x18 => KeyCode::O,
+        0x19 => KeyCode::P,
+        0x1D => KeyCode::LeftControl,
+        0xE01D => KeyCode::RightControl,
+        0x1E => KeyCode::A,
+        0x1F => KeyCode::S,
+        0x20 => KeyCode::D,
+        0x21 => KeyCode::F,
+        0x22 => KeyCode::G,
+        0x23 => KeyCode::H,
+        0x24 => KeyCode::J,
+        0x25 => KeyCode::K,
+        0x26 => KeyCode::L,
+        0x2A => KeyCode::LeftShift,
+        0x2C => KeyCode::Z,
+        0x2D => KeyCode::X,
+        0x2E => KeyCode::C,
+        0x2F => KeyCode::V,
+        0x30 => KeyCode::B,
+        0x31 => KeyCode::N,
+        0x32 => KeyCode::M,
+        0x36 => KeyCode::RightShift,
+        0x38 => KeyCode::LeftAlt,
+        0xE038 => KeyCode::RightAlt,
+        0x39 => KeyCode::Space,
+        0x3B => KeyCode::F1,
+        0x3C => KeyCode::F2,
+        0x3D => KeyCode::F3,
+        0x3E => KeyCode::F4,
+        0x3F => KeyCode::F5,
+        0x40 => KeyCode::F6,
+        0x41 => KeyCode::F7,
+        0x42 => KeyCode::F8,
+        0x43 => KeyCode::F9,
+        0x44 => KeyCode::F10,
+        0x47 => KeyCode::Numpad7,
+        0x48 => KeyCode::Numpad8,
+        0x49 => KeyCode::Numpad9,
+        0x4B => KeyCode::Numpad4,
+        0x4C => KeyCode::Numpad5,
+        0x4D => KeyCode::Numpad6,
+        0x4F => KeyCode::Numpad1,
+        0x50 => KeyCode::Numpad2,
+        0x51 => KeyCode::Numpad3,
+        0x52 => KeyCode::Numpad0,
+        0x57 => KeyCode::F11,
+        0x58 => KeyCode::F12,
+        0x64 => KeyCode::F13,
+        0x65 => KeyCode::F14,
+        0x66 => KeyCode::F15,
+        0x67 => KeyCode::F16,
+        0x68 => KeyCode::F17,
+        0x69 => KeyCode::F18,
+        0x6A => KeyCode::F19,
+        0x6B => KeyCode::F20,
+        0x6C => KeyCode::F21,
+        0x6D => KeyCode::F22,
+        0x6E => KeyCode::F23,
+        0x76 => KeyCode::F24,
+        _ => KeyCode::None,
+    }
+}
+
+/// Applications are dark when `AppsUseLightTheme` is 0, the value is missing before Windows 10
+fn read_system_theme() -> SystemTheme {
+    let key = utf8_to_utf16("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize");
+    let value = utf8_to_utf16("AppsUseLightTheme");
+    let mut data = 1u32;
+    let mut size = size_of::<u32>() as u32;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            PCWSTR(key.as_ptr()),
+            PCWSTR(value.as_ptr()),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut c_void),
+            Some(&mut size),
+        )
+    };
+
+    if result == NO_ERROR && data == 0 {
+        SystemTheme::Dark
+    } else {
+        SystemTheme::Light
+    }
+}
+
+/// `ACLineStatus` values, other values are unknown
+const AC_LINE_OFFLINE: u8 = 0;
+const AC_LINE_ONLINE: u8 = 1;
+
+/// `BatteryLifePercent` when the charge is unknown
+const BATTERY_PERCENTAGE_UNKNOWN: u8 = 255;
+
+/// `BatteryFlag` bit set when the system has no battery
+const BATTERY_FLAG_NO_BATTERY: u8 = 128;
+
+/// `SystemStatusFlag` when the battery saver is on, always 0 before Windows 10
+const SYSTEM_STATUS_BATTERY_SAVER_ON: u8 = 1;
+
+fn read_power_status() -> PowerStatus {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut status) } == false {
+        return PowerStatus::default();
+    }
+
+    let source = match status.ACLineStatus {
+        AC_LINE_OFFLINE => PowerSource::Battery,
+        AC_LINE_ONLINE => PowerSource::Ac,
+        _ => PowerSource::Unknown,
+    };
+
+    let has_battery = status.BatteryFlag & BATTERY_FLAG_NO_BATTERY == 0;
+    let battery_percentage = (has_battery
+        && status.BatteryLifePercent != BATTERY_PERCENTAGE_UNKNOWN)
+        .then_some(status.BatteryLifePercent.min(100));
+
+    PowerStatus {
+        source,
+        battery_percentage,
+        power_saver: status.SystemStatusFlag == SYSTEM_STATUS_BATTERY_SAVER_ON,
+    }
+}
+
 unsafe extern "system" fn enum_display_monitors_callback(
     monitor: HMONITOR,
     _: HDC,
@@ -399,6 +895,9 @@ impl Drop for WindowsPlatform {
                 HINSTANCE::default(),
             );
             timeEndPeriod(1);
+            if self.com_initialized {
+                CoUninitialize();
+            }
         }
     }
 }
@@ -406,6 +905,7 @@ impl Drop for WindowsPlatform {
 impl Platform for WindowsPlatform {
     fn poll_event(&self) -> Option<Message> {
         let mut message_queue = self.message_queue.lock();
+        self.file_dialogs.send_closed(&mut *message_queue);
         if let Some(message) = message_queue.pop_front() {
             Some(message)
         } else {
@@ -423,6 +923,35 @@ impl Platform for WindowsPlatform {
         }
     }
 
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        // The thread message of a closed dialog may have been removed by the last poll
+        let mut message_queue = self.message_queue.lock();
+        self.file_dialogs.send_closed(&mut *message_queue);
+        if let Some(message) = message_queue.pop_front() {
+            return Some(message);
+        }
+        drop(message_queue);
+
+        // Rounded up so short timeouts don't turn into busy loops, u32::MAX is INFINITE
+        let timeout = timeout.map_or(u32::MAX, |timeout| {
+            timeout.as_micros().div_ceil(1000).min(u32::MAX as u128 - 1) as u32
+        });
+
+        // Messages left in the queue by the previous poll don't wake up the thread without
+        // MWMO_INPUTAVAILABLE
+        unsafe {
+            MsgWaitForMultipleObjectsEx(None, timeout, QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+        }
+
+        self.poll_event()
+    }
+
+    fn wake(&self) {
+        unsafe {
+            PostThreadMessageW(self.thread_id, WM_NULL, WPARAM(0), LPARAM(0));
+        }
+    }
+
     fn create_window(
         &self,
         name: &str,
@@ -443,6 +972,12 @@ impl Platform for WindowsPlatform {
 
         if flags.contains(WindowFlagBits::Resizable) {
             style |= WS_THICKFRAME;
+
+            // Snapping and the snap layouts of the maximize button hit test need these,
+            // the frame of borderless windows is hidden when handling WM_NCCALCSIZE
+            if flags.contains(WindowFlagBits::Borderless) {
+                style |= WS_MINIMIZEBOX | WS_MAXIMIZEBOX;
+            }
         }
 
         // Rect must be ajusted since Win32 api include window decoration in the width/height
@@ -457,7 +992,7 @@ impl Platform for WindowsPlatform {
             let class_name = utf8_to_utf16(WIN_CLASS_NAME);
             let window_name = utf8_to_utf16(name);
 
-            AdjustWindowRectEx(&mut initial_rect, style, false, ex_style);
+            adjust_window_rect(&mut initial_rect, style, ex_style);
             let hwnd = CreateWindowExW(
                 ex_style,
                 PCWSTR(class_name.as_ptr()),
@@ -479,6 +1014,7 @@ impl Platform for WindowsPlatform {
             }
 
             SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, LWA_ALPHA);
+            DragAcceptFiles(hwnd, true);
 
             ShowWindow(
                 hwnd,
@@ -533,6 +1069,22 @@ impl Platform for WindowsPlatform {
         Box::new(WindowsCursor::new(cursor.unwrap()))
     }
 
+    fn create_cursor_from_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        match create_icon(width, height, rgba, Some(hotspot)) {
+            Some(icon) => Ok(Box::new(WindowsCursor::new(HCURSOR(icon.0)))),
+            None => {
+                ze_error!("Failed to create a {}x{} cursor", width, height);
+                Err(Error::Unknown)
+            }
+        }
+    }
+
     fn set_cursor(&self, cursor: Option<&dyn Cursor>) {
         let mut win_cursor = HCURSOR::default();
 
@@ -546,12 +1098,54 @@ impl Platform for WindowsPlatform {
         }
     }
 
+    fn show_cursor(&self, show: bool) {
+        if self.cursor_shown.swap(show, Ordering::SeqCst) != show {
+            unsafe {
+                ShowCursor(show);
+            }
+        }
+    }
+
+    fn capture_mouse(&self, window: &dyn Window) {
+        let window = window.downcast_ref::<WindowsWindow>().unwrap();
+        unsafe {
+            SetCapture(window.hwnd());
+        }
+    }
+
+    fn release_mouse(&self) {
+        unsafe {
+            ReleaseCapture();
+        }
+    }
+
     fn mouse_position(&self) -> Point2<i32> {
         let mut pos = POINT::default();
         unsafe { GetCursorPos(&mut pos) };
         Point2::<i32>::new(pos.x, pos.y)
     }
 
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        virtual_key(key).is_some_and(is_virtual_key_down)
+    }
+
+    fn keyboard_modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        if is_virtual_key_down(VK_SHIFT) {
+            modifiers |= ModifierBits::Shift;
+        }
+        if is_virtual_key_down(VK_CONTROL) {
+            modifiers |= ModifierBits::Control;
+        }
+        if is_virtual_key_down(VK_MENU) {
+            modifiers |= ModifierBits::Alt;
+        }
+        if is_virtual_key_down(VK_LWIN) || is_virtual_key_down(VK_RWIN) {
+            modifiers |= ModifierBits::Super;
+        }
+        modifiers
+    }
+
     fn monitor_count(&self) -> usize {
         self.monitors.lock().len()
     }
@@ -559,6 +1153,73 @@ impl Platform for WindowsPlatform {
     fn monitor(&self, index: usize) -> Monitor {
         self.monitors.lock()[index]
     }
+
+    fn system_theme(&self) -> SystemTheme {
+        *self.system_theme.lock()
+    }
+
+    fn power_status(&self) -> PowerStatus {
+        read_power_status()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        unsafe {
+            if OpenClipboard(HWND::default()) == false {
+                return None;
+            }
+
+            let text = GetClipboardData(u32::from(CF_UNICODETEXT.0))
+                .ok()
+                .and_then(|data| {
+                    let text = GlobalLock(data.0) as *const u16;
+                    if text.is_null() {
+                        return None;
+                    }
+
+                    let text = utf16_to_utf8(text);
+                    GlobalUnlock(data.0);
+                    Some(text)
+                });
+
+            CloseClipboard();
+            text
+        }
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        let text = utf8_to_utf16(text);
+
+        unsafe {
+            if OpenClipboard(HWND::default()) == false {
+                ze_error!("Failed to open clipboard: {}", GetLastError().0);
+                return;
+            }
+
+            EmptyClipboard();
+
+            // The clipboard takes ownership of the memory once set
+            let data = GlobalAlloc(GMEM_MOVEABLE, text.len() * size_of::<u16>());
+            let buffer = GlobalLock(data) as *mut u16;
+            if !buffer.is_null() {
+                buffer.copy_from_nonoverlapping(text.as_ptr(), text.len());
+                GlobalUnlock(data);
+                if SetClipboardData(u32::from(CF_UNICODETEXT.0), HANDLE(data)).is_err() {
+                    ze_error!("Failed to set clipboard data: {}", GetLastError().0);
+                    GlobalFree(data);
+                }
+            }
+
+            CloseClipboard();
+        }
+    }
+
+    fn open_file_dialog(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId {
+        self.file_dialogs.open(filters, multi_select)
+    }
+
+    fn save_file_dialog(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId {
+        self.file_dialogs.save(filters, default_name)
+    }
 }
 
 unsafe extern "system" fn wnd_proc(
@@ -576,10 +1237,70 @@ unsafe extern "system" fn wnd_proc(
         (ptr as *const WindowsPlatform).as_ref().unwrap_unchecked()
     };
 
+    if msg == WM_NCHITTEST {
+        if let Some(result) = platform.hit_test(hwnd, lparam) {
+            return result;
+        }
+    }
+
+    // The structure is filled with the default limits, only ours are replaced
+    if msg == WM_GETMINMAXINFO {
+        if let Some(result) = platform.min_max_info(hwnd, &mut *(lparam.0 as *mut MINMAXINFO)) {
+            return result;
+        }
+    }
+
+    if msg == WM_NCCALCSIZE
+        && wparam.0 != 0
+        && is_borderless_resizable(WINDOW_STYLE(GetWindowLongPtrW(hwnd, GWL_STYLE) as u32))
+    {
+        // Maximized windows overflow the monitor by the size of their frame
+        if IsZoomed(hwnd).as_bool() {
+            let params = &mut *(lparam.0 as *mut NCCALCSIZE_PARAMS);
+            let frame_x = GetSystemMetrics(SM_CXSIZEFRAME) + GetSystemMetrics(SM_CXPADDEDBORDER);
+            let frame_y = GetSystemMetrics(SM_CYSIZEFRAME) + GetSystemMetrics(SM_CXPADDEDBORDER);
+            params.rgrc[0].left += frame_x;
+            params.rgrc[0].right -= frame_x;
+            params.rgrc[0].top += frame_y;
+            params.rgrc[0].bottom -= frame_y;
+        }
+        return LRESULT(0);
+    }
+
+    // The text being composed is displayed by the application, only the candidates by the IME
+    let lparam = if msg == WM_IME_SETCONTEXT {
+        LPARAM(lparam.0 & !(ISC_SHOWUICOMPOSITIONWINDOW as isize))
+    } else {
+        lparam
+    };
+
     platform.send_window_message(hwnd, msg, wparam, lparam);
+
+    // Destroying the window is left to the application, see `Window::close`
+    if msg == WM_CLOSE {
+        return LRESULT(0);
+    }
+
+    // Resize to the size suggested for the new DPI, once the DPI change message is queued
+    if msg == WM_DPICHANGED {
+        let rect = &*(lparam.0 as *const RECT);
+        SetWindowPos(
+            hwnd,
+            HWND::default(),
+            rect.left,
+            rect.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+        return LRESULT(0);
+    }
+
     DefWindowProcW(hwnd, msg, wparam, lparam)
 }
 
 mod cursor;
+mod file_dialog;
+mod icon;
 mod utils;
 mod window;
@@ -1,4 +1,14 @@
 ﻿pub fn utf8_to_utf16(str : &str) -> Vec<u16>
 {
     str.encode_utf16().chain(Some(0)).collect()
+}
+
+/// Convert a null terminated UTF-16 string
+pub unsafe fn utf16_to_utf8(str: *const u16) -> String {
+    let mut len = 0;
+    while *str.add(len) != 0 {
+        len += 1;
+    }
+
+    String::from_utf16_lossy(std::slice::from_raw_parts(str, len))
 }
\ No newline at end of file
@@ -1,12 +1,38 @@
-﻿use crate::utf8_to_utf16;
-use raw_window_handle::{RawWindowHandle, Win32WindowHandle};
-use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+﻿use crate::icon::create_icon;
+use crate::utf8_to_utf16;
+use parking_lot::Mutex;
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, Win32WindowHandle, WindowsDisplayHandle,
+};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
 use std::sync::Arc;
 use windows::core::*;
 use windows::Win32::Foundation::*;
+use windows::Win32::Graphics::Gdi::ScreenToClient;
+use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
+use windows::Win32::UI::Input::Ime::{
+    ImmGetContext, ImmReleaseContext, ImmSetCandidateWindow, ImmSetCompositionWindow,
+    CANDIDATEFORM, CFS_CANDIDATEPOS, CFS_POINT, COMPOSITIONFORM,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::{TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT};
+use windows::Win32::UI::Shell::{
+    ITaskbarList3, TaskbarList, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS, TBPF_NORMAL,
+    TBPF_PAUSED,
+};
 use windows::Win32::UI::WindowsAndMessaging::*;
 use ze_core::maths::Point2;
-use ze_platform::Window;
+use ze_platform::{HitTest, HitTestCallback, TaskbarProgressState, Window};
+
+/// Steps of the taskbar progress bar
+const TASKBAR_PROGRESS_TOTAL: u64 = 10000;
+
+/// `ITaskbarList3` isn't thread safe, windows are only used from the thread that created them
+struct Taskbar(ITaskbarList3);
+
+unsafe impl Send for Taskbar {}
+unsafe impl Sync for Taskbar {}
 
 pub struct WindowsWindow {
     hwnd: HWND,
@@ -16,6 +42,18 @@ pub struct WindowsWindow {
     y: AtomicI32,
     style: WINDOW_STYLE,
     ex_style: WINDOW_EX_STYLE,
+    hit_test_callback: Mutex<Option<HitTestCallback>>,
+
+    /// Icon set with `set_icon`, destroyed once replaced
+    icon: Mutex<Option<HICON>>,
+
+    /// The mouse is over the client area, a `WM_MOUSELEAVE` is expected
+    mouse_inside: AtomicBool,
+    min_size: Mutex<Option<(u32, u32)>>,
+    max_size: Mutex<Option<(u32, u32)>>,
+
+    /// Created by the first `set_taskbar_progress` call
+    taskbar: Mutex<Option<Taskbar>>,
 }
 
 impl WindowsWindow {
@@ -36,9 +74,19 @@ impl WindowsWindow {
             y: AtomicI32::new(y),
             style,
             ex_style,
+            hit_test_callback: Mutex::new(None),
+            icon: Mutex::new(None),
+            mouse_inside: AtomicBool::new(false),
+            min_size: Mutex::new(None),
+            max_size: Mutex::new(None),
+            taskbar: Mutex::new(None),
         })
     }
 
+    pub fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
     pub fn send_window_message(&self, msg: u32, _: WPARAM, lparam: LPARAM) {
         match msg {
             WM_SIZE => {
@@ -53,15 +101,96 @@ impl WindowsWindow {
                 self.x.store(x as i32, Ordering::SeqCst);
                 self.y.store(y as i32, Ordering::SeqCst);
             }
+            WM_MOUSELEAVE => self.mouse_inside.store(false, Ordering::SeqCst),
             _ => {}
         }
     }
+
+    /// Ask Windows for a `WM_MOUSELEAVE` once the mouse leaves the client area,
+    /// returns true if the mouse just entered it
+    pub fn track_mouse_leave(&self) -> bool {
+        if self.mouse_inside.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        let mut event = TRACKMOUSEEVENT {
+            cbSize: size_of::<TRACKMOUSEEVENT>() as u32,
+            dwFlags: TME_LEAVE,
+            hwndTrack: self.hwnd,
+            dwHoverTime: 0,
+        };
+        unsafe {
+            TrackMouseEvent(&mut event);
+        }
+        true
+    }
+
+    /// Answer a `WM_GETMINMAXINFO` with the size limits, converted from client to window sizes
+    pub fn min_max_info(&self, info: &mut MINMAXINFO) {
+        let window_size = |(width, height): (u32, u32)| {
+            let mut rect = RECT {
+                left: 0,
+                top: 0,
+                right: width.min(i32::MAX as u32) as i32,
+                bottom: height.min(i32::MAX as u32) as i32,
+            };
+            unsafe {
+                adjust_window_rect(&mut rect, self.style, self.ex_style);
+            }
+            POINT {
+                x: rect.right - rect.left,
+                y: rect.bottom - rect.top,
+            }
+        };
+
+        if let Some(min_size) = *self.min_size.lock() {
+            info.ptMinTrackSize = window_size(min_size);
+        }
+        if let Some(max_size) = *self.max_size.lock() {
+            info.ptMaxTrackSize = window_size(max_size);
+        }
+    }
+
+    /// Answer a `WM_NCHITTEST` using the hit test callback, `None` lets Windows decide
+    pub fn hit_test(&self, lparam: LPARAM) -> Option<LRESULT> {
+        let hit_test_callback = self.hit_test_callback.lock();
+        let callback = hit_test_callback.as_ref()?;
+
+        // Coordinates are signed as they can be on a monitor left or above the primary one
+        let mut point = POINT {
+            x: ze_win_loword!(lparam.0) as i16 as i32,
+            y: ze_win_hiword!(lparam.0) as i16 as i32,
+        };
+        unsafe {
+            ScreenToClient(self.hwnd, &mut point);
+        }
+
+        let result = match callback(Point2::new(point.x, point.y)) {
+            HitTest::Client => HTCLIENT,
+            HitTest::Caption => HTCAPTION,
+            // Hovering the maximize button shows the snap layouts flyout on Windows 11
+            HitTest::MaximizeButton => HTMAXBUTTON,
+            HitTest::Left => HTLEFT,
+            HitTest::Right => HTRIGHT,
+            HitTest::Top => HTTOP,
+            HitTest::Bottom => HTBOTTOM,
+            HitTest::TopLeft => HTTOPLEFT,
+            HitTest::TopRight => HTTOPRIGHT,
+            HitTest::BottomLeft => HTBOTTOMLEFT,
+            HitTest::BottomRight => HTBOTTOMRIGHT,
+        };
+
+        Some(LRESULT(result as isize))
+    }
 }
 
 impl Drop for WindowsWindow {
     fn drop(&mut self) {
         unsafe {
             DestroyWindow(self.hwnd);
+            if let Some(icon) = self.icon.lock().take() {
+                DestroyIcon(icon);
+            }
         }
     }
 }
@@ -91,7 +220,7 @@ impl Window for WindowsWindow {
                 right: width as i32,
                 bottom: height as i32,
             };
-            AdjustWindowRectEx(&mut initial_rect, self.style, false, self.ex_style);
+            adjust_window_rect(&mut initial_rect, self.style, self.ex_style);
 
             self.width.store(width, Ordering::SeqCst);
             self.height.store(height, Ordering::SeqCst);
@@ -108,6 +237,15 @@ impl Window for WindowsWindow {
         };
     }
 
+    /// Only enforced when the window is resized by the user, through `WM_GETMINMAXINFO`
+    fn set_min_size(&self, size: Option<(u32, u32)>) {
+        *self.min_size.lock() = size;
+    }
+
+    fn set_max_size(&self, size: Option<(u32, u32)>) {
+        *self.max_size.lock() = size;
+    }
+
     fn set_title(&self, title: &str) {
         let title = utf8_to_utf16(title);
         unsafe {
@@ -115,18 +253,185 @@ impl Window for WindowsWindow {
         }
     }
 
+    fn set_icon(&self, width: u32, height: u32, rgba: &[u8]) {
+        let icon = match create_icon(width, height, rgba, None) {
+            Some(icon) => icon,
+            None => return,
+        };
+
+        unsafe {
+            // Windows downscales the icon for the title bar
+            for size in [ICON_SMALL, ICON_BIG] {
+                SendMessageW(self.hwnd, WM_SETICON, WPARAM(size as usize), LPARAM(icon.0));
+            }
+            if let Some(previous_icon) = self.icon.lock().replace(icon) {
+                DestroyIcon(previous_icon);
+            }
+        }
+    }
+
     fn show(&self) {
         unsafe {
             ShowWindow(self.hwnd, SW_SHOW);
         }
     }
 
+    fn close(&self) {
+        unsafe {
+            ShowWindow(self.hwnd, SW_HIDE);
+        }
+    }
+
+    fn minimize(&self) {
+        unsafe {
+            ShowWindow(self.hwnd, SW_MINIMIZE);
+        }
+    }
+
+    fn maximize(&self) {
+        unsafe {
+            ShowWindow(self.hwnd, SW_MAXIMIZE);
+        }
+    }
+
+    fn restore(&self) {
+        unsafe {
+            ShowWindow(self.hwnd, SW_RESTORE);
+        }
+    }
+
+    /// Windows may only flash the taskbar button if another process has the foreground
+    fn focus(&self) {
+        unsafe {
+            SetForegroundWindow(self.hwnd);
+        }
+    }
+
+    fn set_opacity(&self, opacity: f32) {
+        unsafe {
+            SetLayeredWindowAttributes(
+                self.hwnd,
+                COLORREF(0),
+                (opacity.clamp(0.0, 1.0) * 255.0).round() as u8,
+                LWA_ALPHA,
+            );
+        }
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) {
+        let insert_after = if always_on_top {
+            HWND_TOPMOST
+        } else {
+            HWND_NOTOPMOST
+        };
+
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    fn set_taskbar_progress(&self, state: TaskbarProgressState, fraction: f32) {
+        let mut taskbar = self.taskbar.lock();
+        if taskbar.is_none() {
+            *taskbar = unsafe { create_taskbar() };
+        }
+
+        let taskbar = match &*taskbar {
+            Some(taskbar) => &taskbar.0,
+            None => return,
+        };
+
+        let flags = match state {
+            TaskbarProgressState::None => TBPF_NOPROGRESS,
+            TaskbarProgressState::Indeterminate => TBPF_INDETERMINATE,
+            TaskbarProgressState::Normal => TBPF_NORMAL,
+            TaskbarProgressState::Paused => TBPF_PAUSED,
+            TaskbarProgressState::Error => TBPF_ERROR,
+        };
+
+        // Setting a value switches the indeterminate state back to normal
+        unsafe {
+            let _ = taskbar.SetProgressState(self.hwnd, flags);
+            if flags != TBPF_NOPROGRESS && flags != TBPF_INDETERMINATE {
+                let completed = fraction.clamp(0.0, 1.0) * TASKBAR_PROGRESS_TOTAL as f32;
+                let _ =
+                    taskbar.SetProgressValue(self.hwnd, completed as u64, TASKBAR_PROGRESS_TOTAL);
+            }
+        }
+    }
+
+    fn request_attention(&self) {
+        unsafe {
+            if GetForegroundWindow() == self.hwnd {
+                return;
+            }
+
+            let info = FLASHWINFO {
+                cbSize: size_of::<FLASHWINFO>() as u32,
+                hwnd: self.hwnd,
+                dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+                uCount: 0,
+                dwTimeout: 0,
+            };
+            FlashWindowEx(&info);
+        }
+    }
+
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>) {
+        *self.hit_test_callback.lock() = callback;
+    }
+
+    fn set_ime_position(&self, position: Point2<i32>) {
+        let point = POINT {
+            x: position.x,
+            y: position.y,
+        };
+
+        unsafe {
+            let context = ImmGetContext(self.hwnd);
+            if context.0 == 0 {
+                return;
+            }
+
+            ImmSetCompositionWindow(
+                context,
+                &COMPOSITIONFORM {
+                    dwStyle: CFS_POINT,
+                    ptCurrentPos: point,
+                    rcArea: RECT::default(),
+                },
+            );
+            ImmSetCandidateWindow(
+                context,
+                &CANDIDATEFORM {
+                    dwIndex: 0,
+                    dwStyle: CFS_CANDIDATEPOS,
+                    ptCurrentPos: point,
+                    rcArea: RECT::default(),
+                },
+            );
+            ImmReleaseContext(self.hwnd, context);
+        }
+    }
+
     fn handle(&self) -> RawWindowHandle {
         let mut handle = Win32WindowHandle::empty();
         handle.hwnd = self.hwnd.0 as *mut std::ffi::c_void;
         RawWindowHandle::Win32(handle)
     }
 
+    fn display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Windows(WindowsDisplayHandle::empty())
+    }
+
     fn width(&self) -> u32 {
         self.width.load(Ordering::SeqCst)
     }
@@ -138,4 +443,44 @@ impl Window for WindowsWindow {
     fn position(&self) -> Point2<i32> {
         Point2::<i32>::new(self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
     }
+
+    fn cursor_position(&self) -> Point2<i32> {
+        let mut point = POINT::default();
+        unsafe {
+            GetCursorPos(&mut point);
+            ScreenToClient(self.hwnd, &mut point);
+        }
+        Point2::new(point.x, point.y)
+    }
+
+    fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.hwnd).as_bool() }
+    }
+
+    fn is_focused(&self) -> bool {
+        unsafe { GetForegroundWindow() == self.hwnd }
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        unsafe { GetDpiForWindow(self.hwnd) as f32 / USER_DEFAULT_SCREEN_DPI as f32 }
+    }
+}
+
+/// Borderless windows keep their frame to be resized and snapped, their client area covers it
+pub fn is_borderless_resizable(style: WINDOW_STYLE) -> bool {
+    style.0 & WS_POPUP.0 != 0 && style.0 & WS_THICKFRAME.0 != 0
+}
+
+/// Grow a client area rect to the window rect, like `AdjustWindowRectEx`
+pub unsafe fn adjust_window_rect(rect: &mut RECT, style: WINDOW_STYLE, ex_style: WINDOW_EX_STYLE) {
+    if !is_borderless_resizable(style) {
+        AdjustWindowRectEx(rect, style, false, ex_style);
+    }
+}
+
+/// Requires COM, initialized by the platform
+unsafe fn create_taskbar() -> Option<Taskbar> {
+    let taskbar: ITaskbarList3 = CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER).ok()?;
+    taskbar.HrInit().ok()?;
+    Some(Taskbar(taskbar))
 }
@@ -0,0 +1,161 @@
+use crate::skeleton::{nlerp, BoneTransform, Skeleton};
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use ze_asset_system::Asset;
+use ze_core::type_uuid::*;
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Interpolation {
+    Step,
+    Linear,
+}
+
+/// Keyframes of a bone property, sorted by time
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Keyframes<T> {
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub values: Vec<T>,
+}
+
+impl<T: Copy> Keyframes<T> {
+    fn sample(&self, time: f32, lerp: impl Fn(&T, &T, f32) -> T) -> Option<T> {
+        let next = self.times.partition_point(|key_time| *key_time <= time);
+        match next {
+            0 => self.values.first().copied(),
+            next if next >= self.times.len() => self.values.last().copied(),
+            next => {
+                let (previous, next) = (next - 1, next);
+                if self.interpolation == Interpolation::Step {
+                    return Some(self.values[previous]);
+                }
+
+                let duration = self.times[next] - self.times[previous];
+                let t = (time - self.times[previous]) / duration;
+                Some(lerp(&self.values[previous], &self.values[next], t))
+            }
+        }
+    }
+}
+
+/// Animation of a single bone, properties without keyframes keep their rest value
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Track {
+    pub bone: String,
+    pub translation: Option<Keyframes<glm::Vec3>>,
+    pub rotation: Option<Keyframes<glm::Quat>>,
+    pub scale: Option<Keyframes<glm::Vec3>>,
+}
+
+/// Named marker fired by the [`crate::player::AnimationPlayer`] when its time is reached
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AnimationEvent {
+    pub time: f32,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, TypeUuid, Default)]
+#[type_uuid = "c5b0e2e9-3f0a-4c8e-a1a4-5d7f1e2b9c31"]
+pub struct AnimationClip {
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) uuid: Uuid,
+    name: String,
+    duration: f32,
+
+    /// Tracks reference bones by name so that clips can be shared by skeletons
+    tracks: Vec<Track>,
+
+    /// Events sorted by time
+    events: Vec<AnimationEvent>,
+}
+
+impl AnimationClip {
+    pub fn new(name: String, tracks: Vec<Track>, mut events: Vec<AnimationEvent>) -> Self {
+        let duration = tracks
+            .iter()
+            .flat_map(|track| {
+                let translation = track.translation.as_ref().map(|keys| &keys.times);
+                let rotation = track.rotation.as_ref().map(|keys| &keys.times);
+                let scale = track.scale.as_ref().map(|keys| &keys.times);
+                [translation, rotation, scale]
+            })
+            .flatten()
+            .filter_map(|times| times.last().copied())
+            .fold(0.0, f32::max);
+
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self {
+            uuid: Uuid::nil(),
+            name,
+            duration,
+            tracks,
+            events,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    pub fn events(&self) -> &[AnimationEvent] {
+        &self.events
+    }
+
+    /// Index of the bone of `skeleton` animated by each track
+    pub fn bone_mapping(&self, skeleton: &Skeleton) -> Vec<Option<usize>> {
+        self.tracks
+            .iter()
+            .map(|track| skeleton.bone_index(&track.bone))
+            .collect()
+    }
+
+    /// Write the transforms of the animated bones at `time` to `pose`
+    ///
+    /// `mapping` must come from [`AnimationClip::bone_mapping`] with the skeleton of `pose`
+    pub fn sample(&self, time: f32, mapping: &[Option<usize>], pose: &mut [BoneTransform]) {
+        for (track, bone) in self.tracks.iter().zip(mapping) {
+            let transform = match bone {
+                Some(bone) => &mut pose[*bone],
+                None => continue,
+            };
+
+            if let Some(translation) = track
+                .translation
+                .as_ref()
+                .and_then(|keys| keys.sample(time, glm::lerp))
+            {
+                transform.translation = translation;
+            }
+
+            if let Some(rotation) = track
+                .rotation
+                .as_ref()
+                .and_then(|keys| keys.sample(time, nlerp))
+            {
+                transform.rotation = rotation;
+            }
+
+            if let Some(scale) = track
+                .scale
+                .as_ref()
+                .and_then(|keys| keys.sample(time, glm::lerp))
+            {
+                transform.scale = scale;
+            }
+        }
+    }
+}
+
+impl Asset for AnimationClip {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
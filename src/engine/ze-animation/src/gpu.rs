@@ -0,0 +1,117 @@
+use crate::player::AnimationPlayer;
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+use std::mem::{size_of, size_of_val};
+use std::sync::Arc;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_gfx::backend::*;
+
+/// Structured buffer containing the skinning matrices of an entity
+pub struct SkinningBuffer {
+    buffer: Arc<Buffer>,
+    srv: Arc<ShaderResourceView>,
+}
+
+impl SkinningBuffer {
+    pub fn buffer(&self) -> &Arc<Buffer> {
+        &self.buffer
+    }
+
+    pub fn srv(&self) -> &Arc<ShaderResourceView> {
+        &self.srv
+    }
+}
+
+/// Upload the skinning matrices of the animation players to the GPU
+pub struct SkinningBuffers {
+    device: Arc<dyn Device>,
+    buffers: HashMap<Entity, SkinningBuffer>,
+}
+
+impl SkinningBuffers {
+    pub fn new(device: Arc<dyn Device>) -> Self {
+        Self {
+            device,
+            buffers: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&SkinningBuffer> {
+        self.buffers.get(&entity)
+    }
+
+    /// Write the skinning matrices of every [`AnimationPlayer`] of `world`, should be called
+    /// once per frame after updating the animations
+    ///
+    /// Buffers of entities that no longer have a player are released
+    pub fn update(&mut self, world: &World) -> Result<(), DeviceError> {
+        let mut players = HashMap::new();
+        world
+            .query::<(Entity, &AnimationPlayer)>()
+            .for_each(world, |(entity, player)| {
+                players.insert(entity, player);
+            });
+        self.buffers
+            .retain(|entity, _| players.contains_key(entity));
+
+        for (entity, player) in players {
+            let matrices = player.skinning_matrices();
+            let size_bytes = (matrices.len().max(1) * size_of::<glm::Mat4>()) as u64;
+            let large_enough = match self.buffers.get(&entity) {
+                Some(buffer) => buffer.buffer.info.size_bytes >= size_bytes,
+                None => false,
+            };
+            if !large_enough {
+                let buffer = self.create_buffer(size_bytes)?;
+                self.buffers.insert(entity, buffer);
+            }
+
+            let buffer = &self.buffers[&entity];
+
+            let ptr = self
+                .device
+                .buffer_mapped_ptr(&buffer.buffer)
+                .expect("Skinning buffer should be mapped");
+
+            // SAFETY: The buffer is large enough to contain all the matrices
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    matrices.as_ptr() as *const u8,
+                    ptr,
+                    size_of_val(matrices),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_buffer(&self, size_bytes: u64) -> Result<SkinningBuffer, DeviceError> {
+        let buffer = Arc::new(self.device.create_buffer(
+            &BufferDesc {
+                size_bytes,
+                usage: BufferUsageFlags::default(),
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::CpuToGpu,
+                    memory_flags: Default::default(),
+                },
+                default_resource_state: ResourceState::Common,
+            },
+            None,
+            "Skinning Matrices",
+        )?);
+
+        let srv = Arc::new(self.device.create_shader_resource_view(
+            &ShaderResourceViewDesc::Buffer(BufferSRV {
+                buffer: buffer.clone(),
+                ty: BufferSRVType::Structured(BufferSRVStructured {
+                    offset_in_bytes: 0,
+                    stride_in_bytes: size_of::<glm::Mat4>() as u32,
+                }),
+            }),
+        )?);
+
+        Ok(SkinningBuffer { buffer, srv })
+    }
+}
@@ -0,0 +1,300 @@
+use crate::clip::{AnimationClip, AnimationEvent, Interpolation, Keyframes, Track};
+use crate::skeleton::{Bone, BoneTransform, Skeleton};
+use gltf::animation::util::ReadOutputs;
+use gltf::animation::Interpolation as GltfInterpolation;
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use uuid::Uuid;
+use ze_asset_system::importer::{
+    AssetImporter, AssetImporterResult, Error, ImportedAsset, SourceAssetMetadata,
+};
+use ze_core::type_uuid::TypeUuid;
+use ze_filesystem::path::Path;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ImporterState {
+    /// UUID of each imported clip, kept between imports
+    clips: BTreeMap<String, Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Parameters {
+    /// Events added to the imported clips, by clip name
+    events: BTreeMap<String, Vec<AnimationEvent>>,
+}
+
+/// Import the skeleton of the first skin of a glTF file and its animations
+///
+/// The main asset is the skeleton, buffers must be embedded (.glb or data URIs)
+#[derive(Default)]
+pub struct GltfAnimationImporter {}
+
+impl AssetImporter for GltfAnimationImporter {
+    type State = ImporterState;
+    type Parameters = Parameters;
+
+    fn import(
+        &self,
+        _: &Path,
+        src: &mut dyn Read,
+        metadata: Option<SourceAssetMetadata<Self::State, Self::Parameters>>,
+    ) -> Result<AssetImporterResult<Self::State, Self::Parameters>, Error> {
+        let mut metadata = metadata.unwrap_or_else(|| {
+            SourceAssetMetadata::new(Uuid::new_v4(), Default::default(), Default::default())
+        });
+
+        let mut data = vec![];
+        src.read_to_end(&mut data)?;
+        let (document, buffers, _) =
+            gltf::import_slice(&data).map_err(|_| Error::InvalidSourceAsset)?;
+
+        let skin = document.skins().next().ok_or(Error::InvalidSourceAsset)?;
+        let joints = skin.joints().collect::<Vec<_>>();
+        if joints.is_empty() {
+            return Err(Error::InvalidSourceAsset);
+        }
+
+        let joint_indices = joints
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.index(), index))
+            .collect::<HashMap<_, _>>();
+
+        let mut parents = HashMap::new();
+        for node in document.nodes() {
+            for child in node.children() {
+                parents.insert(child.index(), node.index());
+            }
+        }
+
+        let inverse_bind_matrices = match skin
+            .reader(|buffer| Some(&buffers[buffer.index()]))
+            .read_inverse_bind_matrices()
+        {
+            Some(matrices) => matrices.map(glm::Mat4::from).collect(),
+            None => vec![glm::Mat4::identity(); joints.len()],
+        };
+
+        let bones = joints
+            .iter()
+            .zip(inverse_bind_matrices)
+            .map(|(node, inverse_bind_matrix)| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                Bone {
+                    name: node_name(node),
+                    parent: parents
+                        .get(&node.index())
+                        .and_then(|parent| joint_indices.get(parent).copied()),
+                    rest: BoneTransform {
+                        translation: translation.into(),
+                        rotation: glm::quat(rotation[0], rotation[1], rotation[2], rotation[3]),
+                        scale: scale.into(),
+                    },
+                    inverse_bind_matrix,
+                }
+            })
+            .collect();
+
+        // Nodes above the skeleton are part of the model space of the bones
+        let nodes = document.nodes().collect::<Vec<_>>();
+        let root_transform = match parents.get(&joints[0].index()) {
+            Some(parent) => global_matrix(&nodes, &parents, *parent),
+            None => glm::Mat4::identity(),
+        };
+
+        let skeleton =
+            Skeleton::new(bones, root_transform).map_err(|_| Error::InvalidSourceAsset)?;
+        let mut assets = vec![ImportedAsset::new(
+            *metadata.uuid(),
+            Skeleton::type_uuid(),
+            encode(&skeleton)?,
+        )];
+
+        for animation in document.animations() {
+            let name = animation
+                .name()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("Animation {}", animation.index()));
+
+            let mut tracks = BTreeMap::new();
+            for channel in animation.channels() {
+                let node = channel.target().node();
+                if !joint_indices.contains_key(&node.index()) {
+                    continue;
+                }
+
+                let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+                let times = match reader.read_inputs() {
+                    Some(times) => times.collect::<Vec<_>>(),
+                    None => continue,
+                };
+
+                let interpolation = channel.sampler().interpolation();
+                let track = tracks.entry(node.index()).or_insert_with(|| Track {
+                    bone: node_name(&node),
+                    translation: None,
+                    rotation: None,
+                    scale: None,
+                });
+
+                match reader.read_outputs() {
+                    Some(ReadOutputs::Translations(values)) => {
+                        track.translation =
+                            Some(keyframes(interpolation, times, values.map(glm::Vec3::from)))
+                    }
+                    Some(ReadOutputs::Rotations(values)) => {
+                        track.rotation = Some(keyframes(
+                            interpolation,
+                            times,
+                            values
+                                .into_f32()
+                                .map(|value| glm::quat(value[0], value[1], value[2], value[3])),
+                        ))
+                    }
+                    Some(ReadOutputs::Scales(values)) => {
+                        track.scale =
+                            Some(keyframes(interpolation, times, values.map(glm::Vec3::from)))
+                    }
+                    _ => {}
+                }
+            }
+
+            let events = metadata
+                .parameters()
+                .events
+                .get(&name)
+                .cloned()
+                .unwrap_or_default();
+            let uuid = *metadata
+                .state_mut()
+                .clips
+                .entry(name.clone())
+                .or_insert_with(Uuid::new_v4);
+            let clip = AnimationClip::new(name, tracks.into_values().collect(), events);
+            assets.push(ImportedAsset::new(
+                uuid,
+                AnimationClip::type_uuid(),
+                encode(&clip)?,
+            ));
+        }
+
+        Ok((assets, metadata))
+    }
+}
+
+fn node_name(node: &gltf::Node) -> String {
+    node.name()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("Node {}", node.index()))
+}
+
+fn global_matrix(nodes: &[gltf::Node], parents: &HashMap<usize, usize>, node: usize) -> glm::Mat4 {
+    let local = glm::Mat4::from(nodes[node].transform().matrix());
+    match parents.get(&node) {
+        Some(parent) => global_matrix(nodes, parents, *parent) * local,
+        None => local,
+    }
+}
+
+/// Cubic spline values are stored with their in and out tangents, which are ignored
+fn keyframes<T>(
+    interpolation: GltfInterpolation,
+    times: Vec<f32>,
+    values: impl Iterator<Item = T>,
+) -> Keyframes<T> {
+    let (interpolation, values) = match interpolation {
+        GltfInterpolation::Step => (Interpolation::Step, values.collect()),
+        GltfInterpolation::Linear => (Interpolation::Linear, values.collect()),
+        GltfInterpolation::CubicSpline => {
+            (Interpolation::Linear, values.skip(1).step_by(3).collect())
+        }
+    };
+
+    Keyframes {
+        interpolation,
+        times,
+        values,
+    }
+}
+
+fn encode(asset: &impl serde::Serialize) -> Result<Vec<u8>, Error> {
+    bincode::serde::encode_to_vec(asset, bincode::config::standard())
+        .map_err(|_| Error::FailedToSerialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::importer::GltfAnimationImporter;
+    use ze_asset_system::importer::AssetImporter;
+    use ze_filesystem::path::Path;
+
+    /// Armature node translated by 1 on Y, a single joint and an animation moving it on X
+    const GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "nodes": [
+            { "name": "Armature", "translation": [0, 1, 0], "children": [1] },
+            { "name": "Root" }
+        ],
+        "skins": [{ "joints": [1] }],
+        "animations": [{
+            "name": "Move",
+            "channels": [{ "sampler": 0, "target": { "node": 1, "path": "translation" } }],
+            "samplers": [{ "input": 0, "output": 1, "interpolation": "LINEAR" }]
+        }],
+        "buffers": [{
+            "byteLength": 32,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAA="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 8 },
+            { "buffer": 0, "byteOffset": 8, "byteLength": 24 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR",
+                "min": [0], "max": [1]
+            },
+            { "bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3" }
+        ]
+    }"#;
+
+    #[test]
+    fn import() {
+        let (assets, metadata) = GltfAnimationImporter::default()
+            .import(
+                &Path::parse("//skeleton.gltf").unwrap(),
+                &mut GLTF.as_bytes(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].uuid(), metadata.uuid());
+        assert_eq!(assets[1].uuid(), &metadata.state().clips["Move"]);
+
+        // Clips keep their UUID when reimported
+        let (assets, _) = GltfAnimationImporter::default()
+            .import(
+                &Path::parse("//skeleton.gltf").unwrap(),
+                &mut GLTF.as_bytes(),
+                Some(metadata),
+            )
+            .unwrap();
+        let (skeleton, _): (crate::skeleton::Skeleton, _) =
+            bincode::serde::decode_from_slice(assets[0].data(), bincode::config::standard())
+                .unwrap();
+        let (clip, _): (crate::clip::AnimationClip, _) =
+            bincode::serde::decode_from_slice(assets[1].data(), bincode::config::standard())
+                .unwrap();
+        assert_eq!(skeleton.bones().len(), 1);
+        assert_eq!(clip.duration(), 1.0);
+
+        let mut pose = skeleton.rest_pose();
+        clip.sample(0.5, &clip.bone_mapping(&skeleton), &mut pose);
+        let mut matrices = vec![];
+        skeleton.model_matrices(&pose, &mut matrices);
+        let translation: nalgebra_glm::Vec4 = matrices[0].column(3).into();
+        assert_eq!(translation, nalgebra_glm::vec4(0.5, 1.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod clip;
+pub mod gpu;
+pub mod importer;
+pub mod loader;
+pub mod player;
+pub mod skeleton;
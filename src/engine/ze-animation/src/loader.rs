@@ -0,0 +1,40 @@
+use crate::clip::AnimationClip;
+use crate::skeleton::Skeleton;
+use std::io::Read;
+use std::sync::Arc;
+use uuid::Uuid;
+use ze_asset_system::loader::{AssetLoader, Error};
+use ze_asset_system::Asset;
+
+fn decode<T: serde::de::DeserializeOwned>(asset: &mut dyn Read) -> Result<T, Error> {
+    let mut data = vec![];
+    asset
+        .read_to_end(&mut data)
+        .map_err(|_| Error::CannotDeserialize)?;
+
+    bincode::serde::decode_from_slice(&data, bincode::config::standard())
+        .map(|(asset, _)| asset)
+        .map_err(|_| Error::CannotDeserialize)
+}
+
+#[derive(Default)]
+pub struct SkeletonLoader {}
+
+impl AssetLoader for SkeletonLoader {
+    fn load(&self, uuid: Uuid, asset: &mut dyn Read) -> Result<Arc<dyn Asset>, Error> {
+        let mut skeleton: Skeleton = decode(asset)?;
+        skeleton.uuid = uuid;
+        Ok(Arc::new(skeleton))
+    }
+}
+
+#[derive(Default)]
+pub struct AnimationClipLoader {}
+
+impl AssetLoader for AnimationClipLoader {
+    fn load(&self, uuid: Uuid, asset: &mut dyn Read) -> Result<Arc<dyn Asset>, Error> {
+        let mut clip: AnimationClip = decode(asset)?;
+        clip.uuid = uuid;
+        Ok(Arc::new(clip))
+    }
+}
@@ -0,0 +1,468 @@
+use crate::clip::AnimationClip;
+use crate::skeleton::{BoneTransform, Skeleton};
+use nalgebra_glm as glm;
+use std::sync::Arc;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+
+/// Clips blended according to a parameter, e.g idle, walk and run blended by the speed
+///
+/// Clips are played synchronized, at the same fraction of their duration
+#[derive(Clone)]
+pub struct BlendSpace1D {
+    samples: Vec<(f32, Arc<AnimationClip>)>,
+}
+
+impl BlendSpace1D {
+    /// Create a blend space from clips and their position on the parameter axis
+    pub fn new(mut samples: Vec<(f32, Arc<AnimationClip>)>) -> Self {
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { samples }
+    }
+
+    /// Indices of the two clips surrounding `parameter` and the blend factor between them
+    fn blend(&self, parameter: f32) -> Option<(usize, usize, f32)> {
+        let next = self
+            .samples
+            .partition_point(|(position, _)| *position <= parameter);
+        match next {
+            _ if self.samples.is_empty() => None,
+            0 => Some((0, 0, 0.0)),
+            next if next >= self.samples.len() => {
+                Some((self.samples.len() - 1, self.samples.len() - 1, 0.0))
+            }
+            next => {
+                let (a, b) = (self.samples[next - 1].0, self.samples[next].0);
+                Some((next - 1, next, (parameter - a) / (b - a)))
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PlaySettings {
+    pub speed: f32,
+    pub looping: bool,
+
+    /// Duration in seconds of the cross-fade with the animations already playing
+    pub fade_duration: f32,
+}
+
+impl Default for PlaySettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            looping: true,
+            fade_duration: 0.0,
+        }
+    }
+}
+
+enum Source {
+    Clip(Arc<AnimationClip>),
+    BlendSpace(BlendSpace1D),
+}
+
+struct Layer {
+    source: Source,
+
+    /// Bone mapping of each clip of the source
+    mappings: Vec<Vec<Option<usize>>>,
+
+    /// Fraction of the duration played
+    phase: f32,
+    settings: PlaySettings,
+    weight: f32,
+
+    /// Weight change per second, negative while fading out and infinite without fade
+    fade_speed: f32,
+}
+
+impl Layer {
+    fn clips(&self) -> Vec<&Arc<AnimationClip>> {
+        match &self.source {
+            Source::Clip(clip) => vec![clip],
+            Source::BlendSpace(blend_space) => {
+                blend_space.samples.iter().map(|(_, clip)| clip).collect()
+            }
+        }
+    }
+
+    fn duration(&self, parameter: f32) -> f32 {
+        match &self.source {
+            Source::Clip(clip) => clip.duration(),
+            Source::BlendSpace(blend_space) => match blend_space.blend(parameter) {
+                Some((a, b, t)) => glm::lerp_scalar(
+                    blend_space.samples[a].1.duration(),
+                    blend_space.samples[b].1.duration(),
+                    t,
+                ),
+                None => 0.0,
+            },
+        }
+    }
+
+    /// Clip whose events are fired
+    fn main_clip(&self, parameter: f32) -> Option<&Arc<AnimationClip>> {
+        match &self.source {
+            Source::Clip(clip) => Some(clip),
+            Source::BlendSpace(blend_space) => blend_space
+                .blend(parameter)
+                .map(|(a, b, t)| &blend_space.samples[if t < 0.5 { a } else { b }].1),
+        }
+    }
+
+    fn sample(&self, parameter: f32, rest_pose: &[BoneTransform], pose: &mut [BoneTransform]) {
+        pose.copy_from_slice(rest_pose);
+        match &self.source {
+            Source::Clip(clip) => {
+                clip.sample(self.phase * clip.duration(), &self.mappings[0], pose);
+            }
+            Source::BlendSpace(blend_space) => {
+                let (a, b, t) = match blend_space.blend(parameter) {
+                    Some(blend) => blend,
+                    None => return,
+                };
+
+                let clip = &blend_space.samples[a].1;
+                clip.sample(self.phase * clip.duration(), &self.mappings[a], pose);
+                if a != b {
+                    let mut other = rest_pose.to_vec();
+                    let clip = &blend_space.samples[b].1;
+                    clip.sample(self.phase * clip.duration(), &self.mappings[b], &mut other);
+                    for (transform, other) in pose.iter_mut().zip(&other) {
+                        *transform = transform.blend(other, t);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Play and blend animations on a skeleton
+///
+/// Starting an animation fades out the ones already playing, the resulting pose and its
+/// skinning matrices are computed by [`AnimationPlayer::update`]
+#[derive(Component)]
+pub struct AnimationPlayer {
+    skeleton: Arc<Skeleton>,
+    layers: Vec<Layer>,
+    blend_parameter: f32,
+    rest_pose: Vec<BoneTransform>,
+    pose: Vec<BoneTransform>,
+    skinning_matrices: Vec<glm::Mat4>,
+    events: Vec<String>,
+}
+
+impl AnimationPlayer {
+    pub fn new(skeleton: Arc<Skeleton>) -> Self {
+        let rest_pose = skeleton.rest_pose();
+        let mut skinning_matrices = vec![];
+        skeleton.skinning_matrices(&rest_pose, &mut skinning_matrices);
+        Self {
+            skeleton,
+            layers: vec![],
+            blend_parameter: 0.0,
+            pose: rest_pose.clone(),
+            rest_pose,
+            skinning_matrices,
+            events: vec![],
+        }
+    }
+
+    pub fn play(&mut self, clip: Arc<AnimationClip>, settings: PlaySettings) {
+        self.push_layer(Source::Clip(clip), settings);
+    }
+
+    pub fn play_blend_space(&mut self, blend_space: BlendSpace1D, settings: PlaySettings) {
+        self.push_layer(Source::BlendSpace(blend_space), settings);
+    }
+
+    /// Set the parameter used by the blend spaces
+    pub fn set_blend_parameter(&mut self, parameter: f32) {
+        self.blend_parameter = parameter;
+    }
+
+    /// Fade out all the animations, going back to the rest pose
+    pub fn stop(&mut self, fade_duration: f32) {
+        for layer in &mut self.layers {
+            layer.fade_speed = -fade_speed(fade_duration);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.layers.is_empty()
+    }
+
+    pub fn skeleton(&self) -> &Arc<Skeleton> {
+        &self.skeleton
+    }
+
+    pub fn pose(&self) -> &[BoneTransform] {
+        &self.pose
+    }
+
+    /// Matrices transforming the bind pose vertices to the current pose, indexed by bone
+    pub fn skinning_matrices(&self) -> &[glm::Mat4] {
+        &self.skinning_matrices
+    }
+
+    /// Events fired during the last update
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.events.clear();
+        for layer in &mut self.layers {
+            layer.weight = if layer.fade_speed.is_infinite() {
+                layer.fade_speed.signum().max(0.0)
+            } else {
+                (layer.weight + layer.fade_speed * delta_time).clamp(0.0, 1.0)
+            };
+
+            let duration = layer.duration(self.blend_parameter);
+            if duration <= 0.0 {
+                continue;
+            }
+
+            let previous_phase = layer.phase;
+            layer.phase += delta_time * layer.settings.speed / duration;
+            let wrapped = layer.settings.looping && !(0.0..1.0).contains(&layer.phase);
+            layer.phase = if layer.settings.looping {
+                layer.phase.rem_euclid(1.0)
+            } else {
+                layer.phase.clamp(0.0, 1.0)
+            };
+
+            // Events of fading out animations are ignored
+            if layer.fade_speed >= 0.0 {
+                if let Some(clip) = layer.main_clip(self.blend_parameter) {
+                    collect_events(
+                        clip,
+                        previous_phase,
+                        layer.phase,
+                        wrapped,
+                        layer.settings.speed < 0.0,
+                        &mut self.events,
+                    );
+                }
+            }
+        }
+
+        self.layers
+            .retain(|layer| layer.weight > 0.0 || layer.fade_speed > 0.0);
+
+        // Layers are blended in order, the rest pose fills the missing weight
+        self.pose.copy_from_slice(&self.rest_pose);
+        let total_weight = self.layers.iter().map(|layer| layer.weight).sum::<f32>();
+        let mut accumulated_weight = (1.0 - total_weight).max(0.0);
+        let mut layer_pose = self.rest_pose.clone();
+        for layer in &self.layers {
+            if layer.weight <= 0.0 {
+                continue;
+            }
+
+            layer.sample(self.blend_parameter, &self.rest_pose, &mut layer_pose);
+            accumulated_weight += layer.weight;
+            let t = layer.weight / accumulated_weight;
+            for (transform, layer_transform) in self.pose.iter_mut().zip(&layer_pose) {
+                *transform = transform.blend(layer_transform, t);
+            }
+        }
+
+        self.skeleton
+            .skinning_matrices(&self.pose, &mut self.skinning_matrices);
+    }
+
+    fn push_layer(&mut self, source: Source, settings: PlaySettings) {
+        let speed = fade_speed(settings.fade_duration);
+        for layer in &mut self.layers {
+            layer.fade_speed = -speed;
+        }
+
+        let mut layer = Layer {
+            source,
+            mappings: vec![],
+            phase: if settings.speed < 0.0 { 1.0 } else { 0.0 },
+            settings,
+            weight: 0.0,
+            fade_speed: speed,
+        };
+        layer.mappings = layer
+            .clips()
+            .iter()
+            .map(|clip| clip.bone_mapping(&self.skeleton))
+            .collect();
+        self.layers.push(layer);
+    }
+}
+
+fn fade_speed(fade_duration: f32) -> f32 {
+    if fade_duration > 0.0 {
+        1.0 / fade_duration
+    } else {
+        f32::INFINITY
+    }
+}
+
+/// Add the events of `clip` between two phases, `wrapped` is true if the animation looped
+fn collect_events(
+    clip: &AnimationClip,
+    from: f32,
+    to: f32,
+    wrapped: bool,
+    backward: bool,
+    events: &mut Vec<String>,
+) {
+    let (from, to) = (from * clip.duration(), to * clip.duration());
+    let in_range = |time: f32| match (backward, wrapped) {
+        (false, false) => time > from && time <= to,
+        (false, true) => time > from || time <= to,
+        (true, false) => time < from && time >= to,
+        (true, true) => time < from || time >= to,
+    };
+
+    events.extend(
+        clip.events()
+            .iter()
+            .filter(|event| in_range(event.time))
+            .map(|event| event.name.clone()),
+    );
+}
+
+/// Update all the animation players of `world`, returns the fired events
+pub fn update_animations(world: &World, delta_time: f32) -> Vec<(Entity, String)> {
+    let mut events = vec![];
+    world
+        .query::<(Entity, &mut AnimationPlayer)>()
+        .for_each(world, |(entity, player)| {
+            player.update(delta_time);
+            events.extend(player.events().iter().map(|event| (entity, event.clone())));
+        });
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clip::{AnimationClip, AnimationEvent, Interpolation, Keyframes, Track};
+    use crate::player::{AnimationPlayer, BlendSpace1D, PlaySettings};
+    use crate::skeleton::{Bone, BoneTransform, Skeleton};
+    use nalgebra_glm as glm;
+    use std::sync::Arc;
+
+    fn skeleton() -> Arc<Skeleton> {
+        let bone = |name: &str, parent, bind_height: f32| Bone {
+            name: name.to_string(),
+            parent,
+            rest: BoneTransform {
+                translation: glm::vec3(0.0, 1.0, 0.0),
+                ..Default::default()
+            },
+            inverse_bind_matrix: glm::translation(&glm::vec3(0.0, -bind_height, 0.0)),
+        };
+
+        // Children before parents to check the evaluation order
+        Arc::new(
+            Skeleton::new(
+                vec![bone("hand", Some(1), 2.0), bone("root", None, 1.0)],
+                glm::Mat4::identity(),
+            )
+            .unwrap(),
+        )
+    }
+
+    /// Clip moving the root to `x` during one second
+    fn clip(x: f32, events: Vec<AnimationEvent>) -> Arc<AnimationClip> {
+        Arc::new(AnimationClip::new(
+            "clip".to_string(),
+            vec![Track {
+                bone: "root".to_string(),
+                translation: Some(Keyframes {
+                    interpolation: Interpolation::Linear,
+                    times: vec![0.0, 1.0],
+                    values: vec![glm::vec3(0.0, 1.0, 0.0), glm::vec3(x, 1.0, 0.0)],
+                }),
+                rotation: None,
+                scale: None,
+            }],
+            events,
+        ))
+    }
+
+    #[test]
+    fn skinning_matrices() {
+        let mut player = AnimationPlayer::new(skeleton());
+        assert_eq!(player.skinning_matrices()[0], glm::Mat4::identity());
+
+        player.play(clip(4.0, vec![]), PlaySettings::default());
+        player.update(0.5);
+        let hand = player.skinning_matrices()[0] * glm::vec4(0.0, 2.0, 0.0, 1.0);
+        assert_eq!(hand, glm::vec4(2.0, 2.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn cross_fade() {
+        let mut player = AnimationPlayer::new(skeleton());
+        let settings = PlaySettings {
+            looping: false,
+            ..Default::default()
+        };
+        player.play(clip(2.0, vec![]), settings);
+        player.update(1.0);
+        assert_eq!(player.pose()[1].translation.x, 2.0);
+
+        player.play(
+            clip(4.0, vec![]),
+            PlaySettings {
+                fade_duration: 1.0,
+                ..settings
+            },
+        );
+        player.update(0.5);
+        assert_eq!(player.pose()[1].translation.x, 2.0 * 0.5 + 2.0 * 0.5);
+        player.update(0.5);
+        assert_eq!(player.pose()[1].translation.x, 4.0);
+        assert_eq!(player.layers.len(), 1);
+
+        player.stop(0.0);
+        player.update(0.0);
+        assert!(!player.is_playing());
+        assert_eq!(player.pose()[1].translation.x, 0.0);
+    }
+
+    #[test]
+    fn blend_space() {
+        let mut player = AnimationPlayer::new(skeleton());
+        player.play_blend_space(
+            BlendSpace1D::new(vec![(1.0, clip(4.0, vec![])), (0.0, clip(2.0, vec![]))]),
+            PlaySettings::default(),
+        );
+        player.set_blend_parameter(0.25);
+        player.update(0.5);
+        assert_eq!(player.pose()[1].translation.x, 1.0 * 0.75 + 2.0 * 0.25);
+    }
+
+    #[test]
+    fn events() {
+        let event = |time: f32, name: &str| AnimationEvent {
+            time,
+            name: name.to_string(),
+        };
+        let mut player = AnimationPlayer::new(skeleton());
+        player.play(
+            clip(1.0, vec![event(0.75, "b"), event(0.25, "a")]),
+            PlaySettings::default(),
+        );
+
+        player.update(0.5);
+        assert_eq!(player.events(), ["a"]);
+        player.update(0.25);
+        assert_eq!(player.events(), ["b"]);
+
+        // Wraps around
+        player.update(0.75);
+        assert_eq!(player.events(), ["a"]);
+    }
+}
@@ -0,0 +1,155 @@
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use ze_asset_system::Asset;
+use ze_core::type_uuid::*;
+
+/// Local transform of a bone, relative to its parent
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub struct BoneTransform {
+    pub translation: glm::Vec3,
+    pub rotation: glm::Quat,
+    pub scale: glm::Vec3,
+}
+
+impl BoneTransform {
+    pub fn to_matrix(&self) -> glm::Mat4 {
+        glm::translation(&self.translation)
+            * glm::quat_to_mat4(&self.rotation)
+            * glm::scaling(&self.scale)
+    }
+
+    /// Interpolate between two transforms, rotations are normalized-lerped along the shortest
+    /// path
+    pub fn blend(&self, other: &BoneTransform, t: f32) -> BoneTransform {
+        BoneTransform {
+            translation: glm::lerp(&self.translation, &other.translation, t),
+            rotation: nlerp(&self.rotation, &other.rotation, t),
+            scale: glm::lerp(&self.scale, &other.scale, t),
+        }
+    }
+}
+
+impl Default for BoneTransform {
+    fn default() -> Self {
+        Self {
+            translation: glm::Vec3::zeros(),
+            rotation: glm::quat_identity(),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+pub(crate) fn nlerp(a: &glm::Quat, b: &glm::Quat, t: f32) -> glm::Quat {
+    let b = if glm::quat_dot(a, b) < 0.0 { -b } else { *b };
+    glm::quat_normalize(&glm::quat_lerp(a, &b, t))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bone {
+    pub name: String,
+    pub parent: Option<usize>,
+
+    /// Transform used when no animation affects the bone
+    pub rest: BoneTransform,
+
+    /// Transform from the model space to the bone space in the bind pose
+    pub inverse_bind_matrix: glm::Mat4,
+}
+
+#[derive(Debug)]
+pub enum SkeletonError {
+    /// A bone references itself or a missing bone as its parent, directly or not
+    InvalidParent(usize),
+}
+
+/// Hierarchy of bones, in the order used by the skinned meshes
+#[derive(Serialize, Deserialize, TypeUuid)]
+#[type_uuid = "8e4a4a5c-7d1c-4f43-9d55-3b3c2f6a1e07"]
+pub struct Skeleton {
+    #[serde(skip_serializing, skip_deserializing)]
+    pub(crate) uuid: Uuid,
+    bones: Vec<Bone>,
+
+    /// Transform applied to the bones without parent
+    root_transform: glm::Mat4,
+
+    /// Bones ordered so that parents are always before their children
+    evaluation_order: Vec<usize>,
+}
+
+impl Skeleton {
+    pub fn new(bones: Vec<Bone>, root_transform: glm::Mat4) -> Result<Self, SkeletonError> {
+        let mut evaluation_order = Vec::with_capacity(bones.len());
+        let mut visited = vec![false; bones.len()];
+        for index in 0..bones.len() {
+            let mut chain = vec![];
+            let mut current = Some(index);
+            while let Some(bone) = current {
+                if visited[bone] {
+                    break;
+                }
+
+                if chain.len() > bones.len() {
+                    return Err(SkeletonError::InvalidParent(index));
+                }
+
+                chain.push(bone);
+                current = bones[bone].parent;
+                if matches!(current, Some(parent) if parent >= bones.len()) {
+                    return Err(SkeletonError::InvalidParent(bone));
+                }
+            }
+
+            for bone in chain.into_iter().rev() {
+                visited[bone] = true;
+                evaluation_order.push(bone);
+            }
+        }
+
+        Ok(Self {
+            uuid: Uuid::nil(),
+            bones,
+            root_transform,
+            evaluation_order,
+        })
+    }
+
+    pub fn bones(&self) -> &[Bone] {
+        &self.bones
+    }
+
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+
+    pub fn rest_pose(&self) -> Vec<BoneTransform> {
+        self.bones.iter().map(|bone| bone.rest).collect()
+    }
+
+    /// Compute the model space matrix of each bone of `pose`
+    pub fn model_matrices(&self, pose: &[BoneTransform], matrices: &mut Vec<glm::Mat4>) {
+        matrices.clear();
+        matrices.resize(self.bones.len(), glm::identity());
+        for index in &self.evaluation_order {
+            let local = pose[*index].to_matrix();
+            matrices[*index] = match self.bones[*index].parent {
+                Some(parent) => matrices[parent] * local,
+                None => self.root_transform * local,
+            };
+        }
+    }
+
+    /// Compute the matrices transforming the bind pose vertices to `pose`
+    pub fn skinning_matrices(&self, pose: &[BoneTransform], matrices: &mut Vec<glm::Mat4>) {
+        self.model_matrices(pose, matrices);
+        for (matrix, bone) in matrices.iter_mut().zip(&self.bones) {
+            *matrix *= bone.inverse_bind_matrix;
+        }
+    }
+}
+
+impl Asset for Skeleton {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
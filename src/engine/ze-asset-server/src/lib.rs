@@ -12,12 +12,15 @@ use ze_asset_system::{AssetLoadResult, AssetProvider, LoadError, ASSET_METADATA_
 use ze_core::{ze_error, ze_info};
 use ze_filesystem::path::Path;
 use ze_filesystem::{DirEntryType, FileSystem, IterDirFlagBits, IterDirFlags};
+use ze_jobsystem::progress::Progress;
+use ze_jobsystem::JobSystem;
 
 #[derive(Debug)]
 pub enum Error {
     CannotCreateOrOpenSourceDb,
     CannotCreateOrOpenAssetDb,
     UnknownAsset,
+    CannotRemoveSourceAsset,
 }
 
 impl Display for Error {
@@ -37,6 +40,7 @@ struct SourceAssetDbEntry {
 /// Asset source file information are stored inside the source database (source.db)
 pub struct AssetServer {
     filesystem: Arc<FileSystem>,
+    jobsystem: Arc<JobSystem>,
     importers: RwLock<HashMap<String, Arc<dyn BoxedAssetImporter>>>,
     asset_dirs: Mutex<Vec<Path>>,
     source_db: sled::Db,
@@ -46,6 +50,7 @@ pub struct AssetServer {
 impl AssetServer {
     pub fn new(
         filesystem: Arc<FileSystem>,
+        jobsystem: Arc<JobSystem>,
         asset_dirs: Vec<Path>,
         cache_path: Path,
     ) -> Result<Self, Error> {
@@ -83,6 +88,7 @@ impl AssetServer {
 
         let server = Self {
             filesystem,
+            jobsystem,
             importers: Default::default(),
             asset_dirs: Default::default(),
             source_db,
@@ -100,24 +106,55 @@ impl AssetServer {
         }
     }
 
-    pub fn scan_asset_directories(&self) {
-        let asset_dirs = self.asset_dirs.lock();
-        for path in asset_dirs.iter() {
-            self.filesystem
-                .iter_dir(
-                    path,
-                    IterDirFlags::from_flag(IterDirFlagBits::Recursive),
-                    |entry| {
-                        if entry.ty == DirEntryType::File {
-                            self.process_potential_source_asset(&entry.path);
-                        }
-                    },
-                )
-                .unwrap_or_else(|_| ze_error!("Failed to scan asset directory {}", path));
+    /// Scan the asset directories and import new or modified source assets in a job
+    ///
+    /// Returns the progress of the import, which can be cancelled between two assets
+    pub fn scan_asset_directories(self: &Arc<Self>) -> Arc<Progress> {
+        let mut files = vec![];
+        {
+            let asset_dirs = self.asset_dirs.lock();
+            for path in asset_dirs.iter() {
+                self.filesystem
+                    .iter_dir(
+                        path,
+                        IterDirFlags::from_flag(IterDirFlagBits::Recursive),
+                        |entry| {
+                            if entry.ty == DirEntryType::File {
+                                files.push(entry.path.clone());
+                            }
+                        },
+                    )
+                    .unwrap_or_else(|_| ze_error!("Failed to scan asset directory {}", path));
+            }
         }
+
+        let progress = self
+            .jobsystem
+            .begin_progress("Importing assets", files.len(), true);
+
+        let server = self.clone();
+        let job_progress = progress.clone();
+        self.jobsystem
+            .spawn(move |_, _| {
+                for path in files {
+                    if job_progress.is_cancelled() {
+                        ze_info!("Asset import cancelled");
+                        break;
+                    }
+
+                    job_progress.set_current_item(path.as_str());
+                    server.process_potential_source_asset(&path);
+                    job_progress.advance();
+                }
+
+                job_progress.finish();
+            })
+            .schedule();
+
+        progress
     }
 
-    pub fn add_importer<T>(&self, extensions: &[&str], importer: T)
+    pub fn add_importer<T>(self: &Arc<Self>, extensions: &[&str], importer: T)
     where
         T: BoxedAssetImporter + 'static,
     {
@@ -134,13 +171,13 @@ impl AssetServer {
 
     pub fn asset_data(&self, uuid: Uuid) -> Result<(Uuid, Vec<u8>), Error> {
         let data = match self.asset_db.get(uuid) {
-            Ok(data) => data.unwrap(),
-            Err(_) => return Err(Error::UnknownAsset),
+            Ok(Some(data)) => data,
+            _ => return Err(Error::UnknownAsset),
         };
 
         let type_uuid_bytes = match self.asset_db.get(format!("{}_type_uuid", uuid.as_u128())) {
-            Ok(data) => data.unwrap(),
-            Err(_) => return Err(Error::UnknownAsset),
+            Ok(Some(data)) => data,
+            _ => return Err(Error::UnknownAsset),
         };
 
         let type_uuid = Uuid::from_slice(&type_uuid_bytes).unwrap();
@@ -172,14 +209,7 @@ impl AssetServer {
                     .expect("source database maybe corrupted!")
                     .0;
 
-            let metadata_path = {
-                let mut path = path.clone();
-                let asset_path =
-                    path.path().to_string().rsplit('.').collect::<Vec<&str>>()[1].to_string();
-                let path_str = format!("{}.{}", asset_path, ASSET_METADATA_EXTENSION);
-                path.set_path(&path_str);
-                path
-            };
+            let metadata_path = metadata_path(path);
 
             if entry.source_hash_sha256.as_slice() != current_file_hash.as_slice()
                 || !self.filesystem.exists(&metadata_path)
@@ -222,14 +252,7 @@ impl AssetServer {
         if let Some(importer) = self.importer_for_extension(&extension) {
             ze_info!("Importing {}", path.to_string());
 
-            let metadata_path = {
-                let mut path = path.clone();
-                let asset_path =
-                    path.path().to_string().rsplit('.').collect::<Vec<&str>>()[1].to_string();
-                let path_str = format!("{}.{}", asset_path, ASSET_METADATA_EXTENSION);
-                path.set_path(&path_str);
-                path
-            };
+            let metadata_path = metadata_path(path);
 
             let mut file = self.filesystem.read(path).unwrap();
             match importer.import(&self.filesystem, path, &mut file, &metadata_path) {
@@ -261,22 +284,15 @@ impl AssetServer {
 
     pub fn asset_type_uuid(&self, uuid: Uuid) -> Option<Uuid> {
         let type_uuid_bytes = match self.asset_db.get(format!("{}_type_uuid", uuid.as_u128())) {
-            Ok(data) => data.unwrap(),
-            Err(_) => return None,
+            Ok(Some(data)) => data,
+            _ => return None,
         };
 
         Some(Uuid::from_slice(&type_uuid_bytes).unwrap())
     }
 
     pub fn asset_uuid_from_path(&self, path: &Path) -> Option<Uuid> {
-        let metadata_path = {
-            let mut path = path.clone();
-            let asset_path =
-                path.path().to_string().rsplit('.').collect::<Vec<&str>>()[1].to_string();
-            let path_str = format!("{}.{}", asset_path, ASSET_METADATA_EXTENSION);
-            path.set_path(&path_str);
-            path
-        };
+        let metadata_path = metadata_path(path);
 
         #[derive(Deserialize)]
         struct Metadata {
@@ -297,6 +313,40 @@ impl AssetServer {
         let importers = self.importers.read();
         importers.get(extension).cloned()
     }
+
+    /// Remove a source asset from the disk alongside its metadata and its imported assets
+    pub fn remove_source_asset(&self, path: &Path) -> Result<(), Error> {
+        let uuid = self.asset_uuid_from_path(path);
+        if self.filesystem.remove(path).is_err() {
+            return Err(Error::CannotRemoveSourceAsset);
+        }
+
+        let metadata_path = metadata_path(path);
+        if self.filesystem.exists(&metadata_path) && self.filesystem.remove(&metadata_path).is_err()
+        {
+            ze_error!("Failed to remove metadata file {}", metadata_path);
+        }
+
+        let _ = self.source_db.remove(path.as_str());
+        if let Some(uuid) = uuid {
+            let _ = self.asset_db.remove(uuid);
+            let _ = self
+                .asset_db
+                .remove(format!("{}_type_uuid", uuid.as_u128()));
+        }
+
+        ze_info!("Removed {}", path);
+        Ok(())
+    }
+}
+
+/// Get the metadata file path of a source asset
+fn metadata_path(path: &Path) -> Path {
+    let mut path = path.clone();
+    let asset_path = path.path().to_string().rsplit('.').collect::<Vec<&str>>()[1].to_string();
+    let path_str = format!("{}.{}", asset_path, ASSET_METADATA_EXTENSION);
+    path.set_path(&path_str);
+    path
 }
 
 pub struct AssetServerProvider {
@@ -320,3 +370,122 @@ impl AssetProvider for AssetServerProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::AssetServer;
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use ze_asset_system::importer::{
+        AssetImporter, AssetImporterResult, Error, ImportedAsset, SourceAssetMetadata,
+    };
+    use ze_filesystem::mount_points::StdMountPoint;
+    use ze_filesystem::path::Path;
+    use ze_filesystem::FileSystem;
+    use ze_jobsystem::JobSystem;
+
+    const TEXT_TYPE_UUID: Uuid = Uuid::from_u128(0x7e47);
+
+    /// Import text files as their content, counting imports
+    struct TextImporter {
+        imports: Arc<AtomicUsize>,
+    }
+
+    impl AssetImporter for TextImporter {
+        type State = ();
+        type Parameters = ();
+
+        fn import(
+            &self,
+            _: &Path,
+            src: &mut dyn Read,
+            metadata: Option<SourceAssetMetadata<(), ()>>,
+        ) -> Result<AssetImporterResult<(), ()>, Error> {
+            let count = self.imports.fetch_add(1, Ordering::SeqCst) + 1;
+            let metadata = metadata.unwrap_or_else(|| {
+                SourceAssetMetadata::new(Uuid::from_u128(count as u128), (), ())
+            });
+
+            let mut data = vec![];
+            src.read_to_end(&mut data)?;
+            Ok((
+                vec![ImportedAsset::new(*metadata.uuid(), TEXT_TYPE_UUID, data)],
+                metadata,
+            ))
+        }
+    }
+
+    fn write(filesystem: &FileSystem, path: &Path, text: &str) {
+        filesystem
+            .write(path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn import_reimport_and_remove() {
+        let root = std::env::temp_dir().join("ze-asset-server-tests");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("assets")).unwrap();
+        std::fs::create_dir_all(root.join("cache")).unwrap();
+        let filesystem = FileSystem::new();
+        filesystem.mount(StdMountPoint::new("test", &root));
+        let path = Path::parse("/test/assets/hello.txt").unwrap();
+        write(&filesystem, &path, "Hello");
+
+        // Without workers, jobs only run in wait_until_idle
+        let jobsystem = JobSystem::new(0);
+        let server = Arc::new(
+            AssetServer::new(
+                filesystem.clone(),
+                jobsystem.clone(),
+                vec![Path::parse("/test/assets").unwrap()],
+                Path::parse("/test/cache").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        let imports = Arc::new(AtomicUsize::new(0));
+        server.add_importer(
+            &["txt"],
+            TextImporter {
+                imports: imports.clone(),
+            },
+        );
+        jobsystem.wait_until_idle();
+        assert_eq!(imports.load(Ordering::SeqCst), 1);
+
+        let uuid = server.asset_uuid_from_path(&path).unwrap();
+        assert_eq!(server.asset_type_uuid(uuid), Some(TEXT_TYPE_UUID));
+        assert_eq!(
+            server.asset_data(uuid).unwrap(),
+            (TEXT_TYPE_UUID, b"Hello".to_vec())
+        );
+
+        // Unchanged sources are skipped
+        server.scan_asset_directories();
+        jobsystem.wait_until_idle();
+        assert_eq!(imports.load(Ordering::SeqCst), 1);
+
+        // Modified sources are reimported, keeping the UUID from their metadata
+        write(&filesystem, &path, "Hello world");
+        let progress = server.scan_asset_directories();
+        jobsystem.wait_until_idle();
+        assert!(progress.is_finished());
+        assert_eq!(imports.load(Ordering::SeqCst), 2);
+        assert_eq!(server.asset_uuid_from_path(&path), Some(uuid));
+        assert_eq!(
+            server.asset_data(uuid).unwrap(),
+            (TEXT_TYPE_UUID, b"Hello world".to_vec())
+        );
+
+        server.remove_source_asset(&path).unwrap();
+        assert!(!filesystem.exists(&path));
+        assert!(server.asset_uuid_from_path(&path).is_none());
+        assert!(server.asset_data(uuid).is_err());
+        assert_eq!(server.asset_type_uuid(uuid), None);
+    }
+}
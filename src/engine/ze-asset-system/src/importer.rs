@@ -144,6 +144,14 @@ impl<S: Serialize + DeserializeOwned, P: Serialize + DeserializeOwned> SourceAss
         &self.uuid
     }
 
+    pub fn state(&self) -> &S {
+        &self.importer_state
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.importer_state
+    }
+
     pub fn parameters(&self) -> &P {
         &self.importer_parameters
     }
@@ -12,6 +12,18 @@ impl<T> Color4<T> {
     }
 }
 
+impl<T> From<[T; 4]> for Color4<T> {
+    fn from([r, g, b, a]: [T; 4]) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl<T> From<Color4<T>> for [T; 4] {
+    fn from(color: Color4<T>) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
 pub type Color4f32 = Color4<f32>;
 
 pub type Color4u8 = Color4<u8>;
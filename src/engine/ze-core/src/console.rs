@@ -0,0 +1,297 @@
+//! Console variables (CVars) and commands
+//! Both can be registered from any subsystem and are executed from text (e.g. the editor console)
+
+use crate::ze_info;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CVarValue {
+    Bool(bool),
+    I32(i32),
+    F32(f32),
+    String(String),
+}
+
+impl CVarValue {
+    /// Parse `str` as a value of the same type as `self`
+    fn parse_same_type(&self, str: &str) -> Option<CVarValue> {
+        match self {
+            CVarValue::Bool(_) => match str {
+                "1" | "true" | "on" => Some(CVarValue::Bool(true)),
+                "0" | "false" | "off" => Some(CVarValue::Bool(false)),
+                _ => None,
+            },
+            CVarValue::I32(_) => str.parse().ok().map(CVarValue::I32),
+            CVarValue::F32(_) => str.parse().ok().map(CVarValue::F32),
+            CVarValue::String(_) => Some(CVarValue::String(str.to_string())),
+        }
+    }
+
+    fn has_same_type(&self, other: &CVarValue) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl Display for CVarValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CVarValue::Bool(value) => write!(f, "{}", value),
+            CVarValue::I32(value) => write!(f, "{}", value),
+            CVarValue::F32(value) => write!(f, "{}", value),
+            CVarValue::String(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+pub struct CVar {
+    name: String,
+    description: String,
+    value: RwLock<CVarValue>,
+}
+
+impl CVar {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn value(&self) -> CVarValue {
+        self.value.read().clone()
+    }
+
+    /// Set the CVar value, `value` must have the same type as the current value
+    pub fn set(&self, value: CVarValue) -> Result<(), Error> {
+        let mut current = self.value.write();
+        if !current.has_same_type(&value) {
+            return Err(Error::TypeMismatch);
+        }
+
+        *current = value;
+        Ok(())
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self.value.read() {
+            CVarValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match *self.value.read() {
+            CVarValue::I32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self.value.read() {
+            CVarValue::F32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<String> {
+        match &*self.value.read() {
+            CVarValue::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+type CommandFn = dyn Fn(&[&str]) -> Result<(), String> + Send + Sync;
+
+pub struct Command {
+    name: String,
+    description: String,
+    func: Box<CommandFn>,
+}
+
+impl Command {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownCommand,
+    InvalidValue,
+    TypeMismatch,
+    CommandFailed(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+static CVARS: Lazy<RwLock<BTreeMap<String, Arc<CVar>>>> = Lazy::new(RwLock::default);
+static COMMANDS: Lazy<RwLock<BTreeMap<String, Arc<Command>>>> = Lazy::new(RwLock::default);
+
+/// Register a new CVar, if a CVar with the same name already exists it is returned instead
+pub fn register_cvar(name: &str, description: &str, default_value: CVarValue) -> Arc<CVar> {
+    CVARS
+        .write()
+        .entry(name.to_string())
+        .or_insert_with(|| {
+            Arc::new(CVar {
+                name: name.to_string(),
+                description: description.to_string(),
+                value: RwLock::new(default_value),
+            })
+        })
+        .clone()
+}
+
+pub fn cvar(name: &str) -> Option<Arc<CVar>> {
+    CVARS.read().get(name).cloned()
+}
+
+pub fn cvars() -> Vec<Arc<CVar>> {
+    CVARS.read().values().cloned().collect()
+}
+
+/// Register a command, replacing any command with the same name
+/// `func` receives the command arguments
+pub fn register_command<F>(name: &str, description: &str, func: F)
+where
+    F: Fn(&[&str]) -> Result<(), String> + Send + Sync + 'static,
+{
+    COMMANDS.write().insert(
+        name.to_string(),
+        Arc::new(Command {
+            name: name.to_string(),
+            description: description.to_string(),
+            func: Box::new(func),
+        }),
+    );
+}
+
+pub fn unregister_command(name: &str) {
+    COMMANDS.write().remove(name);
+}
+
+pub fn commands() -> Vec<Arc<Command>> {
+    COMMANDS.read().values().cloned().collect()
+}
+
+/// Execute a line of text
+/// - `command args...` calls the command
+/// - `cvar` prints the CVar value
+/// - `cvar value` sets the CVar value
+pub fn execute(line: &str) -> Result<(), Error> {
+    let mut tokens = line.split_whitespace();
+    let name = match tokens.next() {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+    let args = tokens.collect::<Vec<_>>();
+
+    // Clone the command so it can register other commands
+    let command = COMMANDS.read().get(name).cloned();
+    if let Some(command) = command {
+        return (command.func)(&args).map_err(Error::CommandFailed);
+    }
+
+    let cvar = cvar(name).ok_or(Error::UnknownCommand)?;
+    if args.is_empty() {
+        ze_info!("{} = {}", cvar.name(), cvar.value());
+    } else {
+        let value = cvar
+            .value()
+            .parse_same_type(&args.join(" "))
+            .ok_or(Error::InvalidValue)?;
+        cvar.set(value)?;
+        ze_info!("{} = {}", cvar.name(), cvar.value());
+    }
+
+    Ok(())
+}
+
+/// Returns the sorted names of commands and CVars starting with `prefix`
+pub fn complete(prefix: &str) -> Vec<String> {
+    let mut candidates = COMMANDS
+        .read()
+        .keys()
+        .chain(CVARS.read().keys())
+        .filter(|name| name.starts_with(prefix))
+        .cloned()
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::console::{
+        complete, cvar, execute, register_command, register_cvar, CVarValue, Error,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn set_cvar() {
+        let cvar = register_cvar("test.set_cvar", "", CVarValue::I32(4));
+        execute("test.set_cvar 12").unwrap();
+        assert_eq!(cvar.as_i32(), Some(12));
+        assert!(matches!(
+            execute("test.set_cvar abc"),
+            Err(Error::InvalidValue)
+        ));
+        assert!(matches!(
+            cvar.set(CVarValue::Bool(true)),
+            Err(Error::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn register_existing_cvar() {
+        let a = register_cvar("test.existing", "", CVarValue::Bool(false));
+        let b = register_cvar("test.existing", "", CVarValue::Bool(true));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cvar("test.existing").unwrap().as_bool(), Some(false));
+    }
+
+    #[test]
+    fn execute_command() {
+        let count = Arc::new(AtomicUsize::new(0));
+        {
+            let count = count.clone();
+            register_command("test.command", "", move |args| {
+                count.fetch_add(args.len(), Ordering::SeqCst);
+                Ok(())
+            });
+        }
+
+        execute("test.command a b c").unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        assert!(matches!(
+            execute("test.unknown"),
+            Err(Error::UnknownCommand)
+        ));
+    }
+
+    #[test]
+    fn complete_names() {
+        register_cvar("test.complete.a", "", CVarValue::F32(0.0));
+        register_command("test.complete.b", "", |_| Ok(()));
+        assert_eq!(
+            complete("test.complete."),
+            vec!["test.complete.a", "test.complete.b"]
+        );
+    }
+}
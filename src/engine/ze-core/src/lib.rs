@@ -1,4 +1,5 @@
 ﻿pub mod color;
+pub mod console;
 pub mod logger;
 pub mod maths;
 pub mod pool;
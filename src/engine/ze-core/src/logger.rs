@@ -2,14 +2,17 @@
 use chrono::Local;
 use once_cell::sync::Lazy;
 use parking_lot::{Mutex, RwLock};
+use std::collections::VecDeque;
 use std::fmt::Arguments;
 use std::fs::File;
 use std::io::Write;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 use std::{fmt, thread};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Severity {
     Verbose,
     Info,
@@ -30,6 +33,13 @@ impl fmt::Display for Severity {
     }
 }
 
+/// Location in the source code where a message was logged
+#[derive(Copy, Clone)]
+pub struct SourceLocation {
+    pub file: &'static str,
+    pub line: u32,
+}
+
 #[derive(Clone)]
 pub struct Message {
     pub severity: Severity,
@@ -37,6 +47,7 @@ pub struct Message {
     pub message: String,
     pub time: chrono::DateTime<Local>,
     pub thread: thread::ThreadId,
+    pub location: Option<SourceLocation>,
 }
 
 /// Implement a "sink". This receives log messages from the global logger and process them.
@@ -66,7 +77,12 @@ impl SinkEntry {
 static SINKS: Lazy<RwLock<Vec<SinkEntry>>> = Lazy::new(RwLock::default);
 
 #[doc(hidden)]
-pub fn internal_log(severity: Severity, crate_name: &str, args: Arguments) {
+pub fn internal_log(
+    severity: Severity,
+    crate_name: &str,
+    location: Option<SourceLocation>,
+    args: Arguments,
+) {
     let str = args.to_string();
     let message = Message {
         severity,
@@ -74,6 +90,7 @@ pub fn internal_log(severity: Severity, crate_name: &str, args: Arguments) {
         message: str,
         time: Local::now(),
         thread: thread::current().id(),
+        location,
     };
 
     for sink in SINKS.read().iter() {
@@ -85,7 +102,7 @@ pub fn internal_log(severity: Severity, crate_name: &str, args: Arguments) {
     }
 }
 
-/** Sink API */
+/* Sink API */
 
 pub fn register_sink(sink: Arc<dyn Sink>) {
     SINKS.write().push(SinkEntry::Arc(sink));
@@ -95,44 +112,69 @@ pub fn register_sink_weak<T: Sink + 'static>(sink: Weak<T>) {
     SINKS.write().push(SinkEntry::Weak(sink));
 }
 
-/** Default logging macros */
+/* Default logging macros */
 #[macro_export]
 macro_rules! ze_verbose {
     ($($arg:tt)*) => ({
-        $crate::logger::internal_log($crate::logger::Severity::Verbose, env!("CARGO_PKG_NAME"), format_args!($($arg)*));
+        $crate::logger::internal_log(
+            $crate::logger::Severity::Verbose,
+            env!("CARGO_PKG_NAME"),
+            Some($crate::logger::SourceLocation { file: file!(), line: line!() }),
+            format_args!($($arg)*),
+        );
     })
 }
 
 #[macro_export]
 macro_rules! ze_info {
     ($($arg:tt)*) => ({
-        $crate::logger::internal_log($crate::logger::Severity::Info, env!("CARGO_PKG_NAME"), format_args!($($arg)*));
+        $crate::logger::internal_log(
+            $crate::logger::Severity::Info,
+            env!("CARGO_PKG_NAME"),
+            Some($crate::logger::SourceLocation { file: file!(), line: line!() }),
+            format_args!($($arg)*),
+        );
     })
 }
 
 #[macro_export]
 macro_rules! ze_warn {
     ($($arg:tt)*) => ({
-        $crate::logger::internal_log($crate::logger::Severity::Warn, env!("CARGO_PKG_NAME"), format_args!($($arg)*));
+        $crate::logger::internal_log(
+            $crate::logger::Severity::Warn,
+            env!("CARGO_PKG_NAME"),
+            Some($crate::logger::SourceLocation { file: file!(), line: line!() }),
+            format_args!($($arg)*),
+        );
     })
 }
 
 #[macro_export]
 macro_rules! ze_error {
     ($($arg:tt)*) => ({
-        $crate::logger::internal_log($crate::logger::Severity::Error, env!("CARGO_PKG_NAME"), format_args!($($arg)*));
+        $crate::logger::internal_log(
+            $crate::logger::Severity::Error,
+            env!("CARGO_PKG_NAME"),
+            Some($crate::logger::SourceLocation { file: file!(), line: line!() }),
+            format_args!($($arg)*),
+        );
     })
 }
 
 #[macro_export]
 macro_rules! ze_fatal {
     ($($arg:tt)*) => ({
-        $crate::logger::internal_log($crate::logger::Severity::Fatal, env!("CARGO_PKG_NAME"), format_args!($($arg)*));
+        $crate::logger::internal_log(
+            $crate::logger::Severity::Fatal,
+            env!("CARGO_PKG_NAME"),
+            Some($crate::logger::SourceLocation { file: file!(), line: line!() }),
+            format_args!($($arg)*),
+        );
         unreachable!();
     })
 }
 
-/** Default sinks */
+/* Default sinks */
 pub struct StdoutSink;
 
 impl StdoutSink {
@@ -211,3 +253,68 @@ impl Sink for FileSink {
         .unwrap();
     }
 }
+
+/// Sink keeping the last messages in memory, used by UIs displaying the log
+pub struct RingBufferSink {
+    messages: Mutex<VecDeque<Message>>,
+    capacity: usize,
+
+    /// Number of messages received since the creation of the sink
+    received_count: AtomicUsize,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            messages: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            received_count: AtomicUsize::new(0),
+        })
+    }
+
+    /// Iterate over stored messages, from the oldest to the newest
+    /// `f` receives the index of the message since the creation of the sink
+    pub fn for_each<F: FnMut(usize, &Message)>(&self, mut f: F) {
+        let messages = self.messages.lock();
+        let first_index = self.received_count.load(Ordering::SeqCst) - messages.len();
+        for (i, message) in messages.iter().enumerate() {
+            f(first_index + i, message);
+        }
+    }
+
+    /// Same as [`RingBufferSink::for_each`] but only for messages with an index in `range`
+    /// Indices of messages no longer stored are skipped
+    pub fn for_each_in<F: FnMut(usize, &Message)>(&self, range: Range<usize>, mut f: F) {
+        let messages = self.messages.lock();
+        let first_index = self.received_count.load(Ordering::SeqCst) - messages.len();
+        let start = range.start.max(first_index);
+        let end = range.end.max(start);
+        for (i, message) in messages
+            .iter()
+            .enumerate()
+            .skip(start - first_index)
+            .take(end - start)
+        {
+            f(first_index + i, message);
+        }
+    }
+
+    pub fn received_count(&self) -> usize {
+        self.received_count.load(Ordering::SeqCst)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Sink for RingBufferSink {
+    fn log(&self, message: &Message) {
+        let mut messages = self.messages.lock();
+        if messages.len() == self.capacity {
+            messages.pop_front();
+        }
+        messages.push_back(message.clone());
+        self.received_count.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -24,7 +24,7 @@ impl<T, const PAGE_SIZE_IN_ELEMENTS: usize, const DISABLE_SLOT_AFTER_OVERFLOW: b
     Page<T, PAGE_SIZE_IN_ELEMENTS, DISABLE_SLOT_AFTER_OVERFLOW>
 {
     fn new(index: usize) -> Self {
-        let memory: [Slot<T>; PAGE_SIZE_IN_ELEMENTS] = array_init::array_init(|_| Slot::Free(0));
+        let memory: [Slot<T>; PAGE_SIZE_IN_ELEMENTS] = std::array::from_fn(|_| Slot::Free(0));
         Self { index, memory }
     }
 
@@ -155,13 +155,13 @@ impl<T, const PAGE_SIZE_IN_ELEMENTS: usize, const DISABLE_SLOT_AFTER_OVERFLOW: b
         self.is_slot_alive(handle.index)
     }
 
-    pub fn iter(&self) -> PoolIterator<T, PAGE_SIZE_IN_ELEMENTS, DISABLE_SLOT_AFTER_OVERFLOW> {
+    pub fn iter(&self) -> PoolIterator<'_, T, PAGE_SIZE_IN_ELEMENTS, DISABLE_SLOT_AFTER_OVERFLOW> {
         PoolIterator::new(self)
     }
 
     pub fn iter_mut(
         &mut self,
-    ) -> PoolIteratorMut<T, PAGE_SIZE_IN_ELEMENTS, DISABLE_SLOT_AFTER_OVERFLOW> {
+    ) -> PoolIteratorMut<'_, T, PAGE_SIZE_IN_ELEMENTS, DISABLE_SLOT_AFTER_OVERFLOW> {
         PoolIteratorMut::new(self)
     }
 
@@ -329,11 +329,7 @@ impl<T> Default for Handle<T> {
 
 impl<T> Clone for Handle<T> {
     fn clone(&self) -> Self {
-        Self {
-            generation: self.generation,
-            index: self.index,
-            _phantom: self._phantom,
-        }
+        *self
     }
 }
 
@@ -1,13 +1,13 @@
 ﻿use crate::sparse_vec::SparseVec;
 use parking_lot::Mutex;
-
-/// An object storing functions to be called when signaled
+use std::fmt::{Debug, Formatter};
 
 type Slot<Args> = Box<dyn FnMut(Args)>;
 type SyncSlot<Args> = Box<dyn FnMut(Args) + Send>;
 
 pub struct Handle(usize);
 
+/// An object storing functions to be called when signaled
 pub struct Signal<Args> {
     slots: SparseVec<Slot<Args>>,
 }
@@ -56,7 +56,7 @@ impl<Args> SyncSignal<Args>
 where
     Args: Clone + 'static,
 {
-    pub fn connect<F>(&mut self, func: F) -> Handle
+    pub fn connect<F>(&self, func: F) -> Handle
     where
         F: FnMut(Args) + Send + Sync + 'static,
     {
@@ -69,7 +69,7 @@ where
     /// # Panics
     ///
     /// Panics if `handle` is not a valid handle
-    pub fn disconnect(&mut self, handle: Handle) {
+    pub fn disconnect(&self, handle: Handle) {
         let mut slots = self.slots.lock();
         let _ = slots.remove(handle.0);
     }
@@ -90,6 +90,12 @@ impl<Args> Default for SyncSignal<Args> {
     }
 }
 
+impl<Args> Debug for SyncSignal<Args> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncSignal").finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::signals::SyncSignal;
@@ -99,7 +105,7 @@ mod tests {
     #[test]
     fn connect_emit() {
         let received = Arc::new(AtomicBool::new(false));
-        let mut signal: SyncSignal<()> = SyncSignal::default();
+        let signal: SyncSignal<()> = SyncSignal::default();
         {
             let received = received.clone();
             signal.connect(move |_| received.store(true, Ordering::SeqCst));
@@ -113,7 +119,7 @@ mod tests {
     #[test]
     fn connect_disconnect_and_emit() {
         let received = Arc::new(AtomicBool::new(false));
-        let mut signal: SyncSignal<()> = SyncSignal::default();
+        let signal: SyncSignal<()> = SyncSignal::default();
         {
             let received = received.clone();
             let handle = signal.connect(move |_| received.store(true, Ordering::SeqCst));
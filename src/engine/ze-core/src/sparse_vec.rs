@@ -78,11 +78,11 @@ impl<T> SparseVec<T> {
         self.len == 0
     }
 
-    pub fn iter(&self) -> SparseArrayIterator<T> {
+    pub fn iter(&self) -> SparseArrayIterator<'_, T> {
         SparseArrayIterator::new(self)
     }
 
-    pub fn iter_mut(&mut self) -> SparseArrayIteratorMut<T> {
+    pub fn iter_mut(&mut self) -> SparseArrayIteratorMut<'_, T> {
         SparseArrayIteratorMut::new(self)
     }
 
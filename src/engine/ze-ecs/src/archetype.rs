@@ -1,4 +1,4 @@
-﻿use crate::component::{ComponentId, ComponentInfo, ComponentRegistry};
+﻿use crate::component::{ComponentId, ComponentInfo, ComponentRegistry, ComponentTicks};
 use crate::entity::Entity;
 use crate::sparse_set::{SparseSet, TypeErasedSparseSet};
 use fnv::FnvHashMap;
@@ -9,15 +9,34 @@ use std::ptr::NonNull;
 /// Store a specific type of component inside an archetype
 pub(crate) struct Column {
     components: TypeErasedSparseSet,
+
+    /// Change ticks of each component, indexed by entity id
+    ticks: Vec<ComponentTicks>,
 }
 
 impl Column {
     pub fn new(component_info: &ComponentInfo) -> Self {
         Self {
             components: TypeErasedSparseSet::new(component_info.type_info),
+            ticks: vec![],
         }
     }
 
+    pub fn ticks(&self, index: usize) -> ComponentTicks {
+        self.ticks[index]
+    }
+
+    pub fn set_ticks(&mut self, index: usize, ticks: ComponentTicks) {
+        if index >= self.ticks.len() {
+            self.ticks.resize(index + 1, ComponentTicks::default());
+        }
+        self.ticks[index] = ticks;
+    }
+
+    pub fn set_changed(&mut self, index: usize, tick: u64) {
+        self.ticks[index].changed = tick;
+    }
+
     pub fn components(&self) -> &TypeErasedSparseSet {
         &self.components
     }
@@ -81,23 +100,24 @@ impl Archetype {
     pub unsafe fn insert_row(
         &mut self,
         entity: Entity,
-        values: Vec<(ComponentId, NonNull<u8>)>,
+        values: Vec<(ComponentId, NonNull<u8>, ComponentTicks)>,
     ) -> usize {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
         debug_assert_eq!(
             self.components,
-            values.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            values.iter().map(|(id, _, _)| *id).collect::<Vec<_>>(),
             "Component ids must match the archetype"
         );
 
-        for (id, value) in values {
+        for (id, value, ticks) in values {
             #[cfg(feature = "profiling")]
             puffin::profile_scope!("Add component", id.to_string());
             let column = self.columns.get_mut(id).unwrap_unchecked().get_mut();
             column
                 .components
                 .insert_unchecked(entity.id() as usize, value);
+            column.set_ticks(entity.id() as usize, ticks);
         }
 
         self.entities.push(entity);
@@ -106,15 +126,17 @@ impl Archetype {
 
     /// Remove the entity's row
     /// Takes a `should_drop` function to determines if a specific component should be dropped and not forgotten
+    /// Returns the entity that was moved to `entity_index` to fill the removed row, if any
     pub fn remove_row<F: Fn(&ComponentId) -> bool>(
         &mut self,
         entity_index: usize,
         entity: Entity,
         should_drop: F,
-    ) {
+    ) -> Option<Entity> {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
         self.entities.swap_remove(entity_index);
+        let moved_entity = self.entities.get(entity_index).copied();
 
         for id in &self.components {
             if should_drop(id) {
@@ -133,6 +155,8 @@ impl Archetype {
                     .remove_forget(entity.id() as usize)
             }
         }
+
+        moved_entity
     }
 
     pub fn id(&self) -> ArchetypeId {
@@ -1,6 +1,8 @@
 ﻿use crate::erased_vec::TypeInfo;
 use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use ze_reflection::{Reflectable, TypeDescription};
 
 /// Data storage for a entity
 pub trait Component: Send + Sync + 'static {
@@ -13,16 +15,57 @@ pub static COMPONENT_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 pub type ComponentId = usize;
 
+/// World change ticks at which a component was added and last mutably accessed
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct ComponentTicks {
+    pub added: u64,
+    pub changed: u64,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: u64) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// Returns true if the component was added after `last_change_tick`
+    pub fn is_added(&self, last_change_tick: u64) -> bool {
+        self.added > last_change_tick
+    }
+
+    /// Returns true if the component was added or mutably accessed after `last_change_tick`
+    pub fn is_changed(&self, last_change_tick: u64) -> bool {
+        self.changed > last_change_tick
+    }
+}
+
 pub struct ComponentInfo {
     pub(crate) type_info: TypeInfo,
+
+    /// Reflection data of the component, set by [`crate::world::World::register_reflectable`]
+    pub(crate) type_desc: Option<Arc<TypeDescription>>,
 }
 
 impl ComponentInfo {
     pub fn new<T: Component>() -> Self {
         Self {
             type_info: TypeInfo::new::<T>(),
+            type_desc: None,
+        }
+    }
+
+    pub fn new_reflectable<T: Component + Reflectable>() -> Self {
+        Self {
+            type_info: TypeInfo::new::<T>(),
+            type_desc: Some(T::type_desc()),
         }
     }
+
+    pub fn type_desc(&self) -> Option<&Arc<TypeDescription>> {
+        self.type_desc.as_ref()
+    }
 }
 
 #[derive(Default)]
@@ -40,6 +83,10 @@ impl ComponentRegistry {
         self.components.get(id)
     }
 
+    pub fn get_mut(&mut self, id: &ComponentId) -> Option<&mut ComponentInfo> {
+        self.components.get_mut(id)
+    }
+
     /// # Safety
     ///
     /// `id` must point to a valid component
@@ -19,6 +19,7 @@ impl Entity {
 #[derive(Default)]
 pub(crate) struct EntityData {
     generation: u32,
+    alive: bool,
     archetype: Option<(ArchetypeId, usize)>,
 }
 
@@ -34,12 +35,16 @@ impl EntityRegistry {
     pub fn alloc(&mut self) -> Entity {
         if let Some(id) = self.available_ids.pop() {
             self.entity_count += 1;
+            self.datas[id as usize].alive = true;
             Entity {
                 generation: self.datas[id as usize].generation,
                 id,
             }
         } else {
-            self.datas.push(EntityData::default());
+            self.datas.push(EntityData {
+                alive: true,
+                ..Default::default()
+            });
             let id = self.free_id;
             self.free_id += 1;
             self.entity_count += 1;
@@ -50,6 +55,7 @@ impl EntityRegistry {
     pub fn free(&mut self, entity: Entity) {
         let data = &mut self.datas[entity.id as usize];
         data.generation += 1;
+        data.alive = false;
         data.archetype = None;
         self.entity_count -= 1;
         self.available_ids.push(entity.id);
@@ -78,6 +84,17 @@ impl EntityRegistry {
         }
     }
 
+    /// Get the alive entity with the given id, if any
+    pub fn try_entity(&self, id: u32) -> Option<Entity> {
+        self.datas
+            .get(id as usize)
+            .filter(|data| data.alive)
+            .map(|data| Entity {
+                generation: data.generation,
+                id,
+            })
+    }
+
     pub fn count(&self) -> u32 {
         self.entity_count
     }
@@ -116,4 +133,15 @@ mod tests {
         assert_eq!(entity.id(), 0);
         assert!(registry.is_valid(entity));
     }
+
+    #[test]
+    fn try_entity() {
+        let mut registry = EntityRegistry::default();
+        let entity = registry.alloc();
+        assert!(registry.try_entity(entity.id()) == Some(entity));
+        assert!(registry.try_entity(1).is_none());
+
+        registry.free(entity);
+        assert!(registry.try_entity(entity.id()).is_none());
+    }
 }
@@ -3,7 +3,7 @@ use std::any::TypeId;
 use std::ptr;
 use std::ptr::NonNull;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, Debug)]
 pub(crate) struct TypeInfo {
     pub(crate) id: TypeId,
     pub(crate) layout: Layout,
@@ -20,6 +20,15 @@ impl TypeInfo {
     }
 }
 
+// Function pointers are not guaranteed to be unique, the type id identifies the type
+impl PartialEq for TypeInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for TypeInfo {}
+
 pub(crate) struct TypeErasedVec {
     type_info: TypeInfo,
     len: usize,
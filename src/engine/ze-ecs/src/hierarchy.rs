@@ -0,0 +1,153 @@
+use crate::entity::Entity;
+use crate::world::World;
+use ze_ecs_macros::Component;
+
+/// Display name of an entity
+#[derive(Component, Clone, Default)]
+pub struct Name(pub String);
+
+/// Parent of an entity, managed by `World::set_parent`
+#[derive(Component)]
+pub struct Parent(Entity);
+
+impl Parent {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Children of an entity, managed by `World::set_parent`
+#[derive(Component, Default)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Hierarchy
+impl World {
+    /// Attach `child` to `parent`, or detach it from its current parent if `parent` is `None`
+    pub fn set_parent(&mut self, child: Entity, parent: Option<Entity>) {
+        if let Some(parent) = parent {
+            assert!(
+                parent != child && !self.is_ancestor_of(child, parent),
+                "Cannot attach an entity to one of its descendants"
+            );
+        }
+
+        if let Some(old_parent) = self.parent(child) {
+            if let Some(children) = self.query::<&mut Children>().get(self, old_parent) {
+                children.0.retain(|entity| *entity != child);
+            }
+            self.remove::<Parent>(child);
+        }
+
+        if let Some(parent) = parent {
+            if self.query::<&Children>().get(self, parent).is_none() {
+                self.add(parent, Children::default());
+            }
+
+            self.query::<&mut Children>()
+                .get(self, parent)
+                .unwrap()
+                .0
+                .push(child);
+            self.add(child, Parent(parent));
+        }
+    }
+
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.query::<&Parent>()
+            .get(self, entity)
+            .map(|parent| parent.get())
+    }
+
+    pub fn children(&self, entity: Entity) -> Vec<Entity> {
+        self.query::<&Children>()
+            .get(self, entity)
+            .map_or(vec![], |children| children.0.clone())
+    }
+
+    /// Returns true if `ancestor` is a parent of `entity`, directly or not
+    pub fn is_ancestor_of(&self, ancestor: Entity, entity: Entity) -> bool {
+        let mut current = self.parent(entity);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.parent(parent);
+        }
+        false
+    }
+
+    /// Destroy an entity and all of its descendants
+    pub fn destroy_recursive(&mut self, entity: Entity) {
+        for child in self.children(entity) {
+            self.destroy_recursive(child);
+        }
+
+        if let Some(parent) = self.parent(entity) {
+            if let Some(children) = self.query::<&mut Children>().get(self, parent) {
+                children.0.retain(|child| *child != entity);
+            }
+        }
+
+        self.destroy(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::World;
+
+    #[test]
+    fn set_parent() {
+        let mut world = World::default();
+        let parent = world.spawn();
+        let child = world.spawn();
+        world.set_parent(child, Some(parent));
+        assert!(world.parent(child) == Some(parent));
+        assert!(world.children(parent) == vec![child]);
+        assert!(world.is_ancestor_of(parent, child));
+
+        world.set_parent(child, None);
+        assert!(world.parent(child).is_none());
+        assert!(world.children(parent).is_empty());
+    }
+
+    #[test]
+    fn reparent() {
+        let mut world = World::default();
+        let a = world.spawn();
+        let b = world.spawn();
+        let child = world.spawn();
+        world.set_parent(child, Some(a));
+        world.set_parent(child, Some(b));
+        assert!(world.parent(child) == Some(b));
+        assert!(world.children(a).is_empty());
+        assert!(world.children(b) == vec![child]);
+    }
+
+    #[test]
+    fn destroy_recursive() {
+        let mut world = World::default();
+        let root = world.spawn();
+        let parent = world.spawn();
+        let child = world.spawn();
+        world.set_parent(parent, Some(root));
+        world.set_parent(child, Some(parent));
+        world.destroy_recursive(parent);
+        assert!(world.children(root).is_empty());
+        assert_eq!(world.entity_count(), 1);
+    }
+}
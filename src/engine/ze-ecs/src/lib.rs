@@ -5,6 +5,7 @@ pub mod archetype;
 pub mod component;
 pub mod entity;
 mod erased_vec;
+pub mod hierarchy;
 mod sparse_set;
 pub mod system;
 pub mod world;
@@ -12,4 +13,5 @@ pub mod world;
 pub use once_cell::sync::Lazy;
 pub use ze_ecs_macros::*;
 
+extern crate self as ze_ecs;
 extern crate ze_ecs_macros;
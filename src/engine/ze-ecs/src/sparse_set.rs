@@ -88,20 +88,24 @@ impl TypeErasedSparseSet {
 
     pub fn remove(&mut self, index: usize) {
         // SAFETY: We don't use the contained value
-        assert!(unsafe { self.get_unchecked::<u8>(index).is_some() });
+        let dense_index = *self.sparse.get(index).expect("Invalid index");
         let last_sparse_index = self.dense_sparse_indices[self.dense.len() - 1];
-        self.sparse[last_sparse_index] = index;
-        unsafe { self.dense.swap_remove_unchecked(index) }
+        self.sparse[last_sparse_index] = dense_index;
+        // SAFETY: sparse always store valid dense indices
+        unsafe { self.dense.swap_remove_unchecked(dense_index) }
+        self.dense_sparse_indices.swap_remove(dense_index);
         self.sparse.remove(index);
     }
 
     /// Same as `remove` but will forget the value instead of dropping it
     pub fn remove_forget(&mut self, index: usize) {
         // SAFETY: We don't use the contained value
-        assert!(unsafe { self.get_unchecked::<u8>(index).is_some() });
+        let dense_index = *self.sparse.get(index).expect("Invalid index");
         let last_sparse_index = self.dense_sparse_indices[self.dense.len() - 1];
-        self.sparse[last_sparse_index] = index;
-        unsafe { self.dense.swap_remove_forget_unchecked(index) }
+        self.sparse[last_sparse_index] = dense_index;
+        // SAFETY: sparse always store valid dense indices
+        unsafe { self.dense.swap_remove_forget_unchecked(dense_index) }
+        self.dense_sparse_indices.swap_remove(dense_index);
         self.sparse.remove(index);
     }
 
@@ -215,5 +219,19 @@ mod tests {
             assert!(set.get::<u128>(2).is_none());
             assert_eq!(*set.get::<u128>(3).unwrap(), 60);
         }
+
+        #[test]
+        fn remove_non_sequential() {
+            let mut set = TypeErasedSparseSet::new(TypeInfo::new::<u128>());
+            insert_typed(&mut set, 5, 10u128);
+            insert_typed(&mut set, 2, 20u128);
+            insert_typed(&mut set, 8, 40u128);
+            set.remove(5);
+            set.remove(8);
+            assert_eq!(set.dense.len(), 1);
+            assert_eq!(*set.get::<u128>(2).unwrap(), 20);
+            assert!(set.get::<u128>(5).is_none());
+            assert!(set.get::<u128>(8).is_none());
+        }
     }
 }
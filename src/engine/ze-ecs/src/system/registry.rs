@@ -116,7 +116,7 @@ impl SystemRegistry {
             .cloned()
             .collect::<Vec<_>>();
 
-        let mut schedule = self.schedules.get_mut(&root_id).unwrap();
+        let schedule = self.schedules.get_mut(&root_id).unwrap();
         schedule.systems = toposorted_systems
             .iter()
             .map(|id| self.node_idx_to_node_data[id].as_system().clone())
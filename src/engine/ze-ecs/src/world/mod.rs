@@ -1,5 +1,5 @@
 ﻿use crate::archetype::{ArchetypeId, ArchetypeRegistry};
-use crate::component::{Component, ComponentId, ComponentInfo, ComponentRegistry};
+use crate::component::{Component, ComponentId, ComponentInfo, ComponentRegistry, ComponentTicks};
 use crate::entity::{Entity, EntityRegistry};
 use crate::erased_vec::TypeInfo;
 use crate::system::executor::{Executor, ParallelExecutor};
@@ -11,6 +11,9 @@ use crate::world::query::Query;
 use std::cell::Cell;
 use std::mem::forget;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use ze_reflection::{Reflectable, TypeDescription};
 
 /// Id of the empty archetype
 pub const EMPTY_ARCHETYPE_ID: ArchetypeId = 0;
@@ -20,6 +23,9 @@ pub struct World {
     pub(crate) archetype_registry: ArchetypeRegistry,
     component_registry: ComponentRegistry,
     system_registry: Cell<SystemRegistry>,
+
+    /// Incremented every time components are added or iterated, used for change detection
+    change_tick: AtomicU64,
 }
 
 impl Default for World {
@@ -29,6 +35,7 @@ impl Default for World {
             archetype_registry: Default::default(),
             component_registry: Default::default(),
             system_registry: Default::default(),
+            change_tick: AtomicU64::new(0),
         };
 
         // Register default empty archetype
@@ -59,9 +66,14 @@ impl World {
 
     pub fn destroy(&mut self, entity: Entity) {
         let (archetype_id, archetype_idx) = self.entity_registry.archetype_id(entity);
-        self.archetype_registry
-            .get_mut(archetype_id)
-            .remove_row(archetype_idx, entity, |_| true);
+        if let Some(moved_entity) =
+            self.archetype_registry
+                .get_mut(archetype_id)
+                .remove_row(archetype_idx, entity, |_| true)
+        {
+            self.entity_registry
+                .set_archetype_id(moved_entity, archetype_id, archetype_idx);
+        }
         self.entity_registry.free(entity);
     }
 
@@ -73,6 +85,7 @@ impl World {
                 T::component_id(),
                 ComponentInfo {
                     type_info: TypeInfo::new::<T>(),
+                    type_desc: None,
                 },
             );
         }
@@ -88,6 +101,77 @@ impl World {
         forget(component)
     }
 
+    /// Register `T` with its reflection data so it can be listed by
+    /// [`World::reflected_components`]
+    pub fn register_reflectable<T: Component + Reflectable>(&mut self) {
+        match self.component_registry.get_mut(&T::component_id()) {
+            Some(info) => info.type_desc = Some(T::type_desc()),
+            None => self
+                .component_registry
+                .register(T::component_id(), ComponentInfo::new_reflectable::<T>()),
+        }
+    }
+
+    /// Components of `entity` registered with [`World::register_reflectable`]
+    pub fn reflected_components(&self, entity: Entity) -> Vec<(ComponentId, Arc<TypeDescription>)> {
+        let archetype_id = self.entity_registry.archetype_id(entity).0;
+        self.archetype_registry
+            .get(archetype_id)
+            .components()
+            .iter()
+            .filter_map(|id| {
+                self.component_registry
+                    .get(id)
+                    .and_then(|info| info.type_desc().cloned())
+                    .map(|type_desc| (*id, type_desc))
+            })
+            .collect()
+    }
+
+    /// Pointer to the component `component` of `entity`
+    pub fn component_ptr(&self, entity: Entity, component: ComponentId) -> Option<NonNull<u8>> {
+        let archetype_id = self.entity_registry.archetype_id(entity).0;
+        let column = self
+            .archetype_registry
+            .get(archetype_id)
+            .columns()
+            .get(component)?;
+
+        // SAFETY: The pointer is returned as a `u8` and never read here
+        unsafe {
+            (*column.get())
+                .components()
+                .get_unchecked::<u8>(entity.id() as usize)
+                .map(|component| NonNull::new_unchecked(component as *const u8 as *mut u8))
+        }
+    }
+
+    /// Pointer to the component `component` of `entity`, the component is marked as changed
+    pub fn component_ptr_mut(
+        &mut self,
+        entity: Entity,
+        component: ComponentId,
+    ) -> Option<NonNull<u8>> {
+        let tick = self.increment_change_tick();
+        let archetype_id = self.entity_registry.archetype_id(entity).0;
+        let column = self
+            .archetype_registry
+            .get_mut(archetype_id)
+            .columns_mut()
+            .get_mut(component)?
+            .get_mut();
+
+        column.set_changed(entity.id() as usize, tick);
+
+        // SAFETY: The pointer is returned as a `u8` and never read here
+        unsafe {
+            column
+                .components_mut()
+                .get_unchecked_mut::<u8>(entity.id() as usize)
+                .map(|component| NonNull::new_unchecked(component as *mut u8))
+        }
+    }
+
     /// Remove component `T` from the entity
     pub fn remove<T: Component>(&mut self, entity: Entity) {
         self.remove_component(entity, T::component_id());
@@ -131,7 +215,7 @@ impl World {
             );
 
             let archetype = self.archetype_registry.get_mut(archetype_id);
-            let mut edge = archetype.edge(&component);
+            let edge = archetype.edge(&component);
             edge.add = Some(new_archetype_id.unwrap());
         }
 
@@ -170,7 +254,7 @@ impl World {
                 archetype.components().to_vec()
             };
 
-            components.remove(component);
+            components.retain(|id| *id != component);
 
             // SAFETY: Components stored inside an archetype are always valid
             new_archetype_id = unsafe {
@@ -182,7 +266,7 @@ impl World {
             };
 
             let archetype = self.archetype_registry.get_mut(archetype_id);
-            let mut edge = archetype.edge(&component);
+            let edge = archetype.edge(&component);
             edge.remove = Some(new_archetype_id.unwrap());
         }
 
@@ -218,6 +302,16 @@ impl World {
         self.system_registry.get_mut().add_system(system);
     }
 
+    /// Current change tick of the world
+    pub fn change_tick(&self) -> u64 {
+        self.change_tick.load(Ordering::SeqCst)
+    }
+
+    /// Increment the change tick, returning the new one
+    pub(crate) fn increment_change_tick(&self) -> u64 {
+        self.change_tick.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
     pub fn query<Q: Query>(&self) -> QueryState<Q> {
         QueryState::new(self)
     }
@@ -231,6 +325,11 @@ impl World {
         self.entity_registry.entity(id)
     }
 
+    /// Get an entity handle from its id, if the entity is alive
+    pub fn try_entity(&self, id: u32) -> Option<Entity> {
+        self.entity_registry.try_entity(id)
+    }
+
     pub fn entity_count(&self) -> u32 {
         self.entity_registry.count()
     }
@@ -258,23 +357,33 @@ impl World {
         let mut components = src_archetype_component_ids_to_move
             .iter()
             .map(|component_id| unsafe {
+                let column = src_archetype
+                    .columns_mut()
+                    .get_mut(*component_id)
+                    .unwrap_unchecked()
+                    .get_mut();
+
                 (
                     *component_id,
                     NonNull::new_unchecked(
-                        src_archetype
-                            .columns_mut()
-                            .get_mut(*component_id)
-                            .unwrap_unchecked()
-                            .get_mut()
+                        column
                             .components_mut()
                             .get_unchecked_mut::<u8>(entity.id() as usize)
                             .unwrap_unchecked() as *mut u8,
                     ),
+                    column.ticks(entity.id() as usize),
                 )
             })
             .collect::<Vec<_>>();
 
-        components.append(&mut new_values);
+        if !new_values.is_empty() {
+            let ticks = ComponentTicks::new(self.increment_change_tick());
+            components.extend(
+                new_values
+                    .drain(..)
+                    .map(|(component_id, value)| (component_id, value, ticks)),
+            );
+        }
 
         let dst_archetype = self.archetype_registry.get_mut(*dst);
 
@@ -285,9 +394,12 @@ impl World {
         // We can remove the row from the src archetype, forgetting every value instead of dropping them if needed
         let src_archetype_index = self.entity_registry.archetype_id(entity).1;
         let src_archetype = self.archetype_registry.get_mut(*src);
-        src_archetype.remove_row(src_archetype_index, entity, |id| {
+        if let Some(moved_entity) = src_archetype.remove_row(src_archetype_index, entity, |id| {
             components_to_drop.contains(id)
-        });
+        }) {
+            self.entity_registry
+                .set_archetype_id(moved_entity, *src, src_archetype_index);
+        }
         self.entity_registry
             .set_archetype_id(entity, *dst, new_archetype_index);
     }
@@ -301,10 +413,16 @@ pub mod query;
 #[cfg(test)]
 mod tests {
     use crate::component::ComponentId;
+    use crate::entity::Entity;
+    use crate::world::query::filter::{Added, Changed};
+    use crate::world::query::state::QueryState;
+    use crate::world::query::Query;
     use crate::world::Component;
     use crate::world::World;
     use std::sync::atomic::{AtomicI32, Ordering};
     use std::sync::Arc;
+    use ze_ecs_macros::Component;
+    use ze_reflection::*;
 
     #[test]
     fn add_remove_query() {
@@ -354,7 +472,7 @@ mod tests {
         });
 
         let mut query = world.query::<&mut F32Counter>();
-        query.for_each(&world, |mut counter| {
+        query.for_each(&world, |counter| {
             assert_eq!(counter.x, 22.01);
             counter.x = 69.01;
         });
@@ -366,6 +484,71 @@ mod tests {
         });
     }
 
+    #[test]
+    fn change_detection() {
+        #[derive(Component)]
+        struct Counter {
+            x: i32,
+        }
+
+        let mut world = World::default();
+        let entity = world.spawn();
+        world.add(entity, Counter { x: 0 });
+        let entity2 = world.spawn();
+        world.add(entity2, Counter { x: 0 });
+
+        let mut added = world.query::<(Entity, Added<Counter>)>();
+        let mut changed = world.query::<(Entity, Changed<Counter>)>();
+        fn count<Q: Query>(query: &mut QueryState<Q>, world: &World) -> usize {
+            let mut count = 0;
+            query.for_each(world, |_| count += 1);
+            count
+        }
+
+        assert_eq!(count(&mut added, &world), 2);
+        assert_eq!(count(&mut added, &world), 0);
+        assert_eq!(count(&mut changed, &world), 2);
+        assert_eq!(count(&mut changed, &world), 0);
+
+        let mut query = world.query::<&mut Counter>();
+        assert!(query.get(&world, entity).is_some());
+        assert_eq!(count(&mut added, &world), 0);
+        assert_eq!(count(&mut changed, &world), 1);
+
+        // A query doesn't report its own changes
+        let mut changed_mut = world.query::<(&mut Counter, Changed<Counter>)>();
+        changed_mut.for_each(&world, |(counter, _)| counter.x += 1);
+        let mut calls = 0;
+        changed_mut.for_each(&world, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn reflected_components() {
+        #[derive(Component, Reflectable)]
+        struct Counter {
+            x: i32,
+        }
+
+        #[derive(Component)]
+        struct Hidden;
+
+        let mut world = World::default();
+        world.register_reflectable::<Counter>();
+        let entity = world.spawn();
+        world.add(entity, Counter { x: 5 });
+        world.add(entity, Hidden);
+
+        let components = world.reflected_components(entity);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].0, Counter::component_id());
+        assert!(Arc::ptr_eq(&components[0].1, &Counter::type_desc()));
+
+        let ptr = world.component_ptr_mut(entity, Counter::component_id());
+        let counter = unsafe { ptr.unwrap().cast::<Counter>().as_mut() };
+        assert_eq!(counter.x, 5);
+    }
+
     #[test]
     fn destroy() {
         struct Counter {
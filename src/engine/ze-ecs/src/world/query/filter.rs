@@ -1,8 +1,9 @@
 use crate::access::Access;
-use crate::archetype::Archetype;
-use crate::component::{Component, ComponentId};
-use crate::world::query::Query;
+use crate::archetype::{Archetype, Column};
+use crate::component::{Component, ComponentId, ComponentTicks};
+use crate::world::query::{Query, QueryTicks};
 use crate::world::World;
+use std::cell::UnsafeCell;
 use std::marker::PhantomData;
 
 pub struct With<T: Component>(PhantomData<T>);
@@ -16,7 +17,12 @@ impl<T: Component> Query for With<T> {
         T::component_id()
     }
 
-    fn initialize_fetch<'world>(_: &'world World, _: &Self::State) -> Self::Fetch<'world> {}
+    fn initialize_fetch<'world>(
+        _: &'world World,
+        _: &Self::State,
+        _: QueryTicks,
+    ) -> Self::Fetch<'world> {
+    }
 
     fn prepare_fetch<'world>(_: &mut Self::Fetch<'world>, _: &Self::State, _: &'world Archetype) {}
 
@@ -43,7 +49,12 @@ impl<T: Component> Query for Without<T> {
         T::component_id()
     }
 
-    fn initialize_fetch<'world>(_: &'world World, _: &Self::State) -> Self::Fetch<'world> {}
+    fn initialize_fetch<'world>(
+        _: &'world World,
+        _: &Self::State,
+        _: QueryTicks,
+    ) -> Self::Fetch<'world> {
+    }
 
     fn prepare_fetch<'world>(_: &mut Self::Fetch<'world>, _: &Self::State, _: &'world Archetype) {}
 
@@ -58,3 +69,98 @@ impl<T: Component> Query for Without<T> {
 
     fn update_archetype_access(_: &Self::State, _: &Archetype, _: &mut Access) {}
 }
+
+/// Fetch object reading the change ticks of a component
+pub struct TicksFetch<'world> {
+    column: Option<&'world UnsafeCell<Column>>,
+    last_change_tick: u64,
+}
+
+// SAFETY: Executors guarantee we don't read columns while we are mutating them
+unsafe impl<'world> Send for TicksFetch<'world> {}
+unsafe impl<'world> Sync for TicksFetch<'world> {}
+
+impl<'world> TicksFetch<'world> {
+    /// # Safety
+    ///
+    /// `prepare_fetch` must have been called
+    unsafe fn ticks(&self, index: usize) -> ComponentTicks {
+        let column = self
+            .column
+            .unwrap_unchecked()
+            .get()
+            .as_ref()
+            .unwrap_unchecked();
+        column.ticks(index)
+    }
+}
+
+macro_rules! impl_change_filter {
+    ($name: ident, $ticks_func: ident) => {
+        impl<T: Component> Query for $name<T> {
+            type Item<'world> = ();
+            type Fetch<'world> = TicksFetch<'world>;
+            type State = ComponentId;
+
+            fn initialize_state(_: &World) -> Self::State {
+                T::component_id()
+            }
+
+            fn initialize_fetch<'world>(
+                _: &'world World,
+                _: &Self::State,
+                ticks: QueryTicks,
+            ) -> Self::Fetch<'world> {
+                TicksFetch {
+                    column: None,
+                    last_change_tick: ticks.last_change_tick,
+                }
+            }
+
+            fn prepare_fetch<'world>(
+                fetch: &mut Self::Fetch<'world>,
+                state: &Self::State,
+                archetype: &'world Archetype,
+            ) {
+                fetch.column = Some(&archetype.columns()[*state]);
+            }
+
+            unsafe fn fetch<'world>(_: &Self::Fetch<'world>, _: usize) -> Self::Item<'world> {}
+
+            unsafe fn filter_fetch(fetch: &Self::Fetch<'_>, index: usize) -> bool {
+                fetch.ticks(index).$ticks_func(fetch.last_change_tick)
+            }
+
+            fn archetype_contains_component<F: Fn(&ComponentId) -> bool>(
+                state: &Self::State,
+                f: F,
+            ) -> bool {
+                f(state)
+            }
+
+            fn update_archetype_access(
+                state: &Self::State,
+                archetype: &Archetype,
+                access: &mut Access,
+            ) {
+                let position = archetype
+                    .components()
+                    .iter()
+                    .position(|id| id == state)
+                    .unwrap();
+
+                access.add_read(archetype.components_archetype_ids()[position]);
+            }
+        }
+    };
+}
+
+/// Only match entities whose component `T` was added since the last iteration of the query
+pub struct Added<T: Component>(PhantomData<T>);
+
+/// Only match entities whose component `T` was added or mutably fetched since the last
+/// iteration of the query
+pub struct Changed<T: Component>(PhantomData<T>);
+
+impl_change_filter!(Added, is_added);
+impl_change_filter!(Changed, is_changed);
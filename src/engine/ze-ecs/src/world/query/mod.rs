@@ -1,10 +1,21 @@
 ﻿use crate::access::Access;
 use crate::archetype::{Archetype, Column};
 use crate::component::{Component, ComponentId};
+use crate::entity::{Entity, EntityRegistry};
 use crate::world::World;
 use std::cell::UnsafeCell;
 use ze_ecs_macros::repeat_tuples;
 
+/// Change ticks of a query iteration
+#[derive(Copy, Clone, Default, Debug)]
+pub struct QueryTicks {
+    /// Tick of the previous iteration, components modified after it are reported as changed
+    pub last_change_tick: u64,
+
+    /// Tick of the current iteration, set on components fetched mutably
+    pub change_tick: u64,
+}
+
 /// A object that fetch components from a world
 pub trait Query {
     type Item<'world>;
@@ -14,7 +25,11 @@ pub trait Query {
     type State: Send + Sync + Sized;
 
     fn initialize_state(world: &World) -> Self::State;
-    fn initialize_fetch<'world>(world: &'world World, state: &Self::State) -> Self::Fetch<'world>;
+    fn initialize_fetch<'world>(
+        world: &'world World,
+        state: &Self::State,
+        ticks: QueryTicks,
+    ) -> Self::Fetch<'world>;
 
     /// Set the next archetype to fetch from
     fn prepare_fetch<'world>(
@@ -33,6 +48,15 @@ pub trait Query {
         component_index: usize,
     ) -> Self::Item<'world>;
 
+    /// Returns false if the entity at `component_index` must be skipped
+    /// # Safety
+    ///
+    /// - `prepare_fetch` must have been called
+    #[inline]
+    unsafe fn filter_fetch(_fetch: &Self::Fetch<'_>, _component_index: usize) -> bool {
+        true
+    }
+
     fn archetype_contains_component<F: Fn(&ComponentId) -> bool>(state: &Self::State, f: F)
         -> bool;
 
@@ -52,12 +76,52 @@ unsafe impl<'world> Sync for ReadFetch<'world> {}
 /// Fetch object meant to read and/or write a component
 pub struct WriteFetch<'world> {
     column: Option<&'world UnsafeCell<Column>>,
+    change_tick: u64,
 }
 
 // SAFETY: Executors guarantee we don't mutate columns while we are reading them
 unsafe impl<'world> Send for WriteFetch<'world> {}
 unsafe impl<'world> Sync for WriteFetch<'world> {}
 
+/// Fetch object returning the entity handle
+pub struct EntityFetch<'world> {
+    registry: &'world EntityRegistry,
+}
+
+impl Query for Entity {
+    type Item<'world> = Entity;
+    type Fetch<'world> = EntityFetch<'world>;
+    type State = ();
+
+    #[inline]
+    fn initialize_state(_: &World) -> Self::State {}
+
+    #[inline]
+    fn initialize_fetch<'world>(
+        world: &'world World,
+        _: &Self::State,
+        _: QueryTicks,
+    ) -> Self::Fetch<'world> {
+        EntityFetch {
+            registry: &world.entity_registry,
+        }
+    }
+
+    #[inline]
+    fn prepare_fetch<'world>(_: &mut Self::Fetch<'world>, _: &Self::State, _: &'world Archetype) {}
+
+    #[inline]
+    unsafe fn fetch<'world>(fetch: &Self::Fetch<'world>, index: usize) -> Self::Item<'world> {
+        fetch.registry.entity(index as u32)
+    }
+
+    fn archetype_contains_component<F: Fn(&ComponentId) -> bool>(_: &Self::State, _: F) -> bool {
+        true
+    }
+
+    fn update_archetype_access(_: &Self::State, _: &Archetype, _: &mut Access) {}
+}
+
 impl<T: Component> Query for &T {
     type Item<'world> = &'world T;
     type Fetch<'world> = ReadFetch<'world>;
@@ -69,7 +133,11 @@ impl<T: Component> Query for &T {
     }
 
     #[inline]
-    fn initialize_fetch<'world>(_: &'world World, _: &Self::State) -> Self::Fetch<'world> {
+    fn initialize_fetch<'world>(
+        _: &'world World,
+        _: &Self::State,
+        _: QueryTicks,
+    ) -> Self::Fetch<'world> {
         ReadFetch { column: None }
     }
 
@@ -124,8 +192,15 @@ impl<T: Component> Query for &mut T {
     }
 
     #[inline]
-    fn initialize_fetch<'world>(_: &'world World, _: &Self::State) -> Self::Fetch<'world> {
-        WriteFetch { column: None }
+    fn initialize_fetch<'world>(
+        _: &'world World,
+        _: &Self::State,
+        ticks: QueryTicks,
+    ) -> Self::Fetch<'world> {
+        WriteFetch {
+            column: None,
+            change_tick: ticks.change_tick,
+        }
     }
 
     #[inline]
@@ -147,6 +222,7 @@ impl<T: Component> Query for &mut T {
             .get()
             .as_mut()
             .unwrap_unchecked();
+        column.set_changed(index, fetch.change_tick);
         column.components_mut().get_mut(index).unwrap_unchecked()
     }
 
@@ -177,14 +253,14 @@ macro_rules! impl_tuples {
             type State = ($($name::State,)*);
 
             #[inline]
-            fn initialize_state<'world>(world: &'world World) -> Self::State {
+            fn initialize_state(world: &World) -> Self::State {
                 ($($name::initialize_state(world)),*)
             }
 
             #[inline]
-            fn initialize_fetch<'world>(world: &'world World, state: &Self::State) -> Self::Fetch<'world> {
+            fn initialize_fetch<'world>(world: &'world World, state: &Self::State, ticks: QueryTicks) -> Self::Fetch<'world> {
                 let ($($name),*) = state;
-                ($($name::initialize_fetch(world, $name)),*)
+                ($($name::initialize_fetch(world, $name, ticks)),*)
             }
 
             #[inline]
@@ -200,6 +276,12 @@ macro_rules! impl_tuples {
                 ($($name::fetch($name, index)),*)
             }
 
+            #[inline]
+            unsafe fn filter_fetch(fetch: &Self::Fetch<'_>, index: usize) -> bool {
+                let ($($name),*) = fetch;
+                true $(&& $name::filter_fetch($name, index))*
+            }
+
             #[inline]
             fn archetype_contains_component<'world, F: Fn(&ComponentId) -> bool>(state: &Self::State, f: F) -> bool {
                 let ($($name),*) = state;
@@ -217,5 +299,5 @@ macro_rules! impl_tuples {
 
 repeat_tuples!(impl_tuples, 8, F, S);
 
-mod filter;
+pub mod filter;
 pub mod state;
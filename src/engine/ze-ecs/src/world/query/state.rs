@@ -3,7 +3,7 @@ use crate::archetype::{Archetype, ArchetypeId};
 use crate::entity::Entity;
 use crate::system::param::{SystemParamFetch, SystemParamState};
 use crate::system::query::SystemQuery;
-use crate::world::query::Query;
+use crate::world::query::{Query, QueryTicks};
 use crate::world::World;
 use ze_core::sync::SyncUnsafeCell;
 use ze_jobsystem::prelude::*;
@@ -13,6 +13,9 @@ pub struct QueryState<Q: Query> {
     archetype_generation: u64,
     archetypes: Vec<ArchetypeId>,
     archetype_access: Access,
+
+    /// Change tick of the last iteration, see [`crate::world::query::filter::Changed`]
+    last_change_tick: u64,
 }
 
 impl<'world, Q: Query> QueryState<Q> {
@@ -22,6 +25,7 @@ impl<'world, Q: Query> QueryState<Q> {
             archetype_generation: 0,
             archetypes: vec![],
             archetype_access: Default::default(),
+            last_change_tick: 0,
         };
         state.collect_archetypes(world);
         state
@@ -30,14 +34,18 @@ impl<'world, Q: Query> QueryState<Q> {
     pub fn for_each<F: FnMut(Q::Item<'world>)>(&mut self, world: &'world World, mut f: F) {
         self.collect_archetypes(world);
 
-        let mut fetch = Q::initialize_fetch(world, &self.state);
+        let ticks = self.begin_iteration(world);
+        let mut fetch = Q::initialize_fetch(world, &self.state, ticks);
         for archetype_id in &self.archetypes {
             let archetype = world.archetype_registry.get(*archetype_id);
             Q::prepare_fetch(&mut fetch, &self.state, archetype);
             for entity in archetype.entities() {
                 // SAFETY: set_archetype is called before fetch
-                let item = unsafe { Q::fetch(&fetch, entity.id() as usize) };
-                f(item);
+                unsafe {
+                    if Q::filter_fetch(&fetch, entity.id() as usize) {
+                        f(Q::fetch(&fetch, entity.id() as usize));
+                    }
+                }
             }
         }
     }
@@ -50,14 +58,19 @@ impl<'world, Q: Query> QueryState<Q> {
         self.collect_archetypes(world);
 
         // We use UnsafeCells so we can mutate inside the parallel iterator
+        let ticks = self.begin_iteration(world);
         let func = SyncUnsafeCell::new(f);
-        let fetch = SyncUnsafeCell::new(Q::initialize_fetch(world, &self.state));
+        let fetch = SyncUnsafeCell::new(Q::initialize_fetch(world, &self.state, ticks));
         for archetype_id in &self.archetypes {
             let fetch = unsafe { &mut *fetch.get() };
             let archetype = world.archetype_registry.get(*archetype_id);
             Q::prepare_fetch(fetch, &self.state, archetype);
             archetype.entities().par_iter().for_each(|entity| {
                 // SAFETY: set_archetype is called before fetch
+                if !unsafe { Q::filter_fetch(fetch, entity.id() as usize) } {
+                    return;
+                }
+
                 let item = unsafe { Q::fetch(fetch, entity.id() as usize) };
 
                 // SAFETY: Function is Send + Sync
@@ -78,10 +91,30 @@ impl<'world, Q: Query> QueryState<Q> {
         }
 
         let archetype = world.archetype_registry.get(archetype_id);
-        let mut fetch = Q::initialize_fetch(world, &self.state);
+        let ticks = QueryTicks {
+            last_change_tick: self.last_change_tick,
+            change_tick: world.increment_change_tick(),
+        };
+        let mut fetch = Q::initialize_fetch(world, &self.state, ticks);
         Q::prepare_fetch(&mut fetch, &self.state, archetype);
-        let item = unsafe { Q::fetch(&fetch, entity.id() as usize) };
-        Some(item)
+        unsafe {
+            if !Q::filter_fetch(&fetch, entity.id() as usize) {
+                return None;
+            }
+
+            Some(Q::fetch(&fetch, entity.id() as usize))
+        }
+    }
+
+    /// Get the ticks of a new iteration, components changed from now will be reported by the
+    /// next one
+    fn begin_iteration(&mut self, world: &'world World) -> QueryTicks {
+        let ticks = QueryTicks {
+            last_change_tick: self.last_change_tick,
+            change_tick: world.increment_change_tick(),
+        };
+        self.last_change_tick = ticks.change_tick;
+        ticks
     }
 
     fn collect_archetypes(&mut self, world: &'world World) {
@@ -58,6 +58,12 @@ pub trait MountPoint: Send + Sync {
     fn exists(&self, path: &Path) -> bool;
     fn read(&self, path: &Path) -> Result<Box<dyn Read>, Error>;
     fn write(&self, path: &Path) -> Result<Box<dyn Write>, Error>;
+
+    /// Remove a file or a directory (and all of its content)
+    fn remove(&self, path: &Path) -> Result<(), Error>;
+
+    /// Create a directory and all of its missing parents
+    fn create_dir_all(&self, path: &Path) -> Result<(), Error>;
     fn iter_dir(
         &self,
         path: &Path,
@@ -67,7 +73,7 @@ pub trait MountPoint: Send + Sync {
     fn watch(
         &self,
         path: &Path,
-        f: &Arc<(dyn Fn(WatchEvent) + Send + Sync + 'static)>,
+        f: &Arc<dyn Fn(WatchEvent) + Send + Sync + 'static>,
     ) -> Result<(), Error>;
     fn alias(&self) -> &str;
     fn to_underlying_path(&self, path: &Path) -> Result<PathBuf, Error>;
@@ -140,6 +146,24 @@ impl FileSystem {
         }
     }
 
+    pub fn remove(&self, path: &Path) -> Result<(), Error> {
+        if let Some(index) = self.matching_mount_point_for_path(path) {
+            let mount_point_guard = self.mount_points.read();
+            mount_point_guard[index].remove(path)
+        } else {
+            Err(Error::UnknownMountPoint)
+        }
+    }
+
+    pub fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
+        if let Some(index) = self.matching_mount_point_for_path(path) {
+            let mount_point_guard = self.mount_points.read();
+            mount_point_guard[index].create_dir_all(path)
+        } else {
+            Err(Error::UnknownMountPoint)
+        }
+    }
+
     pub fn iter_dir(
         &self,
         path: &Path,
@@ -9,7 +9,7 @@ use crate::WatchEvent;
 use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::fs::{read_dir, File};
+use std::fs::{create_dir_all, read_dir, remove_dir_all, remove_file, File};
 use std::io::{ErrorKind, Read, Write};
 use std::path::PathBuf;
 use std::sync::mpsc::channel;
@@ -36,7 +36,7 @@ impl From<notify::Error> for Error {
     }
 }
 
-type StdMountPointWatcher = Arc<(dyn Fn(WatchEvent) + Send + Sync + 'static)>;
+type StdMountPointWatcher = Arc<dyn Fn(WatchEvent) + Send + Sync + 'static>;
 
 pub struct StdMountPoint {
     alias: String,
@@ -65,21 +65,16 @@ impl StdMountPoint {
                         "IO Watcher Thread".to_string(),
                     );
                     loop {
-                        if let Ok(event) = rx.recv() {
-                            match event {
-                                DebouncedEvent::Write(path) => {
-                                    let watcher_closure_map = watcher_closure_map.lock();
-                                    if let Some(f) = watcher_closure_map.get(&path) {
-                                        let path = Self::fs_path_to_zefs_path(
-                                            root.as_ref(),
-                                            &path.canonicalize().unwrap(),
-                                        );
-                                        f(WatchEvent::Write(ZefsPath::from_mount_point_and_path(
-                                            &alias, &path,
-                                        )));
-                                    }
-                                }
-                                _ => {}
+                        if let Ok(DebouncedEvent::Write(path)) = rx.recv() {
+                            let watcher_closure_map = watcher_closure_map.lock();
+                            if let Some(f) = watcher_closure_map.get(&path) {
+                                let path = Self::fs_path_to_zefs_path(
+                                    root.as_ref(),
+                                    &path.canonicalize().unwrap(),
+                                );
+                                f(WatchEvent::Write(ZefsPath::from_mount_point_and_path(
+                                    &alias, &path,
+                                )));
                             }
                         }
                     }
@@ -140,6 +135,21 @@ impl MountPoint for StdMountPoint {
         Ok(Box::new(file))
     }
 
+    fn remove(&self, path: &Path) -> Result<(), Error> {
+        let path = self.to_fs_path(path);
+        if path.is_dir() {
+            remove_dir_all(path)?;
+        } else {
+            remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), Error> {
+        create_dir_all(self.to_fs_path(path))?;
+        Ok(())
+    }
+
     fn iter_dir(
         &self,
         path: &Path,
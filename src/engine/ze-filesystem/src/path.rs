@@ -152,6 +152,29 @@ impl Path {
         self.path().split(PATH_SEPARATOR)
     }
 
+    /// Check if the path is `parent` or is inside it, comparing whole segments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ze_filesystem::path::Path;
+    ///
+    /// let parent = Path::parse("/m/path/to").unwrap();
+    /// assert!(Path::parse("/m/path/to/file.txt").unwrap().is_within(&parent));
+    /// assert!(!Path::parse("/m/path/to_file.txt").unwrap().is_within(&parent));
+    /// ```
+    pub fn is_within(&self, parent: &Path) -> bool {
+        if self.mount_point() != parent.mount_point() {
+            return false;
+        }
+
+        let mut segments = self.path_segments().filter(|segment| !segment.is_empty());
+        parent
+            .path_segments()
+            .filter(|segment| !segment.is_empty())
+            .all(|parent_segment| segments.next() == Some(parent_segment))
+    }
+
     pub fn as_str(&self) -> &str {
         &self.str
     }
@@ -232,4 +255,16 @@ mod tests {
         assert_eq!(path.path(), "assets/textures/texture.png");
         assert_eq!(path.query().unwrap(), "mip=0");
     }
+
+    #[test]
+    fn is_within() {
+        let parent = super::Path::parse("/mnt/assets/textures/").unwrap();
+        let is_within = |path| super::Path::parse(path).unwrap().is_within(&parent);
+
+        assert!(is_within("/mnt/assets/textures"));
+        assert!(is_within("/mnt/assets/textures/texture.png"));
+        assert!(!is_within("/mnt/assets/textures_old/texture.png"));
+        assert!(!is_within("/mnt/assets"));
+        assert!(!is_within("/other/assets/textures/texture.png"));
+    }
 }
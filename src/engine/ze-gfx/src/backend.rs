@@ -1,6 +1,6 @@
 ﻿use crate::{PixelFormat, SampleDesc, ShaderStageFlagBits};
 use enumflags2::{bitflags, BitFlags};
-use raw_window_handle::RawWindowHandle;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use std::any::Any;
 use std::sync::Arc;
 use ze_core::color::Color4f32;
@@ -22,6 +22,8 @@ pub enum DeviceError {
     OutOfMemory,
     NoCompatibleMemoryTypeFound,
     InvalidParameters,
+    UnsupportedFormat,
+    ShaderUnavailable,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -63,6 +65,38 @@ pub struct RenderPassDesc<'a> {
     pub depth_stencil: Option<RenderPassDepthStencil<'a>>,
 }
 
+impl RenderPassDesc<'_> {
+    /// Sample desc shared by every attachment of the pass, used by the pipelines bound during it
+    pub fn sample_desc(&self) -> SampleDesc {
+        self.render_targets
+            .iter()
+            .map(|render_target| {
+                render_target
+                    .render_target_view
+                    .desc
+                    .resource
+                    .desc
+                    .sample_desc
+            })
+            .chain(self.depth_stencil.iter().map(|depth_stencil| {
+                depth_stencil
+                    .depth_stencil_view
+                    .desc
+                    .resource
+                    .desc
+                    .sample_desc
+            }))
+            .reduce(|sample_desc, other| {
+                debug_assert!(
+                    sample_desc.count == other.count && sample_desc.quality == other.quality,
+                    "Render pass attachments must have the same sample desc"
+                );
+                sample_desc
+            })
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum QueueType {
     Graphics,
@@ -81,12 +115,20 @@ pub enum ResourceState {
     ShaderRead,
     CopyRead,
     CopyWrite,
+    ResolveRead,
+    ResolveWrite,
     Present,
 }
 
+#[derive(Copy, Clone)]
 pub enum ResourceTransitionBarrierResource<'a> {
     Buffer(&'a Buffer),
     Texture(&'a Texture),
+
+    /// Single subresource of a texture, its index is `mip_level + array_slice * mip_levels`
+    /// Subresources must be transitioned back to the state of the rest of the texture before the
+    /// end of the command list
+    TextureSubresource(&'a Texture, u32),
 }
 
 pub struct ResourceTransitionBarrier<'a> {
@@ -97,6 +139,9 @@ pub struct ResourceTransitionBarrier<'a> {
 
 pub enum ResourceBarrier<'a> {
     Transition(ResourceTransitionBarrier<'a>),
+
+    /// Wait for all unordered accesses to the resource to be finished
+    UnorderedAccess(ResourceTransitionBarrierResource<'a>),
 }
 
 // Pipeline states
@@ -222,6 +267,8 @@ pub struct BufferCopyRegion {
     pub size_in_bytes: u64,
 }
 
+/// Region copied between a buffer and a texture
+/// Used for both buffer to texture and texture to buffer copies
 pub struct BufferToTextureCopyRegion {
     pub buffer_offset_in_bytes: u64,
     pub buffer_texture_width: u32,
@@ -273,22 +320,114 @@ pub struct Texture2DSRV {
     pub mip_levels: u32,
 }
 
+#[derive(Clone)]
+pub struct Texture2DArraySRV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub min_mip_level: u32,
+    pub mip_levels: u32,
+    pub first_array_slice: u32,
+    pub array_size: u32,
+}
+
+/// Cube views read 6 consecutive array slices as the +X, -X, +Y, -Y, +Z and -Z faces of a cube
+#[derive(Clone)]
+pub struct TextureCubeSRV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub min_mip_level: u32,
+    pub mip_levels: u32,
+}
+
+#[derive(Clone)]
+pub struct TextureCubeArraySRV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub min_mip_level: u32,
+    pub mip_levels: u32,
+    pub first_array_slice: u32,
+    pub cube_count: u32,
+}
+
+#[derive(Clone)]
+pub struct Texture3DSRV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub min_mip_level: u32,
+    pub mip_levels: u32,
+}
+
 #[derive(Clone)]
 pub enum ShaderResourceViewDesc {
     Buffer(BufferSRV),
     Texture2D(Texture2DSRV),
+    Texture2DArray(Texture2DArraySRV),
+    TextureCube(TextureCubeSRV),
+    TextureCubeArray(TextureCubeArraySRV),
+    Texture3D(Texture3DSRV),
+}
+
+// Unordered access view
+
+/// Buffer UAVs are laid out like SRVs, the buffer must have the `UnorderedAccess` usage
+#[derive(Clone)]
+pub struct BufferUAV {
+    pub buffer: Arc<Buffer>,
+    pub ty: BufferSRVType,
+}
+
+/// Texture UAVs give access to a single mip level, the texture must have the `UnorderedAccess`
+/// usage
+#[derive(Clone)]
+pub struct Texture2DUAV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub mip_level: u32,
+}
+
+/// 3D texture UAVs give access to every depth slice of the mip level
+#[derive(Clone)]
+pub struct Texture3DUAV {
+    pub texture: Arc<Texture>,
+    pub format: PixelFormat,
+    pub mip_level: u32,
+}
+
+#[derive(Clone)]
+pub enum UnorderedAccessViewDesc {
+    Buffer(BufferUAV),
+    Texture2D(Texture2DUAV),
+    Texture3D(Texture3DUAV),
 }
 
 // Render target view
 
+/// Multisampled textures are viewed with their sample count, `mip_level` must be 0
 #[derive(Clone)]
 pub struct Texture2DRTV {
     pub mip_level: u32,
 }
 
+#[derive(Clone)]
+pub struct Texture2DArrayRTV {
+    pub mip_level: u32,
+    pub first_array_slice: u32,
+    pub array_size: u32,
+}
+
+/// Depth slices of 3D textures are rendered to like array slices
+#[derive(Clone)]
+pub struct Texture3DRTV {
+    pub mip_level: u32,
+    pub first_depth_slice: u32,
+    pub depth_size: u32,
+}
+
 #[derive(Clone)]
 pub enum RenderTargetViewType {
     Texture2D(Texture2DRTV),
+    Texture2DArray(Texture2DArrayRTV),
+    Texture3D(Texture3DRTV),
 }
 
 #[derive(Clone)]
@@ -300,14 +439,23 @@ pub struct RenderTargetViewDesc {
 
 // Depth stencil view
 
+/// Multisampled textures are viewed with their sample count, `mip_level` must be 0
 #[derive(Clone)]
 pub struct Texture2DDSV {
     pub mip_level: u32,
 }
 
+#[derive(Clone)]
+pub struct Texture2DArrayDSV {
+    pub mip_level: u32,
+    pub first_array_slice: u32,
+    pub array_size: u32,
+}
+
 #[derive(Clone)]
 pub enum DepthStencilViewType {
     Texture2D(Texture2DDSV),
+    Texture2DArray(Texture2DArrayDSV),
 }
 
 #[derive(Clone)]
@@ -381,6 +529,9 @@ impl Default for SamplerDesc {
 pub struct TextureSubresourceLayout {
     pub offset_in_bytes: u64,
     pub row_pitch_in_bytes: u64,
+
+    /// Distance between two depth slices of a 3D texture
+    pub depth_pitch_in_bytes: u64,
     pub size_in_bytes: u64,
 }
 
@@ -407,6 +558,10 @@ pub trait Device: Send + Sync {
         &self,
         desc: &ShaderResourceViewDesc,
     ) -> Result<ShaderResourceView, DeviceError>;
+    fn create_unordered_access_view(
+        &self,
+        desc: &UnorderedAccessViewDesc,
+    ) -> Result<UnorderedAccessView, DeviceError>;
     fn create_render_target_view(
         &self,
         desc: &RenderTargetViewDesc,
@@ -427,10 +582,20 @@ pub trait Device: Send + Sync {
     /// as there are handled by the backend
     fn create_command_list(&self, queue_type: QueueType) -> Result<CommandList, DeviceError>;
     fn create_sampler(&self, desc: &SamplerDesc) -> Result<Sampler, DeviceError>;
+    fn create_query_heap(&self, desc: &QueryHeapDesc, name: &str)
+        -> Result<QueryHeap, DeviceError>;
+    fn create_fence(&self, name: &str) -> Result<Fence, DeviceError>;
 
     // Buffer functions
     fn buffer_mapped_ptr(&self, buffer: &Buffer) -> Option<*mut u8>;
 
+    // Fence functions
+    /// Check if the GPU reached the last signal submitted for this fence
+    fn is_fence_signaled(&self, fence: &Fence) -> bool;
+
+    /// Block the current thread until the GPU reached the last signal submitted for this fence
+    fn wait_for_fence(&self, fence: &Fence);
+
     // Texture functions
     fn texture_subresource_layout(
         &self,
@@ -451,6 +616,13 @@ pub trait Device: Send + Sync {
     // Memory pool functions
     fn transient_memory_pool(&self) -> &MemoryPool;
 
+    /// Current budget and usage of the GPU memory
+    fn memory_stats(&self) -> MemoryStats;
+
+    // Query functions
+    /// Number of timestamp ticks per second on the graphics queue
+    fn timestamp_frequency(&self) -> u64;
+
     // Transfer functions
     fn cmd_copy_buffer_regions(
         &self,
@@ -466,12 +638,46 @@ pub trait Device: Send + Sync {
         dst_texture: &Texture,
         regions: &[BufferToTextureCopyRegion],
     );
+    fn cmd_copy_texture_to_buffer_regions(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_buffer: &Buffer,
+        regions: &[BufferToTextureCopyRegion],
+    );
+
+    /// Resolve a multisampled texture into a single sampled texture of the same size and format
+    /// The source must be in the ResolveRead state and the destination in the ResolveWrite state
+    fn cmd_resolve_texture(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_texture: &Texture,
+    );
 
     // Debug functions
     fn cmd_debug_begin_event(&self, cmd_list: &mut CommandList, name: &str, color: Color4f32);
     fn cmd_debug_end_event(&self, cmd_list: &mut CommandList);
 
+    // Query functions
+    /// Write the GPU timestamp once all previous commands are done
+    fn cmd_write_timestamp(&self, cmd_list: &mut CommandList, query_heap: &QueryHeap, index: u32);
+
+    /// Copy query results as `u64` values to a buffer, which must be in the CopyWrite state
+    /// unless it is a readback buffer
+    fn cmd_resolve_query_data(
+        &self,
+        cmd_list: &mut CommandList,
+        query_heap: &QueryHeap,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &Buffer,
+        dst_offset_in_bytes: u64,
+    );
+
     // Render passes functions
+    /// Every render target and depth stencil of the pass must have the same sample count, which
+    /// is used by the pipelines bound during the pass
     fn cmd_begin_render_pass(&self, cmd_list: &mut CommandList, desc: &RenderPassDesc);
     fn cmd_end_render_pass(&self, cmd_list: &mut CommandList);
     fn cmd_resource_barrier(&self, cmd_list: &mut CommandList, barriers: &[ResourceBarrier]);
@@ -480,6 +686,9 @@ pub trait Device: Send + Sync {
 
     // Pipeline functions
     fn cmd_set_shader_stages(&self, cmd_list: &mut CommandList, stages: &[PipelineShaderStage]);
+
+    /// Switch the command list to a compute pipeline using the given compute shader module
+    fn cmd_set_compute_shader(&self, cmd_list: &mut CommandList, module: &ShaderModule);
     fn cmd_set_input_assembly_state(
         &self,
         cmd_list: &mut CommandList,
@@ -514,6 +723,13 @@ pub trait Device: Send + Sync {
         first_index: u32,
         first_instance: u32,
     );
+    fn cmd_dispatch(
+        &self,
+        cmd_list: &mut CommandList,
+        thread_group_x: u32,
+        thread_group_y: u32,
+        thread_group_z: u32,
+    );
     fn cmd_dispatch_mesh(
         &self,
         cmd_list: &mut CommandList,
@@ -523,6 +739,9 @@ pub trait Device: Send + Sync {
     );
 
     /// Submit work to a specific queue to the GPU, optionally waiting or signaling fences
+    /// The queue waits for the last signal submitted for each fence in `wait_fences` before
+    /// executing the command lists, allowing work submitted to other queues to be waited on
+    /// `command_lists` can be empty to only signal fences after the work already submitted
     fn submit(
         &self,
         queue_type: QueueType,
@@ -540,6 +759,9 @@ pub trait Device: Send + Sync {
 pub enum MemoryLocation {
     CpuToGpu,
     GpuOnly,
+
+    /// Memory readable by the CPU, used to read back GPU results
+    GpuToCpu,
 }
 
 #[bitflags]
@@ -606,6 +828,10 @@ pub struct TextureDesc {
     pub width: u32,
     pub height: u32,
     pub depth: u32,
+
+    /// Number of array slices, must be 1 for 3D textures
+    /// Cube maps are arrays of 6 slices per cube
+    pub array_size: u32,
     pub mip_levels: u32,
     pub format: PixelFormat,
     pub sample_desc: SampleDesc,
@@ -664,6 +890,24 @@ impl ShaderResourceView {
     }
 }
 
+pub struct UnorderedAccessView {
+    pub desc: UnorderedAccessViewDesc,
+    pub backend_data: Box<dyn ShaderVisibleResource>,
+}
+
+impl UnorderedAccessView {
+    pub fn new(
+        desc: UnorderedAccessViewDesc,
+        backend_data: Box<dyn ShaderVisibleResource>,
+    ) -> Self {
+        Self { desc, backend_data }
+    }
+
+    pub fn descriptor_index(&self) -> u32 {
+        self.backend_data.descriptor_index()
+    }
+}
+
 pub struct RenderTargetView {
     pub desc: RenderTargetViewDesc,
     pub backend_data: Box<dyn Any + Send>,
@@ -714,6 +958,7 @@ pub struct SwapChainDesc {
     pub sample_desc: SampleDesc,
     pub usage_flags: TextureUsageFlags,
     pub window_handle: RawWindowHandle,
+    pub display_handle: RawDisplayHandle,
 }
 
 #[derive(Debug)]
@@ -728,7 +973,41 @@ impl SwapChain {
     }
 }
 
-pub struct Fence;
+/// Synchronization primitive between queues and the CPU
+/// Each submission signaling the fence increments its value, waits are done on the last value
+/// submitted
+pub struct Fence {
+    pub backend_data: Box<dyn Any + Send + Sync>,
+}
+
+impl Fence {
+    pub fn new(backend_data: Box<dyn Any + Send + Sync>) -> Self {
+        Self { backend_data }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum QueryType {
+    /// GPU clock value, in ticks of [`Device::timestamp_frequency`]
+    Timestamp,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct QueryHeapDesc {
+    pub ty: QueryType,
+    pub count: u32,
+}
+
+pub struct QueryHeap {
+    pub desc: QueryHeapDesc,
+    pub backend_data: Box<dyn Any + Send + Sync>,
+}
+
+impl QueryHeap {
+    pub fn new(desc: QueryHeapDesc, backend_data: Box<dyn Any + Send + Sync>) -> Self {
+        Self { desc, backend_data }
+    }
+}
 
 pub struct MemoryPool {
     pub backend_data: Box<dyn Any + Send + Sync>,
@@ -739,3 +1018,24 @@ impl MemoryPool {
         Self { backend_data }
     }
 }
+
+/// Statistics of a memory segment
+#[derive(Copy, Clone, Default, Debug)]
+pub struct MemorySegmentStats {
+    /// Amount of memory the application can use before running into performance issues
+    pub budget_bytes: u64,
+
+    /// Memory used by the whole process, including allocations not made by the device
+    pub usage_bytes: u64,
+    pub allocation_count: u32,
+    pub allocation_bytes: u64,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct MemoryStats {
+    /// Memory local to the GPU (video memory on discrete GPUs)
+    pub local: MemorySegmentStats,
+
+    /// System memory accessible by the GPU
+    pub non_local: MemorySegmentStats,
+}
@@ -4,9 +4,12 @@ use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use ze_reflection::*;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromPrimitive, Reflectable)]
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromPrimitive, Reflectable,
+)]
 #[non_exhaustive]
 pub enum PixelFormat {
+    #[default]
     Unknown,
 
     // BGRA formats
@@ -22,15 +25,40 @@ pub enum PixelFormat {
 
     // Depth/stencil formats
     D24UnormS8Uint,
+
+    // Block compressed formats
+    Bc1Unorm,
+    Bc1UnormSrgb,
+    Bc2Unorm,
+    Bc2UnormSrgb,
+    Bc3Unorm,
+    Bc3UnormSrgb,
+    Bc4Unorm,
+    Bc4Snorm,
+    Bc5Unorm,
+    Bc5Snorm,
+    Bc6hUfloat,
+    Bc6hSfloat,
+    Bc7Unorm,
+    Bc7UnormSrgb,
+
+    // Formats are serialized by index, new ones are appended
+    R32Uint,
 }
 
+/// Width and height in texels of the blocks of block compressed formats
+pub const COMPRESSED_BLOCK_SIZE: u32 = 4;
+
 impl PixelFormat {
+    /// Size of a texel, or of a block of [`COMPRESSED_BLOCK_SIZE`]² texels for block compressed
+    /// formats
     pub fn bytes_size(&self) -> usize {
         match self {
             PixelFormat::Unknown => 0,
             PixelFormat::B8G8R8A8UnormSrgb
             | PixelFormat::B8G8R8A8Unorm
-            | PixelFormat::R8G8B8A8Unorm => 4,
+            | PixelFormat::R8G8B8A8Unorm
+            | PixelFormat::R32Uint => 4,
 
             PixelFormat::R8Unorm => 1,
 
@@ -38,17 +66,78 @@ impl PixelFormat {
 
             // Depth/stencil formats
             PixelFormat::D24UnormS8Uint => 4,
+
+            // Block compressed formats
+            PixelFormat::Bc1Unorm
+            | PixelFormat::Bc1UnormSrgb
+            | PixelFormat::Bc4Unorm
+            | PixelFormat::Bc4Snorm => 8,
+
+            PixelFormat::Bc2Unorm
+            | PixelFormat::Bc2UnormSrgb
+            | PixelFormat::Bc3Unorm
+            | PixelFormat::Bc3UnormSrgb
+            | PixelFormat::Bc5Unorm
+            | PixelFormat::Bc5Snorm
+            | PixelFormat::Bc6hUfloat
+            | PixelFormat::Bc6hSfloat
+            | PixelFormat::Bc7Unorm
+            | PixelFormat::Bc7UnormSrgb => 16,
         }
     }
 
-    pub fn texture_size_in_bytes(&self, width: u32, height: u32) -> usize {
-        (width as usize) * (height as usize) * self.bytes_size()
+    pub fn is_block_compressed(&self) -> bool {
+        matches!(
+            self,
+            PixelFormat::Bc1Unorm
+                | PixelFormat::Bc1UnormSrgb
+                | PixelFormat::Bc2Unorm
+                | PixelFormat::Bc2UnormSrgb
+                | PixelFormat::Bc3Unorm
+                | PixelFormat::Bc3UnormSrgb
+                | PixelFormat::Bc4Unorm
+                | PixelFormat::Bc4Snorm
+                | PixelFormat::Bc5Unorm
+                | PixelFormat::Bc5Snorm
+                | PixelFormat::Bc6hUfloat
+                | PixelFormat::Bc6hSfloat
+                | PixelFormat::Bc7Unorm
+                | PixelFormat::Bc7UnormSrgb
+        )
     }
-}
 
-impl Default for PixelFormat {
-    fn default() -> Self {
-        Self::Unknown
+    pub fn is_srgb(&self) -> bool {
+        matches!(
+            self,
+            PixelFormat::B8G8R8A8UnormSrgb
+                | PixelFormat::Bc1UnormSrgb
+                | PixelFormat::Bc2UnormSrgb
+                | PixelFormat::Bc3UnormSrgb
+                | PixelFormat::Bc7UnormSrgb
+        )
+    }
+
+    /// Width and height in texels of the unit [`PixelFormat::bytes_size`] refers to
+    pub fn block_size(&self) -> u32 {
+        if self.is_block_compressed() {
+            COMPRESSED_BLOCK_SIZE
+        } else {
+            1
+        }
+    }
+
+    /// Size of a tightly packed row of texels, or of blocks for block compressed formats
+    pub fn row_pitch_in_bytes(&self, width: u32) -> usize {
+        (width.div_ceil(self.block_size()) as usize) * self.bytes_size()
+    }
+
+    /// Number of rows of [`PixelFormat::row_pitch_in_bytes`] in a texture of the given height
+    pub fn row_count(&self, height: u32) -> u32 {
+        height.div_ceil(self.block_size())
+    }
+
+    pub fn texture_size_in_bytes(&self, width: u32, height: u32) -> usize {
+        self.row_pitch_in_bytes(width) * (self.row_count(height) as usize)
     }
 }
 
@@ -58,6 +147,20 @@ impl Display for PixelFormat {
             PixelFormat::B8G8R8A8UnormSrgb => write!(f, "BGRA 8-bit (unorm, sRGB)"),
             PixelFormat::B8G8R8A8Unorm => write!(f, "BGRA 8-bit (unorm)"),
             PixelFormat::R8G8B8A8Unorm => write!(f, "RGBA 8-bit (unorm)"),
+            PixelFormat::Bc1Unorm => write!(f, "BC1 (unorm)"),
+            PixelFormat::Bc1UnormSrgb => write!(f, "BC1 (unorm, sRGB)"),
+            PixelFormat::Bc2Unorm => write!(f, "BC2 (unorm)"),
+            PixelFormat::Bc2UnormSrgb => write!(f, "BC2 (unorm, sRGB)"),
+            PixelFormat::Bc3Unorm => write!(f, "BC3 (unorm)"),
+            PixelFormat::Bc3UnormSrgb => write!(f, "BC3 (unorm, sRGB)"),
+            PixelFormat::Bc4Unorm => write!(f, "BC4 (unorm)"),
+            PixelFormat::Bc4Snorm => write!(f, "BC4 (snorm)"),
+            PixelFormat::Bc5Unorm => write!(f, "BC5 (unorm)"),
+            PixelFormat::Bc5Snorm => write!(f, "BC5 (snorm)"),
+            PixelFormat::Bc6hUfloat => write!(f, "BC6H (ufloat)"),
+            PixelFormat::Bc6hSfloat => write!(f, "BC6H (sfloat)"),
+            PixelFormat::Bc7Unorm => write!(f, "BC7 (unorm)"),
+            PixelFormat::Bc7UnormSrgb => write!(f, "BC7 (unorm, sRGB)"),
             _ => write!(f, "{:?}", self),
         }
     }
@@ -88,4 +191,84 @@ pub enum ShaderStageFlagBits {
 
 pub mod backend;
 pub mod null;
+pub mod profiler;
 pub mod utils;
+
+#[cfg(test)]
+mod tests {
+    use crate::PixelFormat;
+    use num_traits::FromPrimitive;
+
+    /// Mip chain of a 4x4 texture
+    const MIP_SIZES: [(u32, u32); 3] = [(4, 4), (2, 2), (1, 1)];
+
+    /// Formats keep their index when new ones are added
+    #[test]
+    fn serialized_indices() {
+        assert_eq!(PixelFormat::from_u32(3), Some(PixelFormat::R8Unorm));
+        assert_eq!(PixelFormat::from_u32(4), Some(PixelFormat::R8G8B8A8Unorm));
+        assert_eq!(PixelFormat::from_u32(6), Some(PixelFormat::D24UnormS8Uint));
+        assert_eq!(PixelFormat::from_u32(7), Some(PixelFormat::Bc1Unorm));
+        assert_eq!(PixelFormat::from_u32(21), Some(PixelFormat::R32Uint));
+    }
+
+    #[test]
+    fn block_size() {
+        assert_eq!(PixelFormat::R8G8B8A8Unorm.block_size(), 1);
+        assert_eq!(PixelFormat::Bc1Unorm.block_size(), 4);
+        assert_eq!(PixelFormat::Bc7Unorm.block_size(), 4);
+    }
+
+    #[test]
+    fn uncompressed_sizes() {
+        let format = PixelFormat::R8G8B8A8Unorm;
+        assert_eq!(format.row_pitch_in_bytes(1), 4);
+        assert_eq!(format.row_count(1), 1);
+        assert_eq!(format.texture_size_in_bytes(1, 1), 4);
+
+        assert_eq!(format.row_pitch_in_bytes(5), 20);
+        assert_eq!(format.row_count(3), 3);
+        assert_eq!(format.texture_size_in_bytes(5, 3), 60);
+
+        let mip_sizes =
+            MIP_SIZES.map(|(width, height)| format.texture_size_in_bytes(width, height));
+        assert_eq!(mip_sizes, [64, 16, 4]);
+    }
+
+    /// BC1 stores 4x4 texels in 8 bytes, partial blocks take a whole block
+    #[test]
+    fn bc1_sizes() {
+        let format = PixelFormat::Bc1Unorm;
+        assert_eq!(format.row_pitch_in_bytes(1), 8);
+        assert_eq!(format.row_count(1), 1);
+        assert_eq!(format.texture_size_in_bytes(1, 1), 8);
+
+        assert_eq!(format.row_pitch_in_bytes(5), 16);
+        assert_eq!(format.row_count(3), 1);
+        assert_eq!(format.texture_size_in_bytes(5, 3), 16);
+
+        let mip_sizes =
+            MIP_SIZES.map(|(width, height)| format.texture_size_in_bytes(width, height));
+        assert_eq!(mip_sizes, [8, 8, 8]);
+    }
+
+    /// BC7 stores 4x4 texels in 16 bytes, partial blocks take a whole block
+    #[test]
+    fn bc7_sizes() {
+        let format = PixelFormat::Bc7UnormSrgb;
+        assert_eq!(format.row_pitch_in_bytes(1), 16);
+        assert_eq!(format.row_count(1), 1);
+        assert_eq!(format.texture_size_in_bytes(1, 1), 16);
+
+        assert_eq!(format.row_pitch_in_bytes(5), 32);
+        assert_eq!(format.row_count(3), 1);
+        assert_eq!(format.texture_size_in_bytes(5, 3), 32);
+
+        assert_eq!(format.row_count(5), 2);
+        assert_eq!(format.texture_size_in_bytes(5, 5), 64);
+
+        let mip_sizes =
+            MIP_SIZES.map(|(width, height)| format.texture_size_in_bytes(width, height));
+        assert_eq!(mip_sizes, [16, 16, 16]);
+    }
+}
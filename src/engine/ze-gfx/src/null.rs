@@ -1,8 +1,11 @@
 use crate::backend::*;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use ze_core::color::Color4f32;
 use ze_core::maths::RectI32;
 
+/// Backend without a GPU, see [`NullDevice`]
 #[derive(Default)]
 pub struct NullBackend;
 
@@ -16,8 +19,59 @@ impl Backend for NullBackend {
     }
 }
 
-#[derive(Default)]
-struct NullDevice;
+/// Number of backbuffers of null swapchains
+const NULL_SWAPCHAIN_BACKBUFFER_COUNT: usize = 2;
+
+/// Device that creates resources without doing any GPU work
+///
+/// Buffers accessible by the CPU are backed by system memory so uploads and readbacks still work,
+/// commands are ignored. Used to run the engine without a GPU (dedicated servers, tests, etc)
+pub struct NullDevice {
+    next_descriptor_index: AtomicU32,
+    transient_memory_pool: MemoryPool,
+}
+
+impl NullDevice {
+    pub fn new() -> Self {
+        Self {
+            next_descriptor_index: AtomicU32::new(0),
+            transient_memory_pool: MemoryPool::new(Box::new(())),
+        }
+    }
+
+    fn allocate_descriptor(&self) -> Box<dyn ShaderVisibleResource> {
+        Box::new(NullDescriptor(
+            self.next_descriptor_index.fetch_add(1, Ordering::Relaxed),
+        ))
+    }
+}
+
+impl Default for NullDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct NullBuffer {
+    data: Option<UnsafeCell<Box<[u8]>>>,
+}
+
+// Data is only accessed through the mapped pointer, synchronization is up to the user like with
+// GPU backends
+unsafe impl Sync for NullBuffer {}
+
+struct NullDescriptor(u32);
+
+impl ShaderVisibleResource for NullDescriptor {
+    fn descriptor_index(&self) -> u32 {
+        self.0
+    }
+}
+
+struct NullSwapChain {
+    backbuffers: Vec<Arc<Texture>>,
+    backbuffer_index: AtomicU32,
+}
 
 impl Device for NullDevice {
     fn begin_frame(&self) {}
@@ -26,91 +80,208 @@ impl Device for NullDevice {
 
     fn create_buffer(
         &self,
-        _: &BufferDesc,
+        info: &BufferDesc,
         _: Option<&MemoryPool>,
         _: &str,
     ) -> Result<Buffer, DeviceError> {
-        Err(DeviceError::Unknown)
+        let data = match info.memory_desc.memory_location {
+            MemoryLocation::GpuOnly => None,
+            MemoryLocation::CpuToGpu | MemoryLocation::GpuToCpu => Some(UnsafeCell::new(
+                vec![0; info.size_bytes as usize].into_boxed_slice(),
+            )),
+        };
+
+        Ok(Buffer::new(info, Box::new(NullBuffer { data })))
     }
 
     fn create_texture(
         &self,
-        _: &TextureDesc,
+        info: &TextureDesc,
         _: Option<&MemoryPool>,
         _: &str,
     ) -> Result<Texture, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(Texture::new(*info, Box::new(())))
     }
 
     fn create_shader_resource_view(
         &self,
-        _: &ShaderResourceViewDesc,
+        desc: &ShaderResourceViewDesc,
     ) -> Result<ShaderResourceView, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(ShaderResourceView::new(
+            desc.clone(),
+            self.allocate_descriptor(),
+        ))
+    }
+
+    fn create_unordered_access_view(
+        &self,
+        desc: &UnorderedAccessViewDesc,
+    ) -> Result<UnorderedAccessView, DeviceError> {
+        Ok(UnorderedAccessView::new(
+            desc.clone(),
+            self.allocate_descriptor(),
+        ))
     }
 
     fn create_render_target_view(
         &self,
-        _: &RenderTargetViewDesc,
+        desc: &RenderTargetViewDesc,
     ) -> Result<RenderTargetView, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(RenderTargetView::new(desc.clone(), Box::new(())))
     }
 
     fn create_depth_stencil_view(
         &self,
-        _: &DepthStencilViewDesc,
+        desc: &DepthStencilViewDesc,
     ) -> Result<DepthStencilView, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(DepthStencilView::new(desc.clone(), Box::new(())))
     }
 
     fn create_swapchain(
         &self,
-        _: &SwapChainDesc,
+        info: &SwapChainDesc,
         _: Option<SwapChain>,
     ) -> Result<SwapChain, DeviceError> {
-        Err(DeviceError::Unknown)
+        let backbuffer_desc = TextureDesc {
+            width: info.width,
+            height: info.height,
+            depth: 1,
+            array_size: 1,
+            mip_levels: 1,
+            format: info.format,
+            sample_desc: info.sample_desc,
+            usage_flags: info.usage_flags,
+            memory_desc: MemoryDesc {
+                memory_location: MemoryLocation::GpuOnly,
+                memory_flags: Default::default(),
+            },
+        };
+
+        let backbuffers = (0..NULL_SWAPCHAIN_BACKBUFFER_COUNT)
+            .map(|_| Arc::new(Texture::new(backbuffer_desc, Box::new(()))))
+            .collect();
+
+        Ok(SwapChain::new(
+            *info,
+            Box::new(NullSwapChain {
+                backbuffers,
+                backbuffer_index: AtomicU32::new(0),
+            }),
+        ))
     }
 
     fn create_shader_module(&self, _: &[u8]) -> Result<ShaderModule, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(ShaderModule::new(Box::new(())))
     }
 
     fn create_command_list(&self, _: QueueType) -> Result<CommandList, DeviceError> {
-        Err(DeviceError::Unknown)
+        Ok(CommandList::new(Box::new(())))
+    }
+
+    fn create_sampler(&self, desc: &SamplerDesc) -> Result<Sampler, DeviceError> {
+        Ok(Sampler::new(desc.clone(), self.allocate_descriptor()))
+    }
+
+    fn create_query_heap(&self, desc: &QueryHeapDesc, _: &str) -> Result<QueryHeap, DeviceError> {
+        Ok(QueryHeap::new(*desc, Box::new(())))
     }
 
-    fn create_sampler(&self, _: &SamplerDesc) -> Result<Sampler, DeviceError> {
-        Err(DeviceError::Unknown)
+    fn create_fence(&self, _: &str) -> Result<Fence, DeviceError> {
+        Ok(Fence::new(Box::new(())))
     }
 
-    fn buffer_mapped_ptr(&self, _: &Buffer) -> Option<*mut u8> {
-        None
+    fn buffer_mapped_ptr(&self, buffer: &Buffer) -> Option<*mut u8> {
+        let buffer = buffer.backend_data.downcast_ref::<NullBuffer>()?;
+        buffer
+            .data
+            .as_ref()
+            .map(|data| unsafe { (*data.get()).as_mut_ptr() })
     }
 
-    fn texture_subresource_layout(&self, _: &Texture, _: u32) -> TextureSubresourceLayout {
+    fn is_fence_signaled(&self, _: &Fence) -> bool {
+        true
+    }
+
+    fn wait_for_fence(&self, _: &Fence) {}
+
+    fn texture_subresource_layout(
+        &self,
+        texture: &Texture,
+        subresource_index: u32,
+    ) -> TextureSubresourceLayout {
+        let mip_level = subresource_index % texture.desc.mip_levels.max(1);
+        let width = (texture.desc.width >> mip_level).max(1);
+        let height = (texture.desc.height >> mip_level).max(1);
+        let depth = (texture.desc.depth >> mip_level).max(1) as u64;
+        let row_pitch_in_bytes = texture.desc.format.row_pitch_in_bytes(width) as u64;
+        let depth_pitch_in_bytes =
+            row_pitch_in_bytes * texture.desc.format.row_count(height) as u64;
         TextureSubresourceLayout {
             offset_in_bytes: 0,
-            row_pitch_in_bytes: 0,
-            size_in_bytes: 0,
+            row_pitch_in_bytes,
+            depth_pitch_in_bytes,
+            size_in_bytes: depth_pitch_in_bytes * depth,
         }
     }
 
-    fn swapchain_backbuffer_count(&self, _: &SwapChain) -> usize {
-        0
+    fn swapchain_backbuffer_count(&self, swapchain: &SwapChain) -> usize {
+        swapchain
+            .backend_data
+            .downcast_ref::<NullSwapChain>()
+            .unwrap()
+            .backbuffers
+            .len()
     }
 
-    fn swapchain_backbuffer_index(&self, _: &SwapChain) -> u32 {
-        0
+    fn swapchain_backbuffer_index(&self, swapchain: &SwapChain) -> u32 {
+        swapchain
+            .backend_data
+            .downcast_ref::<NullSwapChain>()
+            .unwrap()
+            .backbuffer_index
+            .load(Ordering::SeqCst)
     }
 
-    fn swapchain_backbuffer(&self, _: &SwapChain, _: u32) -> Result<Arc<Texture>, DeviceError> {
-        Err(DeviceError::Unknown)
+    fn swapchain_backbuffer(
+        &self,
+        swapchain: &SwapChain,
+        index: u32,
+    ) -> Result<Arc<Texture>, DeviceError> {
+        let swapchain = swapchain
+            .backend_data
+            .downcast_ref::<NullSwapChain>()
+            .unwrap();
+
+        swapchain
+            .backbuffers
+            .get(index as usize)
+            .cloned()
+            .ok_or(DeviceError::InvalidParameters)
     }
 
-    fn present(&self, _: &SwapChain) {}
+    fn present(&self, swapchain: &SwapChain) {
+        let swapchain = swapchain
+            .backend_data
+            .downcast_ref::<NullSwapChain>()
+            .unwrap();
+
+        let next_index = (swapchain.backbuffer_index.load(Ordering::SeqCst) + 1)
+            % swapchain.backbuffers.len() as u32;
+        swapchain
+            .backbuffer_index
+            .store(next_index, Ordering::SeqCst);
+    }
 
     fn transient_memory_pool(&self) -> &MemoryPool {
-        unimplemented!()
+        &self.transient_memory_pool
+    }
+
+    fn memory_stats(&self) -> MemoryStats {
+        MemoryStats::default()
+    }
+
+    fn timestamp_frequency(&self) -> u64 {
+        1_000_000_000
     }
 
     fn cmd_copy_buffer_regions(
@@ -131,10 +302,34 @@ impl Device for NullDevice {
     ) {
     }
 
+    fn cmd_copy_texture_to_buffer_regions(
+        &self,
+        _: &mut CommandList,
+        _: &Texture,
+        _: &Buffer,
+        _: &[BufferToTextureCopyRegion],
+    ) {
+    }
+
+    fn cmd_resolve_texture(&self, _: &mut CommandList, _: &Texture, _: &Texture) {}
+
     fn cmd_debug_begin_event(&self, _: &mut CommandList, _: &str, _: Color4f32) {}
 
     fn cmd_debug_end_event(&self, _: &mut CommandList) {}
 
+    fn cmd_write_timestamp(&self, _: &mut CommandList, _: &QueryHeap, _: u32) {}
+
+    fn cmd_resolve_query_data(
+        &self,
+        _: &mut CommandList,
+        _: &QueryHeap,
+        _: u32,
+        _: u32,
+        _: &Buffer,
+        _: u64,
+    ) {
+    }
+
     fn cmd_begin_render_pass(&self, _: &mut CommandList, _: &RenderPassDesc) {}
 
     fn cmd_end_render_pass(&self, _: &mut CommandList) {}
@@ -147,6 +342,8 @@ impl Device for NullDevice {
 
     fn cmd_set_shader_stages(&self, _: &mut CommandList, _: &[PipelineShaderStage]) {}
 
+    fn cmd_set_compute_shader(&self, _: &mut CommandList, _: &ShaderModule) {}
+
     fn cmd_set_input_assembly_state(&self, _: &mut CommandList, _: &PipelineInputAssemblyState) {}
 
     fn cmd_set_blend_state(&self, _: &mut CommandList, _: &PipelineBlendState) {}
@@ -161,6 +358,8 @@ impl Device for NullDevice {
 
     fn cmd_draw_indexed(&self, _: &mut CommandList, _: u32, _: u32, _: u32, _: u32) {}
 
+    fn cmd_dispatch(&self, _: &mut CommandList, _: u32, _: u32, _: u32) {}
+
     fn cmd_dispatch_mesh(&self, _: &mut CommandList, _: u32, _: u32, _: u32) {}
 
     fn submit(&self, _: QueueType, _: &[&CommandList], _: &[&Fence], _: &[&Fence]) {}
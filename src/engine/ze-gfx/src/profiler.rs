@@ -0,0 +1,220 @@
+use crate::backend::{
+    Buffer, BufferDesc, BufferUsageFlags, CommandList, Device, DeviceError, MemoryDesc,
+    MemoryFlags, MemoryLocation, QueryHeap, QueryHeapDesc, QueryType, ResourceState,
+};
+use parking_lot::Mutex;
+use std::mem::size_of;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of frames the profiler cycles through, results of a frame are read back when the frame
+/// is reused so this must be greater than the number of frames the backends keep in flight
+const PROFILER_FRAME_COUNT: usize = 3;
+
+/// GPU duration of a profiled scope
+#[derive(Clone, Debug)]
+pub struct GpuScopeTiming {
+    pub name: String,
+
+    /// Number of scopes this scope is nested in
+    pub depth: u32,
+    pub duration: Duration,
+}
+
+/// Scope started by [`GpuProfiler::begin_scope`], `None` when the profiler ran out of queries
+#[must_use]
+pub struct GpuScope(Option<usize>);
+
+struct Scope {
+    name: String,
+    depth: u32,
+    ended: bool,
+}
+
+struct ProfilerFrame {
+    query_heap: QueryHeap,
+    readback_buffer: Buffer,
+
+    /// Scope `i` uses the queries `2 * i` and `2 * i + 1`
+    scopes: Vec<Scope>,
+    resolved: bool,
+}
+
+struct ProfilerState {
+    frames: Vec<ProfilerFrame>,
+    current_frame: usize,
+    depth: u32,
+    timings: Vec<GpuScopeTiming>,
+}
+
+/// Measure the GPU duration of scopes of command lists using timestamp queries
+///
+/// Timings are read back a few frames later, once the GPU is guaranteed to be done with them
+pub struct GpuProfiler {
+    device: Arc<dyn Device>,
+    max_scope_count: usize,
+    state: Mutex<ProfilerState>,
+}
+
+impl GpuProfiler {
+    pub fn new(device: Arc<dyn Device>, max_scope_count: u32) -> Result<Self, DeviceError> {
+        let query_count = max_scope_count * 2;
+
+        let mut frames = Vec::with_capacity(PROFILER_FRAME_COUNT);
+        for i in 0..PROFILER_FRAME_COUNT {
+            let query_heap = device.create_query_heap(
+                &QueryHeapDesc {
+                    ty: QueryType::Timestamp,
+                    count: query_count,
+                },
+                &format!("GPU Profiler Queries {}", i),
+            )?;
+
+            let readback_buffer = device.create_buffer(
+                &BufferDesc {
+                    size_bytes: (query_count as usize * size_of::<u64>()) as u64,
+                    usage: BufferUsageFlags::default(),
+                    memory_desc: MemoryDesc {
+                        memory_location: MemoryLocation::GpuToCpu,
+                        memory_flags: MemoryFlags::default(),
+                    },
+                    default_resource_state: ResourceState::CopyWrite,
+                },
+                None,
+                &format!("GPU Profiler Readback Buffer {}", i),
+            )?;
+
+            frames.push(ProfilerFrame {
+                query_heap,
+                readback_buffer,
+                scopes: vec![],
+                resolved: false,
+            });
+        }
+
+        Ok(Self {
+            device,
+            max_scope_count: max_scope_count as usize,
+            state: Mutex::new(ProfilerState {
+                frames,
+                current_frame: 0,
+                depth: 0,
+                timings: vec![],
+            }),
+        })
+    }
+
+    /// Start profiling a new frame, reading back the timings of the frame being reused
+    /// Must be called after [`Device::begin_frame`]
+    pub fn begin_frame(&self) {
+        let mut state = self.state.lock();
+        let ProfilerState {
+            frames,
+            current_frame,
+            depth,
+            timings,
+        } = &mut *state;
+
+        *current_frame = (*current_frame + 1) % PROFILER_FRAME_COUNT;
+        *depth = 0;
+
+        let frame = &mut frames[*current_frame];
+        if frame.resolved {
+            if let Some(mapped_ptr) = self.device.buffer_mapped_ptr(&frame.readback_buffer) {
+                let timestamps = mapped_ptr.cast::<u64>();
+                let frequency = self.device.timestamp_frequency() as u128;
+
+                *timings = frame
+                    .scopes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, scope)| scope.ended)
+                    .map(|(i, scope)| {
+                        let (begin, end) = unsafe {
+                            (
+                                ptr::read_unaligned(timestamps.add(i * 2)),
+                                ptr::read_unaligned(timestamps.add(i * 2 + 1)),
+                            )
+                        };
+
+                        let ticks = end.saturating_sub(begin) as u128;
+                        GpuScopeTiming {
+                            name: scope.name.clone(),
+                            depth: scope.depth,
+                            duration: Duration::from_nanos(
+                                (ticks * 1_000_000_000 / frequency) as u64,
+                            ),
+                        }
+                    })
+                    .collect();
+            }
+        }
+
+        frame.scopes.clear();
+        frame.resolved = false;
+    }
+
+    pub fn begin_scope(&self, cmd_list: &mut CommandList, name: &str) -> GpuScope {
+        let mut state = self.state.lock();
+        let depth = state.depth;
+        state.depth += 1;
+
+        let current_frame = state.current_frame;
+        let frame = &mut state.frames[current_frame];
+        if frame.scopes.len() == self.max_scope_count {
+            return GpuScope(None);
+        }
+
+        let index = frame.scopes.len();
+        frame.scopes.push(Scope {
+            name: name.to_string(),
+            depth,
+            ended: false,
+        });
+
+        self.device
+            .cmd_write_timestamp(cmd_list, &frame.query_heap, (index * 2) as u32);
+        GpuScope(Some(index))
+    }
+
+    pub fn end_scope(&self, cmd_list: &mut CommandList, scope: GpuScope) {
+        let mut state = self.state.lock();
+        state.depth = state.depth.saturating_sub(1);
+
+        if let Some(index) = scope.0 {
+            let current_frame = state.current_frame;
+            let frame = &mut state.frames[current_frame];
+            frame.scopes[index].ended = true;
+
+            self.device
+                .cmd_write_timestamp(cmd_list, &frame.query_heap, (index * 2 + 1) as u32);
+        }
+    }
+
+    /// Copy the timestamps of the current frame to its readback buffer
+    /// Must be recorded after every scope of the frame, in the last command list submitted
+    pub fn resolve(&self, cmd_list: &mut CommandList) {
+        let mut state = self.state.lock();
+        let current_frame = state.current_frame;
+        let frame = &mut state.frames[current_frame];
+        if frame.scopes.is_empty() {
+            return;
+        }
+
+        self.device.cmd_resolve_query_data(
+            cmd_list,
+            &frame.query_heap,
+            0,
+            (frame.scopes.len() * 2) as u32,
+            &frame.readback_buffer,
+            0,
+        );
+        frame.resolved = true;
+    }
+
+    /// Timings of the last frame read back, in the order the scopes were started
+    pub fn timings(&self) -> Vec<GpuScopeTiming> {
+        self.state.lock().timings.clone()
+    }
+}
@@ -1,10 +1,15 @@
 ﻿use crate::backend::{
-    Buffer, BufferCopyRegion, BufferDesc, BufferToTextureCopyRegion, BufferUsageFlags, Device,
-    DeviceError, MemoryDesc, MemoryLocation, QueueType, ResourceBarrier, ResourceState,
-    ResourceTransitionBarrier, ResourceTransitionBarrierResource, Texture,
+    Buffer, BufferCopyRegion, BufferDesc, BufferToTextureCopyRegion, BufferUsageFlags, CommandList,
+    Device, DeviceError, MemoryDesc, MemoryLocation, QueueType, ResourceBarrier, ResourceState,
+    ResourceTransitionBarrier, ResourceTransitionBarrierResource, ShaderModule,
+    ShaderResourceViewDesc, Texture, Texture2DSRV, Texture2DUAV, TextureUsageFlagBits,
+    UnorderedAccessViewDesc,
 };
-use std::ptr;
+use crate::PixelFormat;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use std::sync::Arc;
+use std::{mem, ptr, slice};
 use ze_core::maths::Vector3;
 
 /// Copy data over to a buffer (using a staging buffer if required)
@@ -72,7 +77,9 @@ pub fn copy_data_to_buffer(
     Ok(())
 }
 
-/// Copy data over to a texture (using a staging buffer)
+/// Copy data over to the first mip level of a texture (using a staging buffer)
+/// The source row pitch is the size of a row of texels, or of blocks for block compressed formats
+/// Depth slices of 3D textures are read one after the other
 /// The source buffer MUST be in the Common state
 /// The destination resource state must be a state that is understood by transfer queues
 pub fn copy_data_to_texture(
@@ -85,7 +92,6 @@ pub fn copy_data_to_texture(
     dst_resource_state: ResourceState,
 ) -> Result<(), DeviceError> {
     assert!(!data.is_empty());
-    assert_eq!(texture.desc.mip_levels, 1);
     debug_assert!(
         dst_resource_state == ResourceState::Common
             || dst_resource_state == ResourceState::CopyRead
@@ -108,20 +114,31 @@ pub fn copy_data_to_texture(
     )?;
 
     let buffer_data = device.buffer_mapped_ptr(&staging).unwrap();
+    let format = texture.desc.format;
     unsafe {
-        let width = src_width as usize;
-        let height = src_height as usize;
+        let row_size = format.row_pitch_in_bytes(src_width);
         let row_pitch = subresource_layout.row_pitch_in_bytes as usize;
+        let row_count = format.row_count(src_height) as usize;
+        let src_depth_pitch = src_row_pitch_in_bytes * row_count;
+        let depth_pitch = subresource_layout.depth_pitch_in_bytes as usize;
+        debug_assert!(src_row_pitch_in_bytes >= row_size);
+        debug_assert!(data.len() >= src_depth_pitch * texture.desc.depth.max(1) as usize);
 
-        for y in 0..height {
-            ptr::copy_nonoverlapping(
-                data.as_ptr().add(y * width * src_row_pitch_in_bytes),
-                buffer_data.add(y * row_pitch),
-                width * src_row_pitch_in_bytes,
-            );
+        for z in 0..texture.desc.depth.max(1) as usize {
+            for y in 0..row_count {
+                ptr::copy_nonoverlapping(
+                    data.as_ptr()
+                        .add(z * src_depth_pitch + y * src_row_pitch_in_bytes),
+                    buffer_data.add(z * depth_pitch + y * row_pitch),
+                    row_size,
+                );
+            }
         }
     }
 
+    // Block compressed footprints are made of whole blocks
+    let block_size = format.block_size();
+
     let mut cmd_list = device.create_command_list(QueueType::Transfer)?;
     device.cmd_copy_buffer_to_texture_regions(
         &mut cmd_list,
@@ -129,9 +146,9 @@ pub fn copy_data_to_texture(
         texture,
         &[BufferToTextureCopyRegion {
             buffer_offset_in_bytes: 0,
-            buffer_texture_width: src_width,
-            buffer_texture_height: src_height,
-            buffer_texture_depth: 1,
+            buffer_texture_width: src_width.div_ceil(block_size) * block_size,
+            buffer_texture_height: src_height.div_ceil(block_size) * block_size,
+            buffer_texture_depth: texture.desc.depth.max(1),
             buffer_texture_row_pitch_in_bytes: subresource_layout.row_pitch_in_bytes as u32,
             texture_subresource_index: 0,
             texture_subresource_layout: subresource_layout,
@@ -145,3 +162,421 @@ pub fn copy_data_to_texture(
 
     Ok(())
 }
+
+fn create_readback_buffer(
+    device: &Arc<dyn Device>,
+    size_bytes: u64,
+    name: &str,
+) -> Result<Buffer, DeviceError> {
+    device.create_buffer(
+        &BufferDesc {
+            size_bytes,
+            usage: BufferUsageFlags::default(),
+            memory_desc: MemoryDesc {
+                memory_location: MemoryLocation::GpuToCpu,
+                memory_flags: Default::default(),
+            },
+            default_resource_state: ResourceState::CopyWrite,
+        },
+        None,
+        name,
+    )
+}
+
+/// Copy `resource` to the CPU through `copy`, transitioning it from `src_resource_state` to the
+/// CopyRead state and back around the copy
+/// Blocks until the GPU executed the copy
+fn readback(
+    device: &Arc<dyn Device>,
+    resource: ResourceTransitionBarrierResource,
+    src_resource_state: ResourceState,
+    copy: impl FnOnce(&mut CommandList),
+) -> Result<(), DeviceError> {
+    let transition = |source_state, dest_state| {
+        ResourceBarrier::Transition(ResourceTransitionBarrier {
+            resource,
+            source_state,
+            dest_state,
+        })
+    };
+
+    // The source can be in any state, only graphics queues understand all of them
+    let mut cmd_list = device.create_command_list(QueueType::Graphics)?;
+    if src_resource_state != ResourceState::CopyRead {
+        device.cmd_resource_barrier(
+            &mut cmd_list,
+            &[transition(src_resource_state, ResourceState::CopyRead)],
+        );
+    }
+
+    copy(&mut cmd_list);
+
+    if src_resource_state != ResourceState::CopyRead {
+        device.cmd_resource_barrier(
+            &mut cmd_list,
+            &[transition(ResourceState::CopyRead, src_resource_state)],
+        );
+    }
+
+    let fence = device.create_fence("readback Fence")?;
+    device.submit(QueueType::Graphics, &[&cmd_list], &[], &[&fence]);
+    device.wait_for_fence(&fence);
+
+    Ok(())
+}
+
+/// Read back the content of a buffer (using a readback buffer)
+/// The buffer must be in the `src_resource_state` state, it is left in that state
+/// Blocks until the GPU copied the buffer
+pub fn readback_buffer(
+    device: &Arc<dyn Device>,
+    buffer: &Buffer,
+    src_resource_state: ResourceState,
+) -> Result<Vec<u8>, DeviceError> {
+    let size_bytes = buffer.info.size_bytes;
+    let readback_buffer = create_readback_buffer(device, size_bytes, "readback_buffer Buffer")?;
+
+    readback(
+        device,
+        ResourceTransitionBarrierResource::Buffer(buffer),
+        src_resource_state,
+        |cmd_list| {
+            device.cmd_copy_buffer_regions(
+                cmd_list,
+                buffer,
+                &readback_buffer,
+                &[BufferCopyRegion {
+                    src_offset_in_bytes: 0,
+                    dst_offset_in_bytes: 0,
+                    size_in_bytes: size_bytes,
+                }],
+            );
+        },
+    )?;
+
+    let buffer_data = device
+        .buffer_mapped_ptr(&readback_buffer)
+        .ok_or(DeviceError::Unknown)?;
+    Ok(unsafe { slice::from_raw_parts(buffer_data, size_bytes as usize) }.to_vec())
+}
+
+/// Read back a texture subresource (using a readback buffer)
+/// Rows are tightly packed, depth slices of 3D textures follow each other
+/// The texture must be in the `src_resource_state` state, it is left in that state
+/// Blocks until the GPU copied the texture
+pub fn readback_texture(
+    device: &Arc<dyn Device>,
+    texture: &Texture,
+    subresource_index: u32,
+    src_resource_state: ResourceState,
+) -> Result<Vec<u8>, DeviceError> {
+    debug_assert_eq!(
+        texture.desc.sample_desc.count, 1,
+        "Multisampled textures must be resolved before being read back"
+    );
+
+    let mip_level = subresource_index % texture.desc.mip_levels.max(1);
+    let width = (texture.desc.width >> mip_level).max(1);
+    let height = (texture.desc.height >> mip_level).max(1);
+    let depth = (texture.desc.depth >> mip_level).max(1);
+
+    let subresource_layout = device.texture_subresource_layout(texture, subresource_index);
+    let row_pitch = subresource_layout.row_pitch_in_bytes as usize;
+    let depth_pitch = subresource_layout.depth_pitch_in_bytes as usize;
+    let size_in_bytes = subresource_layout.size_in_bytes as usize;
+    let readback_buffer = create_readback_buffer(
+        device,
+        subresource_layout.size_in_bytes,
+        "readback_texture Buffer",
+    )?;
+
+    // Block compressed footprints are made of whole blocks
+    let format = texture.desc.format;
+    let block_size = format.block_size();
+
+    readback(
+        device,
+        ResourceTransitionBarrierResource::Texture(texture),
+        src_resource_state,
+        |cmd_list| {
+            device.cmd_copy_texture_to_buffer_regions(
+                cmd_list,
+                texture,
+                &readback_buffer,
+                &[BufferToTextureCopyRegion {
+                    buffer_offset_in_bytes: 0,
+                    buffer_texture_width: width.div_ceil(block_size) * block_size,
+                    buffer_texture_height: height.div_ceil(block_size) * block_size,
+                    buffer_texture_depth: depth,
+                    buffer_texture_row_pitch_in_bytes: row_pitch as u32,
+                    texture_subresource_index: subresource_index,
+                    texture_subresource_layout: subresource_layout,
+                    texture_subresource_width: width,
+                    texture_subresource_height: height,
+                    texture_subresource_depth: depth,
+                    texture_subresource_offset: Vector3::<i32>::default(),
+                }],
+            );
+        },
+    )?;
+
+    let buffer_data = device
+        .buffer_mapped_ptr(&readback_buffer)
+        .ok_or(DeviceError::Unknown)?;
+    Ok(pack_texture_rows(
+        unsafe { slice::from_raw_parts(buffer_data, size_in_bytes) },
+        format,
+        width,
+        height,
+        depth,
+        row_pitch,
+        depth_pitch,
+    ))
+}
+
+/// Copy the rows of a `width`x`height`x`depth` texture of `format` laid out with `row_pitch` and
+/// `depth_pitch` in `data`, removing the padding between them
+fn pack_texture_rows(
+    data: &[u8],
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    depth: u32,
+    row_pitch: usize,
+    depth_pitch: usize,
+) -> Vec<u8> {
+    let row_size = format.row_pitch_in_bytes(width);
+    let row_count = format.row_count(height) as usize;
+
+    let mut packed = Vec::with_capacity(row_size * row_count * depth as usize);
+    for z in 0..depth as usize {
+        for y in 0..row_count {
+            let offset = z * depth_pitch + y * row_pitch;
+            packed.extend_from_slice(&data[offset..offset + row_size]);
+        }
+    }
+
+    packed
+}
+
+/// Name of the compute shader used by [`generate_mips`]
+pub const DOWNSAMPLE_SHADER: &str = "Downsample";
+
+/// Threads per group along X and Y of the downsample shader
+const DOWNSAMPLE_THREAD_GROUP_SIZE: u32 = 8;
+
+/// Compute module of [`DOWNSAMPLE_SHADER`]
+/// Shaders are compiled by the shader system, which is built on top of this crate, so it sets it
+/// through [`set_downsample_shader_module`]
+static DOWNSAMPLE_SHADER_MODULE: Lazy<RwLock<Option<Arc<ShaderModule>>>> =
+    Lazy::new(RwLock::default);
+
+/// Set the compute module used by [`generate_mips`], called each time [`DOWNSAMPLE_SHADER`] is
+/// compiled
+pub fn set_downsample_shader_module(module: Arc<ShaderModule>) {
+    *DOWNSAMPLE_SHADER_MODULE.write() = Some(module);
+}
+
+/// Generate the mip chain of a 2D texture from its first mip level
+/// Each mip level is the average of the 2x2 texels of the previous one, read through a shader
+/// resource view by the [`DOWNSAMPLE_SHADER`] compute shader
+/// The texture must have the `UnorderedAccess` usage and be in the UnorderedAccessReadWrite state,
+/// it is left in that state
+/// Mip levels are read in the ShaderRead state, so the command list must be a graphics one and it
+/// must be submitted during the current frame as the views are released with it
+///
+/// Formats compute shaders can't write (sRGB, block compressed and BGRA) are rejected with
+/// [`DeviceError::UnsupportedFormat`], [`DeviceError::ShaderUnavailable`] is returned until the
+/// downsample shader has been compiled
+pub fn generate_mips(
+    device: &Arc<dyn Device>,
+    cmd_list: &mut CommandList,
+    texture: &Arc<Texture>,
+) -> Result<(), DeviceError> {
+    debug_assert!(texture
+        .desc
+        .usage_flags
+        .contains(TextureUsageFlagBits::UnorderedAccess));
+    debug_assert_eq!(texture.desc.depth, 1);
+    debug_assert_eq!(texture.desc.array_size, 1);
+
+    // Typed UAV stores to BGRA textures are optional
+    let format = texture.desc.format;
+    if format.is_srgb() || format.is_block_compressed() || format == PixelFormat::B8G8R8A8Unorm {
+        return Err(DeviceError::UnsupportedFormat);
+    }
+
+    let downsample_shader = DOWNSAMPLE_SHADER_MODULE
+        .read()
+        .clone()
+        .ok_or(DeviceError::ShaderUnavailable)?;
+
+    let mip_transition = |mip_level, source_state, dest_state| {
+        ResourceBarrier::Transition(ResourceTransitionBarrier {
+            resource: ResourceTransitionBarrierResource::TextureSubresource(texture, mip_level),
+            source_state,
+            dest_state,
+        })
+    };
+
+    device.cmd_set_compute_shader(cmd_list, &downsample_shader);
+    for mip_level in 1..texture.desc.mip_levels {
+        let width = (texture.desc.width >> mip_level).max(1);
+        let height = (texture.desc.height >> mip_level).max(1);
+
+        let src = device.create_shader_resource_view(&ShaderResourceViewDesc::Texture2D(
+            Texture2DSRV {
+                texture: texture.clone(),
+                format,
+                min_mip_level: mip_level - 1,
+                mip_levels: 1,
+            },
+        ))?;
+        let dst = device.create_unordered_access_view(&UnorderedAccessViewDesc::Texture2D(
+            Texture2DUAV {
+                texture: texture.clone(),
+                format,
+                mip_level,
+            },
+        ))?;
+
+        // Also waits for the previous dispatch to be done writing the source
+        device.cmd_resource_barrier(
+            cmd_list,
+            &[mip_transition(
+                mip_level - 1,
+                ResourceState::UnorderedAccessReadWrite,
+                ResourceState::ShaderRead,
+            )],
+        );
+
+        // Source, destination, destination width and height
+        let constants = [
+            src.descriptor_index(),
+            dst.descriptor_index(),
+            width,
+            height,
+        ];
+        device.cmd_push_constants(cmd_list, 0, unsafe {
+            slice::from_raw_parts(
+                constants.as_ptr() as *const u8,
+                mem::size_of_val(&constants),
+            )
+        });
+        device.cmd_dispatch(
+            cmd_list,
+            width.div_ceil(DOWNSAMPLE_THREAD_GROUP_SIZE),
+            height.div_ceil(DOWNSAMPLE_THREAD_GROUP_SIZE),
+            1,
+        );
+    }
+
+    // Every mip level but the last one has been read
+    let barriers = (0..texture.desc.mip_levels.saturating_sub(1))
+        .map(|mip_level| {
+            mip_transition(
+                mip_level,
+                ResourceState::ShaderRead,
+                ResourceState::UnorderedAccessReadWrite,
+            )
+        })
+        .collect::<Vec<_>>();
+    if !barriers.is_empty() {
+        device.cmd_resource_barrier(cmd_list, &barriers);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{
+        Device, DeviceError, MemoryDesc, MemoryLocation, QueueType, Texture, TextureDesc,
+        TextureUsageFlagBits,
+    };
+    use crate::null::NullDevice;
+    use crate::utils::{generate_mips, pack_texture_rows, set_downsample_shader_module};
+    use crate::PixelFormat;
+    use std::sync::Arc;
+
+    fn texture(device: &Arc<dyn Device>, format: PixelFormat) -> Arc<Texture> {
+        Arc::new(
+            device
+                .create_texture(
+                    &TextureDesc {
+                        width: 16,
+                        height: 8,
+                        depth: 1,
+                        array_size: 1,
+                        mip_levels: 5,
+                        format,
+                        sample_desc: Default::default(),
+                        usage_flags: TextureUsageFlagBits::UnorderedAccess.into(),
+                        memory_desc: MemoryDesc {
+                            memory_location: MemoryLocation::GpuOnly,
+                            memory_flags: Default::default(),
+                        },
+                    },
+                    None,
+                    "Mips",
+                )
+                .unwrap(),
+        )
+    }
+
+    fn generate(device: &Arc<dyn Device>, texture: &Arc<Texture>) -> Result<(), DeviceError> {
+        let mut cmd_list = device.create_command_list(QueueType::Graphics)?;
+        generate_mips(device, &mut cmd_list, texture)
+    }
+
+    #[test]
+    fn generate_mips_with_downsample_shader() {
+        let device: Arc<dyn Device> = Arc::new(NullDevice::new());
+        set_downsample_shader_module(Arc::new(device.create_shader_module(&[]).unwrap()));
+        let texture = texture(&device, PixelFormat::R8G8B8A8Unorm);
+        assert_eq!(generate(&device, &texture), Ok(()));
+    }
+
+    #[test]
+    fn generate_mips_rejects_unsupported_formats() {
+        let device: Arc<dyn Device> = Arc::new(NullDevice::new());
+        for format in [
+            PixelFormat::B8G8R8A8UnormSrgb,
+            PixelFormat::B8G8R8A8Unorm,
+            PixelFormat::Bc1Unorm,
+        ] {
+            let texture = texture(&device, format);
+            assert_eq!(
+                generate(&device, &texture),
+                Err(DeviceError::UnsupportedFormat)
+            );
+        }
+    }
+
+    /// Bytes don't repeat with a period of 256, so rows read at the wrong pitch differ
+    fn padded_data(size: usize) -> Vec<u8> {
+        (0..size).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn pack_padded_rows() {
+        let data = padded_data(2 * 512);
+        let packed = pack_texture_rows(&data, PixelFormat::R8G8B8A8Unorm, 2, 2, 2, 256, 512);
+
+        let expected = [0, 256, 512, 768]
+            .iter()
+            .flat_map(|offset| data[*offset..*offset + 8].iter().copied())
+            .collect::<Vec<_>>();
+        assert_eq!(packed, expected);
+    }
+
+    /// A 5x5 BC1 texture is made of 2 rows of 2 blocks of 8 bytes
+    #[test]
+    fn pack_block_compressed_rows() {
+        let data = padded_data(2 * 256);
+        let packed = pack_texture_rows(&data, PixelFormat::Bc1Unorm, 5, 5, 1, 256, 512);
+
+        let expected = [&data[0..16], &data[256..272]].concat();
+        assert_eq!(packed, expected);
+    }
+}
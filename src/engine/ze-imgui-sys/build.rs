@@ -1,18 +1,30 @@
 ﻿#[cfg(not(target_os = "windows"))]
 use cc::Build;
 use cmake::Config;
+use std::env;
 use std::io;
 use std::path::Path;
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
 
 fn main() -> io::Result<()> {
     let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let freetype = env::var_os("CARGO_FEATURE_FREETYPE").is_some();
 
     cfg_if::cfg_if! {
         if #[cfg(target_os = "windows")] {
-            let cmake = Config::new(manifest_dir.join("third-party/cimgui"))
+            let mut config = Config::new(manifest_dir.join("third-party/cimgui"));
+            config
                 .define("CMAKE_BUILD_TYPE", "Release")
                 .define("IMGUI_STATIC", "ON")
-                .build();
+                .define("IMGUI_FREETYPE", if freetype { "ON" } else { "OFF" });
+
+            // Keep stb_truetype available, FreeType is only used when selected at runtime
+            if freetype {
+                config.cxxflag("-DIMGUI_ENABLE_STB_TRUETYPE");
+            }
+
+            let cmake = config.build();
 
             println!("cargo:rustc-link-search=native={}", cmake.display());
         } else {
@@ -32,12 +44,30 @@ fn main() -> io::Result<()> {
                 .flag("-fno-exceptions")
                 .flag("-std=c++17")
                 .cpp_set_stdlib("c++")
-                .cpp_link_stdlib("c++")
-                .compile("cimgui");
+                .cpp_link_stdlib("c++");
+
+            if freetype {
+                // FreeType headers are found in FREETYPE_INCLUDE_DIR or the usual system location
+                let freetype_include_dir = env::var_os("FREETYPE_INCLUDE_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from("/usr/include/freetype2"));
+
+                build
+                    .file(imgui_dir.join("misc/freetype/imgui_freetype.cpp"))
+                    .include(freetype_include_dir)
+                    .define("IMGUI_ENABLE_FREETYPE", None)
+                    .define("IMGUI_ENABLE_STB_TRUETYPE", None)
+                    .define("CIMGUI_FREETYPE", None);
+            }
+
+            build.compile("cimgui");
         }
     }
 
     println!("cargo:rustc-link-lib=static=cimgui");
+    if freetype {
+        println!("cargo:rustc-link-lib=freetype");
+    }
 
     let bindings = bindgen::Builder::default()
         .header(manifest_dir.join("cimgui-bindgen.h").to_string_lossy())
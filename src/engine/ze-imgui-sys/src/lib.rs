@@ -2,11 +2,18 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(clippy::all)]
+#![allow(unnecessary_transmutes)]
 
 use std::ops::Add;
 
 include!("./bindings.rs");
 
+// Only exported by cimgui when built with FreeType
+#[cfg(feature = "freetype")]
+extern "C" {
+    pub fn ImGuiFreeType_GetBuilderForFreeType() -> *const ImFontBuilderIO;
+}
+
 impl ImVec2 {
     pub fn new(x: f32, y: f32) -> Self {
         Self { x, y }
@@ -52,3 +59,15 @@ impl From<f32> for ImVec4 {
         }
     }
 }
+
+impl From<[f32; 4]> for ImVec4 {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
+impl From<ImVec4> for [f32; 4] {
+    fn from(vec: ImVec4) -> Self {
+        [vec.x, vec.y, vec.z, vec.w]
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{Context, WindowFlags};
+use ze_imgui_sys::*;
+
+/// Call the ImGui end function of a scope when dropped, including on early returns and panics
+struct ScopeEnd(unsafe extern "C" fn());
+
+impl Drop for ScopeEnd {
+    fn drop(&mut self) {
+        unsafe { (self.0)() }
+    }
+}
+
+/// Window whose content is submitted by a closure, created by [`Context::window`]
+#[must_use]
+pub struct WindowBuilder<'a> {
+    context: &'a mut Context,
+    name: &'a str,
+    flags: WindowFlags,
+    open: Option<&'a mut bool>,
+}
+
+impl<'a> WindowBuilder<'a> {
+    pub(crate) fn new(context: &'a mut Context, name: &'a str) -> Self {
+        Self {
+            context,
+            name,
+            flags: WindowFlags::empty(),
+            open: None,
+        }
+    }
+
+    pub fn flags(mut self, flags: WindowFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Show a close button, `open` is set to false when clicked
+    pub fn open(mut self, open: &'a mut bool) -> Self {
+        self.open = Some(open);
+        self
+    }
+
+    /// Submit the window, `f` is only called when the window is visible
+    pub fn build<R, F: FnOnce(&mut Context) -> R>(self, f: F) -> Option<R> {
+        let visible = match self.open {
+            Some(open) => self
+                .context
+                .begin_window_closable(self.name, open, self.flags),
+            None => self.context.begin_window(self.name, self.flags),
+        };
+
+        // igEnd must be called even if the window is collapsed or clipped
+        let _end = ScopeEnd(igEnd);
+        visible.then(|| f(self.context))
+    }
+}
+
+/// Child window whose content is submitted by a closure, created by [`Context::child`]
+#[must_use]
+pub struct ChildBuilder<'a> {
+    context: &'a mut Context,
+    id: &'a str,
+    size: ImVec2,
+    border: bool,
+    flags: WindowFlags,
+}
+
+impl<'a> ChildBuilder<'a> {
+    pub(crate) fn new(context: &'a mut Context, id: &'a str) -> Self {
+        Self {
+            context,
+            id,
+            size: ImVec2::default(),
+            border: false,
+            flags: WindowFlags::empty(),
+        }
+    }
+
+    /// Zero uses the remaining space of the parent window, negative values are relative to it
+    pub fn size(mut self, size: ImVec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn border(mut self, border: bool) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn flags(mut self, flags: WindowFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Submit the child window, `f` is only called when the child window is visible
+    pub fn build<R, F: FnOnce(&mut Context) -> R>(self, f: F) -> Option<R> {
+        let visible = self
+            .context
+            .begin_child(self.id, self.size, self.border, self.flags);
+
+        // Like igEnd, igEndChild must be called even if the child window is clipped
+        let _end = ScopeEnd(igEndChild);
+        visible.then(|| f(self.context))
+    }
+}
+
+/// Popup whose content is submitted by a closure, created by [`Context::popup`]
+#[must_use]
+pub struct PopupBuilder<'a> {
+    context: &'a mut Context,
+    id: &'a str,
+    flags: WindowFlags,
+    modal: Option<&'a mut bool>,
+}
+
+impl<'a> PopupBuilder<'a> {
+    pub(crate) fn new(context: &'a mut Context, id: &'a str) -> Self {
+        Self {
+            context,
+            id,
+            flags: WindowFlags::empty(),
+            modal: None,
+        }
+    }
+
+    pub fn flags(mut self, flags: WindowFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Block interactions with other windows and show a close button, `open` is set to false
+    /// when clicked
+    pub fn modal(mut self, open: &'a mut bool) -> Self {
+        self.modal = Some(open);
+        self
+    }
+
+    /// Submit the popup, `f` is only called when the popup is open
+    pub fn build<R, F: FnOnce(&mut Context) -> R>(self, f: F) -> Option<R> {
+        let open = match self.modal {
+            Some(open) => self.context.begin_popup_modal(self.id, open, self.flags),
+            None => self.context.begin_popup(self.id, self.flags),
+        };
+
+        if open {
+            let _end = ScopeEnd(igEndPopup);
+            Some(f(self.context))
+        } else {
+            None
+        }
+    }
+}
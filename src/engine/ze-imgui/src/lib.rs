@@ -1,21 +1,60 @@
+// ImGui enum constants are c_int or c_uint depending on the platform bindgen ran on
+#![allow(clippy::unnecessary_cast)]
+
+use crate::performance_overlay::PerformanceOverlay;
 use crate::renderer::{SwapChainType, ViewportRendererData};
 use crate::str_buffer::StrBuffer;
 use enumflags2::*;
+use parking_lot::Mutex;
 use std::alloc::Layout;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
 use std::mem::{size_of, MaybeUninit};
+use std::ops::{Range, RangeInclusive};
 use std::os::raw::*;
 use std::ptr::null_mut;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::{mem, slice};
 use ze_core::maths::{Matrix4x4, Point2, RectI32, Vector2};
-use ze_core::{ze_verbose};
+use ze_core::{ze_error, ze_verbose};
+use ze_filesystem::path::Path;
+use ze_filesystem::FileSystem;
 use ze_gfx::backend::*;
 use ze_gfx::{utils, PixelFormat, SampleDesc};
 use ze_imgui_sys::*;
-use ze_platform::{Cursor, KeyCode, Message, MouseButton, Platform, SystemCursor, Window};
+use ze_platform::{
+    Cursor, HitTest, KeyCode, Message, ModifierBits, MouseButton, Platform, SystemCursor, Window,
+};
 use ze_shader_system::ShaderManager;
 
+const INI_SETTINGS_FILE_NAME: &str = "imgui.ini";
+
+/// Thickness of the area around secondary viewports used to resize them, before DPI scaling
+const VIEWPORT_RESIZE_BORDER: f32 = 4.0;
+
+/// Texture registered with [`Context::register_texture`], usable by image widgets
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TextureId(u64);
+
+impl TextureId {
+    /// The null texture id is reserved for the font atlas
+    fn as_imgui(self) -> ImTextureID {
+        self.0 as ImTextureID
+    }
+}
+
+struct RegisteredTexture {
+    srv: Arc<ShaderResourceView>,
+
+    /// Still usable by the draw data of the current frame, the next `begin_frame` keeps it until
+    /// the GPU is done with that draw data
+    unregistered: bool,
+}
+
 #[repr(transparent)]
 pub struct Viewport {
     viewport: ImGuiViewport,
@@ -30,6 +69,15 @@ impl Viewport {
         }
         center
     }
+
+    /// Position of the viewport area not covered by the main menu bar and side bars
+    pub fn work_pos(&self) -> ImVec2 {
+        self.viewport.WorkPos
+    }
+
+    pub fn work_size(&self) -> ImVec2 {
+        self.viewport.WorkSize
+    }
     
     fn draw_data(&mut self) -> *mut ImDrawData {
         self.viewport.DrawData
@@ -54,22 +102,132 @@ pub struct Context {
     platform: Arc<dyn Platform>,
     str_buffer: StrBuffer,
     context: *mut ImGuiContext,
-    _font_texture: Arc<Texture>,
-    font_texture_view: ShaderResourceView,
+
+    /// Built by [`Context::build_fonts`], holds the font atlas texture
+    font_texture_view: Option<ShaderResourceView>,
+    fonts: Vec<FontEntry>,
+
+    /// Scale of the monitor displaying the main viewport, fonts and style are scaled by it
+    dpi_scale: f32,
+
+    /// Style at a 1.0 DPI scale
+    base_style: ImGuiStyle,
+
+    /// Filesystem and directory where the ini settings are persisted
+    ini_settings_location: Option<(Arc<FileSystem>, Path)>,
+
+    textures: HashMap<TextureId, RegisteredTexture>,
+    next_texture_id: u64,
+
+    /// Textures registered with `frame_texture`, unregistered by `end_frame`
+    frame_textures: Vec<TextureId>,
+
+    /// Unregistered textures the GPU may still read, released once `texture_release_fence`
+    /// reaches its last signal
+    released_textures: Vec<Arc<ShaderResourceView>>,
+    texture_release_fence: Fence,
     sampler: Sampler,
     cursors: [Box<dyn Cursor>; ImGuiMouseCursor__ImGuiMouseCursor_COUNT as usize],
+
+    /// Last text read from the platform clipboard, must outlive the pointer returned to ImGui
+    clipboard_text: RefCell<CString>,
+
+    /// Buffers of `draw_to_texture`, a viewport drawn to its swapchain keeps its own buffers
+    offscreen_renderer_data: RefCell<ViewportRendererData>,
+
+    /// Item hovered by `item_tooltip_delayed`
+    tooltip_hover: Option<TooltipHover>,
+
+    /// Text edited by open `text_prompt` dialogs
+    dialog_texts: HashMap<String, String>,
+
+    performance_overlay: Option<PerformanceOverlay>,
+
+    /// Text being composed with an input method, drawn over the focused text field
+    text_composition: String,
+
+    /// Top left of the text cursor of the focused text field, set by ImGui
+    ime_position: Cell<Option<ImVec2>>,
+
+    /// Bits of the mouse buttons held down, the mouse is captured while any is held so drags
+    /// continue outside of the window
+    held_mouse_buttons: u32,
+
+    /// Window the mouse is over, `None` when it is outside of all the viewports
+    hovered_window: Option<Weak<dyn Window>>,
+
+    /// Switch between the light and dark themes with the system, see `follow_system_theme`
+    follows_system_theme: bool,
+}
+
+struct TooltipHover {
+    /// Items are identified by their rect as non-interactive items have no id
+    rect: [f32; 4],
+    time: f32,
+    frame: c_int,
+}
+
+/// Rasterizer used to build the font atlas
+#[derive(Copy, Clone, Default)]
+pub enum FontRasterizer {
+    #[default]
+    StbTrueType,
+
+    /// Better hinting and rendering of small sizes, can load color fonts (e.g emojis)
+    #[cfg(feature = "freetype")]
+    FreeType(FreeTypeFlags),
+}
+
+#[cfg(feature = "freetype")]
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum FreeTypeFlagBits {
+    NoHinting = 1 << 0,
+    NoAutoHint = 1 << 1,
+    ForceAutoHint = 1 << 2,
+    LightHinting = 1 << 3,
+    MonoHinting = 1 << 4,
+    Bold = 1 << 5,
+    Oblique = 1 << 6,
+    Monochrome = 1 << 7,
+    LoadColor = 1 << 8,
+    Bitmap = 1 << 9,
 }
 
+#[cfg(feature = "freetype")]
+pub type FreeTypeFlags = BitFlags<FreeTypeFlagBits>;
+
 impl Context {
     pub fn new(
         device: Arc<dyn Device>,
         shader_manager: Arc<ShaderManager>,
         platform: Arc<dyn Platform>,
         main_window: Arc<dyn Window>,
+        font_rasterizer: FontRasterizer,
     ) -> Box<Self> {
-        let context = unsafe { igCreateContext(null_mut()) };
+        // `igCreateContext` restores the previous current context, if any
+        let context = unsafe {
+            let context = igCreateContext(null_mut());
+            igSetCurrentContext(context);
+            context
+        };
 
         let io = unsafe { igGetIO().as_mut().unwrap_unchecked() };
+        unsafe {
+            let atlas = io.Fonts.as_mut().unwrap_unchecked();
+            match font_rasterizer {
+                FontRasterizer::StbTrueType => {
+                    atlas.FontBuilderIO = igImFontAtlasGetBuilderForStbTruetype();
+                }
+                #[cfg(feature = "freetype")]
+                FontRasterizer::FreeType(flags) => {
+                    atlas.FontBuilderIO = ImGuiFreeType_GetBuilderForFreeType();
+                    atlas.FontBuilderFlags = flags.bits();
+                }
+            }
+        }
+
         io.ConfigFlags |= ImGuiConfigFlags__ImGuiConfigFlags_ViewportsEnable as i32;
         io.ConfigFlags |= ImGuiConfigFlags__ImGuiConfigFlags_DockingEnable as i32;
         io.BackendFlags |= ImGuiBackendFlags__ImGuiBackendFlags_HasMouseCursors as i32;
@@ -77,18 +235,7 @@ impl Context {
         io.BackendFlags |= ImGuiBackendFlags__ImGuiBackendFlags_RendererHasViewports as i32;
         io.BackendFlags |= ImGuiBackendFlags__ImGuiBackendFlags_RendererHasVtxOffset as i32;
 
-        unsafe {
-            let file = CString::new("assets/Inter-SemiBold.ttf").unwrap();
-            ImFontAtlas_AddFontFromFileTTF(
-                io.Fonts,
-                file.as_ptr(),
-                16.0,
-                std::ptr::null(),
-                std::ptr::null(),
-            );
-        }
-
-        let mut platform_io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
+        let platform_io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
         platform_io.Platform_CreateWindow = Some(platform_create_window);
         platform_io.Platform_DestroyWindow = Some(platform_destroy_window);
         //platform_io.Platform_GetWindowPos = Some(platform_get_window_pos);
@@ -96,7 +243,12 @@ impl Context {
         platform_io.Platform_SetWindowPos = Some(platform_set_window_pos);
         platform_io.Platform_SetWindowSize = Some(platform_set_window_size);
         platform_io.Platform_SetWindowTitle = Some(platform_set_window_title);
+        platform_io.Platform_SetWindowAlpha = Some(platform_set_window_alpha);
         platform_io.Platform_ShowWindow = Some(platform_show_window);
+        platform_io.Platform_SetWindowFocus = Some(platform_set_window_focus);
+        platform_io.Platform_GetWindowFocus = Some(platform_get_window_focus);
+        platform_io.Platform_GetWindowMinimized = Some(platform_get_window_minimized);
+        platform_io.Platform_GetWindowDpiScale = Some(platform_get_window_dpi_scale);
 
         platform_io.Renderer_CreateWindow = Some(renderer_create_window);
         platform_io.Renderer_DestroyWindow = Some(renderer_destroy_window);
@@ -119,59 +271,9 @@ impl Context {
             .create_sampler(&SamplerDesc::default())
             .expect("Cannot create ImGui sampler");
 
-        // Build font texture
-        let font_texture = unsafe {
-            let io = igGetIO().as_mut().unwrap_unchecked();
-            let mut pixels = null_mut();
-            let mut width = 0;
-            let mut height = 0;
-            ImFontAtlas_GetTexDataAsRGBA32(
-                io.Fonts,
-                &mut pixels,
-                &mut width,
-                &mut height,
-                null_mut(),
-            );
-
-            let texture = device
-                .create_texture(
-                    &TextureDesc {
-                        width: width as u32,
-                        height: height as u32,
-                        depth: 1,
-                        mip_levels: 1,
-                        format: PixelFormat::R8G8B8A8Unorm,
-                        sample_desc: Default::default(),
-                        usage_flags: TextureUsageFlags::default(),
-                        memory_desc: MemoryDesc { memory_location: MemoryLocation::GpuOnly, memory_flags: Default::default() }
-                    },
-                    None,
-                    "ImGui Font texture",
-                )
-                .expect("Failed to create ImGui font texture");
-
-            utils::copy_data_to_texture(
-                &device,
-                slice::from_raw_parts(pixels, (width * height * 4) as usize),
-                width as u32,
-                height as u32,
-                4,
-                &texture,
-                ResourceState::Common,
-            )
-            .expect("Failed to copy font texture data");
-
-            Arc::new(texture)
-        };
-
-        let font_texture_view = device
-            .create_shader_resource_view(&ShaderResourceViewDesc::Texture2D(Texture2DSRV {
-                texture: font_texture.clone(),
-                format: PixelFormat::R8G8B8A8Unorm,
-                min_mip_level: 0,
-                mip_levels: 1
-            }))
-            .expect("Failed to create ImGui font texture view");
+        let texture_release_fence = device
+            .create_fence("ImGui Texture Release")
+            .expect("Cannot create ImGui texture release fence");
 
         let cursors = [
             platform.create_system_cursor(SystemCursor::Arrow),
@@ -191,20 +293,45 @@ impl Context {
             platform,
             str_buffer: StrBuffer::default(),
             context,
-            _font_texture: font_texture,
             sampler,
-            font_texture_view,
+            font_texture_view: None,
+            fonts: vec![],
+            dpi_scale: 1.0,
+            base_style: unsafe { *igGetStyle() },
+            ini_settings_location: None,
+            textures: HashMap::default(),
+            next_texture_id: 1,
+            frame_textures: vec![],
+            released_textures: vec![],
+            texture_release_fence,
             cursors,
+            clipboard_text: RefCell::default(),
+            offscreen_renderer_data: RefCell::default(),
+            tooltip_hover: None,
+            dialog_texts: HashMap::default(),
+            performance_overlay: None,
+            text_composition: String::new(),
+            ime_position: Cell::new(None),
+            held_mouse_buttons: 0,
+            hovered_window: None,
+            follows_system_theme: false,
         });
 
         io.UserData = (context.as_mut() as *mut Context) as *mut c_void;
 
+        // Settings are persisted through the filesystem, see `set_ini_settings_directory`
+        io.IniFilename = std::ptr::null();
+        io.ClipboardUserData = io.UserData;
+        io.GetClipboardTextFn = Some(platform_get_clipboard_text);
+        io.SetClipboardTextFn = Some(platform_set_clipboard_text);
+        io.SetPlatformImeDataFn = Some(platform_set_ime_data);
+
         // Create main resources for main viewport
         unsafe {
             platform_create_window(igGetMainViewport());
             let platform_data =
                 (*igGetMainViewport()).PlatformUserData as *mut ViewportPlatformData;
-            platform_data.write(ViewportPlatformData::new(main_window));
+            platform_data.write(ViewportPlatformData::new(context.as_ref(), main_window));
 
             renderer_create_window(igGetMainViewport());
             let renderer_data =
@@ -215,100 +342,12 @@ impl Context {
         // Default ZE style
         {
             let style = unsafe { igGetStyle().as_mut().unwrap_unchecked() };
-            style.WindowRounding = 0.0;
-            style.FrameRounding = 3.0;
-            style.TabRounding = 2.0;
-            style.ScrollbarRounding = 0.0;
             style.WindowMenuButtonPosition = ImGuiDir__ImGuiDir_Right;
             style.TabMinWidthForCloseButton = 0.0;
-            style.CellPadding = ImVec2::new(1.0, 0.0);
-            style.WindowPadding = ImVec2::new(3.0, 1.0);
-            style.ItemSpacing = ImVec2::new(8.0, 4.0);
-            style.IndentSpacing = 9.0;
-            style.WindowBorderSize = 0.0;
-            style.FrameBorderSize = 0.0;
-            style.PopupBorderSize = 1.0;
-            style.TabBorderSize = 1.0;
-
-            let colors = &mut style.Colors;
-
-            colors[ImGuiCol__ImGuiCol_Text as usize] = ImVec4::new(0.79, 0.79, 0.79, 1.0);
-            colors[ImGuiCol__ImGuiCol_TextDisabled as usize] = ImVec4::new(0.50, 0.50, 0.50, 1.0);
-            colors[ImGuiCol__ImGuiCol_WindowBg as usize] = ImVec4::new(0.07, 0.07, 0.07, 1.00);
-            colors[ImGuiCol__ImGuiCol_ChildBg as usize] = ImVec4::new(0.14, 0.14, 0.14, 1.00);
-            colors[ImGuiCol__ImGuiCol_PopupBg as usize] = ImVec4::new(0.20, 0.20, 0.20, 0.94);
-            colors[ImGuiCol__ImGuiCol_Border as usize] = ImVec4::new(0.09, 0.09, 0.09, 1.0);
-            colors[ImGuiCol__ImGuiCol_BorderShadow as usize] = ImVec4::new(0.00, 0.00, 0.00, 0.00);
-            colors[ImGuiCol__ImGuiCol_FrameBg as usize] = ImVec4::new(0.09, 0.09, 0.09, 1.0);
-            colors[ImGuiCol__ImGuiCol_FrameBgHovered as usize] = ImVec4::new(0.05, 0.05, 0.05, 1.0);
-            colors[ImGuiCol__ImGuiCol_FrameBgActive as usize] = ImVec4::new(0.33, 0.33, 0.33, 0.67);
-            colors[ImGuiCol__ImGuiCol_TitleBg as usize] = ImVec4::new(0.16, 0.16, 0.16, 1.00);
-            colors[ImGuiCol__ImGuiCol_TitleBgActive as usize] = ImVec4::new(0.16, 0.16, 0.16, 1.00);
-            colors[ImGuiCol__ImGuiCol_TitleBgCollapsed as usize] =
-                ImVec4::new(0.00, 0.00, 0.00, 0.51);
-            colors[ImGuiCol__ImGuiCol_MenuBarBg as usize] = ImVec4::new(0.14, 0.14, 0.14, 1.00);
-            colors[ImGuiCol__ImGuiCol_ScrollbarBg as usize] = ImVec4::new(0.02, 0.02, 0.02, 0.53);
-            colors[ImGuiCol__ImGuiCol_ScrollbarGrab as usize] = ImVec4::new(0.31, 0.31, 0.31, 1.00);
-            colors[ImGuiCol__ImGuiCol_ScrollbarGrabHovered as usize] =
-                ImVec4::new(0.41, 0.41, 0.41, 1.00);
-            colors[ImGuiCol__ImGuiCol_ScrollbarGrabActive as usize] =
-                ImVec4::new(0.51, 0.51, 0.51, 1.00);
-            colors[ImGuiCol__ImGuiCol_CheckMark as usize] = ImVec4::new(0.71, 0.71, 0.71, 1.00);
-            colors[ImGuiCol__ImGuiCol_SliderGrab as usize] = ImVec4::new(0.29, 0.29, 0.29, 1.00);
-            colors[ImGuiCol__ImGuiCol_SliderGrabActive as usize] =
-                ImVec4::new(0.26, 0.26, 0.26, 1.00);
-            colors[ImGuiCol__ImGuiCol_Button as usize] = ImVec4::new(0.29, 0.29, 0.29, 0.40);
-            colors[ImGuiCol__ImGuiCol_ButtonHovered as usize] = ImVec4::new(0.26, 0.26, 0.26, 1.00);
-            colors[ImGuiCol__ImGuiCol_ButtonActive as usize] = ImVec4::new(0.23, 0.23, 0.23, 1.00);
-            colors[ImGuiCol__ImGuiCol_Header as usize] = ImVec4::from(0.115);
-            colors[ImGuiCol__ImGuiCol_HeaderHovered as usize] = ImVec4::new(0.27, 0.33, 0.43, 0.45);
-            colors[ImGuiCol__ImGuiCol_HeaderActive as usize] = ImVec4::new(0.27, 0.33, 0.63, 1.00);
-            colors[ImGuiCol__ImGuiCol_Separator as usize] = ImVec4::new(0.25, 0.25, 0.25, 1.0);
-            colors[ImGuiCol__ImGuiCol_SeparatorHovered as usize] =
-                ImVec4::new(0.15, 0.14, 0.16, 1.00);
-            colors[ImGuiCol__ImGuiCol_SeparatorActive as usize] =
-                ImVec4::new(0.14, 0.13, 0.16, 1.00);
-            colors[ImGuiCol__ImGuiCol_ResizeGrip as usize] = ImVec4::new(0.00, 0.00, 0.00, 0.25);
-            colors[ImGuiCol__ImGuiCol_ResizeGripHovered as usize] =
-                ImVec4::new(0.11, 0.11, 0.11, 0.67);
-            colors[ImGuiCol__ImGuiCol_ResizeGripActive as usize] =
-                ImVec4::new(0.00, 0.00, 0.00, 0.95);
-            colors[ImGuiCol__ImGuiCol_Tab as usize] = ImVec4::new(0.078, 0.078, 0.078, 1.0);
-            colors[ImGuiCol__ImGuiCol_TabHovered as usize] = ImVec4::new(0.29, 0.29, 0.29, 0.80);
-            colors[ImGuiCol__ImGuiCol_TabActive as usize] = ImVec4::new(0.14, 0.14, 0.14, 1.00);
-            colors[ImGuiCol__ImGuiCol_TabUnfocused as usize] = ImVec4::new(0.24, 0.24, 0.24, 0.97);
-            colors[ImGuiCol__ImGuiCol_TabUnfocusedActive as usize] =
-                ImVec4::new(0.24, 0.24, 0.24, 1.00);
-            colors[ImGuiCol__ImGuiCol_DockingPreview as usize] =
-                ImVec4::new(0.26, 0.59, 0.98, 0.70);
-            colors[ImGuiCol__ImGuiCol_DockingEmptyBg as usize] =
-                ImVec4::new(0.12, 0.12, 0.12, 1.00);
-            colors[ImGuiCol__ImGuiCol_PlotLines as usize] = ImVec4::new(0.61, 0.61, 0.61, 1.00);
-            colors[ImGuiCol__ImGuiCol_PlotLinesHovered as usize] =
-                ImVec4::new(1.00, 0.43, 0.35, 1.00);
-            colors[ImGuiCol__ImGuiCol_PlotHistogram as usize] = ImVec4::new(0.90, 0.70, 0.00, 1.00);
-            colors[ImGuiCol__ImGuiCol_PlotHistogramHovered as usize] =
-                ImVec4::new(1.00, 0.60, 0.00, 1.00);
-            colors[ImGuiCol__ImGuiCol_TableHeaderBg as usize] = ImVec4::new(0.19, 0.19, 0.20, 1.00);
-            colors[ImGuiCol__ImGuiCol_TableBorderStrong as usize] =
-                ImVec4::new(0.31, 0.31, 0.35, 1.00);
-            colors[ImGuiCol__ImGuiCol_TableBorderLight as usize] =
-                ImVec4::new(0.10, 0.10, 0.10, 1.00);
-            colors[ImGuiCol__ImGuiCol_TableRowBg as usize] = ImVec4::new(0.00, 0.00, 0.00, 0.00);
-            colors[ImGuiCol__ImGuiCol_TableRowBgAlt as usize] = ImVec4::new(1.00, 1.00, 1.00, 0.06);
-            colors[ImGuiCol__ImGuiCol_TextSelectedBg as usize] =
-                ImVec4::new(0.26, 0.59, 0.98, 0.35);
-            colors[ImGuiCol__ImGuiCol_DragDropTarget as usize] =
-                ImVec4::new(1.00, 1.00, 0.00, 0.90);
-            colors[ImGuiCol__ImGuiCol_NavHighlight as usize] = ImVec4::new(0.26, 0.59, 0.98, 1.00);
-            colors[ImGuiCol__ImGuiCol_NavWindowingHighlight as usize] =
-                ImVec4::new(1.00, 1.00, 1.00, 0.70);
-            colors[ImGuiCol__ImGuiCol_NavWindowingDimBg as usize] =
-                ImVec4::new(0.80, 0.80, 0.80, 0.20);
-            colors[ImGuiCol__ImGuiCol_ModalWindowDimBg as usize] =
-                ImVec4::new(0.80, 0.80, 0.80, 0.0);
+            Theme::dark().apply(style);
         }
 
+        context.base_style = unsafe { *igGetStyle() };
         context.update_monitors();
         context
     }
@@ -316,33 +355,95 @@ impl Context {
 
 impl Drop for Context {
     fn drop(&mut self) {
+        self.make_current();
+        self.save_ini_settings_file();
         unsafe { igDestroyContext(self.context) };
     }
 }
 
 impl Context {
+    /// Make this context the one used by ImGui functions
+    ///
+    /// Multiple contexts can coexist (e.g editor and in-game debug UI), frames and methods
+    /// called outside of a frame make their context current
+    pub fn make_current(&self) {
+        unsafe { igSetCurrentContext(self.context) };
+    }
+
+    pub fn is_current(&self) -> bool {
+        unsafe { igGetCurrentContext() == self.context }
+    }
+
     pub fn begin_frame(
         &mut self,
         delta_time: f32,
         mouse_position: Point2<i32>,
         main_viewport_window: &dyn Window,
     ) {
-        unsafe {
-            igSetCurrentContext(self.context);
+        self.make_current();
+        self.str_buffer.reset();
+
+        // Draw data of the previous frame has been submitted but may still be executing, textures
+        // unregistered during that frame are released once the GPU reaches a signal submitted
+        // after it
+        if self.device.is_fence_signaled(&self.texture_release_fence) {
+            self.released_textures.clear();
+        }
+
+        let released_count = self.released_textures.len();
+        self.textures.retain(|_, texture| {
+            if texture.unregistered {
+                self.released_textures.push(texture.srv.clone());
+            }
+            !texture.unregistered
+        });
+
+        if self.released_textures.len() != released_count {
+            self.device.submit(
+                QueueType::Graphics,
+                &[],
+                &[],
+                &[&self.texture_release_fence],
+            );
         }
 
-        let mut io = unsafe { igGetIO().as_mut().unwrap_unchecked() };
+        let io = unsafe { igGetIO().as_mut().unwrap_unchecked() };
 
         io.DeltaTime = delta_time;
+        if let Some(performance_overlay) = &mut self.performance_overlay {
+            performance_overlay.add_frame_time(delta_time);
+        }
+
         io.DisplaySize = ImVec2 {
             x: main_viewport_window.width() as f32,
             y: main_viewport_window.height() as f32,
         };
-        io.MousePos = ImVec2 {
-            x: mouse_position.x as f32,
-            y: mouse_position.y as f32,
+        // Items are not hovered once the mouse left our windows, unless it is dragging
+        io.MousePos = if self.hovered_window.is_none() && self.held_mouse_buttons == 0 {
+            ImVec2 {
+                x: -f32::MAX,
+                y: -f32::MAX,
+            }
+        } else {
+            ImVec2 {
+                x: mouse_position.x as f32,
+                y: mouse_position.y as f32,
+            }
         };
 
+        // Modifiers are polled, their key events may have been received by another application
+        let modifiers = self.platform.keyboard_modifiers();
+        for (key, modifier) in [
+            (Key::ModCtrl, ModifierBits::Control),
+            (Key::ModShift, ModifierBits::Shift),
+            (Key::ModAlt, ModifierBits::Alt),
+            (Key::ModSuper, ModifierBits::Super),
+        ] {
+            unsafe {
+                ImGuiIO_AddKeyEvent(igGetIO(), key as ImGuiKey, modifiers.contains(modifier));
+            }
+        }
+
         // Update cursor
         let cursor = unsafe { igGetMouseCursor() };
 
@@ -353,140 +454,477 @@ impl Context {
                 .set_cursor(Some(&*self.cursors[cursor as usize]));
         }
 
+        // ImGui asks the platform the DPI scale of each viewport
+        let dpi_scale = unsafe { (*igGetMainViewport()).DpiScale };
+        if dpi_scale > 0.0 && dpi_scale != self.dpi_scale {
+            self.set_dpi_scale(dpi_scale);
+        }
+
+        // Fonts added since the last frame invalidate the atlas
+        if unsafe { !ImFontAtlas_IsBuilt(io.Fonts) } {
+            self.build_fonts();
+        }
+
+        self.update_platform_windows();
+
         unsafe {
             igNewFrame();
         }
     }
 
     pub fn send_platform_message(&mut self, message: &Message) {
-        let mut io = unsafe { igGetIO().as_mut().unwrap_unchecked() };
-
+        self.make_current();
         match message {
-            Message::MouseButtonDown(_, button, _) => {
-                io.MouseDown[*button as usize] = true;
+            Message::MouseButtonDown(window, button, _)
+            | Message::MouseButtonDoubleClick(window, button, _) => {
+                if self.held_mouse_buttons == 0 {
+                    if let Some(window) = window.upgrade() {
+                        self.platform.capture_mouse(&*window);
+                    }
+                }
+                self.held_mouse_buttons |= 1 << imgui_mouse_button(*button);
+
+                unsafe {
+                    ImGuiIO_AddMouseButtonEvent(igGetIO(), imgui_mouse_button(*button), true)
+                };
             }
             Message::MouseButtonUp(_, button, _) => {
-                io.MouseDown[*button as usize] = false;
-            }
-            Message::MouseButtonDoubleClick(_, button, _) => {
-                io.MouseDown[*button as usize] = true;
+                self.held_mouse_buttons &= !(1 << imgui_mouse_button(*button));
+                if self.held_mouse_buttons == 0 {
+                    self.platform.release_mouse();
+                }
+
+                unsafe {
+                    ImGuiIO_AddMouseButtonEvent(igGetIO(), imgui_mouse_button(*button), false)
+                };
             }
             Message::MouseWheel(_, delta, _) => {
-                io.MouseWheel += delta;
+                unsafe { ImGuiIO_AddMouseWheelEvent(igGetIO(), 0.0, *delta) };
             },
-            Message::KeyDown(_, key, _, _) => {
+            Message::MouseWheelH(_, delta, _) => {
+                // ImGui scrolls to the left with positive values
+                unsafe { ImGuiIO_AddMouseWheelEvent(igGetIO(), -*delta, 0.0) };
+            },
+            Message::KeyDown(_, key, _, _, _) => {
                 unsafe { ImGuiIO_AddKeyEvent(igGetIO(), Key::from(*key) as ImGuiKey, true) };
             },
-            Message::KeyUp(_, key, _, _) => {
+            Message::KeyUp(_, key, _, _, _) => {
                 unsafe { ImGuiIO_AddKeyEvent(igGetIO(), Key::from(*key) as ImGuiKey, false) };
             }
+            Message::TextInput(_, character) => {
+                unsafe { ImGuiIO_AddInputCharacter(igGetIO(), *character as c_uint) };
+            }
+            Message::TextComposition(_, text) => {
+                self.text_composition = text.clone();
+            }
+            Message::WindowDpiChanged(_, _) | Message::MonitorsChanged => {
+                self.update_monitors();
+            }
+            Message::WindowFocusGained(_) => {
+                unsafe { ImGuiIO_AddFocusEvent(igGetIO(), true) };
+            }
+            Message::WindowFocusLost(_) => {
+                unsafe { ImGuiIO_AddFocusEvent(igGetIO(), false) };
+            }
+            Message::WindowCloseRequested(window) => self.request_viewport_close(window),
+            Message::SystemThemeChanged(theme) if self.follows_system_theme => {
+                self.apply_theme(&Theme::from_system_theme(*theme));
+            }
+            Message::MouseEntered(window) => self.hovered_window = Some(window.clone()),
+            Message::MouseLeft(window) => {
+                // Entering another window may be reported before leaving this one
+                if matches!(&self.hovered_window, Some(hovered) if Weak::ptr_eq(hovered, window)) {
+                    self.hovered_window = None;
+                }
+            }
             _ => {}
         }
     }
 
     pub fn end_frame(&mut self) {
+        self.make_current();
+        self.draw_text_composition();
+        if let Some(mut performance_overlay) = self.performance_overlay.take() {
+            performance_overlay.draw(self);
+            self.performance_overlay = Some(performance_overlay);
+        }
+
         unsafe {
             igRender();
             igUpdatePlatformWindows();
         }
+
+        if let Some(performance_overlay) = &mut self.performance_overlay {
+            performance_overlay.update_draw_stats();
+        }
+
+        for texture in mem::take(&mut self.frame_textures) {
+            self.unregister_texture(texture);
+        }
+
+        // Set periodically by ImGui when settings changed
+        let io = unsafe { igGetIO().as_mut().unwrap_unchecked() };
+        if io.WantSaveIniSettings {
+            io.WantSaveIniSettings = false;
+            self.save_ini_settings_file();
+        }
     }
 
-    pub fn draw_non_main_viewports(&mut self, cmd_list: &mut CommandList) {
-        let io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
-        let viewports =
-            unsafe { slice::from_raw_parts(io.Viewports.Data, io.Viewports.Size as usize) };
+    /// Render and present the secondary viewports through the renderer callbacks
+    pub fn render_platform_windows(&mut self) {
+        self.make_current();
+        unsafe { igRenderPlatformWindowsDefault(null_mut(), null_mut()) };
+    }
 
-        for viewport in viewports {
-            let viewport = unsafe { (*viewport as *mut Viewport).as_mut().unwrap_unchecked() };
-            let renderer_data = viewport.renderer_user_data() as *mut ViewportRendererData;
+    /// Record the draw commands of a secondary viewport into its swapchain backbuffer
+    fn draw_to_swapchain(&self, viewport: &mut Viewport, cmd_list: &mut CommandList) {
+        let renderer_data = viewport.renderer_user_data() as *mut ViewportRendererData;
+        if let SwapChainType::Owned((swapchain, views)) = unsafe { &(*renderer_data).swapchain } {
+            let swapchain = unsafe { swapchain.assume_init_ref() };
 
-            if viewport != self.main_viewport() {
-                if let SwapChainType::Owned((swapchain, views)) =
-                    unsafe { &(*renderer_data).swapchain }
-                {
-                    let swapchain = unsafe { swapchain.assume_init_ref() };
-
-                    let backbuffer_index = self.device.swapchain_backbuffer_index(swapchain);
-                    let backbuffer = self
-                        .device
-                        .swapchain_backbuffer(swapchain, backbuffer_index)
-                        .unwrap();
-
-                    self.device.cmd_resource_barrier(
-                        cmd_list,
-                        &[ResourceBarrier::Transition(ResourceTransitionBarrier {
-                            resource: ResourceTransitionBarrierResource::Texture(&backbuffer),
-                            source_state: ResourceState::Present,
-                            dest_state: ResourceState::RenderTargetWrite,
-                        })],
-                    );
-
-                    self.device.cmd_begin_render_pass(
-                        cmd_list,
-                        &RenderPassDesc {
-                            render_targets: &[RenderPassRenderTarget {
-                                render_target_view: &views[backbuffer_index as usize],
-                                load_mode: RenderPassTextureLoadMode::Clear,
-                                store_mode: RenderPassTextureStoreMode::Preserve,
-                                clear_value: ClearValue::Color([0.0, 0.0, 0.0, 1.0]),
-                            }],
-                            depth_stencil: None,
-                        },
-                    );
-
-                    draw_viewport_internal(
-                        viewport,
-                        &self.device,
-                        &self.shader_manager,
-                        &self.font_texture_view,
-                        &self.sampler,
-                        cmd_list,
-                    );
-
-                    self.device.cmd_end_render_pass(cmd_list);
-                    self.device.cmd_resource_barrier(
-                        cmd_list,
-                        &[ResourceBarrier::Transition(ResourceTransitionBarrier {
-                            resource: ResourceTransitionBarrierResource::Texture(&backbuffer),
-                            source_state: ResourceState::RenderTargetWrite,
-                            dest_state: ResourceState::Present,
-                        })],
-                    );
-                }
-            }
+            let backbuffer_index = self.device.swapchain_backbuffer_index(swapchain);
+            let backbuffer = self
+                .device
+                .swapchain_backbuffer(swapchain, backbuffer_index)
+                .unwrap();
+
+            self.device.cmd_resource_barrier(
+                cmd_list,
+                &[ResourceBarrier::Transition(ResourceTransitionBarrier {
+                    resource: ResourceTransitionBarrierResource::Texture(&backbuffer),
+                    source_state: ResourceState::Present,
+                    dest_state: ResourceState::RenderTargetWrite,
+                })],
+            );
+
+            self.device.cmd_begin_render_pass(
+                cmd_list,
+                &RenderPassDesc {
+                    render_targets: &[RenderPassRenderTarget {
+                        render_target_view: &views[backbuffer_index as usize],
+                        load_mode: RenderPassTextureLoadMode::Clear,
+                        store_mode: RenderPassTextureStoreMode::Preserve,
+                        clear_value: ClearValue::Color([0.0, 0.0, 0.0, 1.0]),
+                    }],
+                    depth_stencil: None,
+                },
+            );
+
+            self.draw_viewport(cmd_list, viewport);
+
+            self.device.cmd_end_render_pass(cmd_list);
+            self.device.cmd_resource_barrier(
+                cmd_list,
+                &[ResourceBarrier::Transition(ResourceTransitionBarrier {
+                    resource: ResourceTransitionBarrierResource::Texture(&backbuffer),
+                    source_state: ResourceState::RenderTargetWrite,
+                    dest_state: ResourceState::Present,
+                })],
+            );
         }
     }
 
     pub fn draw_viewport(&self, cmd_list: &mut CommandList, viewport: &mut Viewport) {
-        draw_viewport_internal(
-            viewport,
-            &self.device,
-            &self.shader_manager,
-            &self.font_texture_view,
-            &self.sampler,
+        let (draw_data, renderer_data) = unsafe {
+            (
+                viewport.draw_data().as_ref().unwrap_unchecked(),
+                (viewport.renderer_user_data() as *mut ViewportRendererData)
+                    .as_mut()
+                    .unwrap_unchecked(),
+            )
+        };
+        self.draw_internal(draw_data, renderer_data, cmd_list);
+    }
+
+    /// Draw a viewport into a render target instead of its swapchain (e.g UI displayed in the
+    /// world or captured by tests), `render_target` must be in the `RenderTargetWrite` state
+    /// The render target is cleared with `clear_color` if specified
+    pub fn draw_to_texture(
+        &self,
+        cmd_list: &mut CommandList,
+        viewport: &mut Viewport,
+        render_target: &RenderTargetView,
+        clear_color: Option<[f32; 4]>,
+    ) {
+        self.device.cmd_begin_render_pass(
+            cmd_list,
+            &RenderPassDesc {
+                render_targets: &[RenderPassRenderTarget {
+                    render_target_view: render_target,
+                    load_mode: if clear_color.is_some() {
+                        RenderPassTextureLoadMode::Clear
+                    } else {
+                        RenderPassTextureLoadMode::Preserve
+                    },
+                    store_mode: RenderPassTextureStoreMode::Preserve,
+                    clear_value: ClearValue::Color(clear_color.unwrap_or_default()),
+                }],
+                depth_stencil: None,
+            },
+        );
+
+        let draw_data = unsafe { viewport.draw_data().as_ref().unwrap_unchecked() };
+        self.draw_internal(
+            draw_data,
+            &mut self.offscreen_renderer_data.borrow_mut(),
             cmd_list,
         );
+
+        self.device.cmd_end_render_pass(cmd_list);
     }
 
-    pub fn present(&mut self) {
-        let io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
-        let viewports =
-            unsafe { slice::from_raw_parts(io.Viewports.Data, io.Viewports.Size as usize) };
-        for viewport in viewports {
-            if *viewport != unsafe { igGetMainViewport() } {
-                unsafe {
-                    let renderer_data =
-                        (*(*viewport)).RendererUserData as *mut ViewportRendererData;
-                    if let SwapChainType::Owned((swapchain, _)) = &(*renderer_data).swapchain {
-                        self.device.present(swapchain.assume_init_ref());
+    /// Record the draw commands of `draw_data` in the current render pass
+    fn draw_internal(
+        &self,
+        draw_data: &ImDrawData,
+        renderer_data: &mut ViewportRendererData,
+        cmd_list: &mut CommandList,
+    ) {
+        #[repr(C)]
+        struct ShaderData {
+            projection_matrix: Matrix4x4<f32>,
+            base_vertex_location: u32,
+            vertex_buffer: u32,
+            texture: u32,
+            texture_sampler: u32,
+        }
+
+        let font_texture = self.font_texture_view();
+
+        renderer_data.update_buffers(&self.device, draw_data);
+
+        if let Ok(shader) = self
+            .shader_manager
+            .shader_modules(&"ImGui".to_string(), None)
+        {
+            if draw_data.CmdListsCount > 0 {
+                #[rustfmt::skip] 
+                let projection_matrix = {
+                    let left = draw_data.DisplayPos.x;
+                    let right = draw_data.DisplayPos.x + draw_data.DisplaySize.x;
+                    let top = draw_data.DisplayPos.y;
+                    let bottom = draw_data.DisplayPos.y + draw_data.DisplaySize.y;
+                    Matrix4x4::<f32>::from([
+                        [2.0 / (right - left), 0.0, 0.0, 0.0],
+                        [0.0, 2.0 / (top - bottom), 0.0, 0.0],
+                        [0.0, 0.0, 0.5, 0.0],
+                        [(right + left) / (left - right), (top + bottom) / (bottom - top), 0.5, 1.0],
+                    ])
+                };
+
+                let mut shader_data = ShaderData {
+                    projection_matrix,
+                    base_vertex_location: 0,
+                    vertex_buffer: renderer_data
+                        .vertex_buffer_srv
+                        .as_ref()
+                        .unwrap()
+                        .descriptor_index(),
+                    texture: font_texture.descriptor_index(),
+                    texture_sampler: self.sampler.descriptor_index(),
+                };
+
+                self.device
+                    .cmd_set_shader_stages(cmd_list, &shader.pipeline_stages());
+
+                let mut blend_state = PipelineBlendState::default();
+                blend_state.render_targets[0] = PipelineRenderTargetBlendDesc {
+                    enable_blend: true,
+                    src_color_blend_factor: BlendFactor::SrcAlpha,
+                    dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                    color_blend_op: BlendOp::Add,
+                    src_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                    dst_alpha_blend_factor: BlendFactor::Zero,
+                    alpha_blend_op: BlendOp::Add,
+                };
+                self.device.cmd_set_blend_state(cmd_list, &blend_state);
+                self.device.cmd_bind_index_buffer(
+                    cmd_list,
+                    renderer_data.index_buffer.as_ref().unwrap(),
+                    IndexBufferFormat::Uint16,
+                );
+
+                self.device.cmd_set_viewports(
+                    cmd_list,
+                    &[ze_gfx::backend::Viewport {
+                        position: Default::default(),
+                        size: Vector2::<f32>::new(draw_data.DisplaySize.x, draw_data.DisplaySize.y),
+                        min_depth: 0.0,
+                        max_depth: 1.0,
+                    }],
+                );
+
+                let mut vertex_offset = 0;
+                let mut index_offset = 0;
+
+                let draw_lists = unsafe {
+                    slice::from_raw_parts(draw_data.CmdLists, draw_data.CmdListsCount as usize)
+                };
+
+                for draw_list in draw_lists {
+                    let draw_list = unsafe { draw_list.as_ref().unwrap_unchecked() };
+                    let cmd_buffers = unsafe {
+                        slice::from_raw_parts(
+                            draw_list.CmdBuffer.Data,
+                            draw_list.CmdBuffer.Size as usize,
+                        )
+                    };
+                    for cmd in cmd_buffers {
+                        let clip_offset = draw_data.DisplayPos;
+
+                        let clip_min = Vector2::<i32>::new(
+                            (cmd.ClipRect.x - clip_offset.x) as i32,
+                            (cmd.ClipRect.y - clip_offset.y) as i32,
+                        );
+
+                        let clip_max = Vector2::<i32>::new(
+                            (cmd.ClipRect.z - clip_offset.x) as i32,
+                            (cmd.ClipRect.w - clip_offset.y) as i32,
+                        );
+
+                        let clip_rect =
+                            RectI32::new(clip_min.x, clip_min.y, clip_max.x, clip_max.y);
+
+                        self.device.cmd_set_scissors(cmd_list, &[clip_rect]);
+
+                        shader_data.base_vertex_location = cmd.VtxOffset + vertex_offset;
+                        shader_data.texture = if cmd.TextureId.is_null() {
+                            font_texture.descriptor_index()
+                        } else {
+                            match self.textures.get(&TextureId(cmd.TextureId as u64)) {
+                                Some(texture) => texture.srv.descriptor_index(),
+                                None => {
+                                    ze_error!("Unknown ImGui texture {:?}", cmd.TextureId);
+                                    continue;
+                                }
+                            }
+                        };
+
+                        self.device.cmd_push_constants(cmd_list, 0, unsafe {
+                            slice::from_raw_parts(
+                                (&shader_data as *const ShaderData) as *const u8,
+                                size_of::<ShaderData>(),
+                            )
+                        });
+
+                        self.device.cmd_draw_indexed(
+                            cmd_list,
+                            cmd.ElemCount,
+                            1,
+                            cmd.IdxOffset + index_offset,
+                            0,
+                        );
                     }
+
+                    vertex_offset += draw_list.VtxBuffer.Size as u32;
+                    index_offset += draw_list.IdxBuffer.Size as u32;
                 }
             }
         }
     }
 
+    /// Let ImGui close the secondary viewport of `window`, the main window is left to the
+    /// application
+    fn request_viewport_close(&self, window: &Weak<dyn Window>) {
+        let platform_io = unsafe { igGetPlatformIO().as_ref().unwrap_unchecked() };
+        let viewports = unsafe {
+            slice::from_raw_parts(
+                platform_io.Viewports.Data,
+                platform_io.Viewports.Size as usize,
+            )
+        };
+
+        for &vp in viewports {
+            let viewport = unsafe { vp.as_mut().unwrap_unchecked() };
+            let platform_data = viewport.PlatformUserData as *const ViewportPlatformData;
+            if vp == unsafe { igGetMainViewport() } || platform_data.is_null() {
+                continue;
+            }
+
+            let platform_data = unsafe { platform_data.as_ref().unwrap_unchecked() };
+            if Weak::ptr_eq(window, &Arc::downgrade(&platform_data.window)) {
+                viewport.PlatformRequestClose = true;
+            }
+        }
+    }
+
+    /// Sync secondary viewports with changes made by the OS and update their decorations
+    fn update_platform_windows(&mut self) {
+        let platform_io = unsafe { igGetPlatformIO().as_ref().unwrap_unchecked() };
+        let style = unsafe { igGetStyle().as_ref().unwrap_unchecked() };
+        let viewports = unsafe {
+            slice::from_raw_parts(
+                platform_io.Viewports.Data,
+                platform_io.Viewports.Size as usize,
+            )
+        };
+
+        for &vp in viewports {
+            let viewport = unsafe { vp.as_mut().unwrap_unchecked() };
+            let platform_data = viewport.PlatformUserData as *mut ViewportPlatformData;
+            if vp == unsafe { igGetMainViewport() } || platform_data.is_null() {
+                continue;
+            }
+
+            let platform_data = unsafe { platform_data.as_mut().unwrap_unchecked() };
+
+            // Windows dragged or resized through their decorations are moved by the OS
+            let position = platform_data.window.position();
+            if position != platform_data.position {
+                platform_data.position = position;
+                viewport.PlatformRequestMove = true;
+            }
+
+            let size = (platform_data.window.width(), platform_data.window.height());
+            if size != platform_data.size {
+                platform_data.size = size;
+                viewport.PlatformRequestResize = true;
+            }
+
+            let mut decorations = platform_data.decorations.lock();
+            decorations.width = size.0 as i32;
+            decorations.height = size.1 as i32;
+            decorations.caption = None;
+            decorations.resize_border = 0;
+
+            let window = unsafe { (*(vp as *mut ImGuiViewportP)).Window };
+            if window.is_null() {
+                continue;
+            }
+
+            let flags = unsafe { (*window).Flags };
+            if flags & WindowFlagBits::NoTitleBar as i32 == 0 {
+                let mut title_bar = ImRect {
+                    Min: ImVec2::default(),
+                    Max: ImVec2::default(),
+                };
+                let title_bar_height = unsafe {
+                    ImGuiWindow_TitleBarRect(&mut title_bar, window);
+                    ImGuiWindow_TitleBarHeight(window)
+                };
+
+                // Keep the collapse and close buttons clickable
+                let button_size = title_bar_height - style.FramePadding.y * 2.0;
+                let buttons_width =
+                    style.FramePadding.x + button_size * 2.0 + style.ItemInnerSpacing.x;
+
+                decorations.caption = Some(RectI32::new(
+                    (title_bar.Min.x - viewport.Pos.x) as i32,
+                    (title_bar.Min.y - viewport.Pos.y) as i32,
+                    (title_bar.Max.x - title_bar.Min.x - buttons_width) as i32,
+                    title_bar_height as i32,
+                ));
+            }
+
+            if flags & WindowFlagBits::NoResize as i32 == 0 {
+                decorations.resize_border = (VIEWPORT_RESIZE_BORDER * viewport.DpiScale) as i32;
+            }
+        }
+    }
+
     pub fn update_monitors(&mut self) {
-        let mut io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
+        self.make_current();
+        let io = unsafe { igGetPlatformIO().as_mut().unwrap_unchecked() };
         let monitor_count = self.platform.monitor_count();
         if io.Monitors.Capacity > 0 {
             unsafe {
@@ -530,7 +968,7 @@ impl Context {
     pub fn main_viewport(&self) -> &Viewport {
         unsafe { (igGetMainViewport() as *mut Viewport).as_ref().unwrap_unchecked() }
     }
-    
+
     #[allow(clippy::mut_from_ref)]
     pub fn main_viewport_mut(&self) -> &mut Viewport {
         unsafe { (igGetMainViewport() as *mut Viewport).as_mut().unwrap_unchecked() }
@@ -608,6 +1046,37 @@ pub enum TableColumnFlagBits {
 
 pub type TableColumnFlags = BitFlags<TableColumnFlagBits>;
 
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum SelectableFlagBits {
+    DontClosePopups = 1 << 0,
+    SpanAllColumns = 1 << 1,
+    AllowDoubleClick = 1 << 2,
+    Disabled = 1 << 3,
+    AllowItemOverlap = 1 << 4,
+}
+
+pub type SelectableFlags = BitFlags<SelectableFlagBits>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ColumnSortSpec {
+    pub column: u32,
+    pub direction: SortDirection,
+}
+
+/// Columns to sort a table by, ordered by priority
+pub struct TableSortSpecs {
+    pub specs: Vec<ColumnSortSpec>,
+    pub dirty: bool,
+}
+
 #[bitflags]
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
 #[repr(u32)]
@@ -669,6 +1138,66 @@ pub enum StyleVar {
     SelectableTextAlign,
 }
 
+/// Style color, can be changed temporarily with [`Context::push_style_color`]
+#[derive(Copy, Clone)]
+pub enum Col {
+    Text,
+    TextDisabled,
+    WindowBg,
+    ChildBg,
+    PopupBg,
+    Border,
+    BorderShadow,
+    FrameBg,
+    FrameBgHovered,
+    FrameBgActive,
+    TitleBg,
+    TitleBgActive,
+    TitleBgCollapsed,
+    MenuBarBg,
+    ScrollbarBg,
+    ScrollbarGrab,
+    ScrollbarGrabHovered,
+    ScrollbarGrabActive,
+    CheckMark,
+    SliderGrab,
+    SliderGrabActive,
+    Button,
+    ButtonHovered,
+    ButtonActive,
+    Header,
+    HeaderHovered,
+    HeaderActive,
+    Separator,
+    SeparatorHovered,
+    SeparatorActive,
+    ResizeGrip,
+    ResizeGripHovered,
+    ResizeGripActive,
+    Tab,
+    TabHovered,
+    TabActive,
+    TabUnfocused,
+    TabUnfocusedActive,
+    DockingPreview,
+    DockingEmptyBg,
+    PlotLines,
+    PlotLinesHovered,
+    PlotHistogram,
+    PlotHistogramHovered,
+    TableHeaderBg,
+    TableBorderStrong,
+    TableBorderLight,
+    TableRowBg,
+    TableRowBgAlt,
+    TextSelectedBg,
+    DragDropTarget,
+    NavHighlight,
+    NavWindowingHighlight,
+    NavWindowingDimBg,
+    ModalWindowDimBg,
+}
+
 pub enum Cond {
     None,
     Always,
@@ -689,6 +1218,25 @@ impl From<Cond> for ImGuiCond {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Dir {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl From<Dir> for ImGuiDir {
+    fn from(other: Dir) -> Self {
+        match other {
+            Dir::Left => ImGuiDir__ImGuiDir_Left,
+            Dir::Right => ImGuiDir__ImGuiDir_Right,
+            Dir::Up => ImGuiDir__ImGuiDir_Up,
+            Dir::Down => ImGuiDir__ImGuiDir_Down,
+        }
+    }
+}
+
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -927,7 +1475,68 @@ impl Context {
         let label = self.str_buffer.convert(label);
         unsafe { igButton(label, size) }
     }
-    
+
+    /// Button drawn as pressed while `active`, clicking it toggles `active`
+    pub fn toggle_button(&mut self, label: &str, active: &mut bool, size: ImVec2) -> bool {
+        let was_active = *active;
+        if was_active {
+            let color = unsafe { *igGetStyleColorVec4(Col::ButtonActive as i32) };
+            self.push_style_color(Col::Button, color);
+            self.push_style_color(Col::ButtonHovered, color);
+        }
+
+        let clicked = self.button(label, size);
+        if clicked {
+            *active = !*active;
+        }
+
+        if was_active {
+            self.pop_style_color(2);
+        }
+        clicked
+    }
+
+    /// Returns true when clicked, `active` is not changed
+    pub fn radio_button(&mut self, label: &str, active: bool) -> bool {
+        let label = self.str_buffer.convert(label);
+        unsafe { igRadioButton_Bool(label, active) }
+    }
+
+    /// Radio buttons selecting one of `items`, returns true when `current_index` changed
+    pub fn radio_group(
+        &mut self,
+        current_index: &mut usize,
+        items: &[&str],
+        horizontal: bool,
+    ) -> bool {
+        let mut changed = false;
+        for (index, item) in items.iter().enumerate() {
+            if horizontal && index > 0 {
+                self.same_line(0.0, -1.0);
+            }
+
+            if self.radio_button(item, index == *current_index) && index != *current_index {
+                *current_index = index;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Draw a progress bar, `fraction` being between 0 and 1
+    pub fn progress_bar(&mut self, fraction: f32, size: ImVec2, overlay: Option<&str>) {
+        let overlay = match overlay {
+            Some(overlay) => self.str_buffer.convert(overlay),
+            None => std::ptr::null(),
+        };
+        unsafe { igProgressBar(fraction, size, overlay) }
+    }
+
+    pub fn invisible_button(&mut self, id: &str, size: ImVec2) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe { igInvisibleButton(id, size, ImGuiButtonFlags__ImGuiButtonFlags_None as i32) }
+    }
+    
     pub fn set_scroll_x(&mut self, scroll: f32) {
         unsafe { igSetScrollX_Float(scroll) }
     }
@@ -935,12 +1544,35 @@ impl Context {
     pub fn set_scroll_y(&mut self, scroll: f32) {
         unsafe { igSetScrollY_Float(scroll) }
     }
+
+    pub fn scroll_y(&self) -> f32 {
+        unsafe { igGetScrollY() }
+    }
+
+    pub fn scroll_max_y(&self) -> f32 {
+        unsafe { igGetScrollMaxY() }
+    }
     
     pub fn text(&mut self, text: &str) {
         let c_text = self.str_buffer.convert(text);
         unsafe { igTextUnformatted(c_text, c_text.add(text.len())) };
     }
 
+    /// Display an icon of an icon font merged into the current font (e.g "\u{f07b}")
+    /// Icons can also be used in any label, e.g "\u{f07b} Open"
+    pub fn icon(&mut self, icon: &str) {
+        self.text(icon);
+    }
+
+    pub fn text_colored(&mut self, color: ImVec4, text: &str) {
+        let c_text = self.str_buffer.convert(text);
+        unsafe {
+            igPushStyleColor_Vec4(ImGuiCol__ImGuiCol_Text as i32, color);
+            igTextUnformatted(c_text, c_text.add(text.len()));
+            igPopStyleColor(1);
+        }
+    }
+
     pub fn text_wrapped(&mut self, text: &str) {
         let text = self.str_buffer.convert(text);
         unsafe { igTextWrappedV(text, null_mut()) };
@@ -969,37 +1601,84 @@ impl Context {
         }
     }
 
-    pub fn image(&mut self, srv: &ShaderResourceView, size: ImVec2) {
-        let srv = srv as *const _ as *mut ShaderResourceView as *mut c_void;
+    pub fn image(&mut self, texture: TextureId, size: ImVec2) {
+        self.image_uv(
+            texture,
+            size,
+            ImVec2::new(0.0, 0.0),
+            ImVec2::new(1.0, 1.0),
+            ImVec4::new(1.0, 1.0, 1.0, 1.0),
+        );
+    }
 
+    /// Display the `uv0` to `uv1` part of the texture (e.g a sprite of an atlas)
+    pub fn image_uv(
+        &mut self,
+        texture: TextureId,
+        size: ImVec2,
+        uv0: ImVec2,
+        uv1: ImVec2,
+        tint: ImVec4,
+    ) {
         unsafe {
             igImage(
-                srv,
+                texture.as_imgui(),
                 size,
-                ImVec2::new(0.0, 0.0),
-                ImVec2::new(1.0, 1.0),
-                ImVec4::new(1.0, 1.0, 1.0, 1.0),
+                uv0,
+                uv1,
+                tint,
                 ImVec4::new(0.0, 0.0, 0.0, 0.0),
             )
         }
     }
 
-    pub fn image_centered(&mut self, srv: &ShaderResourceView, size: ImVec2) {
+    /// Returns true when clicked, `id` differentiates buttons using the same texture
+    pub fn image_button(&mut self, id: &str, texture: TextureId, size: ImVec2) -> bool {
+        self.image_button_uv(
+            id,
+            texture,
+            size,
+            ImVec2::new(0.0, 0.0),
+            ImVec2::new(1.0, 1.0),
+            ImVec4::new(0.0, 0.0, 0.0, 0.0),
+            ImVec4::new(1.0, 1.0, 1.0, 1.0),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn image_button_uv(
+        &mut self,
+        id: &str,
+        texture: TextureId,
+        size: ImVec2,
+        uv0: ImVec2,
+        uv1: ImVec2,
+        background: ImVec4,
+        tint: ImVec4,
+    ) -> bool {
+        self.push_id_str(id);
+        let clicked =
+            unsafe { igImageButton(texture.as_imgui(), size, uv0, uv1, -1, background, tint) };
+        self.pop_id();
+        clicked
+    }
+
+    pub fn image_centered(&mut self, texture: TextureId, size: ImVec2) {
         unsafe {
             let window_width = igGetWindowWidth();
             let window_height = igGetWindowHeight();
             igSetCursorPosX((window_width - size.x) * 0.5);
             igSetCursorPosY((window_height - size.y) * 0.5);
         }
-        self.image(srv, size);
+        self.image(texture, size);
     }
 
-    pub fn image_centered_x(&mut self, srv: &ShaderResourceView, size: ImVec2) {
+    pub fn image_centered_x(&mut self, texture: TextureId, size: ImVec2) {
         unsafe {
             let window_width = igGetWindowWidth();
             igSetCursorPosX((window_width - size.x) * 0.5);
         }
-        self.image(srv, size);
+        self.image(texture, size);
     }
 
     pub fn selectable(&mut self, label: &str, size: ImVec2) -> bool {
@@ -1014,6 +1693,18 @@ impl Context {
         }
     }
 
+    /// Selectable highlighted only when `selected`, returns true when clicked
+    pub fn selectable_ex(
+        &mut self,
+        label: &str,
+        selected: bool,
+        flags: SelectableFlags,
+        size: ImVec2,
+    ) -> bool {
+        let label = self.str_buffer.convert(label);
+        unsafe { igSelectable_Bool(label, selected, flags.bits() as i32, size) }
+    }
+
     pub fn set_cursor_pos(&mut self, cursor_pos: ImVec2) {
         unsafe {
             igSetCursorPos(cursor_pos);
@@ -1026,6 +1717,12 @@ impl Context {
         }
     }
 
+    pub fn set_next_window_viewport(&self, viewport: &Viewport) {
+        unsafe {
+            igSetNextWindowViewport(viewport.viewport.ID);
+        }
+    }
+
     pub fn push_style_var_f32(&mut self, var: StyleVar, val: f32) {
         unsafe { igPushStyleVar_Float(var as i32, val) }
     }
@@ -1038,6 +1735,34 @@ impl Context {
         unsafe { igPopStyleVar(count) }
     }
 
+    pub fn push_style_color(&mut self, col: Col, color: ImVec4) {
+        unsafe { igPushStyleColor_Vec4(col as i32, color) }
+    }
+
+    pub fn pop_style_color(&mut self, count: i32) {
+        unsafe { igPopStyleColor(count) }
+    }
+
+    /// Input methods leave the display of the text being composed to the application
+    fn draw_text_composition(&mut self) {
+        let position = match self.ime_position.get() {
+            Some(position) if !self.text_composition.is_empty() => position,
+            _ => return,
+        };
+
+        self.set_next_window_pos(position, Cond::Always, ImVec2::new(0.0, 0.0));
+        let flags = make_bitflags!(WindowFlagBits::{NoTitleBar | NoResize | NoMove | NoScrollbar
+            | NoCollapse | AlwaysAutoResize | NoSavedSettings | NoFocusOnAppearing | NoNavInputs
+            | NoNavFocus | NoDocking | NoMouseInputs});
+        if self.begin_window("##TextComposition", flags) {
+            unsafe { igBringWindowToDisplayFront(igGetCurrentWindow()) };
+            let text = mem::take(&mut self.text_composition);
+            self.text(&text);
+            self.text_composition = text;
+        }
+        self.end_window();
+    }
+
     pub fn begin_window(&mut self, name: &str, flags: WindowFlags) -> bool {
         let name = self.str_buffer.convert(name);
         unsafe { igBegin(name, null_mut(), flags.bits() as i32) }
@@ -1056,6 +1781,11 @@ impl Context {
     pub fn end_window(&mut self) {
         unsafe { igEnd() };
     }
+
+    /// Window submitted with a closure, always ended even on early returns
+    pub fn window<'a>(&'a mut self, name: &'a str) -> WindowBuilder<'a> {
+        WindowBuilder::new(self, name)
+    }
     
     pub fn begin_popup(&mut self, id: &str, flags: WindowFlags) -> bool {
         unsafe {
@@ -1075,6 +1805,11 @@ impl Context {
         unsafe { igEndPopup() }
     }
 
+    /// Popup submitted with a closure, only ended when it was open
+    pub fn popup<'a>(&'a mut self, id: &'a str) -> PopupBuilder<'a> {
+        PopupBuilder::new(self, id)
+    }
+
     pub fn open_popup(&mut self, id: &str) {
         unsafe {
             let id = self.str_buffer.convert(id);
@@ -1098,6 +1833,70 @@ impl Context {
         }
     }
 
+    /// Serialize the windows and docking settings to the ini format
+    pub fn save_ini_settings(&self) -> String {
+        unsafe {
+            let mut size = 0;
+            let data = igSaveIniSettingsToMemory(&mut size);
+            String::from_utf8_lossy(slice::from_raw_parts(data as *const u8, size as usize))
+                .into_owned()
+        }
+    }
+
+    /// Load windows and docking settings saved with `save_ini_settings`
+    /// Must be called outside of a frame
+    pub fn load_ini_settings(&mut self, settings: &str) {
+        unsafe {
+            igLoadIniSettingsFromMemory(
+                settings.as_ptr() as *const c_char,
+                settings.len() as size_t,
+            )
+        }
+    }
+
+    /// Persist windows and docking settings in `directory` instead of ImGui `imgui.ini` file
+    /// Settings already saved in `directory` are loaded, must be called outside of a frame
+    pub fn set_ini_settings_directory(&mut self, filesystem: Arc<FileSystem>, directory: Path) {
+        self.make_current();
+        let path = directory.join(INI_SETTINGS_FILE_NAME);
+        if filesystem.exists(&path) {
+            let mut settings = String::new();
+            match filesystem
+                .read(&path)
+                .map_err(Error::FileSystem)
+                .and_then(|mut file| file.read_to_string(&mut settings).map_err(Error::Io))
+            {
+                Ok(_) => self.load_ini_settings(&settings),
+                Err(error) => ze_error!("Failed to load ImGui settings: {}", error),
+            }
+        }
+
+        self.ini_settings_location = Some((filesystem, directory));
+    }
+
+    fn save_ini_settings_file(&self) {
+        if let Some((filesystem, directory)) = &self.ini_settings_location {
+            let settings = self.save_ini_settings();
+            let result = filesystem
+                .create_dir_all(directory)
+                .and_then(|_| filesystem.write(&directory.join(INI_SETTINGS_FILE_NAME)))
+                .map_err(Error::FileSystem)
+                .and_then(|mut file| file.write_all(settings.as_bytes()).map_err(Error::Io));
+
+            if let Err(error) = result {
+                ze_error!("Failed to save ImGui settings: {}", error);
+            }
+        }
+    }
+
+    pub fn window_size(&self) -> ImVec2 {
+        let mut size = ImVec2::default();
+        unsafe {
+            igGetWindowSize(&mut size);
+        }
+        size
+    }
+
     pub fn available_content_region(&self) -> ImVec2 {
         let mut vec = ImVec2::default();
         unsafe {
@@ -1106,6 +1905,10 @@ impl Context {
         vec
     }
 
+    pub fn frame_height_with_spacing(&self) -> f32 {
+        unsafe { igGetFrameHeightWithSpacing() }
+    }
+
     pub fn begin_table(
         &mut self,
         name: &str,
@@ -1137,6 +1940,10 @@ impl Context {
         }
     }
 
+    pub fn table_set_column_index(&mut self, column: u32) -> bool {
+        unsafe { igTableSetColumnIndex(column as c_int) }
+    }
+
     pub fn table_setup_column(
         &mut self,
         label: &str,
@@ -1154,6 +1961,45 @@ impl Context {
         }
     }
 
+    pub fn table_headers_row(&mut self) {
+        unsafe {
+            igTableHeadersRow();
+        }
+    }
+
+    /// Sort specs of the current table, `None` if the table is not `Sortable`
+    ///
+    /// `dirty` is set when the sort specs changed since the last call, the table should be
+    /// sorted again
+    pub fn table_sort_specs(&mut self) -> Option<TableSortSpecs> {
+        let sort_specs = unsafe { igTableGetSortSpecs().as_mut() }?;
+        let specs = if sort_specs.SpecsCount > 0 {
+            unsafe { slice::from_raw_parts(sort_specs.Specs, sort_specs.SpecsCount as usize) }
+        } else {
+            &[]
+        };
+
+        let table_sort_specs = TableSortSpecs {
+            specs: specs
+                .iter()
+                .map(|spec| ColumnSortSpec {
+                    column: spec.ColumnIndex as u32,
+                    direction: if spec.SortDirection()
+                        == ImGuiSortDirection__ImGuiSortDirection_Descending
+                    {
+                        SortDirection::Descending
+                    } else {
+                        SortDirection::Ascending
+                    },
+                })
+                .collect(),
+            dirty: sort_specs.SpecsDirty,
+        };
+
+        sort_specs.SpecsDirty = false;
+        Some(table_sort_specs)
+    }
+
     pub fn end_table(&mut self) {
         unsafe {
             igEndTable();
@@ -1164,6 +2010,11 @@ impl Context {
         unsafe { igIsWindowHovered(ImGuiHoveredFlags__ImGuiHoveredFlags_None as i32) }
     }
 
+    /// Focus keyboard on the next widget, or on a previous one if `offset` is negative
+    pub fn set_keyboard_focus_here(&self, offset: i32) {
+        unsafe { igSetKeyboardFocusHere(offset) }
+    }
+
     pub fn is_item_hovered(&self) -> bool {
         unsafe { igIsItemHovered(ImGuiHoveredFlags__ImGuiHoveredFlags_None as i32) }
     }
@@ -1178,6 +2029,57 @@ impl Context {
         }
     }
 
+    pub fn mouse_pos(&self) -> ImVec2 {
+        let mut pos = ImVec2::default();
+        unsafe {
+            igGetMousePos(&mut pos);
+        }
+        pos
+    }
+
+    /// Vertical mouse wheel delta of this frame
+    pub fn mouse_wheel(&self) -> f32 {
+        unsafe { (*igGetIO()).MouseWheel }
+    }
+
+    /// Horizontal wheel delta, positive when scrolling to the left
+    pub fn mouse_wheel_h(&self) -> f32 {
+        unsafe { (*igGetIO()).MouseWheelH }
+    }
+
+    /// Time elapsed since the last frame in seconds
+    pub fn delta_time(&self) -> f32 {
+        unsafe { (*igGetIO()).DeltaTime }
+    }
+
+    /// Mouse movement since the last frame
+    pub fn mouse_delta(&self) -> ImVec2 {
+        unsafe { (*igGetIO()).MouseDelta }
+    }
+
+    /// ImGui uses the mouse this frame, the application should ignore mouse inputs
+    pub fn wants_capture_mouse(&self) -> bool {
+        self.make_current();
+        unsafe { (*igGetIO()).WantCaptureMouse }
+    }
+
+    /// ImGui uses the keyboard this frame (e.g a text input is active), the application should
+    /// ignore keyboard inputs
+    pub fn wants_capture_keyboard(&self) -> bool {
+        self.make_current();
+        unsafe { (*igGetIO()).WantCaptureKeyboard }
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        unsafe {
+            igIsMouseDown(match button {
+                MouseButton::Left => ImGuiMouseButton__ImGuiMouseButton_Left as i32,
+                MouseButton::Middle => ImGuiMouseButton__ImGuiMouseButton_Middle as i32,
+                MouseButton::Right => ImGuiMouseButton__ImGuiMouseButton_Right as i32,
+            })
+        }
+    }
+
     pub fn is_mouse_double_clicked(&self, button: MouseButton) -> bool {
         unsafe {
             igIsMouseDoubleClicked(match button {
@@ -1205,6 +2107,15 @@ impl Context {
             igIsKeyDown(key as ImGuiKey)
         }
     }
+
+    /// A key or a mouse button is held down, it may be driving something every frame
+    pub fn is_any_input_down(&self) -> bool {
+        unsafe {
+            igIsAnyMouseDown()
+                || (ImGuiKey__ImGuiKey_NamedKey_BEGIN..ImGuiKey__ImGuiKey_NamedKey_END)
+                    .any(|key| igIsKeyDown(key as ImGuiKey))
+        }
+    }
     
     pub fn begin_main_menu_bar(&self) -> bool {
         unsafe { igBeginMainMenuBar() }
@@ -1214,6 +2125,35 @@ impl Context {
         unsafe { igEndMainMenuBar() }
     }
 
+    /// Begin a bar docked at the bottom of the main viewport, `end_status_bar` must be called
+    /// only if it returns true
+    pub fn begin_status_bar(&mut self) -> bool {
+        let flags = make_bitflags!(WindowFlagBits::{NoScrollbar | NoSavedSettings | MenuBar});
+        let name = self.str_buffer.convert("##StatusBar");
+        unsafe {
+            if igBeginViewportSideBar(
+                name,
+                igGetMainViewport(),
+                ImGuiDir__ImGuiDir_Down,
+                igGetFrameHeight(),
+                flags.bits() as i32,
+            ) && igBeginMenuBar()
+            {
+                return true;
+            }
+
+            igEnd();
+        }
+        false
+    }
+
+    pub fn end_status_bar(&mut self) {
+        unsafe {
+            igEndMenuBar();
+            igEnd();
+        }
+    }
+
     pub fn begin_tooltip(&self) {
         unsafe { igBeginTooltip() }
     }
@@ -1222,6 +2162,63 @@ impl Context {
         unsafe { igEndTooltip() }
     }
 
+    /// Show `text` in a tooltip while the last item is hovered
+    pub fn item_tooltip(&mut self, text: &str) {
+        if self.is_item_hovered() {
+            self.begin_tooltip();
+            self.text(text);
+            self.end_tooltip();
+        }
+    }
+
+    /// Show `text` in a tooltip once the mouse stayed still over the last item for `delay_secs`
+    pub fn item_tooltip_delayed(&mut self, text: &str, delay_secs: f32) {
+        if !self.is_item_hovered() {
+            return;
+        }
+
+        let mut min = ImVec2::default();
+        let mut max = ImVec2::default();
+        let frame = unsafe {
+            igGetItemRectMin(&mut min);
+            igGetItemRectMax(&mut max);
+            igGetFrameCount()
+        };
+        let rect = [min.x, min.y, max.x, max.y];
+        let mouse_delta = self.mouse_delta();
+        let stationary = mouse_delta.x == 0.0 && mouse_delta.y == 0.0;
+        let delta_time = self.delta_time();
+
+        let hover_time = match &mut self.tooltip_hover {
+            // Moving the mouse restarts the delay until the tooltip is shown
+            Some(hover)
+                if hover.rect == rect
+                    && hover.frame >= frame - 1
+                    && (stationary || hover.time >= delay_secs) =>
+            {
+                if hover.frame != frame {
+                    hover.time += delta_time;
+                    hover.frame = frame;
+                }
+                hover.time
+            }
+            _ => {
+                self.tooltip_hover = Some(TooltipHover {
+                    rect,
+                    time: 0.0,
+                    frame,
+                });
+                0.0
+            }
+        };
+
+        if hover_time >= delay_secs {
+            self.begin_tooltip();
+            self.text(text);
+            self.end_tooltip();
+        }
+    }
+
     pub fn dummy(&self, size: ImVec2) {
         unsafe { igDummy(size) }
     }
@@ -1259,10 +2256,7 @@ impl Context {
     }
 
     pub fn begin_combo(&mut self, label: &str, preview_value: &str) -> bool {
-        // TODO: Rework strbuffer, i'm lazy for now
-        let c_label = self.str_buffer.convert(label);
-        let mut preview_value_buffer = StrBuffer::default();
-        let preview_value = preview_value_buffer.convert(preview_value);
+        let (c_label, preview_value) = self.str_buffer.convert_pair(label, preview_value);
         unsafe {
             igBeginCombo(
                 c_label,
@@ -1277,6 +2271,43 @@ impl Context {
             igEndCombo();
         }
     }
+
+    /// Combo box listing `items`, returns the index of the item selected this frame
+    pub fn combo(&mut self, label: &str, current_index: usize, items: &[&str]) -> Option<usize> {
+        let preview_value = items.get(current_index).copied().unwrap_or_default();
+        self.combo_items(label, preview_value, |imgui| {
+            let mut selected_index = None;
+            for (index, item) in items.iter().enumerate() {
+                let selected = index == current_index;
+                if imgui.selectable_ex(item, selected, SelectableFlags::empty(), ImVec2::default())
+                {
+                    selected_index = Some(index);
+                }
+
+                if selected {
+                    unsafe { igSetItemDefaultFocus() };
+                }
+            }
+            selected_index
+        })
+        .flatten()
+    }
+
+    /// Combo box whose items are submitted by `f`, returns `None` if the combo is closed
+    pub fn combo_items<R, F: FnOnce(&mut Context) -> R>(
+        &mut self,
+        label: &str,
+        preview_value: &str,
+        f: F,
+    ) -> Option<R> {
+        if self.begin_combo(label, preview_value) {
+            let result = f(self);
+            self.end_combo();
+            Some(result)
+        } else {
+            None
+        }
+    }
 }
 
 impl Context {
@@ -1320,216 +2351,1938 @@ impl Context {
             )
         }
     }
-}
 
-#[bitflags]
-#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
-#[repr(u32)]
-pub enum TreeNodeFlagBits {
-    Selected = 1 << 0,
-    Framed = 1 << 1,
-    AllowItemOverlap = 1 << 2,
-    NoTreePushOnOpen = 1 << 3,
-    NoAutoOpenOnLog = 1 << 4,
-    DefaultOpen = 1 << 5,
-    OpenOnDoubleClick = 1 << 6,
-    OpenOnArrow = 1 << 7,
-    Leaf = 1 << 8,
-    Bullet = 1 << 9,
-    FramePadding = 1 << 10,
-    SpanAvailWidth = 1 << 11,
-    SpanFullWidth = 1 << 12,
-    NavLeftJumpsBackHere = 1 << 13,
-}
+    pub fn window_add_triangle_filled(&mut self, a: ImVec2, b: ImVec2, c: ImVec2, color: ImVec4) {
+        unsafe {
+            ImDrawList_AddTriangleFilled(
+                igGetWindowDrawList(),
+                a,
+                b,
+                c,
+                igColorConvertFloat4ToU32(color),
+            )
+        }
+    }
 
-pub type TreeNodeFlags = BitFlags<TreeNodeFlagBits>;
+    pub fn window_add_polyline(&mut self, points: &[ImVec2], color: ImVec4, thickness: f32) {
+        unsafe {
+            ImDrawList_AddPolyline(
+                igGetWindowDrawList(),
+                points.as_ptr(),
+                points.len() as i32,
+                igColorConvertFloat4ToU32(color),
+                ImDrawFlags__ImDrawFlags_None as i32,
+                thickness,
+            )
+        }
+    }
 
-// Tree
-impl Context {
-    pub fn tree_node_ex(&mut self, id: &str, flags: TreeNodeFlags) -> bool {
-        let id = self.str_buffer.convert(id);
-        unsafe { igTreeNodeEx_Str(id, flags.bits() as i32) }
+    pub fn window_add_text(&mut self, pos: ImVec2, color: ImVec4, text: &str) {
+        let range = text.as_bytes().as_ptr_range();
+        unsafe {
+            ImDrawList_AddText_Vec2(
+                igGetWindowDrawList(),
+                pos,
+                igColorConvertFloat4ToU32(color),
+                range.start as *const c_char,
+                range.end as *const c_char,
+            )
+        }
     }
 
-    pub fn tree_pop(&mut self) {
+    pub fn window_push_clip_rect(&mut self, min: ImVec2, max: ImVec2) {
         unsafe {
-            igTreePop();
+            ImDrawList_PushClipRect(igGetWindowDrawList(), min, max, true);
         }
     }
-}
 
-impl Context {
-    pub fn begin_child(
-        &mut self,
-        id: &str,
-        size: ImVec2,
-        border: bool,
-        flags: WindowFlags,
-    ) -> bool {
-        let id = self.str_buffer.convert(id);
-        unsafe { igBeginChild_Str(id, size, border, flags.bits() as i32) }
+    pub fn window_pop_clip_rect(&mut self) {
+        unsafe {
+            ImDrawList_PopClipRect(igGetWindowDrawList());
+        }
     }
 
-    pub fn end_child(&self) {
-        unsafe { igEndChild() }
+    pub fn calc_text_size(&self, text: &str) -> ImVec2 {
+        let range = text.as_bytes().as_ptr_range();
+        let mut size = ImVec2::default();
+        unsafe {
+            igCalcTextSize(
+                &mut size,
+                range.start as *const c_char,
+                range.end as *const c_char,
+                false,
+                -1.0,
+            );
+        }
+        size
     }
 }
 
-struct ViewportPlatformData {
-    window: Arc<dyn Window>,
+/// Draw list of a window or of the viewports foreground/background
+/// Positions are in screen space and colors are converted to ImGui packed colors
+pub struct DrawList<'a> {
+    draw_list: *mut ImDrawList,
+    _context: PhantomData<&'a mut Context>,
 }
 
-impl ViewportPlatformData {
-    fn new(window: Arc<dyn Window>) -> Self {
-        Self { window }
+impl<'a> DrawList<'a> {
+    pub fn add_line(&mut self, a: ImVec2, b: ImVec2, color: ImVec4, thickness: f32) {
+        unsafe { ImDrawList_AddLine(self.draw_list, a, b, color_to_u32(color), thickness) }
     }
-}
 
-fn draw_viewport_internal(
-    viewport: &mut Viewport,
-    device: &Arc<dyn Device>,
-    shader_manager: &Arc<ShaderManager>,
-    font_texture: &ShaderResourceView,
-    sampler: &Sampler,
-    cmd_list: &mut CommandList,
-) {
-    #[repr(C)]
-    struct ShaderData {
-        projection_matrix: Matrix4x4<f32>,
-        base_vertex_location: u32,
-        vertex_buffer: u32,
-        texture: u32,
-        texture_sampler: u32,
+    pub fn add_rect(
+        &mut self,
+        min: ImVec2,
+        max: ImVec2,
+        color: ImVec4,
+        rounding: f32,
+        thickness: f32,
+    ) {
+        unsafe {
+            ImDrawList_AddRect(
+                self.draw_list,
+                min,
+                max,
+                color_to_u32(color),
+                rounding,
+                ImDrawFlags__ImDrawFlags_None,
+                thickness,
+            )
+        }
+    }
+
+    pub fn add_rect_filled(&mut self, min: ImVec2, max: ImVec2, color: ImVec4, rounding: f32) {
+        unsafe {
+            ImDrawList_AddRectFilled(
+                self.draw_list,
+                min,
+                max,
+                color_to_u32(color),
+                rounding,
+                ImDrawFlags__ImDrawFlags_None,
+            )
+        }
+    }
+
+    /// A `segments` count of 0 computes it from the radius
+    pub fn add_circle(
+        &mut self,
+        center: ImVec2,
+        radius: f32,
+        color: ImVec4,
+        segments: u32,
+        thickness: f32,
+    ) {
+        unsafe {
+            ImDrawList_AddCircle(
+                self.draw_list,
+                center,
+                radius,
+                color_to_u32(color),
+                segments as c_int,
+                thickness,
+            )
+        }
+    }
+
+    pub fn add_circle_filled(&mut self, center: ImVec2, radius: f32, color: ImVec4, segments: u32) {
+        unsafe {
+            ImDrawList_AddCircleFilled(
+                self.draw_list,
+                center,
+                radius,
+                color_to_u32(color),
+                segments as c_int,
+            )
+        }
+    }
+
+    /// Cubic bezier curve from `points[0]` to `points[3]`
+    /// A `segments` count of 0 computes it from the curve length
+    pub fn add_bezier(
+        &mut self,
+        points: [ImVec2; 4],
+        color: ImVec4,
+        thickness: f32,
+        segments: u32,
+    ) {
+        unsafe {
+            ImDrawList_AddBezierCubic(
+                self.draw_list,
+                points[0],
+                points[1],
+                points[2],
+                points[3],
+                color_to_u32(color),
+                thickness,
+                segments as c_int,
+            )
+        }
+    }
+
+    pub fn add_polyline(&mut self, points: &[ImVec2], color: ImVec4, closed: bool, thickness: f32) {
+        let flags = if closed {
+            ImDrawFlags__ImDrawFlags_Closed
+        } else {
+            ImDrawFlags__ImDrawFlags_None
+        };
+
+        unsafe {
+            ImDrawList_AddPolyline(
+                self.draw_list,
+                points.as_ptr(),
+                points.len() as c_int,
+                color_to_u32(color),
+                flags,
+                thickness,
+            )
+        }
+    }
+
+    /// Fill a convex polygon, points must be ordered clockwise
+    pub fn add_convex_poly_filled(&mut self, points: &[ImVec2], color: ImVec4) {
+        unsafe {
+            ImDrawList_AddConvexPolyFilled(
+                self.draw_list,
+                points.as_ptr(),
+                points.len() as c_int,
+                color_to_u32(color),
+            )
+        }
+    }
+
+    pub fn add_text(&mut self, pos: ImVec2, color: ImVec4, text: &str) {
+        let range = text.as_bytes().as_ptr_range();
+        unsafe {
+            ImDrawList_AddText_Vec2(
+                self.draw_list,
+                pos,
+                color_to_u32(color),
+                range.start as *const c_char,
+                range.end as *const c_char,
+            )
+        }
+    }
+
+    pub fn add_image(
+        &mut self,
+        texture: TextureId,
+        min: ImVec2,
+        max: ImVec2,
+        uv_min: ImVec2,
+        uv_max: ImVec2,
+        tint: ImVec4,
+    ) {
+        unsafe {
+            ImDrawList_AddImage(
+                self.draw_list,
+                texture.as_imgui(),
+                min,
+                max,
+                uv_min,
+                uv_max,
+                color_to_u32(tint),
+            )
+        }
+    }
+
+    pub fn push_clip_rect(&mut self, min: ImVec2, max: ImVec2, intersect_with_current: bool) {
+        unsafe { ImDrawList_PushClipRect(self.draw_list, min, max, intersect_with_current) }
+    }
+
+    pub fn pop_clip_rect(&mut self) {
+        unsafe { ImDrawList_PopClipRect(self.draw_list) }
+    }
+
+    /// Split the draw list in `count` channels, drawn in order when merged
+    /// Useful to draw items out of order (e.g node graph links under the nodes)
+    pub fn channels_split(&mut self, count: u32) {
+        unsafe { ImDrawList_ChannelsSplit(self.draw_list, count as c_int) }
+    }
+
+    pub fn channels_set_current(&mut self, channel: u32) {
+        unsafe { ImDrawList_ChannelsSetCurrent(self.draw_list, channel as c_int) }
+    }
+
+    pub fn channels_merge(&mut self) {
+        unsafe { ImDrawList_ChannelsMerge(self.draw_list) }
+    }
+}
+
+fn color_to_u32(color: ImVec4) -> ImU32 {
+    unsafe { igColorConvertFloat4ToU32(color) }
+}
+
+// Draw lists
+impl Context {
+    /// Draw list of the current window
+    pub fn window_draw_list(&mut self) -> DrawList<'_> {
+        DrawList {
+            draw_list: unsafe { igGetWindowDrawList() },
+            _context: PhantomData,
+        }
+    }
+
+    /// Draw list drawn over all windows of the main viewport
+    pub fn foreground_draw_list(&mut self) -> DrawList<'_> {
+        DrawList {
+            draw_list: unsafe { igGetForegroundDrawList_Nil() },
+            _context: PhantomData,
+        }
+    }
+
+    /// Draw list drawn under all windows of the main viewport
+    pub fn background_draw_list(&mut self) -> DrawList<'_> {
+        DrawList {
+            draw_list: unsafe { igGetBackgroundDrawList_Nil() },
+            _context: PhantomData,
+        }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum TreeNodeFlagBits {
+    Selected = 1 << 0,
+    Framed = 1 << 1,
+    AllowItemOverlap = 1 << 2,
+    NoTreePushOnOpen = 1 << 3,
+    NoAutoOpenOnLog = 1 << 4,
+    DefaultOpen = 1 << 5,
+    OpenOnDoubleClick = 1 << 6,
+    OpenOnArrow = 1 << 7,
+    Leaf = 1 << 8,
+    Bullet = 1 << 9,
+    FramePadding = 1 << 10,
+    SpanAvailWidth = 1 << 11,
+    SpanFullWidth = 1 << 12,
+    NavLeftJumpsBackHere = 1 << 13,
+}
+
+pub type TreeNodeFlags = BitFlags<TreeNodeFlagBits>;
+
+// Tree
+impl Context {
+    pub fn tree_node_ex(&mut self, id: &str, flags: TreeNodeFlags) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe { igTreeNodeEx_Str(id, flags.bits() as i32) }
+    }
+
+    pub fn tree_pop(&mut self) {
+        unsafe {
+            igTreePop();
+        }
+    }
+
+    /// Set the open state of the next tree node
+    pub fn set_next_item_open(&mut self, open: bool, cond: Cond) {
+        unsafe { igSetNextItemOpen(open, ImGuiCond::from(cond)) }
+    }
+
+    /// The last tree node was opened or closed this frame
+    pub fn is_item_toggled_open(&self) -> bool {
+        unsafe { igIsItemToggledOpen() }
+    }
+}
+
+impl Context {
+    pub fn begin_child(
+        &mut self,
+        id: &str,
+        size: ImVec2,
+        border: bool,
+        flags: WindowFlags,
+    ) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe { igBeginChild_Str(id, size, border, flags.bits() as i32) }
+    }
+
+    pub fn end_child(&self) {
+        unsafe { igEndChild() }
+    }
+
+    /// Child window submitted with a closure, always ended even on early returns
+    pub fn child<'a>(&'a mut self, id: &'a str) -> ChildBuilder<'a> {
+        ChildBuilder::new(self, id)
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum TabBarFlagBits {
+    Reorderable = 1 << 0,
+    AutoSelectNewTabs = 1 << 1,
+    TabListPopupButton = 1 << 2,
+    NoCloseWithMiddleMouseButton = 1 << 3,
+    NoTabListScrollingButtons = 1 << 4,
+    NoTooltip = 1 << 5,
+    FittingPolicyResizeDown = 1 << 6,
+    FittingPolicyScroll = 1 << 7,
+}
+
+pub type TabBarFlags = BitFlags<TabBarFlagBits>;
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum TabItemFlagBits {
+    UnsavedDocument = 1 << 0,
+    SetSelected = 1 << 1,
+    NoCloseWithMiddleMouseButton = 1 << 2,
+    NoPushId = 1 << 3,
+    NoTooltip = 1 << 4,
+    NoReorder = 1 << 5,
+    Leading = 1 << 6,
+    Trailing = 1 << 7,
+}
+
+pub type TabItemFlags = BitFlags<TabItemFlagBits>;
+
+// Tabs
+impl Context {
+    /// `end_tab_bar` must be called only if it returns true
+    pub fn begin_tab_bar(&mut self, id: &str, flags: TabBarFlags) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe { igBeginTabBar(id, flags.bits() as i32) }
+    }
+
+    pub fn end_tab_bar(&self) {
+        unsafe { igEndTabBar() }
+    }
+
+    /// Returns true if the tab is selected, `end_tab_item` must then be called
+    pub fn begin_tab_item(&mut self, label: &str, flags: TabItemFlags) -> bool {
+        let label = self.str_buffer.convert(label);
+        unsafe { igBeginTabItem(label, null_mut(), flags.bits() as i32) }
+    }
+
+    /// Tab with a close button, `open` is set to false when it is clicked
+    pub fn begin_tab_item_closable(
+        &mut self,
+        label: &str,
+        open: &mut bool,
+        flags: TabItemFlags,
+    ) -> bool {
+        let label = self.str_buffer.convert(label);
+        unsafe { igBeginTabItem(label, open, flags.bits() as i32) }
+    }
+
+    pub fn end_tab_item(&self) {
+        unsafe { igEndTabItem() }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum ItemFlagBits {
+    NoTabStop = 1 << 0,
+    ButtonRepeat = 1 << 1,
+    Disabled = 1 << 2,
+    NoNav = 1 << 3,
+    NoNavDefaultFocus = 1 << 4,
+    SelectableDontClosePopup = 1 << 5,
+    MixedValue = 1 << 6,
+    ReadOnly = 1 << 7,
+}
+
+pub type ItemFlags = BitFlags<ItemFlagBits>;
+
+// Item flags
+impl Context {
+    pub fn push_item_flag(&self, flag: ItemFlagBits, enabled: bool) {
+        unsafe { igPushItemFlag(flag as i32, enabled) }
+    }
+
+    pub fn pop_item_flag(&self) {
+        unsafe { igPopItemFlag() }
+    }
+}
+
+/// Disabled scope started by [`Context::disabled_scope`], ended when dropped
+#[must_use]
+pub struct DisabledScope {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for DisabledScope {
+    fn drop(&mut self) {
+        unsafe { igEndDisabled() }
+    }
+}
+
+// Disabled
+// Items submitted while disabled are greyed out and don't react to inputs, scopes can be nested
+impl Context {
+    pub fn begin_disabled(&self, disabled: bool) {
+        unsafe { igBeginDisabled(disabled) }
+    }
+
+    pub fn end_disabled(&self) {
+        unsafe { igEndDisabled() }
+    }
+
+    /// Begin a disabled scope that ends when the returned guard is dropped
+    pub fn disabled_scope(&self, disabled: bool) -> DisabledScope {
+        self.begin_disabled(disabled);
+        DisabledScope {
+            _not_send: PhantomData,
+        }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum FocusedFlagBits {
+    ChildWindows = 1 << 0,
+    RootWindow = 1 << 1,
+    AnyWindow = 1 << 2,
+    NoPopupHierarchy = 1 << 3,
+    DockHierarchy = 1 << 4,
+}
+
+pub type FocusedFlags = BitFlags<FocusedFlagBits>;
+
+// Focus & activation
+// Property editors should commit values when `is_item_deactivated_after_edit` returns true
+impl Context {
+    /// The last item is being held (e.g a button pressed, a text input being edited)
+    pub fn is_item_active(&self) -> bool {
+        unsafe { igIsItemActive() }
+    }
+
+    pub fn is_item_focused(&self) -> bool {
+        unsafe { igIsItemFocused() }
+    }
+
+    /// The value of the last item changed this frame
+    pub fn is_item_edited(&self) -> bool {
+        unsafe { igIsItemEdited() }
+    }
+
+    /// The last item stopped being active this frame after its value changed
+    pub fn is_item_deactivated_after_edit(&self) -> bool {
+        unsafe { igIsItemDeactivatedAfterEdit() }
+    }
+
+    pub fn is_window_focused(&self, flags: FocusedFlags) -> bool {
+        unsafe { igIsWindowFocused(flags.bits() as i32) }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum InputTextFlagBits {
+    CharsDecimal = 1 << 0,
+    CharsHexadecimal = 1 << 1,
+    CharsUppercase = 1 << 2,
+    CharsNoBlank = 1 << 3,
+    AutoSelectAll = 1 << 4,
+    EnterReturnsTrue = 1 << 5,
+    CallbackCompletion = 1 << 6,
+    CallbackHistory = 1 << 7,
+    CallbackAlways = 1 << 8,
+    CallbackCharFilter = 1 << 9,
+    AllowTabInput = 1 << 10,
+    CtrlEnterForNewLine = 1 << 11,
+    NoHorizontalScroll = 1 << 12,
+    AlwaysOverwrite = 1 << 13,
+    ReadOnly = 1 << 14,
+    Password = 1 << 15,
+    NoUndoRedo = 1 << 16,
+    CharsScientific = 1 << 17,
+    CallbackResize = 1 << 18,
+    CallbackEdit = 1 << 19,
+}
+
+pub type InputTextFlags = BitFlags<InputTextFlagBits>;
+
+/// Data passed to input text callbacks, allow to inspect and modify the edited text
+pub struct InputTextCallbackData<'a> {
+    data: &'a mut ImGuiInputTextCallbackData,
+}
+
+impl<'a> InputTextCallbackData<'a> {
+    /// Flag of the callback that triggered the event
+    pub fn event_flag(&self) -> Option<InputTextFlagBits> {
+        InputTextFlags::from_bits_truncate(self.data.EventFlag as u32)
+            .iter()
+            .next()
+    }
+
+    /// Returns true if `key` triggered the event (only for `CallbackCompletion` and `CallbackHistory`)
+    pub fn is_event_key(&self, key: Key) -> bool {
+        self.data.EventKey == key as i32
+    }
+
+    pub fn text(&self) -> &str {
+        unsafe {
+            let bytes =
+                slice::from_raw_parts(self.data.Buf as *const u8, self.data.BufTextLen as usize);
+            std::str::from_utf8(bytes).unwrap_or_default()
+        }
+    }
+
+    pub fn cursor_pos(&self) -> usize {
+        self.data.CursorPos as usize
+    }
+
+    /// Replace the whole text
+    pub fn set_text(&mut self, text: &str) {
+        unsafe {
+            ImGuiInputTextCallbackData_DeleteChars(self.data, 0, self.data.BufTextLen);
+            ImGuiInputTextCallbackData_InsertChars(
+                self.data,
+                0,
+                text.as_ptr() as *const c_char,
+                text.as_ptr().add(text.len()) as *const c_char,
+            );
+        }
+    }
+}
+
+// Input text
+impl Context {
+    /// Edit `text`, returns true when it changed (or when enter is pressed with `EnterReturnsTrue`)
+    pub fn input_text(&mut self, label: &str, text: &mut String, flags: InputTextFlags) -> bool {
+        let c_label = self.str_buffer.convert(label);
+        input_text_internal(c_label, InputTextKind::SingleLine, text, flags, None)
+    }
+
+    /// Multiline text edit, a zero `size` uses the default size
+    pub fn input_text_multiline(
+        &mut self,
+        label: &str,
+        text: &mut String,
+        size: ImVec2,
+        flags: InputTextFlags,
+    ) -> bool {
+        let c_label = self.str_buffer.convert(label);
+        input_text_internal(c_label, InputTextKind::Multiline(size), text, flags, None)
+    }
+
+    /// Input text displaying `hint` while empty
+    pub fn input_text_with_hint(
+        &mut self,
+        label: &str,
+        hint: &str,
+        text: &mut String,
+        flags: InputTextFlags,
+    ) -> bool {
+        let (c_label, c_hint) = self.str_buffer.convert_pair(label, hint);
+        input_text_internal(c_label, InputTextKind::WithHint(c_hint), text, flags, None)
+    }
+
+    /// Input text calling `callback` for each event enabled by `flags` (e.g `CallbackHistory`)
+    pub fn input_text_with_callback<F: FnMut(&mut InputTextCallbackData)>(
+        &mut self,
+        label: &str,
+        text: &mut String,
+        flags: InputTextFlags,
+        mut callback: F,
+    ) -> bool {
+        let c_label = self.str_buffer.convert(label);
+        input_text_internal(
+            c_label,
+            InputTextKind::SingleLine,
+            text,
+            flags,
+            Some(&mut callback),
+        )
+    }
+}
+
+enum InputTextKind {
+    SingleLine,
+    Multiline(ImVec2),
+    WithHint(*const c_char),
+}
+
+struct InputTextUserData<'a> {
+    buffer: Vec<u8>,
+    callback: Option<&'a mut dyn FnMut(&mut InputTextCallbackData)>,
+}
+
+/// Call ImGui's InputText using a Rust string, growing the string buffer when ImGui requests it
+fn input_text_internal(
+    label: *const c_char,
+    kind: InputTextKind,
+    text: &mut String,
+    flags: InputTextFlags,
+    callback: Option<&mut dyn FnMut(&mut InputTextCallbackData)>,
+) -> bool {
+    unsafe extern "C" fn input_text_callback(data: *mut ImGuiInputTextCallbackData) -> c_int {
+        let data = &mut *data;
+        let user_data = &mut *(data.UserData as *mut InputTextUserData);
+        if data.EventFlag == ImGuiInputTextFlags__ImGuiInputTextFlags_CallbackResize as i32 {
+            user_data.buffer.resize(data.BufSize as usize, 0);
+            data.Buf = user_data.buffer.as_mut_ptr() as *mut c_char;
+        } else if let Some(callback) = &mut user_data.callback {
+            callback(&mut InputTextCallbackData { data });
+        }
+        0
+    }
+
+    let mut buffer = mem::take(text).into_bytes();
+    buffer.push(b'\0');
+
+    let mut user_data = InputTextUserData { buffer, callback };
+    let flags = flags | InputTextFlagBits::CallbackResize;
+    let changed = unsafe {
+        let buffer = user_data.buffer.as_mut_ptr() as *mut c_char;
+        let buffer_size = user_data.buffer.len() as _;
+        let user_data = &mut user_data as *mut InputTextUserData as *mut c_void;
+        match kind {
+            InputTextKind::SingleLine => igInputText(
+                label,
+                buffer,
+                buffer_size,
+                flags.bits() as i32,
+                Some(input_text_callback),
+                user_data,
+            ),
+            InputTextKind::Multiline(size) => igInputTextMultiline(
+                label,
+                buffer,
+                buffer_size,
+                size,
+                flags.bits() as i32,
+                Some(input_text_callback),
+                user_data,
+            ),
+            InputTextKind::WithHint(hint) => igInputTextWithHint(
+                label,
+                hint,
+                buffer,
+                buffer_size,
+                flags.bits() as i32,
+                Some(input_text_callback),
+                user_data,
+            ),
+        }
+    };
+
+    let mut buffer = user_data.buffer;
+    let len = buffer
+        .iter()
+        .position(|c| *c == b'\0')
+        .unwrap_or(buffer.len());
+    buffer.truncate(len);
+    *text = String::from_utf8(buffer).unwrap_or_default();
+    changed
+}
+
+// Numeric inputs
+// Values outside of `range` are clamped once edited, a `step` of 0 hides the step buttons
+impl Context {
+    pub fn input_int(
+        &mut self,
+        label: &str,
+        value: &mut i32,
+        step: i32,
+        range: Option<RangeInclusive<i32>>,
+        flags: InputTextFlags,
+    ) -> bool {
+        let label = self.str_buffer.convert(label);
+        let changed = unsafe { igInputInt(label, value, step, step * 10, flags.bits() as i32) };
+        if changed {
+            if let Some(range) = range {
+                *value = (*value).clamp(*range.start(), *range.end());
+            }
+        }
+        changed
+    }
+
+    pub fn input_float(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        step: f32,
+        range: Option<RangeInclusive<f32>>,
+        format: &str,
+        flags: InputTextFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        let changed =
+            unsafe { igInputFloat(label, value, step, step * 10.0, format, flags.bits() as i32) };
+        if changed {
+            if let Some(range) = range {
+                *value = value.clamp(*range.start(), *range.end());
+            }
+        }
+        changed
+    }
+
+    pub fn input_float3(
+        &mut self,
+        label: &str,
+        value: &mut [f32; 3],
+        range: Option<RangeInclusive<f32>>,
+        format: &str,
+        flags: InputTextFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        let changed =
+            unsafe { igInputFloat3(label, value.as_mut_ptr(), format, flags.bits() as i32) };
+        if changed {
+            if let Some(range) = range {
+                for value in value {
+                    *value = value.clamp(*range.start(), *range.end());
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum SliderFlagBits {
+    /// Clamp values entered with ctrl+click too
+    AlwaysClamp = 1 << 4,
+
+    /// Logarithmic scale, replaces the power curves of older ImGui versions
+    Logarithmic = 1 << 5,
+    NoRoundToFormat = 1 << 6,
+    NoInput = 1 << 7,
+}
+
+pub type SliderFlags = BitFlags<SliderFlagBits>;
+
+// Drags and sliders
+// `range` clamps the value, an empty range (e.g `0.0..=0.0`) leaves drags unbounded
+// `format` is a printf format (e.g "%.3f")
+impl Context {
+    pub fn drag_float(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        speed: f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        self.drag_floats(label, slice::from_mut(value), speed, range, format, flags)
+    }
+
+    pub fn drag_float2(
+        &mut self,
+        label: &str,
+        value: &mut [f32; 2],
+        speed: f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        self.drag_floats(label, value, speed, range, format, flags)
+    }
+
+    pub fn drag_float3(
+        &mut self,
+        label: &str,
+        value: &mut [f32; 3],
+        speed: f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        self.drag_floats(label, value, speed, range, format, flags)
+    }
+
+    pub fn drag_float4(
+        &mut self,
+        label: &str,
+        value: &mut [f32; 4],
+        speed: f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        self.drag_floats(label, value, speed, range, format, flags)
+    }
+
+    pub fn drag_int(
+        &mut self,
+        label: &str,
+        value: &mut i32,
+        speed: f32,
+        range: RangeInclusive<i32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        unsafe {
+            igDragInt(
+                label,
+                value,
+                speed,
+                *range.start(),
+                *range.end(),
+                format,
+                flags.bits() as i32,
+            )
+        }
+    }
+
+    pub fn slider_float(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        unsafe {
+            igSliderFloat(
+                label,
+                value,
+                *range.start(),
+                *range.end(),
+                format,
+                flags.bits() as i32,
+            )
+        }
+    }
+
+    pub fn slider_int(
+        &mut self,
+        label: &str,
+        value: &mut i32,
+        range: RangeInclusive<i32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        unsafe {
+            igSliderInt(
+                label,
+                value,
+                *range.start(),
+                *range.end(),
+                format,
+                flags.bits() as i32,
+            )
+        }
+    }
+
+    /// Slider editing an angle in radians, displayed and bounded in degrees
+    pub fn slider_angle(
+        &mut self,
+        label: &str,
+        radians: &mut f32,
+        degrees_range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        unsafe {
+            igSliderAngle(
+                label,
+                radians,
+                *degrees_range.start(),
+                *degrees_range.end(),
+                format,
+                flags.bits() as i32,
+            )
+        }
+    }
+
+    fn drag_floats(
+        &mut self,
+        label: &str,
+        values: &mut [f32],
+        speed: f32,
+        range: RangeInclusive<f32>,
+        format: &str,
+        flags: SliderFlags,
+    ) -> bool {
+        let (label, format) = self.str_buffer.convert_pair(label, format);
+        unsafe {
+            igDragScalarN(
+                label,
+                ImGuiDataType__ImGuiDataType_Float,
+                values.as_mut_ptr() as *mut c_void,
+                values.len() as i32,
+                speed,
+                range.start() as *const f32 as *const c_void,
+                range.end() as *const f32 as *const c_void,
+                format,
+                flags.bits() as i32,
+            )
+        }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum ColorEditFlagBits {
+    NoAlpha = 1 << 1,
+    NoPicker = 1 << 2,
+    NoOptions = 1 << 3,
+    NoSmallPreview = 1 << 4,
+    NoInputs = 1 << 5,
+    NoTooltip = 1 << 6,
+    NoLabel = 1 << 7,
+    NoSidePreview = 1 << 8,
+    NoDragDrop = 1 << 9,
+    NoBorder = 1 << 10,
+    AlphaBar = 1 << 16,
+    AlphaPreview = 1 << 17,
+    AlphaPreviewHalf = 1 << 18,
+
+    /// Allow values outside of the [0, 1] range
+    HDR = 1 << 19,
+    DisplayRGB = 1 << 20,
+    DisplayHSV = 1 << 21,
+    DisplayHex = 1 << 22,
+    Uint8 = 1 << 23,
+    Float = 1 << 24,
+    PickerHueBar = 1 << 25,
+    PickerHueWheel = 1 << 26,
+    InputRGB = 1 << 27,
+    InputHSV = 1 << 28,
+}
+
+pub type ColorEditFlags = BitFlags<ColorEditFlagBits>;
+
+// Colors
+// Colors can be any type convertible from and to RGBA arrays (e.g `Color4f32` or `ImVec4`)
+impl Context {
+    /// Edit the RGB components of `color`, alpha is left untouched
+    pub fn color_edit3<C>(&mut self, label: &str, color: &mut C, flags: ColorEditFlags) -> bool
+    where
+        C: Copy + Into<[f32; 4]> + From<[f32; 4]>,
+    {
+        let label = self.str_buffer.convert(label);
+        let mut rgba: [f32; 4] = (*color).into();
+        let changed = unsafe { igColorEdit3(label, rgba.as_mut_ptr(), flags.bits() as i32) };
+        *color = rgba.into();
+        changed
+    }
+
+    pub fn color_edit4<C>(&mut self, label: &str, color: &mut C, flags: ColorEditFlags) -> bool
+    where
+        C: Copy + Into<[f32; 4]> + From<[f32; 4]>,
+    {
+        let label = self.str_buffer.convert(label);
+        let mut rgba: [f32; 4] = (*color).into();
+        let changed = unsafe { igColorEdit4(label, rgba.as_mut_ptr(), flags.bits() as i32) };
+        *color = rgba.into();
+        changed
+    }
+
+    pub fn color_picker4<C>(&mut self, label: &str, color: &mut C, flags: ColorEditFlags) -> bool
+    where
+        C: Copy + Into<[f32; 4]> + From<[f32; 4]>,
+    {
+        let label = self.str_buffer.convert(label);
+        let mut rgba: [f32; 4] = (*color).into();
+        let changed = unsafe {
+            igColorPicker4(
+                label,
+                rgba.as_mut_ptr(),
+                flags.bits() as i32,
+                std::ptr::null(),
+            )
+        };
+        *color = rgba.into();
+        changed
+    }
+}
+
+type PlotFn = unsafe extern "C" fn(
+    *const c_char,
+    *const f32,
+    c_int,
+    c_int,
+    *const c_char,
+    f32,
+    f32,
+    ImVec2,
+    c_int,
+);
+
+// Plots
+// `None` scales are computed from the values, a zero `size` uses the default size
+impl Context {
+    pub fn plot_lines(
+        &mut self,
+        label: &str,
+        values: &[f32],
+        overlay: Option<&str>,
+        scale_min: Option<f32>,
+        scale_max: Option<f32>,
+        size: ImVec2,
+    ) {
+        self.plot(
+            igPlotLines_FloatPtr,
+            label,
+            values,
+            overlay,
+            scale_min,
+            scale_max,
+            size,
+        )
+    }
+
+    pub fn plot_histogram(
+        &mut self,
+        label: &str,
+        values: &[f32],
+        overlay: Option<&str>,
+        scale_min: Option<f32>,
+        scale_max: Option<f32>,
+        size: ImVec2,
+    ) {
+        self.plot(
+            igPlotHistogram_FloatPtr,
+            label,
+            values,
+            overlay,
+            scale_min,
+            scale_max,
+            size,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn plot(
+        &mut self,
+        plot: PlotFn,
+        label: &str,
+        values: &[f32],
+        overlay: Option<&str>,
+        scale_min: Option<f32>,
+        scale_max: Option<f32>,
+        size: ImVec2,
+    ) {
+        let (label, overlay) = match overlay {
+            Some(overlay) => self.str_buffer.convert_pair(label, overlay),
+            None => (self.str_buffer.convert(label), std::ptr::null()),
+        };
+
+        // ImGui computes scales given as FLT_MAX
+        unsafe {
+            plot(
+                label,
+                values.as_ptr(),
+                values.len() as c_int,
+                0,
+                overlay,
+                scale_min.unwrap_or(f32::MAX),
+                scale_max.unwrap_or(f32::MAX),
+                size,
+                size_of::<f32>() as c_int,
+            );
+        }
+    }
+}
+
+/// Font added to the context
+#[derive(Copy, Clone)]
+pub struct Font(usize);
+
+/// Glyphs of an icon font merged into a font with [`Context::merge_icon_font_from_memory`]
+/// Sizes and offsets are in pixels at 96 DPI
+#[derive(Clone)]
+pub struct IconFontConfig {
+    /// Codepoints taken from the icon font (e.g `0xe005..=0xf8ff` for Font Awesome)
+    pub glyph_ranges: Vec<RangeInclusive<u16>>,
+    pub size: f32,
+
+    /// Offset applied to the icons to align them with the text of the font
+    pub glyph_offset: ImVec2,
+
+    /// Minimum advance of the icons, a non-zero value makes them monospaced
+    pub glyph_min_advance_x: f32,
+}
+
+enum FontSource {
+    Memory(Vec<u8>),
+    Embedded,
+}
+
+/// Fonts are kept to be rasterized again when the DPI changes
+struct FontEntry {
+    source: FontSource,
+    size: f32,
+    icon_fonts: Vec<IconFont>,
+}
+
+struct IconFont {
+    source: FontSource,
+    config: IconFontConfig,
+
+    /// Zero-terminated ranges read by ImGui until the atlas is built
+    glyph_ranges: Vec<ImWchar>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    FileSystem(ze_filesystem::Error),
+    Io(std::io::Error),
+    InvalidYaml(serde_yaml::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Fonts
+// The first font added is the default one, ImGui embedded font is used if none are added
+// Fonts can't be added during a frame, the atlas is rebuilt by the next `begin_frame`
+// Sizes are in pixels at 96 DPI, fonts are rasterized for the DPI of the main viewport
+impl Context {
+    /// Add a TTF/OTF font
+    pub fn add_font_from_memory(&mut self, data: &[u8], size: f32) -> Font {
+        self.add_font(FontSource::Memory(data.to_vec()), size)
+    }
+
+    pub fn add_font_from_file(
+        &mut self,
+        filesystem: &FileSystem,
+        path: &Path,
+        size: f32,
+    ) -> Result<Font, Error> {
+        let mut data = vec![];
+        filesystem
+            .read(path)
+            .map_err(Error::FileSystem)?
+            .read_to_end(&mut data)
+            .map_err(Error::Io)?;
+        Ok(self.add_font(FontSource::Memory(data), size))
+    }
+
+    /// ImGui embedded font (ProggyClean), monospace and designed for a 13 pixels size
+    pub fn add_embedded_font(&mut self) -> Font {
+        self.add_font(FontSource::Embedded, 13.0)
+    }
+
+    /// Merge the glyphs of an icon font into `font`, they can then be displayed with `icon`
+    pub fn merge_icon_font_from_memory(&mut self, font: Font, data: &[u8], config: IconFontConfig) {
+        self.merge_icon_font(font, FontSource::Memory(data.to_vec()), config);
+    }
+
+    pub fn merge_icon_font_from_file(
+        &mut self,
+        font: Font,
+        filesystem: &FileSystem,
+        path: &Path,
+        config: IconFontConfig,
+    ) -> Result<(), Error> {
+        let mut data = vec![];
+        filesystem
+            .read(path)
+            .map_err(Error::FileSystem)?
+            .read_to_end(&mut data)
+            .map_err(Error::Io)?;
+        self.merge_icon_font(font, FontSource::Memory(data), config);
+        Ok(())
+    }
+
+    pub fn push_font(&mut self, font: Font) {
+        unsafe {
+            let fonts = &(*(*igGetIO()).Fonts).Fonts;
+            assert!(font.0 < fonts.Size as usize);
+            igPushFont(*fonts.Data.add(font.0));
+        }
+    }
+
+    pub fn pop_font(&mut self) {
+        unsafe { igPopFont() }
+    }
+
+    fn add_font(&mut self, source: FontSource, size: f32) -> Font {
+        add_font_to_atlas(&source, size * self.dpi_scale, None);
+        self.fonts.push(FontEntry {
+            source,
+            size,
+            icon_fonts: vec![],
+        });
+        Font(self.fonts.len() - 1)
+    }
+
+    fn merge_icon_font(&mut self, font: Font, source: FontSource, config: IconFontConfig) {
+        let mut glyph_ranges = Vec::with_capacity(config.glyph_ranges.len() * 2 + 1);
+        for range in &config.glyph_ranges {
+            glyph_ranges.push(*range.start());
+            glyph_ranges.push(*range.end());
+        }
+        glyph_ranges.push(0);
+
+        self.fonts[font.0].icon_fonts.push(IconFont {
+            source,
+            config,
+            glyph_ranges,
+        });
+
+        // Glyphs are merged into the last font added to the atlas
+        self.rebuild_font_atlas();
+    }
+
+    /// Rasterize fonts and scale the style for a new DPI scale, keeping font indices
+    fn set_dpi_scale(&mut self, dpi_scale: f32) {
+        self.dpi_scale = dpi_scale;
+        self.rebuild_font_atlas();
+        self.apply_base_style();
+    }
+
+    fn rebuild_font_atlas(&mut self) {
+        self.make_current();
+        unsafe {
+            ImFontAtlas_Clear((*igGetIO()).Fonts);
+        }
+
+        for font in &self.fonts {
+            add_font_to_atlas(&font.source, font.size * self.dpi_scale, None);
+            for icon_font in &font.icon_fonts {
+                add_font_to_atlas(
+                    &icon_font.source,
+                    icon_font.config.size * self.dpi_scale,
+                    Some((icon_font, self.dpi_scale)),
+                );
+            }
+        }
+    }
+
+    fn apply_base_style(&mut self) {
+        self.make_current();
+        unsafe {
+            let style = igGetStyle();
+            *style = self.base_style;
+            ImGuiStyle_ScaleAllSizes(style, self.dpi_scale);
+        }
+    }
+
+    /// Rasterize the fonts and (re)create the atlas texture
+    pub fn build_fonts(&mut self) {
+        self.make_current();
+        let font_texture = unsafe {
+            let io = igGetIO().as_mut().unwrap_unchecked();
+            let mut pixels = null_mut();
+            let mut width = 0;
+            let mut height = 0;
+            ImFontAtlas_GetTexDataAsRGBA32(
+                io.Fonts,
+                &mut pixels,
+                &mut width,
+                &mut height,
+                null_mut(),
+            );
+
+            let texture = self
+                .device
+                .create_texture(
+                    &TextureDesc {
+                        width: width as u32,
+                        height: height as u32,
+                        depth: 1,
+                        array_size: 1,
+                        mip_levels: 1,
+                        format: PixelFormat::R8G8B8A8Unorm,
+                        sample_desc: Default::default(),
+                        usage_flags: TextureUsageFlags::default(),
+                        memory_desc: MemoryDesc {
+                            memory_location: MemoryLocation::GpuOnly,
+                            memory_flags: Default::default(),
+                        },
+                    },
+                    None,
+                    "ImGui Font texture",
+                )
+                .expect("Failed to create ImGui font texture");
+
+            utils::copy_data_to_texture(
+                &self.device,
+                slice::from_raw_parts(pixels, (width * height * 4) as usize),
+                width as u32,
+                height as u32,
+                width as usize * 4,
+                &texture,
+                ResourceState::Common,
+            )
+            .expect("Failed to copy font texture data");
+
+            Arc::new(texture)
+        };
+
+        self.font_texture_view = Some(
+            self.device
+                .create_shader_resource_view(&ShaderResourceViewDesc::Texture2D(Texture2DSRV {
+                    texture: font_texture,
+                    format: PixelFormat::R8G8B8A8Unorm,
+                    min_mip_level: 0,
+                    mip_levels: 1,
+                }))
+                .expect("Failed to create ImGui font texture view"),
+        );
+    }
+
+    fn font_texture_view(&self) -> &ShaderResourceView {
+        self.font_texture_view
+            .as_ref()
+            .expect("Fonts must be built before drawing")
+    }
+}
+
+// Theme
+impl Context {
+    /// Apply a theme, sizes are scaled by the current DPI scale
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.follows_system_theme = false;
+        self.apply_theme(theme);
+    }
+
+    /// Apply the light or dark theme matching the system theme, and again each time it changes
+    /// until another theme is set with `set_theme`
+    pub fn follow_system_theme(&mut self) {
+        self.follows_system_theme = true;
+        self.apply_theme(&Theme::from_system_theme(self.platform.system_theme()));
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        theme.apply(&mut self.base_style);
+        self.apply_base_style();
     }
 
-    let renderer_data =
-        unsafe { (viewport.renderer_user_data() as *mut ViewportRendererData).as_mut() }.unwrap();
-
-    let draw_data = unsafe { viewport.draw_data().as_ref().unwrap_unchecked() };
-    renderer_data.update_buffers(device, draw_data);
-
-    if let Ok(shader) = shader_manager.shader_modules(&"ImGui".to_string(), None) {
-        if draw_data.CmdListsCount > 0 {
-            #[rustfmt::skip] 
-            let projection_matrix = {
-                let left = draw_data.DisplayPos.x;
-                let right = draw_data.DisplayPos.x + draw_data.DisplaySize.x;
-                let top = draw_data.DisplayPos.y;
-                let bottom = draw_data.DisplayPos.y + draw_data.DisplaySize.y;
-                Matrix4x4::<f32>::from([
-                    [2.0 / (right - left), 0.0, 0.0, 0.0],
-                    [0.0, 2.0 / (top - bottom), 0.0, 0.0],
-                    [0.0, 0.0, 0.5, 0.0],
-                    [(right + left) / (left - right), (top + bottom) / (bottom - top), 0.5, 1.0],
-                ])
-            };
+    pub fn theme(&self) -> Theme {
+        Theme::from_style(&self.base_style)
+    }
+}
 
-            let mut shader_data = ShaderData {
-                projection_matrix,
-                base_vertex_location: 0,
-                vertex_buffer: renderer_data
-                    .vertex_buffer_srv
-                    .as_ref()
-                    .unwrap()
-                    .descriptor_index(),
-                texture: font_texture.descriptor_index(),
-                texture_sampler: sampler.descriptor_index(),
-            };
+// Textures
+// Registered textures are kept alive until the draw data that may reference them is submitted
+impl Context {
+    pub fn register_texture(&mut self, srv: Arc<ShaderResourceView>) -> TextureId {
+        let id = TextureId(self.next_texture_id);
+        self.next_texture_id += 1;
+        self.textures.insert(
+            id,
+            RegisteredTexture {
+                srv,
+                unregistered: false,
+            },
+        );
+        id
+    }
 
-            device.cmd_set_shader_stages(cmd_list, &shader.pipeline_stages());
-
-            let mut blend_state = PipelineBlendState::default();
-            blend_state.render_targets[0] = PipelineRenderTargetBlendDesc {
-                enable_blend: true,
-                src_color_blend_factor: BlendFactor::SrcAlpha,
-                dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
-                color_blend_op: BlendOp::Add,
-                src_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
-                dst_alpha_blend_factor: BlendFactor::Zero,
-                alpha_blend_op: BlendOp::Add,
-            };
-            device.cmd_set_blend_state(cmd_list, &blend_state);
-            device.cmd_bind_index_buffer(
-                cmd_list,
-                renderer_data.index_buffer.as_ref().unwrap(),
-                IndexBufferFormat::Uint16,
+    pub fn unregister_texture(&mut self, id: TextureId) {
+        if let Some(texture) = self.textures.get_mut(&id) {
+            texture.unregistered = true;
+        }
+    }
+
+    /// Register a texture only for the current frame
+    pub fn frame_texture(&mut self, srv: Arc<ShaderResourceView>) -> TextureId {
+        let id = self.register_texture(srv);
+        self.frame_textures.push(id);
+        id
+    }
+}
+
+// List clipper
+impl Context {
+    /// Only submit the items of a large list that are visible
+    /// `f` is called with ranges of item indices to submit, `None` as `item_height` measures the
+    /// first submitted item
+    pub fn list_clipper<F: FnMut(&mut Context, Range<usize>)>(
+        &mut self,
+        count: usize,
+        item_height: Option<f32>,
+        mut f: F,
+    ) {
+        unsafe {
+            let clipper = ImGuiListClipper_ImGuiListClipper();
+            ImGuiListClipper_Begin(clipper, count as c_int, item_height.unwrap_or(-1.0));
+            while ImGuiListClipper_Step(clipper) {
+                let range = (*clipper).DisplayStart as usize..(*clipper).DisplayEnd as usize;
+                f(self, range);
+            }
+            ImGuiListClipper_End(clipper);
+            ImGuiListClipper_destroy(clipper);
+        }
+    }
+}
+
+// Debug windows
+// Windows are only shown while `open` is true, closing them sets it to false
+impl Context {
+    pub fn show_style_editor(&mut self, open: &mut bool) {
+        if !*open {
+            return;
+        }
+
+        let name = self.str_buffer.convert("Style Editor");
+        unsafe {
+            if igBegin(name, open, 0) {
+                igShowStyleEditor(null_mut());
+            }
+            igEnd();
+        }
+    }
+
+    pub fn show_demo_window(&mut self, open: &mut bool) {
+        if *open {
+            unsafe { igShowDemoWindow(open) };
+        }
+    }
+
+    pub fn show_metrics_window(&mut self, open: &mut bool) {
+        if *open {
+            unsafe { igShowMetricsWindow(open) };
+        }
+    }
+}
+
+// Performance overlay
+// Shows frame times and draw stats over the main viewport, stats are only collected while enabled
+impl Context {
+    pub fn set_performance_overlay_enabled(&mut self, enabled: bool) {
+        if enabled != self.performance_overlay.is_some() {
+            self.performance_overlay = enabled.then(PerformanceOverlay::default);
+        }
+    }
+
+    pub fn is_performance_overlay_enabled(&self) -> bool {
+        self.performance_overlay.is_some()
+    }
+
+    /// Show an application stat in the overlay this frame (e.g job system or GPU memory stats)
+    pub fn add_performance_stat(&mut self, name: &str, value: impl Display) {
+        if let Some(performance_overlay) = &mut self.performance_overlay {
+            performance_overlay.add_stat(name, value.to_string());
+        }
+    }
+}
+
+#[bitflags]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum DragDropFlagBits {
+    SourceNoPreviewTooltip = 1 << 0,
+    SourceNoDisableHover = 1 << 1,
+    SourceNoHoldToOpenOthers = 1 << 2,
+    SourceAllowNullID = 1 << 3,
+    SourceExtern = 1 << 4,
+    SourceAutoExpirePayload = 1 << 5,
+    AcceptBeforeDelivery = 1 << 10,
+    AcceptNoDrawDefaultRect = 1 << 11,
+    AcceptNoPreviewTooltip = 1 << 12,
+}
+
+pub type DragDropFlags = BitFlags<DragDropFlagBits>;
+
+// Dock builder
+// Nodes built after the dock space is submitted are applied on the next frame
+impl Context {
+    /// Remove all nodes and windows docked in `node_id` and recreate it as an empty dock space
+    pub fn dock_builder_reset(&mut self, node_id: ImGuiID, size: ImVec2) {
+        unsafe {
+            igDockBuilderRemoveNode(node_id);
+            igDockBuilderAddNode(
+                node_id,
+                ImGuiDockNodeFlagsPrivate__ImGuiDockNodeFlags_DockSpace,
             );
+            igDockBuilderSetNodeSize(node_id, size);
+        }
+    }
 
-            device.cmd_set_viewports(
-                cmd_list,
-                &[ze_gfx::backend::Viewport {
-                    position: Default::default(),
-                    size: Vector2::<f32>::new(draw_data.DisplaySize.x, draw_data.DisplaySize.y),
-                    min_depth: 0.0,
-                    max_depth: 1.0,
-                }],
+    /// Split `node_id` in two, returns the node at `dir` and the remaining node
+    ///
+    /// `ratio` is the size of the node at `dir`, relative to the size of `node_id`
+    pub fn dock_builder_split_node(
+        &mut self,
+        node_id: ImGuiID,
+        dir: Dir,
+        ratio: f32,
+    ) -> (ImGuiID, ImGuiID) {
+        let mut node_at_dir = 0;
+        let mut remaining_node = 0;
+        unsafe {
+            igDockBuilderSplitNode(
+                node_id,
+                dir.into(),
+                ratio,
+                &mut node_at_dir,
+                &mut remaining_node,
             );
+        }
+        (node_at_dir, remaining_node)
+    }
 
-            let mut vertex_offset = 0;
-            let mut index_offset = 0;
+    pub fn dock_builder_dock_window(&mut self, window_name: &str, node_id: ImGuiID) {
+        let window_name = self.str_buffer.convert(window_name);
+        unsafe { igDockBuilderDockWindow(window_name, node_id) }
+    }
 
-            let draw_lists = unsafe {
-                slice::from_raw_parts(draw_data.CmdLists, draw_data.CmdListsCount as usize)
-            };
+    /// Must be called once all the nodes of `node_id` are built
+    pub fn dock_builder_finish(&mut self, node_id: ImGuiID) {
+        unsafe { igDockBuilderFinish(node_id) }
+    }
+}
 
-            for draw_list in draw_lists {
-                let draw_list = unsafe { draw_list.as_ref().unwrap_unchecked() };
-                let cmd_buffers = unsafe {
-                    slice::from_raw_parts(
-                        draw_list.CmdBuffer.Data,
-                        draw_list.CmdBuffer.Size as usize,
-                    )
-                };
-                for cmd in cmd_buffers {
-                    let clip_offset = draw_data.DisplayPos;
+// Drag and drop
+impl Context {
+    pub fn begin_drag_drop_source(&self, flags: DragDropFlags) -> bool {
+        unsafe { igBeginDragDropSource(flags.bits() as i32) }
+    }
+
+    /// Set the payload of the current drag and drop source, `data` is copied by ImGui
+    pub fn set_drag_drop_payload(&mut self, ty: &str, data: &[u8]) -> bool {
+        let ty = self.str_buffer.convert(ty);
+        unsafe {
+            igSetDragDropPayload(
+                ty,
+                data.as_ptr() as *const c_void,
+                data.len() as _,
+                ImGuiCond__ImGuiCond_None as i32,
+            )
+        }
+    }
 
-                    let clip_min = Vector2::<i32>::new(
-                        (cmd.ClipRect.x - clip_offset.x) as i32,
-                        (cmd.ClipRect.y - clip_offset.y) as i32,
-                    );
+    pub fn end_drag_drop_source(&self) {
+        unsafe { igEndDragDropSource() }
+    }
+
+    pub fn begin_drag_drop_target(&self) -> bool {
+        unsafe { igBeginDragDropTarget() }
+    }
 
-                    let clip_max = Vector2::<i32>::new(
-                        (cmd.ClipRect.z - clip_offset.x) as i32,
-                        (cmd.ClipRect.w - clip_offset.y) as i32,
-                    );
+    /// Accept a payload of the specified type, returning its data when it has been dropped
+    pub fn accept_drag_drop_payload(&mut self, ty: &str, flags: DragDropFlags) -> Option<&[u8]> {
+        let ty = self.str_buffer.convert(ty);
+        unsafe {
+            let payload = igAcceptDragDropPayload(ty, flags.bits() as i32);
+            if payload.is_null() || (*payload).Data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(
+                    (*payload).Data as *const u8,
+                    (*payload).DataSize as usize,
+                ))
+            }
+        }
+    }
 
-                    let clip_rect = RectI32::new(clip_min.x, clip_min.y, clip_max.x, clip_max.y);
+    pub fn end_drag_drop_target(&self) {
+        unsafe { igEndDragDropTarget() }
+    }
+}
 
-                    device.cmd_set_scissors(cmd_list, &[clip_rect]);
+// Menus & context popups
+impl Context {
+    /// Begin the menu bar of the current window, created with `WindowFlagBits::MenuBar`
+    /// `end_menu_bar` must be called only if it returns true
+    pub fn begin_menu_bar(&mut self) -> bool {
+        unsafe { igBeginMenuBar() }
+    }
 
-                    shader_data.base_vertex_location = cmd.VtxOffset + vertex_offset;
-                    if cmd.TextureId.is_null() {
-                        shader_data.texture = font_texture.descriptor_index();
-                    } else {
-                        let srv = cmd.TextureId as *mut ShaderResourceView;
-                        shader_data.texture = unsafe { srv.as_ref() }.unwrap().descriptor_index();
-                    }
+    pub fn end_menu_bar(&mut self) {
+        unsafe { igEndMenuBar() }
+    }
 
-                    device.cmd_push_constants(cmd_list, 0, unsafe {
-                        slice::from_raw_parts(
-                            (&shader_data as *const ShaderData) as *const u8,
-                            size_of::<ShaderData>(),
-                        )
-                    });
-
-                    device.cmd_draw_indexed(
-                        cmd_list,
-                        cmd.ElemCount,
-                        1,
-                        cmd.IdxOffset + index_offset,
-                        0,
-                    );
+    pub fn begin_menu(&mut self, label: &str, enabled: bool) -> bool {
+        let label = self.str_buffer.convert(label);
+        unsafe { igBeginMenu(label, enabled) }
+    }
+
+    pub fn end_menu(&mut self) {
+        unsafe { igEndMenu() }
+    }
+
+    pub fn menu_item(
+        &mut self,
+        label: &str,
+        shortcut: Option<&str>,
+        selected: bool,
+        enabled: bool,
+    ) -> bool {
+        let (c_label, c_shortcut) = match shortcut {
+            Some(shortcut) => self.str_buffer.convert_pair(label, shortcut),
+            None => (self.str_buffer.convert(label), std::ptr::null()),
+        };
+        unsafe { igMenuItem_Bool(c_label, c_shortcut, selected, enabled) }
+    }
+
+    /// Begin a popup opened when right-clicking the last item
+    pub fn begin_popup_context_item(&mut self, id: &str) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe {
+            igBeginPopupContextItem(id, ImGuiPopupFlags__ImGuiPopupFlags_MouseButtonRight as i32)
+        }
+    }
+
+    /// Begin a popup opened when right-clicking the current window
+    pub fn begin_popup_context_window(&mut self, id: &str) -> bool {
+        let id = self.str_buffer.convert(id);
+        unsafe {
+            igBeginPopupContextWindow(
+                id,
+                ImGuiPopupFlags__ImGuiPopupFlags_MouseButtonRight as i32
+                    | ImGuiPopupFlags__ImGuiPopupFlags_NoOpenOverItems as i32,
+            )
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DialogResult<T> {
+    /// The dialog is closed or waiting for the user
+    Pending,
+    Confirmed(T),
+    Cancelled,
+}
+
+const DIALOG_BUTTON_SIZE: ImVec2 = ImVec2 { x: 120.0, y: 0.0 };
+
+// Dialogs
+// Dialogs are opened with `open_dialog` and must be submitted every frame, a result other than
+// `Pending` is returned once when the dialog is closed
+impl Context {
+    pub fn open_dialog(&mut self, id: &str) {
+        self.open_popup(id);
+    }
+
+    /// Open a `text_prompt` dialog with an initial text
+    pub fn open_text_prompt(&mut self, id: &str, text: &str) {
+        self.dialog_texts.insert(id.to_string(), text.to_string());
+        self.open_popup(id);
+    }
+
+    pub fn message_box(&mut self, id: &str, message: &str) -> DialogResult<()> {
+        self.dialog(id, message, |imgui| {
+            if imgui.button("Ok", DIALOG_BUTTON_SIZE) {
+                DialogResult::Confirmed(())
+            } else {
+                DialogResult::Pending
+            }
+        })
+    }
+
+    pub fn confirm_dialog(
+        &mut self,
+        id: &str,
+        message: &str,
+        confirm_label: &str,
+    ) -> DialogResult<()> {
+        self.dialog(id, message, |imgui| {
+            if imgui.button(confirm_label, DIALOG_BUTTON_SIZE) {
+                return DialogResult::Confirmed(());
+            }
+
+            imgui.same_line(0.0, -1.0);
+            if imgui.button("Cancel", DIALOG_BUTTON_SIZE) {
+                DialogResult::Cancelled
+            } else {
+                DialogResult::Pending
+            }
+        })
+    }
+
+    /// Ask for a text, confirmed by the "Ok" button or by pressing enter
+    pub fn text_prompt(&mut self, id: &str, message: &str) -> DialogResult<String> {
+        let mut text = self.dialog_texts.remove(id).unwrap_or_default();
+        let result = self.dialog(id, message, |imgui| {
+            if unsafe { igIsWindowAppearing() } {
+                imgui.set_keyboard_focus_here(0);
+            }
+
+            let mut confirmed = imgui.input_text(
+                "##Text",
+                &mut text,
+                make_bitflags!(InputTextFlagBits::{EnterReturnsTrue | AutoSelectAll}),
+            );
+            imgui.dummy(ImVec2::new(1.0, 10.0));
+            confirmed |= imgui.button("Ok", DIALOG_BUTTON_SIZE);
+            if confirmed {
+                return DialogResult::Confirmed(text.clone());
+            }
+
+            imgui.same_line(0.0, -1.0);
+            if imgui.button("Cancel", DIALOG_BUTTON_SIZE) {
+                DialogResult::Cancelled
+            } else {
+                DialogResult::Pending
+            }
+        });
+
+        if result == DialogResult::Pending {
+            self.dialog_texts.insert(id.to_string(), text);
+        }
+        result
+    }
+
+    /// Modal centered on the main viewport, closed when `buttons` returns a result
+    fn dialog<T, F: FnOnce(&mut Context) -> DialogResult<T>>(
+        &mut self,
+        id: &str,
+        message: &str,
+        buttons: F,
+    ) -> DialogResult<T> {
+        self.set_next_window_pos(
+            self.main_viewport().center(),
+            Cond::Appearing,
+            ImVec2::new(0.5, 0.5),
+        );
+
+        let mut open = true;
+        if !self.begin_popup_modal(
+            id,
+            &mut open,
+            make_bitflags! { WindowFlagBits::{AlwaysAutoResize} },
+        ) {
+            // Closed with the title bar button
+            return if open {
+                DialogResult::Pending
+            } else {
+                DialogResult::Cancelled
+            };
+        }
+
+        self.dummy(ImVec2::new(1.0, 15.0));
+        self.text_centered_wrapped(message, 100);
+        self.dummy(ImVec2::new(1.0, 25.0));
+        let result = buttons(self);
+        self.dummy(ImVec2::new(1.0, 15.0));
+
+        if !matches!(result, DialogResult::Pending) {
+            self.close_current_popup();
+        }
+        self.end_popup();
+        result
+    }
+}
+
+/// Parts of a secondary viewport window behaving like native decorations, in client coordinates
+#[derive(Default)]
+struct ViewportDecorations {
+    width: i32,
+    height: i32,
+    caption: Option<RectI32>,
+    resize_border: i32,
+}
+
+impl ViewportDecorations {
+    fn hit_test(&self, point: Point2<i32>) -> HitTest {
+        let left = point.x < self.resize_border;
+        let right = point.x >= self.width - self.resize_border;
+        let top = point.y < self.resize_border;
+        let bottom = point.y >= self.height - self.resize_border;
+
+        match (left, right, top, bottom) {
+            (true, _, true, _) => HitTest::TopLeft,
+            (_, true, true, _) => HitTest::TopRight,
+            (true, _, _, true) => HitTest::BottomLeft,
+            (_, true, _, true) => HitTest::BottomRight,
+            (true, ..) => HitTest::Left,
+            (_, true, ..) => HitTest::Right,
+            (_, _, true, _) => HitTest::Top,
+            (.., true) => HitTest::Bottom,
+            _ => match self.caption {
+                Some(caption)
+                    if point.x >= caption.x
+                        && point.x < caption.x + caption.width
+                        && point.y >= caption.y
+                        && point.y < caption.y + caption.height =>
+                {
+                    HitTest::Caption
                 }
+                _ => HitTest::Client,
+            },
+        }
+    }
+}
+
+struct ViewportPlatformData {
+    /// Context owning the viewport, used by the callbacks instead of the current context
+    context: *const Context,
+    window: Arc<dyn Window>,
+    decorations: Arc<Mutex<ViewportDecorations>>,
+
+    /// Last window position and size known by ImGui
+    position: Point2<i32>,
+    size: (u32, u32),
+}
+
+impl ViewportPlatformData {
+    fn new(context: &Context, window: Arc<dyn Window>) -> Self {
+        Self {
+            context,
+            position: window.position(),
+            size: (window.width(), window.height()),
+            window,
+            decorations: Default::default(),
+        }
+    }
+}
+
+fn imgui_mouse_button(button: MouseButton) -> c_int {
+    match button {
+        MouseButton::Left => ImGuiMouseButton__ImGuiMouseButton_Left as c_int,
+        MouseButton::Middle => ImGuiMouseButton__ImGuiMouseButton_Middle as c_int,
+        MouseButton::Right => ImGuiMouseButton__ImGuiMouseButton_Right as c_int,
+    }
+}
+
+/// Add a font to the atlas, icon fonts are merged into the previous font
+fn add_font_to_atlas(source: &FontSource, size: f32, icon_font: Option<(&IconFont, f32)>) {
+    unsafe {
+        let atlas = (*igGetIO()).Fonts;
+        let config = ImFontConfig_ImFontConfig();
+        (*config).SizePixels = size;
+        if let Some((icon_font, dpi_scale)) = icon_font {
+            (*config).MergeMode = true;
+            (*config).GlyphRanges = icon_font.glyph_ranges.as_ptr();
+            (*config).GlyphOffset = ImVec2::new(
+                icon_font.config.glyph_offset.x * dpi_scale,
+                icon_font.config.glyph_offset.y * dpi_scale,
+            );
+            (*config).GlyphMinAdvanceX = icon_font.config.glyph_min_advance_x * dpi_scale;
+        }
 
-                vertex_offset += draw_list.VtxBuffer.Size as u32;
-                index_offset += draw_list.IdxBuffer.Size as u32;
+        match source {
+            FontSource::Memory(data) => {
+                // The atlas takes ownership of the data and frees it using ImGui allocator
+                let font_data = igMemAlloc(data.len());
+                std::ptr::copy_nonoverlapping(data.as_ptr(), font_data as *mut u8, data.len());
+                ImFontAtlas_AddFontFromMemoryTTF(
+                    atlas,
+                    font_data,
+                    data.len() as c_int,
+                    size,
+                    config,
+                    (*config).GlyphRanges,
+                );
+            }
+            FontSource::Embedded => {
+                ImFontAtlas_AddFontDefault(atlas, config);
             }
         }
+
+        ImFontConfig_destroy(config);
     }
 }
 
+/// Context owning a viewport created by `platform_create_window`
+unsafe fn viewport_context<'a>(vp: *mut ImGuiViewport) -> &'a Context {
+    let platform_data = ((*vp).PlatformUserData as *const ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+    platform_data.context.as_ref().unwrap_unchecked()
+}
+
 // ImGui Platform IO callbacks
 unsafe extern "C" fn platform_create_window(vp: *mut ImGuiViewport) {
+    // Viewports are created by ImGui while their context is current
     let context = ((*igGetIO()).UserData as *const Context)
         .as_ref()
         .unwrap_unchecked();
@@ -1550,12 +4303,64 @@ unsafe extern "C" fn platform_create_window(vp: *mut ImGuiViewport) {
                 ze_platform::WindowFlags::from_flag(ze_platform::WindowFlagBits::Borderless),
             )
             .unwrap();
-        platform_data.write(ViewportPlatformData::new(window));
+        platform_data.write(ViewportPlatformData::new(context, window));
+
+        let decorations = (*platform_data).decorations.clone();
+        (*platform_data)
+            .window
+            .set_hit_test_callback(Some(Box::new(move |point| {
+                decorations.lock().hit_test(point)
+            })));
+
+        // ImGui windows can't be smaller, the OS must not let the user resize them below it
+        let min_size = (*igGetStyle()).WindowMinSize;
+        (*platform_data)
+            .window
+            .set_min_size(Some((min_size.x as u32, min_size.y as u32)));
     }
 
     viewport.PlatformUserData = platform_data as *mut c_void;
 }
 
+unsafe extern "C" fn platform_get_clipboard_text(user_data: *mut c_void) -> *const c_char {
+    let context = (user_data as *const Context).as_ref().unwrap_unchecked();
+    let text = context.platform.clipboard_text().unwrap_or_default();
+    context
+        .clipboard_text
+        .replace(CString::new(text).unwrap_or_default());
+    context.clipboard_text.borrow().as_ptr()
+}
+
+unsafe extern "C" fn platform_set_clipboard_text(user_data: *mut c_void, text: *const c_char) {
+    let context = (user_data as *const Context).as_ref().unwrap_unchecked();
+    context
+        .platform
+        .set_clipboard_text(&CStr::from_ptr(text).to_string_lossy());
+}
+
+/// Called when a text field is focused, to place the input method candidates below its cursor
+unsafe extern "C" fn platform_set_ime_data(
+    vp: *mut ImGuiViewport,
+    data: *mut ImGuiPlatformImeData,
+) {
+    let platform_data = match ((*vp).PlatformUserData as *const ViewportPlatformData).as_ref() {
+        Some(platform_data) => platform_data,
+        None => return,
+    };
+
+    let data = &*data;
+    let context = platform_data.context.as_ref().unwrap_unchecked();
+    context
+        .ime_position
+        .set(data.WantVisible.then_some(data.InputPos));
+    if data.WantVisible {
+        platform_data.window.set_ime_position(Point2::new(
+            (data.InputPos.x - (*vp).Pos.x) as i32,
+            (data.InputPos.y + data.InputLineHeight - (*vp).Pos.y) as i32,
+        ));
+    }
+}
+
 unsafe extern "C" fn platform_destroy_window(vp: *mut ImGuiViewport) {
     let platform_data = (*vp).PlatformUserData as *mut ViewportPlatformData;
     platform_data.drop_in_place();
@@ -1566,8 +4371,22 @@ unsafe extern "C" fn platform_destroy_window(vp: *mut ImGuiViewport) {
     (*vp).PlatformUserData = null_mut();
 }
 
-unsafe extern "C" fn platform_get_window_size(_: *mut ImGuiViewport, _: *mut ImVec2) {
-    todo!()
+unsafe extern "C" fn platform_get_window_size(vp: *mut ImGuiViewport, size: *mut ImVec2) {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    (*size).x = platform_user_data.window.width() as f32;
+    (*size).y = platform_user_data.window.height() as f32;
+}
+
+/// Windows may not use the DPI of the monitor ImGui thinks they are on
+unsafe extern "C" fn platform_get_window_dpi_scale(vp: *mut ImGuiViewport) -> f32 {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    platform_user_data.window.dpi_scale()
 }
 
 unsafe extern "C" fn platform_get_window_pos(vp: *mut ImGuiViewport, pos: *mut ImVec2) {
@@ -1581,19 +4400,21 @@ unsafe extern "C" fn platform_get_window_pos(vp: *mut ImGuiViewport, pos: *mut I
 
 unsafe extern "C" fn platform_set_window_pos(vp: *mut ImGuiViewport, pos: ImVec2) {
     let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
-        .as_ref()
+        .as_mut()
         .unwrap_unchecked();
 
+    platform_user_data.position = Point2::<i32>::new(pos.x as i32, pos.y as i32);
     platform_user_data
         .window
-        .set_position(Point2::<i32>::new(pos.x as i32, pos.y as i32));
+        .set_position(platform_user_data.position);
 }
 
 unsafe extern "C" fn platform_set_window_size(vp: *mut ImGuiViewport, size: ImVec2) {
     let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
-        .as_ref()
+        .as_mut()
         .unwrap_unchecked();
 
+    platform_user_data.size = (size.x as u32, size.y as u32);
     platform_user_data
         .window
         .set_size(size.x as u32, size.y as u32);
@@ -1616,14 +4437,51 @@ unsafe extern "C" fn platform_show_window(vp: *mut ImGuiViewport) {
         .unwrap_unchecked();
 
     platform_user_data.window.show();
+
+    // Window managers only keep shown windows on top
+    if (*vp).Flags & ImGuiViewportFlags__ImGuiViewportFlags_TopMost as ImGuiViewportFlags != 0 {
+        platform_user_data.window.set_always_on_top(true);
+    }
 }
 
-// Renderer
-unsafe extern "C" fn renderer_create_window(vp: *mut ImGuiViewport) {
-    let context = ((*igGetIO()).UserData as *const Context)
+unsafe extern "C" fn platform_set_window_alpha(vp: *mut ImGuiViewport, alpha: f32) {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    platform_user_data.window.set_opacity(alpha);
+}
+
+unsafe extern "C" fn platform_set_window_focus(vp: *mut ImGuiViewport) {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    platform_user_data.window.focus();
+}
+
+unsafe extern "C" fn platform_get_window_focus(vp: *mut ImGuiViewport) -> bool {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    platform_user_data.window.is_focused()
+}
+
+/// Minimized viewports are skipped when rendering
+unsafe extern "C" fn platform_get_window_minimized(vp: *mut ImGuiViewport) -> bool {
+    let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
         .as_ref()
         .unwrap_unchecked();
 
+    platform_user_data.window.is_minimized()
+}
+
+// Renderer
+#[allow(clippy::arc_with_non_send_sync)]
+unsafe extern "C" fn renderer_create_window(vp: *mut ImGuiViewport) {
+    let context = viewport_context(vp);
+
     let viewport = vp.as_mut().unwrap_unchecked();
     let renderer_data =
         std::alloc::alloc(Layout::new::<ViewportRendererData>()) as *mut ViewportRendererData;
@@ -1642,6 +4500,7 @@ unsafe extern "C" fn renderer_create_window(vp: *mut ImGuiViewport) {
                     sample_desc: SampleDesc::default(),
                     usage_flags: TextureUsageFlags::from_flag(TextureUsageFlagBits::RenderTarget),
                     window_handle: (*platform_data).window.handle(),
+                    display_handle: (*platform_data).window.display_handle(),
                 },
                 None,
             )
@@ -1683,16 +4542,15 @@ unsafe extern "C" fn renderer_destroy_window(vp: *mut ImGuiViewport) {
     (*vp).RendererUserData = null_mut();
 }
 
+#[allow(clippy::arc_with_non_send_sync)]
 unsafe extern "C" fn renderer_set_window_size(vp: *mut ImGuiViewport, size: ImVec2) {
-    let context = ((*igGetIO()).UserData as *const Context)
-        .as_ref()
-        .unwrap_unchecked();
+    let context = viewport_context(vp);
 
     let platform_user_data = ((*vp).PlatformUserData as *mut ViewportPlatformData)
         .as_ref()
         .unwrap_unchecked();
 
-    let mut renderer_user_data = ((*vp).RendererUserData as *mut ViewportRendererData)
+    let renderer_user_data = ((*vp).RendererUserData as *mut ViewportRendererData)
         .as_mut()
         .unwrap_unchecked();
 
@@ -1712,6 +4570,7 @@ unsafe extern "C" fn renderer_set_window_size(vp: *mut ImGuiViewport, size: ImVe
                     sample_desc: SampleDesc::default(),
                     usage_flags: TextureUsageFlags::from_flag(TextureUsageFlagBits::RenderTarget),
                     window_handle: platform_user_data.window.handle(),
+                    display_handle: platform_user_data.window.display_handle(),
                 },
                 Some(Arc::try_unwrap(old_swapchain.assume_init()).expect("Failed to unwrap arc!")),
             )
@@ -1741,15 +4600,47 @@ unsafe extern "C" fn renderer_set_window_size(vp: *mut ImGuiViewport, size: ImVe
     }
 }
 
-unsafe extern "C" fn renderer_swap_buffers(_: *mut ImGuiViewport, _: *mut c_void) {
-    unimplemented!();
+unsafe extern "C" fn renderer_swap_buffers(vp: *mut ImGuiViewport, _: *mut c_void) {
+    let context = viewport_context(vp);
+
+    let renderer_user_data = ((*vp).RendererUserData as *mut ViewportRendererData)
+        .as_ref()
+        .unwrap_unchecked();
+
+    if let SwapChainType::Owned((swapchain, _)) = &renderer_user_data.swapchain {
+        context.device.present(swapchain.assume_init_ref());
+    }
 }
 
-unsafe extern "C" fn renderer_render_window(_: *mut ImGuiViewport, _: *mut c_void) {
-    unimplemented!();
+unsafe extern "C" fn renderer_render_window(vp: *mut ImGuiViewport, _: *mut c_void) {
+    let context = viewport_context(vp);
+
+    let viewport = (vp as *mut Viewport).as_mut().unwrap_unchecked();
+
+    let mut cmd_list = context
+        .device
+        .create_command_list(QueueType::Graphics)
+        .expect("Failed to create ImGui viewport command list");
+    context.draw_to_swapchain(viewport, &mut cmd_list);
+    context
+        .device
+        .submit(QueueType::Graphics, &[&cmd_list], &[], &[]);
+
+    let renderer_user_data = ((*vp).RendererUserData as *mut ViewportRendererData)
+        .as_mut()
+        .unwrap_unchecked();
+    renderer_user_data.cmd_list = Some(cmd_list);
 }
 
+mod builders;
+mod performance_overlay;
 mod renderer;
 mod str_buffer;
+mod theme;
+mod tree_view;
+
+pub use builders::{ChildBuilder, PopupBuilder, WindowBuilder};
+pub use theme::Theme;
+pub use tree_view::{TreeView, TreeViewEvent};
 
 pub extern crate ze_imgui_sys;
@@ -0,0 +1,115 @@
+use crate::{Cond, Context, WindowFlagBits};
+use enumflags2::make_bitflags;
+use std::collections::VecDeque;
+use std::slice;
+use ze_imgui_sys::*;
+
+/// Frames displayed by the frame time graph
+const FRAME_TIME_HISTORY: usize = 120;
+
+/// Distance between the overlay and the top right corner of the main viewport
+const OVERLAY_MARGIN: f32 = 10.0;
+
+/// Always on top window showing frame times and what was rendered by the last frame
+#[derive(Default)]
+pub(crate) struct PerformanceOverlay {
+    /// In milliseconds
+    frame_times: VecDeque<f32>,
+    draw_calls: usize,
+    vertices: usize,
+    indices: usize,
+
+    /// Stats added by the application this frame
+    stats: Vec<(String, String)>,
+}
+
+impl PerformanceOverlay {
+    pub fn add_frame_time(&mut self, delta_time: f32) {
+        if self.frame_times.len() == FRAME_TIME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(delta_time * 1000.0);
+    }
+
+    pub fn add_stat(&mut self, name: &str, value: String) {
+        self.stats.push((name.to_string(), value));
+    }
+
+    /// Count the draw commands of every viewport, must be called after `igRender`
+    pub fn update_draw_stats(&mut self) {
+        self.draw_calls = 0;
+        self.vertices = 0;
+        self.indices = 0;
+
+        let platform_io = unsafe { igGetPlatformIO().as_ref().unwrap_unchecked() };
+        let viewports = unsafe {
+            slice::from_raw_parts(
+                platform_io.Viewports.Data,
+                platform_io.Viewports.Size as usize,
+            )
+        };
+
+        for viewport in viewports {
+            let draw_data = match unsafe { (**viewport).DrawData.as_ref() } {
+                Some(draw_data) => draw_data,
+                None => continue,
+            };
+
+            let draw_lists = unsafe {
+                slice::from_raw_parts(draw_data.CmdLists, draw_data.CmdListsCount as usize)
+            };
+            for draw_list in draw_lists {
+                self.draw_calls += unsafe { (**draw_list).CmdBuffer.Size } as usize;
+            }
+
+            self.vertices += draw_data.TotalVtxCount as usize;
+            self.indices += draw_data.TotalIdxCount as usize;
+        }
+    }
+
+    pub fn draw(&mut self, imgui: &mut Context) {
+        let viewport = imgui.main_viewport();
+        let position = ImVec2::new(
+            viewport.work_pos().x + viewport.work_size().x - OVERLAY_MARGIN,
+            viewport.work_pos().y + OVERLAY_MARGIN,
+        );
+        imgui.set_next_window_viewport(viewport);
+        imgui.set_next_window_pos(position, Cond::Always, ImVec2::new(1.0, 0.0));
+        unsafe { igSetNextWindowBgAlpha(0.35) };
+
+        let flags = make_bitflags!(WindowFlagBits::{NoTitleBar | NoResize | NoMove | NoScrollbar
+            | NoCollapse | AlwaysAutoResize | NoSavedSettings | NoFocusOnAppearing | NoNavInputs
+            | NoNavFocus | NoDocking | NoMouseInputs});
+        if imgui.begin_window("##PerformanceOverlay", flags) {
+            unsafe { igBringWindowToDisplayFront(igGetCurrentWindow()) };
+
+            let frame_time = self.frame_times.back().copied().unwrap_or_default();
+            if frame_time > 0.0 {
+                imgui.text(&format!(
+                    "{:.2} ms ({:.0} FPS)",
+                    frame_time,
+                    1000.0 / frame_time
+                ));
+            }
+
+            imgui.plot_lines(
+                "##FrameTimes",
+                self.frame_times.make_contiguous(),
+                None,
+                Some(0.0),
+                None,
+                ImVec2::new(200.0, 40.0),
+            );
+
+            imgui.text(&format!("Draw calls: {}", self.draw_calls));
+            imgui.text(&format!("Vertices: {}", self.vertices));
+            imgui.text(&format!("Indices: {}", self.indices));
+            for (name, value) in &self.stats {
+                imgui.text(&format!("{}: {}", name, value));
+            }
+        }
+        imgui.end_window();
+
+        self.stats.clear();
+    }
+}
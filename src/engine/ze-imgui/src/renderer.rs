@@ -29,6 +29,9 @@ pub struct ViewportRendererData {
     pub vertex_buffer: Option<Arc<Buffer>>,
     pub vertex_buffer_srv: Option<ShaderResourceView>,
     pub index_buffer: Option<Arc<Buffer>>,
+
+    /// Recorded by `Renderer_RenderWindow`, kept alive until the viewport is rendered again
+    pub cmd_list: Option<CommandList>,
 }
 
 impl ViewportRendererData {
@@ -1,33 +1,62 @@
 ﻿use std::os::raw::c_char;
-use std::ptr;
 
-const DEFAULT_BUFFER_CAPACITY_IN_CHARS: usize = 1024;
+const CHUNK_CAPACITY_IN_CHARS: usize = 4096;
 
-/// Simple buffer to convert from Rust strings to C-null terminated UTF-8 strings
+/// Frame arena converting Rust strings to C-null terminated UTF-8 strings
+///
+/// Converted strings are never moved, so multiple strings can be used by the same call.
+/// Memory is reclaimed when the arena is reset at the beginning of each frame.
 pub struct StrBuffer {
-    buffer: Vec<u8>,
+    chunks: Vec<Vec<u8>>,
+    current_chunk: usize,
 }
 
 impl StrBuffer {
+    /// The returned pointer is valid until the next reset
     pub fn convert(&mut self, text: &str) -> *const c_char {
-        if text.len() > self.buffer.len() {
-            self.buffer.resize(text.len().next_power_of_two(), 0);
+        let chunk = self.chunk_with_space(text.len() + 1);
+        let offset = chunk.len();
+        chunk.extend_from_slice(text.as_bytes());
+        chunk.push(b'\0');
+        unsafe { chunk.as_ptr().add(offset) as *const c_char }
+    }
+
+    /// Convert two strings for functions taking both, e.g a label and a format
+    pub fn convert_pair(&mut self, first: &str, second: &str) -> (*const c_char, *const c_char) {
+        (self.convert(first), self.convert(second))
+    }
+
+    /// Invalidate every converted string, chunks are kept for the next frame
+    pub fn reset(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.clear();
         }
+        self.current_chunk = 0;
+    }
 
-        unsafe {
-            ptr::copy_nonoverlapping(text.as_ptr(), self.buffer.as_mut_ptr(), text.len());
+    /// Find a chunk able to hold `len` more bytes without reallocating
+    fn chunk_with_space(&mut self, len: usize) -> &mut Vec<u8> {
+        while let Some(chunk) = self.chunks.get(self.current_chunk) {
+            if chunk.capacity() - chunk.len() >= len {
+                break;
+            }
+            self.current_chunk += 1;
         }
-        self.buffer[text.len()] = b'\0';
-        self.buffer.as_ptr() as *const c_char
+
+        if self.current_chunk == self.chunks.len() {
+            self.chunks
+                .push(Vec::with_capacity(len.max(CHUNK_CAPACITY_IN_CHARS)));
+        }
+
+        &mut self.chunks[self.current_chunk]
     }
 }
 
 impl Default for StrBuffer {
     fn default() -> Self {
-        let buffer = [0; DEFAULT_BUFFER_CAPACITY_IN_CHARS];
-
         Self {
-            buffer: Vec::from(buffer),
+            chunks: vec![Vec::with_capacity(CHUNK_CAPACITY_IN_CHARS)],
+            current_chunk: 0,
         }
     }
 }
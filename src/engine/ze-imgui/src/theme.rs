@@ -0,0 +1,370 @@
+use crate::Error;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::io::{Read, Write};
+use ze_filesystem::path::Path;
+use ze_filesystem::FileSystem;
+use ze_imgui_sys::*;
+use ze_platform::SystemTheme;
+
+/// Sizes and colors of the ImGui style, saved as YAML
+///
+/// Sizes are at a 1.0 DPI scale, colors are indexed by their ImGui name (e.g `WindowBg`)
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Theme {
+    pub window_rounding: f32,
+    pub frame_rounding: f32,
+    pub tab_rounding: f32,
+    pub scrollbar_rounding: f32,
+    pub window_padding: [f32; 2],
+    pub cell_padding: [f32; 2],
+    pub item_spacing: [f32; 2],
+    pub indent_spacing: f32,
+    pub window_border_size: f32,
+    pub frame_border_size: f32,
+    pub popup_border_size: f32,
+    pub tab_border_size: f32,
+
+    /// Colors missing from the map are left unchanged when applying the theme
+    pub colors: BTreeMap<String, [f32; 4]>,
+}
+
+impl Theme {
+    /// Default ZE theme
+    pub fn dark() -> Self {
+        let mut theme = Self {
+            window_rounding: 0.0,
+            frame_rounding: 3.0,
+            tab_rounding: 2.0,
+            scrollbar_rounding: 0.0,
+            window_padding: [3.0, 1.0],
+            cell_padding: [1.0, 0.0],
+            item_spacing: [8.0, 4.0],
+            indent_spacing: 9.0,
+            window_border_size: 0.0,
+            frame_border_size: 0.0,
+            popup_border_size: 1.0,
+            tab_border_size: 1.0,
+            colors: BTreeMap::default(),
+        };
+
+        theme.set_color(ImGuiCol__ImGuiCol_Text, ImVec4::new(0.79, 0.79, 0.79, 1.0));
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TextDisabled,
+            ImVec4::new(0.50, 0.50, 0.50, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_WindowBg,
+            ImVec4::new(0.07, 0.07, 0.07, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ChildBg,
+            ImVec4::new(0.14, 0.14, 0.14, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_PopupBg,
+            ImVec4::new(0.20, 0.20, 0.20, 0.94),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_Border,
+            ImVec4::new(0.09, 0.09, 0.09, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_BorderShadow,
+            ImVec4::new(0.00, 0.00, 0.00, 0.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_FrameBg,
+            ImVec4::new(0.09, 0.09, 0.09, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_FrameBgHovered,
+            ImVec4::new(0.05, 0.05, 0.05, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_FrameBgActive,
+            ImVec4::new(0.33, 0.33, 0.33, 0.67),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TitleBg,
+            ImVec4::new(0.16, 0.16, 0.16, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TitleBgActive,
+            ImVec4::new(0.16, 0.16, 0.16, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TitleBgCollapsed,
+            ImVec4::new(0.00, 0.00, 0.00, 0.51),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_MenuBarBg,
+            ImVec4::new(0.14, 0.14, 0.14, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ScrollbarBg,
+            ImVec4::new(0.02, 0.02, 0.02, 0.53),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ScrollbarGrab,
+            ImVec4::new(0.31, 0.31, 0.31, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ScrollbarGrabHovered,
+            ImVec4::new(0.41, 0.41, 0.41, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ScrollbarGrabActive,
+            ImVec4::new(0.51, 0.51, 0.51, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_CheckMark,
+            ImVec4::new(0.71, 0.71, 0.71, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_SliderGrab,
+            ImVec4::new(0.29, 0.29, 0.29, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_SliderGrabActive,
+            ImVec4::new(0.26, 0.26, 0.26, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_Button,
+            ImVec4::new(0.29, 0.29, 0.29, 0.40),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ButtonHovered,
+            ImVec4::new(0.26, 0.26, 0.26, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ButtonActive,
+            ImVec4::new(0.23, 0.23, 0.23, 1.00),
+        );
+        theme.set_color(ImGuiCol__ImGuiCol_Header, ImVec4::from(0.115));
+        theme.set_color(
+            ImGuiCol__ImGuiCol_HeaderHovered,
+            ImVec4::new(0.27, 0.33, 0.43, 0.45),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_HeaderActive,
+            ImVec4::new(0.27, 0.33, 0.63, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_Separator,
+            ImVec4::new(0.25, 0.25, 0.25, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_SeparatorHovered,
+            ImVec4::new(0.15, 0.14, 0.16, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_SeparatorActive,
+            ImVec4::new(0.14, 0.13, 0.16, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ResizeGrip,
+            ImVec4::new(0.00, 0.00, 0.00, 0.25),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ResizeGripHovered,
+            ImVec4::new(0.11, 0.11, 0.11, 0.67),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ResizeGripActive,
+            ImVec4::new(0.00, 0.00, 0.00, 0.95),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_Tab,
+            ImVec4::new(0.078, 0.078, 0.078, 1.0),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TabHovered,
+            ImVec4::new(0.29, 0.29, 0.29, 0.80),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TabActive,
+            ImVec4::new(0.14, 0.14, 0.14, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TabUnfocused,
+            ImVec4::new(0.24, 0.24, 0.24, 0.97),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TabUnfocusedActive,
+            ImVec4::new(0.24, 0.24, 0.24, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_DockingPreview,
+            ImVec4::new(0.26, 0.59, 0.98, 0.70),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_DockingEmptyBg,
+            ImVec4::new(0.12, 0.12, 0.12, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_PlotLines,
+            ImVec4::new(0.61, 0.61, 0.61, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_PlotLinesHovered,
+            ImVec4::new(1.00, 0.43, 0.35, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_PlotHistogram,
+            ImVec4::new(0.90, 0.70, 0.00, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_PlotHistogramHovered,
+            ImVec4::new(1.00, 0.60, 0.00, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TableHeaderBg,
+            ImVec4::new(0.19, 0.19, 0.20, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TableBorderStrong,
+            ImVec4::new(0.31, 0.31, 0.35, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TableBorderLight,
+            ImVec4::new(0.10, 0.10, 0.10, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TableRowBg,
+            ImVec4::new(0.00, 0.00, 0.00, 0.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TableRowBgAlt,
+            ImVec4::new(1.00, 1.00, 1.00, 0.06),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_TextSelectedBg,
+            ImVec4::new(0.26, 0.59, 0.98, 0.35),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_DragDropTarget,
+            ImVec4::new(1.00, 1.00, 0.00, 0.90),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_NavHighlight,
+            ImVec4::new(0.26, 0.59, 0.98, 1.00),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_NavWindowingHighlight,
+            ImVec4::new(1.00, 1.00, 1.00, 0.70),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_NavWindowingDimBg,
+            ImVec4::new(0.80, 0.80, 0.80, 0.20),
+        );
+        theme.set_color(
+            ImGuiCol__ImGuiCol_ModalWindowDimBg,
+            ImVec4::new(0.80, 0.80, 0.80, 0.0),
+        );
+        theme
+    }
+
+    /// Sizes of the dark theme with ImGui light colors
+    pub fn light() -> Self {
+        let mut theme = Self::dark();
+        unsafe {
+            let style = ImGuiStyle_ImGuiStyle();
+            igStyleColorsLight(style);
+            for (index, color) in (*style).Colors.iter().enumerate() {
+                theme.set_color(index as ImGuiCol_, *color);
+            }
+            ImGuiStyle_destroy(style);
+        }
+        theme
+    }
+
+    pub fn from_system_theme(theme: SystemTheme) -> Self {
+        match theme {
+            SystemTheme::Light => Self::light(),
+            SystemTheme::Dark => Self::dark(),
+        }
+    }
+
+    pub fn load(filesystem: &FileSystem, path: &Path) -> Result<Self, Error> {
+        let mut content = String::new();
+        filesystem
+            .read(path)
+            .map_err(Error::FileSystem)?
+            .read_to_string(&mut content)
+            .map_err(Error::Io)?;
+        serde_yaml::from_str(&content).map_err(Error::InvalidYaml)
+    }
+
+    pub fn save(&self, filesystem: &FileSystem, path: &Path) -> Result<(), Error> {
+        let content = serde_yaml::to_string(self).map_err(Error::InvalidYaml)?;
+        filesystem
+            .write(path)
+            .map_err(Error::FileSystem)?
+            .write_all(content.as_bytes())
+            .map_err(Error::Io)
+    }
+
+    pub fn set_color(&mut self, color: ImGuiCol_, value: ImVec4) {
+        self.colors
+            .insert(color_name(color).to_string(), <[f32; 4]>::from(value));
+    }
+
+    pub(crate) fn from_style(style: &ImGuiStyle) -> Self {
+        let mut theme = Self {
+            window_rounding: style.WindowRounding,
+            frame_rounding: style.FrameRounding,
+            tab_rounding: style.TabRounding,
+            scrollbar_rounding: style.ScrollbarRounding,
+            window_padding: [style.WindowPadding.x, style.WindowPadding.y],
+            cell_padding: [style.CellPadding.x, style.CellPadding.y],
+            item_spacing: [style.ItemSpacing.x, style.ItemSpacing.y],
+            indent_spacing: style.IndentSpacing,
+            window_border_size: style.WindowBorderSize,
+            frame_border_size: style.FrameBorderSize,
+            popup_border_size: style.PopupBorderSize,
+            tab_border_size: style.TabBorderSize,
+            colors: BTreeMap::default(),
+        };
+
+        for (index, color) in style.Colors.iter().enumerate() {
+            theme.set_color(index as ImGuiCol_, *color);
+        }
+        theme
+    }
+
+    pub(crate) fn apply(&self, style: &mut ImGuiStyle) {
+        style.WindowRounding = self.window_rounding;
+        style.FrameRounding = self.frame_rounding;
+        style.TabRounding = self.tab_rounding;
+        style.ScrollbarRounding = self.scrollbar_rounding;
+        style.WindowPadding = ImVec2::new(self.window_padding[0], self.window_padding[1]);
+        style.CellPadding = ImVec2::new(self.cell_padding[0], self.cell_padding[1]);
+        style.ItemSpacing = ImVec2::new(self.item_spacing[0], self.item_spacing[1]);
+        style.IndentSpacing = self.indent_spacing;
+        style.WindowBorderSize = self.window_border_size;
+        style.FrameBorderSize = self.frame_border_size;
+        style.PopupBorderSize = self.popup_border_size;
+        style.TabBorderSize = self.tab_border_size;
+
+        for (index, color) in style.Colors.iter_mut().enumerate() {
+            if let Some(value) = self.colors.get(color_name(index as ImGuiCol_)) {
+                *color = ImVec4::from(*value);
+            }
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+fn color_name(color: ImGuiCol_) -> &'static str {
+    unsafe { CStr::from_ptr(igGetStyleColorName(color)) }
+        .to_str()
+        .unwrap_or_default()
+}
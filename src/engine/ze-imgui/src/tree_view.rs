@@ -0,0 +1,165 @@
+use crate::{Cond, Context, Key, TreeNodeFlagBits, TreeNodeFlags};
+use std::collections::HashMap;
+use std::hash::Hash;
+use ze_imgui_sys::igGetFrameCount;
+use ze_platform::MouseButton;
+
+pub enum TreeViewEvent<T> {
+    /// Selection changed by a click, the last item is the primary selection
+    SelectionChanged(Vec<T>),
+
+    /// Node double clicked
+    Activated(T),
+}
+
+/// Tree of nodes identified by `T`, keeping their open state and a multi-selection
+///
+/// Nodes are submitted every frame with [`TreeView::node`], ctrl-click toggles a node
+/// selection and shift-click selects a range of nodes.
+/// Events are queued until [`TreeView::for_each_event`] is called.
+pub struct TreeView<T> {
+    multi_selection: bool,
+    open: HashMap<T, bool>,
+    selection: Vec<T>,
+
+    /// Start of shift-click ranges
+    anchor: Option<T>,
+
+    /// Nodes in submission order, ranges are resolved with the previous frame order
+    order: Vec<T>,
+    previous_order: Vec<T>,
+    frame: i32,
+
+    events: Vec<TreeViewEvent<T>>,
+}
+
+impl<T: Clone + Eq + Hash> TreeView<T> {
+    pub fn new(multi_selection: bool) -> Self {
+        Self {
+            multi_selection,
+            open: HashMap::default(),
+            selection: vec![],
+            anchor: None,
+            order: vec![],
+            previous_order: vec![],
+            frame: -1,
+            events: vec![],
+        }
+    }
+
+    /// Submit a node, `tree_pop` must be called if it returns true
+    ///
+    /// `Selected` is managed by the tree view and ignored from `flags`
+    pub fn node(
+        &mut self,
+        imgui: &mut Context,
+        item: T,
+        label: &str,
+        mut flags: TreeNodeFlags,
+    ) -> bool {
+        let frame = unsafe { igGetFrameCount() };
+        if frame != self.frame {
+            self.frame = frame;
+            self.previous_order = std::mem::take(&mut self.order);
+        }
+        self.order.push(item.clone());
+
+        flags.set(TreeNodeFlagBits::Selected, self.is_selected(&item));
+        if let Some(open) = self.open.get(&item) {
+            imgui.set_next_item_open(*open, Cond::Always);
+        }
+
+        let open = imgui.tree_node_ex(label, flags);
+        self.open.insert(item.clone(), open);
+
+        if imgui.is_item_clicked(MouseButton::Left) && !imgui.is_item_toggled_open() {
+            self.click(imgui, item.clone());
+        }
+
+        if imgui.is_item_hovered() && imgui.is_mouse_double_clicked(MouseButton::Left) {
+            self.events.push(TreeViewEvent::Activated(item));
+        }
+
+        open
+    }
+
+    fn click(&mut self, imgui: &Context, item: T) {
+        let ctrl = imgui.is_key_down(Key::LeftCtrl) || imgui.is_key_down(Key::RightCtrl);
+        let shift = imgui.is_key_down(Key::LeftShift) || imgui.is_key_down(Key::RightShift);
+
+        if self.multi_selection && shift {
+            if let Some(range) = self.range(&item) {
+                if !ctrl {
+                    self.selection.clear();
+                }
+                for range_item in range {
+                    if !self.selection.contains(&range_item) {
+                        self.selection.push(range_item);
+                    }
+                }
+                // Keep the clicked item as the primary selection
+                self.selection.retain(|selected| *selected != item);
+                self.selection.push(item);
+                self.notify_selection_changed();
+                return;
+            }
+        }
+
+        if self.multi_selection && ctrl {
+            if self.is_selected(&item) {
+                self.selection.retain(|selected| *selected != item);
+            } else {
+                self.selection.push(item.clone());
+            }
+        } else {
+            self.selection = vec![item.clone()];
+        }
+
+        self.anchor = Some(item);
+        self.notify_selection_changed();
+    }
+
+    /// Items between the anchor and `item` in the previous frame order
+    fn range(&self, item: &T) -> Option<Vec<T>> {
+        let anchor = self.anchor.as_ref()?;
+        let anchor_index = self.previous_order.iter().position(|node| node == anchor)?;
+        let item_index = self.previous_order.iter().position(|node| node == item)?;
+        let range = anchor_index.min(item_index)..=anchor_index.max(item_index);
+        Some(self.previous_order[range].to_vec())
+    }
+
+    fn notify_selection_changed(&mut self) {
+        self.events
+            .push(TreeViewEvent::SelectionChanged(self.selection.clone()));
+    }
+
+    /// Consume the events queued since the last call
+    pub fn for_each_event<F: FnMut(TreeViewEvent<T>)>(&mut self, f: F) {
+        self.events.drain(..).for_each(f);
+    }
+
+    pub fn is_selected(&self, item: &T) -> bool {
+        self.selection.contains(item)
+    }
+
+    pub fn selection(&self) -> &[T] {
+        &self.selection
+    }
+
+    /// Replace the selection without emitting an event, e.g to sync it with an external selection
+    pub fn set_selection(&mut self, selection: Vec<T>) {
+        if !matches!(&self.anchor, Some(anchor) if selection.contains(anchor)) {
+            self.anchor = selection.last().cloned();
+        }
+        self.selection = selection;
+    }
+
+    pub fn is_open(&self, item: &T) -> bool {
+        self.open.get(item).copied().unwrap_or(false)
+    }
+
+    /// Open or close a node the next time it is submitted
+    pub fn set_open(&mut self, item: T, open: bool) {
+        self.open.insert(item, open);
+    }
+}
@@ -54,11 +54,11 @@ impl<'s, T: Sync> Producer for IterProducer<'s, T> {
 }
 
 pub trait ParallelSlice<T: Sync> {
-    fn par_iter(&self) -> Iter<T>;
+    fn par_iter(&self) -> Iter<'_, T>;
 }
 
 impl<T: Sync> ParallelSlice<T> for [T] {
-    fn par_iter(&self) -> Iter<T> {
+    fn par_iter(&self) -> Iter<'_, T> {
         self.into_par_iter()
     }
 }
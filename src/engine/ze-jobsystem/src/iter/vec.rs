@@ -2,7 +2,7 @@ use crate::iter::slice::{Iter, ParallelSlice};
 use crate::iter::IntoParallelIterator;
 
 impl<T: Sync> ParallelSlice<T> for Vec<T> {
-    fn par_iter(&self) -> Iter<T> {
+    fn par_iter(&self) -> Iter<'_, T> {
         self.into_par_iter()
     }
 }
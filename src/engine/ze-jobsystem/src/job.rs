@@ -71,6 +71,9 @@ impl Default for Job {
 #[inline]
 pub(crate) fn execute(job: JobHandle, shared_worker_data: &SharedWorkerData) {
     {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!("Job");
+
         let func = unsafe { job.function.assume_init() };
         func(job);
     }
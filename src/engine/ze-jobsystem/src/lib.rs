@@ -1,5 +1,6 @@
 ﻿use crate::job::{JobHandle, MAX_CONTINUATIONS, MAX_USERDATA_SIZE};
 use crate::job_allocator::JobAllocator;
+use crate::progress::Progress;
 use crate::worker_thread::WorkerThread;
 use crossbeam::deque::{Injector, Stealer, Worker};
 use once_cell::sync::OnceCell;
@@ -9,6 +10,7 @@ use std::mem;
 use std::mem::MaybeUninit;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use ze_core::signals::SyncSignal;
 use ze_core::ze_info;
 
 /// Maximum amount of jobs allocated per thread
@@ -66,6 +68,9 @@ pub struct JobSystem {
     job_allocator: JobAllocator,
     worker_threads: Vec<WorkerThread>,
     shared_worker_data: Arc<SharedWorkerData>,
+
+    /// Emitted when a progress is created by [`JobSystem::begin_progress`]
+    pub on_progress_started: SyncSignal<Arc<Progress>>,
 }
 
 impl JobSystem {
@@ -90,10 +95,20 @@ impl JobSystem {
             worker_threads,
             job_allocator: JobAllocator::with_capacity(JOB_CAPACITY_PER_THREAD),
             shared_worker_data,
+            on_progress_started: Default::default(),
         })
     }
 
-    pub fn spawn<F>(&self, f: F) -> JobBuilder
+    /// Create a progress tracking a long-running operation of `total` items
+    ///
+    /// Jobs doing the work must update it and call [`Progress::finish`] once done
+    pub fn begin_progress(&self, name: &str, total: usize, cancellable: bool) -> Arc<Progress> {
+        let progress = Arc::new(Progress::new(name, total, cancellable));
+        self.on_progress_started.emit(progress.clone());
+        progress
+    }
+
+    pub fn spawn<F>(&self, f: F) -> JobBuilder<'_>
     where
         F: FnOnce(&JobSystem, JobHandle),
         F: Send + 'static,
@@ -145,7 +160,7 @@ impl JobSystem {
     /// # Safety
     ///
     /// The function or caller must guarantee correct data lifetime management
-    pub unsafe fn spawn_unchecked<F>(&self, f: F) -> JobBuilder
+    pub unsafe fn spawn_unchecked<F>(&self, f: F) -> JobBuilder<'_>
     where
         F: FnOnce(&JobSystem, JobHandle),
         F: Send,
@@ -251,6 +266,21 @@ impl JobSystem {
     pub fn cpu_thread_count() -> usize {
         num_cpus::get()
     }
+
+    pub fn worker_count(&self) -> usize {
+        self.worker_threads.len()
+    }
+
+    /// Jobs scheduled but not started yet, only meant for statistics as it changes concurrently
+    pub fn pending_job_count(&self) -> usize {
+        self.shared_worker_data.injector().len()
+            + self
+                .shared_worker_data
+                .stealers()
+                .iter()
+                .map(|stealer| stealer.len())
+                .sum::<usize>()
+    }
 }
 
 impl Drop for JobSystem {
@@ -336,6 +366,7 @@ pub mod iter;
 mod job;
 mod job_allocator;
 pub mod prelude;
+pub mod progress;
 #[cfg(test)]
 mod tests;
 mod worker_thread;
@@ -0,0 +1,94 @@
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Progress of a long-running operation, shared between the jobs doing the work and the code
+/// displaying it
+///
+/// Created with [`crate::JobSystem::begin_progress`] so listeners are notified
+#[derive(Debug)]
+pub struct Progress {
+    name: String,
+    total: AtomicUsize,
+    completed: AtomicUsize,
+    current_item: Mutex<String>,
+    cancellable: bool,
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl Progress {
+    pub(crate) fn new(name: &str, total: usize, cancellable: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            total: AtomicUsize::new(total),
+            completed: AtomicUsize::new(0),
+            current_item: Default::default(),
+            cancellable,
+            cancelled: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+
+    /// Add `count` items to process
+    pub fn add_total(&self, count: usize) {
+        self.total.fetch_add(count, Ordering::SeqCst);
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::SeqCst)
+    }
+
+    /// Mark one item as processed, returns the number of processed items
+    pub fn advance(&self) -> usize {
+        self.completed.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Completion ratio between 0 and 1
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => (self.completed() as f32 / total as f32).min(1.0),
+        }
+    }
+
+    pub fn current_item(&self) -> String {
+        self.current_item.lock().clone()
+    }
+
+    pub fn set_current_item(&self, item: &str) {
+        let mut current_item = self.current_item.lock();
+        current_item.clear();
+        current_item.push_str(item);
+    }
+
+    pub fn is_cancellable(&self) -> bool {
+        self.cancellable
+    }
+
+    /// Request the operation to stop, it is up to the jobs to check [`Progress::is_cancelled`]
+    pub fn cancel(&self) {
+        if self.cancellable {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+}
@@ -22,13 +22,9 @@ fn spawn_one_job_and_wait() {
 fn drop() {
     let jobsystem = JobSystem::new(JobSystem::cpu_thread_count() - 1);
 
-    struct TestDrop {
-        dropped: AtomicBool,
-    }
+    struct TestDrop;
 
-    let drop_test = Arc::new(TestDrop {
-        dropped: Default::default(),
-    });
+    let drop_test = Arc::new(TestDrop);
 
     {
         let drop_test = drop_test.clone();
@@ -91,3 +87,34 @@ fn spawn_three_jobs_one_continuation_per_job() {
     jobsystem.wait_until_idle();
     assert_eq!(counter.load(Ordering::SeqCst), 6);
 }
+
+#[test]
+fn progress_started() {
+    let jobsystem = JobSystem::new(JobSystem::cpu_thread_count() - 1);
+    let started = Arc::new(AtomicBool::new(false));
+    {
+        let started = started.clone();
+        jobsystem
+            .on_progress_started
+            .connect(move |progress| started.store(progress.name() == "Test", Ordering::SeqCst));
+    }
+
+    let progress = jobsystem.begin_progress("Test", 2, true);
+    assert!(started.load(Ordering::SeqCst));
+
+    progress.advance();
+    assert_eq!(progress.fraction(), 0.5);
+    progress.cancel();
+    assert!(progress.is_cancelled());
+}
+
+#[test]
+fn stats() {
+    let jobsystem = JobSystem::new(2);
+    assert_eq!(jobsystem.worker_count(), 2);
+
+    let job = jobsystem.spawn(|_, _| {}).schedule();
+    jobsystem.wait_for(&[job]);
+    jobsystem.wait_until_idle();
+    assert_eq!(jobsystem.pending_job_count(), 0);
+}
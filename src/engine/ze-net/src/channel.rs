@@ -0,0 +1,187 @@
+use crate::packet::{sequence_greater_than, MessageData, MESSAGE_HEADER_SIZE};
+use crate::Error;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Maximum number of reliable messages waiting for an ack on a channel
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ChannelKind {
+    /// Messages are sent once and may be lost, duplicated or received out of order
+    Unreliable,
+
+    /// Messages are resent until acknowledged and received in order
+    ReliableOrdered,
+}
+
+struct OutgoingMessage {
+    id: u16,
+    data: Vec<u8>,
+    last_sent: Option<Instant>,
+}
+
+pub(crate) struct Channel {
+    kind: ChannelKind,
+    next_send_id: u16,
+
+    /// Messages to send, reliable ones are kept until acknowledged
+    outgoing: VecDeque<OutgoingMessage>,
+    next_receive_id: u16,
+
+    /// Reliable messages received before the ones preceding them
+    out_of_order: HashMap<u16, Vec<u8>>,
+    received: VecDeque<Vec<u8>>,
+}
+
+impl Channel {
+    pub fn new(kind: ChannelKind) -> Self {
+        Self {
+            kind,
+            next_send_id: 0,
+            outgoing: VecDeque::new(),
+            next_receive_id: 0,
+            out_of_order: HashMap::new(),
+            received: VecDeque::new(),
+        }
+    }
+
+    pub fn send(&mut self, data: &[u8]) -> Result<(), Error> {
+        if self.kind == ChannelKind::ReliableOrdered && self.outgoing.len() >= MAX_PENDING_MESSAGES
+        {
+            return Err(Error::ChannelFull);
+        }
+
+        self.outgoing.push_back(OutgoingMessage {
+            id: self.next_send_id,
+            data: data.to_vec(),
+            last_sent: None,
+        });
+        self.next_send_id = self.next_send_id.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Add the messages to send to `messages` as long as they fit in `budget` bytes
+    ///
+    /// Reliable messages are only sent again once `resend_delay` elapsed
+    pub fn write(
+        &mut self,
+        channel: u8,
+        now: Instant,
+        resend_delay: Duration,
+        budget: &mut usize,
+        messages: &mut Vec<MessageData>,
+    ) {
+        for message in &mut self.outgoing {
+            let size = MESSAGE_HEADER_SIZE + message.data.len();
+            if size > *budget {
+                break;
+            }
+
+            if matches!(message.last_sent, Some(last_sent) if now - last_sent < resend_delay) {
+                continue;
+            }
+
+            *budget -= size;
+            message.last_sent = Some(now);
+            messages.push(MessageData {
+                channel,
+                id: message.id,
+                data: message.data.clone(),
+            });
+        }
+
+        if self.kind == ChannelKind::Unreliable {
+            self.outgoing.retain(|message| message.last_sent.is_none());
+        }
+    }
+
+    pub fn on_acked(&mut self, id: u16) {
+        self.outgoing.retain(|message| message.id != id);
+    }
+
+    pub fn receive(&mut self, id: u16, data: Vec<u8>) {
+        match self.kind {
+            ChannelKind::Unreliable => self.received.push_back(data),
+            ChannelKind::ReliableOrdered => {
+                if id == self.next_receive_id {
+                    self.received.push_back(data);
+                    self.next_receive_id = self.next_receive_id.wrapping_add(1);
+                    while let Some(data) = self.out_of_order.remove(&self.next_receive_id) {
+                        self.received.push_back(data);
+                        self.next_receive_id = self.next_receive_id.wrapping_add(1);
+                    }
+                } else if sequence_greater_than(id, self.next_receive_id) {
+                    self.out_of_order.insert(id, data);
+                }
+            }
+        }
+    }
+
+    pub fn take_received(&mut self) -> Option<Vec<u8>> {
+        self.received.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::channel::{Channel, ChannelKind};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn reliable_resend_and_order() {
+        let mut channel = Channel::new(ChannelKind::ReliableOrdered);
+        channel.send(&[0]).unwrap();
+        channel.send(&[1]).unwrap();
+
+        let now = Instant::now();
+        let delay = Duration::from_millis(100);
+        let mut budget = usize::MAX;
+        let mut messages = vec![];
+        channel.write(0, now, delay, &mut budget, &mut messages);
+        assert_eq!(messages.len(), 2);
+
+        // Not acked yet, resent once the delay elapsed
+        messages.clear();
+        channel.write(0, now, delay, &mut budget, &mut messages);
+        assert!(messages.is_empty());
+        channel.on_acked(0);
+        channel.write(0, now + delay, delay, &mut budget, &mut messages);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id, 1);
+
+        let mut receiver = Channel::new(ChannelKind::ReliableOrdered);
+        receiver.receive(1, vec![1]);
+        assert!(receiver.take_received().is_none());
+        receiver.receive(0, vec![0]);
+        receiver.receive(0, vec![0]);
+        assert_eq!(receiver.take_received(), Some(vec![0]));
+        assert_eq!(receiver.take_received(), Some(vec![1]));
+        assert!(receiver.take_received().is_none());
+    }
+
+    #[test]
+    fn unreliable_sent_once() {
+        let mut channel = Channel::new(ChannelKind::Unreliable);
+        channel.send(&[0; 10]).unwrap();
+        channel.send(&[1; 10]).unwrap();
+
+        // Only the first message fits
+        let mut budget = 20;
+        let mut messages = vec![];
+        channel.write(
+            1,
+            Instant::now(),
+            Duration::ZERO,
+            &mut budget,
+            &mut messages,
+        );
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].channel, 1);
+
+        messages.clear();
+        channel.write(1, Instant::now(), Duration::ZERO, &mut 100, &mut messages);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].data, vec![1; 10]);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::channel::{Channel, ChannelKind};
+use crate::packet::{
+    sequence_greater_than, MessageData, PacketHeader, PacketKind, ACK_BITS, HEADER_SIZE,
+    MAX_PACKET_SIZE,
+};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Connections with a RTT above this value are considered congested
+const CONGESTION_RTT: Duration = Duration::from_millis(250);
+
+const GOOD_SEND_INTERVAL: Duration = Duration::from_millis(1000 / 30);
+const BAD_SEND_INTERVAL: Duration = Duration::from_millis(1000 / 10);
+
+/// Time the RTT must stay low before leaving the congested mode, doubled every time the
+/// connection becomes congested shortly after recovering
+const MIN_RECOVERY_TIME: Duration = Duration::from_secs(1);
+const MAX_RECOVERY_TIME: Duration = Duration::from_secs(60);
+
+/// Time without congestion after which the recovery time is halved
+const STABLE_TIME: Duration = Duration::from_secs(10);
+
+const MIN_RESEND_DELAY: Duration = Duration::from_millis(100);
+
+/// Send rate control based on the RTT, the connection sends less packets while congested
+struct CongestionControl {
+    congested: bool,
+
+    /// Time of the last mode change, or of the last high RTT while congested
+    since: Instant,
+    recovery_time: Duration,
+}
+
+impl CongestionControl {
+    fn new(now: Instant) -> Self {
+        Self {
+            congested: false,
+            since: now,
+            recovery_time: MIN_RECOVERY_TIME,
+        }
+    }
+
+    fn update(&mut self, rtt: Duration, now: Instant) {
+        let elapsed = now - self.since;
+        if self.congested {
+            if rtt > CONGESTION_RTT {
+                self.since = now;
+            } else if elapsed >= self.recovery_time {
+                self.congested = false;
+                self.since = now;
+            }
+        } else if rtt > CONGESTION_RTT {
+            if elapsed < STABLE_TIME {
+                self.recovery_time = (self.recovery_time * 2).min(MAX_RECOVERY_TIME);
+            }
+            self.congested = true;
+            self.since = now;
+        } else if elapsed >= STABLE_TIME {
+            self.recovery_time = (self.recovery_time / 2).max(MIN_RECOVERY_TIME);
+            self.since = now;
+        }
+    }
+
+    fn send_interval(&self) -> Duration {
+        if self.congested {
+            BAD_SEND_INTERVAL
+        } else {
+            GOOD_SEND_INTERVAL
+        }
+    }
+}
+
+/// Payload packet waiting for an ack, with the reliable messages it contains
+struct SentPacket {
+    sequence: u16,
+    sent_at: Instant,
+    messages: Vec<(u8, u16)>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum ConnectionState {
+    /// Client waiting for the server to accept the connection
+    Connecting,
+    Connected,
+}
+
+pub(crate) struct Connection {
+    pub address: SocketAddr,
+    pub state: ConnectionState,
+    local_sequence: u16,
+    remote_sequence: Option<u16>,
+    received_bits: u32,
+    sent_packets: VecDeque<SentPacket>,
+    rtt: Option<Duration>,
+    congestion: CongestionControl,
+    channels: Vec<Channel>,
+    pub last_received: Instant,
+    pub last_sent: Option<Instant>,
+}
+
+impl Connection {
+    pub fn new(
+        address: SocketAddr,
+        state: ConnectionState,
+        channels: &[ChannelKind],
+        now: Instant,
+    ) -> Self {
+        Self {
+            address,
+            state,
+            local_sequence: 0,
+            remote_sequence: None,
+            received_bits: 0,
+            sent_packets: VecDeque::new(),
+            rtt: None,
+            congestion: CongestionControl::new(now),
+            channels: channels.iter().map(|kind| Channel::new(*kind)).collect(),
+            last_received: now,
+            last_sent: None,
+        }
+    }
+
+    /// Smoothed round trip time, zero until a packet is acknowledged
+    pub fn rtt(&self) -> Duration {
+        self.rtt.unwrap_or_default()
+    }
+
+    pub fn is_congested(&self) -> bool {
+        self.congestion.congested
+    }
+
+    pub fn channel_mut(&mut self, channel: usize) -> Option<&mut Channel> {
+        self.channels.get_mut(channel)
+    }
+
+    pub fn header(&self, kind: PacketKind, sequence: u16) -> PacketHeader {
+        PacketHeader {
+            kind,
+            sequence,
+            ack: self.remote_sequence.unwrap_or(u16::MAX),
+            ack_bits: self.received_bits,
+        }
+    }
+
+    /// Returns true if a payload packet should be sent according to the send rate
+    pub fn should_send(&self, now: Instant) -> bool {
+        match self.last_sent {
+            Some(last_sent) => now - last_sent >= self.congestion.send_interval(),
+            None => true,
+        }
+    }
+
+    /// Build the next payload packet, messages are taken from the channels in order
+    pub fn write_payload(&mut self, now: Instant) -> (PacketHeader, Vec<MessageData>) {
+        let resend_delay = (self.rtt() * 3 / 2).max(MIN_RESEND_DELAY);
+        let mut budget = MAX_PACKET_SIZE - HEADER_SIZE;
+        let mut messages = vec![];
+        for (index, channel) in self.channels.iter_mut().enumerate() {
+            channel.write(index as u8, now, resend_delay, &mut budget, &mut messages);
+        }
+
+        let sequence = self.local_sequence;
+        self.local_sequence = self.local_sequence.wrapping_add(1);
+        self.sent_packets.push_back(SentPacket {
+            sequence,
+            sent_at: now,
+            messages: messages
+                .iter()
+                .map(|message| (message.channel, message.id))
+                .collect(),
+        });
+
+        // Packets older than the ack window can't be acknowledged anymore, their messages are
+        // resent after the resend delay
+        while self.sent_packets.len() > ACK_BITS as usize + 1 {
+            self.sent_packets.pop_front();
+        }
+
+        self.last_sent = Some(now);
+        (self.header(PacketKind::Payload, sequence), messages)
+    }
+
+    /// Process a received payload packet, returns false if it has already been received
+    pub fn receive_payload(
+        &mut self,
+        header: &PacketHeader,
+        messages: Vec<MessageData>,
+        now: Instant,
+    ) -> bool {
+        if !self.mark_received(header.sequence) {
+            return false;
+        }
+
+        self.last_received = now;
+        self.process_acks(header, now);
+        for message in messages {
+            if let Some(channel) = self.channels.get_mut(message.channel as usize) {
+                channel.receive(message.id, message.data);
+            }
+        }
+
+        true
+    }
+
+    /// Next received message of any channel
+    pub fn take_received(&mut self) -> Option<(usize, Vec<u8>)> {
+        self.channels
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, channel)| channel.take_received().map(|data| (index, data)))
+    }
+
+    fn mark_received(&mut self, sequence: u16) -> bool {
+        let remote_sequence = match self.remote_sequence {
+            Some(remote_sequence) => remote_sequence,
+            None => {
+                self.remote_sequence = Some(sequence);
+                return true;
+            }
+        };
+
+        if sequence_greater_than(sequence, remote_sequence) {
+            let shift = sequence.wrapping_sub(remote_sequence) as u32;
+            self.received_bits = if shift > ACK_BITS as u32 {
+                0
+            } else {
+                // The previous most recent sequence is now part of the bits
+                (((self.received_bits as u64) << shift) | (1 << (shift - 1))) as u32
+            };
+            self.remote_sequence = Some(sequence);
+            true
+        } else {
+            let distance = remote_sequence.wrapping_sub(sequence);
+            if distance == 0 || distance > ACK_BITS {
+                return false;
+            }
+
+            let bit = 1 << (distance - 1);
+            let received = self.received_bits & bit != 0;
+            self.received_bits |= bit;
+            !received
+        }
+    }
+
+    fn process_acks(&mut self, header: &PacketHeader, now: Instant) {
+        let is_acked = |sequence: u16| {
+            let distance = header.ack.wrapping_sub(sequence);
+            distance == 0 || (distance <= ACK_BITS && header.ack_bits & (1 << (distance - 1)) != 0)
+        };
+
+        let mut index = 0;
+        while index < self.sent_packets.len() {
+            if !is_acked(self.sent_packets[index].sequence) {
+                index += 1;
+                continue;
+            }
+
+            let packet = self.sent_packets.remove(index).unwrap();
+            let sample = now - packet.sent_at;
+            self.rtt = Some(match self.rtt {
+                Some(rtt) if sample > rtt => rtt + (sample - rtt) / 10,
+                Some(rtt) => rtt - (rtt - sample) / 10,
+                None => sample,
+            });
+
+            for (channel, id) in packet.messages {
+                if let Some(channel) = self.channels.get_mut(channel as usize) {
+                    channel.on_acked(id);
+                }
+            }
+        }
+
+        self.congestion.update(self.rtt(), now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::channel::ChannelKind;
+    use crate::connection::{Connection, ConnectionState};
+    use std::time::Instant;
+
+    fn connection() -> Connection {
+        Connection::new(
+            "127.0.0.1:0".parse().unwrap(),
+            ConnectionState::Connected,
+            &[ChannelKind::ReliableOrdered],
+            Instant::now(),
+        )
+    }
+
+    #[test]
+    fn acks() {
+        let now = Instant::now();
+        let mut sender = connection();
+        let mut receiver = connection();
+        sender.channel_mut(0).unwrap().send(&[42]).unwrap();
+
+        // First packet is lost, the second one is received twice
+        sender.write_payload(now);
+        let (header, messages) = sender.write_payload(now);
+        assert!(receiver.receive_payload(&header, messages.clone(), now));
+        assert!(!receiver.receive_payload(&header, messages, now));
+        assert_eq!(receiver.take_received(), None);
+
+        // Message is resent after the resend delay
+        let later = now + std::time::Duration::from_millis(200);
+        let (header, messages) = sender.write_payload(later);
+        assert_eq!(messages.len(), 1);
+        assert!(receiver.receive_payload(&header, messages, later));
+        assert_eq!(receiver.take_received(), Some((0, vec![42])));
+
+        let (header, messages) = receiver.write_payload(later);
+        assert_eq!(header.ack, 2);
+        assert_eq!(header.ack_bits, 0b1);
+        sender.receive_payload(&header, messages, later);
+        assert_eq!(sender.sent_packets.len(), 1);
+        assert_eq!(sender.sent_packets[0].sequence, 0);
+        assert!(sender
+            .write_payload(later + std::time::Duration::from_secs(1))
+            .1
+            .is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt::{Debug, Display, Formatter};
+
+mod channel;
+mod connection;
+pub mod packet;
+pub mod replication;
+pub mod transport;
+
+pub use channel::ChannelKind;
+
+/// Index of a channel in [`transport::TransportConfig::channels`]
+pub type ChannelId = usize;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+
+    /// Message doesn't fit in a single packet
+    MessageTooLarge,
+
+    /// Too many reliable messages are waiting for an ack
+    ChannelFull,
+    InvalidConnection,
+    InvalidChannel,
+    InvalidMessage,
+    Scene(ze_scene::scene::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,183 @@
+/// Maximum size of a datagram, small enough to avoid IP fragmentation
+pub const MAX_PACKET_SIZE: usize = 1200;
+
+/// Protocol id, kind, sequence, ack and ack bits
+pub(crate) const HEADER_SIZE: usize = 13;
+
+/// Channel, message id and length
+pub(crate) const MESSAGE_HEADER_SIZE: usize = 5;
+
+/// Number of packets acknowledged by a single header, in addition to `ack`
+pub(crate) const ACK_BITS: u16 = 32;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum PacketKind {
+    ConnectRequest,
+    ConnectAccept,
+    ConnectDeny,
+    Disconnect,
+
+    /// Messages and acks, sent even without any message to keep the connection alive
+    Payload,
+}
+
+impl PacketKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PacketKind::ConnectRequest),
+            1 => Some(PacketKind::ConnectAccept),
+            2 => Some(PacketKind::ConnectDeny),
+            3 => Some(PacketKind::Disconnect),
+            4 => Some(PacketKind::Payload),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct PacketHeader {
+    pub kind: PacketKind,
+    pub sequence: u16,
+
+    /// Most recent sequence received from the remote
+    pub ack: u16,
+
+    /// Bit N set if `ack - N - 1` has been received
+    pub ack_bits: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct MessageData {
+    pub channel: u8,
+    pub id: u16,
+    pub data: Vec<u8>,
+}
+
+/// Returns true if `a` is more recent than `b`, handling wrap around
+pub(crate) fn sequence_greater_than(a: u16, b: u16) -> bool {
+    (a > b && a - b <= u16::MAX / 2) || (a < b && b - a > u16::MAX / 2)
+}
+
+pub(crate) fn write_packet(
+    protocol_id: u32,
+    header: &PacketHeader,
+    messages: &[MessageData],
+    buffer: &mut Vec<u8>,
+) {
+    buffer.clear();
+    buffer.extend_from_slice(&protocol_id.to_le_bytes());
+    buffer.push(header.kind as u8);
+    buffer.extend_from_slice(&header.sequence.to_le_bytes());
+    buffer.extend_from_slice(&header.ack.to_le_bytes());
+    buffer.extend_from_slice(&header.ack_bits.to_le_bytes());
+    for message in messages {
+        buffer.push(message.channel);
+        buffer.extend_from_slice(&message.id.to_le_bytes());
+        buffer.extend_from_slice(&(message.data.len() as u16).to_le_bytes());
+        buffer.extend_from_slice(&message.data);
+    }
+}
+
+/// Returns None if the packet is malformed or belongs to another protocol
+pub(crate) fn read_packet(
+    protocol_id: u32,
+    packet: &[u8],
+) -> Option<(PacketHeader, Vec<MessageData>)> {
+    let mut reader = Reader(packet);
+    if reader.u32()? != protocol_id {
+        return None;
+    }
+
+    let header = PacketHeader {
+        kind: PacketKind::from_u8(reader.u8()?)?,
+        sequence: reader.u16()?,
+        ack: reader.u16()?,
+        ack_bits: reader.u32()?,
+    };
+
+    let mut messages = vec![];
+    while !reader.0.is_empty() {
+        let channel = reader.u8()?;
+        let id = reader.u16()?;
+        let length = reader.u16()? as usize;
+        messages.push(MessageData {
+            channel,
+            id,
+            data: reader.bytes(length)?.to_vec(),
+        });
+    }
+
+    Some((header, messages))
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.0.len() < count {
+            return None;
+        }
+
+        let (bytes, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2)
+            .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::packet::{
+        read_packet, sequence_greater_than, write_packet, MessageData, PacketHeader, PacketKind,
+        HEADER_SIZE,
+    };
+
+    #[test]
+    fn packet_roundtrip() {
+        let header = PacketHeader {
+            kind: PacketKind::Payload,
+            sequence: 12,
+            ack: 65535,
+            ack_bits: 0b1011,
+        };
+        let messages = vec![
+            MessageData {
+                channel: 0,
+                id: 3,
+                data: vec![1, 2, 3],
+            },
+            MessageData {
+                channel: 1,
+                id: 0,
+                data: vec![],
+            },
+        ];
+
+        let mut buffer = vec![];
+        write_packet(7, &header, &messages, &mut buffer);
+        assert_eq!(read_packet(7, &buffer), Some((header, messages)));
+        assert_eq!(read_packet(8, &buffer), None);
+        assert_eq!(read_packet(7, &buffer[..HEADER_SIZE + 2]), None);
+    }
+
+    #[test]
+    fn sequence_wrap_around() {
+        assert!(sequence_greater_than(1, 0));
+        assert!(!sequence_greater_than(0, 1));
+        assert!(sequence_greater_than(0, u16::MAX));
+        assert!(!sequence_greater_than(u16::MAX, 0));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::transport::{Transport, MAX_MESSAGE_SIZE, RELIABLE_CHANNEL, UNRELIABLE_CHANNEL};
+use crate::{ChannelId, Error};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_scene::scene::SceneSerializer;
+use ze_scene::value::Value;
+
+/// Room kept in state messages for the tick and the entity count
+const STATE_HEADER_MARGIN: usize = 32;
+
+/// Identifier of a replicated entity, identical on the server and the clients
+#[derive(Component, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Debug)]
+pub struct NetworkId(pub u64);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct EntityState {
+    id: u64,
+    components: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+enum ReplicationMessage {
+    /// Snapshot of the components of some entities, sent unreliably every tick
+    State {
+        tick: u64,
+        entities: Vec<EntityState>,
+    },
+
+    /// Entities destroyed on the server, sent reliably
+    Despawn(Vec<u64>),
+}
+
+impl ReplicationMessage {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(|_| Error::InvalidMessage)
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::serde::decode_from_slice(data, bincode::config::standard())
+            .map(|(message, _)| message)
+            .map_err(|_| Error::InvalidMessage)
+    }
+}
+
+/// Sends the components registered in its [`SceneSerializer`] of the replicated entities to all
+/// the connections of a server transport
+///
+/// Used by both dedicated and listen servers, the host of a listen server directly uses the
+/// server world
+pub struct ReplicationServer {
+    serializer: SceneSerializer,
+    entities: BTreeMap<u64, Entity>,
+    next_id: u64,
+    tick: u64,
+}
+
+impl ReplicationServer {
+    pub fn new(serializer: SceneSerializer) -> Self {
+        Self {
+            serializer,
+            entities: BTreeMap::new(),
+            next_id: 0,
+            tick: 0,
+        }
+    }
+
+    /// Start replicating `entity`, it is despawned on the clients once destroyed
+    ///
+    /// The hierarchy is not replicated
+    pub fn replicate(&mut self, world: &mut World, entity: Entity) -> NetworkId {
+        let id = NetworkId(self.next_id);
+        self.next_id += 1;
+        world.add(entity, id);
+        self.entities.insert(id.0, entity);
+        id
+    }
+
+    /// Send a snapshot of the replicated entities to every connection
+    pub fn send(&mut self, world: &World, transport: &mut Transport) -> Result<(), Error> {
+        let messages = self.messages(world)?;
+        let connections = transport.connections().collect::<Vec<_>>();
+        for connection in connections {
+            for (channel, data) in &messages {
+                transport.send(connection, *channel, data)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn messages(&mut self, world: &World) -> Result<Vec<(ChannelId, Vec<u8>)>, Error> {
+        let mut messages = vec![];
+
+        let despawned = self
+            .entities
+            .iter()
+            .filter(|(_, entity)| !world.is_valid(**entity))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        if !despawned.is_empty() {
+            for id in &despawned {
+                self.entities.remove(id);
+            }
+
+            messages.push((
+                RELIABLE_CHANNEL,
+                ReplicationMessage::Despawn(despawned).encode()?,
+            ));
+        }
+
+        // Entities are split in multiple messages so that each one fits in a packet
+        let mut entities = vec![];
+        let mut size = STATE_HEADER_MARGIN;
+        for (id, entity) in &self.entities {
+            let state = EntityState {
+                id: *id,
+                components: self.serializer.components(world, *entity),
+            };
+            let state_size = bincode::serde::encode_to_vec(&state, bincode::config::standard())
+                .map_err(|_| Error::InvalidMessage)?
+                .len();
+            if STATE_HEADER_MARGIN + state_size > MAX_MESSAGE_SIZE {
+                return Err(Error::MessageTooLarge);
+            }
+
+            if size + state_size > MAX_MESSAGE_SIZE {
+                messages.push((UNRELIABLE_CHANNEL, self.state_message(&mut entities)?));
+                size = STATE_HEADER_MARGIN;
+            }
+
+            size += state_size;
+            entities.push(state);
+        }
+
+        if !entities.is_empty() {
+            messages.push((UNRELIABLE_CHANNEL, self.state_message(&mut entities)?));
+        }
+
+        self.tick += 1;
+        Ok(messages)
+    }
+
+    fn state_message(&self, entities: &mut Vec<EntityState>) -> Result<Vec<u8>, Error> {
+        ReplicationMessage::State {
+            tick: self.tick,
+            entities: std::mem::take(entities),
+        }
+        .encode()
+    }
+}
+
+/// Applies the messages of a [`ReplicationServer`] to the client world
+///
+/// Replicated entities are spawned with a [`NetworkId`] when first received
+pub struct ReplicationClient {
+    serializer: SceneSerializer,
+
+    /// Entity and last applied tick of each network id
+    entities: HashMap<u64, (Entity, u64)>,
+
+    /// Despawned ids, states received after the despawn are ignored
+    despawned: HashSet<u64>,
+}
+
+impl ReplicationClient {
+    pub fn new(serializer: SceneSerializer) -> Self {
+        Self {
+            serializer,
+            entities: HashMap::new(),
+            despawned: HashSet::new(),
+        }
+    }
+
+    pub fn entity(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id.0).map(|(entity, _)| *entity)
+    }
+
+    /// Apply a message received from the server, states older than the last applied one are
+    /// ignored
+    pub fn receive(&mut self, world: &mut World, data: &[u8]) -> Result<(), Error> {
+        match ReplicationMessage::decode(data)? {
+            ReplicationMessage::State { tick, entities } => {
+                for state in entities {
+                    if self.despawned.contains(&state.id) {
+                        continue;
+                    }
+
+                    let entity = match self.entities.get_mut(&state.id) {
+                        Some((_, last_tick)) if *last_tick >= tick => continue,
+                        Some((entity, last_tick)) => {
+                            *last_tick = tick;
+                            *entity
+                        }
+                        None => {
+                            let entity = world.spawn();
+                            world.add(entity, NetworkId(state.id));
+                            self.entities.insert(state.id, (entity, tick));
+                            entity
+                        }
+                    };
+
+                    if world.is_valid(entity) {
+                        self.serializer
+                            .apply_components(world, entity, &state.components)
+                            .map_err(Error::Scene)?;
+                    }
+                }
+            }
+            ReplicationMessage::Despawn(ids) => {
+                for id in ids {
+                    if let Some((entity, _)) = self.entities.remove(&id) {
+                        if world.is_valid(entity) {
+                            world.destroy(entity);
+                        }
+                    }
+                    self.despawned.insert(id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::replication::{NetworkId, ReplicationClient, ReplicationServer};
+    use crate::transport::{MAX_MESSAGE_SIZE, RELIABLE_CHANNEL, UNRELIABLE_CHANNEL};
+    use nalgebra_glm as glm;
+    use ze_ecs::world::World;
+    use ze_scene::scene::SceneSerializer;
+    use ze_scene::transform::Transform;
+
+    fn serializer() -> SceneSerializer {
+        let mut serializer = SceneSerializer::default();
+        serializer.register::<Transform>();
+        serializer
+    }
+
+    #[test]
+    fn replicate_and_despawn() {
+        let mut server_world = World::default();
+        let mut server = ReplicationServer::new(serializer());
+        let entities = (0..100)
+            .map(|i| {
+                let entity = server_world.spawn();
+                server_world.add(
+                    entity,
+                    Transform::from_translation(glm::vec3(i as f32, 0.0, 0.0)),
+                );
+                (entity, server.replicate(&mut server_world, entity))
+            })
+            .collect::<Vec<_>>();
+
+        let mut client_world = World::default();
+        let mut client = ReplicationClient::new(serializer());
+        let old_messages = server.messages(&server_world).unwrap();
+        assert!(old_messages.len() > 1);
+
+        server_world.destroy(entities[0].0);
+        let messages = server.messages(&server_world).unwrap();
+        assert_eq!(messages[0].0, RELIABLE_CHANNEL);
+        for (channel, data) in &messages {
+            assert!(data.len() <= MAX_MESSAGE_SIZE);
+            if *channel == UNRELIABLE_CHANNEL {
+                client.receive(&mut client_world, data).unwrap();
+            }
+        }
+
+        // Despawn is received after the states, older states are ignored
+        client.receive(&mut client_world, &messages[0].1).unwrap();
+        for (_, data) in &old_messages {
+            client.receive(&mut client_world, data).unwrap();
+        }
+
+        assert!(client.entity(entities[0].1).is_none());
+        let mut count = 0;
+        client_world
+            .query::<&NetworkId>()
+            .for_each(&client_world, |_| count += 1);
+        assert_eq!(count, 99);
+        let entity = client.entity(entities[42].1).unwrap();
+        let transform = client_world
+            .query::<&Transform>()
+            .get(&client_world, entity)
+            .copied()
+            .unwrap();
+        assert_eq!(transform.translation, glm::vec3(42.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,494 @@
+use crate::channel::ChannelKind;
+use crate::connection::{Connection, ConnectionState};
+use crate::packet::{
+    read_packet, write_packet, MessageData, PacketHeader, PacketKind, HEADER_SIZE, MAX_PACKET_SIZE,
+    MESSAGE_HEADER_SIZE,
+};
+use crate::{ChannelId, Error};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Maximum size of a message, a message must fit in a single packet
+pub const MAX_MESSAGE_SIZE: usize = MAX_PACKET_SIZE - HEADER_SIZE - MESSAGE_HEADER_SIZE;
+
+/// Channels of the default configuration
+pub const RELIABLE_CHANNEL: ChannelId = 0;
+pub const UNRELIABLE_CHANNEL: ChannelId = 1;
+
+/// Delay between two connection requests of a client
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Debug)]
+pub struct ConnectionId(u32);
+
+#[derive(Clone, Debug)]
+pub struct TransportConfig {
+    /// Packets with a different protocol id are ignored
+    pub protocol_id: u32,
+    pub channels: Vec<ChannelKind>,
+
+    /// Maximum number of players of a server, including the host of a listen server
+    pub max_players: usize,
+
+    /// Connections are closed after not receiving any packet for this duration
+    pub timeout: Duration,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            protocol_id: 0x5A45_4E45,
+            channels: vec![ChannelKind::ReliableOrdered, ChannelKind::Unreliable],
+            max_players: 16,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ServerMode {
+    /// Server without any local player
+    Dedicated,
+
+    /// Server hosted by a player, the host doesn't need a connection and uses one of the player
+    /// slots
+    Listen,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TransportEvent {
+    Connected(ConnectionId),
+
+    /// Connection closed by the remote, timed out or denied by the server
+    Disconnected(ConnectionId),
+    Message {
+        connection: ConnectionId,
+        channel: ChannelId,
+        data: Vec<u8>,
+    },
+}
+
+enum Role {
+    Client,
+    Server(ServerMode),
+}
+
+/// Connection oriented transport over UDP, messages are sent on channels that can be reliable
+/// Packets are sent at a fixed rate that is lowered when the connection is congested
+pub struct Transport {
+    socket: UdpSocket,
+    config: TransportConfig,
+    role: Role,
+    connections: HashMap<ConnectionId, Connection>,
+    next_connection_id: u32,
+    buffer: Vec<u8>,
+}
+
+impl Transport {
+    pub fn server(
+        address: impl ToSocketAddrs,
+        config: TransportConfig,
+        mode: ServerMode,
+    ) -> Result<Self, Error> {
+        Self::new(address, config, Role::Server(mode))
+    }
+
+    /// Create a client and start connecting to `server`
+    pub fn client(server: SocketAddr, config: TransportConfig) -> Result<Self, Error> {
+        let local_address: SocketAddr = if server.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0; 16], 0).into()
+        };
+
+        let mut transport = Self::new(local_address, config, Role::Client)?;
+        transport.add_connection(server, ConnectionState::Connecting, Instant::now());
+        Ok(transport)
+    }
+
+    fn new(
+        address: impl ToSocketAddrs,
+        config: TransportConfig,
+        role: Role,
+    ) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(address).map_err(Error::Io)?;
+        socket.set_nonblocking(true).map_err(Error::Io)?;
+        Ok(Self {
+            socket,
+            config,
+            role,
+            connections: HashMap::new(),
+            next_connection_id: 0,
+            buffer: Vec::with_capacity(MAX_PACKET_SIZE),
+        })
+    }
+
+    pub fn local_address(&self) -> Result<SocketAddr, Error> {
+        self.socket.local_addr().map_err(Error::Io)
+    }
+
+    /// Established connections
+    pub fn connections(&self) -> impl Iterator<Item = ConnectionId> + '_ {
+        self.connections
+            .iter()
+            .filter(|(_, connection)| connection.state == ConnectionState::Connected)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn is_connected(&self, connection: ConnectionId) -> bool {
+        self.connections
+            .get(&connection)
+            .map(|connection| connection.state == ConnectionState::Connected)
+            .unwrap_or(false)
+    }
+
+    pub fn rtt(&self, connection: ConnectionId) -> Option<Duration> {
+        self.connections
+            .get(&connection)
+            .map(|connection| connection.rtt())
+    }
+
+    pub fn is_congested(&self, connection: ConnectionId) -> Option<bool> {
+        self.connections
+            .get(&connection)
+            .map(|connection| connection.is_congested())
+    }
+
+    /// Queue a message, it is sent by the next [`Transport::update`]
+    ///
+    /// Messages can be queued while the client is connecting
+    pub fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: ChannelId,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if data.len() > MAX_MESSAGE_SIZE {
+            return Err(Error::MessageTooLarge);
+        }
+
+        self.connections
+            .get_mut(&connection)
+            .ok_or(Error::InvalidConnection)?
+            .channel_mut(channel)
+            .ok_or(Error::InvalidChannel)?
+            .send(data)
+    }
+
+    /// Close the connection, the remote is notified but may not receive the notification
+    pub fn disconnect(&mut self, connection: ConnectionId) -> Result<(), Error> {
+        let connection = self
+            .connections
+            .remove(&connection)
+            .ok_or(Error::InvalidConnection)?;
+        self.send_control(&connection, PacketKind::Disconnect)
+    }
+
+    /// Receive the incoming packets, close timed out connections and send the queued messages
+    pub fn update(&mut self, now: Instant) -> Result<Vec<TransportEvent>, Error> {
+        let mut events = vec![];
+        self.receive(now, &mut events)?;
+
+        let timed_out = self
+            .connections
+            .iter()
+            .filter(|(_, connection)| now - connection.last_received > self.config.timeout)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in timed_out {
+            self.connections.remove(&id);
+            events.push(TransportEvent::Disconnected(id));
+        }
+
+        self.send_packets(now)?;
+        Ok(events)
+    }
+
+    fn receive(&mut self, now: Instant, events: &mut Vec<TransportEvent>) -> Result<(), Error> {
+        let mut packet = [0; MAX_PACKET_SIZE];
+        loop {
+            let (size, address) = match self.socket.recv_from(&mut packet) {
+                Ok(result) => result,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                // Reported on some platforms when a previous packet couldn't be delivered
+                Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+                Err(error) => return Err(Error::Io(error)),
+            };
+
+            if let Some((header, messages)) = read_packet(self.config.protocol_id, &packet[..size])
+            {
+                let id = self
+                    .connections
+                    .iter()
+                    .find(|(_, connection)| connection.address == address)
+                    .map(|(id, _)| *id);
+
+                match (id, header.kind) {
+                    (None, PacketKind::ConnectRequest) => {
+                        self.accept(address, now, events)?;
+                    }
+                    (Some(id), kind) => {
+                        self.process_packet(id, kind, &header, messages, now, events)?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn accept(
+        &mut self,
+        address: SocketAddr,
+        now: Instant,
+        events: &mut Vec<TransportEvent>,
+    ) -> Result<(), Error> {
+        let max_connections = match self.role {
+            Role::Client => return Ok(()),
+            Role::Server(ServerMode::Dedicated) => self.config.max_players,
+            Role::Server(ServerMode::Listen) => self.config.max_players.saturating_sub(1),
+        };
+
+        if self.connections.len() >= max_connections {
+            let connection = Connection::new(
+                address,
+                ConnectionState::Connecting,
+                &self.config.channels,
+                now,
+            );
+            return self.send_control(&connection, PacketKind::ConnectDeny);
+        }
+
+        let id = self.add_connection(address, ConnectionState::Connected, now);
+        self.send_control(&self.connections[&id], PacketKind::ConnectAccept)?;
+        events.push(TransportEvent::Connected(id));
+        Ok(())
+    }
+
+    fn process_packet(
+        &mut self,
+        id: ConnectionId,
+        kind: PacketKind,
+        header: &PacketHeader,
+        messages: Vec<MessageData>,
+        now: Instant,
+        events: &mut Vec<TransportEvent>,
+    ) -> Result<(), Error> {
+        let connection = self.connections.get_mut(&id).unwrap();
+        match kind {
+            // The accept packet has been lost, the client is still connecting
+            PacketKind::ConnectRequest => {
+                connection.last_received = now;
+                let connection = &self.connections[&id];
+                self.send_control(connection, PacketKind::ConnectAccept)?;
+            }
+            PacketKind::ConnectAccept | PacketKind::Payload => {
+                // Payloads can be received before the accept packet if it has been lost
+                if connection.state == ConnectionState::Connecting {
+                    connection.state = ConnectionState::Connected;
+                    connection.last_received = now;
+                    events.push(TransportEvent::Connected(id));
+                }
+
+                if kind == PacketKind::Payload && connection.receive_payload(header, messages, now)
+                {
+                    while let Some((channel, data)) = connection.take_received() {
+                        events.push(TransportEvent::Message {
+                            connection: id,
+                            channel,
+                            data,
+                        });
+                    }
+                }
+            }
+            PacketKind::ConnectDeny | PacketKind::Disconnect => {
+                self.connections.remove(&id);
+                events.push(TransportEvent::Disconnected(id));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_packets(&mut self, now: Instant) -> Result<(), Error> {
+        for connection in self.connections.values_mut() {
+            let (header, messages) = match connection.state {
+                ConnectionState::Connecting => {
+                    let retry = match connection.last_sent {
+                        Some(last_sent) => now - last_sent >= CONNECT_RETRY_DELAY,
+                        None => true,
+                    };
+                    if !retry {
+                        continue;
+                    }
+
+                    connection.last_sent = Some(now);
+                    (connection.header(PacketKind::ConnectRequest, 0), vec![])
+                }
+                ConnectionState::Connected => {
+                    if !connection.should_send(now) {
+                        continue;
+                    }
+
+                    connection.write_payload(now)
+                }
+            };
+
+            write_packet(
+                self.config.protocol_id,
+                &header,
+                &messages,
+                &mut self.buffer,
+            );
+            send_to(&self.socket, &self.buffer, connection.address)?;
+        }
+
+        Ok(())
+    }
+
+    fn send_control(&self, connection: &Connection, kind: PacketKind) -> Result<(), Error> {
+        let mut buffer = Vec::with_capacity(HEADER_SIZE);
+        write_packet(
+            self.config.protocol_id,
+            &connection.header(kind, 0),
+            &[],
+            &mut buffer,
+        );
+        send_to(&self.socket, &buffer, connection.address)
+    }
+
+    fn add_connection(
+        &mut self,
+        address: SocketAddr,
+        state: ConnectionState,
+        now: Instant,
+    ) -> ConnectionId {
+        let id = ConnectionId(self.next_connection_id);
+        self.next_connection_id += 1;
+        self.connections.insert(
+            id,
+            Connection::new(address, state, &self.config.channels, now),
+        );
+        id
+    }
+}
+
+fn send_to(socket: &UdpSocket, packet: &[u8], address: SocketAddr) -> Result<(), Error> {
+    match socket.send_to(packet, address) {
+        Ok(_) => Ok(()),
+        // Packet is dropped, as if it was lost on the network
+        Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(()),
+        Err(error) => Err(Error::Io(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::{
+        ServerMode, Transport, TransportConfig, TransportEvent, RELIABLE_CHANNEL,
+        UNRELIABLE_CHANNEL,
+    };
+    use std::time::{Duration, Instant};
+
+    /// Update both transports until `f` returns true
+    fn update_until(
+        server: &mut Transport,
+        clients: &mut [&mut Transport],
+        mut f: impl FnMut(&[TransportEvent], &[TransportEvent]) -> bool,
+    ) {
+        let start = Instant::now();
+        loop {
+            assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+            let server_events = server.update(Instant::now()).unwrap();
+            let mut client_events = vec![];
+            for client in clients.iter_mut() {
+                client_events.extend(client.update(Instant::now()).unwrap());
+            }
+
+            if f(&server_events, &client_events) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn server(mode: ServerMode, max_players: usize) -> Transport {
+        Transport::server(
+            "127.0.0.1:0",
+            TransportConfig {
+                max_players,
+                ..Default::default()
+            },
+            mode,
+        )
+        .unwrap()
+    }
+
+    fn client(server: &Transport) -> Transport {
+        Transport::client(server.local_address().unwrap(), TransportConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn connect_and_send() {
+        let mut server = server(ServerMode::Dedicated, 4);
+        let mut client = client(&server);
+        let client_connection = client.connections.keys().copied().next().unwrap();
+        client
+            .send(client_connection, RELIABLE_CHANNEL, &[1, 2, 3])
+            .unwrap();
+
+        let mut server_connection = None;
+        let mut received = vec![];
+        update_until(&mut server, &mut [&mut client], |server_events, _| {
+            for event in server_events {
+                match event {
+                    TransportEvent::Connected(id) => server_connection = Some(*id),
+                    TransportEvent::Message { data, channel, .. } => {
+                        received.push((*channel, data.clone()))
+                    }
+                    _ => {}
+                }
+            }
+            !received.is_empty()
+        });
+        assert_eq!(received, vec![(RELIABLE_CHANNEL, vec![1, 2, 3])]);
+        assert!(client.is_connected(client_connection));
+
+        let server_connection = server_connection.unwrap();
+        server
+            .send(server_connection, UNRELIABLE_CHANNEL, &[4])
+            .unwrap();
+        let mut received = vec![];
+        update_until(&mut server, &mut [&mut client], |_, client_events| {
+            for event in client_events {
+                if let TransportEvent::Message { data, .. } = event {
+                    received.push(data.clone());
+                }
+            }
+            !received.is_empty()
+        });
+        assert_eq!(received, vec![vec![4]]);
+
+        client.disconnect(client_connection).unwrap();
+        update_until(&mut server, &mut [], |server_events, _| {
+            server_events.contains(&TransportEvent::Disconnected(server_connection))
+        });
+    }
+
+    #[test]
+    fn listen_server_full() {
+        // Host uses one of the two slots
+        let mut server = server(ServerMode::Listen, 2);
+        let mut first = client(&server);
+        update_until(&mut server, &mut [&mut first], |_, client_events| {
+            matches!(client_events, [TransportEvent::Connected(_)])
+        });
+
+        let mut second = client(&server);
+        update_until(&mut server, &mut [&mut second], |_, client_events| {
+            matches!(client_events, [TransportEvent::Disconnected(_)])
+        });
+        assert_eq!(server.connections().count(), 1);
+    }
+}
@@ -0,0 +1,497 @@
+use parking_lot::{Condvar, Mutex};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle, WebDisplayHandle, WebWindowHandle};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use ze_core::maths::{Point2, RectI32};
+use ze_platform::{
+    Cursor, Error, FileDialogId, FileFilter, HitTestCallback, KeyCode, Message, ModifierBits,
+    Modifiers, Monitor, Platform, PowerStatus, SystemCursor, SystemTheme, TaskbarProgressState,
+    Window, WindowFlags,
+};
+
+const NULL_MONITOR_WIDTH: i32 = 1920;
+const NULL_MONITOR_HEIGHT: i32 = 1080;
+const NULL_MONITOR_DPI: f32 = 96.0;
+
+type MessageQueue = Arc<Mutex<VecDeque<Message>>>;
+
+/// Platform without any display server, used by dedicated servers, CI and automated tests
+///
+/// Windows are virtual and no input is ever received, messages are only the ones pushed with
+/// [`NullPlatform::send_message`] or generated by the windows themselves (e.g resizes)
+pub struct NullPlatform {
+    message_queue: MessageQueue,
+    mouse_position: Arc<Mutex<Point2<i32>>>,
+
+    /// Keys held according to the key messages returned by `poll_event`
+    keys_down: Mutex<HashSet<KeyCode>>,
+
+    /// Theme of the last `SystemThemeChanged` message returned by `poll_event`
+    system_theme: Mutex<SystemTheme>,
+
+    /// Set with `set_power_status`, unknown by default
+    power_status: Mutex<PowerStatus>,
+
+    /// Clipboard local to this platform
+    clipboard: Mutex<Option<String>>,
+
+    /// Set by `wake` and `send_message`, notified to the thread blocked in `wait_event`
+    woken: Mutex<bool>,
+    wake_condition: Condvar,
+}
+
+impl NullPlatform {
+    pub fn new() -> Arc<NullPlatform> {
+        Arc::new(Self {
+            message_queue: Default::default(),
+            mouse_position: Default::default(),
+            keys_down: Default::default(),
+            system_theme: Mutex::new(SystemTheme::Light),
+            power_status: Default::default(),
+            clipboard: Default::default(),
+            woken: Mutex::new(false),
+            wake_condition: Condvar::new(),
+        })
+    }
+
+    /// Queue a message, returned by a later [`Platform::poll_event`] call
+    pub fn send_message(&self, message: Message) {
+        self.message_queue.lock().push_back(message);
+        self.wake();
+    }
+
+    pub fn set_mouse_position(&self, position: Point2<i32>) {
+        *self.mouse_position.lock() = position;
+    }
+
+    pub fn set_power_status(&self, status: PowerStatus) {
+        *self.power_status.lock() = status;
+    }
+}
+
+impl Platform for NullPlatform {
+    fn poll_event(&self) -> Option<Message> {
+        let message = self.message_queue.lock().pop_front()?;
+        match &message {
+            Message::KeyDown(_, key, _, _, _) => {
+                self.keys_down.lock().insert(*key);
+            }
+            Message::KeyUp(_, key, _, _, _) => {
+                self.keys_down.lock().remove(key);
+            }
+            Message::SystemThemeChanged(theme) => {
+                *self.system_theme.lock() = *theme;
+            }
+            _ => {}
+        }
+        Some(message)
+    }
+
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        let mut woken = self.woken.lock();
+        if !*woken && self.message_queue.lock().is_empty() {
+            match timeout {
+                Some(timeout) => {
+                    self.wake_condition.wait_for(&mut woken, timeout);
+                }
+                None => self.wake_condition.wait(&mut woken),
+            }
+        }
+        *woken = false;
+        drop(woken);
+
+        self.poll_event()
+    }
+
+    fn wake(&self) {
+        *self.woken.lock() = true;
+        self.wake_condition.notify_all();
+    }
+
+    fn create_window(
+        &self,
+        _: &str,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        _: WindowFlags,
+    ) -> Result<Arc<dyn Window>, Error> {
+        Ok(Arc::new_cyclic(|window| NullWindow {
+            window: window.clone(),
+            message_queue: self.message_queue.clone(),
+            mouse_position: self.mouse_position.clone(),
+            width: AtomicU32::new(width),
+            height: AtomicU32::new(height),
+            x: AtomicI32::new(x),
+            y: AtomicI32::new(y),
+            minimized: AtomicBool::new(false),
+            focused: AtomicBool::new(false),
+        }))
+    }
+
+    fn create_system_cursor(&self, _: SystemCursor) -> Box<dyn Cursor> {
+        Box::new(NullCursor)
+    }
+
+    fn create_cursor_from_rgba(
+        &self,
+        _: &[u8],
+        _: u32,
+        _: u32,
+        _: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        Ok(Box::new(NullCursor))
+    }
+
+    fn set_cursor(&self, _: Option<&dyn Cursor>) {}
+
+    fn show_cursor(&self, _: bool) {}
+
+    fn capture_mouse(&self, _: &dyn Window) {}
+
+    fn release_mouse(&self) {}
+
+    fn mouse_position(&self) -> Point2<i32> {
+        *self.mouse_position.lock()
+    }
+
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys_down.lock().contains(&key)
+    }
+
+    /// There is no key code for super, it is never reported
+    fn keyboard_modifiers(&self) -> Modifiers {
+        let keys_down = self.keys_down.lock();
+        let any_down = |left, right| keys_down.contains(&left) || keys_down.contains(&right);
+        let mut modifiers = Modifiers::empty();
+        if any_down(KeyCode::LeftShift, KeyCode::RightShift) {
+            modifiers |= ModifierBits::Shift;
+        }
+        if any_down(KeyCode::LeftControl, KeyCode::RightControl) {
+            modifiers |= ModifierBits::Control;
+        }
+        if any_down(KeyCode::LeftAlt, KeyCode::RightAlt) {
+            modifiers |= ModifierBits::Alt;
+        }
+        modifiers
+    }
+
+    fn monitor_count(&self) -> usize {
+        1
+    }
+
+    fn monitor(&self, _: usize) -> Monitor {
+        let bounds = RectI32::new(0, 0, NULL_MONITOR_WIDTH, NULL_MONITOR_HEIGHT);
+        Monitor {
+            bounds,
+            work_bounds: bounds,
+            dpi: NULL_MONITOR_DPI,
+        }
+    }
+
+    fn system_theme(&self) -> SystemTheme {
+        *self.system_theme.lock()
+    }
+
+    fn power_status(&self) -> PowerStatus {
+        *self.power_status.lock()
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        self.clipboard.lock().clone()
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        *self.clipboard.lock() = Some(text.to_string());
+    }
+
+    /// Nobody can answer the dialog, it is cancelled right away
+    fn open_file_dialog(&self, _: &[FileFilter], _: bool) -> FileDialogId {
+        let id = FileDialogId::new();
+        self.send_message(Message::FileDialogClosed(id, vec![]));
+        id
+    }
+
+    fn save_file_dialog(&self, _: &[FileFilter], _: &str) -> FileDialogId {
+        let id = FileDialogId::new();
+        self.send_message(Message::FileDialogClosed(id, vec![]));
+        id
+    }
+}
+
+/// Window only existing in memory
+pub struct NullWindow {
+    window: Weak<NullWindow>,
+    message_queue: MessageQueue,
+
+    /// Mouse position of the platform, in screen coordinates
+    mouse_position: Arc<Mutex<Point2<i32>>>,
+    width: AtomicU32,
+    height: AtomicU32,
+    x: AtomicI32,
+    y: AtomicI32,
+    minimized: AtomicBool,
+    focused: AtomicBool,
+}
+
+impl Window for NullWindow {
+    fn set_position(&self, position: Point2<i32>) {
+        self.x.store(position.x, Ordering::SeqCst);
+        self.y.store(position.y, Ordering::SeqCst);
+    }
+
+    /// Resizes are reported like with any other platform
+    fn set_size(&self, width: u32, height: u32) {
+        self.width.store(width, Ordering::SeqCst);
+        self.height.store(height, Ordering::SeqCst);
+        self.message_queue.lock().push_back(Message::WindowResized(
+            self.window.clone(),
+            width,
+            height,
+        ));
+    }
+
+    /// Nobody can resize a null window, the limits have nothing to apply to
+    fn set_min_size(&self, _: Option<(u32, u32)>) {}
+
+    fn set_max_size(&self, _: Option<(u32, u32)>) {}
+
+    fn set_title(&self, _: &str) {}
+
+    fn set_icon(&self, _: u32, _: u32, _: &[u8]) {}
+
+    fn show(&self) {}
+
+    fn close(&self) {}
+
+    fn minimize(&self) {
+        self.minimized.store(true, Ordering::SeqCst);
+    }
+
+    fn maximize(&self) {
+        self.minimized.store(false, Ordering::SeqCst);
+    }
+
+    fn restore(&self) {
+        self.minimized.store(false, Ordering::SeqCst);
+    }
+
+    /// Focus changes are reported like with any other platform
+    fn focus(&self) {
+        if !self.focused.swap(true, Ordering::SeqCst) {
+            self.message_queue
+                .lock()
+                .push_back(Message::WindowFocusGained(self.window.clone()));
+        }
+    }
+
+    fn set_opacity(&self, _: f32) {}
+
+    fn set_always_on_top(&self, _: bool) {}
+
+    fn set_taskbar_progress(&self, _: TaskbarProgressState, _: f32) {}
+
+    fn request_attention(&self) {}
+
+    /// Nothing can be dragged without a mouse
+    fn set_hit_test_callback(&self, _: Option<HitTestCallback>) {}
+
+    fn set_ime_position(&self, _: Point2<i32>) {}
+
+    /// There is no native window, an empty handle is returned that only null backends accept
+    fn handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Web(WebWindowHandle::empty())
+    }
+
+    fn display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Web(WebDisplayHandle::empty())
+    }
+
+    fn width(&self) -> u32 {
+        self.width.load(Ordering::SeqCst)
+    }
+
+    fn height(&self) -> u32 {
+        self.height.load(Ordering::SeqCst)
+    }
+
+    fn position(&self) -> Point2<i32> {
+        Point2::new(self.x.load(Ordering::SeqCst), self.y.load(Ordering::SeqCst))
+    }
+
+    fn cursor_position(&self) -> Point2<i32> {
+        let mouse_position = *self.mouse_position.lock();
+        let position = self.position();
+        Point2::new(mouse_position.x - position.x, mouse_position.y - position.y)
+    }
+
+    fn is_minimized(&self) -> bool {
+        self.minimized.load(Ordering::SeqCst)
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        1.0
+    }
+}
+
+pub struct NullCursor;
+
+impl Cursor for NullCursor {}
+
+#[cfg(test)]
+mod tests {
+    use crate::NullPlatform;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use ze_core::maths::Point2;
+    use ze_platform::{
+        KeyCode, Message, ModifierBits, Platform, PowerSource, PowerStatus, SystemTheme, Window,
+        WindowFlags,
+    };
+
+    fn create_window(platform: &NullPlatform) -> Arc<dyn Window> {
+        platform
+            .create_window("Test", 800, 600, 10, 20, WindowFlags::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn poll_event_returns_messages_in_order() {
+        let platform = NullPlatform::new();
+        let window = create_window(&platform);
+
+        platform.send_message(Message::KeyDown(
+            Arc::downgrade(&window),
+            KeyCode::LeftShift,
+            KeyCode::LeftShift,
+            0,
+            false,
+        ));
+        platform.send_message(Message::SystemThemeChanged(SystemTheme::Dark));
+        platform.send_message(Message::KeyUp(
+            Arc::downgrade(&window),
+            KeyCode::LeftShift,
+            KeyCode::LeftShift,
+            0,
+            false,
+        ));
+
+        // Input state follows the messages as they are returned
+        assert!(!platform.is_key_down(KeyCode::LeftShift));
+        assert!(matches!(
+            platform.poll_event(),
+            Some(Message::KeyDown(_, KeyCode::LeftShift, ..))
+        ));
+        assert!(platform.is_key_down(KeyCode::LeftShift));
+        assert!(platform.keyboard_modifiers().contains(ModifierBits::Shift));
+
+        assert!(matches!(
+            platform.poll_event(),
+            Some(Message::SystemThemeChanged(SystemTheme::Dark))
+        ));
+        assert_eq!(platform.system_theme(), SystemTheme::Dark);
+
+        assert!(matches!(
+            platform.poll_event(),
+            Some(Message::KeyUp(_, KeyCode::LeftShift, ..))
+        ));
+        assert!(!platform.is_key_down(KeyCode::LeftShift));
+        assert!(platform.poll_event().is_none());
+    }
+
+    #[test]
+    fn wait_event_times_out_without_messages() {
+        let platform = NullPlatform::new();
+        let timeout = Duration::from_millis(50);
+
+        let start = Instant::now();
+        assert!(platform.wait_event(Some(timeout)).is_none());
+        assert!(start.elapsed() >= timeout);
+    }
+
+    #[test]
+    fn wait_event_returns_pending_messages_immediately() {
+        let platform = NullPlatform::new();
+        platform.send_message(Message::SystemThemeChanged(SystemTheme::Dark));
+
+        let start = Instant::now();
+        assert!(matches!(
+            platform.wait_event(Some(Duration::from_secs(10))),
+            Some(Message::SystemThemeChanged(SystemTheme::Dark))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn wait_event_is_woken_by_other_threads() {
+        let platform = NullPlatform::new();
+
+        let sender = {
+            let platform = platform.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                platform.send_message(Message::SystemThemeChanged(SystemTheme::Dark));
+            })
+        };
+
+        assert!(matches!(
+            platform.wait_event(None),
+            Some(Message::SystemThemeChanged(SystemTheme::Dark))
+        ));
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn null_window() {
+        let platform = NullPlatform::new();
+        let window = create_window(&platform);
+        assert_eq!((window.width(), window.height()), (800, 600));
+        assert_eq!(window.position(), Point2::new(10, 20));
+
+        // Cursor position is relative to the window
+        platform.set_mouse_position(Point2::new(110, 220));
+        assert_eq!(window.cursor_position(), Point2::new(100, 200));
+
+        // Resizes and focus changes are reported
+        window.set_size(1024, 768);
+        assert_eq!((window.width(), window.height()), (1024, 768));
+        match platform.poll_event() {
+            Some(Message::WindowResized(resized, width, height)) => {
+                assert!(Arc::ptr_eq(&resized.upgrade().unwrap(), &window));
+                assert_eq!((width, height), (1024, 768));
+            }
+            _ => panic!("Expected a resize message"),
+        }
+
+        window.focus();
+        window.focus();
+        assert!(window.is_focused());
+        assert!(matches!(
+            platform.poll_event(),
+            Some(Message::WindowFocusGained(_))
+        ));
+        assert!(platform.poll_event().is_none());
+    }
+
+    #[test]
+    fn power_status() {
+        let platform = NullPlatform::new();
+        assert_eq!(platform.power_status(), PowerStatus::default());
+
+        let status = PowerStatus {
+            source: PowerSource::Battery,
+            battery_percentage: Some(42),
+            power_saver: true,
+        };
+        platform.set_power_status(status);
+        assert_eq!(platform.power_status(), status);
+    }
+}
@@ -0,0 +1,94 @@
+use nalgebra_glm as glm;
+use ze_ecs::Component;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum BodyType {
+    /// Moved by the simulation
+    #[default]
+    Dynamic,
+
+    /// Never moves
+    Fixed,
+
+    /// Moved by changing its transform, pushes dynamic bodies but is not affected by them
+    Kinematic,
+}
+
+/// Simulate the entity as a rigid body
+///
+/// The transform of the entity is considered to be in world space
+/// Velocities are updated by the simulation for dynamic bodies
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct RigidBody {
+    pub body_type: BodyType,
+    pub linear_velocity: glm::Vec3,
+    pub angular_velocity: glm::Vec3,
+    pub gravity_scale: f32,
+
+    /// Enable continuous collision detection, prevents fast bodies from going through thin
+    /// colliders
+    pub ccd: bool,
+}
+
+impl RigidBody {
+    pub fn new(body_type: BodyType) -> Self {
+        Self {
+            body_type,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        Self {
+            body_type: BodyType::Dynamic,
+            linear_velocity: glm::Vec3::zeros(),
+            angular_velocity: glm::Vec3::zeros(),
+            gravity_scale: 1.0,
+            ccd: false,
+        }
+    }
+}
+
+/// Shape of a collider, not affected by the entity scale
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColliderShape {
+    Cuboid {
+        half_extents: glm::Vec3,
+    },
+    Ball {
+        radius: f32,
+    },
+
+    /// Capsule along the Y axis
+    Capsule {
+        half_height: f32,
+        radius: f32,
+    },
+}
+
+/// Collision shape of an entity, attached to the [`RigidBody`] of the entity if it has one
+/// or fixed otherwise
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub friction: f32,
+    pub restitution: f32,
+    pub density: f32,
+
+    /// Sensors detect overlaps but don't generate contacts
+    pub sensor: bool,
+}
+
+impl Collider {
+    pub fn new(shape: ColliderShape) -> Self {
+        Self {
+            shape,
+            friction: 0.5,
+            restitution: 0.0,
+            density: 1.0,
+            sensor: false,
+        }
+    }
+}
@@ -0,0 +1,577 @@
+use crate::component::{BodyType, Collider, ColliderShape, RigidBody};
+use nalgebra_glm as glm;
+use rapier3d::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use ze_ecs::entity::Entity;
+use ze_ecs::world::query::filter::With;
+use ze_ecs::world::World;
+use ze_scene::debug_draw::DebugDraw;
+use ze_scene::transform::Transform;
+
+pub mod component;
+
+/// Duration of a simulation step in seconds
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Maximum amount of steps done by a single update, the simulation slows down instead of trying
+/// to catch up when frames take too long
+const MAX_STEPS_PER_UPDATE: u32 = 5;
+
+const GRAVITY: f32 = -9.81;
+
+#[derive(Copy, Clone)]
+pub struct RaycastHit {
+    pub entity: Entity,
+
+    /// Distance between the ray origin and the hit point
+    pub distance: f32,
+    pub point: glm::Vec3,
+    pub normal: glm::Vec3,
+}
+
+/// Simulation objects of an entity with the components they were created from, used to detect
+/// the changes made to the components
+struct PhysicsEntity {
+    body: Option<(RigidBodyHandle, RigidBody)>,
+    collider: Option<(ColliderHandle, Collider)>,
+    transform: Transform,
+}
+
+/// Rigid body simulation of the entities having a [`RigidBody`] and/or a [`Collider`]
+pub struct PhysicsWorld {
+    gravity: Vector<Real>,
+    integration_parameters: IntegrationParameters,
+    pipeline: PhysicsPipeline,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    query_pipeline: QueryPipeline,
+    debug_render_pipeline: DebugRenderPipeline,
+    entities: HashMap<Entity, PhysicsEntity>,
+    collider_entities: HashMap<ColliderHandle, Entity>,
+
+    /// Time not simulated yet, always lower than [`FIXED_TIMESTEP`] after an update
+    accumulator: f32,
+}
+
+impl Default for PhysicsWorld {
+    fn default() -> Self {
+        Self {
+            gravity: vector![0.0, GRAVITY, 0.0],
+            integration_parameters: IntegrationParameters {
+                dt: FIXED_TIMESTEP,
+                ..Default::default()
+            },
+            pipeline: PhysicsPipeline::new(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders: ColliderSet::new(),
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            debug_render_pipeline: DebugRenderPipeline::new(
+                DebugRenderStyle::default(),
+                DebugRenderMode::COLLIDER_SHAPES,
+            ),
+            entities: HashMap::new(),
+            collider_entities: HashMap::new(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Advance the simulation by `delta_time` seconds using fixed steps, the transforms and
+    /// velocities of the dynamic bodies are then written back to `world`
+    pub fn update(&mut self, world: &mut World, delta_time: f32) {
+        self.sync(world);
+
+        let max_time = FIXED_TIMESTEP * MAX_STEPS_PER_UPDATE as f32;
+        self.accumulator = (self.accumulator + delta_time).min(max_time);
+        let mut stepped = false;
+        while self.accumulator >= FIXED_TIMESTEP {
+            self.step();
+            self.accumulator -= FIXED_TIMESTEP;
+            stepped = true;
+        }
+
+        if stepped {
+            self.write_back(world);
+        }
+    }
+
+    /// Create, update and remove the simulation objects from the components of `world`
+    ///
+    /// Called by [`PhysicsWorld::update`], can be called alone to keep the queries and the debug
+    /// draw up to date without simulating (e.g. in the editor)
+    pub fn sync(&mut self, world: &World) {
+        let mut entities = BTreeSet::new();
+        world
+            .query::<(Entity, With<RigidBody>)>()
+            .for_each(world, |(entity, _)| {
+                entities.insert(entity);
+            });
+        world
+            .query::<(Entity, With<Collider>)>()
+            .for_each(world, |(entity, _)| {
+                entities.insert(entity);
+            });
+
+        let removed = self
+            .entities
+            .keys()
+            .filter(|entity| !entities.contains(entity))
+            .copied()
+            .collect::<Vec<_>>();
+        for entity in removed {
+            let mut state = self.entities.remove(&entity).unwrap();
+            self.sync_body(&mut state, None);
+            self.sync_collider(entity, &mut state, None);
+        }
+
+        for entity in entities {
+            let body = world.query::<&RigidBody>().get(world, entity).copied();
+            let collider = world.query::<&Collider>().get(world, entity).copied();
+            let transform = world
+                .query::<&Transform>()
+                .get(world, entity)
+                .copied()
+                .unwrap_or_default();
+
+            let mut state = self.entities.remove(&entity).unwrap_or(PhysicsEntity {
+                body: None,
+                collider: None,
+                transform,
+            });
+
+            if state.transform != transform {
+                state.transform = transform;
+                self.move_entity(&state);
+            }
+            self.sync_body(&mut state, body);
+            self.sync_collider(entity, &mut state, collider);
+            self.entities.insert(entity, state);
+        }
+
+        self.query_pipeline.update(&self.bodies, &self.colliders);
+    }
+
+    fn sync_body(&mut self, state: &mut PhysicsEntity, body: Option<RigidBody>) {
+        match (&mut state.body, body) {
+            (Some((handle, old)), Some(body)) => {
+                if *old != body {
+                    let rigid_body = &mut self.bodies[*handle];
+                    rigid_body.set_body_type(body_type(body.body_type), true);
+                    rigid_body.set_linvel(to_vector(&body.linear_velocity), true);
+                    rigid_body.set_angvel(to_vector(&body.angular_velocity), true);
+                    rigid_body.set_gravity_scale(body.gravity_scale, true);
+                    rigid_body.enable_ccd(body.ccd);
+                    *old = body;
+                }
+            }
+            (Some((handle, _)), None) => {
+                self.bodies.remove(
+                    *handle,
+                    &mut self.islands,
+                    &mut self.colliders,
+                    &mut self.impulse_joints,
+                    &mut self.multibody_joints,
+                    true,
+                );
+                state.body = None;
+
+                // Attached collider has been removed with the body
+                if let Some((handle, _)) = state.collider.take() {
+                    self.collider_entities.remove(&handle);
+                }
+            }
+            (None, Some(body)) => {
+                let rigid_body = RigidBodyBuilder::new(body_type(body.body_type))
+                    .position(to_isometry(&state.transform))
+                    .linvel(to_vector(&body.linear_velocity))
+                    .angvel(to_vector(&body.angular_velocity))
+                    .gravity_scale(body.gravity_scale)
+                    .ccd_enabled(body.ccd)
+                    .build();
+                state.body = Some((self.bodies.insert(rigid_body), body));
+
+                // Fixed collider will be recreated attached to the body
+                self.remove_collider(state);
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn sync_collider(
+        &mut self,
+        entity: Entity,
+        state: &mut PhysicsEntity,
+        collider: Option<Collider>,
+    ) {
+        if let Some((_, old)) = &state.collider {
+            if Some(*old) == collider {
+                return;
+            }
+
+            self.remove_collider(state);
+        }
+
+        if let Some(collider) = collider {
+            let builder = ColliderBuilder::new(shared_shape(&collider.shape))
+                .friction(collider.friction)
+                .restitution(collider.restitution)
+                .density(collider.density)
+                .sensor(collider.sensor);
+
+            let handle = match &state.body {
+                Some((body, _)) => {
+                    self.colliders
+                        .insert_with_parent(builder.build(), *body, &mut self.bodies)
+                }
+                None => self
+                    .colliders
+                    .insert(builder.position(to_isometry(&state.transform)).build()),
+            };
+
+            self.collider_entities.insert(handle, entity);
+            state.collider = Some((handle, collider));
+        }
+    }
+
+    fn remove_collider(&mut self, state: &mut PhysicsEntity) {
+        if let Some((handle, _)) = state.collider.take() {
+            self.colliders
+                .remove(handle, &mut self.islands, &mut self.bodies, true);
+            self.collider_entities.remove(&handle);
+        }
+    }
+
+    /// Teleport the simulation objects of the entity to its transform
+    fn move_entity(&mut self, state: &PhysicsEntity) {
+        let position = to_isometry(&state.transform);
+        match (&state.body, &state.collider) {
+            (Some((handle, body)), _) => {
+                let rigid_body = &mut self.bodies[*handle];
+                if body.body_type == BodyType::Kinematic {
+                    rigid_body.set_next_kinematic_position(position);
+                } else {
+                    rigid_body.set_position(position, true);
+                }
+            }
+            (None, Some((handle, _))) => self.colliders[*handle].set_position(position),
+            (None, None) => {}
+        }
+    }
+
+    fn step(&mut self) {
+        self.pipeline.step(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+    }
+
+    /// Write the state of the awake dynamic bodies to their components
+    fn write_back(&mut self, world: &mut World) {
+        for (entity, state) in &mut self.entities {
+            let (handle, body) = match &mut state.body {
+                Some((handle, body)) if body.body_type == BodyType::Dynamic => (handle, body),
+                _ => continue,
+            };
+
+            let rigid_body = &self.bodies[*handle];
+            if rigid_body.is_sleeping() {
+                continue;
+            }
+
+            let position = rigid_body.position();
+            let rotation = position.rotation;
+            state.transform.translation = from_vector(&position.translation.vector);
+            state.transform.rotation = glm::quat(rotation.i, rotation.j, rotation.k, rotation.w);
+            body.linear_velocity = from_vector(rigid_body.linvel());
+            body.angular_velocity = from_vector(rigid_body.angvel());
+
+            if let Some(transform) = world.query::<&mut Transform>().get(world, *entity) {
+                *transform = state.transform;
+            }
+
+            if let Some(component) = world.query::<&mut RigidBody>().get(world, *entity) {
+                *component = *body;
+            }
+        }
+    }
+
+    /// Closest collider hit by the ray, `direction` doesn't need to be normalized
+    pub fn raycast(
+        &self,
+        origin: &glm::Vec3,
+        direction: &glm::Vec3,
+        max_distance: f32,
+    ) -> Option<RaycastHit> {
+        let direction = direction.try_normalize(f32::EPSILON)?;
+        let ray = Ray::new(point![origin.x, origin.y, origin.z], to_vector(&direction));
+
+        let (handle, intersection) = self.query_pipeline.cast_ray_and_get_normal(
+            &self.bodies,
+            &self.colliders,
+            &ray,
+            max_distance,
+            true,
+            QueryFilter::default(),
+        )?;
+
+        let point = ray.point_at(intersection.toi);
+        Some(RaycastHit {
+            entity: *self.collider_entities.get(&handle)?,
+            distance: intersection.toi,
+            point: glm::vec3(point.x, point.y, point.z),
+            normal: from_vector(&intersection.normal),
+        })
+    }
+
+    /// Entities whose collider overlaps `shape` placed at `translation` with `rotation`
+    pub fn overlap(
+        &self,
+        shape: &ColliderShape,
+        translation: &glm::Vec3,
+        rotation: &glm::Quat,
+    ) -> Vec<Entity> {
+        let shape = shared_shape(shape);
+        let position = to_isometry(&Transform {
+            translation: *translation,
+            rotation: *rotation,
+            ..Default::default()
+        });
+
+        let mut entities = vec![];
+        self.query_pipeline.intersections_with_shape(
+            &self.bodies,
+            &self.colliders,
+            &position,
+            &*shape,
+            QueryFilter::default(),
+            |handle| {
+                if let Some(entity) = self.collider_entities.get(&handle) {
+                    entities.push(*entity);
+                }
+                true
+            },
+        );
+        entities
+    }
+
+    /// Add the outlines of the colliders to `debug_draw`
+    pub fn debug_draw(&mut self, debug_draw: &mut DebugDraw) {
+        struct Backend<'a>(&'a mut DebugDraw);
+
+        impl DebugRenderBackend for Backend<'_> {
+            fn draw_line(
+                &mut self,
+                _: DebugRenderObject,
+                a: Point<Real>,
+                b: Point<Real>,
+                color: [f32; 4],
+            ) {
+                self.0.line(
+                    glm::vec3(a.x, a.y, a.z),
+                    glm::vec3(b.x, b.y, b.z),
+                    hsla_to_rgba(color),
+                );
+            }
+        }
+
+        self.debug_render_pipeline.render(
+            &mut Backend(debug_draw),
+            &self.bodies,
+            &self.colliders,
+            &self.impulse_joints,
+            &self.multibody_joints,
+            &self.narrow_phase,
+        );
+    }
+}
+
+fn body_type(body_type: BodyType) -> RigidBodyType {
+    match body_type {
+        BodyType::Dynamic => RigidBodyType::Dynamic,
+        BodyType::Fixed => RigidBodyType::Fixed,
+        BodyType::Kinematic => RigidBodyType::KinematicPositionBased,
+    }
+}
+
+fn shared_shape(shape: &ColliderShape) -> SharedShape {
+    match *shape {
+        ColliderShape::Cuboid { half_extents } => {
+            SharedShape::cuboid(half_extents.x, half_extents.y, half_extents.z)
+        }
+        ColliderShape::Ball { radius } => SharedShape::ball(radius),
+        ColliderShape::Capsule {
+            half_height,
+            radius,
+        } => SharedShape::capsule_y(half_height, radius),
+    }
+}
+
+// Vectors are converted component by component as rapier may not use the same nalgebra version
+// as nalgebra-glm
+
+fn to_vector(vector: &glm::Vec3) -> Vector<Real> {
+    vector![vector.x, vector.y, vector.z]
+}
+
+fn from_vector(vector: &Vector<Real>) -> glm::Vec3 {
+    glm::vec3(vector.x, vector.y, vector.z)
+}
+
+/// Scale is ignored
+fn to_isometry(transform: &Transform) -> Isometry<Real> {
+    let rotation = transform.rotation;
+    Isometry::from_parts(
+        Translation::from(to_vector(&transform.translation)),
+        Rotation::from_quaternion(nalgebra::Quaternion::new(
+            rotation.w, rotation.i, rotation.j, rotation.k,
+        )),
+    )
+}
+
+/// Debug render colors of rapier are in HSLA
+fn hsla_to_rgba([hue, saturation, lightness, alpha]: [f32; 4]) -> [f32; 4] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue = hue / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma * 0.5;
+    [r + m, g + m, b + m, alpha]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::{BodyType, Collider, ColliderShape, RigidBody};
+    use crate::PhysicsWorld;
+    use nalgebra_glm as glm;
+    use ze_ecs::entity::Entity;
+    use ze_ecs::world::World;
+    use ze_scene::debug_draw::DebugDraw;
+    use ze_scene::transform::Transform;
+
+    fn spawn(world: &mut World, translation: glm::Vec3, body_type: Option<BodyType>) -> Entity {
+        let entity = world.spawn();
+        world.add(entity, Transform::from_translation(translation));
+        world.add(
+            entity,
+            Collider::new(ColliderShape::Cuboid {
+                half_extents: glm::vec3(0.5, 0.5, 0.5),
+            }),
+        );
+        if let Some(body_type) = body_type {
+            world.add(entity, RigidBody::new(body_type));
+        }
+        entity
+    }
+
+    fn translation(world: &World, entity: Entity) -> glm::Vec3 {
+        world
+            .query::<&Transform>()
+            .get(world, entity)
+            .unwrap()
+            .translation
+    }
+
+    #[test]
+    fn dynamic_body_falls() {
+        let mut world = World::default();
+        let mut physics = PhysicsWorld::default();
+        let dynamic = spawn(
+            &mut world,
+            glm::vec3(0.0, 10.0, 0.0),
+            Some(BodyType::Dynamic),
+        );
+        let fixed = spawn(&mut world, glm::vec3(5.0, 10.0, 0.0), Some(BodyType::Fixed));
+
+        for _ in 0..10 {
+            physics.update(&mut world, 1.0 / 30.0);
+        }
+
+        assert!(translation(&world, dynamic).y < 10.0);
+        assert!(
+            world
+                .query::<&RigidBody>()
+                .get(&world, dynamic)
+                .unwrap()
+                .linear_velocity
+                .y
+                < 0.0
+        );
+        assert_eq!(translation(&world, fixed).y, 10.0);
+    }
+
+    #[test]
+    fn raycast() {
+        let mut world = World::default();
+        let mut physics = PhysicsWorld::default();
+        let near = spawn(&mut world, glm::vec3(0.0, 0.0, 5.0), None);
+        let far = spawn(&mut world, glm::vec3(0.0, 0.0, 10.0), None);
+        physics.sync(&world);
+
+        let hit = physics
+            .raycast(&glm::Vec3::zeros(), &glm::vec3(0.0, 0.0, 2.0), 100.0)
+            .unwrap();
+        assert!(hit.entity == near);
+        assert!((hit.distance - 4.5).abs() < 1e-4);
+        assert!((hit.normal - glm::vec3(0.0, 0.0, -1.0)).norm() < 1e-4);
+
+        world.destroy(near);
+        physics.sync(&world);
+        let hit = physics
+            .raycast(&glm::Vec3::zeros(), &glm::Vec3::z(), 100.0)
+            .unwrap();
+        assert!(hit.entity == far);
+        assert!(physics
+            .raycast(&glm::Vec3::zeros(), &glm::Vec3::x(), 100.0)
+            .is_none());
+    }
+
+    #[test]
+    fn overlap() {
+        let mut world = World::default();
+        let mut physics = PhysicsWorld::default();
+        let entity = spawn(&mut world, glm::vec3(3.0, 0.0, 0.0), None);
+        physics.sync(&world);
+
+        let ball = ColliderShape::Ball { radius: 1.0 };
+        let overlapping = physics.overlap(&ball, &glm::vec3(2.0, 0.0, 0.0), &glm::Quat::identity());
+        assert!(overlapping == vec![entity]);
+        assert!(physics
+            .overlap(&ball, &glm::vec3(-2.0, 0.0, 0.0), &glm::Quat::identity())
+            .is_empty());
+
+        let mut debug_draw = DebugDraw::default();
+        physics.debug_draw(&mut debug_draw);
+        assert!(!debug_draw.lines().is_empty());
+    }
+}
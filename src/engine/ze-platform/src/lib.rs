@@ -1,7 +1,10 @@
 use enumflags2::*;
-use raw_window_handle::RawWindowHandle;
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use ze_core::downcast_rs::{impl_downcast, Downcast};
 use ze_core::maths::{Point2, RectI32};
 
@@ -15,6 +18,19 @@ pub enum WindowFlagBits {
 }
 pub type WindowFlags = BitFlags<WindowFlagBits>;
 
+#[bitflags]
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModifierBits {
+    Shift = 1 << 0,
+    Control = 1 << 1,
+    Alt = 1 << 2,
+
+    /// Windows key on Windows, command key on macOS
+    Super = 1 << 3,
+}
+pub type Modifiers = BitFlags<ModifierBits>;
+
 pub enum SystemCursor {
     No,
     Crosshair,
@@ -37,36 +53,169 @@ pub enum MouseButton {
     Right,
 }
 
+/// Part of a window located under a point, used by borderless windows to provide their own decorations
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HitTest {
+    Client,
+    Caption,
+    MaximizeButton,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Kind of progress shown on the taskbar button of a window
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TaskbarProgressState {
+    /// Nothing shown
+    None,
+
+    /// Operation of unknown length, the fraction is ignored
+    Indeterminate,
+    Normal,
+    Paused,
+    Error,
+}
+
+/// Called with a point relative to the window client area
+pub type HitTestCallback = Box<dyn Fn(Point2<i32>) -> HitTest + Send + Sync>;
+
 pub trait Cursor: Downcast {}
 impl_downcast!(Cursor);
 
 pub trait Window: Downcast + Send + Sync {
     fn set_position(&self, pos: Point2<i32>);
     fn set_size(&self, width: u32, height: u32);
+
+    /// Smallest client area size the user can resize the window to, `None` to remove the limit
+    fn set_min_size(&self, size: Option<(u32, u32)>);
+
+    /// Largest client area size the user can resize the window to, `None` to remove the limit
+    fn set_max_size(&self, size: Option<(u32, u32)>);
     fn set_title(&self, title: &str);
+
+    /// Set the icon shown in the title bar and the taskbar from `width * height` 8-bit RGBA pixels,
+    /// rows from top to bottom
+    fn set_icon(&self, width: u32, height: u32, rgba: &[u8]);
     fn show(&self);
 
+    /// Close the window, usually in response to [`Message::WindowCloseRequested`].
+    /// It is hidden right away, its native resources are released once it is dropped
+    fn close(&self);
+    fn minimize(&self);
+    fn maximize(&self);
+
+    /// Restore a minimized or maximized window to its previous size and position
+    fn restore(&self);
+
+    /// Bring the window to the front and give it the keyboard focus
+    fn focus(&self);
+
+    /// Opacity of the whole window, from 0 (transparent) to 1 (opaque)
+    fn set_opacity(&self, opacity: f32);
+
+    /// Keep the window above the windows that aren't always on top
+    fn set_always_on_top(&self, always_on_top: bool);
+
+    /// Show the progress of a long operation on the taskbar button of the window,
+    /// `fraction` goes from 0 to 1
+    fn set_taskbar_progress(&self, state: TaskbarProgressState, fraction: f32);
+
+    /// Flash the taskbar button of the window until it gets the focus, nothing happens if it
+    /// already has it
+    fn request_attention(&self);
+
+    /// Set the callback deciding which parts of the window can be used to move or resize it
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>);
+
+    /// Place the input method candidates below this point, relative to the client area,
+    /// usually the bottom of the text cursor
+    fn set_ime_position(&self, position: Point2<i32>);
+
     fn handle(&self) -> RawWindowHandle;
+
+    /// Connection to the display server the window belongs to, some graphics APIs need it
+    /// with the window handle to present to the window
+    fn display_handle(&self) -> RawDisplayHandle;
+
     fn width(&self) -> u32;
     fn height(&self) -> u32;
     fn position(&self) -> Point2<i32>;
+
+    /// Mouse position relative to the client area, outside of it when the mouse is not over it
+    fn cursor_position(&self) -> Point2<i32>;
+    fn is_minimized(&self) -> bool;
+    fn is_focused(&self) -> bool;
+
+    /// DPI of the monitor the window is on divided by 96, the reference DPI
+    fn dpi_scale(&self) -> f32;
 }
 impl_downcast!(Window);
 
 pub enum Message {
-    WindowClosed(Weak<dyn Window>),
+    /// The user asked to close the window, nothing happens until the application calls
+    /// [`Window::close`] so it can be vetoed (e.g. to save changes first)
+    WindowCloseRequested(Weak<dyn Window>),
     WindowResized(Weak<dyn Window>, u32, u32),
 
+    /// Window moved to a monitor with a different DPI, or the monitor DPI changed.
+    /// The window size is updated by the platform, a resize message follows if it changed
+    WindowDpiChanged(Weak<dyn Window>, f32),
+
+    /// Window received the keyboard focus
+    WindowFocusGained(Weak<dyn Window>),
+    WindowFocusLost(Weak<dyn Window>),
+
+    /// The mouse moved over the client area of the window
+    MouseEntered(Weak<dyn Window>),
+    MouseLeft(Weak<dyn Window>),
+
     MouseButtonDown(Weak<dyn Window>, MouseButton, Point2<i32>),
     MouseButtonUp(Weak<dyn Window>, MouseButton, Point2<i32>),
     MouseButtonDoubleClick(Weak<dyn Window>, MouseButton, Point2<i32>),
     MouseWheel(Weak<dyn Window>, f32, Point2<i32>),
 
-    KeyDown(Weak<dyn Window>, KeyCode, u32, bool),
-    KeyUp(Weak<dyn Window>, KeyCode, u32, bool),
+    /// Horizontal wheel or trackpad scrolling, positive when scrolling to the right
+    MouseWheelH(Weak<dyn Window>, f32, Point2<i32>),
+
+    /// Key translated with the keyboard layout, then the physical key from the hardware
+    /// scancode, named after the key at its position on a US QWERTY keyboard so bindings
+    /// don't move with the layout. Followed by the character code and the repeat state
+    KeyDown(Weak<dyn Window>, KeyCode, KeyCode, u32, bool),
+    KeyUp(Weak<dyn Window>, KeyCode, KeyCode, u32, bool),
+
+    /// Character typed by the user, control characters are only reported as key events
+    TextInput(Weak<dyn Window>, char),
+
+    /// Text being composed with an input method, empty when the composition ends.
+    /// The composed text is sent as `TextInput` messages once committed
+    TextComposition(Weak<dyn Window>, String),
+
+    /// Relative mouse motion before pointer acceleration, reported even when the cursor is
+    /// stuck at a screen edge
+    MouseMotionRaw(f32, f32),
+
+    /// Monitors were connected, disconnected or changed resolution, [`Platform::monitor`] returns
+    /// the updated ones
+    MonitorsChanged,
+
+    /// The user switched between the light and dark modes, see [`Platform::system_theme`]
+    SystemThemeChanged(SystemTheme),
+
+    /// Files dragged from the system file explorer and dropped on the window, at a screen position
+    FilesDropped(Weak<dyn Window>, Vec<PathBuf>, Point2<i32>),
+
+    /// A dialog opened with [`Platform::open_file_dialog`] or [`Platform::save_file_dialog`]
+    /// was closed, with the chosen paths or none if it was cancelled
+    FileDialogClosed(FileDialogId, Vec<PathBuf>),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 #[repr(u32)]
 pub enum KeyCode {
     None,
@@ -151,7 +300,73 @@ pub enum KeyCode {
     F24,
 }
 
-#[derive(Copy, Clone)]
+/// Color scheme preferred by the user for applications
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SystemTheme {
+    Light,
+    Dark,
+}
+
+/// Where the power of the device comes from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PowerSource {
+    Unknown,
+
+    /// Plugged in, the batteries may be charging
+    Ac,
+    Battery,
+}
+
+/// Power state of the device, see [`Platform::power_status`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PowerStatus {
+    pub source: PowerSource,
+
+    /// Remaining charge of the batteries from 0 to 100, `None` without battery or if unknown
+    pub battery_percentage: Option<u8>,
+
+    /// The user or the system asked applications to save power
+    pub power_saver: bool,
+}
+
+impl Default for PowerStatus {
+    fn default() -> Self {
+        Self {
+            source: PowerSource::Unknown,
+            battery_percentage: None,
+            power_saver: false,
+        }
+    }
+}
+
+/// Files shown by a file dialog, e.g. "Images" with `["png", "jpg"]`
+#[derive(Copy, Clone, Debug)]
+pub struct FileFilter<'a> {
+    pub name: &'a str,
+
+    /// Extensions without the leading dot
+    pub extensions: &'a [&'a str],
+}
+
+/// Identify the dialog of a [`Message::FileDialogClosed`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FileDialogId(u64);
+
+impl FileDialogId {
+    /// Unique id for a new dialog, for the platform implementations
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for FileDialogId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Monitor {
     pub bounds: RectI32,
     pub work_bounds: RectI32,
@@ -174,6 +389,13 @@ impl std::error::Error for Error {}
 /// Trait describing a platform, supporting window creation, event handling etc
 pub trait Platform: Send + Sync {
     fn poll_event(&self) -> Option<Message>;
+
+    /// Block until events are received or `timeout` elapsed, then return the next message like
+    /// [`Platform::poll_event`]. `None` waits without limit, the events may produce no message
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message>;
+
+    /// Make the current [`Platform::wait_event`] call return, can be called from any thread
+    fn wake(&self);
     fn create_window(
         &self,
         name: &str,
@@ -185,9 +407,53 @@ pub trait Platform: Send + Sync {
     ) -> Result<Arc<dyn Window>, Error>;
 
     fn create_system_cursor(&self, cursor: SystemCursor) -> Box<dyn Cursor>;
+
+    /// Create a cursor from `width * height` 8-bit RGBA pixels, rows from top to bottom.
+    /// The hotspot is the pixel pointing at the mouse position
+    fn create_cursor_from_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error>;
+
     fn set_cursor(&self, cursor: Option<&dyn Cursor>);
+
+    /// Show or hide the cursor over the application windows, keeping the cursor set with
+    /// [`Platform::set_cursor`]
+    fn show_cursor(&self, show: bool);
+
+    /// Keep sending the mouse events to `window` while the mouse is outside of it, until
+    /// [`Platform::release_mouse`] is called
+    fn capture_mouse(&self, window: &dyn Window);
+    fn release_mouse(&self);
+
     fn mouse_position(&self) -> Point2<i32>;
 
+    /// Whether `key` is held down, without having to go through the key messages
+    fn is_key_down(&self, key: KeyCode) -> bool;
+    fn keyboard_modifiers(&self) -> Modifiers;
+
     fn monitor_count(&self) -> usize;
     fn monitor(&self, index: usize) -> Monitor;
+
+    /// Light when the platform has no dark mode or it can't be queried
+    fn system_theme(&self) -> SystemTheme;
+
+    /// Queried from the system on each call, meant to be polled every few seconds
+    fn power_status(&self) -> PowerStatus;
+
+    /// Text in the system clipboard, `None` if it is empty or doesn't contain text
+    fn clipboard_text(&self) -> Option<String>;
+    fn set_clipboard_text(&self, text: &str);
+
+    /// Show a dialog to pick existing files without blocking, the result is sent as a
+    /// [`Message::FileDialogClosed`] with the returned id. All files are shown when `filters`
+    /// is empty
+    fn open_file_dialog(&self, filters: &[FileFilter], multi_select: bool) -> FileDialogId;
+
+    /// Show a dialog to pick the path of a file to write, starting with `default_name`.
+    /// The result is sent like [`Platform::open_file_dialog`]
+    fn save_file_dialog(&self, filters: &[FileFilter], default_name: &str) -> FileDialogId;
 }
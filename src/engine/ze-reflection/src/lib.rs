@@ -189,7 +189,7 @@ pub enum TypeDataDescription {
 
 pub struct TypeDescription {
     name: String,
-    _size_in_bytes: usize,
+    size_in_bytes: usize,
     _alignment_in_bytes: usize,
     data: TypeDataDescription,
 }
@@ -203,7 +203,7 @@ impl TypeDescription {
     ) -> Self {
         Self {
             name,
-            _size_in_bytes: size_in_bytes,
+            size_in_bytes,
             _alignment_in_bytes: alignment_in_bytes,
             data,
         }
@@ -232,6 +232,10 @@ impl TypeDescription {
         &self.name
     }
 
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
     pub fn data(&self) -> &TypeDataDescription {
         &self.data
     }
@@ -1,7 +1,8 @@
 mod registry;
 pub mod render_pass;
 
-use registry::{ResourceData, ResourceHandle, ResourceRegistry};
+pub use registry::ResourceHandle;
+use registry::{ResourceData, ResourceRegistry};
 use render_pass::{
     RenderPass, RenderPassBuilder, RenderPassExecutor, RenderPassType, TypedRenderPassExecutor,
 };
@@ -11,6 +12,7 @@ use std::mem::MaybeUninit;
 use std::sync::Arc;
 use ze_core::color::Color4f32;
 use ze_gfx::backend::*;
+use ze_gfx::profiler::GpuProfiler;
 use ze_gfx::PixelFormat;
 
 #[derive(Clone)]
@@ -61,6 +63,17 @@ impl<'a> FrameGraph<'a> {
         handle
     }
 
+    /// Import a buffer owned outside of the graph, it is expected in the `Common` state and
+    /// returned to it at the end of the graph
+    pub fn import_external_buffer(&mut self, buffer: Arc<Buffer>, name: &str) -> ResourceHandle {
+        self.resource_registry.import_buffer(name, buffer)
+    }
+
+    pub fn texture_desc(&self, handle: ResourceHandle) -> &FrameGraphTextureDesc {
+        let handle = self.resource_registry.resolve_handle(handle);
+        &self.resource_registry.texture(handle).desc
+    }
+
     pub fn add_pass<T, S, E>(&mut self, name: &str, ty: RenderPassType, setup: S, exec: E)
     where
         T: 'static,
@@ -104,6 +117,9 @@ pub struct CompiledFrameGraph<'a> {
     handle_to_compiled_texture: HashMap<ResourceHandle, usize>,
     rtvs: HashMap<ResourceHandle, RenderTargetView>,
     dsvs: HashMap<ResourceHandle, DepthStencilView>,
+    srvs: HashMap<ResourceHandle, ShaderResourceView>,
+    uavs: HashMap<ResourceHandle, UnorderedAccessView>,
+    gpu_profiler: Option<&'a GpuProfiler>,
 }
 
 impl<'a> CompiledFrameGraph<'a> {
@@ -127,15 +143,26 @@ impl<'a> CompiledFrameGraph<'a> {
             handle_to_compiled_texture,
             rtvs: Default::default(),
             dsvs: Default::default(),
+            srvs: Default::default(),
+            uavs: Default::default(),
+            gpu_profiler: None,
         }
     }
 
+    /// Measure the GPU time of the graph and of each pass with the given profiler
+    pub fn set_gpu_profiler(&mut self, gpu_profiler: &'a GpuProfiler) {
+        self.gpu_profiler = Some(gpu_profiler);
+    }
+
     pub fn execute(&mut self, cmd_list: &mut CommandList) {
         self.device.cmd_debug_begin_event(
             cmd_list,
             "Render Graph",
             Color4f32::new(0.75, 0.3, 0.15, 1.0),
         );
+        let graph_scope = self
+            .gpu_profiler
+            .map(|profiler| (profiler, profiler.begin_scope(cmd_list, "Render Graph")));
 
         let mut passes = mem::take(&mut self.passes);
         for pass in &mut passes {
@@ -144,98 +171,125 @@ impl<'a> CompiledFrameGraph<'a> {
                 &pass.name,
                 Color4f32::new(0.3, 0.75, 0.15, 1.0),
             );
+            let pass_scope = self
+                .gpu_profiler
+                .map(|profiler| (profiler, profiler.begin_scope(cmd_list, &pass.name)));
 
             self.prepare_pass_resources(pass);
 
-            // Apply invalidate barriers
-            if !pass.invalidate_barriers.is_empty() {
-                let mut barriers = Vec::with_capacity(pass.invalidate_barriers.len());
-                for invalidate in &pass.invalidate_barriers {
-                    barriers.push(ResourceBarrier::Transition(ResourceTransitionBarrier {
-                        resource: ResourceTransitionBarrierResource::Texture(
-                            self.resource_registry
-                                .texture(invalidate.resource)
-                                .resource
-                                .as_ref()
-                                .unwrap(),
-                        ),
-                        source_state: invalidate.src_state,
-                        dest_state: invalidate.dst_state,
-                    }));
-                }
+            self.apply_barriers(&pass.invalidate_barriers, cmd_list);
 
-                self.device.cmd_resource_barrier(cmd_list, &barriers);
+            if pass.ty == RenderPassType::Compute {
+                pass.executor.execute(self, cmd_list);
+            } else {
+                self.execute_render_pass(pass, cmd_list);
             }
 
-            let rtvs = pass
-                .render_targets
-                .iter()
-                .map(|rt| RenderPassRenderTarget {
-                    render_target_view: &self.rtvs[&rt.texture],
-                    load_mode: rt.load_mode,
-                    store_mode: rt.store_mode,
-                    clear_value: rt.clear_value,
-                })
-                .collect::<Vec<_>>();
-
-            let dsv = pass
-                .depth_stencil
-                .as_ref()
-                .map(|rt| RenderPassDepthStencil {
-                    depth_stencil_view: &self.dsvs[&rt.texture],
-                    load_mode: rt.load_mode,
-                    store_mode: rt.store_mode,
-                    clear_value: rt.clear_value,
-                });
-
-            let render_pass_desc = RenderPassDesc {
-                render_targets: &rtvs,
-                depth_stencil: dsv,
-            };
-
-            self.device
-                .cmd_begin_render_pass(cmd_list, &render_pass_desc);
-            pass.executor.execute(self, cmd_list);
-            self.device.cmd_end_render_pass(cmd_list);
-
-            // Apply flush barriers
-            if !pass.flush_barriers.is_empty() {
-                let mut barriers = Vec::with_capacity(pass.flush_barriers.len());
-                for flush in &pass.flush_barriers {
-                    barriers.push(ResourceBarrier::Transition(ResourceTransitionBarrier {
-                        resource: ResourceTransitionBarrierResource::Texture(
-                            self.resource_registry
-                                .texture(flush.resource)
-                                .resource
-                                .as_ref()
-                                .unwrap(),
-                        ),
-                        source_state: flush.src_state,
-                        dest_state: flush.dst_state,
-                    }));
-                }
-
-                self.device.cmd_resource_barrier(cmd_list, &barriers);
+            self.apply_barriers(&pass.flush_barriers, cmd_list);
+            if let Some((profiler, scope)) = pass_scope {
+                profiler.end_scope(cmd_list, scope);
             }
-
             self.device.cmd_debug_end_event(cmd_list);
         }
 
+        if let Some((profiler, scope)) = graph_scope {
+            profiler.end_scope(cmd_list, scope);
+        }
         self.device.cmd_debug_end_event(cmd_list);
         self.passes = passes;
     }
 
+    fn execute_render_pass(&self, pass: &mut CompiledPass<'a>, cmd_list: &mut CommandList) {
+        let rtvs = pass
+            .render_targets
+            .iter()
+            .map(|rt| RenderPassRenderTarget {
+                render_target_view: &self.rtvs[&rt.texture],
+                load_mode: rt.load_mode,
+                store_mode: rt.store_mode,
+                clear_value: rt.clear_value,
+            })
+            .collect::<Vec<_>>();
+
+        let dsv = pass
+            .depth_stencil
+            .as_ref()
+            .map(|rt| RenderPassDepthStencil {
+                depth_stencil_view: &self.dsvs[&rt.texture],
+                load_mode: rt.load_mode,
+                store_mode: rt.store_mode,
+                clear_value: rt.clear_value,
+            });
+
+        let render_pass_desc = RenderPassDesc {
+            render_targets: &rtvs,
+            depth_stencil: dsv,
+        };
+
+        self.device
+            .cmd_begin_render_pass(cmd_list, &render_pass_desc);
+        pass.executor.execute(self, cmd_list);
+        self.device.cmd_end_render_pass(cmd_list);
+    }
+
     pub fn texture(&mut self, handle: ResourceHandle) -> &Arc<Texture> {
         let texture = self.resource_registry.texture(handle);
         texture.resource.as_ref().unwrap()
     }
 
+    /// Shader resource view of a texture read by the executing pass
+    pub fn texture_srv(&self, handle: ResourceHandle) -> &ShaderResourceView {
+        &self.srvs[&self.resource_registry.resolve_handle(handle)]
+    }
+
+    /// Unordered access view of a texture written by the executing compute pass
+    pub fn texture_uav(&self, handle: ResourceHandle) -> &UnorderedAccessView {
+        &self.uavs[&self.resource_registry.resolve_handle(handle)]
+    }
+
+    fn apply_barriers(&self, barriers: &[Barrier], cmd_list: &mut CommandList) {
+        if !barriers.is_empty() {
+            let barriers = barriers
+                .iter()
+                .map(|barrier| self.resource_barrier(barrier))
+                .collect::<Vec<_>>();
+            self.device.cmd_resource_barrier(cmd_list, &barriers);
+        }
+    }
+
+    fn resource_barrier(&self, barrier: &Barrier) -> ResourceBarrier<'_> {
+        let resource = if self.resource_registry.is_buffer(barrier.resource) {
+            ResourceTransitionBarrierResource::Buffer(
+                self.resource_registry.buffer(barrier.resource),
+            )
+        } else {
+            ResourceTransitionBarrierResource::Texture(
+                self.resource_registry
+                    .texture(barrier.resource)
+                    .resource
+                    .as_ref()
+                    .unwrap(),
+            )
+        };
+
+        if barrier.src_state == barrier.dst_state {
+            ResourceBarrier::UnorderedAccess(resource)
+        } else {
+            ResourceBarrier::Transition(ResourceTransitionBarrier {
+                resource,
+                source_state: barrier.src_state,
+                dest_state: barrier.dst_state,
+            })
+        }
+    }
+
     fn prepare_pass_resources(&mut self, pass: &mut CompiledPass<'a>) {
         for handle in pass
             .render_targets
             .iter()
             .map(|rt| &rt.texture)
             .chain(pass.depth_stencil.iter().map(|rt| &rt.texture))
+            .chain(pass.storage.iter())
         {
             let compiled_texture = &self.textures[self.handle_to_compiled_texture[handle]];
             let resource = self.resource_registry.resource(*handle);
@@ -248,6 +302,7 @@ impl<'a> CompiledFrameGraph<'a> {
                                 width: compiled_texture.width,
                                 height: compiled_texture.height,
                                 depth: 1,
+                                array_size: 1,
                                 mip_levels: 1,
                                 format: compiled_texture.format,
                                 sample_desc: Default::default(),
@@ -301,6 +356,43 @@ impl<'a> CompiledFrameGraph<'a> {
                 self.dsvs.insert(ds.texture, dsv);
             }
         }
+
+        for &read in &pass.sampled {
+            #[allow(clippy::map_entry)]
+            if !self.srvs.contains_key(&read) {
+                let texture = self.texture(read).clone();
+                let format = texture.desc.format;
+                let srv = self
+                    .device
+                    .create_shader_resource_view(&ShaderResourceViewDesc::Texture2D(Texture2DSRV {
+                        texture,
+                        format,
+                        min_mip_level: 0,
+                        mip_levels: 1,
+                    }))
+                    .unwrap();
+                self.srvs.insert(read, srv);
+            }
+        }
+
+        for &write in &pass.storage {
+            #[allow(clippy::map_entry)]
+            if !self.uavs.contains_key(&write) {
+                let texture = self.texture(write).clone();
+                let format = texture.desc.format;
+                let uav = self
+                    .device
+                    .create_unordered_access_view(&UnorderedAccessViewDesc::Texture2D(
+                        Texture2DUAV {
+                            texture,
+                            format,
+                            mip_level: 0,
+                        },
+                    ))
+                    .unwrap();
+                self.uavs.insert(write, uav);
+            }
+        }
     }
 }
 
@@ -312,6 +404,8 @@ struct CompiledPassRenderTarget {
     pub clear_value: ClearValue,
 }
 
+/// Transition of a resource, a barrier between identical states waits for the unordered
+/// accesses to the resource instead
 struct Barrier {
     resource: ResourceHandle,
     src_state: ResourceState,
@@ -320,11 +414,19 @@ struct Barrier {
 
 struct CompiledPass<'a> {
     name: String,
+    ty: RenderPassType,
     invalidate_barriers: Vec<Barrier>,
     flush_barriers: Vec<Barrier>,
     render_targets: Vec<CompiledPassRenderTarget>,
     depth_stencil: Option<CompiledPassRenderTarget>,
+    reads: Vec<ResourceHandle>,
     writes: Vec<ResourceHandle>,
+
+    /// Resolved textures read and not written by the pass, they are given an SRV
+    sampled: Vec<ResourceHandle>,
+
+    /// Resolved textures written by a compute pass, they are given an UAV
+    storage: Vec<ResourceHandle>,
     executor: Box<dyn RenderPassExecutor<'a>>,
 }
 
@@ -335,7 +437,6 @@ struct CompiledTexture {
     format: PixelFormat,
     usage: TextureUsageFlags,
     handle: ResourceHandle,
-    alias_with: Option<usize>,
 }
 
 struct CompilationData<'a> {
@@ -343,7 +444,6 @@ struct CompilationData<'a> {
     ordered_pass_list: Vec<usize>,
     handle_to_compiled_texture_idx: HashMap<ResourceHandle, usize>,
     textures: Vec<CompiledTexture>,
-    free_texture_pool: Vec<usize>,
     compiled_passes: Vec<CompiledPass<'a>>,
 }
 
@@ -354,7 +454,6 @@ impl<'a> FrameGraph<'a> {
             ordered_pass_list: Vec::with_capacity(self.passes.len()),
             handle_to_compiled_texture_idx: Default::default(),
             textures: Default::default(),
-            free_texture_pool: vec![],
             compiled_passes: vec![],
         };
 
@@ -373,9 +472,13 @@ impl<'a> FrameGraph<'a> {
             let mut pass_queue = compilation_data.ordered_pass_list.clone();
             while let Some(pass) = pass_queue.pop() {
                 let pass = &self.passes[pass];
-                for &input in &pass.reads {
+                for &input in pass.reads.iter().chain(pass.depth_stencil_input.iter()) {
                     for (i, pass) in self.passes.iter().enumerate() {
-                        if pass.writes.iter().any(|&output| output == input)
+                        if pass
+                            .writes
+                            .iter()
+                            .chain(pass.depth_stencil_output.iter())
+                            .any(|&output| output == input)
                             && !compilation_data.ordered_pass_list.contains(&i)
                         {
                             compilation_data.ordered_pass_list.push(i);
@@ -386,8 +489,9 @@ impl<'a> FrameGraph<'a> {
             }
         }
 
-        compilation_data.ordered_pass_list.dedup();
-        compilation_data.ordered_pass_list.reverse();
+        // Passes can only use resources of the passes added before them, the order they were
+        // added in is a valid execution order
+        compilation_data.ordered_pass_list.sort_unstable();
 
         // Ordered pass list is now in the correct order
         self.build_physical_textures(&mut compilation_data);
@@ -404,26 +508,39 @@ impl<'a> FrameGraph<'a> {
 
     fn build_physical_textures(&mut self, compilation_data: &mut CompilationData) {
         let ordered_pass_list = compilation_data.ordered_pass_list.clone();
-        for pass_idx in ordered_pass_list {
-            // Set textures last use
+
+        // Set textures last use, proxies are resolved so a texture lives as long as its last proxy
+        // is used
+        for &pass_idx in &ordered_pass_list {
             let pass = &self.passes[pass_idx];
             for &texture in pass.iter_resources() {
-                if !self.resource_registry.is_texture(texture) {
-                    continue;
+                let texture = self.resource_registry.resolve_handle(texture);
+                if self.resource_registry.is_texture(texture) {
+                    self.resource_registry.resource_mut(texture).last_pass_use = Some(pass_idx);
                 }
-
-                self.resource_registry.resource_mut(texture).last_pass_use = Some(pass_idx);
             }
+        }
+
+        for pass_idx in ordered_pass_list {
+            let pass = &self.passes[pass_idx];
 
             // Collect texture usages
             for &read in &pass.reads {
-                let texture = self.add_physical_texture(compilation_data, read);
-                texture.usage |= TextureUsageFlagBits::Sampled;
+                if self.is_texture(read) {
+                    let texture = self.add_physical_texture(compilation_data, read);
+                    texture.usage |= TextureUsageFlagBits::Sampled;
+                }
             }
 
             for &write in &pass.writes {
-                let texture = self.add_physical_texture(compilation_data, write);
-                texture.usage |= TextureUsageFlagBits::RenderTarget;
+                if self.is_texture(write) {
+                    let texture = self.add_physical_texture(compilation_data, write);
+                    texture.usage |= if pass.ty == RenderPassType::Compute {
+                        TextureUsageFlagBits::UnorderedAccess
+                    } else {
+                        TextureUsageFlagBits::RenderTarget
+                    };
+                }
             }
 
             if let Some(depth_stencil_input) = pass.depth_stencil_input {
@@ -433,19 +550,6 @@ impl<'a> FrameGraph<'a> {
                 let texture = self.add_physical_texture(compilation_data, depth_stencil_output);
                 texture.usage |= TextureUsageFlagBits::DepthStencil;
             }
-
-            // If at this pass, any textures are not anymore used, we can push them to the free texture pool
-            for &texture in pass.iter_resources() {
-                let texture = self.resource_registry.resolve_handle(texture);
-
-                if self.resource_registry.is_texture(texture)
-                    && self.resource_registry.resource(texture).last_pass_use == Some(pass_idx)
-                {
-                    compilation_data
-                        .free_texture_pool
-                        .push(compilation_data.handle_to_compiled_texture_idx[&texture]);
-                }
-            }
         }
     }
 
@@ -463,7 +567,8 @@ impl<'a> FrameGraph<'a> {
             let mut depth_stencil = None;
             for (i, &output) in pass.writes.iter().enumerate() {
                 let output = self.resource_registry.resolve_handle(output);
-                if self.resource_registry.is_texture(output) {
+                if self.resource_registry.is_texture(output) && pass.ty == RenderPassType::Graphics
+                {
                     let clear_value = &pass.writes_clear_color[i];
                     let load_mode = {
                         if pass.reads.contains(&output) {
@@ -484,7 +589,10 @@ impl<'a> FrameGraph<'a> {
             }
 
             if let Some(depth_stencil_input) = pass.depth_stencil_input {
-                let clear_value = pass.depth_stencil_clear_value.unwrap();
+                // Not cleared, the clear value is only required by the render pass description
+                let clear_value = pass
+                    .depth_stencil_clear_value
+                    .unwrap_or(ClearValue::DepthStencil((1.0, 0)));
                 depth_stencil = Some(CompiledPassRenderTarget {
                     texture: depth_stencil_input,
                     load_mode: RenderPassTextureLoadMode::Preserve,
@@ -501,13 +609,42 @@ impl<'a> FrameGraph<'a> {
                 });
             }
 
+            let written = pass
+                .writes
+                .iter()
+                .map(|&write| self.resource_registry.resolve_handle(write))
+                .collect::<Vec<_>>();
+            let mut sampled = vec![];
+            for &read in &pass.reads {
+                let read = self.resource_registry.resolve_handle(read);
+                if self.resource_registry.is_texture(read)
+                    && !written.contains(&read)
+                    && !sampled.contains(&read)
+                {
+                    sampled.push(read);
+                }
+            }
+
+            let storage = if pass.ty == RenderPassType::Compute {
+                written
+                    .into_iter()
+                    .filter(|&write| self.resource_registry.is_texture(write))
+                    .collect()
+            } else {
+                vec![]
+            };
+
             compilation_data.compiled_passes.push(CompiledPass {
                 name: pass.name,
+                ty: pass.ty,
                 invalidate_barriers: vec![],
                 flush_barriers: vec![],
                 render_targets,
                 depth_stencil,
+                reads: pass.reads,
                 writes: pass.writes,
+                sampled,
+                storage,
                 executor: pass.executor,
             });
         }
@@ -520,6 +657,8 @@ impl<'a> FrameGraph<'a> {
         // Special cases:
         // - Backbuffer initial state is considered Present
         // - Backbuffer final state will be Present
+        // - Imported buffers final state will be Common
+        // - Consecutive unordered accesses wait for each other
 
         let mut resource_states = Vec::with_capacity(self.resource_registry.resources().len());
         for i in 0..self.resource_registry.resources().len() {
@@ -531,17 +670,58 @@ impl<'a> FrameGraph<'a> {
         }
 
         for compiled_pass in &mut compilation_data.compiled_passes {
-            for &color_output in &compiled_pass.writes {
-                let color_output = self.resource_registry.resolve_handle(color_output);
-                let src_state = resource_states[color_output.0];
-                if src_state != ResourceState::RenderTargetWrite {
+            let writes = compiled_pass
+                .writes
+                .iter()
+                .map(|&output| self.resource_registry.resolve_handle(output))
+                .collect::<Vec<_>>();
+
+            for &input in &compiled_pass.reads {
+                let input = self.resource_registry.resolve_handle(input);
+                let src_state = resource_states[input.0];
+                if !writes.contains(&input) && src_state != ResourceState::ShaderRead {
                     compiled_pass.invalidate_barriers.push(Barrier {
-                        resource: color_output,
+                        resource: input,
                         src_state,
-                        dst_state: ResourceState::RenderTargetWrite,
+                        dst_state: ResourceState::ShaderRead,
                     });
 
-                    resource_states[color_output.0] = ResourceState::RenderTargetWrite;
+                    resource_states[input.0] = ResourceState::ShaderRead;
+                }
+            }
+
+            for &output in &writes {
+                let dst_state = if self.resource_registry.is_texture(output)
+                    && compiled_pass.ty == RenderPassType::Graphics
+                {
+                    ResourceState::RenderTargetWrite
+                } else {
+                    ResourceState::UnorderedAccessReadWrite
+                };
+
+                let src_state = resource_states[output.0];
+                if src_state != dst_state || dst_state == ResourceState::UnorderedAccessReadWrite {
+                    compiled_pass.invalidate_barriers.push(Barrier {
+                        resource: output,
+                        src_state,
+                        dst_state,
+                    });
+
+                    resource_states[output.0] = dst_state;
+                }
+            }
+
+            if let Some(depth_stencil) = &compiled_pass.depth_stencil {
+                let depth_stencil = self.resource_registry.resolve_handle(depth_stencil.texture);
+                let src_state = resource_states[depth_stencil.0];
+                if src_state != ResourceState::DepthWrite {
+                    compiled_pass.invalidate_barriers.push(Barrier {
+                        resource: depth_stencil,
+                        src_state,
+                        dst_state: ResourceState::DepthWrite,
+                    });
+
+                    resource_states[depth_stencil.0] = ResourceState::DepthWrite;
                 }
             }
         }
@@ -554,6 +734,24 @@ impl<'a> FrameGraph<'a> {
                 src_state: resource_states[compilation_data.backbuffer.0],
                 dst_state: ResourceState::Present,
             });
+
+        for (i, &state) in resource_states.iter().enumerate() {
+            let handle = ResourceHandle(i);
+            if self.resource_registry.is_buffer(handle) && state != ResourceState::Common {
+                compilation_data.compiled_passes[last_idx]
+                    .flush_barriers
+                    .push(Barrier {
+                        resource: handle,
+                        src_state: state,
+                        dst_state: ResourceState::Common,
+                    });
+            }
+        }
+    }
+
+    fn is_texture(&self, handle: ResourceHandle) -> bool {
+        let handle = self.resource_registry.resolve_handle(handle);
+        self.resource_registry.is_texture(handle)
     }
 
     fn add_physical_texture<'b>(
@@ -566,35 +764,6 @@ impl<'a> FrameGraph<'a> {
         } else {
             let texture = self.resource_registry.texture(handle);
 
-            // Fetch the free pool to find a texture that can be reused
-            let reusable_texture = {
-                if !self.resource_registry.is_external(handle) {
-                    let reusable_texture_idx =
-                        compilation_data
-                            .free_texture_pool
-                            .iter()
-                            .position(|&free_texture_idx| {
-                                let free_texture = &compilation_data.textures[free_texture_idx];
-                                let can_contain_texture = free_texture
-                                    .format
-                                    .texture_size_in_bytes(free_texture.width, free_texture.height)
-                                    >= texture.desc.format.texture_size_in_bytes(
-                                        texture.desc.width,
-                                        texture.desc.height,
-                                    );
-
-                                can_contain_texture
-                                    && !self.resource_registry.is_external(free_texture.handle)
-                            });
-
-                    reusable_texture_idx.map(|idx| compilation_data.free_texture_pool.remove(idx))
-                } else {
-                    None
-                }
-            };
-
-            assert!(reusable_texture.is_none(), "aliasing not implemented yet");
-
             let idx = compilation_data
                 .handle_to_compiled_texture_idx
                 .entry(handle)
@@ -605,7 +774,6 @@ impl<'a> FrameGraph<'a> {
                         format: texture.desc.format,
                         usage: TextureUsageFlags::empty(),
                         handle,
-                        alias_with: reusable_texture,
                     });
                     compilation_data.textures.len() - 1
                 });
@@ -614,3 +782,348 @@ impl<'a> FrameGraph<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::render_pass::RenderPassType;
+    use crate::{FrameGraph, FrameGraphTextureDesc};
+    use std::cell::RefCell;
+    use std::sync::Arc;
+    use ze_gfx::backend::*;
+    use ze_gfx::null::NullDevice;
+    use ze_gfx::profiler::GpuProfiler;
+    use ze_gfx::PixelFormat;
+    use ze_null_platform::NullPlatform;
+    use ze_platform::{Message, Platform, WindowFlags};
+
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 32;
+
+    fn backbuffer(device: &NullDevice) -> Arc<Texture> {
+        let texture = device
+            .create_texture(
+                &TextureDesc {
+                    width: WIDTH,
+                    height: HEIGHT,
+                    depth: 1,
+                    array_size: 1,
+                    mip_levels: 1,
+                    format: PixelFormat::R8G8B8A8Unorm,
+                    sample_desc: Default::default(),
+                    usage_flags: TextureUsageFlagBits::RenderTarget.into(),
+                    memory_desc: MemoryDesc {
+                        memory_location: MemoryLocation::GpuOnly,
+                        memory_flags: Default::default(),
+                    },
+                },
+                None,
+                "Backbuffer",
+            )
+            .unwrap();
+        Arc::new(texture)
+    }
+
+    fn texture_desc() -> FrameGraphTextureDesc {
+        FrameGraphTextureDesc {
+            format: PixelFormat::R8G8B8A8Unorm,
+            width: WIDTH,
+            height: HEIGHT,
+        }
+    }
+
+    #[test]
+    fn execute_passes_needed_by_backbuffer() {
+        let device = Arc::new(NullDevice::new());
+        let backbuffer = backbuffer(&device);
+        let executed = RefCell::new(vec![]);
+
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+
+        let mut scene = None;
+        graph.add_pass(
+            "Scene",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.create_texture("Scene", texture_desc());
+                scene = Some(builder.write(texture));
+            },
+            |_, _, _| executed.borrow_mut().push("Scene"),
+        );
+
+        graph.add_pass(
+            "Unused",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.create_texture("Unused", texture_desc());
+                let _ = builder.write(texture);
+            },
+            |_, _, _| executed.borrow_mut().push("Unused"),
+        );
+
+        graph.add_pass(
+            "Composite",
+            RenderPassType::Graphics,
+            |builder| {
+                let _ = builder.read(scene.unwrap());
+                let _ = builder.write(backbuffer);
+            },
+            |_, _, _| executed.borrow_mut().push("Composite"),
+        );
+
+        let mut graph = graph.compile(backbuffer);
+        let mut cmd_list = device.create_command_list(QueueType::Graphics).unwrap();
+        graph.execute(&mut cmd_list);
+
+        assert_eq!(*executed.borrow(), vec!["Scene", "Composite"]);
+    }
+
+    #[test]
+    fn depth_stencil_is_kept_between_passes() {
+        let device = Arc::new(NullDevice::new());
+        let backbuffer = backbuffer(&device);
+        let executed = RefCell::new(vec![]);
+
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+
+        let mut depth = None;
+        graph.add_pass(
+            "Depth Prepass",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.create_texture(
+                    "Depth",
+                    FrameGraphTextureDesc {
+                        format: PixelFormat::D24UnormS8Uint,
+                        width: WIDTH,
+                        height: HEIGHT,
+                    },
+                );
+                builder.set_depth_stencil_output(texture, ClearValue::DepthStencil((1.0, 0)));
+                depth = Some(texture);
+            },
+            |_, _, _| executed.borrow_mut().push("Depth Prepass"),
+        );
+
+        graph.add_pass(
+            "Opaque",
+            RenderPassType::Graphics,
+            |builder| {
+                builder.set_depth_stencil_input(depth.unwrap());
+                let _ = builder.write(backbuffer);
+            },
+            |_, _, _| executed.borrow_mut().push("Opaque"),
+        );
+
+        let mut graph = graph.compile(backbuffer);
+        let mut cmd_list = device.create_command_list(QueueType::Graphics).unwrap();
+        graph.execute(&mut cmd_list);
+
+        assert_eq!(*executed.borrow(), vec!["Depth Prepass", "Opaque"]);
+    }
+
+    #[test]
+    fn texture_last_use_follows_proxies() {
+        let device = Arc::new(NullDevice::new());
+        let backbuffer = backbuffer(&device);
+
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+
+        let mut scene = None;
+        graph.add_pass(
+            "Scene",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.create_texture("Scene", texture_desc());
+                scene = Some(builder.write(texture));
+            },
+            |_, _, _| {},
+        );
+
+        // Writing a texture that was read gives a proxy of it
+        let mut blended = None;
+        graph.add_pass(
+            "Blend",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.read(scene.unwrap());
+                blended = Some(builder.write(texture));
+            },
+            |_, _, _| {},
+        );
+        assert_ne!(scene, blended);
+
+        graph.add_pass(
+            "Composite",
+            RenderPassType::Graphics,
+            |builder| {
+                let _ = builder.read(blended.unwrap());
+                let _ = builder.write(backbuffer);
+            },
+            |_, _, _| {},
+        );
+
+        let graph = graph.compile(backbuffer);
+        assert_eq!(
+            graph
+                .resource_registry
+                .resource(scene.unwrap())
+                .last_pass_use,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn compute_pass_writes_textures_through_uavs() {
+        let device = Arc::new(NullDevice::new());
+        let backbuffer = backbuffer(&device);
+        let executed = RefCell::new(vec![]);
+
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+
+        let mut bloom = None;
+        graph.add_pass(
+            "Bloom",
+            RenderPassType::Compute,
+            |builder| {
+                let texture = builder.create_texture("Bloom", texture_desc());
+                let texture = builder.write(texture);
+                bloom = Some(texture);
+                texture
+            },
+            |graph, &texture, _| {
+                match &graph.texture_uav(texture).desc {
+                    UnorderedAccessViewDesc::Texture2D(uav) => assert!(uav
+                        .texture
+                        .desc
+                        .usage_flags
+                        .contains(TextureUsageFlagBits::UnorderedAccess)),
+                    _ => panic!("Expected a texture UAV"),
+                }
+                executed.borrow_mut().push("Bloom");
+            },
+        );
+
+        graph.add_pass(
+            "Composite",
+            RenderPassType::Graphics,
+            |builder| {
+                let _ = builder.write(backbuffer);
+                builder.read(bloom.unwrap())
+            },
+            |graph, &bloom, _| {
+                let _ = graph.texture_srv(bloom);
+                executed.borrow_mut().push("Composite");
+            },
+        );
+
+        let mut graph = graph.compile(backbuffer);
+        let mut cmd_list = device.create_command_list(QueueType::Graphics).unwrap();
+        graph.execute(&mut cmd_list);
+
+        assert_eq!(*executed.borrow(), vec!["Bloom", "Composite"]);
+    }
+
+    #[test]
+    fn gpu_profiler_times_graph_and_passes() {
+        let device = Arc::new(NullDevice::new());
+        let backbuffer = backbuffer(&device);
+        let profiler = GpuProfiler::new(device.clone(), 16).unwrap();
+        profiler.begin_frame();
+
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+        graph.add_pass(
+            "Opaque",
+            RenderPassType::Graphics,
+            |builder| {
+                let _ = builder.write(backbuffer);
+            },
+            |_, _, _| {},
+        );
+
+        let mut graph = graph.compile(backbuffer);
+        graph.set_gpu_profiler(&profiler);
+        let mut cmd_list = device.create_command_list(QueueType::Graphics).unwrap();
+        graph.execute(&mut cmd_list);
+        profiler.resolve(&mut cmd_list);
+
+        // Timings are read back once the frame is reused
+        while profiler.timings().is_empty() {
+            profiler.begin_frame();
+        }
+
+        let scopes = profiler
+            .timings()
+            .into_iter()
+            .map(|timing| (timing.name, timing.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scopes,
+            vec![("Render Graph".to_string(), 0), ("Opaque".to_string(), 1)]
+        );
+    }
+
+    /// Graphs rendering to a window can run without a display server through the null platform
+    #[test]
+    fn render_to_null_window() {
+        let platform = NullPlatform::new();
+        let window = platform
+            .create_window("Test", WIDTH, HEIGHT, 0, 0, WindowFlags::empty())
+            .unwrap();
+
+        let device: Arc<dyn Device> = Arc::new(NullDevice::new());
+        let swapchain_desc = |width, height| SwapChainDesc {
+            width,
+            height,
+            format: PixelFormat::R8G8B8A8Unorm,
+            sample_desc: Default::default(),
+            usage_flags: TextureUsageFlagBits::RenderTarget.into(),
+            window_handle: window.handle(),
+            display_handle: window.display_handle(),
+        };
+        let swapchain = device
+            .create_swapchain(&swapchain_desc(window.width(), window.height()), None)
+            .unwrap();
+
+        // Resizes are reported like by any other platform, the swapchain is recreated to follow
+        window.set_size(WIDTH * 2, HEIGHT * 2);
+        let (width, height) = match platform.poll_event() {
+            Some(Message::WindowResized(_, width, height)) => (width, height),
+            _ => panic!("Expected a resize message"),
+        };
+        let swapchain = device
+            .create_swapchain(&swapchain_desc(width, height), Some(swapchain))
+            .unwrap();
+
+        let backbuffer = device
+            .swapchain_backbuffer(&swapchain, device.swapchain_backbuffer_index(&swapchain))
+            .unwrap();
+        assert_eq!(
+            (backbuffer.desc.width, backbuffer.desc.height),
+            (WIDTH * 2, HEIGHT * 2)
+        );
+
+        let executed = RefCell::new(vec![]);
+        let mut graph = FrameGraph::new(device.clone());
+        let backbuffer = graph.import_external_texture(backbuffer, "Backbuffer");
+        graph.add_pass(
+            "Clear",
+            RenderPassType::Graphics,
+            |builder| {
+                let _ = builder.write(backbuffer);
+            },
+            |_, _, _| executed.borrow_mut().push("Clear"),
+        );
+
+        let mut graph = graph.compile(backbuffer);
+        let mut cmd_list = device.create_command_list(QueueType::Graphics).unwrap();
+        graph.execute(&mut cmd_list);
+        device.present(&swapchain);
+
+        assert_eq!(*executed.borrow(), vec!["Clear"]);
+    }
+}
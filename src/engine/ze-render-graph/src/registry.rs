@@ -12,6 +12,7 @@ pub(crate) struct Texture {
 
 pub(crate) enum ResourceData {
     Texture(Texture),
+    Buffer(Arc<ze_gfx::backend::Buffer>),
     Proxy(ResourceHandle),
 }
 
@@ -45,6 +46,24 @@ impl ResourceRegistry {
         ResourceHandle(self.resources.len() - 1)
     }
 
+    pub fn import_buffer(
+        &mut self,
+        name: &str,
+        buffer: Arc<ze_gfx::backend::Buffer>,
+    ) -> ResourceHandle {
+        assert!(
+            !self.resources.iter().any(|res| res.name == name),
+            "Resource already exists"
+        );
+        self.resources.push(Resource {
+            name: name.to_string(),
+            data: ResourceData::Buffer(buffer),
+            external: true,
+            last_pass_use: None,
+        });
+        ResourceHandle(self.resources.len() - 1)
+    }
+
     pub fn create_proxy(&mut self, handle: ResourceHandle) -> ResourceHandle {
         self.resources.push(Resource {
             name: String::default(),
@@ -88,14 +107,23 @@ impl ResourceRegistry {
         }
     }
 
+    pub fn buffer(&self, handle: ResourceHandle) -> &Arc<ze_gfx::backend::Buffer> {
+        let resource = &self.resources[handle.0];
+        if let ResourceData::Buffer(buffer) = &resource.data {
+            buffer
+        } else {
+            panic!("Resource is not a buffer");
+        }
+    }
+
     pub fn is_texture(&self, handle: ResourceHandle) -> bool {
         let resource = &self.resources[handle.0];
         matches!(resource.data, ResourceData::Texture(_))
     }
 
-    pub fn is_external(&self, handle: ResourceHandle) -> bool {
+    pub fn is_buffer(&self, handle: ResourceHandle) -> bool {
         let resource = &self.resources[handle.0];
-        resource.external
+        matches!(resource.data, ResourceData::Buffer(_))
     }
 
     pub fn resources(&self) -> &[Resource] {
@@ -2,6 +2,8 @@
 use crate::{CompiledFrameGraph, FrameGraph, FrameGraphTextureDesc};
 use ze_gfx::backend::{ClearValue, CommandList};
 
+/// Compute passes don't begin a render pass, their writes are unordered accesses
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum RenderPassType {
     Graphics,
     Compute,
@@ -11,9 +13,12 @@ pub(crate) trait RenderPassExecutor<'graph>: 'graph {
     fn execute(&mut self, render_graph: &CompiledFrameGraph, command_list: &mut CommandList);
 }
 
+type RenderPassExecuteFn<'graph, T> =
+    Box<dyn FnMut(&CompiledFrameGraph, &T, &mut CommandList) + 'graph>;
+
 pub(crate) struct TypedRenderPassExecutor<'graph, T> {
     pub data: T,
-    pub func: Box<dyn FnMut(&CompiledFrameGraph, &T, &mut CommandList) + 'graph>,
+    pub func: RenderPassExecuteFn<'graph, T>,
 }
 
 impl<'graph, T: 'static> RenderPassExecutor<'graph> for TypedRenderPassExecutor<'graph, T> {
@@ -107,4 +112,9 @@ impl<'a, 'b> RenderPassBuilder<'a, 'b> {
         self.depth_stencil_output = Some(resource);
         self.depth_stencil_clear_value = Some(clear_color);
     }
+
+    /// Use a depth stencil written by a previous pass, its content is preserved
+    pub fn set_depth_stencil_input(&mut self, resource: ResourceHandle) {
+        self.depth_stencil_input = Some(resource);
+    }
 }
@@ -0,0 +1,167 @@
+use nalgebra_glm as glm;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_scene::transform::global_matrix;
+
+/// Number of jitter offsets before the sequence repeats
+const JITTER_SEQUENCE_LENGTH: u64 = 8;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Projection {
+    /// Vertical field of view in radians
+    Perspective { fov_y: f32 },
+
+    /// Height of the view volume in world units
+    Orthographic { height: f32 },
+}
+
+/// Camera looking along the entity forward (+Z) axis
+/// Uses a left-handed coordinate system with Y up and a zero to one depth range
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Camera {
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+    pub clear_color: Option<[f32; 4]>,
+}
+
+impl Camera {
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> glm::Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y } => {
+                glm::perspective_lh_zo(aspect_ratio, fov_y, self.near, self.far)
+            }
+            Projection::Orthographic { height } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect_ratio;
+                glm::ortho_lh_zo(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            projection: Projection::Perspective {
+                fov_y: 60.0_f32.to_radians(),
+            },
+            near: 0.1,
+            far: 1000.0,
+            clear_color: Some([0.0, 0.0, 0.0, 1.0]),
+        }
+    }
+}
+
+/// Camera a scene is rendered from
+pub struct View {
+    pub view: glm::Mat4,
+    pub projection: glm::Mat4,
+    pub position: glm::Vec3,
+
+    /// Sub-pixel offset of the projection, used by temporal anti-aliasing
+    /// Culling ignores it
+    pub jitter: glm::Vec2,
+
+    /// Clear the target before rendering, the scene is drawn over the target content otherwise
+    pub clear_color: Option<[f32; 4]>,
+}
+
+impl View {
+    /// View of a camera entity, `None` if the entity has no [`Camera`]
+    pub fn from_camera(world: &World, entity: Entity, aspect_ratio: f32) -> Option<Self> {
+        let camera = *world.query::<&Camera>().get(world, entity)?;
+        let transform = global_matrix(world, entity);
+        Some(Self {
+            view: glm::inverse(&transform),
+            projection: camera.projection_matrix(aspect_ratio),
+            position: glm::vec4_to_vec3(&transform.column(3).into()),
+            jitter: glm::Vec2::zeros(),
+            clear_color: camera.clear_color,
+        })
+    }
+
+    /// Projection offset by the jitter, for a target of `width` by `height` pixels
+    pub fn jittered_projection(&self, width: u32, height: u32) -> glm::Mat4 {
+        let offset = glm::vec3(
+            2.0 * self.jitter.x / width as f32,
+            2.0 * self.jitter.y / height as f32,
+            0.0,
+        );
+        glm::translation(&offset) * self.projection
+    }
+
+    /// View projection matrix without jitter
+    pub fn view_projection(&self) -> glm::Mat4 {
+        self.projection * self.view
+    }
+}
+
+/// Jitter of a frame in pixels, in the [-0.5, 0.5] range
+///
+/// Follows the Halton (2, 3) sequence so consecutive frames cover the pixel evenly
+pub fn jitter_offset(frame: u64) -> glm::Vec2 {
+    let index = frame % JITTER_SEQUENCE_LENGTH + 1;
+    glm::vec2(halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+}
+
+fn halton(mut index: u64, base: u64) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::{jitter_offset, Camera, View};
+    use nalgebra_glm as glm;
+    use ze_ecs::world::World;
+    use ze_scene::transform::Transform;
+
+    #[test]
+    fn jitter() {
+        assert_eq!(jitter_offset(0), glm::vec2(0.0, 1.0 / 3.0 - 0.5));
+        assert_eq!(jitter_offset(1), glm::vec2(-0.25, 2.0 / 3.0 - 0.5));
+        assert_eq!(jitter_offset(0), jitter_offset(8));
+        for frame in 0..8 {
+            let jitter = jitter_offset(frame);
+            assert!(jitter.x.abs() <= 0.5 && jitter.y.abs() <= 0.5);
+        }
+    }
+
+    #[test]
+    fn view_from_camera() {
+        let mut world = World::default();
+        let camera = world.spawn();
+        world.add(camera, Camera::default());
+        world.add(
+            camera,
+            Transform::from_translation(glm::vec3(1.0, 2.0, 3.0)),
+        );
+        let empty = world.spawn();
+        world.add(empty, Transform::default());
+
+        assert!(View::from_camera(&world, empty, 1.0).is_none());
+
+        let view = View::from_camera(&world, camera, 1.0).unwrap();
+        assert_eq!(view.position, glm::vec3(1.0, 2.0, 3.0));
+
+        // A point in front of the camera projects to the center of the screen
+        let point = view.view_projection() * glm::vec4(1.0, 2.0, 10.0, 1.0);
+        assert!((point.x / point.w).abs() < 1e-6 && (point.y / point.w).abs() < 1e-6);
+        assert!(point.z / point.w > 0.0 && point.z / point.w < 1.0);
+    }
+}
@@ -0,0 +1,164 @@
+use nalgebra_glm as glm;
+use std::sync::atomic::{AtomicBool, Ordering};
+use ze_jobsystem::prelude::*;
+
+/// Axis aligned bounding box
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct BoundingBox {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl BoundingBox {
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box containing all points, an empty box at the origin if there are none
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a glm::Vec3>) -> Self {
+        let mut points = points.into_iter();
+        let first = match points.next() {
+            Some(first) => *first,
+            None => return Self::default(),
+        };
+
+        points.fold(Self::new(first, first), |bounds, point| {
+            Self::new(bounds.min.inf(point), bounds.max.sup(point))
+        })
+    }
+
+    pub fn center(&self) -> glm::Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Half size of the box along each axis
+    pub fn extents(&self) -> glm::Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    /// Bounding box of this box transformed by `matrix`
+    pub fn transform(&self, matrix: &glm::Mat4) -> Self {
+        let center = glm::vec4_to_vec3(&(matrix * self.center().push(1.0)));
+        let extents = glm::abs(&glm::mat4_to_mat3(matrix)) * self.extents();
+        Self::new(center - extents, center + extents)
+    }
+}
+
+/// Planes of a view volume, pointing inside
+pub struct Frustum {
+    planes: [glm::Vec4; 6],
+}
+
+impl Frustum {
+    /// Extract the planes of a view projection matrix with a zero to one depth range
+    pub fn from_matrix(matrix: &glm::Mat4) -> Self {
+        let row = |index: usize| -> glm::Vec4 { matrix.row(index).transpose() };
+        let planes = [
+            row(3) + row(0),
+            row(3) - row(0),
+            row(3) + row(1),
+            row(3) - row(1),
+            row(2),
+            row(3) - row(2),
+        ];
+
+        Self {
+            planes: planes.map(|plane| plane / glm::vec4_to_vec3(&plane).norm()),
+        }
+    }
+
+    /// Returns false only if `bounds` is entirely outside of the frustum
+    pub fn intersects(&self, bounds: &BoundingBox) -> bool {
+        let center = bounds.center();
+        let extents = bounds.extents();
+        self.planes.iter().all(|plane| {
+            let normal = glm::vec4_to_vec3(plane);
+            let distance = normal.dot(&center) + plane.w;
+            let radius = glm::abs(&normal).dot(&extents);
+            distance + radius >= 0.0
+        })
+    }
+
+    /// Visibility of each bounding box, tested in parallel on the global jobsystem
+    pub fn cull(&self, bounds: &[BoundingBox]) -> Vec<bool> {
+        let visible: Vec<AtomicBool> = bounds.iter().map(|_| AtomicBool::new(false)).collect();
+        bounds
+            .par_iter()
+            .zip(&visible)
+            .for_each(|(bounds, visible)| {
+                visible.store(self.intersects(bounds), Ordering::Relaxed);
+            });
+
+        visible.into_iter().map(AtomicBool::into_inner).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::culling::{BoundingBox, Frustum};
+    use nalgebra_glm as glm;
+    use ze_jobsystem::{try_initialize_global, JobSystem};
+
+    fn frustum() -> Frustum {
+        let projection = glm::perspective_lh_zo(1.0, 90.0_f32.to_radians(), 0.1, 100.0);
+        Frustum::from_matrix(&projection)
+    }
+
+    fn unit_box(center: glm::Vec3) -> BoundingBox {
+        BoundingBox::new(
+            center - glm::Vec3::repeat(0.5),
+            center + glm::Vec3::repeat(0.5),
+        )
+    }
+
+    #[test]
+    fn from_points() {
+        let points = [
+            glm::vec3(1.0, -2.0, 3.0),
+            glm::vec3(-1.0, 4.0, 0.0),
+            glm::vec3(0.0, 0.0, 5.0),
+        ];
+        let bounds = BoundingBox::from_points(&points);
+        assert_eq!(bounds.min, glm::vec3(-1.0, -2.0, 0.0));
+        assert_eq!(bounds.max, glm::vec3(1.0, 4.0, 5.0));
+        assert_eq!(BoundingBox::from_points(&[]), BoundingBox::default());
+    }
+
+    #[test]
+    fn transform() {
+        let bounds = unit_box(glm::vec3(1.0, 0.0, 0.0));
+        let matrix = glm::translation(&glm::vec3(0.0, 0.0, 10.0))
+            * glm::rotation(90.0_f32.to_radians(), &glm::Vec3::y())
+            * glm::scaling(&glm::vec3(2.0, 1.0, 1.0));
+
+        let transformed = bounds.transform(&matrix);
+        assert!(glm::distance(&transformed.center(), &glm::vec3(0.0, 0.0, 8.0)) < 1e-5);
+        assert!(glm::distance(&transformed.extents(), &glm::vec3(0.5, 0.5, 1.0)) < 1e-5);
+    }
+
+    #[test]
+    fn intersects() {
+        let frustum = frustum();
+        assert!(frustum.intersects(&unit_box(glm::vec3(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects(&unit_box(glm::vec3(0.0, 0.0, -10.0))));
+        assert!(!frustum.intersects(&unit_box(glm::vec3(0.0, 0.0, 200.0))));
+        assert!(!frustum.intersects(&unit_box(glm::vec3(20.0, 0.0, 10.0))));
+
+        // Partially inside
+        assert!(frustum.intersects(&unit_box(glm::vec3(10.4, 0.0, 10.0))));
+    }
+
+    #[test]
+    fn cull() {
+        let _ = try_initialize_global(JobSystem::new(JobSystem::cpu_thread_count() - 1));
+
+        let bounds: Vec<BoundingBox> = (0..1000)
+            .map(|i| unit_box(glm::vec3(0.0, 0.0, if i % 3 == 0 { -10.0 } else { 10.0 })))
+            .collect();
+        let visible = frustum().cull(&bounds);
+        assert_eq!(visible.len(), bounds.len());
+        for (i, visible) in visible.iter().enumerate() {
+            assert_eq!(*visible, i % 3 != 0);
+        }
+    }
+}
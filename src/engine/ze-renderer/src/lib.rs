@@ -0,0 +1,10 @@
+pub mod camera;
+pub mod culling;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod particles;
+mod renderer;
+mod upload;
+
+pub use renderer::{Renderer, DEPTH_PREPASS_SHADER_PASS, FORWARD_SHADER_PASS};
@@ -0,0 +1,40 @@
+use nalgebra_glm as glm;
+use ze_ecs::Component;
+
+/// Light coming from an infinitely far source, shining along the entity forward (+Z) axis
+///
+/// Only the first directional light of a world is used
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct DirectionalLight {
+    pub color: glm::Vec3,
+    pub intensity: f32,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            color: glm::Vec3::repeat(1.0),
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Light emitted in all directions from the entity position
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct PointLight {
+    pub color: glm::Vec3,
+    pub intensity: f32,
+
+    /// Distance at which the light has no effect
+    pub range: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            color: glm::Vec3::repeat(1.0),
+            intensity: 1.0,
+            range: 10.0,
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::Arc;
+use ze_asset_system::importer::{
+    AssetImporter, AssetImporterResult, ImportedAsset, SourceAssetMetadata,
+};
+use ze_asset_system::loader::{AssetLoader, Error};
+use ze_asset_system::{importer, Asset};
+use ze_core::type_uuid::*;
+use ze_core::ze_warn;
+use ze_filesystem::path::Path;
+use ze_shader_system::{Parameter, ParameterType};
+
+/// Value of a shader parameter, see [`Material`]
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum MaterialParameter {
+    Uint(u32),
+    Uint64(u64),
+    Float(f32),
+    Float2([f32; 2]),
+    Float3([f32; 3]),
+    Float4([f32; 4]),
+
+    /// Matrix given column by column
+    Float4x4([[f32; 4]; 4]),
+
+    /// UUID of a texture asset
+    Texture(Uuid),
+}
+
+/// Shader and values of the parameters declared in its `parameters` block
+///
+/// Source materials are YAML `.zematerial` files:
+/// ```yaml
+/// shader: Lit
+/// parameters:
+///   base_color: !Float4 [1.0, 0.5, 0.5, 1.0]
+///   base_color_texture: !Texture 8f1c7c6e-5f0e-4b7a-9c55-0e4a3f0d2b11
+/// ```
+#[derive(Serialize, Deserialize, TypeUuid, Default)]
+#[type_uuid = "b7e1c0d4-2a5f-4f3e-8d6b-1c9a7e4f5b20"]
+pub struct Material {
+    #[serde(skip_serializing, skip_deserializing)]
+    uuid: Uuid,
+    shader: String,
+
+    #[serde(default)]
+    parameters: BTreeMap<String, MaterialParameter>,
+}
+
+impl Material {
+    /// Create a material with a random UUID
+    pub fn new(shader: String, parameters: BTreeMap<String, MaterialParameter>) -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            shader,
+            parameters,
+        }
+    }
+
+    pub fn shader(&self) -> &str {
+        &self.shader
+    }
+
+    pub fn parameters(&self) -> &BTreeMap<String, MaterialParameter> {
+        &self.parameters
+    }
+}
+
+impl Asset for Material {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+#[derive(Default)]
+pub struct MaterialLoader {}
+
+impl AssetLoader for MaterialLoader {
+    fn load(&self, uuid: Uuid, asset: &mut dyn Read) -> Result<Arc<dyn Asset>, Error> {
+        let mut data = vec![];
+        asset
+            .read_to_end(&mut data)
+            .map_err(|_| Error::CannotDeserialize)?;
+
+        let mut material: Material =
+            bincode::serde::decode_from_slice(&data, bincode::config::standard())
+                .map(|(material, _)| material)
+                .map_err(|_| Error::CannotDeserialize)?;
+        material.uuid = uuid;
+        Ok(Arc::new(material))
+    }
+}
+
+/// Import `.zematerial` files
+#[derive(Default)]
+pub struct MaterialImporter {}
+
+impl AssetImporter for MaterialImporter {
+    type State = ();
+    type Parameters = ();
+
+    fn import(
+        &self,
+        _: &Path,
+        src: &mut dyn Read,
+        metadata: Option<SourceAssetMetadata<Self::State, Self::Parameters>>,
+    ) -> Result<AssetImporterResult<Self::State, Self::Parameters>, importer::Error> {
+        let metadata = metadata.unwrap_or_else(|| SourceAssetMetadata::new(Uuid::new_v4(), (), ()));
+
+        let mut source = String::new();
+        src.read_to_string(&mut source)?;
+        let material: Material = serde_yaml::from_str(&source)?;
+        let data = bincode::serde::encode_to_vec(&material, bincode::config::standard())
+            .map_err(|_| importer::Error::FailedToSerialize)?;
+
+        Ok((
+            vec![ImportedAsset::new(
+                *metadata.uuid(),
+                Material::type_uuid(),
+                data,
+            )],
+            metadata,
+        ))
+    }
+}
+
+fn parameter_size(ty: ParameterType) -> usize {
+    match ty {
+        ParameterType::Uint
+        | ParameterType::Float
+        | ParameterType::Texture2D
+        | ParameterType::Sampler
+        | ParameterType::ByteAddressBuffer
+        | ParameterType::RWByteAddressBuffer => 4,
+        ParameterType::Uint64 | ParameterType::Float2 => 8,
+        ParameterType::Float3 => 12,
+        ParameterType::Float4 => 16,
+        ParameterType::Float4x4 => 64,
+    }
+}
+
+/// Pack the material values of the shader parameters, in declaration order and without padding
+///
+/// Textures and samplers are written as descriptor indices, `texture` is called with `None`
+/// for textures without a value. Buffers are not supported and written as 0, as are missing
+/// values
+pub(crate) fn pack_parameters(
+    parameters: &[Parameter],
+    values: &BTreeMap<String, MaterialParameter>,
+    mut texture: impl FnMut(Option<&Uuid>) -> u32,
+    sampler: u32,
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    for parameter in parameters {
+        let value = values.get(&parameter.name);
+        let start = data.len();
+        match (parameter.ty, value) {
+            (ParameterType::Uint, Some(MaterialParameter::Uint(value))) => {
+                data.extend_from_slice(&value.to_le_bytes())
+            }
+            (ParameterType::Uint64, Some(MaterialParameter::Uint64(value))) => {
+                data.extend_from_slice(&value.to_le_bytes())
+            }
+            (ParameterType::Float, Some(MaterialParameter::Float(value))) => {
+                data.extend_from_slice(&value.to_le_bytes())
+            }
+            (ParameterType::Float2, Some(MaterialParameter::Float2(value))) => {
+                extend_floats(&mut data, value)
+            }
+            (ParameterType::Float3, Some(MaterialParameter::Float3(value))) => {
+                extend_floats(&mut data, value)
+            }
+            (ParameterType::Float4, Some(MaterialParameter::Float4(value))) => {
+                extend_floats(&mut data, value)
+            }
+            (ParameterType::Float4x4, Some(MaterialParameter::Float4x4(value))) => {
+                for column in value {
+                    extend_floats(&mut data, column);
+                }
+            }
+            (ParameterType::Texture2D, Some(MaterialParameter::Texture(uuid))) => {
+                data.extend_from_slice(&texture(Some(uuid)).to_le_bytes())
+            }
+            (ParameterType::Texture2D, None) => {
+                data.extend_from_slice(&texture(None).to_le_bytes())
+            }
+            (ParameterType::Sampler, _) => data.extend_from_slice(&sampler.to_le_bytes()),
+            (ty, Some(value)) => ze_warn!(
+                "Material value {:?} doesn't match the type {:?} of parameter \"{}\"",
+                value,
+                ty,
+                parameter.name
+            ),
+            (_, None) => {}
+        }
+
+        data.resize(start + parameter_size(parameter.ty), 0);
+    }
+
+    data
+}
+
+fn extend_floats(data: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::{pack_parameters, Material, MaterialImporter, MaterialParameter};
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+    use ze_asset_system::importer::AssetImporter;
+    use ze_filesystem::path::Path;
+    use ze_shader_system::{Parameter, ParameterType};
+
+    fn floats(data: &[u8]) -> Vec<f32> {
+        data.chunks(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn pack() {
+        let texture = Uuid::from_u128(42);
+        let parameters = vec![
+            Parameter::new(ParameterType::Float4, "color".to_string()),
+            Parameter::new(ParameterType::Texture2D, "texture".to_string()),
+            Parameter::new(ParameterType::Texture2D, "normal_map".to_string()),
+            Parameter::new(ParameterType::Sampler, "sampler".to_string()),
+            Parameter::new(ParameterType::Float, "roughness".to_string()),
+            Parameter::new(ParameterType::Float2, "tiling".to_string()),
+        ];
+
+        let values = BTreeMap::from([
+            (
+                "color".to_string(),
+                MaterialParameter::Float4([1.0, 0.5, 0.25, 1.0]),
+            ),
+            ("texture".to_string(), MaterialParameter::Texture(texture)),
+            ("roughness".to_string(), MaterialParameter::Uint(3)),
+            ("tiling".to_string(), MaterialParameter::Float2([2.0, 3.0])),
+        ]);
+
+        let data = pack_parameters(
+            &parameters,
+            &values,
+            |uuid| match uuid {
+                Some(uuid) if *uuid == texture => 7,
+                _ => 1,
+            },
+            9,
+        );
+
+        assert_eq!(data.len(), 40);
+        assert_eq!(floats(&data[0..16]), vec![1.0, 0.5, 0.25, 1.0]);
+        assert_eq!(data[16..20], 7u32.to_le_bytes());
+        assert_eq!(data[20..24], 1u32.to_le_bytes());
+        assert_eq!(data[24..28], 9u32.to_le_bytes());
+
+        // Mismatching values are ignored
+        assert_eq!(floats(&data[28..]), vec![0.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn import() {
+        let source = r#"
+            shader: Lit
+            parameters:
+              base_color: !Float4 [1.0, 0.5, 0.5, 1.0]
+              base_color_texture: !Texture 00000000-0000-0000-0000-00000000002a
+        "#;
+
+        let (assets, metadata) = MaterialImporter::default()
+            .import(
+                &Path::parse("//material.zematerial").unwrap(),
+                &mut source.as_bytes(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].uuid(), metadata.uuid());
+
+        let (material, _): (Material, _) =
+            bincode::serde::decode_from_slice(assets[0].data(), bincode::config::standard())
+                .unwrap();
+        assert_eq!(material.shader(), "Lit");
+        assert_eq!(
+            material.parameters()["base_color"],
+            MaterialParameter::Float4([1.0, 0.5, 0.5, 1.0])
+        );
+        assert_eq!(
+            material.parameters()["base_color_texture"],
+            MaterialParameter::Texture(Uuid::from_u128(42))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use crate::culling::BoundingBox;
+use nalgebra_glm as glm;
+use serde_derive::{Deserialize, Serialize};
+use std::io::Read;
+use std::mem::size_of;
+use std::sync::Arc;
+use ze_asset_system::loader::{AssetLoader, Error};
+use ze_asset_system::Asset;
+use ze_core::type_uuid::*;
+use ze_ecs::Component;
+use ze_gfx::backend::*;
+use ze_gfx::utils;
+use ze_reflection::*;
+use ze_scene::value::AssetRef;
+
+/// Vertex layout read by the mesh shaders from the vertex buffer
+#[repr(C)]
+#[derive(Copy, Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 2],
+}
+
+/// GPU copy of a mesh, the vertex buffer is read through its SRV
+struct MeshBuffers {
+    vertex_buffer_srv: ShaderResourceView,
+    index_buffer: Buffer,
+}
+
+/// Indexed triangle list
+#[derive(Serialize, Deserialize, TypeUuid, Default)]
+#[type_uuid = "3f6d2b8a-91c4-4e57-a0d3-6b2e8c1f4a95"]
+pub struct Mesh {
+    #[serde(skip_serializing, skip_deserializing)]
+    uuid: Uuid,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+
+    /// Local bounds of the vertices, computed when created or loaded
+    #[serde(skip_serializing, skip_deserializing)]
+    bounds: BoundingBox,
+
+    #[serde(skip_serializing, skip_deserializing)]
+    buffers: Option<MeshBuffers>,
+}
+
+impl Mesh {
+    /// Create a mesh with a random UUID, it must be uploaded before being rendered
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        let mut mesh = Self {
+            uuid: Uuid::new_v4(),
+            vertices,
+            indices,
+            bounds: BoundingBox::default(),
+            buffers: None,
+        };
+        mesh.compute_bounds();
+        mesh
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn bounds(&self) -> &BoundingBox {
+        &self.bounds
+    }
+
+    fn compute_bounds(&mut self) {
+        let positions: Vec<glm::Vec3> = self
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.into())
+            .collect();
+        self.bounds = BoundingBox::from_points(&positions);
+    }
+
+    /// Copy the vertices and indices to GPU buffers
+    pub fn upload(&mut self, device: &Arc<dyn Device>) -> Result<(), DeviceError> {
+        if self.vertices.is_empty() || self.indices.is_empty() {
+            return Err(DeviceError::InvalidParameters);
+        }
+
+        let create_buffer = |data: &[u8], usage, name| -> Result<Buffer, DeviceError> {
+            let buffer = device.create_buffer(
+                &BufferDesc {
+                    size_bytes: data.len() as u64,
+                    usage,
+                    memory_desc: MemoryDesc {
+                        memory_location: MemoryLocation::GpuOnly,
+                        memory_flags: Default::default(),
+                    },
+                    default_resource_state: ResourceState::Common,
+                },
+                None,
+                name,
+            )?;
+            utils::copy_data_to_buffer(device, &buffer, data, ResourceState::Common)?;
+            Ok(buffer)
+        };
+
+        let vertex_buffer_name = format!("{} Vertices", self.uuid);
+        let vertex_buffer = Arc::new(create_buffer(
+            as_bytes(&self.vertices),
+            BufferUsageFlags::default(),
+            &vertex_buffer_name,
+        )?);
+        let index_buffer_name = format!("{} Indices", self.uuid);
+        let index_buffer = create_buffer(
+            as_bytes(&self.indices),
+            BufferUsageFlags::from_flag(BufferUsageFlagBits::IndexBuffer),
+            &index_buffer_name,
+        )?;
+
+        let vertex_buffer_srv =
+            device.create_shader_resource_view(&ShaderResourceViewDesc::Buffer(BufferSRV {
+                buffer: vertex_buffer,
+                ty: BufferSRVType::Structured(BufferSRVStructured {
+                    offset_in_bytes: 0,
+                    stride_in_bytes: size_of::<Vertex>() as u32,
+                }),
+            }))?;
+
+        self.buffers = Some(MeshBuffers {
+            vertex_buffer_srv,
+            index_buffer,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn vertex_buffer_srv(&self) -> Option<&ShaderResourceView> {
+        self.buffers
+            .as_ref()
+            .map(|buffers| &buffers.vertex_buffer_srv)
+    }
+
+    pub(crate) fn index_buffer(&self) -> Option<&Buffer> {
+        self.buffers.as_ref().map(|buffers| &buffers.index_buffer)
+    }
+}
+
+impl Asset for Mesh {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+/// Render a mesh with a material, using the entity global transform
+#[derive(Component, Reflectable, Clone, Default, PartialEq, Debug)]
+pub struct MeshRenderer {
+    #[ze_reflect]
+    pub mesh: AssetRef,
+
+    #[ze_reflect]
+    pub material: AssetRef,
+}
+
+impl MeshRenderer {
+    pub fn new(mesh: Uuid, material: Uuid) -> Self {
+        Self {
+            mesh: AssetRef::new(mesh),
+            material: AssetRef::new(material),
+        }
+    }
+}
+
+pub struct MeshLoader {
+    device: Arc<dyn Device>,
+}
+
+impl MeshLoader {
+    pub fn new(device: Arc<dyn Device>) -> Self {
+        Self { device }
+    }
+}
+
+impl AssetLoader for MeshLoader {
+    fn load(&self, uuid: Uuid, asset: &mut dyn Read) -> Result<Arc<dyn Asset>, Error> {
+        let mut data = vec![];
+        asset
+            .read_to_end(&mut data)
+            .map_err(|_| Error::CannotDeserialize)?;
+
+        let mut mesh: Mesh = bincode::serde::decode_from_slice(&data, bincode::config::standard())
+            .map(|(mesh, _)| mesh)
+            .map_err(|_| Error::CannotDeserialize)?;
+        mesh.uuid = uuid;
+        mesh.compute_bounds();
+        mesh.upload(&self.device)
+            .map_err(|_| Error::CannotDeserialize)?;
+        Ok(Arc::new(mesh))
+    }
+}
+
+/// View a slice of plain data as bytes
+pub(crate) fn as_bytes<T: Copy>(data: &[T]) -> &[u8] {
+    // SAFETY: T is plain data (Copy) and the slice covers exactly the memory of `data`
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
@@ -0,0 +1,730 @@
+use crate::mesh::as_bytes;
+use crate::upload::UploadBuffer;
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::slice;
+use std::sync::Arc;
+use ze_core::maths::{RectI32, Vector2};
+use ze_core::type_uuid::Uuid;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_gfx::backend::*;
+use ze_gfx::utils;
+use ze_gfx::ShaderStageFlagBits;
+use ze_reflection::*;
+use ze_render_graph::render_pass::RenderPassType;
+use ze_render_graph::{FrameGraph, ResourceHandle};
+use ze_scene::transform::global_matrix;
+use ze_scene::value::AssetRef;
+use ze_shader_system::{ShaderManager, ShaderModules};
+
+/// Shader simulating and drawing the particles
+pub const PARTICLES_SHADER: &str = "Particles";
+
+/// Threads per group of the particle compute shaders
+const THREAD_GROUP_SIZE: u32 = 64;
+
+/// Color with a straight alpha
+#[derive(Reflectable, Copy, Clone, PartialEq, Debug)]
+pub struct ParticleColor {
+    #[ze_reflect]
+    pub r: f32,
+
+    #[ze_reflect]
+    pub g: f32,
+
+    #[ze_reflect]
+    pub b: f32,
+
+    #[ze_reflect]
+    pub a: f32,
+}
+
+impl ParticleColor {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl Default for ParticleColor {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0, 1.0)
+    }
+}
+
+impl From<ParticleColor> for [f32; 4] {
+    fn from(color: ParticleColor) -> Self {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+/// Emit particles from the entity position along its up (+Y) axis
+///
+/// Particles are simulated on the GPU and drawn as camera facing quads blended back to front.
+/// Size and color are interpolated over the lifetime of each particle
+#[derive(Component, Reflectable, Clone, PartialEq, Debug)]
+pub struct ParticleEmitter {
+    /// Particles alive at once, the oldest particles are replaced when exceeded
+    #[ze_reflect(display_name = "Max Particles")]
+    pub max_particles: u32,
+
+    /// Particles spawned per second
+    #[ze_reflect(display_name = "Spawn Rate")]
+    pub spawn_rate: f32,
+
+    /// Seconds a particle lives
+    #[ze_reflect(display_name = "Lifetime")]
+    pub lifetime: f32,
+
+    /// Initial speed in units per second
+    #[ze_reflect(display_name = "Speed")]
+    pub speed: f32,
+
+    /// Half angle in radians of the cone the particles are emitted in
+    #[ze_reflect(display_name = "Spread")]
+    pub spread: f32,
+
+    /// Downward acceleration in units per second squared
+    #[ze_reflect(display_name = "Gravity")]
+    pub gravity: f32,
+
+    #[ze_reflect(display_name = "Start Size")]
+    pub start_size: f32,
+
+    #[ze_reflect(display_name = "End Size")]
+    pub end_size: f32,
+
+    #[ze_reflect(display_name = "Start Color")]
+    pub start_color: ParticleColor,
+
+    #[ze_reflect(display_name = "End Color")]
+    pub end_color: ParticleColor,
+
+    /// Texture of the particles, white if unset
+    #[ze_reflect(display_name = "Texture")]
+    pub texture: AssetRef,
+
+    /// Distance over which particles fade out in front of opaque geometry, 0 disables it
+    #[ze_reflect(display_name = "Softness")]
+    pub softness: f32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            max_particles: 1024,
+            spawn_rate: 64.0,
+            lifetime: 2.0,
+            speed: 2.0,
+            spread: 30.0_f32.to_radians(),
+            gravity: 0.0,
+            start_size: 0.25,
+            end_size: 0.0,
+            start_color: ParticleColor::default(),
+            end_color: ParticleColor::new(1.0, 1.0, 1.0, 0.0),
+            texture: AssetRef::default(),
+            softness: 0.5,
+        }
+    }
+}
+
+/// Particle as stored on the GPU, dead once its age reaches its lifetime
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Particle {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    lifetime: f32,
+}
+
+/// Per-emitter constants, read by the shaders as a structured buffer
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct EmitterConstants {
+    transform: glm::Mat4,
+    start_color: [f32; 4],
+    end_color: [f32; 4],
+    gravity: [f32; 3],
+    delta_time: f32,
+    capacity: u32,
+    sort_capacity: u32,
+    spawn_start: u32,
+    spawn_count: u32,
+    lifetime: f32,
+    speed: f32,
+    spread: f32,
+    seed: u32,
+    start_size: f32,
+    end_size: f32,
+    softness: f32,
+    texture: u32,
+    sampler: u32,
+    _padding: [u32; 3],
+}
+
+/// Push constants of the particle shaders
+///
+/// Particles and keys are given as UAVs when simulating and as SRVs when rendering
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+struct ParticleConstants {
+    view: u32,
+    emitters: u32,
+    emitter: u32,
+    particles: u32,
+    keys: u32,
+    depth: u32,
+    sort_block: u32,
+    sort_distance: u32,
+}
+
+/// Particles spawned by an emitter, in a ring of `capacity` particles
+#[derive(Default)]
+struct Emission {
+    /// Fraction of a particle left to spawn
+    accumulator: f32,
+    next: u32,
+}
+
+impl Emission {
+    /// Advance by `delta_time` seconds
+    ///
+    /// Returns the first particle to spawn and the number of particles to spawn
+    fn advance(&mut self, rate: f32, delta_time: f32, capacity: u32) -> (u32, u32) {
+        if capacity == 0 {
+            return (0, 0);
+        }
+
+        self.accumulator += rate.max(0.0) * delta_time;
+        let count = self.accumulator.floor();
+        self.accumulator -= count;
+
+        let count = (count as u32).min(capacity);
+        let start = self.next;
+        self.next = (self.next + count) % capacity;
+        (start, count)
+    }
+}
+
+/// Block size and compare distance of each step of a bitonic sort of `count` keys
+///
+/// `count` must be a power of two
+fn bitonic_sort_steps(count: u32) -> Vec<(u32, u32)> {
+    let mut steps = vec![];
+    let mut block = 2;
+    while block <= count {
+        let mut distance = block / 2;
+        while distance > 0 {
+            steps.push((block, distance));
+            distance /= 2;
+        }
+        block *= 2;
+    }
+    steps
+}
+
+fn thread_group_count(threads: u32) -> u32 {
+    threads.div_ceil(THREAD_GROUP_SIZE)
+}
+
+/// Structured buffer written by the compute shaders
+struct StructuredBuffer {
+    buffer: Arc<Buffer>,
+    uav: UnorderedAccessView,
+    srv: ShaderResourceView,
+}
+
+impl StructuredBuffer {
+    /// Create a zeroed buffer of `count` elements
+    fn new<T>(device: &Arc<dyn Device>, count: u32, name: &str) -> Result<Self, DeviceError> {
+        let size = count as usize * size_of::<T>();
+        let buffer = Arc::new(device.create_buffer(
+            &BufferDesc {
+                size_bytes: size as u64,
+                usage: BufferUsageFlags::from_flag(BufferUsageFlagBits::UnorderedAccess),
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::GpuOnly,
+                    memory_flags: Default::default(),
+                },
+                default_resource_state: ResourceState::Common,
+            },
+            None,
+            name,
+        )?);
+        utils::copy_data_to_buffer(device, &buffer, &vec![0; size], ResourceState::Common)?;
+
+        let ty = BufferSRVType::Structured(BufferSRVStructured {
+            offset_in_bytes: 0,
+            stride_in_bytes: size_of::<T>() as u32,
+        });
+        let uav =
+            device.create_unordered_access_view(&UnorderedAccessViewDesc::Buffer(BufferUAV {
+                buffer: buffer.clone(),
+                ty: ty.clone(),
+            }))?;
+        let srv =
+            device.create_shader_resource_view(&ShaderResourceViewDesc::Buffer(BufferSRV {
+                buffer: buffer.clone(),
+                ty,
+            }))?;
+
+        Ok(Self { buffer, uav, srv })
+    }
+}
+
+/// GPU buffers of an emitter
+struct EmitterBuffers {
+    /// Zeroed particles are dead
+    particles: StructuredBuffer,
+
+    /// Sort key and index of each particle, padded to a power of two with dead keys
+    keys: StructuredBuffer,
+}
+
+struct EmitterState {
+    max_particles: u32,
+    buffers: Arc<EmitterBuffers>,
+    emission: Emission,
+
+    /// Seconds to simulate on the next frame
+    delta_time: f32,
+}
+
+impl EmitterState {
+    fn new(device: &Arc<dyn Device>, max_particles: u32) -> Result<Self, DeviceError> {
+        let sort_capacity = max_particles.next_power_of_two();
+        Ok(Self {
+            max_particles,
+            buffers: Arc::new(EmitterBuffers {
+                particles: StructuredBuffer::new::<Particle>(device, max_particles, "Particles")?,
+                keys: StructuredBuffer::new::<[u32; 2]>(
+                    device,
+                    sort_capacity,
+                    "Particle Sort Keys",
+                )?,
+            }),
+            emission: Emission::default(),
+            delta_time: 0.0,
+        })
+    }
+}
+
+struct ParticleShaders {
+    spawn: Arc<ShaderModules>,
+    update: Arc<ShaderModules>,
+    sort: Arc<ShaderModules>,
+    render: Arc<ShaderModules>,
+}
+
+impl ParticleShaders {
+    /// `None` until every pass of the particle shader is compiled
+    fn new(shader_manager: &Arc<ShaderManager>) -> Option<Self> {
+        let modules = |pass: &str| {
+            shader_manager
+                .shader_modules(&PARTICLES_SHADER.to_string(), Some(pass.to_string()))
+                .ok()
+        };
+        let compute_modules = |pass: &str| {
+            modules(pass).filter(|modules| modules.stage(ShaderStageFlagBits::Compute).is_some())
+        };
+
+        Some(Self {
+            spawn: compute_modules("Spawn")?,
+            update: compute_modules("Update")?,
+            sort: compute_modules("Sort")?,
+            render: modules("Render")?,
+        })
+    }
+}
+
+/// Compute module of a compute pass, checked by [`ParticleShaders::new`]
+fn compute_shader(modules: &ShaderModules) -> &ShaderModule {
+    modules.stage(ShaderStageFlagBits::Compute).unwrap()
+}
+
+/// GPU state of the [`ParticleEmitter`]s, kept between frames
+#[derive(Default)]
+pub(crate) struct ParticleSystem {
+    emitters: HashMap<Entity, EmitterState>,
+    seed: u32,
+}
+
+impl ParticleSystem {
+    pub fn update(&mut self, delta_time: f32) {
+        for state in self.emitters.values_mut() {
+            state.delta_time += delta_time;
+        }
+    }
+
+    /// Advance the emission of the emitters of `world` and write their constants
+    ///
+    /// `textures` contains the descriptor index of the emitter textures, `None` is returned if
+    /// there is nothing to simulate or the shaders aren't available
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &Arc<dyn Device>,
+        shader_manager: &Arc<ShaderManager>,
+        world: &World,
+        slot: &mut Option<UploadBuffer>,
+        textures: &HashMap<Uuid, u32>,
+        sampler: u32,
+        view: u32,
+    ) -> Result<Option<PreparedParticles>, DeviceError> {
+        let mut emitters = vec![];
+        world
+            .query::<(Entity, &ParticleEmitter)>()
+            .for_each(world, |(entity, emitter)| {
+                emitters.push((entity, emitter.clone(), global_matrix(world, entity)));
+            });
+
+        self.emitters
+            .retain(|entity, _| emitters.iter().any(|(emitter, _, _)| emitter == entity));
+
+        let shaders = match ParticleShaders::new(shader_manager) {
+            Some(shaders) if !emitters.is_empty() => shaders,
+            _ => return Ok(None),
+        };
+
+        let mut constants = Vec::with_capacity(emitters.len());
+        let mut prepared = Vec::with_capacity(emitters.len());
+        for (entity, emitter, transform) in emitters {
+            if emitter.max_particles == 0 {
+                continue;
+            }
+
+            let state = match self.emitters.get_mut(&entity) {
+                Some(state) if state.max_particles == emitter.max_particles => state,
+                _ => {
+                    let state = EmitterState::new(device, emitter.max_particles)?;
+                    self.emitters.insert(entity, state);
+                    self.emitters.get_mut(&entity).unwrap()
+                }
+            };
+
+            let capacity = emitter.max_particles;
+            let sort_capacity = capacity.next_power_of_two();
+            let (spawn_start, spawn_count) =
+                state
+                    .emission
+                    .advance(emitter.spawn_rate, state.delta_time, capacity);
+
+            self.seed = self.seed.wrapping_add(1);
+            constants.push(EmitterConstants {
+                transform,
+                start_color: emitter.start_color.into(),
+                end_color: emitter.end_color.into(),
+                gravity: [0.0, -emitter.gravity, 0.0],
+                delta_time: state.delta_time,
+                capacity,
+                sort_capacity,
+                spawn_start,
+                spawn_count,
+                lifetime: emitter.lifetime,
+                speed: emitter.speed,
+                spread: emitter.spread,
+                seed: self.seed,
+                start_size: emitter.start_size,
+                end_size: emitter.end_size,
+                softness: emitter.softness,
+                texture: textures[&emitter.texture.uuid],
+                sampler,
+                _padding: [0; 3],
+            });
+            state.delta_time = 0.0;
+
+            prepared.push(PreparedEmitter {
+                buffers: state.buffers.clone(),
+                capacity,
+                sort_capacity,
+                spawn_count,
+            });
+        }
+
+        if prepared.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(PreparedParticles {
+            device: device.clone(),
+            shaders,
+            view,
+            emitters: UploadBuffer::write(device, slot, &constants, "Particle Emitters")?,
+            prepared,
+        }))
+    }
+}
+
+struct PreparedEmitter {
+    buffers: Arc<EmitterBuffers>,
+    capacity: u32,
+    sort_capacity: u32,
+    spawn_count: u32,
+}
+
+/// Emitters of a view, shared by the particle passes of the frame graph
+pub(crate) struct PreparedParticles {
+    device: Arc<dyn Device>,
+    shaders: ParticleShaders,
+    view: u32,
+    emitters: u32,
+    prepared: Vec<PreparedEmitter>,
+}
+
+impl PreparedParticles {
+    /// Add the passes simulating, sorting and drawing the particles over `target`
+    ///
+    /// `depth` is the depth of the opaque geometry, returns the handle of `target` written by
+    /// the particles
+    pub fn add_passes(
+        self: &Arc<Self>,
+        graph: &mut FrameGraph<'_>,
+        depth: ResourceHandle,
+        target: ResourceHandle,
+        width: u32,
+        height: u32,
+    ) -> ResourceHandle {
+        let buffers = self
+            .prepared
+            .iter()
+            .enumerate()
+            .map(|(i, emitter)| {
+                let particles = format!("Particles {}", i);
+                let keys = format!("Particle Sort Keys {}", i);
+                (
+                    graph.import_external_buffer(
+                        emitter.buffers.particles.buffer.clone(),
+                        &particles,
+                    ),
+                    graph.import_external_buffer(emitter.buffers.keys.buffer.clone(), &keys),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let simulation = self.clone();
+        let mut simulated = vec![];
+        graph.add_pass(
+            "Particle Simulation",
+            RenderPassType::Compute,
+            |builder| {
+                for &(particles, keys) in &buffers {
+                    simulated.push((builder.write(particles), builder.write(keys)));
+                }
+            },
+            move |_, _, cmd_list| simulation.simulate(cmd_list),
+        );
+
+        let sort = self.clone();
+        let mut sorted = vec![];
+        graph.add_pass(
+            "Particle Sort",
+            RenderPassType::Compute,
+            |builder| {
+                for &(particles, keys) in &simulated {
+                    let keys = builder.read(keys);
+                    sorted.push((particles, builder.write(keys)));
+                }
+            },
+            move |_, _, cmd_list| sort.sort(cmd_list),
+        );
+
+        let render = self.clone();
+        let mut output = target;
+        graph.add_pass(
+            "Particles",
+            RenderPassType::Graphics,
+            |builder| {
+                for &(particles, keys) in &sorted {
+                    let _ = builder.read(particles);
+                    let _ = builder.read(keys);
+                }
+
+                let target = builder.read(target);
+                output = builder.write(target);
+                builder.read(depth)
+            },
+            move |graph, depth, cmd_list| {
+                let depth = graph.texture_srv(*depth).descriptor_index();
+                render.draw(cmd_list, depth, width, height)
+            },
+        );
+
+        output
+    }
+
+    fn constants(&self, index: usize) -> ParticleConstants {
+        ParticleConstants {
+            view: self.view,
+            emitters: self.emitters,
+            emitter: index as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Spawn the new particles, then update the particles and their sort keys
+    fn simulate(&self, cmd_list: &mut CommandList) {
+        let device = &self.device;
+        for (index, emitter) in self.prepared.iter().enumerate() {
+            let constants = ParticleConstants {
+                particles: emitter.buffers.particles.uav.descriptor_index(),
+                keys: emitter.buffers.keys.uav.descriptor_index(),
+                ..self.constants(index)
+            };
+
+            if emitter.spawn_count > 0 {
+                device.cmd_set_compute_shader(cmd_list, compute_shader(&self.shaders.spawn));
+                device.cmd_push_constants(cmd_list, 0, as_bytes(slice::from_ref(&constants)));
+                device.cmd_dispatch(cmd_list, thread_group_count(emitter.spawn_count), 1, 1);
+                device.cmd_resource_barrier(
+                    cmd_list,
+                    &[ResourceBarrier::UnorderedAccess(
+                        ResourceTransitionBarrierResource::Buffer(
+                            &emitter.buffers.particles.buffer,
+                        ),
+                    )],
+                );
+            }
+
+            device.cmd_set_compute_shader(cmd_list, compute_shader(&self.shaders.update));
+            device.cmd_push_constants(cmd_list, 0, as_bytes(slice::from_ref(&constants)));
+            device.cmd_dispatch(cmd_list, thread_group_count(emitter.sort_capacity), 1, 1);
+        }
+    }
+
+    /// Sort the particles back to front, dead particles last
+    fn sort(&self, cmd_list: &mut CommandList) {
+        let device = &self.device;
+        for (index, emitter) in self.prepared.iter().enumerate() {
+            device.cmd_set_compute_shader(cmd_list, compute_shader(&self.shaders.sort));
+            for (step, (block, distance)) in bitonic_sort_steps(emitter.sort_capacity)
+                .into_iter()
+                .enumerate()
+            {
+                if step > 0 {
+                    device.cmd_resource_barrier(
+                        cmd_list,
+                        &[ResourceBarrier::UnorderedAccess(
+                            ResourceTransitionBarrierResource::Buffer(&emitter.buffers.keys.buffer),
+                        )],
+                    );
+                }
+
+                let constants = ParticleConstants {
+                    keys: emitter.buffers.keys.uav.descriptor_index(),
+                    sort_block: block,
+                    sort_distance: distance,
+                    ..self.constants(index)
+                };
+                device.cmd_push_constants(cmd_list, 0, as_bytes(slice::from_ref(&constants)));
+                device.cmd_dispatch(cmd_list, thread_group_count(emitter.sort_capacity), 1, 1);
+            }
+        }
+    }
+
+    /// Draw a camera facing quad per particle, without depth testing as the particles fade
+    /// out against the depth of the opaque geometry
+    fn draw(&self, cmd_list: &mut CommandList, depth: u32, width: u32, height: u32) {
+        let device = &self.device;
+        device.cmd_set_viewports(
+            cmd_list,
+            &[Viewport {
+                position: Default::default(),
+                size: Vector2::<f32>::new(width as f32, height as f32),
+                min_depth: 0.0,
+                max_depth: 1.0,
+            }],
+        );
+        device.cmd_set_scissors(cmd_list, &[RectI32::new(0, 0, width as i32, height as i32)]);
+
+        let mut blend_state = PipelineBlendState::default();
+        blend_state.render_targets[0] = PipelineRenderTargetBlendDesc {
+            enable_blend: true,
+            src_color_blend_factor: BlendFactor::SrcAlpha,
+            dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+            color_blend_op: BlendOp::Add,
+            src_alpha_blend_factor: BlendFactor::One,
+            dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+            alpha_blend_op: BlendOp::Add,
+        };
+
+        for (index, emitter) in self.prepared.iter().enumerate() {
+            let constants = ParticleConstants {
+                particles: emitter.buffers.particles.srv.descriptor_index(),
+                keys: emitter.buffers.keys.srv.descriptor_index(),
+                depth,
+                ..self.constants(index)
+            };
+
+            device.cmd_set_shader_stages(cmd_list, &self.shaders.render.pipeline_stages());
+            device.cmd_set_input_assembly_state(
+                cmd_list,
+                &PipelineInputAssemblyState {
+                    primitive_topology: PrimitiveTopology::Triangle,
+                },
+            );
+            device.cmd_set_blend_state(cmd_list, &blend_state);
+            device.cmd_set_depth_stencil_state(
+                cmd_list,
+                &PipelineDepthStencilState {
+                    depth_test_enable: false,
+                    depth_write_mask: 0,
+                    depth_write_enable: false,
+                    depth_compare_op: CompareOp::Always,
+                    stencil_test_enable: false,
+                    stencil_read_mask: 0,
+                    stencil_write_mask: 0,
+                    front: Default::default(),
+                    back: Default::default(),
+                },
+            );
+            device.cmd_push_constants(cmd_list, 0, as_bytes(slice::from_ref(&constants)));
+            device.cmd_draw(cmd_list, 6, emitter.capacity, 0, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::particles::{bitonic_sort_steps, Emission};
+
+    #[test]
+    fn emission() {
+        let mut emission = Emission::default();
+        assert_eq!(emission.advance(10.0, 0.25, 8), (0, 2));
+        assert_eq!(emission.advance(10.0, 0.25, 8), (2, 3));
+
+        // Wraps around the ring
+        assert_eq!(emission.advance(10.0, 0.4, 8), (5, 4));
+        assert_eq!(emission.next, 1);
+
+        // Never spawns more than the capacity
+        assert_eq!(emission.advance(100.0, 1.0, 8), (1, 8));
+        assert_eq!(emission.advance(10.0, 1.0, 0), (0, 0));
+    }
+
+    #[test]
+    fn bitonic_sort() {
+        assert_eq!(
+            bitonic_sort_steps(8),
+            vec![(2, 1), (4, 2), (4, 1), (8, 4), (8, 2), (8, 1)]
+        );
+        assert!(bitonic_sort_steps(1).is_empty());
+
+        // Same compare and swap as the sort shader
+        let mut keys: Vec<u32> = (0..64).map(|i| (i * 37 + 11) % 64).collect();
+        for (block, distance) in bitonic_sort_steps(keys.len() as u32) {
+            for index in 0..keys.len() {
+                let other = index ^ distance as usize;
+                let ascending = index & block as usize == 0;
+                if other > index && (keys[index] > keys[other]) == ascending {
+                    keys.swap(index, other);
+                }
+            }
+        }
+
+        assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -0,0 +1,687 @@
+use crate::camera::View;
+use crate::culling::Frustum;
+use crate::light::{DirectionalLight, PointLight};
+use crate::material::{pack_parameters, Material, MaterialParameter};
+use crate::mesh::{as_bytes, Mesh, MeshRenderer};
+use crate::particles::{ParticleEmitter, ParticleSystem, PreparedParticles};
+use crate::upload::UploadBuffer;
+use nalgebra_glm as glm;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use ze_asset_system::{Asset, AssetManager};
+use ze_core::maths::{RectI32, Vector2};
+use ze_core::type_uuid::Uuid;
+use ze_core::ze_error;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_filesystem::path::Path;
+use ze_gfx::backend::*;
+use ze_gfx::{utils, PixelFormat};
+use ze_render_graph::render_pass::RenderPassType;
+use ze_render_graph::{FrameGraph, FrameGraphTextureDesc, ResourceHandle};
+use ze_scene::transform::global_matrix;
+use ze_shader_system::{Parameter, ShaderManager, ShaderModules};
+use ze_texture_asset::Texture as TextureAsset;
+
+/// Shader pass writing the depth of the meshes
+pub const DEPTH_PREPASS_SHADER_PASS: &str = "DepthPrepass";
+
+/// Shader pass shading the meshes, depth is tested for equality with the depth pre-pass
+pub const FORWARD_SHADER_PASS: &str = "Forward";
+
+/// Frames that can be in flight, per-frame buffers are reused after this many frames
+const FRAME_COUNT: usize = 3;
+
+const DEPTH_FORMAT: PixelFormat = PixelFormat::D24UnormS8Uint;
+
+/// Per-view constants, read by the shaders as a structured buffer of one element
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct ViewConstants {
+    view: glm::Mat4,
+    projection: glm::Mat4,
+    view_projection: glm::Mat4,
+    position: [f32; 3],
+    point_light_count: u32,
+    sun_direction: [f32; 3],
+    point_lights: u32,
+    sun_color: [f32; 3],
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct PointLightData {
+    position: [f32; 3],
+    range: f32,
+    color: [f32; 3],
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct InstanceData {
+    model: glm::Mat4,
+
+    /// Inverse transpose of the model matrix
+    normal: glm::Mat4,
+}
+
+/// Push constants of each draw, buffers are given as descriptor indices
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct DrawConstants {
+    view: u32,
+    vertex_buffer: u32,
+    instances: u32,
+    base_instance: u32,
+    material: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Pass {
+    DepthPrepass,
+    Forward,
+}
+
+/// Packed parameters of a material, rebuilt when the shader parameters change
+struct MaterialBuffer {
+    parameters: Vec<Parameter>,
+    srv: ShaderResourceView,
+}
+
+#[derive(Default)]
+struct FrameBuffers {
+    view: Option<UploadBuffer>,
+    instances: Option<UploadBuffer>,
+    point_lights: Option<UploadBuffer>,
+    emitters: Option<UploadBuffer>,
+}
+
+/// Instanced draw of a mesh
+struct Draw {
+    mesh: Arc<Mesh>,
+    depth_prepass: Option<Arc<ShaderModules>>,
+    forward: Option<Arc<ShaderModules>>,
+    material: u32,
+    instances: Range<u32>,
+}
+
+/// Draws of a view, shared by the passes of the frame graph
+struct PreparedView {
+    device: Arc<dyn Device>,
+    width: u32,
+    height: u32,
+    view: u32,
+    instances: u32,
+    draws: Vec<Draw>,
+    particles: Option<Arc<PreparedParticles>>,
+}
+
+impl PreparedView {
+    fn draw(&self, cmd_list: &mut CommandList, pass: Pass) {
+        let device = &self.device;
+        device.cmd_set_viewports(
+            cmd_list,
+            &[Viewport {
+                position: Default::default(),
+                size: Vector2::<f32>::new(self.width as f32, self.height as f32),
+                min_depth: 0.0,
+                max_depth: 1.0,
+            }],
+        );
+        device.cmd_set_scissors(
+            cmd_list,
+            &[RectI32::new(0, 0, self.width as i32, self.height as i32)],
+        );
+
+        for draw in &self.draws {
+            let modules = match pass {
+                Pass::DepthPrepass => &draw.depth_prepass,
+                Pass::Forward => &draw.forward,
+            };
+
+            let (modules, vertex_buffer, index_buffer) = match (
+                modules,
+                draw.mesh.vertex_buffer_srv(),
+                draw.mesh.index_buffer(),
+            ) {
+                (Some(modules), Some(vertex_buffer), Some(index_buffer)) => {
+                    (modules, vertex_buffer, index_buffer)
+                }
+                _ => continue,
+            };
+
+            let constants = DrawConstants {
+                view: self.view,
+                vertex_buffer: vertex_buffer.descriptor_index(),
+                instances: self.instances,
+                base_instance: draw.instances.start,
+                material: draw.material,
+            };
+
+            // Pipeline states are only kept once the command list has graphics shader stages
+            device.cmd_set_shader_stages(cmd_list, &modules.pipeline_stages());
+            device.cmd_set_input_assembly_state(
+                cmd_list,
+                &PipelineInputAssemblyState {
+                    primitive_topology: PrimitiveTopology::Triangle,
+                },
+            );
+            device.cmd_set_blend_state(cmd_list, &PipelineBlendState::default());
+            device.cmd_set_depth_stencil_state(
+                cmd_list,
+                &match pass {
+                    Pass::DepthPrepass => depth_stencil_state(true, CompareOp::Less),
+                    Pass::Forward => depth_stencil_state(false, CompareOp::Equal),
+                },
+            );
+            device.cmd_bind_index_buffer(cmd_list, index_buffer, IndexBufferFormat::Uint32);
+            device.cmd_push_constants(cmd_list, 0, as_bytes(std::slice::from_ref(&constants)));
+            device.cmd_draw_indexed(
+                cmd_list,
+                draw.mesh.indices().len() as u32,
+                draw.instances.len() as u32,
+                0,
+                0,
+            );
+        }
+    }
+}
+
+/// Render the [`MeshRenderer`] of a world through a frame graph
+///
+/// Meshes are drawn in a depth pre-pass then shaded in a forward pass, lit by the first
+/// [`DirectionalLight`] and the [`PointLight`]s of the world. Entities sharing a mesh and a
+/// material are drawn with a single instanced draw, after culling the ones outside of the view
+/// frustum. [`ParticleEmitter`]s are then simulated and drawn over the shaded meshes
+pub struct Renderer {
+    device: Arc<dyn Device>,
+    shader_manager: Arc<ShaderManager>,
+    asset_manager: Arc<AssetManager>,
+    sampler: Sampler,
+
+    /// White texture used by materials without a value or a valid texture for a parameter
+    fallback_texture_srv: ShaderResourceView,
+
+    /// Loaded meshes, materials and textures, `None` if the asset cannot be loaded
+    assets: HashMap<Uuid, Option<Arc<dyn Asset>>>,
+    materials: HashMap<Uuid, MaterialBuffer>,
+    frames: Vec<FrameBuffers>,
+    frame_index: usize,
+    particles: ParticleSystem,
+}
+
+impl Renderer {
+    pub fn new(
+        device: Arc<dyn Device>,
+        shader_manager: Arc<ShaderManager>,
+        asset_manager: Arc<AssetManager>,
+    ) -> Result<Self, DeviceError> {
+        let sampler = device.create_sampler(&SamplerDesc::default())?;
+
+        let fallback_texture = Arc::new(device.create_texture(
+            &TextureDesc {
+                width: 1,
+                height: 1,
+                depth: 1,
+                array_size: 1,
+                mip_levels: 1,
+                format: PixelFormat::R8G8B8A8Unorm,
+                sample_desc: Default::default(),
+                usage_flags: TextureUsageFlags::empty(),
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::GpuOnly,
+                    memory_flags: Default::default(),
+                },
+            },
+            None,
+            "Renderer Fallback Texture",
+        )?);
+        utils::copy_data_to_texture(
+            &device,
+            &[255; 4],
+            1,
+            1,
+            4,
+            &fallback_texture,
+            ResourceState::Common,
+        )?;
+        let fallback_texture_srv = device.create_shader_resource_view(
+            &ShaderResourceViewDesc::Texture2D(Texture2DSRV {
+                texture: fallback_texture,
+                format: PixelFormat::R8G8B8A8Unorm,
+                min_mip_level: 0,
+                mip_levels: 1,
+            }),
+        )?;
+
+        Ok(Self {
+            device,
+            shader_manager,
+            asset_manager,
+            sampler,
+            fallback_texture_srv,
+            assets: HashMap::new(),
+            materials: HashMap::new(),
+            frames: (0..FRAME_COUNT).map(|_| FrameBuffers::default()).collect(),
+            frame_index: 0,
+            particles: ParticleSystem::default(),
+        })
+    }
+
+    /// Advance the particles by `delta_time` seconds, they are simulated by the next render
+    pub fn update(&mut self, delta_time: f32) {
+        self.particles.update(delta_time);
+    }
+
+    /// Add the passes rendering `world` to `target` in `graph`
+    ///
+    /// Returns the handle of `target` written by the forward pass, to be used by the following
+    /// passes. A graph can only contain one view rendered by this renderer
+    pub fn render(
+        &mut self,
+        graph: &mut FrameGraph<'_>,
+        world: &World,
+        view: &View,
+        target: ResourceHandle,
+    ) -> ResourceHandle {
+        let desc = graph.texture_desc(target).clone();
+        let prepared = match self.prepare(world, view, desc.width, desc.height) {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                ze_error!("Failed to prepare the view: {:?}", error);
+                PreparedView {
+                    device: self.device.clone(),
+                    width: desc.width,
+                    height: desc.height,
+                    view: 0,
+                    instances: 0,
+                    draws: vec![],
+                    particles: None,
+                }
+            }
+        };
+
+        let particles = prepared.particles.clone();
+        let prepared = Arc::new(prepared);
+        let depth_prepass = prepared.clone();
+        let mut depth = None;
+        graph.add_pass(
+            "Depth Prepass",
+            RenderPassType::Graphics,
+            |builder| {
+                let texture = builder.create_texture(
+                    "Depth",
+                    FrameGraphTextureDesc {
+                        format: DEPTH_FORMAT,
+                        width: desc.width,
+                        height: desc.height,
+                    },
+                );
+                builder.set_depth_stencil_output(texture, ClearValue::DepthStencil((1.0, 0)));
+                depth = Some(texture);
+            },
+            move |_, _, cmd_list| depth_prepass.draw(cmd_list, Pass::DepthPrepass),
+        );
+
+        let mut output = target;
+        graph.add_pass(
+            "Forward",
+            RenderPassType::Graphics,
+            |builder| {
+                builder.set_depth_stencil_input(depth.unwrap());
+                output = match view.clear_color {
+                    Some(color) => {
+                        let output = builder.write(target);
+                        builder.set_clear_color(output, ClearValue::Color(color));
+                        output
+                    }
+                    None => {
+                        let target = builder.read(target);
+                        builder.write(target)
+                    }
+                };
+            },
+            move |_, _, cmd_list| prepared.draw(cmd_list, Pass::Forward),
+        );
+
+        match particles {
+            Some(particles) => {
+                particles.add_passes(graph, depth.unwrap(), output, desc.width, desc.height)
+            }
+            None => output,
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        world: &World,
+        view: &View,
+        width: u32,
+        height: u32,
+    ) -> Result<PreparedView, DeviceError> {
+        let mut renderers = vec![];
+        world
+            .query::<(Entity, &MeshRenderer)>()
+            .for_each(world, |(entity, renderer)| {
+                renderers.push((
+                    (renderer.material.uuid, renderer.mesh.uuid),
+                    global_matrix(world, entity),
+                ));
+            });
+
+        // Meshes that can't be loaded are never drawn, skip them before culling
+        let mut bounds = Vec::with_capacity(renderers.len());
+        renderers.retain(|((_, mesh), model)| match self.asset::<Mesh>(*mesh) {
+            Some(mesh) => {
+                bounds.push(mesh.bounds().transform(model));
+                true
+            }
+            None => false,
+        });
+
+        let visible = Frustum::from_matrix(&view.view_projection()).cull(&bounds);
+        let instances = renderers
+            .into_iter()
+            .zip(visible)
+            .filter(|(_, visible)| *visible)
+            .map(|((key, model), _)| {
+                (
+                    key,
+                    InstanceData {
+                        model,
+                        normal: glm::inverse_transpose(model),
+                    },
+                )
+            })
+            .collect();
+
+        let (batches, instances) = batch_instances(instances);
+        let mut draws = Vec::with_capacity(batches.len());
+        for ((material, mesh), instances) in batches {
+            let (material, mesh) =
+                match (self.asset::<Material>(material), self.asset::<Mesh>(mesh)) {
+                    (Some(material), Some(mesh)) => (material, mesh),
+                    _ => continue,
+                };
+
+            if mesh.index_buffer().is_none() {
+                continue;
+            }
+
+            let material_buffer = match self.material_buffer(&material) {
+                Some(material_buffer) => material_buffer,
+                None => continue,
+            };
+
+            let shader = material.shader().to_string();
+            let modules = |pass: &str| {
+                self.shader_manager
+                    .shader_modules(&shader, Some(pass.to_string()))
+                    .ok()
+            };
+
+            draws.push(Draw {
+                mesh,
+                depth_prepass: modules(DEPTH_PREPASS_SHADER_PASS),
+                forward: modules(FORWARD_SHADER_PASS),
+                material: material_buffer,
+                instances,
+            });
+        }
+
+        let mut sun = None;
+        world
+            .query::<(Entity, &DirectionalLight)>()
+            .for_each(world, |(entity, light)| {
+                if sun.is_none() {
+                    let direction = global_matrix(world, entity) * glm::vec4(0.0, 0.0, 1.0, 0.0);
+                    sun = Some((
+                        glm::normalize(&glm::vec4_to_vec3(&direction)),
+                        light.color * light.intensity,
+                    ));
+                }
+            });
+        let (sun_direction, sun_color) = sun.unwrap_or((glm::Vec3::y(), glm::Vec3::zeros()));
+
+        let mut point_lights = vec![];
+        world
+            .query::<(Entity, &PointLight)>()
+            .for_each(world, |(entity, light)| {
+                let position = global_matrix(world, entity) * glm::vec4(0.0, 0.0, 0.0, 1.0);
+                point_lights.push(PointLightData {
+                    position: glm::vec4_to_vec3(&position).into(),
+                    range: light.range,
+                    color: (light.color * light.intensity).into(),
+                    _padding: 0,
+                });
+            });
+
+        let mut emitter_textures = HashMap::new();
+        world
+            .query::<&ParticleEmitter>()
+            .for_each(world, |emitter| {
+                emitter_textures.insert(emitter.texture.uuid, 0);
+            });
+        for (uuid, index) in &mut emitter_textures {
+            *index = self.texture_index(*uuid);
+        }
+
+        self.frame_index = (self.frame_index + 1) % FRAME_COUNT;
+        let frame = &mut self.frames[self.frame_index];
+        let point_lights_index = UploadBuffer::write(
+            &self.device,
+            &mut frame.point_lights,
+            &point_lights,
+            "Renderer Point Lights",
+        )?;
+
+        let projection = view.jittered_projection(width, height);
+        let view_constants = ViewConstants {
+            view: view.view,
+            projection,
+            view_projection: projection * view.view,
+            position: view.position.into(),
+            point_light_count: point_lights.len() as u32,
+            sun_direction: sun_direction.into(),
+            point_lights: point_lights_index,
+            sun_color: sun_color.into(),
+            _padding: 0,
+        };
+
+        let view_index = UploadBuffer::write(
+            &self.device,
+            &mut frame.view,
+            &[view_constants],
+            "Renderer View Constants",
+        )?;
+        let particles = self.particles.prepare(
+            &self.device,
+            &self.shader_manager,
+            world,
+            &mut frame.emitters,
+            &emitter_textures,
+            self.sampler.descriptor_index(),
+            view_index,
+        )?;
+
+        Ok(PreparedView {
+            device: self.device.clone(),
+            width,
+            height,
+            view: view_index,
+            instances: UploadBuffer::write(
+                &self.device,
+                &mut frame.instances,
+                &instances,
+                "Renderer Instances",
+            )?,
+            draws,
+            particles: particles.map(Arc::new),
+        })
+    }
+
+    fn asset<T: Asset>(&mut self, uuid: Uuid) -> Option<Arc<T>> {
+        let asset_manager = &self.asset_manager;
+        let asset = self.assets.entry(uuid).or_insert_with(|| {
+            if uuid.is_nil() {
+                return None;
+            }
+
+            match asset_manager.load_sync(&Path::parse(&format!("//{}", uuid)).unwrap()) {
+                Ok(asset) => Some(asset),
+                Err(error) => {
+                    ze_error!("Failed to load asset {}: {:?}", uuid, error);
+                    None
+                }
+            }
+        });
+
+        asset.clone()?.into_any_arc().downcast::<T>().ok()
+    }
+
+    /// Descriptor index of a texture asset, the fallback texture if it cannot be loaded
+    fn texture_index(&mut self, uuid: Uuid) -> u32 {
+        self.asset::<TextureAsset>(uuid)
+            .and_then(|texture| {
+                texture
+                    .default_srv()
+                    .as_ref()
+                    .map(|srv| srv.descriptor_index())
+            })
+            .unwrap_or_else(|| self.fallback_texture_srv.descriptor_index())
+    }
+
+    /// Descriptor index of the packed parameters of `material`
+    fn material_buffer(&mut self, material: &Material) -> Option<u32> {
+        let parameters = self.shader_manager.shader_parameters(material.shader())?;
+        if let Some(buffer) = self.materials.get(&material.uuid()) {
+            if buffer.parameters == parameters {
+                return Some(buffer.srv.descriptor_index());
+            }
+        }
+
+        let fallback = self.fallback_texture_srv.descriptor_index();
+        let mut textures = HashMap::new();
+        for value in material.parameters().values() {
+            if let MaterialParameter::Texture(uuid) = value {
+                textures.insert(*uuid, self.texture_index(*uuid));
+            }
+        }
+
+        let mut data = pack_parameters(
+            &parameters,
+            material.parameters(),
+            |uuid| {
+                uuid.and_then(|uuid| textures.get(uuid).copied())
+                    .unwrap_or(fallback)
+            },
+            self.sampler.descriptor_index(),
+        );
+
+        // Buffers cannot be empty
+        if data.is_empty() {
+            data.resize(4, 0);
+        }
+
+        match self.create_material_buffer(material, &data) {
+            Ok(srv) => {
+                let index = srv.descriptor_index();
+                self.materials
+                    .insert(material.uuid(), MaterialBuffer { parameters, srv });
+                Some(index)
+            }
+            Err(error) => {
+                ze_error!(
+                    "Failed to create the buffer of material {}: {:?}",
+                    material.uuid(),
+                    error
+                );
+                None
+            }
+        }
+    }
+
+    fn create_material_buffer(
+        &self,
+        material: &Material,
+        data: &[u8],
+    ) -> Result<ShaderResourceView, DeviceError> {
+        let buffer = self.device.create_buffer(
+            &BufferDesc {
+                size_bytes: data.len() as u64,
+                usage: BufferUsageFlags::default(),
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::GpuOnly,
+                    memory_flags: Default::default(),
+                },
+                default_resource_state: ResourceState::Common,
+            },
+            None,
+            &format!("{} Parameters", material.uuid()),
+        )?;
+        utils::copy_data_to_buffer(&self.device, &buffer, data, ResourceState::Common)?;
+
+        self.device
+            .create_shader_resource_view(&ShaderResourceViewDesc::Buffer(BufferSRV {
+                buffer: Arc::new(buffer),
+                ty: BufferSRVType::Raw(BufferSRVRaw { offset_in_bytes: 0 }),
+            }))
+    }
+}
+
+fn depth_stencil_state(write: bool, compare_op: CompareOp) -> PipelineDepthStencilState {
+    PipelineDepthStencilState {
+        depth_test_enable: true,
+        depth_write_mask: i32::from(write),
+        depth_write_enable: write,
+        depth_compare_op: compare_op,
+        stencil_test_enable: false,
+        stencil_read_mask: 0,
+        stencil_write_mask: 0,
+        front: Default::default(),
+        back: Default::default(),
+    }
+}
+
+/// Sort the instances by key and group the ones sharing a key
+///
+/// Returns the instance range of each key and the sorted instances
+fn batch_instances<K: Ord + Copy, I>(mut instances: Vec<(K, I)>) -> (Vec<(K, Range<u32>)>, Vec<I>) {
+    instances.sort_by_key(|(key, _)| *key);
+
+    let mut batches: Vec<(K, Range<u32>)> = vec![];
+    let mut sorted = Vec::with_capacity(instances.len());
+    for (key, instance) in instances {
+        let index = sorted.len() as u32;
+        match batches.last_mut() {
+            Some((last, range)) if *last == key => range.end = index + 1,
+            _ => batches.push((key, index..index + 1)),
+        }
+        sorted.push(instance);
+    }
+
+    (batches, sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::renderer::batch_instances;
+
+    #[test]
+    fn batch() {
+        let (batches, instances) = batch_instances(vec![
+            ((1, 2), 'a'),
+            ((0, 1), 'b'),
+            ((1, 2), 'c'),
+            ((1, 0), 'd'),
+        ]);
+        assert_eq!(
+            batches,
+            vec![((0, 1), 0..1), ((1, 0), 1..2), ((1, 2), 2..4)]
+        );
+        assert_eq!(instances, vec!['b', 'd', 'a', 'c']);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::mesh::as_bytes;
+use std::mem::size_of;
+use std::sync::Arc;
+use ze_gfx::backend::*;
+
+/// CPU visible structured buffer rewritten every frame
+pub(crate) struct UploadBuffer {
+    buffer: Arc<Buffer>,
+    srv: ShaderResourceView,
+    capacity: usize,
+}
+
+impl UploadBuffer {
+    /// Write `data` to the buffer of `slot`, recreating it if it is too small
+    ///
+    /// Returns the descriptor index of the buffer SRV
+    pub fn write<T: Copy>(
+        device: &Arc<dyn Device>,
+        slot: &mut Option<UploadBuffer>,
+        data: &[T],
+        name: &str,
+    ) -> Result<u32, DeviceError> {
+        let large_enough = matches!(slot, Some(buffer) if buffer.capacity >= data.len());
+        if !large_enough {
+            *slot = Some(Self::new::<T>(device, data.len().max(1), name)?);
+        }
+
+        let buffer = slot.as_ref().unwrap();
+        let ptr = device
+            .buffer_mapped_ptr(&buffer.buffer)
+            .ok_or(DeviceError::Unknown)?;
+        let bytes = as_bytes(data);
+
+        // SAFETY: The buffer holds at least `capacity` elements
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+        }
+
+        Ok(buffer.srv.descriptor_index())
+    }
+
+    fn new<T>(device: &Arc<dyn Device>, capacity: usize, name: &str) -> Result<Self, DeviceError> {
+        let buffer = Arc::new(device.create_buffer(
+            &BufferDesc {
+                size_bytes: (capacity * size_of::<T>()) as u64,
+                usage: BufferUsageFlags::default(),
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::CpuToGpu,
+                    memory_flags: Default::default(),
+                },
+                default_resource_state: ResourceState::Common,
+            },
+            None,
+            name,
+        )?);
+
+        let srv =
+            device.create_shader_resource_view(&ShaderResourceViewDesc::Buffer(BufferSRV {
+                buffer: buffer.clone(),
+                ty: BufferSRVType::Structured(BufferSRVStructured {
+                    offset_in_bytes: 0,
+                    stride_in_bytes: size_of::<T>() as u32,
+                }),
+            }))?;
+
+        Ok(Self {
+            buffer,
+            srv,
+            capacity,
+        })
+    }
+}
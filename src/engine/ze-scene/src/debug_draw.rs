@@ -0,0 +1,32 @@
+use nalgebra_glm as glm;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DebugLine {
+    pub start: glm::Vec3,
+    pub end: glm::Vec3,
+
+    /// RGBA color
+    pub color: [f32; 4],
+}
+
+/// World space lines drawn over the scene, used to visualize data like physics colliders
+///
+/// Lines are kept until [`DebugDraw::clear`] is called
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+}
+
+impl DebugDraw {
+    pub fn line(&mut self, start: glm::Vec3, end: glm::Vec3, color: [f32; 4]) {
+        self.lines.push(DebugLine { start, end, color });
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
@@ -0,0 +1,4 @@
+pub mod debug_draw;
+pub mod scene;
+pub mod transform;
+pub mod value;
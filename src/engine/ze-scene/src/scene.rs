@@ -0,0 +1,532 @@
+use crate::value::{diff, read_value, write_value, Value};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use ze_ecs::component::{Component, ComponentId};
+use ze_ecs::entity::Entity;
+use ze_ecs::hierarchy::Name;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_reflection::{Reflectable, TypeDescription};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidYaml(serde_yaml::Error),
+    InvalidBinary,
+
+    /// Component not registered in the [`SceneSerializer`]
+    UnknownComponent(String),
+    UnknownPrefab(String),
+
+    /// Value of the component doesn't match its type
+    InvalidValue(String),
+
+    /// Parent of an entity is not declared before it
+    InvalidParent,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Serialized hierarchy of entities
+///
+/// Saved as YAML by the editor and as binary when cooked
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct SceneData {
+    /// Entities in depth-first order, parents are always declared before their children
+    pub entities: Vec<EntityData>,
+}
+
+impl SceneData {
+    pub fn from_yaml(yaml: &str) -> Result<Self, Error> {
+        serde_yaml::from_str(yaml).map_err(Error::InvalidYaml)
+    }
+
+    pub fn to_yaml(&self) -> Result<String, Error> {
+        serde_yaml::to_string(self).map_err(Error::InvalidYaml)
+    }
+
+    pub fn from_binary(data: &[u8]) -> Result<Self, Error> {
+        bincode::serde::decode_from_slice(data, bincode::config::standard())
+            .map(|(scene, _)| scene)
+            .map_err(|_| Error::InvalidBinary)
+    }
+
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(|_| Error::InvalidBinary)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct EntityData {
+    /// Index of the parent in [`SceneData::entities`]
+    pub parent: Option<usize>,
+    pub name: Option<String>,
+
+    /// Set if this entity is the root of a prefab instance
+    pub prefab: Option<PrefabData>,
+
+    /// Components by type name
+    pub components: BTreeMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct PrefabData {
+    pub path: String,
+
+    /// Modified component fields, by index of the entity in the prefab scene
+    pub overrides: BTreeMap<usize, BTreeMap<String, Value>>,
+}
+
+/// Added on the entity holding a prefab instance, the prefab roots are its children
+#[derive(Component)]
+pub struct PrefabInstance {
+    path: String,
+
+    /// Instantiated entities, in the prefab scene order
+    entities: Vec<Entity>,
+}
+
+impl PrefabInstance {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+}
+
+/// Give access to the scenes used as prefabs
+pub trait PrefabProvider {
+    fn prefab(&self, path: &str) -> Option<&SceneData>;
+}
+
+impl PrefabProvider for HashMap<String, SceneData> {
+    fn prefab(&self, path: &str) -> Option<&SceneData> {
+        self.get(path)
+    }
+}
+
+struct SerializableComponent {
+    id: ComponentId,
+    type_desc: Arc<TypeDescription>,
+
+    /// Add the default value of the component to an entity if not present
+    add_default: fn(&mut World, Entity),
+}
+
+fn add_default<T: Component + Reflectable + Default>(world: &mut World, entity: Entity) {
+    world.register_reflectable::<T>();
+    if world.query::<&T>().get(world, entity).is_none() {
+        world.add(entity, T::default());
+    }
+}
+
+/// Convert the entities of a world to [`SceneData`] and back, using the reflection data of
+/// the registered components
+#[derive(Default)]
+pub struct SceneSerializer {
+    components: HashMap<String, SerializableComponent>,
+}
+
+impl SceneSerializer {
+    pub fn register<T: Component + Reflectable + Default>(&mut self) {
+        let type_desc = T::type_desc();
+        self.components.insert(
+            type_desc.name().to_string(),
+            SerializableComponent {
+                id: T::component_id(),
+                type_desc,
+                add_default: add_default::<T>,
+            },
+        );
+    }
+
+    /// Serialize all the entities of `world`
+    ///
+    /// Entities of prefab instances are only saved as overrides of their prefab
+    pub fn serialize(
+        &self,
+        world: &World,
+        prefabs: &impl PrefabProvider,
+    ) -> Result<SceneData, Error> {
+        let mut roots = vec![];
+        world
+            .query::<Entity>()
+            .for_each(world, |entity| roots.push(entity));
+        roots.retain(|entity| world.parent(*entity).is_none());
+        roots.sort();
+
+        let mut scene = SceneData::default();
+        for root in roots {
+            self.serialize_entity(world, prefabs, root, None, &mut scene)?;
+        }
+
+        Ok(scene)
+    }
+
+    fn serialize_entity(
+        &self,
+        world: &World,
+        prefabs: &impl PrefabProvider,
+        entity: Entity,
+        parent: Option<usize>,
+        scene: &mut SceneData,
+    ) -> Result<(), Error> {
+        let index = scene.entities.len();
+        let instance = world.query::<&PrefabInstance>().get(world, entity);
+        let prefab = match instance {
+            Some(instance) => Some(self.serialize_prefab_instance(world, prefabs, instance)?),
+            None => None,
+        };
+
+        scene.entities.push(EntityData {
+            parent,
+            name: world
+                .query::<&Name>()
+                .get(world, entity)
+                .map(|name| name.0.clone()),
+            prefab,
+            components: self.components(world, entity),
+        });
+
+        for child in world.children(entity) {
+            if matches!(instance, Some(instance) if instance.entities.contains(&child)) {
+                continue;
+            }
+
+            self.serialize_entity(world, prefabs, child, Some(index), scene)?;
+        }
+
+        Ok(())
+    }
+
+    fn serialize_prefab_instance(
+        &self,
+        world: &World,
+        prefabs: &impl PrefabProvider,
+        instance: &PrefabInstance,
+    ) -> Result<PrefabData, Error> {
+        let prefab = prefabs
+            .prefab(&instance.path)
+            .ok_or_else(|| Error::UnknownPrefab(instance.path.clone()))?;
+
+        let mut overrides = BTreeMap::new();
+        for (index, (entity, data)) in instance.entities.iter().zip(&prefab.entities).enumerate() {
+            if !world.is_valid(*entity) {
+                continue;
+            }
+
+            let components = self
+                .components(world, *entity)
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let value = match data.components.get(&name) {
+                        Some(base) => diff(base, &value),
+                        None => Some(value),
+                    };
+                    value.map(|value| (name, value))
+                })
+                .collect::<BTreeMap<_, _>>();
+
+            if !components.is_empty() {
+                overrides.insert(index, components);
+            }
+        }
+
+        Ok(PrefabData {
+            path: instance.path.clone(),
+            overrides,
+        })
+    }
+
+    /// Names of the registered components
+    pub fn component_names(&self) -> impl Iterator<Item = &str> {
+        self.components.keys().map(|name| name.as_str())
+    }
+
+    /// Value of the registered component `name` of `entity`
+    pub fn component(&self, world: &World, entity: Entity, name: &str) -> Option<Value> {
+        let component = self.components.get(name)?;
+        let ptr = world.component_ptr(entity, component.id)?;
+
+        // SAFETY: The component has been registered with its own type description
+        Some(unsafe { read_value(ptr.as_ptr(), &component.type_desc) })
+    }
+
+    /// Values of the registered components of `entity`
+    pub fn components(&self, world: &World, entity: Entity) -> BTreeMap<String, Value> {
+        self.components
+            .keys()
+            .filter_map(|name| {
+                self.component(world, entity, name)
+                    .map(|value| (name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Spawn the entities of `scene` in `world`, returns them in the scene order
+    ///
+    /// Entities spawned before an error are not destroyed
+    pub fn instantiate(
+        &self,
+        world: &mut World,
+        scene: &SceneData,
+        prefabs: &impl PrefabProvider,
+    ) -> Result<Vec<Entity>, Error> {
+        let mut entities: Vec<Entity> = Vec::with_capacity(scene.entities.len());
+        for data in &scene.entities {
+            let parent = match data.parent {
+                Some(parent) => Some(*entities.get(parent).ok_or(Error::InvalidParent)?),
+                None => None,
+            };
+
+            let entity = world.spawn();
+            entities.push(entity);
+            if parent.is_some() {
+                world.set_parent(entity, parent);
+            }
+
+            if let Some(name) = &data.name {
+                world.add(entity, Name(name.clone()));
+            }
+
+            if let Some(prefab) = &data.prefab {
+                self.instantiate_prefab(world, entity, prefab, prefabs)?;
+            }
+
+            self.apply_components(world, entity, &data.components)?;
+        }
+
+        Ok(entities)
+    }
+
+    fn instantiate_prefab(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        prefab: &PrefabData,
+        prefabs: &impl PrefabProvider,
+    ) -> Result<(), Error> {
+        let scene = prefabs
+            .prefab(&prefab.path)
+            .ok_or_else(|| Error::UnknownPrefab(prefab.path.clone()))?;
+
+        let instance = self.instantiate(world, scene, prefabs)?;
+        for (index, components) in &prefab.overrides {
+            let prefab_entity = *instance
+                .get(*index)
+                .ok_or_else(|| Error::InvalidValue(prefab.path.clone()))?;
+            self.apply_components(world, prefab_entity, components)?;
+        }
+
+        for (prefab_entity, data) in instance.iter().zip(&scene.entities) {
+            if data.parent.is_none() {
+                world.set_parent(*prefab_entity, Some(entity));
+            }
+        }
+
+        world.add(
+            entity,
+            PrefabInstance {
+                path: prefab.path.clone(),
+                entities: instance,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Add the components to `entity`, partial values only modify the fields they contain
+    pub fn apply_components(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        components: &BTreeMap<String, Value>,
+    ) -> Result<(), Error> {
+        for (name, value) in components {
+            let component = self
+                .components
+                .get(name)
+                .ok_or_else(|| Error::UnknownComponent(name.clone()))?;
+
+            (component.add_default)(world, entity);
+            let ptr = world
+                .component_ptr_mut(entity, component.id)
+                .expect("Component should have been added");
+
+            // SAFETY: The component has been registered with its own type description
+            if !unsafe { write_value(ptr.as_ptr(), &component.type_desc, value) } {
+                return Err(Error::InvalidValue(name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scene::{PrefabData, PrefabInstance, SceneData, SceneSerializer};
+    use crate::transform::Transform;
+    use crate::value::{AssetRef, Value};
+    use nalgebra_glm as glm;
+    use std::collections::{BTreeMap, HashMap};
+    use uuid::Uuid;
+    use ze_ecs::component::Component;
+    use ze_ecs::entity::Entity;
+    use ze_ecs::hierarchy::Name;
+    use ze_ecs::world::World;
+    use ze_ecs::Component;
+    use ze_reflection::*;
+
+    #[derive(Component, Reflectable, Clone, Default, PartialEq, Debug)]
+    struct Mesh {
+        #[ze_reflect]
+        mesh: AssetRef,
+
+        #[ze_reflect]
+        lod_bias: i32,
+
+        #[ze_reflect]
+        cast_shadows: bool,
+    }
+
+    fn serializer() -> SceneSerializer {
+        let mut serializer = SceneSerializer::default();
+        serializer.register::<Transform>();
+        serializer.register::<Mesh>();
+        serializer
+    }
+
+    fn get<T: Component + Clone>(world: &World, entity: Entity) -> Option<T> {
+        world.query::<&T>().get(world, entity).cloned()
+    }
+
+    fn build_world() -> (World, Entity, Entity) {
+        let mut world = World::default();
+        let root = world.spawn();
+        world.add(root, Name("Root".to_string()));
+        world.add(root, Transform::from_translation(glm::vec3(1.0, 2.0, 3.0)));
+
+        let child = world.spawn();
+        world.add(child, Name("Child".to_string()));
+        world.add(
+            child,
+            Mesh {
+                mesh: AssetRef::new(Uuid::from_u128(42)),
+                lod_bias: -2,
+                cast_shadows: true,
+            },
+        );
+        world.set_parent(child, Some(root));
+        (world, root, child)
+    }
+
+    fn check_world(world: &World, entities: &[Entity]) {
+        assert_eq!(entities.len(), 2);
+        let (root, child) = (entities[0], entities[1]);
+        assert_eq!(get::<Name>(world, root).unwrap().0, "Root");
+        assert_eq!(
+            get::<Transform>(world, root).unwrap().translation,
+            glm::vec3(1.0, 2.0, 3.0)
+        );
+        assert!(world.parent(child) == Some(root));
+        assert_eq!(
+            get::<Mesh>(world, child),
+            Some(Mesh {
+                mesh: AssetRef::new(Uuid::from_u128(42)),
+                lod_bias: -2,
+                cast_shadows: true,
+            })
+        );
+    }
+
+    #[test]
+    fn yaml_roundtrip() {
+        let serializer = serializer();
+        let prefabs = HashMap::new();
+        let (world, _, _) = build_world();
+        let scene = serializer.serialize(&world, &prefabs).unwrap();
+        let scene = SceneData::from_yaml(&scene.to_yaml().unwrap()).unwrap();
+
+        let mut world = World::default();
+        let entities = serializer
+            .instantiate(&mut world, &scene, &prefabs)
+            .unwrap();
+        check_world(&world, &entities);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let serializer = serializer();
+        let prefabs = HashMap::new();
+        let (world, _, _) = build_world();
+        let scene = serializer.serialize(&world, &prefabs).unwrap();
+        let loaded = SceneData::from_binary(&scene.to_binary().unwrap()).unwrap();
+        assert_eq!(scene, loaded);
+
+        let mut world = World::default();
+        let entities = serializer
+            .instantiate(&mut world, &loaded, &prefabs)
+            .unwrap();
+        check_world(&world, &entities);
+    }
+
+    #[test]
+    fn prefab_overrides() {
+        let serializer = serializer();
+        let (world, _, _) = build_world();
+        let mut prefabs = HashMap::new();
+        prefabs.insert(
+            "prefab".to_string(),
+            serializer.serialize(&world, &HashMap::new()).unwrap(),
+        );
+
+        let mut lod_bias = BTreeMap::new();
+        lod_bias.insert("lod_bias".to_string(), Value::Int(5));
+        let mut components = BTreeMap::new();
+        components.insert(
+            std::any::type_name::<Mesh>().to_string(),
+            Value::Struct(lod_bias),
+        );
+        let mut overrides = BTreeMap::new();
+        overrides.insert(1, components);
+
+        let mut scene = SceneData::default();
+        scene.entities.push(Default::default());
+        scene.entities[0].prefab = Some(PrefabData {
+            path: "prefab".to_string(),
+            overrides: overrides.clone(),
+        });
+
+        let mut world = World::default();
+        let entities = serializer
+            .instantiate(&mut world, &scene, &prefabs)
+            .unwrap();
+        let instance = world
+            .query::<&PrefabInstance>()
+            .get(&world, entities[0])
+            .unwrap()
+            .entities()
+            .to_vec();
+        assert!(world.parent(instance[0]) == Some(entities[0]));
+        let mesh = get::<Mesh>(&world, instance[1]).unwrap();
+        assert_eq!(mesh.lod_bias, 5);
+        assert!(mesh.cast_shadows);
+
+        // Only the overrides are saved, prefab entities are not duplicated
+        let saved = serializer.serialize(&world, &prefabs).unwrap();
+        assert_eq!(saved.entities.len(), 1);
+        assert_eq!(
+            saved.entities[0].prefab.as_ref().unwrap().overrides,
+            overrides
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use nalgebra_glm as glm;
+use once_cell::sync::Lazy;
+use std::mem::{align_of, size_of};
+use std::sync::Arc;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_reflection::{
+    Field, MetaAttributeList, Reflectable, StructDescription, TypeDataDescription, TypeDescription,
+};
+
+/// Position, rotation and scale of an entity, relative to its parent
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Transform {
+    pub translation: glm::Vec3,
+    pub rotation: glm::Quat,
+    pub scale: glm::Vec3,
+}
+
+impl Transform {
+    pub fn from_translation(translation: glm::Vec3) -> Self {
+        Self {
+            translation,
+            ..Default::default()
+        }
+    }
+
+    /// Local to parent matrix
+    pub fn matrix(&self) -> glm::Mat4 {
+        glm::translation(&self.translation)
+            * glm::quat_to_mat4(&self.rotation)
+            * glm::scaling(&self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: glm::Vec3::zeros(),
+            rotation: glm::Quat::identity(),
+            scale: glm::Vec3::repeat(1.0),
+        }
+    }
+}
+
+/// Describe a glm vector as a struct of `f32` fields
+fn vector_desc<T>(name: &str, fields: &[&str]) -> Arc<TypeDescription> {
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            Field::new(
+                field.to_string(),
+                index * size_of::<f32>(),
+                f32::type_desc(),
+                MetaAttributeList::new(vec![]),
+            )
+        })
+        .collect();
+
+    Arc::new(TypeDescription::new(
+        name.to_string(),
+        size_of::<T>(),
+        align_of::<T>(),
+        TypeDataDescription::Struct(StructDescription::new(fields)),
+    ))
+}
+
+static VEC3_DESC: Lazy<Arc<TypeDescription>> =
+    Lazy::new(|| vector_desc::<glm::Vec3>("Vec3", &["x", "y", "z"]));
+
+/// Quaternions are stored as (i, j, k, w)
+static QUAT_DESC: Lazy<Arc<TypeDescription>> =
+    Lazy::new(|| vector_desc::<glm::Quat>("Quat", &["x", "y", "z", "w"]));
+
+impl Reflectable for Transform {
+    fn type_desc() -> Arc<TypeDescription> {
+        TypeDescription::get_or_create::<Transform, _>(|| {
+            let field = |name: &str, offset: usize, ty: &Arc<TypeDescription>| {
+                Field::new(
+                    name.to_string(),
+                    offset,
+                    ty.clone(),
+                    MetaAttributeList::new(vec![]),
+                )
+            };
+
+            TypeDescription::new(
+                std::any::type_name::<Transform>().to_string(),
+                size_of::<Transform>(),
+                align_of::<Transform>(),
+                TypeDataDescription::Struct(StructDescription::new(vec![
+                    field(
+                        "translation",
+                        ze_reflection::ze_reflection_offset_of!(Transform, translation),
+                        &VEC3_DESC,
+                    ),
+                    field(
+                        "rotation",
+                        ze_reflection::ze_reflection_offset_of!(Transform, rotation),
+                        &QUAT_DESC,
+                    ),
+                    field(
+                        "scale",
+                        ze_reflection::ze_reflection_offset_of!(Transform, scale),
+                        &VEC3_DESC,
+                    ),
+                ])),
+            )
+        })
+    }
+}
+
+/// Local to world matrix of `entity`, entities without a transform are considered at the origin
+pub fn global_matrix(world: &World, entity: Entity) -> glm::Mat4 {
+    let local = world
+        .query::<&Transform>()
+        .get(world, entity)
+        .map_or(glm::Mat4::identity(), |transform| transform.matrix());
+
+    match world.parent(entity) {
+        Some(parent) => global_matrix(world, parent) * local,
+        None => local,
+    }
+}
@@ -0,0 +1,198 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::mem::{align_of, size_of};
+use std::sync::Arc;
+use uuid::Uuid;
+use ze_reflection::{
+    PrimitiveType, Reflectable, StructDescription, TypeDataDescription, TypeDescription,
+};
+
+/// Reference to an asset, serialized as its UUID
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct AssetRef {
+    pub uuid: Uuid,
+}
+
+impl AssetRef {
+    pub fn new(uuid: Uuid) -> Self {
+        Self { uuid }
+    }
+}
+
+impl Reflectable for AssetRef {
+    fn type_desc() -> Arc<TypeDescription> {
+        TypeDescription::get_or_create::<AssetRef, _>(|| {
+            TypeDescription::new(
+                std::any::type_name::<AssetRef>().to_string(),
+                size_of::<AssetRef>(),
+                align_of::<AssetRef>(),
+                TypeDataDescription::Struct(StructDescription::default()),
+            )
+        })
+    }
+}
+
+/// Serialized value of a reflected type
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum Value {
+    Bool(bool),
+    Char(char),
+    Int(i128),
+    UInt(u128),
+    Float(f64),
+
+    /// Name of the enum variant
+    Enum(String),
+    Asset(Uuid),
+
+    /// Fields of a struct, may only contain a subset of the fields when used as an override
+    Struct(BTreeMap<String, Value>),
+}
+
+/// Read the value of type `type_desc` at `ptr`
+///
+/// # Safety
+///
+/// `ptr` must point to a valid instance of `type_desc`
+pub unsafe fn read_value(ptr: *const u8, type_desc: &Arc<TypeDescription>) -> Value {
+    if Arc::ptr_eq(type_desc, &AssetRef::type_desc()) {
+        return Value::Asset((*(ptr as *const AssetRef)).uuid);
+    }
+
+    match type_desc.data() {
+        TypeDataDescription::Primitive(primitive) => match primitive {
+            PrimitiveType::Char => Value::Char(*(ptr as *const char)),
+            PrimitiveType::Bool => Value::Bool(*(ptr as *const bool)),
+            PrimitiveType::U8 => Value::UInt(*ptr as u128),
+            PrimitiveType::U16 => Value::UInt(*(ptr as *const u16) as u128),
+            PrimitiveType::U32 => Value::UInt(*(ptr as *const u32) as u128),
+            PrimitiveType::U64 => Value::UInt(*(ptr as *const u64) as u128),
+            PrimitiveType::U128 => Value::UInt(*(ptr as *const u128)),
+            PrimitiveType::USize => Value::UInt(*(ptr as *const usize) as u128),
+            PrimitiveType::I8 => Value::Int(*(ptr as *const i8) as i128),
+            PrimitiveType::I16 => Value::Int(*(ptr as *const i16) as i128),
+            PrimitiveType::I32 => Value::Int(*(ptr as *const i32) as i128),
+            PrimitiveType::I64 => Value::Int(*(ptr as *const i64) as i128),
+            PrimitiveType::I128 => Value::Int(*(ptr as *const i128)),
+            PrimitiveType::ISize => Value::Int(*(ptr as *const isize) as i128),
+            PrimitiveType::F32 => Value::Float(*(ptr as *const f32) as f64),
+            PrimitiveType::F64 => Value::Float(*(ptr as *const f64)),
+        },
+        TypeDataDescription::Struct(desc) => Value::Struct(
+            desc.fields()
+                .iter()
+                .map(|field| {
+                    (
+                        field.name().to_string(),
+                        read_value(ptr.add(field.offset_in_bytes()), field.ty()),
+                    )
+                })
+                .collect(),
+        ),
+        TypeDataDescription::Enum(desc) => Value::Enum(
+            desc.variant_of_ptr(ptr)
+                .map(|variant| variant.name().to_string())
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Write `value` to the instance of `type_desc` at `ptr`
+///
+/// Struct fields missing from `value` are left untouched, unknown fields are ignored
+/// Returns false if `value` doesn't match the type
+///
+/// # Safety
+///
+/// `ptr` must point to a valid instance of `type_desc`
+pub unsafe fn write_value(ptr: *mut u8, type_desc: &Arc<TypeDescription>, value: &Value) -> bool {
+    if Arc::ptr_eq(type_desc, &AssetRef::type_desc()) {
+        return match value {
+            Value::Asset(uuid) => {
+                (*(ptr as *mut AssetRef)).uuid = *uuid;
+                true
+            }
+            _ => false,
+        };
+    }
+
+    match (type_desc.data(), value) {
+        (TypeDataDescription::Primitive(primitive), value) => {
+            write_primitive(ptr, primitive, value)
+        }
+        (TypeDataDescription::Struct(desc), Value::Struct(fields)) => {
+            desc.fields()
+                .iter()
+                .all(|field| match fields.get(field.name()) {
+                    Some(value) => write_value(ptr.add(field.offset_in_bytes()), field.ty(), value),
+                    None => true,
+                })
+        }
+        (TypeDataDescription::Enum(desc), Value::Enum(name)) => {
+            match desc
+                .variants()
+                .iter()
+                .find(|variant| variant.name() == name)
+            {
+                Some(variant) => {
+                    desc.set_variant_of_ptr(ptr, variant.discriminant());
+                    true
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+unsafe fn write_primitive(ptr: *mut u8, primitive: &PrimitiveType, value: &Value) -> bool {
+    match (primitive, value) {
+        (PrimitiveType::Char, Value::Char(value)) => *(ptr as *mut char) = *value,
+        (PrimitiveType::Bool, Value::Bool(value)) => *(ptr as *mut bool) = *value,
+        (PrimitiveType::U8, Value::UInt(value)) => *ptr = *value as u8,
+        (PrimitiveType::U16, Value::UInt(value)) => *(ptr as *mut u16) = *value as u16,
+        (PrimitiveType::U32, Value::UInt(value)) => *(ptr as *mut u32) = *value as u32,
+        (PrimitiveType::U64, Value::UInt(value)) => *(ptr as *mut u64) = *value as u64,
+        (PrimitiveType::U128, Value::UInt(value)) => *(ptr as *mut u128) = *value,
+        (PrimitiveType::USize, Value::UInt(value)) => *(ptr as *mut usize) = *value as usize,
+        (PrimitiveType::I8, Value::Int(value)) => *(ptr as *mut i8) = *value as i8,
+        (PrimitiveType::I16, Value::Int(value)) => *(ptr as *mut i16) = *value as i16,
+        (PrimitiveType::I32, Value::Int(value)) => *(ptr as *mut i32) = *value as i32,
+        (PrimitiveType::I64, Value::Int(value)) => *(ptr as *mut i64) = *value as i64,
+        (PrimitiveType::I128, Value::Int(value)) => *(ptr as *mut i128) = *value,
+        (PrimitiveType::ISize, Value::Int(value)) => *(ptr as *mut isize) = *value as isize,
+        (PrimitiveType::F32, Value::Float(value)) => *(ptr as *mut f32) = *value as f32,
+        (PrimitiveType::F64, Value::Float(value)) => *(ptr as *mut f64) = *value,
+        _ => return false,
+    }
+
+    true
+}
+
+/// Part of `value` that differs from `base`, only containing the modified fields of structs
+///
+/// Returns None if both values are equal
+pub fn diff(base: &Value, value: &Value) -> Option<Value> {
+    match (base, value) {
+        (Value::Struct(base), Value::Struct(fields)) => {
+            let fields = fields
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = match base.get(name) {
+                        Some(base) => diff(base, value),
+                        None => Some(value.clone()),
+                    };
+                    value.map(|value| (name.clone(), value))
+                })
+                .collect::<BTreeMap<_, _>>();
+
+            if fields.is_empty() {
+                None
+            } else {
+                Some(Value::Struct(fields))
+            }
+        }
+        _ if base == value => None,
+        _ => Some(value.clone()),
+    }
+}
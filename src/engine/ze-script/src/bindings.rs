@@ -0,0 +1,152 @@
+use mlua::{Lua, Scope, Table};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_scene::scene::SceneSerializer;
+use ze_scene::value::Value;
+
+/// Entities are given to scripts as integers containing their id and generation
+pub(crate) fn entity_to_lua(entity: Entity) -> i64 {
+    ((entity.generation() as i64) << 32) | entity.id() as i64
+}
+
+pub(crate) fn entity_from_lua(world: &World, value: i64) -> mlua::Result<Entity> {
+    let generation = (value >> 32) as u32;
+    world
+        .try_entity(value as u32)
+        .filter(|entity| entity.generation() == generation)
+        .ok_or_else(|| mlua::Error::RuntimeError("Invalid entity".to_string()))
+}
+
+pub(crate) fn value_to_lua<'lua>(lua: &'lua Lua, value: &Value) -> mlua::Result<mlua::Value<'lua>> {
+    Ok(match value {
+        Value::Bool(value) => mlua::Value::Boolean(*value),
+        Value::Char(value) => mlua::Value::String(lua.create_string(value.to_string())?),
+        Value::Int(value) => mlua::Value::Integer(*value as i64),
+        Value::UInt(value) => mlua::Value::Integer(*value as i64),
+        Value::Float(value) => mlua::Value::Number(*value),
+        Value::Enum(value) => mlua::Value::String(lua.create_string(value)?),
+        Value::Asset(uuid) => mlua::Value::String(lua.create_string(uuid.to_string())?),
+        Value::Struct(fields) => {
+            let table = lua.create_table()?;
+            for (name, value) in fields {
+                table.set(name.as_str(), value_to_lua(lua, value)?)?;
+            }
+            mlua::Value::Table(table)
+        }
+    })
+}
+
+/// Convert a Lua value to a [`Value`] of the same type as `template`
+///
+/// Tables can only contain a subset of the fields of a struct, missing fields are left
+/// untouched when written
+pub(crate) fn value_from_lua(value: mlua::Value, template: &Value) -> mlua::Result<Value> {
+    let invalid = || mlua::Error::RuntimeError(format!("Expected a value like {:?}", template));
+    Ok(match (template, value) {
+        (Value::Bool(_), mlua::Value::Boolean(value)) => Value::Bool(value),
+        (Value::Char(_), mlua::Value::String(value)) => {
+            let mut chars = value.to_str()?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(char), None) => Value::Char(char),
+                _ => return Err(invalid()),
+            }
+        }
+        (Value::Int(_), mlua::Value::Integer(value)) => Value::Int(value as i128),
+        (Value::UInt(_), mlua::Value::Integer(value)) if value >= 0 => Value::UInt(value as u128),
+        (Value::Float(_), mlua::Value::Number(value)) => Value::Float(value),
+        (Value::Float(_), mlua::Value::Integer(value)) => Value::Float(value as f64),
+        (Value::Enum(_), mlua::Value::String(value)) => Value::Enum(value.to_str()?.to_string()),
+        (Value::Asset(_), mlua::Value::String(value)) => {
+            Value::Asset(Uuid::parse_str(value.to_str()?).map_err(|_| invalid())?)
+        }
+        (Value::Struct(template), mlua::Value::Table(table)) => {
+            let mut fields = BTreeMap::new();
+            for pair in table.pairs::<String, mlua::Value>() {
+                let (name, value) = pair?;
+                let template = template
+                    .get(&name)
+                    .ok_or_else(|| mlua::Error::RuntimeError(format!("Unknown field {}", name)))?;
+                fields.insert(name, value_from_lua(value, template)?);
+            }
+            Value::Struct(fields)
+        }
+        _ => return Err(invalid()),
+    })
+}
+
+/// Create a global table for each component registered in `serializer`, named after the
+/// last segment of its type name
+///
+/// - `Component.get(entity)` returns the component as a table or nil
+/// - `Component.set(entity, table)` adds the component if missing and writes the given fields
+pub(crate) fn register_components<'lua, 'scope>(
+    lua: &'lua Lua,
+    scope: &Scope<'lua, 'scope>,
+    serializer: &'scope SceneSerializer,
+    world: &'scope RefCell<&mut World>,
+) -> mlua::Result<()>
+where
+    'lua: 'scope,
+{
+    for name in serializer.component_names() {
+        let table: Table = lua.create_table()?;
+        table.set(
+            "get",
+            scope.create_function(move |lua, entity: i64| {
+                let world = world.borrow();
+                let entity = entity_from_lua(&world, entity)?;
+                match serializer.component(&world, entity, name) {
+                    Some(value) => value_to_lua(lua, &value),
+                    None => Ok(mlua::Value::Nil),
+                }
+            })?,
+        )?;
+        table.set(
+            "set",
+            scope.create_function(move |_, (entity, value): (i64, mlua::Value)| {
+                let mut world = world.borrow_mut();
+                let entity = entity_from_lua(&world, entity)?;
+
+                // Writing an empty struct adds the default value of the component
+                let current = match serializer.component(&world, entity, name) {
+                    Some(current) => current,
+                    None => {
+                        set_component(
+                            serializer,
+                            &mut world,
+                            entity,
+                            name,
+                            Value::Struct(BTreeMap::new()),
+                        )?;
+                        serializer
+                            .component(&world, entity, name)
+                            .expect("Component should have been added")
+                    }
+                };
+
+                let value = value_from_lua(value, &current)?;
+                set_component(serializer, &mut world, entity, name, value)
+            })?,
+        )?;
+
+        let short_name = name.rsplit("::").next().unwrap_or(name);
+        lua.globals().set(short_name, table)?;
+    }
+
+    Ok(())
+}
+
+fn set_component(
+    serializer: &SceneSerializer,
+    world: &mut World,
+    entity: Entity,
+    name: &str,
+    value: Value,
+) -> mlua::Result<()> {
+    serializer
+        .apply_components(world, entity, &BTreeMap::from([(name.to_string(), value)]))
+        .map_err(|error| mlua::Error::RuntimeError(error.to_string()))
+}
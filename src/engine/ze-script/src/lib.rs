@@ -0,0 +1,323 @@
+use crate::bindings::{entity_to_lua, register_components};
+use mlua::{Function, Lua, RegistryKey, Table};
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::Read;
+use std::sync::Arc;
+use ze_core::{ze_error, ze_info, ze_warn};
+use ze_ecs::entity::Entity;
+use ze_ecs::world::World;
+use ze_ecs::Component;
+use ze_filesystem::path::Path;
+use ze_filesystem::{FileSystem, WatchEvent};
+use ze_scene::scene::SceneSerializer;
+
+mod bindings;
+
+#[derive(Debug)]
+pub enum Error {
+    FileSystem(ze_filesystem::Error),
+    Io(std::io::Error),
+    Lua(mlua::Error),
+
+    /// Script doesn't return a table
+    InvalidScript,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Run the Lua script at `path` on the entity
+///
+/// Scripts return a table whose functions are called with a per-entity instance as `self`:
+/// - `start(self, entity)` when the script is first run on the entity
+/// - `update(self, entity, delta_time)` every [`ScriptRuntime::update`]
+#[derive(Component, Clone)]
+pub struct Script {
+    pub path: Path,
+}
+
+impl Script {
+    pub fn new(path: Path) -> Self {
+        Self { path }
+    }
+}
+
+struct ScriptInstance {
+    path: Path,
+    table: RegistryKey,
+}
+
+/// Lua state running the [`Script`] components of a world
+///
+/// Components registered in the serializer are exposed to scripts as global tables named after
+/// their type (e.g `Transform.get(entity)`, `Transform.set(entity, { translation = ... })`)
+/// Scripts are reloaded when modified, instances keep their state and use the new functions
+pub struct ScriptRuntime {
+    lua: Lua,
+    filesystem: Arc<FileSystem>,
+    serializer: SceneSerializer,
+
+    /// Metatable shared by the instances of each script, its `__index` is the script table
+    modules: HashMap<Path, RegistryKey>,
+    instances: HashMap<Entity, ScriptInstance>,
+
+    /// Scripts modified since the last update, filled by the filesystem watcher
+    modified: Arc<Mutex<Vec<Path>>>,
+}
+
+impl ScriptRuntime {
+    pub fn new(filesystem: Arc<FileSystem>, serializer: SceneSerializer) -> Result<Self, Error> {
+        let lua = Lua::new();
+        let print = lua
+            .create_function(|_, message: String| {
+                ze_info!("{}", message);
+                Ok(())
+            })
+            .map_err(Error::Lua)?;
+        lua.globals().set("print", print).map_err(Error::Lua)?;
+
+        Ok(Self {
+            lua,
+            filesystem,
+            serializer,
+            modules: HashMap::new(),
+            instances: HashMap::new(),
+            modified: Default::default(),
+        })
+    }
+
+    /// Load or reload the script at `path`
+    ///
+    /// The script is watched for modifications once loaded, even if it contains errors
+    pub fn load(&mut self, path: &Path) -> Result<(), Error> {
+        let metatable = match self.modules.get(path) {
+            Some(metatable) => self.lua.registry_value::<Table>(metatable),
+            None => {
+                self.watch(path);
+                let metatable = self.lua.create_table().map_err(Error::Lua)?;
+                self.modules.insert(
+                    path.clone(),
+                    self.lua
+                        .create_registry_value(metatable.clone())
+                        .map_err(Error::Lua)?,
+                );
+                Ok(metatable)
+            }
+        }
+        .map_err(Error::Lua)?;
+
+        let mut source = String::new();
+        self.filesystem
+            .read(path)
+            .map_err(Error::FileSystem)?
+            .read_to_string(&mut source)
+            .map_err(Error::Io)?;
+
+        let module = match self
+            .lua
+            .load(&source)
+            .set_name(path.as_str())
+            .eval::<mlua::Value>()
+            .map_err(Error::Lua)?
+        {
+            mlua::Value::Table(module) => module,
+            _ => return Err(Error::InvalidScript),
+        };
+
+        metatable.set("__index", module).map_err(Error::Lua)
+    }
+
+    /// Reload the modified scripts and run the scripts of `world`
+    ///
+    /// Script errors are logged, a script failing on an entity doesn't stop the other ones
+    pub fn update(&mut self, world: &mut World, delta_time: f32) {
+        let modified = std::mem::take(&mut *self.modified.lock());
+        for path in modified {
+            match self.load(&path) {
+                Ok(()) => ze_info!("Reloaded script {}", path.as_str()),
+                Err(error) => ze_error!("Failed to reload script {}: {}", path.as_str(), error),
+            }
+        }
+
+        let mut scripts = BTreeMap::new();
+        world
+            .query::<(Entity, &Script)>()
+            .for_each(world, |(entity, script)| {
+                scripts.insert(entity, script.path.clone());
+            });
+
+        self.instances.retain(
+            |entity, instance| matches!(scripts.get(entity), Some(path) if *path == instance.path),
+        );
+        self.lua.expire_registry_values();
+
+        for path in scripts.values() {
+            if !self.modules.contains_key(path) {
+                if let Err(error) = self.load(path) {
+                    ze_error!("Failed to load script {}: {}", path.as_str(), error);
+                }
+            }
+        }
+
+        let Self {
+            lua,
+            serializer,
+            modules,
+            instances,
+            ..
+        } = self;
+        let world = RefCell::new(world);
+        let result = lua.scope(|scope| {
+            register_components(lua, scope, serializer, &world)?;
+            for (entity, path) in &scripts {
+                if let Err(error) = run(lua, modules, instances, *entity, path, delta_time) {
+                    ze_error!("Script {} failed: {}", path.as_str(), error);
+                }
+            }
+            Ok(())
+        });
+
+        if let Err(error) = result {
+            ze_error!("Failed to create script bindings: {}", error);
+        }
+    }
+
+    fn watch(&self, path: &Path) {
+        let modified = self.modified.clone();
+        let result = self.filesystem.watch(path, move |event| {
+            if let WatchEvent::Write(path) = event {
+                modified.lock().push(path);
+            }
+        });
+
+        if let Err(error) = result {
+            ze_warn!("Cannot watch script {}: {}", path.as_str(), error);
+        }
+    }
+}
+
+/// Run the script on `entity`, creating its instance if needed
+fn run(
+    lua: &Lua,
+    modules: &HashMap<Path, RegistryKey>,
+    instances: &mut HashMap<Entity, ScriptInstance>,
+    entity: Entity,
+    path: &Path,
+    delta_time: f32,
+) -> mlua::Result<()> {
+    let instance = match instances.get(&entity) {
+        Some(instance) => lua.registry_value::<Table>(&instance.table)?,
+        None => {
+            let instance = lua.create_table()?;
+            instance.set_metatable(Some(lua.registry_value::<Table>(&modules[path])?));
+            instances.insert(
+                entity,
+                ScriptInstance {
+                    path: path.clone(),
+                    table: lua.create_registry_value(instance.clone())?,
+                },
+            );
+
+            if let Some(start) = instance.get::<_, Option<Function>>("start")? {
+                start.call::<_, ()>((instance.clone(), entity_to_lua(entity)))?;
+            }
+            instance
+        }
+    };
+
+    if let Some(update) = instance.get::<_, Option<Function>>("update")? {
+        update.call::<_, ()>((instance, entity_to_lua(entity), delta_time))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Script, ScriptRuntime};
+    use std::io::Write;
+    use ze_ecs::world::World;
+    use ze_filesystem::mount_points::StdMountPoint;
+    use ze_filesystem::path::Path;
+    use ze_filesystem::FileSystem;
+    use ze_scene::scene::SceneSerializer;
+    use ze_scene::transform::Transform;
+
+    const MOVE_SCRIPT: &str = r#"
+        local Mover = {}
+
+        function Mover:start(entity)
+            self.ticks = 0
+        end
+
+        function Mover:update(entity, delta_time)
+            self.ticks = self.ticks + 1
+            local transform = Transform.get(entity)
+            Transform.set(entity, { translation = { x = transform.translation.x + delta_time } })
+        end
+
+        return Mover
+    "#;
+
+    const RELOADED_SCRIPT: &str = r#"
+        local Mover = {}
+
+        function Mover:update(entity, delta_time)
+            self.ticks = self.ticks + 1
+            Transform.set(entity, { translation = { y = self.ticks } })
+        end
+
+        return Mover
+    "#;
+
+    fn write(filesystem: &FileSystem, path: &Path, source: &str) {
+        filesystem
+            .write(path)
+            .unwrap()
+            .write_all(source.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn update_and_reload() {
+        let root = std::env::temp_dir().join("ze-script-tests");
+        std::fs::create_dir_all(&root).unwrap();
+        let filesystem = FileSystem::new();
+        filesystem.mount(StdMountPoint::new("test", &root));
+        let path = Path::parse("/test/mover.lua").unwrap();
+        write(&filesystem, &path, MOVE_SCRIPT);
+
+        let mut serializer = SceneSerializer::default();
+        serializer.register::<Transform>();
+        let mut runtime = ScriptRuntime::new(filesystem.clone(), serializer).unwrap();
+
+        let mut world = World::default();
+        let entity = world.spawn();
+        world.add(entity, Transform::default());
+        world.add(entity, Script::new(path.clone()));
+
+        runtime.update(&mut world, 0.5);
+        runtime.update(&mut world, 0.5);
+        let transform = *world.query::<&Transform>().get(&world, entity).unwrap();
+        assert_eq!(transform.translation.x, 1.0);
+
+        // Instance keeps its state after a reload
+        write(&filesystem, &path, RELOADED_SCRIPT);
+        runtime.modified.lock().push(path.clone());
+        runtime.update(&mut world, 0.5);
+        let transform = *world.query::<&Transform>().get(&world, entity).unwrap();
+        assert_eq!(transform.translation.x, 1.0);
+        assert_eq!(transform.translation.y, 3.0);
+
+        // Instances of removed scripts are dropped
+        world.remove::<Script>(entity);
+        runtime.update(&mut world, 0.5);
+        assert!(runtime.instances.is_empty());
+    }
+}
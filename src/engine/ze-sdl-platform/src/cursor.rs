@@ -0,0 +1,13 @@
+use ze_platform::Cursor;
+
+pub struct SdlCursor {
+    pub cursor: sdl2::mouse::Cursor,
+}
+
+impl SdlCursor {
+    pub fn new(cursor: sdl2::mouse::Cursor) -> Self {
+        Self { cursor }
+    }
+}
+
+impl Cursor for SdlCursor {}
@@ -0,0 +1,821 @@
+use crate::cursor::SdlCursor;
+use crate::window::SdlWindow;
+use parking_lot::Mutex;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::MouseWheelDirection;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use sdl2::sys::{
+    SDL_Event, SDL_EventType, SDL_GetGlobalMouseState, SDL_GetPowerInfo, SDL_PowerState,
+    SDL_PushEvent,
+};
+use sdl2::{EventPump, Sdl, VideoSubsystem};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::path::PathBuf;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use ze_core::maths::{Point2, RectI32};
+use ze_core::{ze_error, ze_verbose, ze_warn};
+use ze_platform::{
+    Cursor, Error, FileDialogId, FileFilter, KeyCode, Message, ModifierBits, Modifiers, Monitor,
+    MouseButton, Platform, PowerSource, PowerStatus, SystemCursor, SystemTheme, Window,
+    WindowFlagBits, WindowFlags,
+};
+
+/// Used when SDL can't tell the DPI of a display
+pub(crate) const DEFAULT_DPI: f32 = 96.0;
+
+/// Platform implemented over SDL2, must be created and used from the main thread
+///
+/// Meant as a fallback for platforms without a native backend and as a reference
+/// to compare the native backends input handling against.
+pub struct SdlPlatform {
+    sdl: Sdl,
+    video: VideoSubsystem,
+    event_pump: Mutex<EventPump>,
+    window_map: Mutex<HashMap<u32, Weak<SdlWindow>>>,
+    message_queue: Mutex<VecDeque<Message>>,
+
+    /// SDL sends one event per dropped file, they are sent together once the drop is complete
+    dropped_files: Mutex<Vec<PathBuf>>,
+
+    /// Visibility set with `show_cursor`, the cursor is also hidden while it is set to `None`
+    cursor_shown: AtomicBool,
+    has_cursor: AtomicBool,
+}
+
+// SDL objects are only used from the main thread, which owns the platform
+unsafe impl Send for SdlPlatform {}
+unsafe impl Sync for SdlPlatform {}
+
+impl SdlPlatform {
+    pub fn new() -> Result<Arc<SdlPlatform>, Error> {
+        let sdl = sdl2::init().map_err(|error| {
+            ze_error!("Failed to initialize SDL: {}", error);
+            Error::Unknown
+        })?;
+
+        let video = sdl.video().map_err(|error| {
+            ze_error!("Failed to initialize SDL video subsystem: {}", error);
+            Error::Unknown
+        })?;
+
+        let event_pump = sdl.event_pump().map_err(|error| {
+            ze_error!("Failed to get SDL event pump: {}", error);
+            Error::Unknown
+        })?;
+
+        // Clicking an unfocused window should also be sent to it, like the other platforms
+        sdl2::hint::set("SDL_MOUSE_FOCUS_CLICKTHROUGH", "1");
+        video.text_input().start();
+
+        Ok(Arc::new(SdlPlatform {
+            sdl,
+            video,
+            event_pump: Mutex::new(event_pump),
+            window_map: Default::default(),
+            message_queue: Default::default(),
+            dropped_files: Default::default(),
+            cursor_shown: AtomicBool::new(true),
+            has_cursor: AtomicBool::new(true),
+        }))
+    }
+
+    fn display_dpi(&self, index: i32) -> f32 {
+        self.video
+            .display_dpi(index)
+            .map(|(dpi, _, _)| dpi)
+            .unwrap_or(DEFAULT_DPI)
+    }
+
+    fn window(&self, window_id: u32) -> Option<(Arc<SdlWindow>, Weak<dyn Window>)> {
+        let handle = self.window_map.lock().get(&window_id)?.clone();
+        let window = handle.upgrade()?;
+        let handle: Weak<dyn Window> = handle;
+        Some((window, handle))
+    }
+
+    fn cancel_file_dialog(&self) -> FileDialogId {
+        ze_warn!("File dialogs aren't supported by the SDL platform");
+        let id = FileDialogId::new();
+        self.message_queue
+            .lock()
+            .push_back(Message::FileDialogClosed(id, vec![]));
+        id
+    }
+
+    fn process_event(&self, event: Event) {
+        // Displays connected, disconnected or rotated, the monitors are always queried from SDL
+        if let Event::Display { .. } = event {
+            self.message_queue
+                .lock()
+                .push_back(Message::MonitorsChanged);
+            return;
+        }
+
+        let window_id = match event.get_window_id() {
+            Some(window_id) => window_id,
+            None => return,
+        };
+
+        let (window, handle) = match self.window(window_id) {
+            Some(window) => window,
+            None => return,
+        };
+
+        let mut message_queue = self.message_queue.lock();
+        match event {
+            Event::Window { win_event, .. } => match win_event {
+                WindowEvent::Close => {
+                    message_queue.push_back(Message::WindowCloseRequested(handle))
+                }
+                WindowEvent::SizeChanged(width, height) => {
+                    message_queue.push_back(Message::WindowResized(
+                        handle.clone(),
+                        width as u32,
+                        height as u32,
+                    ));
+                    self.send_dpi_changed(&window, handle, &mut message_queue);
+                }
+                WindowEvent::Moved(..) => {
+                    self.send_dpi_changed(&window, handle, &mut message_queue)
+                }
+                WindowEvent::FocusGained => {
+                    message_queue.push_back(Message::WindowFocusGained(handle))
+                }
+                WindowEvent::FocusLost => message_queue.push_back(Message::WindowFocusLost(handle)),
+                WindowEvent::Enter => message_queue.push_back(Message::MouseEntered(handle)),
+                WindowEvent::Leave => message_queue.push_back(Message::MouseLeft(handle)),
+                _ => {}
+            },
+            Event::MouseMotion { xrel, yrel, .. } => {
+                message_queue.push_back(Message::MouseMotionRaw(xrel as f32, yrel as f32))
+            }
+            Event::MouseButtonDown {
+                mouse_btn, clicks, ..
+            } => {
+                if let Some(button) = convert_mouse_button(mouse_btn) {
+                    let position = self.mouse_position();
+                    message_queue.push_back(if clicks == 2 {
+                        Message::MouseButtonDoubleClick(handle, button, position)
+                    } else {
+                        Message::MouseButtonDown(handle, button, position)
+                    });
+                }
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                if let Some(button) = convert_mouse_button(mouse_btn) {
+                    message_queue.push_back(Message::MouseButtonUp(
+                        handle,
+                        button,
+                        self.mouse_position(),
+                    ));
+                }
+            }
+            Event::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let (x, y) = match direction {
+                    MouseWheelDirection::Flipped => (-x, -y),
+                    _ => (x, y),
+                };
+
+                let position = self.mouse_position();
+                if y != 0 {
+                    message_queue.push_back(Message::MouseWheel(
+                        handle.clone(),
+                        y as f32,
+                        position,
+                    ));
+                }
+
+                if x != 0 {
+                    message_queue.push_back(Message::MouseWheelH(handle, x as f32, position));
+                }
+            }
+            Event::KeyDown {
+                keycode: Some(keycode),
+                scancode,
+                repeat,
+                ..
+            } => {
+                message_queue.push_back(Message::KeyDown(
+                    handle,
+                    convert_key_code(keycode),
+                    scancode.map_or(KeyCode::None, convert_scancode),
+                    character_code(keycode),
+                    repeat,
+                ));
+            }
+            Event::KeyUp {
+                keycode: Some(keycode),
+                scancode,
+                repeat,
+                ..
+            } => {
+                message_queue.push_back(Message::KeyUp(
+                    handle,
+                    convert_key_code(keycode),
+                    scancode.map_or(KeyCode::None, convert_scancode),
+                    character_code(keycode),
+                    repeat,
+                ));
+            }
+            Event::DropFile { filename, .. } => {
+                self.dropped_files.lock().push(PathBuf::from(filename));
+            }
+            Event::DropComplete { .. } => {
+                let files = std::mem::take(&mut *self.dropped_files.lock());
+                if !files.is_empty() {
+                    message_queue.push_back(Message::FilesDropped(
+                        handle,
+                        files,
+                        self.mouse_position(),
+                    ));
+                }
+            }
+            Event::TextInput { text, .. } => {
+                for character in text.chars() {
+                    if !character.is_control() {
+                        message_queue.push_back(Message::TextInput(handle.clone(), character));
+                    }
+                }
+            }
+            Event::TextEditing { text, .. } => {
+                message_queue.push_back(Message::TextComposition(handle, text))
+            }
+            _ => {}
+        }
+    }
+
+    /// SDL doesn't notify DPI changes, check if the window moved to a display with another DPI
+    fn send_dpi_changed(
+        &self,
+        window: &SdlWindow,
+        handle: Weak<dyn Window>,
+        message_queue: &mut VecDeque<Message>,
+    ) {
+        if let Some(display_index) = window.display_index() {
+            let dpi = self.display_dpi(display_index);
+            if window.update_dpi(dpi) {
+                message_queue.push_back(Message::WindowDpiChanged(handle, dpi));
+            }
+        }
+    }
+}
+
+fn convert_mouse_button(button: sdl2::mouse::MouseButton) -> Option<MouseButton> {
+    match button {
+        sdl2::mouse::MouseButton::Left => Some(MouseButton::Left),
+        sdl2::mouse::MouseButton::Middle => Some(MouseButton::Middle),
+        sdl2::mouse::MouseButton::Right => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+/// SDL key codes of printable keys are their unshifted character, upper case like `MapVirtualKey`
+fn character_code(keycode: Keycode) -> u32 {
+    match char::from_u32(keycode as u32) {
+        Some(character) if character.is_ascii_graphic() || character == ' ' => {
+            character.to_ascii_uppercase() as u32
+        }
+        _ => 0,
+    }
+}
+
+/// Inverse of `convert_key_code`
+fn sdl_keycode(key: KeyCode) -> Option<Keycode> {
+    Some(match key {
+        KeyCode::None => return None,
+        KeyCode::Escape => Keycode::Escape,
+        KeyCode::Space => Keycode::Space,
+        KeyCode::Backspace => Keycode::Backspace,
+        KeyCode::A => Keycode::A,
+        KeyCode::B => Keycode::B,
+        KeyCode::C => Keycode::C,
+        KeyCode::D => Keycode::D,
+        KeyCode::E => Keycode::E,
+        KeyCode::F => Keycode::F,
+        KeyCode::G => Keycode::G,
+        KeyCode::H => Keycode::H,
+        KeyCode::I => Keycode::I,
+        KeyCode::J => Keycode::J,
+        KeyCode::K => Keycode::K,
+        KeyCode::L => Keycode::L,
+        KeyCode::M => Keycode::M,
+        KeyCode::N => Keycode::N,
+        KeyCode::O => Keycode::O,
+        KeyCode::P => Keycode::P,
+        KeyCode::Q => Keycode::Q,
+        KeyCode::R => Keycode::R,
+        KeyCode::S => Keycode::S,
+        KeyCode::T => Keycode::T,
+        KeyCode::U => Keycode::U,
+        KeyCode::V => Keycode::V,
+        KeyCode::W => Keycode::W,
+        KeyCode::X => Keycode::X,
+        KeyCode::Y => Keycode::Y,
+        KeyCode::Z => Keycode::Z,
+        KeyCode::Numpad0 => Keycode::Kp0,
+        KeyCode::Numpad1 => Keycode::Kp1,
+        KeyCode::Numpad2 => Keycode::Kp2,
+        KeyCode::Numpad3 => Keycode::Kp3,
+        KeyCode::Numpad4 => Keycode::Kp4,
+        KeyCode::Numpad5 => Keycode::Kp5,
+        KeyCode::Numpad6 => Keycode::Kp6,
+        KeyCode::Numpad7 => Keycode::Kp7,
+        KeyCode::Numpad8 => Keycode::Kp8,
+        KeyCode::Numpad9 => Keycode::Kp9,
+        KeyCode::Num0 => Keycode::Num0,
+        KeyCode::Num1 => Keycode::Num1,
+        KeyCode::Num2 => Keycode::Num2,
+        KeyCode::Num3 => Keycode::Num3,
+        KeyCode::Num4 => Keycode::Num4,
+        KeyCode::Num5 => Keycode::Num5,
+        KeyCode::Num6 => Keycode::Num6,
+        KeyCode::Num7 => Keycode::Num7,
+        KeyCode::Num8 => Keycode::Num8,
+        KeyCode::Num9 => Keycode::Num9,
+        KeyCode::LeftControl => Keycode::LCtrl,
+        KeyCode::RightControl => Keycode::RCtrl,
+        KeyCode::LeftShift => Keycode::LShift,
+        KeyCode::RightShift => Keycode::RShift,
+        KeyCode::LeftAlt => Keycode::LAlt,
+        KeyCode::RightAlt => Keycode::RAlt,
+        KeyCode::F1 => Keycode::F1,
+        KeyCode::F2 => Keycode::F2,
+        KeyCode::F3 => Keycode::F3,
+        KeyCode::F4 => Keycode::F4,
+        KeyCode::F5 => Keycode::F5,
+        KeyCode::F6 => Keycode::F6,
+        KeyCode::F7 => Keycode::F7,
+        KeyCode::F8 => Keycode::F8,
+        KeyCode::F9 => Keycode::F9,
+        KeyCode::F10 => Keycode::F10,
+        KeyCode::F11 => Keycode::F11,
+        KeyCode::F12 => Keycode::F12,
+        KeyCode::F13 => Keycode::F13,
+        KeyCode::F14 => Keycode::F14,
+        KeyCode::F15 => Keycode::F15,
+        KeyCode::F16 => Keycode::F16,
+        KeyCode::F17 => Keycode::F17,
+        KeyCode::F18 => Keycode::F18,
+        KeyCode::F19 => Keycode::F19,
+        KeyCode::F20 => Keycode::F20,
+        KeyCode::F21 => Keycode::F21,
+        KeyCode::F22 => Keycode::F22,
+        KeyCode::F23 => Keycode::F23,
+        KeyCode::F24 => Keycode::F24,
+    })
+}
+
+fn convert_key_code(keycode: Keycode) -> KeyCode {
+    match keycode {
+        Keycode::Escape => KeyCode::Escape,
+        Keycode::Space => KeyCode::Space,
+        Keycode::Backspace => KeyCode::Backspace,
+        Keycode::A => KeyCode::A,
+        Keycode::B => KeyCode::B,
+        Keycode::C => KeyCode::C,
+        Keycode::D => KeyCode::D,
+        Keycode::E => KeyCode::E,
+        Keycode::F => KeyCode::F,
+        Keycode::G => KeyCode::G,
+        Keycode::H => KeyCode::H,
+        Keycode::I => KeyCode::I,
+        Keycode::J => KeyCode::J,
+        Keycode::K => KeyCode::K,
+        Keycode::L => KeyCode::L,
+        Keycode::M => KeyCode::M,
+        Keycode::N => KeyCode::N,
+        Keycode::O => KeyCode::O,
+        Keycode::P => KeyCode::P,
+        Keycode::Q => KeyCode::Q,
+        Keycode::R => KeyCode::R,
+        Keycode::S => KeyCode::S,
+        Keycode::T => KeyCode::T,
+        Keycode::U => KeyCode::U,
+        Keycode::V => KeyCode::V,
+        Keycode::W => KeyCode::W,
+        Keycode::X => KeyCode::X,
+        Keycode::Y => KeyCode::Y,
+        Keycode::Z => KeyCode::Z,
+        Keycode::Kp0 => KeyCode::Numpad0,
+        Keycode::Kp1 => KeyCode::Numpad1,
+        Keycode::Kp2 => KeyCode::Numpad2,
+        Keycode::Kp3 => KeyCode::Numpad3,
+        Keycode::Kp4 => KeyCode::Numpad4,
+        Keycode::Kp5 => KeyCode::Numpad5,
+        Keycode::Kp6 => KeyCode::Numpad6,
+        Keycode::Kp7 => KeyCode::Numpad7,
+        Keycode::Kp8 => KeyCode::Numpad8,
+        Keycode::Kp9 => KeyCode::Numpad9,
+        Keycode::Num0 => KeyCode::Num0,
+        Keycode::Num1 => KeyCode::Num1,
+        Keycode::Num2 => KeyCode::Num2,
+        Keycode::Num3 => KeyCode::Num3,
+        Keycode::Num4 => KeyCode::Num4,
+        Keycode::Num5 => KeyCode::Num5,
+        Keycode::Num6 => KeyCode::Num6,
+        Keycode::Num7 => KeyCode::Num7,
+        Keycode::Num8 => KeyCode::Num8,
+        Keycode::Num9 => KeyCode::Num9,
+        Keycode::LCtrl => KeyCode::LeftControl,
+        Keycode::RCtrl => KeyCode::RightControl,
+        Keycode::LShift => KeyCode::LeftShift,
+        Keycode::RShift => KeyCode::RightShift,
+        Keycode::LAlt => KeyCode::LeftAlt,
+        Keycode::RAlt => KeyCode::RightAlt,
+        Keycode::F1 => KeyCode::F1,
+        Keycode::F2 => KeyCode::F2,
+        Keycode::F3 => KeyCode::F3,
+        Keycode::F4 => KeyCode::F4,
+        Keycode::F5 => KeyCode::F5,
+        Keycode::F6 => KeyCode::F6,
+        Keycode::F7 => KeyCode::F7,
+        Keycode::F8 => KeyCode::F8,
+        Keycode::F9 => KeyCode::F9,
+        Keycode::F10 => KeyCode::F10,
+        Keycode::F11 => KeyCode::F11,
+        Keycode::F12 => KeyCode::F12,
+        Keycode::F13 => KeyCode::F13,
+        Keycode::F14 => KeyCode::F14,
+        Keycode::F15 => KeyCode::F15,
+        Keycode::F16 => KeyCode::F16,
+        Keycode::F17 => KeyCode::F17,
+        Keycode::F18 => KeyCode::F18,
+        Keycode::F19 => KeyCode::F19,
+        Keycode::F20 => KeyCode::F20,
+        Keycode::F21 => KeyCode::F21,
+        Keycode::F22 => KeyCode::F22,
+        Keycode::F23 => KeyCode::F23,
+        Keycode::F24 => KeyCode::F24,
+        _ => {
+            ze_verbose!("Key {} not handled", keycode);
+            KeyCode::None
+        }
+    }
+}
+
+/// Scancodes are named after the keys of a US QWERTY keyboard, like physical keys
+fn convert_scancode(scancode: Scancode) -> KeyCode {
+    match scancode {
+        Scancode::Escape => KeyCode::Escape,
+        Scancode::Space => KeyCode::Space,
+        Scancode::Backspace => KeyCode::Backspace,
+        Scancode::A => KeyCode::A,
+        Scancode::B => KeyCode::B,
+        Scancode::C => KeyCode::C,
+        Scancode::D => KeyCode::D,
+        Scancode::E => KeyCode::E,
+        Scancode::F => KeyCode::F,
+        Scancode::G => KeyCode::G,
+        Scancode::H => KeyCode::H,
+        Scancode::I => KeyCode::I,
+        Scancode::J => KeyCode::J,
+        Scancode::K => KeyCode::K,
+        Scancode::L => KeyCode::L,
+        Scancode::M => KeyCode::M,
+        Scancode::N => KeyCode::N,
+        Scancode::O => KeyCode::O,
+        Scancode::P => KeyCode::P,
+        Scancode::Q => KeyCode::Q,
+        Scancode::R => KeyCode::R,
+        Scancode::S => KeyCode::S,
+        Scancode::T => KeyCode::T,
+        Scancode::U => KeyCode::U,
+        Scancode::V => KeyCode::V,
+        Scancode::W => KeyCode::W,
+        Scancode::X => KeyCode::X,
+        Scancode::Y => KeyCode::Y,
+        Scancode::Z => KeyCode::Z,
+        Scancode::Kp0 => KeyCode::Numpad0,
+        Scancode::Kp1 => KeyCode::Numpad1,
+        Scancode::Kp2 => KeyCode::Numpad2,
+        Scancode::Kp3 => KeyCode::Numpad3,
+        Scancode::Kp4 => KeyCode::Numpad4,
+        Scancode::Kp5 => KeyCode::Numpad5,
+        Scancode::Kp6 => KeyCode::Numpad6,
+        Scancode::Kp7 => KeyCode::Numpad7,
+        Scancode::Kp8 => KeyCode::Numpad8,
+        Scancode::Kp9 => KeyCode::Numpad9,
+        Scancode::Num0 => KeyCode::Num0,
+        Scancode::Num1 => KeyCode::Num1,
+        Scancode::Num2 => KeyCode::Num2,
+        Scancode::Num3 => KeyCode::Num3,
+        Scancode::Num4 => KeyCode::Num4,
+        Scancode::Num5 => KeyCode::Num5,
+        Scancode::Num6 => KeyCode::Num6,
+        Scancode::Num7 => KeyCode::Num7,
+        Scancode::Num8 => KeyCode::Num8,
+        Scancode::Num9 => KeyCode::Num9,
+        Scancode::LCtrl => KeyCode::LeftControl,
+        Scancode::RCtrl => KeyCode::RightControl,
+        Scancode::LShift => KeyCode::LeftShift,
+        Scancode::RShift => KeyCode::RightShift,
+        Scancode::LAlt => KeyCode::LeftAlt,
+        Scancode::RAlt => KeyCode::RightAlt,
+        Scancode::F1 => KeyCode::F1,
+        Scancode::F2 => KeyCode::F2,
+        Scancode::F3 => KeyCode::F3,
+        Scancode::F4 => KeyCode::F4,
+        Scancode::F5 => KeyCode::F5,
+        Scancode::F6 => KeyCode::F6,
+        Scancode::F7 => KeyCode::F7,
+        Scancode::F8 => KeyCode::F8,
+        Scancode::F9 => KeyCode::F9,
+        Scancode::F10 => KeyCode::F10,
+        Scancode::F11 => KeyCode::F11,
+        Scancode::F12 => KeyCode::F12,
+        Scancode::F13 => KeyCode::F13,
+        Scancode::F14 => KeyCode::F14,
+        Scancode::F15 => KeyCode::F15,
+        Scancode::F16 => KeyCode::F16,
+        Scancode::F17 => KeyCode::F17,
+        Scancode::F18 => KeyCode::F18,
+        Scancode::F19 => KeyCode::F19,
+        Scancode::F20 => KeyCode::F20,
+        Scancode::F21 => KeyCode::F21,
+        Scancode::F22 => KeyCode::F22,
+        Scancode::F23 => KeyCode::F23,
+        Scancode::F24 => KeyCode::F24,
+        _ => KeyCode::None,
+    }
+}
+
+impl Platform for SdlPlatform {
+    fn poll_event(&self) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        let events: Vec<Event> = self.event_pump.lock().poll_iter().collect();
+        for event in events {
+            self.process_event(event);
+        }
+
+        self.message_queue.lock().pop_front()
+    }
+
+    fn wait_event(&self, timeout: Option<Duration>) -> Option<Message> {
+        if let Some(message) = self.message_queue.lock().pop_front() {
+            return Some(message);
+        }
+
+        // Rounded up so short timeouts don't turn into busy loops
+        let event = {
+            let mut event_pump = self.event_pump.lock();
+            match timeout {
+                Some(timeout) => event_pump.wait_event_timeout(
+                    timeout.as_micros().div_ceil(1000).min(u32::MAX as u128) as u32,
+                ),
+                None => Some(event_pump.wait_event()),
+            }
+        };
+        if let Some(event) = event {
+            self.process_event(event);
+        }
+
+        self.poll_event()
+    }
+
+    /// Pushes a user event, ignored as it has no window
+    fn wake(&self) {
+        unsafe {
+            let mut event: SDL_Event = mem::zeroed();
+            event.type_ = SDL_EventType::SDL_USEREVENT as u32;
+            SDL_PushEvent(&mut event);
+        }
+    }
+
+    fn create_window(
+        &self,
+        name: &str,
+        width: u32,
+        height: u32,
+        x: i32,
+        y: i32,
+        flags: WindowFlags,
+    ) -> Result<Arc<dyn Window>, Error> {
+        let mut builder = self.video.window(name, width, height);
+        builder.position(x, y).allow_highdpi().hidden();
+
+        if flags.contains(WindowFlagBits::Borderless) {
+            builder.borderless();
+        }
+
+        if flags.contains(WindowFlagBits::Resizable) {
+            builder.resizable();
+        }
+
+        if flags.contains(WindowFlagBits::Maximized) {
+            builder.maximized();
+        }
+
+        let window = builder.build().map_err(|error| {
+            ze_error!("Failed to create window: {}", error);
+            Error::Unknown
+        })?;
+
+        let dpi = window
+            .display_index()
+            .map(|index| self.display_dpi(index))
+            .unwrap_or(DEFAULT_DPI);
+        let window = Arc::new(SdlWindow::new(window, dpi));
+        window.show();
+
+        self.window_map
+            .lock()
+            .insert(window.id(), Arc::downgrade(&window));
+
+        Ok(window)
+    }
+
+    fn create_system_cursor(&self, cursor: SystemCursor) -> Box<dyn Cursor> {
+        let system_cursor = match cursor {
+            SystemCursor::No => sdl2::mouse::SystemCursor::No,
+            SystemCursor::Crosshair => sdl2::mouse::SystemCursor::Crosshair,
+            SystemCursor::Ibeam => sdl2::mouse::SystemCursor::IBeam,
+            SystemCursor::Arrow => sdl2::mouse::SystemCursor::Arrow,
+            SystemCursor::Hand => sdl2::mouse::SystemCursor::Hand,
+            SystemCursor::SizeAll => sdl2::mouse::SystemCursor::SizeAll,
+            SystemCursor::SizeNorthEastSouthWest => sdl2::mouse::SystemCursor::SizeNESW,
+            SystemCursor::SizeNorthSouth => sdl2::mouse::SystemCursor::SizeNS,
+            SystemCursor::SizeNorthWestSouthEast => sdl2::mouse::SystemCursor::SizeNWSE,
+            SystemCursor::SizeWestEast => sdl2::mouse::SystemCursor::SizeWE,
+            SystemCursor::Wait => sdl2::mouse::SystemCursor::Wait,
+            SystemCursor::WaitArrow => sdl2::mouse::SystemCursor::WaitArrow,
+        };
+
+        Box::new(SdlCursor::new(
+            sdl2::mouse::Cursor::from_system(system_cursor).unwrap(),
+        ))
+    }
+
+    fn create_cursor_from_rgba(
+        &self,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: Point2<u32>,
+    ) -> Result<Box<dyn Cursor>, Error> {
+        // SDL copies the surface pixels in the cursor
+        let mut pixels = rgba[..(width * height * 4) as usize].to_vec();
+        Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 4,
+            PixelFormatEnum::RGBA32,
+        )
+        .and_then(|surface| {
+            sdl2::mouse::Cursor::from_surface(surface, hotspot.x as i32, hotspot.y as i32)
+        })
+        .map(|cursor| Box::new(SdlCursor::new(cursor)) as Box<dyn Cursor>)
+        .map_err(|error| {
+            ze_error!("Failed to create a {}x{} cursor: {}", width, height, error);
+            Error::Unknown
+        })
+    }
+
+    fn set_cursor(&self, cursor: Option<&dyn Cursor>) {
+        if let Some(cursor) = cursor {
+            cursor.downcast_ref::<SdlCursor>().unwrap().cursor.set();
+        }
+        self.has_cursor.store(cursor.is_some(), Ordering::SeqCst);
+        self.sdl
+            .mouse()
+            .show_cursor(cursor.is_some() && self.cursor_shown.load(Ordering::SeqCst));
+    }
+
+    fn show_cursor(&self, show: bool) {
+        self.cursor_shown.store(show, Ordering::SeqCst);
+        self.sdl
+            .mouse()
+            .show_cursor(show && self.has_cursor.load(Ordering::SeqCst));
+    }
+
+    /// SDL captures the mouse for the window having the keyboard focus
+    fn capture_mouse(&self, _: &dyn Window) {
+        self.sdl.mouse().capture(true);
+    }
+
+    fn release_mouse(&self) {
+        self.sdl.mouse().capture(false);
+    }
+
+    fn mouse_position(&self) -> Point2<i32> {
+        let (mut x, mut y) = (0, 0);
+        unsafe {
+            SDL_GetGlobalMouseState(&mut x, &mut y);
+        }
+        Point2::new(x, y)
+    }
+
+    /// SDL only knows the keys of the events it received, so the window must have the focus
+    fn is_key_down(&self, key: KeyCode) -> bool {
+        sdl_keycode(key)
+            .and_then(Scancode::from_keycode)
+            .is_some_and(|scancode| {
+                self.event_pump
+                    .lock()
+                    .keyboard_state()
+                    .is_scancode_pressed(scancode)
+            })
+    }
+
+    fn keyboard_modifiers(&self) -> Modifiers {
+        let mod_state = self.sdl.keyboard().mod_state();
+        let mut modifiers = Modifiers::empty();
+        for (mods, modifier) in [
+            (Mod::LSHIFTMOD | Mod::RSHIFTMOD, ModifierBits::Shift),
+            (Mod::LCTRLMOD | Mod::RCTRLMOD, ModifierBits::Control),
+            (Mod::LALTMOD | Mod::RALTMOD, ModifierBits::Alt),
+            (Mod::LGUIMOD | Mod::RGUIMOD, ModifierBits::Super),
+        ] {
+            if mod_state.intersects(mods) {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    }
+
+    fn monitor_count(&self) -> usize {
+        self.video.num_video_displays().unwrap_or(0) as usize
+    }
+
+    fn monitor(&self, index: usize) -> Monitor {
+        let index = index as i32;
+        let bounds = self.video.display_bounds(index).unwrap();
+        let work_bounds = self.video.display_usable_bounds(index).unwrap_or(bounds);
+        let to_rect = |rect: sdl2::rect::Rect| {
+            RectI32::new(
+                rect.x(),
+                rect.y(),
+                rect.width() as i32,
+                rect.height() as i32,
+            )
+        };
+
+        Monitor {
+            bounds: to_rect(bounds),
+            work_bounds: to_rect(work_bounds),
+            dpi: self.display_dpi(index),
+        }
+    }
+
+    /// SDL 2 has no way to know the system theme
+    fn system_theme(&self) -> SystemTheme {
+        SystemTheme::Light
+    }
+
+    /// SDL 2 doesn't know whether power saving is enabled
+    fn power_status(&self) -> PowerStatus {
+        let mut percentage = -1;
+        let state = unsafe { SDL_GetPowerInfo(null_mut(), &mut percentage) };
+        let source = match state {
+            SDL_PowerState::SDL_POWERSTATE_ON_BATTERY => PowerSource::Battery,
+            SDL_PowerState::SDL_POWERSTATE_NO_BATTERY
+            | SDL_PowerState::SDL_POWERSTATE_CHARGING
+            | SDL_PowerState::SDL_POWERSTATE_CHARGED => PowerSource::Ac,
+            _ => PowerSource::Unknown,
+        };
+
+        PowerStatus {
+            source,
+            battery_percentage: u8::try_from(percentage).ok(),
+            power_saver: false,
+        }
+    }
+
+    fn clipboard_text(&self) -> Option<String> {
+        let clipboard = self.video.clipboard();
+        if !clipboard.has_clipboard_text() {
+            return None;
+        }
+
+        clipboard.clipboard_text().ok()
+    }
+
+    fn set_clipboard_text(&self, text: &str) {
+        if let Err(error) = self.video.clipboard().set_clipboard_text(text) {
+            ze_error!("Failed to set clipboard text: {}", error);
+        }
+    }
+
+    /// SDL 2 has no file dialogs, they are reported as cancelled
+    fn open_file_dialog(&self, _: &[FileFilter], _: bool) -> FileDialogId {
+        self.cancel_file_dialog()
+    }
+
+    fn save_file_dialog(&self, _: &[FileFilter], _: &str) -> FileDialogId {
+        self.cancel_file_dialog()
+    }
+}
+
+mod cursor;
+mod window;
@@ -0,0 +1,267 @@
+use crate::DEFAULT_DPI;
+use parking_lot::Mutex;
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::surface::Surface;
+use sdl2::sys::{
+    SDL_GetGlobalMouseState, SDL_HitTestResult, SDL_Point, SDL_SetWindowHitTest, SDL_Window,
+    SDL_WindowFlags,
+};
+use sdl2::video::{FlashOperation, WindowPos};
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use ze_core::maths::Point2;
+use ze_core::ze_error;
+use ze_platform::{HitTest, HitTestCallback, TaskbarProgressState, Window};
+
+pub struct SdlWindow {
+    window: Mutex<sdl2::video::Window>,
+    id: u32,
+
+    /// Boxed so its address can be given to SDL
+    hit_test_callback: Box<Mutex<Option<HitTestCallback>>>,
+
+    /// Last DPI reported to the application
+    dpi: Mutex<f32>,
+}
+
+// SDL windows are only used from the main thread, which owns the platform
+unsafe impl Send for SdlWindow {}
+unsafe impl Sync for SdlWindow {}
+
+impl SdlWindow {
+    pub fn new(window: sdl2::video::Window, dpi: f32) -> Self {
+        Self {
+            id: window.id(),
+            window: Mutex::new(window),
+            hit_test_callback: Box::new(Mutex::new(None)),
+            dpi: Mutex::new(dpi),
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn display_index(&self) -> Option<i32> {
+        self.window.lock().display_index().ok()
+    }
+
+    /// Store the DPI of the window display, returns true if it changed
+    pub fn update_dpi(&self, dpi: f32) -> bool {
+        let mut current_dpi = self.dpi.lock();
+        if *current_dpi == dpi {
+            return false;
+        }
+
+        *current_dpi = dpi;
+        true
+    }
+}
+
+impl Drop for SdlWindow {
+    fn drop(&mut self) {
+        // The callback data is freed after the window, SDL must not use it anymore
+        unsafe {
+            SDL_SetWindowHitTest(self.window.get_mut().raw(), None, null_mut());
+        }
+    }
+}
+
+impl Window for SdlWindow {
+    fn set_position(&self, position: Point2<i32>) {
+        self.window.lock().set_position(
+            WindowPos::Positioned(position.x),
+            WindowPos::Positioned(position.y),
+        );
+    }
+
+    fn set_size(&self, width: u32, height: u32) {
+        if let Err(error) = self.window.lock().set_size(width, height) {
+            ze_error!("Failed to resize window: {}", error);
+        }
+    }
+
+    /// SDL rejects empty limits, the smallest and largest sizes it accepts remove them
+    fn set_min_size(&self, size: Option<(u32, u32)>) {
+        let (width, height) = size.unwrap_or((1, 1));
+        if let Err(error) = self
+            .window
+            .lock()
+            .set_minimum_size(width.max(1), height.max(1))
+        {
+            ze_error!("Failed to set the window minimum size: {}", error);
+        }
+    }
+
+    fn set_max_size(&self, size: Option<(u32, u32)>) {
+        let (width, height) = size.unwrap_or((i32::MAX as u32, i32::MAX as u32));
+        if let Err(error) = self
+            .window
+            .lock()
+            .set_maximum_size(width.max(1), height.max(1))
+        {
+            ze_error!("Failed to set the window maximum size: {}", error);
+        }
+    }
+
+    fn set_title(&self, title: &str) {
+        if let Err(error) = self.window.lock().set_title(title) {
+            ze_error!("Invalid window title: {}", error);
+        }
+    }
+
+    fn set_icon(&self, width: u32, height: u32, rgba: &[u8]) {
+        let mut pixels = rgba[..(width * height * 4) as usize].to_vec();
+        match Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 4,
+            PixelFormatEnum::RGBA32,
+        ) {
+            Ok(surface) => self.window.lock().set_icon(surface),
+            Err(error) => ze_error!("Failed to create the icon surface: {}", error),
+        };
+    }
+
+    fn show(&self) {
+        self.window.lock().show();
+    }
+
+    fn close(&self) {
+        self.window.lock().hide();
+    }
+
+    fn minimize(&self) {
+        self.window.lock().minimize();
+    }
+
+    fn maximize(&self) {
+        self.window.lock().maximize();
+    }
+
+    fn restore(&self) {
+        self.window.lock().restore();
+    }
+
+    fn focus(&self) {
+        self.window.lock().raise();
+    }
+
+    fn set_opacity(&self, opacity: f32) {
+        if let Err(error) = self.window.lock().set_opacity(opacity.clamp(0.0, 1.0)) {
+            ze_error!("Failed to set the window opacity: {}", error);
+        }
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) {
+        self.window.lock().set_always_on_top(always_on_top);
+    }
+
+    /// SDL 2 has no taskbar progress
+    fn set_taskbar_progress(&self, _: TaskbarProgressState, _: f32) {}
+
+    fn request_attention(&self) {
+        if let Err(error) = self.window.lock().flash(FlashOperation::UntilFocused) {
+            ze_error!("Failed to flash window: {}", error);
+        }
+    }
+
+    fn set_hit_test_callback(&self, callback: Option<HitTestCallback>) {
+        let raw = self.window.lock().raw();
+        let enabled = callback.is_some();
+        *self.hit_test_callback.lock() = callback;
+
+        unsafe {
+            if enabled {
+                let data = &*self.hit_test_callback as *const Mutex<Option<HitTestCallback>>;
+                SDL_SetWindowHitTest(raw, Some(hit_test), data as *mut c_void);
+            } else {
+                SDL_SetWindowHitTest(raw, None, null_mut());
+            }
+        }
+    }
+
+    /// SDL places the candidates of the focused window only
+    fn set_ime_position(&self, position: Point2<i32>) {
+        let window = self.window.lock();
+        window
+            .subsystem()
+            .text_input()
+            .set_rect(Rect::new(position.x, position.y, 1, 1));
+    }
+
+    fn handle(&self) -> RawWindowHandle {
+        self.window.lock().raw_window_handle()
+    }
+
+    fn display_handle(&self) -> RawDisplayHandle {
+        self.window.lock().raw_display_handle()
+    }
+
+    fn width(&self) -> u32 {
+        self.window.lock().size().0
+    }
+
+    fn height(&self) -> u32 {
+        self.window.lock().size().1
+    }
+
+    fn position(&self) -> Point2<i32> {
+        let (x, y) = self.window.lock().position();
+        Point2::new(x, y)
+    }
+
+    /// `SDL_GetMouseState` is relative to the focused window only
+    fn cursor_position(&self) -> Point2<i32> {
+        let (mut x, mut y) = (0, 0);
+        unsafe {
+            SDL_GetGlobalMouseState(&mut x, &mut y);
+        }
+        let position = self.position();
+        Point2::new(x - position.x, y - position.y)
+    }
+
+    fn is_minimized(&self) -> bool {
+        self.window.lock().window_flags() & SDL_WindowFlags::SDL_WINDOW_MINIMIZED as u32 != 0
+    }
+
+    fn is_focused(&self) -> bool {
+        self.window.lock().window_flags() & SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32 != 0
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        *self.dpi.lock() / DEFAULT_DPI
+    }
+}
+
+/// SDL has no maximize button area, it is left to the application like the client area
+unsafe extern "C" fn hit_test(
+    _: *mut SDL_Window,
+    area: *const SDL_Point,
+    data: *mut c_void,
+) -> SDL_HitTestResult {
+    let callback = &*(data as *const Mutex<Option<HitTestCallback>>);
+    let callback = callback.lock();
+    let callback = match callback.as_ref() {
+        Some(callback) => callback,
+        None => return SDL_HitTestResult::SDL_HITTEST_NORMAL,
+    };
+
+    match callback(Point2::new((*area).x, (*area).y)) {
+        HitTest::Client | HitTest::MaximizeButton => SDL_HitTestResult::SDL_HITTEST_NORMAL,
+        HitTest::Caption => SDL_HitTestResult::SDL_HITTEST_DRAGGABLE,
+        HitTest::Left => SDL_HitTestResult::SDL_HITTEST_RESIZE_LEFT,
+        HitTest::Right => SDL_HitTestResult::SDL_HITTEST_RESIZE_RIGHT,
+        HitTest::Top => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOP,
+        HitTest::Bottom => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOM,
+        HitTest::TopLeft => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOPLEFT,
+        HitTest::TopRight => SDL_HitTestResult::SDL_HITTEST_RESIZE_TOPRIGHT,
+        HitTest::BottomLeft => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOMLEFT,
+        HitTest::BottomRight => SDL_HitTestResult::SDL_HITTEST_RESIZE_BOTTOMRIGHT,
+    }
+}
@@ -0,0 +1,114 @@
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A message emitted by the shader compiler
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub file: Option<String>,
+
+    /// 1-based line in `file`
+    pub line: Option<u32>,
+
+    /// 1-based column in `line`
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+impl ShaderDiagnostic {
+    /// Parse a compiler output using the clang diagnostic format (`file:line:column: severity: message`)
+    ///
+    /// Lines not following this format (source excerpts, carets) are ignored
+    /// If no diagnostic can be parsed, the whole output is returned as a single error
+    pub fn parse(output: &str) -> Vec<ShaderDiagnostic> {
+        let diagnostics = output.lines().filter_map(parse_line).collect::<Vec<_>>();
+        if diagnostics.is_empty() && !output.trim().is_empty() {
+            vec![ShaderDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                file: None,
+                line: None,
+                column: None,
+                message: output.trim().to_string(),
+            }]
+        } else {
+            diagnostics
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<ShaderDiagnostic> {
+    let (location, severity, message) = [
+        (": error: ", DiagnosticSeverity::Error),
+        (": fatal error: ", DiagnosticSeverity::Error),
+        (": warning: ", DiagnosticSeverity::Warning),
+        (": note: ", DiagnosticSeverity::Note),
+    ]
+    .into_iter()
+    .find_map(|(separator, severity)| {
+        line.split_once(separator)
+            .map(|(location, message)| (location, severity, message))
+    })?;
+
+    // Split from the end as the file can contain ':' (e.g. Windows drive letters)
+    let (rest, last) = location.rsplit_once(':')?;
+    let last = last.trim().parse::<u32>().ok()?;
+    let (file, line, column) = match rest.rsplit_once(':') {
+        Some((file, line)) => match line.trim().parse::<u32>() {
+            Ok(line) => (file, line, Some(last)),
+            Err(_) => (rest, last, None),
+        },
+        None => (rest, last, None),
+    };
+
+    Some(ShaderDiagnostic {
+        severity,
+        file: Some(file.trim().to_string()),
+        line: Some(line),
+        column,
+        message: message.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::{DiagnosticSeverity, ShaderDiagnostic};
+
+    #[test]
+    fn parse_clang_output() {
+        let output = "C:\\shaders\\test.hlsl:12:5: error: use of undeclared identifier 'a'\n    \
+            a = 1;\n    ^\nhlsl.hlsl:3:1: warning: unused variable\n";
+        let diagnostics = ShaderDiagnostic::parse(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            diagnostics[0].file.as_deref(),
+            Some("C:\\shaders\\test.hlsl")
+        );
+        assert_eq!(diagnostics[0].line, Some(12));
+        assert_eq!(diagnostics[0].column, Some(5));
+        assert_eq!(diagnostics[0].message, "use of undeclared identifier 'a'");
+        assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[1].line, Some(3));
+    }
+
+    #[test]
+    fn parse_without_column() {
+        let diagnostics = ShaderDiagnostic::parse("test.hlsl:7: error: syntax error");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file.as_deref(), Some("test.hlsl"));
+        assert_eq!(diagnostics[0].line, Some(7));
+        assert_eq!(diagnostics[0].column, None);
+    }
+
+    #[test]
+    fn parse_unknown_output() {
+        let diagnostics = ShaderDiagnostic::parse("Something went wrong\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, None);
+        assert_eq!(diagnostics[0].message, "Something went wrong");
+    }
+}
@@ -1,5 +1,7 @@
 use ze_gfx::ShaderStageFlagBits;
 
+pub mod diagnostic;
+
 pub struct ShaderCompilerInput<'a> {
     pub name: &'a str,
     pub stage: ShaderStageFlagBits,
@@ -11,8 +11,11 @@ use ze_core::{ze_error, ze_info};
 use ze_filesystem::path::Path;
 use ze_filesystem::{FileSystem, IterDirFlagBits, IterDirFlags, WatchEvent};
 use ze_gfx::backend::{Device, PipelineShaderStage, ShaderModule};
+use ze_gfx::utils::{set_downsample_shader_module, DOWNSAMPLE_SHADER};
 use ze_gfx::ShaderStageFlagBits;
+use ze_jobsystem::progress::Progress;
 use ze_jobsystem::JobSystem;
+use ze_shader_compiler::diagnostic::ShaderDiagnostic;
 use ze_shader_compiler::{ShaderCompiler, ShaderCompilerInput};
 
 enum ShaderStageSourceData {
@@ -52,11 +55,22 @@ pub struct Shader {
     ty: ShaderType,
     name: String,
     passes: Vec<ShaderPass>,
+    parameters: Vec<Parameter>,
 }
 
 impl Shader {
-    fn new(ty: ShaderType, name: String, passes: Vec<ShaderPass>) -> Self {
-        Self { ty, name, passes }
+    fn new(
+        ty: ShaderType,
+        name: String,
+        passes: Vec<ShaderPass>,
+        parameters: Vec<Parameter>,
+    ) -> Self {
+        Self {
+            ty,
+            name,
+            passes,
+            parameters,
+        }
     }
 
     fn pass_index(&self, name: &str) -> Option<usize> {
@@ -77,7 +91,7 @@ pub struct ShaderModules {
 }
 
 impl ShaderModules {
-    pub fn pipeline_stages(&self) -> Vec<PipelineShaderStage> {
+    pub fn pipeline_stages(&self) -> Vec<PipelineShaderStage<'_>> {
         let mut stages = Vec::with_capacity(self.stages.len());
         for stage in &self.stages {
             stages.push(PipelineShaderStage {
@@ -87,6 +101,14 @@ impl ShaderModules {
         }
         stages
     }
+
+    /// Module of the given stage, if the pass has one
+    pub fn stage(&self, stage: ShaderStageFlagBits) -> Option<&ShaderModule> {
+        self.stages
+            .iter()
+            .find(|(module_stage, _)| *module_stage == stage)
+            .map(|(_, module)| module)
+    }
 }
 
 /// Simple cache storing the shader modules in a Arc
@@ -105,6 +127,7 @@ impl ShaderModulesCache {
 pub struct CompilingShader {
     name: String,
     bytecodes: Mutex<Vec<(ShaderStageFlagBits, Vec<u8>)>>,
+    diagnostics: Mutex<Vec<(ShaderStageFlagBits, Vec<ShaderDiagnostic>)>>,
     processed_stages: AtomicUsize,
     stage_count: usize,
     pub on_compiled: SyncSignal<()>,
@@ -115,17 +138,59 @@ impl CompilingShader {
         Self {
             name,
             bytecodes: Default::default(),
+            diagnostics: Default::default(),
             processed_stages: Default::default(),
             stage_count,
             on_compiled: Default::default(),
         }
     }
+
+    fn failed(&self) -> bool {
+        self.bytecodes.lock().len() != self.stage_count
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShaderStatus {
+    NotCompiled,
+    Compiling,
+    Compiled,
+    Failed,
+}
+
+pub struct ShaderStageInfo {
+    pub stage: ShaderStageFlagBits,
+
+    /// Diagnostics of the last compilation of this stage
+    pub diagnostics: Vec<ShaderDiagnostic>,
+}
+
+pub struct ShaderPassInfo {
+    pub name: String,
+    pub status: ShaderStatus,
+
+    /// Number of compiled permutations of this pass
+    pub permutation_count: usize,
+    pub stages: Vec<ShaderStageInfo>,
+}
+
+/// Snapshot of the state of a shader, see [`ShaderManager::shaders`]
+pub struct ShaderInfo {
+    pub name: String,
+    pub passes: Vec<ShaderPassInfo>,
 }
 
 struct CompilationManager {
     jobsystem: Arc<JobSystem>,
     shader_compiler: Arc<dyn ShaderCompiler>,
     shaders: Arc<Mutex<HashMap<u64, Arc<CompilingShader>>>>,
+
+    /// Last finished compilation of each permutation, kept to report errors
+    finished_shaders: Arc<Mutex<HashMap<u64, Arc<CompilingShader>>>>,
+
+    /// Progress of the stages being compiled, shared by all compilations started while it
+    /// is not finished
+    progress: Arc<Mutex<Option<Arc<Progress>>>>,
 }
 
 impl CompilationManager {
@@ -134,6 +199,8 @@ impl CompilationManager {
             jobsystem,
             shader_compiler,
             shaders: Default::default(),
+            finished_shaders: Default::default(),
+            progress: Default::default(),
         }
     }
 
@@ -142,6 +209,10 @@ impl CompilationManager {
         shaders.get(&id).cloned()
     }
 
+    fn last_compilation(&self, id: u64) -> Option<Arc<CompilingShader>> {
+        self.finished_shaders.lock().get(&id).cloned()
+    }
+
     fn compile_permutation(
         &self,
         key: u64,
@@ -153,6 +224,23 @@ impl CompilationManager {
         let shader = Arc::new(CompilingShader::new(name.to_string(), pass.stages.len()));
         shaders.insert(key, shader.clone());
 
+        let progress = {
+            let mut progress = self.progress.lock();
+            match progress.as_ref() {
+                Some(progress) if !progress.is_finished() => {
+                    progress.add_total(pass.stages.len());
+                    progress.clone()
+                }
+                _ => progress
+                    .insert(self.jobsystem.begin_progress(
+                        "Compiling shaders",
+                        pass.stages.len(),
+                        false,
+                    ))
+                    .clone(),
+            }
+        };
+
         for stage in &pass.stages {
             if let ShaderStageSourceData::Hlsl(code) = &stage.source_data {
                 struct CompilationData {
@@ -175,8 +263,12 @@ impl CompilationManager {
                 });
 
                 let shaders = self.shaders.clone();
+                let finished_shaders = self.finished_shaders.clone();
+                let current_progress = self.progress.clone();
+                let progress = progress.clone();
                 self.jobsystem
                     .spawn(move |_, _| {
+                        progress.set_current_item(&compilation_data.shader.name);
                         let output =
                             compilation_data
                                 .shader_compiler
@@ -198,6 +290,11 @@ impl CompilationManager {
                                     error_message.push_str(&error);
                                 }
 
+                                shader.diagnostics.lock().push((
+                                    compilation_data.stage_type,
+                                    ShaderDiagnostic::parse(&error_message),
+                                ));
+
                                 ze_error!(
                                     "Failed to compile shader {} stage {:?}: {}",
                                     shader.name,
@@ -207,9 +304,18 @@ impl CompilationManager {
                             }
                         }
 
+                        {
+                            // Locked so no compilation is added to a finished progress
+                            let _current_progress = current_progress.lock();
+                            if progress.advance() >= progress.total() {
+                                progress.finish();
+                            }
+                        }
+
                         shader.processed_stages.fetch_add(1, Ordering::SeqCst);
                         if shader.processed_stages.load(Ordering::SeqCst) == shader.stage_count {
                             (*compilation_data.callback)(shader.clone());
+                            finished_shaders.lock().insert(key, shader.clone());
                             shaders.lock().remove(&key);
                         }
                     })
@@ -302,10 +408,7 @@ impl ShaderManager {
             if let Some(pass_idx) = shader.pass_index(pass) {
                 let pass = &shader.passes[pass_idx];
                 // First search on the cache
-                let mut id = DefaultHasher::new();
-                id.write_usize(shader_index);
-                id.write_usize(pass_idx);
-                let id = id.finish();
+                let id = permutation_id(shader_index, pass_idx);
                 if let Some(modules) = self.module_cache.get(id) {
                     Ok(modules)
                 } else {
@@ -332,6 +435,18 @@ impl ShaderManager {
                                         modules.push((*stage, module));
                                     }
                                     shaders.insert(id, Arc::new(ShaderModules { stages: modules }));
+
+                                    if name == DOWNSAMPLE_SHADER {
+                                        if let Some((_, bytecode)) =
+                                            bytecodes.iter().find(|(stage, _)| {
+                                                *stage == ShaderStageFlagBits::Compute
+                                            })
+                                        {
+                                            set_downsample_shader_module(Arc::new(
+                                                device.create_shader_module(bytecode).unwrap(),
+                                            ));
+                                        }
+                                    }
                                 }
                             },
                         );
@@ -346,21 +461,154 @@ impl ShaderManager {
         }
     }
 
+    /// Get the state of every loaded shader, sorted by name
+    pub fn shaders(&self) -> Vec<ShaderInfo> {
+        // Copy the map so the shaders can be locked without risking a deadlock with a reload
+        let shader_indices = self
+            .shader_name_to_index_map
+            .read()
+            .iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect::<Vec<_>>();
+
+        let shaders = self.shaders.read();
+        let cache = self.module_cache.shaders.read();
+        let mut infos = shader_indices
+            .into_iter()
+            .filter_map(|(name, index)| {
+                let shader = shaders.get(index)?;
+                let passes = shader
+                    .passes
+                    .iter()
+                    .enumerate()
+                    .map(|(pass_idx, pass)| {
+                        let id = permutation_id(index, pass_idx);
+                        let last_compilation = self.compilation_manager.last_compilation(id);
+                        let status = if self.compilation_manager.is_compiling(id).is_some() {
+                            ShaderStatus::Compiling
+                        } else if cache.contains_key(&id) {
+                            ShaderStatus::Compiled
+                        } else if last_compilation
+                            .as_ref()
+                            .is_some_and(|shader| shader.failed())
+                        {
+                            ShaderStatus::Failed
+                        } else {
+                            ShaderStatus::NotCompiled
+                        };
+
+                        let diagnostics = last_compilation
+                            .map(|shader| shader.diagnostics.lock().clone())
+                            .unwrap_or_default();
+                        let stages = pass
+                            .stages
+                            .iter()
+                            .map(|stage| ShaderStageInfo {
+                                stage: stage.stage,
+                                diagnostics: diagnostics
+                                    .iter()
+                                    .filter(|(diagnostic_stage, _)| {
+                                        *diagnostic_stage == stage.stage
+                                    })
+                                    .flat_map(|(_, diagnostics)| diagnostics.clone())
+                                    .collect(),
+                            })
+                            .collect();
+
+                        ShaderPassInfo {
+                            name: pass.name.clone(),
+                            status,
+                            permutation_count: usize::from(cache.contains_key(&id)),
+                            stages,
+                        }
+                    })
+                    .collect();
+
+                Some(ShaderInfo { name, passes })
+            })
+            .collect::<Vec<_>>();
+
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// HLSL code of a shader stage, as given to the compiler
+    pub fn stage_source(
+        &self,
+        name: &str,
+        pass: &str,
+        stage: ShaderStageFlagBits,
+    ) -> Option<String> {
+        let index = *self.shader_name_to_index_map.read().get(name)?;
+        let shaders = self.shaders.read();
+        let shader = shaders.get(index)?;
+        let pass = &shader.passes[shader.pass_index(pass)?];
+        pass.stages
+            .iter()
+            .find(|pass_stage| pass_stage.stage == stage)
+            .map(|pass_stage| match &pass_stage.source_data {
+                ShaderStageSourceData::Hlsl(code) => code.clone(),
+                ShaderStageSourceData::_Bytecode(_) => String::default(),
+            })
+    }
+
+    /// Parameters declared in the `parameters` block of a shader, in declaration order
+    pub fn shader_parameters(&self, name: &str) -> Option<Vec<Parameter>> {
+        let index = *self.shader_name_to_index_map.read().get(name)?;
+        let shaders = self.shaders.read();
+        shaders.get(index).map(|shader| shader.parameters.clone())
+    }
+
+    /// Discard the compiled modules of a shader and compile all of its passes again
+    pub fn recompile(self: &Arc<ShaderManager>, name: &str) {
+        let index = match self.shader_name_to_index_map.read().get(name) {
+            Some(index) => *index,
+            None => return,
+        };
+
+        let passes = {
+            let shaders = self.shaders.read();
+            let shader = match shaders.get(index) {
+                Some(shader) => shader,
+                None => return,
+            };
+
+            let mut cache = self.module_cache.shaders.write();
+            for pass_idx in 0..shader.passes.len() {
+                cache.remove(&permutation_id(index, pass_idx));
+            }
+
+            shader
+                .passes
+                .iter()
+                .map(|pass| pass.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        for pass in passes {
+            let _ = self.shader_modules(&name.to_string(), Some(pass));
+        }
+    }
+
     /// Load a .zeshader shader file into a `Shader`
-    fn load_zeshader_file(&self, filesystem: &Arc<FileSystem>, path: &Path) -> Result<(), ()> {
+    fn load_zeshader_file(
+        self: &Arc<ShaderManager>,
+        filesystem: &Arc<FileSystem>,
+        path: &Path,
+    ) -> Result<(), ()> {
         match self.parse_zeshader_file(filesystem, path) {
             Ok(declaration) => {
+                let name = declaration.name.clone();
                 let mut shaders = self.shaders.write();
                 for (index, shader) in shaders.iter().enumerate() {
                     if shader.name == declaration.name {
                         let mut cache = self.module_cache.shaders.write();
+                        let mut finished_shaders = self.compilation_manager.finished_shaders.lock();
                         // Remove from cache the shader modules
                         for (pass_idx, _) in shader.passes.iter().enumerate() {
-                            let mut id = DefaultHasher::new();
-                            id.write_usize(index);
-                            id.write_usize(pass_idx);
-                            let id = id.finish();
+                            let id = permutation_id(index, pass_idx);
                             cache.remove(&id);
+                            finished_shaders.remove(&id);
                         }
 
                         shaders.remove(index);
@@ -383,7 +631,12 @@ impl ShaderManager {
                     passes.push(ShaderPass::new(pass.name, stages));
                 }
 
-                let shader = Shader::new(ShaderType::Zeshader, declaration.name.clone(), passes);
+                let shader = Shader::new(
+                    ShaderType::Zeshader,
+                    declaration.name.clone(),
+                    passes,
+                    declaration.parameters,
+                );
                 ze_info!(
                     "Loaded shader \"{}\" ({} passes/zeshader)",
                     shader.name,
@@ -392,9 +645,17 @@ impl ShaderManager {
                 let index = shaders.push(shader);
                 let mut shader_name_to_index_map = self.shader_name_to_index_map.write();
                 shader_name_to_index_map.insert(declaration.name, index);
+                drop(shader_name_to_index_map);
+                drop(shaders);
 
                 // TODO: Insert into big hashmap
 
+                // Nothing requests the downsample shader through the manager, ze-gfx gets it
+                // once compiled
+                if name == DOWNSAMPLE_SHADER {
+                    let _ = self.shader_modules(&name, Some(name.clone()));
+                }
+
                 Ok(())
             }
             Err(err) => {
@@ -419,4 +680,14 @@ impl ShaderManager {
     }
 }
 
+/// Id of a shader pass permutation in the module cache
+fn permutation_id(shader_index: usize, pass_index: usize) -> u64 {
+    let mut id = DefaultHasher::new();
+    id.write_usize(shader_index);
+    id.write_usize(pass_index);
+    id.finish()
+}
+
 mod zeshader;
+
+pub use zeshader::{Parameter, ParameterType};
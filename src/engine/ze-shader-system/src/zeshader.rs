@@ -40,7 +40,7 @@ impl Pass {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ParameterType {
     Uint,
     Uint64,
@@ -76,7 +76,7 @@ impl FromStr for ParameterType {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Parameter {
     pub ty: ParameterType,
     pub name: String,
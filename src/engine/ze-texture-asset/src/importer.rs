@@ -45,7 +45,7 @@ impl Default for Parameters {
         Self {
             compression_mode: TextureCompressionMode::HighQuality,
             s_rgb: true,
-            generate_mipmaps: false,
+            generate_mipmaps: true,
         }
     }
 }
@@ -101,6 +101,7 @@ impl AssetImporter for TextureImporter {
             depth: 1,
             format,
             mip_levels: vec![image.to_rgba8().as_bytes().to_vec()],
+            generate_mips: metadata.parameters().generate_mipmaps,
             texture: None,
             default_srv: None,
         };
@@ -26,6 +26,9 @@ pub struct Texture {
 
     mip_levels: Vec<Vec<u8>>,
 
+    /// Generate the rest of the mip chain from the first mip level when loading
+    generate_mips: bool,
+
     #[serde(skip_serializing, skip_deserializing)]
     texture: Option<Arc<backend::Texture>>,
 
@@ -4,8 +4,9 @@ use std::sync::Arc;
 use uuid::Uuid;
 use ze_asset_system::loader::{AssetLoader, Error};
 use ze_asset_system::Asset;
+use ze_core::ze_error;
 use ze_gfx::backend::*;
-use ze_gfx::{utils, PixelFormat};
+use ze_gfx::utils;
 
 pub struct TextureLoader {
     device: Arc<dyn Device>,
@@ -15,6 +16,43 @@ impl TextureLoader {
     pub fn new(device: Arc<dyn Device>) -> Self {
         Self { device }
     }
+
+    /// Generate the mip chain of a texture from its first mip level once it has been copied
+    /// The texture is expected in the Common state and left in it
+    fn generate_mips(&self, texture: &Arc<ze_gfx::backend::Texture>) -> Result<(), DeviceError> {
+        let copy_fence = self.device.create_fence("TextureLoader Copy Fence")?;
+        self.device
+            .submit(QueueType::Transfer, &[], &[], &[&copy_fence]);
+
+        let transition = |source_state, dest_state| {
+            ResourceBarrier::Transition(ResourceTransitionBarrier {
+                resource: ResourceTransitionBarrierResource::Texture(texture),
+                source_state,
+                dest_state,
+            })
+        };
+
+        let mut cmd_list = self.device.create_command_list(QueueType::Graphics)?;
+        self.device.cmd_resource_barrier(
+            &mut cmd_list,
+            &[transition(
+                ResourceState::Common,
+                ResourceState::UnorderedAccessReadWrite,
+            )],
+        );
+        utils::generate_mips(&self.device, &mut cmd_list, texture)?;
+        self.device.cmd_resource_barrier(
+            &mut cmd_list,
+            &[transition(
+                ResourceState::UnorderedAccessReadWrite,
+                ResourceState::Common,
+            )],
+        );
+        self.device
+            .submit(QueueType::Graphics, &[&cmd_list], &[&copy_fence], &[]);
+
+        Ok(())
+    }
 }
 
 impl AssetLoader for TextureLoader {
@@ -30,15 +68,27 @@ impl AssetLoader for TextureLoader {
 
         texture.uuid = uuid;
 
+        // Mips are either all stored or generated from the first one
+        let mut mip_levels = if texture.generate_mips {
+            u32::BITS - texture.width.max(texture.height).leading_zeros()
+        } else {
+            texture.mip_levels.len() as u32
+        };
+
         texture.texture = match self.device.create_texture(
             &TextureDesc {
                 width: texture.width,
                 height: texture.height,
                 depth: texture.depth,
-                mip_levels: texture.mip_levels.len() as u32,
+                array_size: 1,
+                mip_levels,
                 format: texture.format,
                 sample_desc: Default::default(),
-                usage_flags: TextureUsageFlags::empty(),
+                usage_flags: if texture.generate_mips {
+                    TextureUsageFlagBits::UnorderedAccess.into()
+                } else {
+                    TextureUsageFlags::empty()
+                },
                 memory_desc: MemoryDesc {
                     memory_location: MemoryLocation::GpuOnly,
                     memory_flags: Default::default(),
@@ -57,11 +107,19 @@ impl AssetLoader for TextureLoader {
             &texture.mip_levels[0],
             texture.width,
             texture.height,
-            texture.format.bytes_size(),
+            texture.format.row_pitch_in_bytes(texture.width),
             &texture_handle.unwrap(),
             ResourceState::Common,
         )
         .expect("Cannot copy texture data to GPU");
+
+        if texture.generate_mips {
+            if let Err(error) = self.generate_mips(texture.texture.as_ref().unwrap()) {
+                ze_error!("Failed to generate mips of texture {}: {:?}", uuid, error);
+                mip_levels = 1;
+            }
+        }
+
         let texture_handle = texture.texture.clone();
 
         texture.default_srv =
@@ -69,9 +127,9 @@ impl AssetLoader for TextureLoader {
                 .device
                 .create_shader_resource_view(&ShaderResourceViewDesc::Texture2D(Texture2DSRV {
                     texture: texture_handle.unwrap(),
-                    format: PixelFormat::R8G8B8A8Unorm,
+                    format: texture.format,
                     min_mip_level: 0,
-                    mip_levels: texture.mip_levels.len() as u32,
+                    mip_levels,
                 })) {
                 Ok(srv) => Some(Arc::new(srv)),
                 Err(_) => return Err(Error::CannotDeserialize),
@@ -0,0 +1,59 @@
+use gpu_allocator::vulkan::{Allocation, AllocationCreateDesc, Allocator};
+use gpu_allocator::Result;
+
+#[derive(Copy, Clone, Default)]
+pub(crate) struct AllocationCounters {
+    pub count: u32,
+    pub bytes: u64,
+}
+
+/// Wrap gpu-allocator to count live allocations as it doesn't report any statistics
+///
+/// gpu-allocator persistently maps all host visible memory, mapped allocations are counted as
+/// non-local memory
+pub(crate) struct VulkanAllocator {
+    allocator: Allocator,
+    local: AllocationCounters,
+    non_local: AllocationCounters,
+}
+
+impl VulkanAllocator {
+    pub fn new(allocator: Allocator) -> Self {
+        Self {
+            allocator,
+            local: AllocationCounters::default(),
+            non_local: AllocationCounters::default(),
+        }
+    }
+
+    pub fn allocate(&mut self, desc: &AllocationCreateDesc) -> Result<Allocation> {
+        let allocation = self.allocator.allocate(desc)?;
+        let counters = self.counters_mut(&allocation);
+        counters.count += 1;
+        counters.bytes += allocation.size();
+        Ok(allocation)
+    }
+
+    pub fn free(&mut self, allocation: Allocation) -> Result<()> {
+        let counters = self.counters_mut(&allocation);
+        counters.count -= 1;
+        counters.bytes -= allocation.size();
+        self.allocator.free(allocation)
+    }
+
+    pub fn local_counters(&self) -> AllocationCounters {
+        self.local
+    }
+
+    pub fn non_local_counters(&self) -> AllocationCounters {
+        self.non_local
+    }
+
+    fn counters_mut(&mut self, allocation: &Allocation) -> &mut AllocationCounters {
+        if allocation.mapped_ptr().is_some() {
+            &mut self.non_local
+        } else {
+            &mut self.local
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::device::VulkanDevice;
+use ash::extensions::ext::DebugUtils;
+use ash::extensions::khr;
+use ash::vk;
+use parking_lot::Mutex;
+use std::ffi::{c_void, CStr};
+use std::sync::{Arc, Weak};
+use ze_core::{ze_error, ze_info, ze_verbose, ze_warn};
+use ze_gfx::backend::*;
+
+#[cfg(debug_assertions)]
+const ENABLE_DEBUG_LAYERS: bool = true;
+
+#[cfg(not(debug_assertions))]
+const ENABLE_DEBUG_LAYERS: bool = false;
+
+const VALIDATION_LAYER_NAME: &CStr = c"VK_LAYER_KHRONOS_validation";
+
+/// Surface extensions enabled when the loader supports them, the platform of the windows is only
+/// known when creating swapchains
+const SURFACE_EXTENSION_NAMES: [&CStr; 6] = [
+    khr::Surface::name(),
+    khr::XlibSurface::name(),
+    khr::XcbSurface::name(),
+    khr::WaylandSurface::name(),
+    khr::Win32Surface::name(),
+    ash::extensions::ext::MetalSurface::name(),
+];
+
+/// Vulkan instance, shared by the backend and its devices so it outlives them
+pub(crate) struct Instance {
+    pub entry: ash::Entry,
+    pub instance: ash::Instance,
+    pub debug_utils: Option<DebugUtils>,
+    debug_messenger: vk::DebugUtilsMessengerEXT,
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(debug_utils) = &self.debug_utils {
+                debug_utils.destroy_debug_utils_messenger(self.debug_messenger, None);
+            }
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+pub struct VulkanBackend {
+    instance: Arc<Instance>,
+    devices: Mutex<Vec<Weak<VulkanDevice>>>,
+}
+
+impl VulkanBackend {
+    pub fn new() -> Result<Arc<VulkanBackend>, BackendError> {
+        // The loader is loaded at runtime so a missing Vulkan driver isn't a link error
+        let entry = match unsafe { ash::Entry::load() } {
+            Ok(entry) => entry,
+            Err(_) => return Err(BackendError::Unsupported),
+        };
+
+        let available_extensions = entry
+            .enumerate_instance_extension_properties(None)
+            .unwrap_or_default();
+        let is_extension_available = |name: &CStr| {
+            available_extensions.iter().any(
+                |extension| unsafe { CStr::from_ptr(extension.extension_name.as_ptr()) } == name,
+            )
+        };
+
+        let mut extensions: Vec<*const i8> = SURFACE_EXTENSION_NAMES
+            .iter()
+            .filter(|name| is_extension_available(name))
+            .map(|name| name.as_ptr())
+            .collect();
+
+        let enable_debug_utils = ENABLE_DEBUG_LAYERS && is_extension_available(DebugUtils::name());
+        if enable_debug_utils {
+            extensions.push(DebugUtils::name().as_ptr());
+        }
+
+        // Enable validation layers
+        let mut layers = vec![];
+        if ENABLE_DEBUG_LAYERS {
+            let available_layers = entry
+                .enumerate_instance_layer_properties()
+                .unwrap_or_default();
+            if available_layers.iter().any(|layer| {
+                (unsafe { CStr::from_ptr(layer.layer_name.as_ptr()) }) == VALIDATION_LAYER_NAME
+            }) {
+                layers.push(VALIDATION_LAYER_NAME.as_ptr());
+                ze_info!("Using Vulkan validation layer");
+            }
+        }
+
+        let application_info = vk::ApplicationInfo::builder()
+            .application_name(c"ZinoEngine")
+            .engine_name(c"ZinoEngine")
+            .api_version(vk::API_VERSION_1_3);
+
+        let instance = unsafe {
+            match entry.create_instance(
+                &vk::InstanceCreateInfo::builder()
+                    .application_info(&application_info)
+                    .enabled_layer_names(&layers)
+                    .enabled_extension_names(&extensions),
+                None,
+            ) {
+                Ok(instance) => instance,
+                Err(_) => return Err(BackendError::Unsupported),
+            }
+        };
+
+        let (debug_utils, debug_messenger) = if enable_debug_utils {
+            let debug_utils = DebugUtils::new(&entry, &instance);
+            let messenger = unsafe {
+                debug_utils.create_debug_utils_messenger(
+                    &vk::DebugUtilsMessengerCreateInfoEXT::builder()
+                        .message_severity(
+                            vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+                                | vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+                        )
+                        .message_type(
+                            vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
+                                | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+                                | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
+                        )
+                        .pfn_user_callback(Some(debug_messenger_callback)),
+                    None,
+                )
+            }
+            .unwrap_or_default();
+            (Some(debug_utils), messenger)
+        } else {
+            (None, vk::DebugUtilsMessengerEXT::null())
+        };
+
+        Ok(Arc::new(VulkanBackend {
+            instance: Arc::new(Instance {
+                entry,
+                instance,
+                debug_utils,
+                debug_messenger,
+            }),
+            devices: Default::default(),
+        }))
+    }
+}
+
+impl Drop for VulkanBackend {
+    fn drop(&mut self) {
+        for device in self.devices.lock().iter() {
+            assert_eq!(device.strong_count(), 0);
+        }
+    }
+}
+
+impl Backend for VulkanBackend {
+    fn create_device(&self) -> Result<Arc<dyn Device>, BackendError> {
+        let instance = &self.instance.instance;
+
+        unsafe {
+            // Search for a compatible physical device, preferring discrete GPUs
+            let physical_devices = instance
+                .enumerate_physical_devices()
+                .map_err(|_| BackendError::Unsupported)?;
+
+            let mut physical_device_to_use = None;
+            for physical_device in physical_devices {
+                let properties = instance.get_physical_device_properties(physical_device);
+                if properties.device_type == vk::PhysicalDeviceType::CPU
+                    || properties.api_version < vk::API_VERSION_1_3
+                    || !VulkanDevice::is_physical_device_supported(&self.instance, physical_device)
+                {
+                    continue;
+                }
+
+                let name = CStr::from_ptr(properties.device_name.as_ptr()).to_string_lossy();
+                ze_info!("Found compatible adapter: {}", name);
+
+                let is_discrete = properties.device_type == vk::PhysicalDeviceType::DISCRETE_GPU;
+                if physical_device_to_use.is_none() || is_discrete {
+                    physical_device_to_use = Some(physical_device);
+                }
+            }
+
+            // Try create a device with this physical device
+            if let Some(physical_device) = physical_device_to_use {
+                match VulkanDevice::new(self.instance.clone(), physical_device) {
+                    Ok(device) => {
+                        let device = Arc::new(device);
+                        self.devices.lock().push(Arc::downgrade(&device));
+                        Ok(device)
+                    }
+                    Err(_) => Err(BackendError::Unsupported),
+                }
+            } else {
+                Err(BackendError::Unsupported)
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Vulkan"
+    }
+}
+
+unsafe extern "system" fn debug_messenger_callback(
+    severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    _: vk::DebugUtilsMessageTypeFlagsEXT,
+    data: *const vk::DebugUtilsMessengerCallbackDataEXT,
+    _: *mut c_void,
+) -> vk::Bool32 {
+    if data.is_null() || (*data).p_message.is_null() {
+        return vk::FALSE;
+    }
+
+    let message = CStr::from_ptr((*data).p_message).to_string_lossy();
+    match severity {
+        vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => ze_error!("{}", message),
+        vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => ze_warn!("{}", message),
+        vk::DebugUtilsMessageSeverityFlagsEXT::INFO => ze_info!("{}", message),
+        _ => ze_verbose!("{}", message),
+    }
+
+    vk::FALSE
+}
@@ -0,0 +1,395 @@
+use crate::device::cmd_list::VulkanCommandList;
+use crate::device::fence::VulkanFence;
+use crate::device::RawDevice;
+use ash::prelude::VkResult;
+use ash::vk;
+use parking_lot::Mutex;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use thread_local::ThreadLocal;
+use ze_gfx::backend;
+use ze_gfx::backend::{Fence, QueueType};
+
+/// A Vulkan queue, shared by every frame and possibly by multiple queue types when the device
+/// doesn't expose dedicated queue families
+pub(crate) struct Queue {
+    family_index: u32,
+    queue: Mutex<vk::Queue>,
+
+    /// Binary semaphores the next submission must wait on (e.g swapchain image acquisitions)
+    pending_wait_semaphores: Mutex<Vec<vk::Semaphore>>,
+}
+
+impl Queue {
+    pub fn new(family_index: u32, queue: vk::Queue) -> Self {
+        Self {
+            family_index,
+            queue: Mutex::new(queue),
+            pending_wait_semaphores: Default::default(),
+        }
+    }
+
+    pub fn family_index(&self) -> u32 {
+        self.family_index
+    }
+
+    pub fn add_pending_wait_semaphore(&self, semaphore: vk::Semaphore) {
+        self.pending_wait_semaphores.lock().push(semaphore);
+    }
+
+    pub fn submit(
+        &self,
+        device: &ash::Device,
+        wait_semaphores: &[vk::SemaphoreSubmitInfo],
+        command_buffers: &[vk::CommandBuffer],
+        signal_semaphores: &[vk::SemaphoreSubmitInfo],
+    ) -> VkResult<()> {
+        let queue = self.queue.lock();
+
+        let wait_semaphores: Vec<vk::SemaphoreSubmitInfo> = self
+            .pending_wait_semaphores
+            .lock()
+            .drain(..)
+            .map(|semaphore| {
+                vk::SemaphoreSubmitInfo::builder()
+                    .semaphore(semaphore)
+                    .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                    .build()
+            })
+            .chain(wait_semaphores.iter().copied())
+            .collect();
+
+        let command_buffers: Vec<vk::CommandBufferSubmitInfo> = command_buffers
+            .iter()
+            .map(|command_buffer| {
+                vk::CommandBufferSubmitInfo::builder()
+                    .command_buffer(*command_buffer)
+                    .build()
+            })
+            .collect();
+
+        let submit_info = vk::SubmitInfo2::builder()
+            .wait_semaphore_infos(&wait_semaphores)
+            .command_buffer_infos(&command_buffers)
+            .signal_semaphore_infos(signal_semaphores);
+
+        unsafe { device.queue_submit2(*queue, &[submit_info.build()], vk::Fence::null()) }
+    }
+
+    pub fn present(&self, device: &RawDevice, present_info: &vk::PresentInfoKHR) -> VkResult<bool> {
+        let queue = self.queue.lock();
+        unsafe { device.swapchain_loader.queue_present(*queue, present_info) }
+    }
+}
+
+struct CommandPool {
+    pool: vk::CommandPool,
+    free_command_buffers: VecDeque<vk::CommandBuffer>,
+    allocated_command_buffers: VecDeque<vk::CommandBuffer>,
+}
+
+impl CommandPool {
+    pub fn new(pool: vk::CommandPool) -> Self {
+        Self {
+            pool,
+            free_command_buffers: Default::default(),
+            allocated_command_buffers: Default::default(),
+        }
+    }
+
+    pub fn allocate(&mut self, device: &ash::Device) -> vk::CommandBuffer {
+        let command_buffer = if let Some(command_buffer) = self.free_command_buffers.pop_front() {
+            command_buffer
+        } else {
+            unsafe {
+                device.allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::builder()
+                        .command_pool(self.pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )
+            }
+            .unwrap()[0]
+        };
+
+        unsafe {
+            device
+                .begin_command_buffer(
+                    command_buffer,
+                    &vk::CommandBufferBeginInfo::builder()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )
+                .unwrap();
+        }
+
+        self.allocated_command_buffers.push_back(command_buffer);
+        command_buffer
+    }
+
+    pub fn reset(&mut self, device: &ash::Device) {
+        unsafe {
+            device
+                .reset_command_pool(self.pool, vk::CommandPoolResetFlags::empty())
+                .unwrap();
+        }
+
+        self.free_command_buffers
+            .append(&mut self.allocated_command_buffers);
+    }
+}
+
+struct SyncRefCell<T>(RefCell<T>);
+unsafe impl<T> Sync for SyncRefCell<T> {}
+
+struct CommandQueue {
+    ty: QueueType,
+    queue: Arc<Queue>,
+    pools: ThreadLocal<SyncRefCell<CommandPool>>,
+    work_semaphore: vk::Semaphore,
+    semaphore_counter: AtomicU64,
+    device: Arc<RawDevice>,
+}
+
+impl CommandQueue {
+    fn new(ty: QueueType, queue: Arc<Queue>, device: Arc<RawDevice>) -> Self {
+        let work_semaphore = unsafe {
+            let mut type_info = vk::SemaphoreTypeCreateInfo::builder()
+                .semaphore_type(vk::SemaphoreType::TIMELINE)
+                .initial_value(0);
+
+            device
+                .device
+                .create_semaphore(
+                    &vk::SemaphoreCreateInfo::builder().push_next(&mut type_info),
+                    None,
+                )
+                .unwrap()
+        };
+
+        Self {
+            ty,
+            queue,
+            pools: Default::default(),
+            work_semaphore,
+            semaphore_counter: AtomicU64::new(0),
+            device,
+        }
+    }
+
+    fn wait_for_work(&self) {
+        let prev_counter = self.semaphore_counter.fetch_add(1, Ordering::SeqCst);
+
+        let signal_info = vk::SemaphoreSubmitInfo::builder()
+            .semaphore(self.work_semaphore)
+            .value(prev_counter + 1)
+            .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .build();
+
+        unsafe {
+            self.queue
+                .submit(&self.device.device, &[], &[], &[signal_info])
+                .unwrap();
+
+            let semaphores = [self.work_semaphore];
+            let values = [prev_counter + 1];
+            self.device
+                .device
+                .wait_semaphores(
+                    &vk::SemaphoreWaitInfo::builder()
+                        .semaphores(&semaphores)
+                        .values(&values),
+                    u64::MAX,
+                )
+                .unwrap();
+        }
+    }
+
+    fn reset(&self) {
+        for pool in self.pools.iter() {
+            pool.0.borrow_mut().reset(&self.device.device);
+        }
+    }
+
+    fn submit(
+        &self,
+        command_lists: &[&backend::CommandList],
+        wait_fences: &[&Fence],
+        signal_fences: &[&Fence],
+    ) {
+        let wait_semaphores: Vec<vk::SemaphoreSubmitInfo> = wait_fences
+            .iter()
+            .map(|fence| {
+                let fence = unsafe {
+                    fence
+                        .backend_data
+                        .downcast_ref::<VulkanFence>()
+                        .unwrap_unchecked()
+                };
+
+                vk::SemaphoreSubmitInfo::builder()
+                    .semaphore(fence.semaphore)
+                    .value(fence.value.load(Ordering::SeqCst))
+                    .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                    .build()
+            })
+            .collect();
+
+        let mut command_buffers = Vec::with_capacity(command_lists.len());
+        for command_list in command_lists {
+            let command_list = unsafe {
+                command_list
+                    .backend_data
+                    .downcast_ref::<VulkanCommandList>()
+                    .unwrap_unchecked()
+            };
+
+            unsafe {
+                self.device
+                    .device
+                    .end_command_buffer(command_list.command_buffer)
+                    .unwrap();
+            }
+            command_buffers.push(command_list.command_buffer);
+        }
+
+        let signal_semaphores: Vec<vk::SemaphoreSubmitInfo> = signal_fences
+            .iter()
+            .map(|fence| {
+                let fence = unsafe {
+                    fence
+                        .backend_data
+                        .downcast_ref::<VulkanFence>()
+                        .unwrap_unchecked()
+                };
+
+                vk::SemaphoreSubmitInfo::builder()
+                    .semaphore(fence.semaphore)
+                    .value(fence.value.fetch_add(1, Ordering::SeqCst) + 1)
+                    .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                    .build()
+            })
+            .collect();
+
+        self.queue
+            .submit(
+                &self.device.device,
+                &wait_semaphores,
+                &command_buffers,
+                &signal_semaphores,
+            )
+            .unwrap();
+    }
+
+    fn get_or_create_pool(&self) -> &SyncRefCell<CommandPool> {
+        self.pools.get_or(|| {
+            let pool = unsafe {
+                self.device.device.create_command_pool(
+                    &vk::CommandPoolCreateInfo::builder()
+                        .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+                        .queue_family_index(self.queue.family_index()),
+                    None,
+                )
+            }
+            .unwrap();
+
+            self.device.set_object_name(
+                pool,
+                &format!("Command Pool (Thread: {:?})", thread::current().id()),
+            );
+
+            SyncRefCell(RefCell::new(CommandPool::new(pool)))
+        })
+    }
+}
+
+impl Drop for CommandQueue {
+    fn drop(&mut self) {
+        unsafe {
+            for pool in self.pools.iter_mut() {
+                self.device
+                    .device
+                    .destroy_command_pool(pool.0.get_mut().pool, None);
+            }
+
+            self.device
+                .device
+                .destroy_semaphore(self.work_semaphore, None);
+        }
+    }
+}
+
+/// Manage command pools and buffers
+/// When a command buffer is allocated, it'll be recycled on the next frame
+/// There is a set of pools per thread and one pool per queue type
+pub(crate) struct CommandManager {
+    queues: HashMap<QueueType, CommandQueue>,
+}
+
+impl CommandManager {
+    pub fn new(
+        device: &Arc<RawDevice>,
+        graphics_queue: &Arc<Queue>,
+        compute_queue: &Arc<Queue>,
+        transfer_queue: &Arc<Queue>,
+    ) -> Self {
+        let mut queues = HashMap::new();
+        queues.insert(
+            QueueType::Graphics,
+            CommandQueue::new(QueueType::Graphics, graphics_queue.clone(), device.clone()),
+        );
+        queues.insert(
+            QueueType::Compute,
+            CommandQueue::new(QueueType::Compute, compute_queue.clone(), device.clone()),
+        );
+        queues.insert(
+            QueueType::Transfer,
+            CommandQueue::new(QueueType::Transfer, transfer_queue.clone(), device.clone()),
+        );
+
+        Self { queues }
+    }
+
+    pub fn new_frame(&self) {
+        for queue in self.queues.values() {
+            queue.wait_for_work();
+            queue.reset();
+        }
+    }
+
+    pub fn create_command_list(&self, queue_type: QueueType) -> vk::CommandBuffer {
+        if let Some(queue) = self.queues.get(&queue_type) {
+            debug_assert_eq!(queue.ty, queue_type);
+
+            queue
+                .get_or_create_pool()
+                .0
+                .borrow_mut()
+                .allocate(&queue.device.device)
+        } else {
+            panic!("Queue not found");
+        }
+    }
+
+    pub fn wait_for_work(&self) {
+        for queue in self.queues.values() {
+            queue.wait_for_work();
+        }
+    }
+
+    pub fn submit(
+        &self,
+        queue_type: QueueType,
+        command_lists: &[&backend::CommandList],
+        wait_fences: &[&Fence],
+        signal_fences: &[&Fence],
+    ) {
+        if let Some(queue) = self.queues.get(&queue_type) {
+            queue.submit(command_lists, wait_fences, signal_fences);
+        } else {
+            panic!("Queue not found");
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::device::RawDevice;
+use ash::vk;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+pub const MAX_RESOURCE_DESCRIPTOR_COUNT: u32 = 1_000_000;
+pub const MAX_SAMPLER_DESCRIPTOR_COUNT: u32 = 1000;
+
+/// Binding of the resource array, resources share the same index space like in a D3D12
+/// CBV/SRV/UAV heap
+pub const RESOURCE_BINDING: u32 = 0;
+pub const SAMPLER_BINDING: u32 = 1;
+
+/// Descriptor types a resource descriptor can hold
+const RESOURCE_DESCRIPTOR_TYPES: [vk::DescriptorType; 3] = [
+    vk::DescriptorType::SAMPLED_IMAGE,
+    vk::DescriptorType::STORAGE_IMAGE,
+    vk::DescriptorType::STORAGE_BUFFER,
+];
+
+struct DescriptorIndexAllocator {
+    tail_index: Mutex<u32>,
+    free_indices_queue: Mutex<VecDeque<u32>>,
+    count: u32,
+}
+
+impl DescriptorIndexAllocator {
+    fn new(count: u32) -> Self {
+        Self {
+            tail_index: Mutex::new(0),
+            free_indices_queue: Default::default(),
+            count,
+        }
+    }
+
+    fn allocate(&self) -> u32 {
+        let mut queue = self.free_indices_queue.lock();
+        if let Some(index) = queue.pop_front() {
+            index
+        } else {
+            let mut tail_index = self.tail_index.lock();
+            assert!(*tail_index < self.count, "Out of descriptors");
+            let index = *tail_index;
+            *tail_index += 1;
+            index
+        }
+    }
+
+    fn free(&self, index: u32) {
+        let mut queue = self.free_indices_queue.lock();
+        queue.push_back(index);
+    }
+}
+
+/// Manage the bindless descriptor set
+/// A single update-after-bind set holds every shader visible resource and sampler, shaders
+/// index it directly like D3D12 descriptor heaps
+pub struct DescriptorManager {
+    resources: DescriptorIndexAllocator,
+    samplers: DescriptorIndexAllocator,
+    set_layout: vk::DescriptorSetLayout,
+    pool: vk::DescriptorPool,
+    set: Mutex<vk::DescriptorSet>,
+    device: Arc<RawDevice>,
+}
+
+impl DescriptorManager {
+    pub fn new(device: Arc<RawDevice>, resource_count: u32, sampler_count: u32) -> Self {
+        let mutable_type_lists = [
+            vk::MutableDescriptorTypeListEXT::builder()
+                .descriptor_types(&RESOURCE_DESCRIPTOR_TYPES)
+                .build(),
+            vk::MutableDescriptorTypeListEXT::default(),
+        ];
+
+        let set_layout = unsafe {
+            let bindings = [
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(RESOURCE_BINDING)
+                    .descriptor_type(vk::DescriptorType::MUTABLE_EXT)
+                    .descriptor_count(resource_count)
+                    .stage_flags(vk::ShaderStageFlags::ALL)
+                    .build(),
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(SAMPLER_BINDING)
+                    .descriptor_type(vk::DescriptorType::SAMPLER)
+                    .descriptor_count(sampler_count)
+                    .stage_flags(vk::ShaderStageFlags::ALL)
+                    .build(),
+            ];
+
+            let binding_flags = [vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND; 2];
+
+            let mut binding_flags_info = vk::DescriptorSetLayoutBindingFlagsCreateInfo::builder()
+                .binding_flags(&binding_flags);
+            let mut mutable_type_info = vk::MutableDescriptorTypeCreateInfoEXT::builder()
+                .mutable_descriptor_type_lists(&mutable_type_lists);
+
+            device
+                .device
+                .create_descriptor_set_layout(
+                    &vk::DescriptorSetLayoutCreateInfo::builder()
+                        .flags(vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+                        .bindings(&bindings)
+                        .push_next(&mut binding_flags_info)
+                        .push_next(&mut mutable_type_info),
+                    None,
+                )
+                .unwrap()
+        };
+
+        let pool = unsafe {
+            let pool_sizes = [
+                vk::DescriptorPoolSize {
+                    ty: vk::DescriptorType::MUTABLE_EXT,
+                    descriptor_count: resource_count,
+                },
+                vk::DescriptorPoolSize {
+                    ty: vk::DescriptorType::SAMPLER,
+                    descriptor_count: sampler_count,
+                },
+            ];
+
+            let mut mutable_type_info = vk::MutableDescriptorTypeCreateInfoEXT::builder()
+                .mutable_descriptor_type_lists(&mutable_type_lists);
+
+            device
+                .device
+                .create_descriptor_pool(
+                    &vk::DescriptorPoolCreateInfo::builder()
+                        .flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+                        .max_sets(1)
+                        .pool_sizes(&pool_sizes)
+                        .push_next(&mut mutable_type_info),
+                    None,
+                )
+                .unwrap()
+        };
+
+        let set = unsafe {
+            let set_layouts = [set_layout];
+            device
+                .device
+                .allocate_descriptor_sets(
+                    &vk::DescriptorSetAllocateInfo::builder()
+                        .descriptor_pool(pool)
+                        .set_layouts(&set_layouts),
+                )
+                .unwrap()[0]
+        };
+
+        device.set_object_name(set_layout, "Bindless Descriptor Set Layout");
+        device.set_object_name(pool, "Bindless Descriptor Pool");
+        device.set_object_name(set, "Bindless Descriptor Set");
+
+        Self {
+            resources: DescriptorIndexAllocator::new(resource_count),
+            samplers: DescriptorIndexAllocator::new(sampler_count),
+            set_layout,
+            pool,
+            set: Mutex::new(set),
+            device,
+        }
+    }
+
+    pub fn set_layout(&self) -> vk::DescriptorSetLayout {
+        self.set_layout
+    }
+
+    pub fn set(&self) -> vk::DescriptorSet {
+        *self.set.lock()
+    }
+
+    pub fn allocate_resource_descriptor_index(&self) -> u32 {
+        self.resources.allocate()
+    }
+
+    pub fn allocate_sampler_descriptor_index(&self) -> u32 {
+        self.samplers.allocate()
+    }
+
+    pub fn free_resource_descriptor_index(&self, index: u32) {
+        self.resources.free(index);
+    }
+
+    pub fn free_sampler_descriptor_index(&self, index: u32) {
+        self.samplers.free(index);
+    }
+
+    pub fn write_image_descriptor(
+        &self,
+        index: u32,
+        descriptor_type: vk::DescriptorType,
+        image_view: vk::ImageView,
+        image_layout: vk::ImageLayout,
+    ) {
+        let image_info = [vk::DescriptorImageInfo::builder()
+            .image_view(image_view)
+            .image_layout(image_layout)
+            .build()];
+
+        self.write(
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(RESOURCE_BINDING)
+                .dst_array_element(index)
+                .descriptor_type(descriptor_type)
+                .image_info(&image_info),
+        );
+    }
+
+    pub fn write_storage_buffer_descriptor(
+        &self,
+        index: u32,
+        buffer: vk::Buffer,
+        offset: u64,
+        range: u64,
+    ) {
+        let buffer_info = [vk::DescriptorBufferInfo::builder()
+            .buffer(buffer)
+            .offset(offset)
+            .range(range)
+            .build()];
+
+        self.write(
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(RESOURCE_BINDING)
+                .dst_array_element(index)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(&buffer_info),
+        );
+    }
+
+    pub fn write_sampler_descriptor(&self, index: u32, sampler: vk::Sampler) {
+        let image_info = [vk::DescriptorImageInfo::builder().sampler(sampler).build()];
+
+        self.write(
+            vk::WriteDescriptorSet::builder()
+                .dst_binding(SAMPLER_BINDING)
+                .dst_array_element(index)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .image_info(&image_info),
+        );
+    }
+
+    fn write(&self, write: vk::WriteDescriptorSetBuilder) {
+        // The set must be externally synchronized when updated
+        let set = self.set.lock();
+        unsafe {
+            self.device
+                .device
+                .update_descriptor_sets(&[write.dst_set(*set).build()], &[]);
+        }
+    }
+}
+
+impl Drop for DescriptorManager {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.device.destroy_descriptor_pool(self.pool, None);
+            self.device
+                .device
+                .destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
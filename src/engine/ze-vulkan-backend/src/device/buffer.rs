@@ -0,0 +1,47 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use gpu_allocator::vulkan::Allocation;
+use std::sync::Arc;
+
+pub(crate) struct VulkanBuffer {
+    pub frame_manager: Arc<FrameManager>,
+    pub buffer: vk::Buffer,
+    pub allocation: Option<Allocation>,
+    pub mapped_ptr: Option<*mut u8>,
+}
+
+unsafe impl Send for VulkanBuffer {}
+unsafe impl Sync for VulkanBuffer {}
+
+impl VulkanBuffer {
+    pub fn new(
+        frame_manager: Arc<FrameManager>,
+        buffer: vk::Buffer,
+        allocation: Option<Allocation>,
+        mapped_ptr: Option<*mut u8>,
+    ) -> Self {
+        Self {
+            frame_manager,
+            buffer,
+            allocation,
+            mapped_ptr,
+        }
+    }
+}
+
+impl Drop for VulkanBuffer {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::Buffer(self.buffer));
+
+        if let Some(allocation) = self.allocation.take() {
+            self.frame_manager
+                .current_frame()
+                .resource_queue()
+                .push(Entry::Allocation(allocation));
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::pipeline_manager::{GraphicsPipelineDesc, ShaderStageDesc};
+use ash::vk;
+use ze_gfx::backend::{QueueType, MAX_RENDER_PASS_RENDER_TARGET_COUNT};
+
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum VulkanCommandListPipelineType {
+    None,
+    Graphics(GraphicsPipelineDesc),
+    Compute(ShaderStageDesc),
+}
+
+impl VulkanCommandListPipelineType {
+    pub fn as_graphics_mut(&mut self) -> &mut GraphicsPipelineDesc {
+        match self {
+            VulkanCommandListPipelineType::Graphics(pipeline) => pipeline,
+            _ => panic!("Invalid pipeline type"),
+        }
+    }
+}
+
+pub(crate) struct VulkanCommandList {
+    pub command_buffer: vk::CommandBuffer,
+    pub queue_type: QueueType,
+    pub pipeline: VulkanCommandListPipelineType,
+    pub render_pass_color_count: u32,
+    pub render_pass_color_formats: [vk::Format; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
+    pub render_pass_depth_stencil_format: vk::Format,
+    pub render_pass_sample_count: vk::SampleCountFlags,
+    pub pipeline_state_dirty: bool,
+}
+
+impl VulkanCommandList {
+    pub fn new(command_buffer: vk::CommandBuffer, queue_type: QueueType) -> Self {
+        Self {
+            command_buffer,
+            queue_type,
+            pipeline: VulkanCommandListPipelineType::None,
+            render_pass_color_count: 0,
+            render_pass_color_formats: [vk::Format::UNDEFINED; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
+            render_pass_depth_stencil_format: vk::Format::UNDEFINED,
+            render_pass_sample_count: vk::SampleCountFlags::TYPE_1,
+            pipeline_state_dirty: true,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+/// Fences are backed by timeline semaphores
+pub(crate) struct VulkanFence {
+    pub frame_manager: Arc<FrameManager>,
+    pub semaphore: vk::Semaphore,
+
+    /// Last value a queue has been asked to signal
+    pub value: AtomicU64,
+}
+
+impl Drop for VulkanFence {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::Semaphore(self.semaphore));
+    }
+}
@@ -0,0 +1,3 @@
+/// Vulkan memory pools are not backed by a dedicated allocator pool yet,
+/// resources created with one are allocated from the default pools
+pub struct VulkanMemoryPool;
@@ -0,0 +1,2916 @@
+pub(crate) mod buffer;
+pub(crate) mod cmd_list;
+pub(crate) mod fence;
+mod memory_pool;
+pub(crate) mod query_pool;
+pub(crate) mod sampler;
+pub(crate) mod shader;
+pub(crate) mod swapchain;
+pub(crate) mod texture;
+pub(crate) mod views;
+
+use crate::allocator::VulkanAllocator;
+use crate::backend::Instance;
+use crate::command_manager::Queue;
+use crate::descriptor_manager::{
+    DescriptorManager, MAX_RESOURCE_DESCRIPTOR_COUNT, MAX_SAMPLER_DESCRIPTOR_COUNT,
+};
+use crate::device::buffer::VulkanBuffer;
+use crate::device::cmd_list::{VulkanCommandList, VulkanCommandListPipelineType};
+use crate::device::fence::VulkanFence;
+use crate::device::memory_pool::VulkanMemoryPool;
+use crate::device::query_pool::VulkanQueryPool;
+use crate::device::sampler::VulkanSampler;
+use crate::device::shader::{spirv_entry_point_name, VulkanShaderModule};
+use crate::device::swapchain::VulkanSwapChain;
+use crate::device::texture::VulkanTexture;
+use crate::device::views::{
+    VulkanDepthStencilView, VulkanRenderTargetView, VulkanShaderResourceView,
+    VulkanUnorderedAccessView,
+};
+use crate::frame_manager::FrameManager;
+use crate::pipeline_manager::{
+    DepthStencilDesc, GraphicsPipelineDesc, PipelineManager, RenderTargetBlendDesc,
+    ShaderStageDesc, StencilOpDesc,
+};
+use crate::utils::*;
+use ash::extensions::{ext, khr};
+use ash::vk;
+use ash::vk::Handle;
+use gpu_allocator::vulkan::{
+    AllocationCreateDesc, AllocationScheme, Allocator, AllocatorCreateDesc,
+};
+use gpu_allocator::AllocatorDebugSettings;
+use parking_lot::Mutex;
+use std::ffi::{CStr, CString};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use ze_core::color::Color4f32;
+use ze_core::maths::RectI32;
+use ze_core::{ze_error, ze_warn};
+use ze_gfx::backend::*;
+use ze_gfx::{PixelFormat, SampleDesc, ShaderStageFlagBits};
+
+/// Alignment of texture rows in buffers, matches D3D12 so the same copy code works on both
+const TEXTURE_ROW_PITCH_ALIGNMENT: u64 = 256;
+
+/// Push constants size, matches the 32 root constants of the D3D12 backend
+const PUSH_CONSTANTS_SIZE_IN_BYTES: u32 = 128;
+
+/// Device handle and extension loaders
+/// Shared with every object that needs to destroy Vulkan objects, the device is destroyed
+/// when the last reference is dropped
+pub(crate) struct RawDevice {
+    pub device: ash::Device,
+    pub swapchain_loader: khr::Swapchain,
+    pub surface_loader: khr::Surface,
+    pub mesh_shader: Option<ext::MeshShader>,
+    pub physical_device: vk::PhysicalDevice,
+    pub instance: Arc<Instance>,
+}
+
+impl RawDevice {
+    pub fn set_object_name<T: Handle>(&self, object: T, name: &str) {
+        set_object_name(
+            self.instance.debug_utils.as_ref(),
+            self.device.handle(),
+            object,
+            name,
+        );
+    }
+}
+
+impl Drop for RawDevice {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_device(None);
+        }
+    }
+}
+
+struct QueueFamilies {
+    graphics: u32,
+    compute: u32,
+    transfer: u32,
+}
+
+pub(crate) struct VulkanDevice {
+    descriptor_manager: Arc<DescriptorManager>,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline_manager: PipelineManager,
+    graphics_queue: Arc<Queue>,
+    _compute_queue: Arc<Queue>,
+    _transfer_queue: Arc<Queue>,
+    queue_family_indices: Vec<u32>,
+    frame_index: AtomicU64,
+    frame_manager: Arc<FrameManager>,
+    transient_memory_pool: MemoryPool,
+    depth_stencil_format: vk::Format,
+    timestamp_period: f32,
+    memory_budget_supported: bool,
+    allocator: Arc<Mutex<VulkanAllocator>>,
+    device: Arc<RawDevice>,
+}
+
+impl VulkanDevice {
+    pub fn is_physical_device_supported(
+        instance: &Instance,
+        physical_device: vk::PhysicalDevice,
+    ) -> bool {
+        if !is_device_extension_supported(instance, physical_device, khr::Swapchain::name())
+            || !is_device_extension_supported(
+                instance,
+                physical_device,
+                vk::ExtMutableDescriptorTypeFn::name(),
+            )
+            || Self::find_queue_families(instance, physical_device).is_none()
+        {
+            return false;
+        }
+
+        let mut features12 = vk::PhysicalDeviceVulkan12Features::default();
+        let mut features13 = vk::PhysicalDeviceVulkan13Features::default();
+        let mut mutable_descriptor_features =
+            vk::PhysicalDeviceMutableDescriptorTypeFeaturesEXT::default();
+        let mut features = vk::PhysicalDeviceFeatures2::builder()
+            .push_next(&mut features12)
+            .push_next(&mut features13)
+            .push_next(&mut mutable_descriptor_features);
+
+        unsafe {
+            instance
+                .instance
+                .get_physical_device_features2(physical_device, &mut features);
+        }
+
+        [
+            features12.runtime_descriptor_array,
+            features12.descriptor_binding_partially_bound,
+            features12.descriptor_binding_sampled_image_update_after_bind,
+            features12.descriptor_binding_storage_image_update_after_bind,
+            features12.descriptor_binding_storage_buffer_update_after_bind,
+            features12.shader_sampled_image_array_non_uniform_indexing,
+            features12.shader_storage_image_array_non_uniform_indexing,
+            features12.shader_storage_buffer_array_non_uniform_indexing,
+            features12.scalar_block_layout,
+            features12.timeline_semaphore,
+            features12.host_query_reset,
+            features13.dynamic_rendering,
+            features13.synchronization2,
+            mutable_descriptor_features.mutable_descriptor_type,
+        ]
+        .iter()
+        .all(|feature| *feature == vk::TRUE)
+    }
+
+    /// Search queue families for each queue type, preferring dedicated families for compute and
+    /// transfer queues
+    fn find_queue_families(
+        instance: &Instance,
+        physical_device: vk::PhysicalDevice,
+    ) -> Option<QueueFamilies> {
+        let families = unsafe {
+            instance
+                .instance
+                .get_physical_device_queue_family_properties(physical_device)
+        };
+
+        let find_family = |required: vk::QueueFlags, excluded: vk::QueueFlags| {
+            families
+                .iter()
+                .position(|family| {
+                    family.queue_count > 0
+                        && family.queue_flags.contains(required)
+                        && !family.queue_flags.intersects(excluded)
+                })
+                .map(|index| index as u32)
+        };
+
+        let graphics = find_family(
+            vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE,
+            vk::QueueFlags::empty(),
+        )?;
+        let compute =
+            find_family(vk::QueueFlags::COMPUTE, vk::QueueFlags::GRAPHICS).unwrap_or(graphics);
+        let transfer = find_family(
+            vk::QueueFlags::TRANSFER,
+            vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE,
+        )
+        .unwrap_or(compute);
+
+        Some(QueueFamilies {
+            graphics,
+            compute,
+            transfer,
+        })
+    }
+
+    pub fn new(
+        instance: Arc<Instance>,
+        physical_device: vk::PhysicalDevice,
+    ) -> Result<Self, DeviceError> {
+        let queue_families = Self::find_queue_families(&instance, physical_device)
+            .ok_or(DeviceError::InvalidParameters)?;
+
+        let mut queue_family_indices = vec![
+            queue_families.graphics,
+            queue_families.compute,
+            queue_families.transfer,
+        ];
+        queue_family_indices.dedup();
+
+        let queue_priorities = [1.0];
+        let queue_create_infos: Vec<vk::DeviceQueueCreateInfo> = queue_family_indices
+            .iter()
+            .map(|index| {
+                vk::DeviceQueueCreateInfo::builder()
+                    .queue_family_index(*index)
+                    .queue_priorities(&queue_priorities)
+                    .build()
+            })
+            .collect();
+
+        let mesh_shader_supported = {
+            let mut mesh_shader_features = vk::PhysicalDeviceMeshShaderFeaturesEXT::default();
+            if is_device_extension_supported(&instance, physical_device, ext::MeshShader::name()) {
+                let mut features =
+                    vk::PhysicalDeviceFeatures2::builder().push_next(&mut mesh_shader_features);
+                unsafe {
+                    instance
+                        .instance
+                        .get_physical_device_features2(physical_device, &mut features);
+                }
+            }
+            mesh_shader_features.mesh_shader == vk::TRUE
+        };
+
+        let (supported_features, shader_draw_parameters_supported) = {
+            let mut features11 = vk::PhysicalDeviceVulkan11Features::default();
+            let mut features = vk::PhysicalDeviceFeatures2::builder().push_next(&mut features11);
+            unsafe {
+                instance
+                    .instance
+                    .get_physical_device_features2(physical_device, &mut features);
+            }
+            (features.features, features11.shader_draw_parameters)
+        };
+
+        let mut extensions = vec![
+            khr::Swapchain::name().as_ptr(),
+            vk::ExtMutableDescriptorTypeFn::name().as_ptr(),
+        ];
+        if mesh_shader_supported {
+            extensions.push(ext::MeshShader::name().as_ptr());
+        }
+
+        let memory_budget_supported = is_device_extension_supported(
+            &instance,
+            physical_device,
+            vk::ExtMemoryBudgetFn::name(),
+        );
+        if memory_budget_supported {
+            extensions.push(vk::ExtMemoryBudgetFn::name().as_ptr());
+        }
+
+        let features = vk::PhysicalDeviceFeatures::builder()
+            .sampler_anisotropy(supported_features.sampler_anisotropy == vk::TRUE)
+            .shader_int16(supported_features.shader_int16 == vk::TRUE)
+            .shader_int64(supported_features.shader_int64 == vk::TRUE)
+            .build();
+        let mut features11 = vk::PhysicalDeviceVulkan11Features::builder()
+            .shader_draw_parameters(shader_draw_parameters_supported == vk::TRUE);
+        let mut features12 = vk::PhysicalDeviceVulkan12Features::builder()
+            .runtime_descriptor_array(true)
+            .descriptor_binding_partially_bound(true)
+            .descriptor_binding_sampled_image_update_after_bind(true)
+            .descriptor_binding_storage_image_update_after_bind(true)
+            .descriptor_binding_storage_buffer_update_after_bind(true)
+            .shader_sampled_image_array_non_uniform_indexing(true)
+            .shader_storage_image_array_non_uniform_indexing(true)
+            .shader_storage_buffer_array_non_uniform_indexing(true)
+            .scalar_block_layout(true)
+            .timeline_semaphore(true)
+            .host_query_reset(true);
+        let mut features13 = vk::PhysicalDeviceVulkan13Features::builder()
+            .dynamic_rendering(true)
+            .synchronization2(true);
+        let mut mutable_descriptor_features =
+            vk::PhysicalDeviceMutableDescriptorTypeFeaturesEXT::builder()
+                .mutable_descriptor_type(true);
+        let mut mesh_shader_features =
+            vk::PhysicalDeviceMeshShaderFeaturesEXT::builder().mesh_shader(true);
+
+        let mut device_create_info = vk::DeviceCreateInfo::builder()
+            .queue_create_infos(&queue_create_infos)
+            .enabled_extension_names(&extensions)
+            .enabled_features(&features)
+            .push_next(&mut features11)
+            .push_next(&mut features12)
+            .push_next(&mut features13)
+            .push_next(&mut mutable_descriptor_features);
+        if mesh_shader_supported {
+            device_create_info = device_create_info.push_next(&mut mesh_shader_features);
+        }
+
+        let device = unsafe {
+            instance
+                .instance
+                .create_device(physical_device, &device_create_info, None)
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+
+        let device = Arc::new(RawDevice {
+            swapchain_loader: khr::Swapchain::new(&instance.instance, &device),
+            surface_loader: khr::Surface::new(&instance.entry, &instance.instance),
+            mesh_shader: if mesh_shader_supported {
+                Some(ext::MeshShader::new(&instance.instance, &device))
+            } else {
+                None
+            },
+            device,
+            physical_device,
+            instance: instance.clone(),
+        });
+
+        let get_queue = |family_index: u32, name: &str| {
+            let queue = unsafe { device.device.get_device_queue(family_index, 0) };
+            device.set_object_name(queue, name);
+            Arc::new(Queue::new(family_index, queue))
+        };
+
+        let graphics_queue = get_queue(queue_families.graphics, "Graphics Queue");
+        let compute_queue = if queue_families.compute == queue_families.graphics {
+            graphics_queue.clone()
+        } else {
+            get_queue(queue_families.compute, "Compute Queue")
+        };
+        let transfer_queue = if queue_families.transfer == queue_families.graphics {
+            graphics_queue.clone()
+        } else if queue_families.transfer == queue_families.compute {
+            compute_queue.clone()
+        } else {
+            get_queue(queue_families.transfer, "Transfer Queue")
+        };
+
+        let allocator = Allocator::new(&AllocatorCreateDesc {
+            instance: instance.instance.clone(),
+            device: device.device.clone(),
+            physical_device,
+            debug_settings: AllocatorDebugSettings::default(),
+            buffer_device_address: false,
+        })
+        .map_err(convert_allocation_error_to_ze_device_error)?;
+        let allocator = Arc::new(Mutex::new(VulkanAllocator::new(allocator)));
+
+        // Clamp bindless arrays to the device limits
+        let (resource_descriptor_count, sampler_descriptor_count) = {
+            let mut properties12 = vk::PhysicalDeviceVulkan12Properties::default();
+            let mut properties =
+                vk::PhysicalDeviceProperties2::builder().push_next(&mut properties12);
+            unsafe {
+                instance
+                    .instance
+                    .get_physical_device_properties2(physical_device, &mut properties);
+            }
+
+            let sampler_count = MAX_SAMPLER_DESCRIPTOR_COUNT
+                .min(properties12.max_per_stage_descriptor_update_after_bind_samplers)
+                .min(properties12.max_descriptor_set_update_after_bind_samplers);
+
+            let resource_count = MAX_RESOURCE_DESCRIPTOR_COUNT
+                .min(properties12.max_per_stage_descriptor_update_after_bind_sampled_images)
+                .min(properties12.max_per_stage_descriptor_update_after_bind_storage_images)
+                .min(properties12.max_per_stage_descriptor_update_after_bind_storage_buffers)
+                .min(properties12.max_descriptor_set_update_after_bind_sampled_images)
+                .min(properties12.max_descriptor_set_update_after_bind_storage_images)
+                .min(properties12.max_descriptor_set_update_after_bind_storage_buffers)
+                .min(
+                    properties12
+                        .max_per_stage_update_after_bind_resources
+                        .saturating_sub(sampler_count),
+                );
+
+            (resource_count, sampler_count)
+        };
+
+        let descriptor_manager = Arc::new(DescriptorManager::new(
+            device.clone(),
+            resource_descriptor_count,
+            sampler_descriptor_count,
+        ));
+
+        let pipeline_layout = {
+            let set_layouts = [descriptor_manager.set_layout()];
+            let push_constant_ranges = [vk::PushConstantRange {
+                stage_flags: vk::ShaderStageFlags::ALL,
+                offset: 0,
+                size: PUSH_CONSTANTS_SIZE_IN_BYTES,
+            }];
+
+            unsafe {
+                device.device.create_pipeline_layout(
+                    &vk::PipelineLayoutCreateInfo::builder()
+                        .set_layouts(&set_layouts)
+                        .push_constant_ranges(&push_constant_ranges),
+                    None,
+                )
+            }
+            .map_err(convert_vk_error_to_ze_device_error)?
+        };
+        device.set_object_name(pipeline_layout, "Default Pipeline Layout");
+
+        let timestamp_period = unsafe {
+            instance
+                .instance
+                .get_physical_device_properties(physical_device)
+        }
+        .limits
+        .timestamp_period;
+
+        // D24S8 is not supported everywhere (e.g AMD), use the closest format instead
+        let depth_stencil_format = {
+            let properties = unsafe {
+                instance.instance.get_physical_device_format_properties(
+                    physical_device,
+                    vk::Format::D24_UNORM_S8_UINT,
+                )
+            };
+
+            if properties
+                .optimal_tiling_features
+                .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+            {
+                vk::Format::D24_UNORM_S8_UINT
+            } else {
+                vk::Format::D32_SFLOAT_S8_UINT
+            }
+        };
+
+        Ok(Self {
+            frame_manager: Arc::new(FrameManager::new(
+                2,
+                &device,
+                &allocator,
+                &graphics_queue,
+                &compute_queue,
+                &transfer_queue,
+            )),
+            descriptor_manager,
+            pipeline_layout,
+            pipeline_manager: PipelineManager::new(device.clone()),
+            graphics_queue,
+            _compute_queue: compute_queue,
+            _transfer_queue: transfer_queue,
+            queue_family_indices,
+            frame_index: AtomicU64::new(0),
+            transient_memory_pool: MemoryPool::new(Box::new(VulkanMemoryPool)),
+            depth_stencil_format,
+            timestamp_period,
+            memory_budget_supported,
+            allocator,
+            device,
+        })
+    }
+
+    fn flush_pipeline_state(&self, command_list: &mut VulkanCommandList) {
+        if command_list.pipeline_state_dirty {
+            match &mut command_list.pipeline {
+                VulkanCommandListPipelineType::Graphics(desc) => {
+                    // Apply render pass parameters to desc
+                    desc.color_formats = command_list.render_pass_color_formats;
+                    desc.color_format_count = command_list.render_pass_color_count;
+                    desc.depth_stencil_format = command_list.render_pass_depth_stencil_format;
+                    desc.sample_count = command_list.render_pass_sample_count;
+
+                    let pipeline = self
+                        .pipeline_manager
+                        .get_or_create_graphics_pipeline(self.pipeline_layout, desc);
+                    unsafe {
+                        self.device.device.cmd_bind_pipeline(
+                            command_list.command_buffer,
+                            vk::PipelineBindPoint::GRAPHICS,
+                            pipeline,
+                        );
+                    }
+                }
+                VulkanCommandListPipelineType::Compute(compute_shader) => {
+                    let pipeline = self
+                        .pipeline_manager
+                        .get_or_create_compute_pipeline(self.pipeline_layout, compute_shader);
+                    unsafe {
+                        self.device.device.cmd_bind_pipeline(
+                            command_list.command_buffer,
+                            vk::PipelineBindPoint::COMPUTE,
+                            pipeline,
+                        );
+                    }
+                }
+                _ => {}
+            }
+
+            command_list.pipeline_state_dirty = false;
+        }
+    }
+
+    fn set_compute_shader(&self, cmd_list: &mut VulkanCommandList, module: &ShaderModule) {
+        cmd_list.pipeline = VulkanCommandListPipelineType::Compute(get_shader_stage_desc(module));
+        cmd_list.pipeline_state_dirty = true;
+    }
+
+    fn get_vk_format(&self, format: PixelFormat) -> vk::Format {
+        match format {
+            PixelFormat::D24UnormS8Uint => self.depth_stencil_format,
+            _ => get_vk_format_from_ze_format(format),
+        }
+    }
+
+    fn sharing_mode(&self) -> vk::SharingMode {
+        if self.queue_family_indices.len() > 1 {
+            vk::SharingMode::CONCURRENT
+        } else {
+            vk::SharingMode::EXCLUSIVE
+        }
+    }
+
+    /// Images start in an undefined layout, transition them to the layout matching the common
+    /// state so they can be used like D3D12 resources that are implicitly promoted
+    fn promote_texture_from_undefined(&self, command_buffer: vk::CommandBuffer, texture: &Texture) {
+        let vk_texture = texture
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+        if vk_texture.layout() != vk::ImageLayout::UNDEFINED {
+            return;
+        }
+
+        vk_texture.set_layout(vk::ImageLayout::GENERAL);
+
+        let image_barriers = [vk::ImageMemoryBarrier2::builder()
+            .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .dst_access_mask(vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE)
+            .old_layout(vk::ImageLayout::UNDEFINED)
+            .new_layout(vk::ImageLayout::GENERAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(vk_texture.image)
+            .subresource_range(full_subresource_range(vk_texture.aspect_mask))
+            .build()];
+
+        unsafe {
+            self.device.device.cmd_pipeline_barrier2(
+                command_buffer,
+                &vk::DependencyInfo::builder().image_memory_barriers(&image_barriers),
+            );
+        }
+    }
+
+    /// Make transfer writes to a readback buffer visible to the host, readback buffers are not
+    /// transitioned by the user
+    fn cmd_readback_buffer_barrier(&self, command_buffer: vk::CommandBuffer, buffer: vk::Buffer) {
+        let buffer_barriers = [vk::BufferMemoryBarrier2::builder()
+            .src_stage_mask(vk::PipelineStageFlags2::COPY)
+            .src_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::HOST)
+            .dst_access_mask(vk::AccessFlags2::HOST_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+            .build()];
+
+        unsafe {
+            self.device.device.cmd_pipeline_barrier2(
+                command_buffer,
+                &vk::DependencyInfo::builder().buffer_memory_barriers(&buffer_barriers),
+            );
+        }
+    }
+
+    fn create_image_view(
+        &self,
+        texture: &Texture,
+        view_type: vk::ImageViewType,
+        subresource_range: vk::ImageSubresourceRange,
+    ) -> Result<vk::ImageView, DeviceError> {
+        let vk_texture = texture
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+
+        unsafe {
+            self.device.device.create_image_view(
+                &vk::ImageViewCreateInfo::builder()
+                    .image(vk_texture.image)
+                    .view_type(view_type)
+                    .format(vk_texture.format)
+                    .subresource_range(subresource_range),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)
+    }
+
+    /// Create a sampled image view of a texture and write it to the resource descriptor `index`
+    /// The descriptor index is freed on failure
+    fn create_texture_srv(
+        &self,
+        index: u32,
+        texture: &Texture,
+        view_type: vk::ImageViewType,
+        mut subresource_range: vk::ImageSubresourceRange,
+    ) -> Result<vk::ImageView, DeviceError> {
+        let vk_texture = texture
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+
+        // Depth textures are sampled through their depth aspect only
+        subresource_range.aspect_mask =
+            if vk_texture.aspect_mask.contains(vk::ImageAspectFlags::DEPTH) {
+                vk::ImageAspectFlags::DEPTH
+            } else {
+                vk_texture.aspect_mask
+            };
+
+        let view = match self.create_image_view(texture, view_type, subresource_range) {
+            Ok(view) => view,
+            Err(err) => {
+                self.descriptor_manager
+                    .free_resource_descriptor_index(index);
+                return Err(err);
+            }
+        };
+
+        self.descriptor_manager.write_image_descriptor(
+            index,
+            vk::DescriptorType::SAMPLED_IMAGE,
+            view,
+            vk::ImageLayout::GENERAL,
+        );
+
+        Ok(view)
+    }
+
+    /// Create a storage image view of a texture mip level and write it to the resource descriptor
+    /// `index`
+    /// The descriptor index is freed on failure
+    fn create_texture_uav(
+        &self,
+        index: u32,
+        texture: &Texture,
+        view_type: vk::ImageViewType,
+        mip_level: u32,
+    ) -> Result<vk::ImageView, DeviceError> {
+        debug_assert!(texture
+            .desc
+            .usage_flags
+            .contains(TextureUsageFlagBits::UnorderedAccess));
+
+        let vk_texture = texture
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+
+        let view = match self.create_image_view(
+            texture,
+            view_type,
+            vk::ImageSubresourceRange {
+                aspect_mask: vk_texture.aspect_mask,
+                base_mip_level: mip_level,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+        ) {
+            Ok(view) => view,
+            Err(err) => {
+                self.descriptor_manager
+                    .free_resource_descriptor_index(index);
+                return Err(err);
+            }
+        };
+
+        self.descriptor_manager.write_image_descriptor(
+            index,
+            vk::DescriptorType::STORAGE_IMAGE,
+            view,
+            vk::ImageLayout::GENERAL,
+        );
+
+        Ok(view)
+    }
+
+    fn create_swapchain_for_surface(
+        &self,
+        info: &SwapChainDesc,
+        surface: vk::SurfaceKHR,
+        old_swapchain: vk::SwapchainKHR,
+    ) -> Result<(vk::SwapchainKHR, vk::Format, vk::Extent2D), vk::Result> {
+        let surface_loader = &self.device.surface_loader;
+        let physical_device = self.device.physical_device;
+
+        unsafe {
+            if !surface_loader.get_physical_device_surface_support(
+                physical_device,
+                self.graphics_queue.family_index(),
+                surface,
+            )? {
+                return Err(vk::Result::ERROR_INCOMPATIBLE_DISPLAY_KHR);
+            }
+
+            let capabilities = surface_loader
+                .get_physical_device_surface_capabilities(physical_device, surface)?;
+            let formats =
+                surface_loader.get_physical_device_surface_formats(physical_device, surface)?;
+            let present_modes = surface_loader
+                .get_physical_device_surface_present_modes(physical_device, surface)?;
+
+            // Use the requested format if possible
+            let requested_format = self.get_vk_format(info.format);
+            let surface_format = formats
+                .iter()
+                .find(|format| format.format == requested_format)
+                .or_else(|| {
+                    formats
+                        .iter()
+                        .find(|format| format.format == vk::Format::B8G8R8A8_UNORM)
+                })
+                .or_else(|| formats.first())
+                .copied()
+                .ok_or(vk::Result::ERROR_FORMAT_NOT_SUPPORTED)?;
+
+            // Don't wait for vertical blanks, like the D3D12 backend that allows tearing
+            let present_mode = [
+                vk::PresentModeKHR::IMMEDIATE,
+                vk::PresentModeKHR::MAILBOX,
+                vk::PresentModeKHR::FIFO,
+            ]
+            .into_iter()
+            .find(|mode| present_modes.contains(mode))
+            .unwrap_or(vk::PresentModeKHR::FIFO);
+
+            let mut image_count = (self.frame_manager.frame_count() as u32)
+                .max(2)
+                .max(capabilities.min_image_count);
+            if capabilities.max_image_count > 0 {
+                image_count = image_count.min(capabilities.max_image_count);
+            }
+
+            let extent = if capabilities.current_extent.width != u32::MAX {
+                capabilities.current_extent
+            } else {
+                vk::Extent2D {
+                    width: info.width.clamp(
+                        capabilities.min_image_extent.width,
+                        capabilities.max_image_extent.width,
+                    ),
+                    height: info.height.clamp(
+                        capabilities.min_image_extent.height,
+                        capabilities.max_image_extent.height,
+                    ),
+                }
+            };
+
+            let mut usage = vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSFER_SRC
+                | vk::ImageUsageFlags::TRANSFER_DST;
+            if info
+                .usage_flags
+                .contains(TextureUsageFlagBits::UnorderedAccess)
+            {
+                usage |= vk::ImageUsageFlags::STORAGE;
+            }
+            usage &= capabilities.supported_usage_flags;
+
+            let composite_alpha = [
+                vk::CompositeAlphaFlagsKHR::OPAQUE,
+                vk::CompositeAlphaFlagsKHR::INHERIT,
+                vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+                vk::CompositeAlphaFlagsKHR::POST_MULTIPLIED,
+            ]
+            .into_iter()
+            .find(|flag| capabilities.supported_composite_alpha.contains(*flag))
+            .unwrap_or(vk::CompositeAlphaFlagsKHR::OPAQUE);
+
+            let swapchain = self.device.swapchain_loader.create_swapchain(
+                &vk::SwapchainCreateInfoKHR::builder()
+                    .surface(surface)
+                    .min_image_count(image_count)
+                    .image_format(surface_format.format)
+                    .image_color_space(surface_format.color_space)
+                    .image_extent(extent)
+                    .image_array_layers(1)
+                    .image_usage(usage)
+                    .image_sharing_mode(self.sharing_mode())
+                    .queue_family_indices(&self.queue_family_indices)
+                    .pre_transform(capabilities.current_transform)
+                    .composite_alpha(composite_alpha)
+                    .present_mode(present_mode)
+                    .clipped(true)
+                    .old_swapchain(old_swapchain),
+                None,
+            )?;
+
+            Ok((swapchain, surface_format.format, extent))
+        }
+    }
+}
+
+impl Drop for VulkanDevice {
+    fn drop(&mut self) {
+        self.wait_idle();
+        unsafe {
+            self.device.device.device_wait_idle().unwrap();
+            self.device
+                .device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+        }
+        assert_eq!(Arc::strong_count(&self.frame_manager), 1);
+    }
+}
+
+impl Device for VulkanDevice {
+    fn begin_frame(&self) {
+        let old_count = self.frame_index.fetch_add(1, Ordering::SeqCst);
+
+        if old_count > 0 {
+            self.frame_manager.begin_frame(self);
+        }
+    }
+
+    fn end_frame(&self) {}
+
+    fn create_buffer(
+        &self,
+        info: &BufferDesc,
+        _: Option<&MemoryPool>,
+        name: &str,
+    ) -> Result<Buffer, DeviceError> {
+        let mut usage = vk::BufferUsageFlags::TRANSFER_SRC
+            | vk::BufferUsageFlags::TRANSFER_DST
+            | vk::BufferUsageFlags::STORAGE_BUFFER;
+        if info.usage.contains(BufferUsageFlagBits::IndexBuffer) {
+            usage |= vk::BufferUsageFlags::INDEX_BUFFER;
+        }
+
+        let buffer = unsafe {
+            self.device.device.create_buffer(
+                &vk::BufferCreateInfo::builder()
+                    .size(info.size_bytes)
+                    .usage(usage)
+                    .sharing_mode(self.sharing_mode())
+                    .queue_family_indices(&self.queue_family_indices),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+
+        let requirements = unsafe { self.device.device.get_buffer_memory_requirements(buffer) };
+        let allocation = self.allocator.lock().allocate(&AllocationCreateDesc {
+            name,
+            requirements,
+            location: get_memory_location_from_ze_memory_location(info.memory_desc.memory_location),
+            linear: true,
+            allocation_scheme: AllocationScheme::GpuAllocatorManaged,
+        });
+
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { self.device.device.destroy_buffer(buffer, None) };
+                return Err(convert_allocation_error_to_ze_device_error(err));
+            }
+        };
+
+        if let Err(err) = unsafe {
+            self.device
+                .device
+                .bind_buffer_memory(buffer, allocation.memory(), allocation.offset())
+        } {
+            unsafe { self.device.device.destroy_buffer(buffer, None) };
+            let _ = self.allocator.lock().free(allocation);
+            return Err(convert_vk_error_to_ze_device_error(err));
+        }
+
+        // CPU visible memory is persistently mapped by the allocator
+        let mapped_ptr = match info.memory_desc.memory_location {
+            MemoryLocation::CpuToGpu | MemoryLocation::GpuToCpu => allocation
+                .mapped_ptr()
+                .map(|mapped_ptr| mapped_ptr.as_ptr().cast::<u8>()),
+            MemoryLocation::GpuOnly => None,
+        };
+
+        self.device.set_object_name(buffer, name);
+
+        Ok(Buffer::new(
+            info,
+            Box::new(VulkanBuffer::new(
+                self.frame_manager.clone(),
+                buffer,
+                Some(allocation),
+                mapped_ptr,
+            )),
+        ))
+    }
+
+    fn create_texture(
+        &self,
+        info: &TextureDesc,
+        _: Option<&MemoryPool>,
+        name: &str,
+    ) -> Result<Texture, DeviceError> {
+        let mut usage = vk::ImageUsageFlags::TRANSFER_SRC
+            | vk::ImageUsageFlags::TRANSFER_DST
+            | vk::ImageUsageFlags::SAMPLED;
+        if info
+            .usage_flags
+            .contains(TextureUsageFlagBits::UnorderedAccess)
+        {
+            usage |= vk::ImageUsageFlags::STORAGE;
+        }
+
+        if info
+            .usage_flags
+            .contains(TextureUsageFlagBits::RenderTarget)
+        {
+            usage |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+        }
+
+        if info
+            .usage_flags
+            .contains(TextureUsageFlagBits::DepthStencil)
+        {
+            usage |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+        }
+
+        let image_type = {
+            if info.depth > 1 {
+                vk::ImageType::TYPE_3D
+            } else if info.height > 0 {
+                vk::ImageType::TYPE_2D
+            } else {
+                vk::ImageType::TYPE_1D
+            }
+        };
+
+        // Allow cube views on square textures with a multiple of 6 slices
+        let mut flags = if info.array_size >= 6
+            && info.array_size.is_multiple_of(6)
+            && info.width == info.height
+        {
+            vk::ImageCreateFlags::CUBE_COMPATIBLE
+        } else {
+            vk::ImageCreateFlags::empty()
+        };
+
+        // Render target views of 3D textures are 2D array views
+        if image_type == vk::ImageType::TYPE_3D
+            && info
+                .usage_flags
+                .contains(TextureUsageFlagBits::RenderTarget)
+        {
+            flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE;
+        }
+
+        let format = self.get_vk_format(info.format);
+        let image = unsafe {
+            self.device.device.create_image(
+                &vk::ImageCreateInfo::builder()
+                    .image_type(image_type)
+                    .format(format)
+                    .extent(vk::Extent3D {
+                        width: info.width,
+                        height: info.height.max(1),
+                        depth: info.depth.max(1),
+                    })
+                    .flags(flags)
+                    .mip_levels(info.mip_levels)
+                    .array_layers(info.array_size.max(1))
+                    .samples(get_vk_sample_count_from_ze_sample_desc(info.sample_desc))
+                    .tiling(vk::ImageTiling::OPTIMAL)
+                    .usage(usage)
+                    .sharing_mode(self.sharing_mode())
+                    .queue_family_indices(&self.queue_family_indices)
+                    .initial_layout(vk::ImageLayout::UNDEFINED),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+
+        let requirements = unsafe { self.device.device.get_image_memory_requirements(image) };
+        let allocation = self.allocator.lock().allocate(&AllocationCreateDesc {
+            name,
+            requirements,
+            location: get_memory_location_from_ze_memory_location(info.memory_desc.memory_location),
+            linear: false,
+            allocation_scheme: AllocationScheme::GpuAllocatorManaged,
+        });
+
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(err) => {
+                unsafe { self.device.device.destroy_image(image, None) };
+                return Err(convert_allocation_error_to_ze_device_error(err));
+            }
+        };
+
+        if let Err(err) = unsafe {
+            self.device
+                .device
+                .bind_image_memory(image, allocation.memory(), allocation.offset())
+        } {
+            unsafe { self.device.device.destroy_image(image, None) };
+            let _ = self.allocator.lock().free(allocation);
+            return Err(convert_vk_error_to_ze_device_error(err));
+        }
+
+        self.device.set_object_name(image, name);
+
+        Ok(Texture::new(
+            *info,
+            Box::new(VulkanTexture::new(
+                self.frame_manager.clone(),
+                image,
+                Some(allocation),
+                format,
+                get_vk_aspect_mask_from_vk_format(format),
+                false,
+            )),
+        ))
+    }
+
+    fn create_shader_resource_view(
+        &self,
+        desc: &ShaderResourceViewDesc,
+    ) -> Result<ShaderResourceView, DeviceError> {
+        let index = self.descriptor_manager.allocate_resource_descriptor_index();
+
+        let view = match desc {
+            ShaderResourceViewDesc::Buffer(buffer) => {
+                let offset = match &buffer.ty {
+                    BufferSRVType::Raw(raw) => raw.offset_in_bytes as u64,
+                    BufferSRVType::Structured(structured) => structured.offset_in_bytes,
+                };
+
+                let vk_buffer = buffer
+                    .buffer
+                    .backend_data
+                    .downcast_ref::<VulkanBuffer>()
+                    .unwrap();
+
+                self.descriptor_manager.write_storage_buffer_descriptor(
+                    index,
+                    vk_buffer.buffer,
+                    offset,
+                    vk::WHOLE_SIZE,
+                );
+
+                None
+            }
+            ShaderResourceViewDesc::Texture2D(texture) => Some(self.create_texture_srv(
+                index,
+                &texture.texture,
+                vk::ImageViewType::TYPE_2D,
+                vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::empty(),
+                    base_mip_level: texture.min_mip_level,
+                    level_count: texture.mip_levels,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+            )?),
+            ShaderResourceViewDesc::Texture2DArray(texture) => Some(self.create_texture_srv(
+                index,
+                &texture.texture,
+                vk::ImageViewType::TYPE_2D_ARRAY,
+                vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::empty(),
+                    base_mip_level: texture.min_mip_level,
+                    level_count: texture.mip_levels,
+                    base_array_layer: texture.first_array_slice,
+                    layer_count: texture.array_size,
+                },
+            )?),
+            ShaderResourceViewDesc::TextureCube(texture) => Some(self.create_texture_srv(
+                index,
+                &texture.texture,
+                vk::ImageViewType::CUBE,
+                vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::empty(),
+                    base_mip_level: texture.min_mip_level,
+                    level_count: texture.mip_levels,
+                    base_array_layer: 0,
+                    layer_count: 6,
+                },
+            )?),
+            ShaderResourceViewDesc::TextureCubeArray(texture) => Some(self.create_texture_srv(
+                index,
+                &texture.texture,
+                vk::ImageViewType::CUBE_ARRAY,
+                vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::empty(),
+                    base_mip_level: texture.min_mip_level,
+                    level_count: texture.mip_levels,
+                    base_array_layer: texture.first_array_slice,
+                    layer_count: texture.cube_count * 6,
+                },
+            )?),
+            ShaderResourceViewDesc::Texture3D(texture) => Some(self.create_texture_srv(
+                index,
+                &texture.texture,
+                vk::ImageViewType::TYPE_3D,
+                vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::empty(),
+                    base_mip_level: texture.min_mip_level,
+                    level_count: texture.mip_levels,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+            )?),
+        };
+
+        Ok(ShaderResourceView::new(
+            desc.clone(),
+            Box::new(VulkanShaderResourceView {
+                descriptor_manager: self.descriptor_manager.clone(),
+                frame_manager: self.frame_manager.clone(),
+                view,
+                index,
+            }),
+        ))
+    }
+
+    fn create_unordered_access_view(
+        &self,
+        desc: &UnorderedAccessViewDesc,
+    ) -> Result<UnorderedAccessView, DeviceError> {
+        let index = self.descriptor_manager.allocate_resource_descriptor_index();
+
+        let view = match desc {
+            UnorderedAccessViewDesc::Buffer(buffer) => {
+                let offset = match &buffer.ty {
+                    BufferSRVType::Raw(raw) => raw.offset_in_bytes as u64,
+                    BufferSRVType::Structured(structured) => structured.offset_in_bytes,
+                };
+
+                let vk_buffer = buffer
+                    .buffer
+                    .backend_data
+                    .downcast_ref::<VulkanBuffer>()
+                    .unwrap();
+
+                self.descriptor_manager.write_storage_buffer_descriptor(
+                    index,
+                    vk_buffer.buffer,
+                    offset,
+                    vk::WHOLE_SIZE,
+                );
+
+                None
+            }
+            UnorderedAccessViewDesc::Texture2D(texture) => Some(self.create_texture_uav(
+                index,
+                &texture.texture,
+                vk::ImageViewType::TYPE_2D,
+                texture.mip_level,
+            )?),
+            UnorderedAccessViewDesc::Texture3D(texture) => Some(self.create_texture_uav(
+                index,
+                &texture.texture,
+                vk::ImageViewType::TYPE_3D,
+                texture.mip_level,
+            )?),
+        };
+
+        Ok(UnorderedAccessView::new(
+            desc.clone(),
+            Box::new(VulkanUnorderedAccessView {
+                descriptor_manager: self.descriptor_manager.clone(),
+                frame_manager: self.frame_manager.clone(),
+                view,
+                index,
+            }),
+        ))
+    }
+
+    fn create_render_target_view(
+        &self,
+        desc: &RenderTargetViewDesc,
+    ) -> Result<RenderTargetView, DeviceError> {
+        let vk_texture = desc
+            .resource
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+
+        let (view_type, mip_level, base_array_layer, layer_count) = match &desc.ty {
+            RenderTargetViewType::Texture2D(info) => {
+                (vk::ImageViewType::TYPE_2D, info.mip_level, 0, 1)
+            }
+            RenderTargetViewType::Texture2DArray(info) => (
+                vk::ImageViewType::TYPE_2D_ARRAY,
+                info.mip_level,
+                info.first_array_slice,
+                info.array_size,
+            ),
+            // 3D textures are rendered to through a 2D array view of their depth slices
+            RenderTargetViewType::Texture3D(info) => (
+                vk::ImageViewType::TYPE_2D_ARRAY,
+                info.mip_level,
+                info.first_depth_slice,
+                info.depth_size,
+            ),
+        };
+
+        let view = self.create_image_view(
+            &desc.resource,
+            view_type,
+            vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: mip_level,
+                level_count: 1,
+                base_array_layer,
+                layer_count,
+            },
+        )?;
+
+        Ok(RenderTargetView::new(
+            desc.clone(),
+            Box::new(VulkanRenderTargetView {
+                frame_manager: self.frame_manager.clone(),
+                view,
+                format: vk_texture.format,
+                extent: mip_extent(&desc.resource.desc, mip_level),
+                layer_count,
+            }),
+        ))
+    }
+
+    fn create_depth_stencil_view(
+        &self,
+        desc: &DepthStencilViewDesc,
+    ) -> Result<DepthStencilView, DeviceError> {
+        let vk_texture = desc
+            .resource
+            .backend_data
+            .downcast_ref::<VulkanTexture>()
+            .unwrap();
+
+        let (view_type, mip_level, base_array_layer, layer_count) = match &desc.ty {
+            DepthStencilViewType::Texture2D(info) => {
+                (vk::ImageViewType::TYPE_2D, info.mip_level, 0, 1)
+            }
+            DepthStencilViewType::Texture2DArray(info) => (
+                vk::ImageViewType::TYPE_2D_ARRAY,
+                info.mip_level,
+                info.first_array_slice,
+                info.array_size,
+            ),
+        };
+
+        let view = self.create_image_view(
+            &desc.resource,
+            view_type,
+            vk::ImageSubresourceRange {
+                aspect_mask: vk_texture.aspect_mask,
+                base_mip_level: mip_level,
+                level_count: 1,
+                base_array_layer,
+                layer_count,
+            },
+        )?;
+
+        Ok(DepthStencilView::new(
+            desc.clone(),
+            Box::new(VulkanDepthStencilView {
+                frame_manager: self.frame_manager.clone(),
+                view,
+                format: vk_texture.format,
+                extent: mip_extent(&desc.resource.desc, mip_level),
+                layer_count,
+            }),
+        ))
+    }
+
+    fn create_swapchain(
+        &self,
+        info: &SwapChainDesc,
+        old_swapchain: Option<SwapChain>,
+    ) -> Result<SwapChain, DeviceError> {
+        // Reuse the surface of the old swapchain, it is destroyed after the new one is created
+        let (surface, old_handle) = if let Some(old_swapchain) = &old_swapchain {
+            let old_swapchain = old_swapchain
+                .backend_data
+                .downcast_ref::<VulkanSwapChain>()
+                .unwrap();
+            let surface = old_swapchain.surface.lock().take().unwrap();
+            (surface, old_swapchain.swapchain)
+        } else {
+            let surface = unsafe {
+                ash_window::create_surface(
+                    &self.device.instance.entry,
+                    &self.device.instance.instance,
+                    info.display_handle,
+                    info.window_handle,
+                    None,
+                )
+            }
+            .map_err(convert_vk_error_to_ze_device_error)?;
+            (surface, vk::SwapchainKHR::null())
+        };
+
+        let (swapchain, format, extent) =
+            match self.create_swapchain_for_surface(info, surface, old_handle) {
+                Ok(swapchain) => swapchain,
+                Err(err) => {
+                    if let Some(old_swapchain) = &old_swapchain {
+                        *old_swapchain
+                            .backend_data
+                            .downcast_ref::<VulkanSwapChain>()
+                            .unwrap()
+                            .surface
+                            .lock() = Some(surface);
+                    } else {
+                        unsafe {
+                            self.device.surface_loader.destroy_surface(surface, None);
+                        }
+                    }
+
+                    ze_error!("Failed to create swapchain: {:?}", err);
+                    return Err(convert_vk_error_to_ze_device_error(err));
+                }
+            };
+
+        drop(old_swapchain);
+
+        let images = unsafe { self.device.swapchain_loader.get_swapchain_images(swapchain) }
+            .map_err(convert_vk_error_to_ze_device_error)?;
+
+        let mut textures = Vec::with_capacity(images.len());
+        for (i, image) in images.iter().enumerate() {
+            let desc = TextureDesc {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+                array_size: 1,
+                mip_levels: 1,
+                format: get_ze_format_from_vk_format(format),
+                sample_desc: SampleDesc::default(),
+                usage_flags: info.usage_flags,
+                memory_desc: MemoryDesc {
+                    memory_location: MemoryLocation::GpuOnly,
+                    memory_flags: Default::default(),
+                },
+            };
+
+            self.device
+                .set_object_name(*image, &format!("Swapchain Texture {}", i));
+
+            let texture = Texture::new(
+                desc,
+                Box::new(VulkanTexture::new(
+                    self.frame_manager.clone(),
+                    *image,
+                    None,
+                    format,
+                    vk::ImageAspectFlags::COLOR,
+                    true,
+                )),
+            );
+            textures.push(Arc::new(texture));
+        }
+
+        let create_semaphore = || unsafe {
+            self.device
+                .device
+                .create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
+                .unwrap()
+        };
+
+        let acquire_semaphores = (0..=images.len()).map(|_| create_semaphore()).collect();
+        let render_finished_semaphores = (0..images.len()).map(|_| create_semaphore()).collect();
+
+        Ok(SwapChain::new(
+            *info,
+            Box::new(VulkanSwapChain {
+                frame_manager: self.frame_manager.clone(),
+                swapchain,
+                surface: Mutex::new(Some(surface)),
+                textures,
+                acquire_semaphores,
+                next_acquire_semaphore: AtomicUsize::new(0),
+                render_finished_semaphores,
+                current_image: Mutex::new(None),
+            }),
+        ))
+    }
+
+    fn create_shader_module(&self, bytecode: &[u8]) -> Result<ShaderModule, DeviceError> {
+        let code = ash::util::read_spv(&mut Cursor::new(bytecode))
+            .map_err(|_| DeviceError::InvalidParameters)?;
+
+        let entry_point = spirv_entry_point_name(&code).ok_or(DeviceError::InvalidParameters)?;
+
+        let module = unsafe {
+            self.device
+                .device
+                .create_shader_module(&vk::ShaderModuleCreateInfo::builder().code(&code), None)
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+
+        Ok(ShaderModule::new(Box::new(VulkanShaderModule {
+            frame_manager: self.frame_manager.clone(),
+            module,
+            entry_point,
+        })))
+    }
+
+    fn create_command_list(&self, queue_type: QueueType) -> Result<CommandList, DeviceError> {
+        let command_buffer = self
+            .frame_manager
+            .current_frame()
+            .command_manager()
+            .create_command_list(queue_type);
+
+        let cmd_list = VulkanCommandList::new(command_buffer, queue_type);
+
+        if queue_type != QueueType::Transfer {
+            let descriptor_sets = [self.descriptor_manager.set()];
+            let mut bind_points = vec![vk::PipelineBindPoint::COMPUTE];
+            if queue_type == QueueType::Graphics {
+                bind_points.push(vk::PipelineBindPoint::GRAPHICS);
+            }
+
+            for bind_point in bind_points {
+                unsafe {
+                    self.device.device.cmd_bind_descriptor_sets(
+                        command_buffer,
+                        bind_point,
+                        self.pipeline_layout,
+                        0,
+                        &descriptor_sets,
+                        &[],
+                    );
+                }
+            }
+        }
+
+        Ok(CommandList::new(Box::new(cmd_list)))
+    }
+
+    fn create_sampler(&self, desc: &SamplerDesc) -> Result<Sampler, DeviceError> {
+        let (filter, mipmap_mode) = get_vk_filter_from_ze_filter(desc.filter);
+
+        let sampler = unsafe {
+            self.device.device.create_sampler(
+                &vk::SamplerCreateInfo::builder()
+                    .mag_filter(filter)
+                    .min_filter(filter)
+                    .mipmap_mode(mipmap_mode)
+                    .address_mode_u(get_vk_sampler_address_mode_from_ze_texture_address_mode(
+                        desc.address_u,
+                    ))
+                    .address_mode_v(get_vk_sampler_address_mode_from_ze_texture_address_mode(
+                        desc.address_v,
+                    ))
+                    .address_mode_w(get_vk_sampler_address_mode_from_ze_texture_address_mode(
+                        desc.address_w,
+                    ))
+                    .mip_lod_bias(desc.mip_lod_bias)
+                    .anisotropy_enable(desc.max_anisotropy > 1)
+                    .max_anisotropy(desc.max_anisotropy as f32)
+                    .compare_op(get_vk_compare_op_from_ze_compare_op(desc.compare_op))
+                    .min_lod(desc.min_lod)
+                    .max_lod(desc.max_lod)
+                    .border_color(vk::BorderColor::FLOAT_OPAQUE_BLACK),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+
+        let index = self.descriptor_manager.allocate_sampler_descriptor_index();
+        self.descriptor_manager
+            .write_sampler_descriptor(index, sampler);
+
+        Ok(Sampler::new(
+            desc.clone(),
+            Box::new(VulkanSampler {
+                descriptor_manager: self.descriptor_manager.clone(),
+                frame_manager: self.frame_manager.clone(),
+                sampler,
+                index,
+            }),
+        ))
+    }
+
+    fn create_query_heap(
+        &self,
+        desc: &QueryHeapDesc,
+        name: &str,
+    ) -> Result<QueryHeap, DeviceError> {
+        let pool = unsafe {
+            self.device.device.create_query_pool(
+                &vk::QueryPoolCreateInfo::builder()
+                    .query_type(match desc.ty {
+                        QueryType::Timestamp => vk::QueryType::TIMESTAMP,
+                    })
+                    .query_count(desc.count),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+        self.device.set_object_name(pool, name);
+
+        // Queries must be reset before their first use
+        unsafe {
+            self.device.device.reset_query_pool(pool, 0, desc.count);
+        }
+
+        Ok(QueryHeap::new(
+            *desc,
+            Box::new(VulkanQueryPool {
+                frame_manager: self.frame_manager.clone(),
+                pool,
+            }),
+        ))
+    }
+
+    fn create_fence(&self, name: &str) -> Result<Fence, DeviceError> {
+        let semaphore = unsafe {
+            let mut type_info = vk::SemaphoreTypeCreateInfo::builder()
+                .semaphore_type(vk::SemaphoreType::TIMELINE)
+                .initial_value(0);
+
+            self.device.device.create_semaphore(
+                &vk::SemaphoreCreateInfo::builder().push_next(&mut type_info),
+                None,
+            )
+        }
+        .map_err(convert_vk_error_to_ze_device_error)?;
+        self.device.set_object_name(semaphore, name);
+
+        Ok(Fence::new(Box::new(VulkanFence {
+            frame_manager: self.frame_manager.clone(),
+            semaphore,
+            value: AtomicU64::new(0),
+        })))
+    }
+
+    fn buffer_mapped_ptr(&self, buffer: &Buffer) -> Option<*mut u8> {
+        let buffer = unsafe {
+            buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        buffer.mapped_ptr
+    }
+
+    fn is_fence_signaled(&self, fence: &Fence) -> bool {
+        let fence = unsafe {
+            fence
+                .backend_data
+                .downcast_ref::<VulkanFence>()
+                .unwrap_unchecked()
+        };
+
+        let completed_value = unsafe {
+            self.device
+                .device
+                .get_semaphore_counter_value(fence.semaphore)
+        }
+        .unwrap();
+        completed_value >= fence.value.load(Ordering::SeqCst)
+    }
+
+    fn wait_for_fence(&self, fence: &Fence) {
+        let fence = unsafe {
+            fence
+                .backend_data
+                .downcast_ref::<VulkanFence>()
+                .unwrap_unchecked()
+        };
+
+        let semaphores = [fence.semaphore];
+        let values = [fence.value.load(Ordering::SeqCst)];
+        unsafe {
+            self.device
+                .device
+                .wait_semaphores(
+                    &vk::SemaphoreWaitInfo::builder()
+                        .semaphores(&semaphores)
+                        .values(&values),
+                    u64::MAX,
+                )
+                .unwrap();
+        }
+    }
+
+    fn texture_subresource_layout(
+        &self,
+        texture: &Texture,
+        subresource_index: u32,
+    ) -> TextureSubresourceLayout {
+        let mip_level = subresource_index % texture.desc.mip_levels.max(1);
+        let width = (texture.desc.width >> mip_level).max(1);
+        let height = (texture.desc.height >> mip_level).max(1);
+        let depth = (texture.desc.depth >> mip_level).max(1) as u64;
+
+        let row_size_in_bytes = texture.desc.format.row_pitch_in_bytes(width) as u64;
+        let row_pitch_in_bytes =
+            row_size_in_bytes.div_ceil(TEXTURE_ROW_PITCH_ALIGNMENT) * TEXTURE_ROW_PITCH_ALIGNMENT;
+        let depth_pitch_in_bytes =
+            row_pitch_in_bytes * texture.desc.format.row_count(height) as u64;
+
+        TextureSubresourceLayout {
+            offset_in_bytes: 0,
+            row_pitch_in_bytes,
+            depth_pitch_in_bytes,
+            size_in_bytes: depth_pitch_in_bytes * depth,
+        }
+    }
+
+    fn swapchain_backbuffer_count(&self, swapchain: &SwapChain) -> usize {
+        let swapchain = unsafe {
+            swapchain
+                .backend_data
+                .downcast_ref::<VulkanSwapChain>()
+                .unwrap_unchecked()
+        };
+
+        swapchain.textures.len()
+    }
+
+    fn swapchain_backbuffer_index(&self, swapchain: &SwapChain) -> u32 {
+        let swapchain = unsafe {
+            swapchain
+                .backend_data
+                .downcast_ref::<VulkanSwapChain>()
+                .unwrap_unchecked()
+        };
+
+        // Images are acquired when first requested in a frame, the next graphics submission
+        // waits for the acquisition to finish
+        let mut current_image = swapchain.current_image.lock();
+        if let Some(index) = *current_image {
+            return index;
+        }
+
+        let semaphore = swapchain.next_acquire_semaphore();
+        match unsafe {
+            self.device.swapchain_loader.acquire_next_image(
+                swapchain.swapchain,
+                u64::MAX,
+                semaphore,
+                vk::Fence::null(),
+            )
+        } {
+            Ok((index, _)) => {
+                self.graphics_queue.add_pending_wait_semaphore(semaphore);
+                *current_image = Some(index);
+                index
+            }
+            Err(err) => {
+                ze_warn!("Failed to acquire swapchain image: {:?}", err);
+                0
+            }
+        }
+    }
+
+    fn swapchain_backbuffer(
+        &self,
+        swapchain: &SwapChain,
+        index: u32,
+    ) -> Result<Arc<Texture>, DeviceError> {
+        let swapchain = unsafe {
+            swapchain
+                .backend_data
+                .downcast_ref::<VulkanSwapChain>()
+                .unwrap_unchecked()
+        };
+
+        Ok(swapchain.textures[index as usize].clone())
+    }
+
+    fn present(&self, swapchain: &SwapChain) {
+        let swapchain = unsafe {
+            swapchain
+                .backend_data
+                .downcast_ref::<VulkanSwapChain>()
+                .unwrap_unchecked()
+        };
+
+        let image_index = match swapchain.current_image.lock().take() {
+            Some(image_index) => image_index,
+            None => return,
+        };
+
+        // Signal once all the work submitted to the graphics queue is done
+        let render_finished_semaphore = swapchain.render_finished_semaphores[image_index as usize];
+        self.graphics_queue
+            .submit(
+                &self.device.device,
+                &[],
+                &[],
+                &[vk::SemaphoreSubmitInfo::builder()
+                    .semaphore(render_finished_semaphore)
+                    .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                    .build()],
+            )
+            .unwrap();
+
+        let wait_semaphores = [render_finished_semaphore];
+        let swapchains = [swapchain.swapchain];
+        let image_indices = [image_index];
+        match self.graphics_queue.present(
+            &self.device,
+            &vk::PresentInfoKHR::builder()
+                .wait_semaphores(&wait_semaphores)
+                .swapchains(&swapchains)
+                .image_indices(&image_indices),
+        ) {
+            // Out of date swapchains are recreated when the window is resized
+            Ok(_) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {}
+            Err(err) => ze_error!("Failed to present: {:?}", err),
+        }
+    }
+
+    fn transient_memory_pool(&self) -> &MemoryPool {
+        &self.transient_memory_pool
+    }
+
+    fn memory_stats(&self) -> MemoryStats {
+        let mut budget_properties = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let memory_properties = {
+            let mut properties = vk::PhysicalDeviceMemoryProperties2::builder();
+            if self.memory_budget_supported {
+                properties = properties.push_next(&mut budget_properties);
+            }
+
+            unsafe {
+                self.device
+                    .instance
+                    .instance
+                    .get_physical_device_memory_properties2(
+                        self.device.physical_device,
+                        &mut properties,
+                    );
+            }
+
+            properties.memory_properties
+        };
+
+        let mut stats = MemoryStats::default();
+        for (i, heap) in memory_properties.memory_heaps
+            [..memory_properties.memory_heap_count as usize]
+            .iter()
+            .enumerate()
+        {
+            let segment = if heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL) {
+                &mut stats.local
+            } else {
+                &mut stats.non_local
+            };
+
+            // Without VK_EXT_memory_budget, assume the whole heap is available
+            if self.memory_budget_supported {
+                segment.budget_bytes += budget_properties.heap_budget[i];
+                segment.usage_bytes += budget_properties.heap_usage[i];
+            } else {
+                segment.budget_bytes += heap.size;
+            }
+        }
+
+        let allocator = self.allocator.lock();
+        for (segment, counters) in [
+            (&mut stats.local, allocator.local_counters()),
+            (&mut stats.non_local, allocator.non_local_counters()),
+        ] {
+            segment.allocation_count = counters.count;
+            segment.allocation_bytes = counters.bytes;
+            if !self.memory_budget_supported {
+                segment.usage_bytes = counters.bytes;
+            }
+        }
+
+        stats
+    }
+
+    fn timestamp_frequency(&self) -> u64 {
+        (1_000_000_000.0 / self.timestamp_period as f64) as u64
+    }
+
+    fn cmd_copy_buffer_regions(
+        &self,
+        cmd_list: &mut CommandList,
+        src_buffer: &Buffer,
+        dst_buffer: &Buffer,
+        regions: &[BufferCopyRegion],
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let is_readback_buffer =
+            dst_buffer.info.memory_desc.memory_location == MemoryLocation::GpuToCpu;
+        let src_buffer = unsafe {
+            src_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        let dst_buffer = unsafe {
+            dst_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        let regions: Vec<vk::BufferCopy> = regions
+            .iter()
+            .map(|region| vk::BufferCopy {
+                src_offset: region.src_offset_in_bytes,
+                dst_offset: region.dst_offset_in_bytes,
+                size: region.size_in_bytes,
+            })
+            .collect();
+
+        unsafe {
+            self.device.device.cmd_copy_buffer(
+                cmd_list.command_buffer,
+                src_buffer.buffer,
+                dst_buffer.buffer,
+                &regions,
+            );
+        }
+
+        if is_readback_buffer {
+            self.cmd_readback_buffer_barrier(cmd_list.command_buffer, dst_buffer.buffer);
+        }
+    }
+
+    fn cmd_copy_buffer_to_texture_regions(
+        &self,
+        cmd_list: &mut CommandList,
+        src_buffer: &Buffer,
+        dst_texture: &Texture,
+        regions: &[BufferToTextureCopyRegion],
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let vk_src_buffer = unsafe {
+            src_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        let vk_dst_texture = unsafe {
+            dst_texture
+                .backend_data
+                .downcast_ref::<VulkanTexture>()
+                .unwrap_unchecked()
+        };
+
+        self.promote_texture_from_undefined(cmd_list.command_buffer, dst_texture);
+
+        let regions: Vec<vk::BufferImageCopy> = regions
+            .iter()
+            .map(|region| {
+                get_vk_buffer_image_copy(
+                    dst_texture,
+                    vk_dst_texture,
+                    region,
+                    vk::Extent3D {
+                        width: region.buffer_texture_width,
+                        height: region.buffer_texture_height,
+                        depth: region.buffer_texture_depth,
+                    },
+                )
+            })
+            .collect();
+
+        unsafe {
+            self.device.device.cmd_copy_buffer_to_image(
+                cmd_list.command_buffer,
+                vk_src_buffer.buffer,
+                vk_dst_texture.image,
+                vk_dst_texture.layout(),
+                &regions,
+            );
+        }
+    }
+
+    fn cmd_copy_texture_to_buffer_regions(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_buffer: &Buffer,
+        regions: &[BufferToTextureCopyRegion],
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let is_readback_buffer =
+            dst_buffer.info.memory_desc.memory_location == MemoryLocation::GpuToCpu;
+        let vk_src_texture = unsafe {
+            src_texture
+                .backend_data
+                .downcast_ref::<VulkanTexture>()
+                .unwrap_unchecked()
+        };
+
+        let vk_dst_buffer = unsafe {
+            dst_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        self.promote_texture_from_undefined(cmd_list.command_buffer, src_texture);
+
+        let regions: Vec<vk::BufferImageCopy> = regions
+            .iter()
+            .map(|region| {
+                get_vk_buffer_image_copy(
+                    src_texture,
+                    vk_src_texture,
+                    region,
+                    vk::Extent3D {
+                        width: region.texture_subresource_width,
+                        height: region.texture_subresource_height,
+                        depth: region.texture_subresource_depth,
+                    },
+                )
+            })
+            .collect();
+
+        unsafe {
+            self.device.device.cmd_copy_image_to_buffer(
+                cmd_list.command_buffer,
+                vk_src_texture.image,
+                vk_src_texture.layout(),
+                vk_dst_buffer.buffer,
+                &regions,
+            );
+        }
+
+        if is_readback_buffer {
+            self.cmd_readback_buffer_barrier(cmd_list.command_buffer, vk_dst_buffer.buffer);
+        }
+    }
+
+    fn cmd_resolve_texture(
+        &self,
+        cmd_list: &mut CommandList,
+        src_texture: &Texture,
+        dst_texture: &Texture,
+    ) {
+        debug_assert!(src_texture.desc.sample_desc.count > 1);
+        debug_assert_eq!(dst_texture.desc.sample_desc.count, 1);
+
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let vk_src_texture = unsafe {
+            src_texture
+                .backend_data
+                .downcast_ref::<VulkanTexture>()
+                .unwrap_unchecked()
+        };
+
+        let vk_dst_texture = unsafe {
+            dst_texture
+                .backend_data
+                .downcast_ref::<VulkanTexture>()
+                .unwrap_unchecked()
+        };
+
+        self.promote_texture_from_undefined(cmd_list.command_buffer, src_texture);
+        self.promote_texture_from_undefined(cmd_list.command_buffer, dst_texture);
+
+        let subresource = |aspect_mask| vk::ImageSubresourceLayers {
+            aspect_mask,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+
+        let region = vk::ImageResolve {
+            src_subresource: subresource(vk_src_texture.aspect_mask),
+            src_offset: vk::Offset3D::default(),
+            dst_subresource: subresource(vk_dst_texture.aspect_mask),
+            dst_offset: vk::Offset3D::default(),
+            extent: vk::Extent3D {
+                width: dst_texture.desc.width,
+                height: dst_texture.desc.height.max(1),
+                depth: 1,
+            },
+        };
+
+        unsafe {
+            self.device.device.cmd_resolve_image(
+                cmd_list.command_buffer,
+                vk_src_texture.image,
+                vk_src_texture.layout(),
+                vk_dst_texture.image,
+                vk_dst_texture.layout(),
+                &[region],
+            );
+        }
+    }
+
+    fn cmd_debug_begin_event(&self, cmd_list: &mut CommandList, name: &str, color: Color4f32) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        if let Some(debug_utils) = &self.device.instance.debug_utils {
+            let name = CString::new(name).unwrap_or_default();
+            unsafe {
+                debug_utils.cmd_begin_debug_utils_label(
+                    cmd_list.command_buffer,
+                    &vk::DebugUtilsLabelEXT::builder()
+                        .label_name(&name)
+                        .color([color.r, color.g, color.b, color.a]),
+                );
+            }
+        }
+    }
+
+    fn cmd_debug_end_event(&self, cmd_list: &mut CommandList) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        if let Some(debug_utils) = &self.device.instance.debug_utils {
+            unsafe {
+                debug_utils.cmd_end_debug_utils_label(cmd_list.command_buffer);
+            }
+        }
+    }
+
+    fn cmd_write_timestamp(&self, cmd_list: &mut CommandList, query_heap: &QueryHeap, index: u32) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let query_pool = unsafe {
+            query_heap
+                .backend_data
+                .downcast_ref::<VulkanQueryPool>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            self.device.device.cmd_write_timestamp2(
+                cmd_list.command_buffer,
+                vk::PipelineStageFlags2::ALL_COMMANDS,
+                query_pool.pool,
+                index,
+            );
+        }
+    }
+
+    fn cmd_resolve_query_data(
+        &self,
+        cmd_list: &mut CommandList,
+        query_heap: &QueryHeap,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &Buffer,
+        dst_offset_in_bytes: u64,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let query_pool = unsafe {
+            query_heap
+                .backend_data
+                .downcast_ref::<VulkanQueryPool>()
+                .unwrap_unchecked()
+        };
+
+        let is_readback_buffer =
+            dst_buffer.info.memory_desc.memory_location == MemoryLocation::GpuToCpu;
+        let dst_buffer = unsafe {
+            dst_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            self.device.device.cmd_copy_query_pool_results(
+                cmd_list.command_buffer,
+                query_pool.pool,
+                first_query,
+                query_count,
+                dst_buffer.buffer,
+                dst_offset_in_bytes,
+                std::mem::size_of::<u64>() as u64,
+                vk::QueryResultFlags::TYPE_64,
+            );
+
+            // Unlike D3D12, queries must be reset before being written again
+            self.device.device.cmd_reset_query_pool(
+                cmd_list.command_buffer,
+                query_pool.pool,
+                first_query,
+                query_count,
+            );
+
+            // Make the results visible to the host, readback buffers are not transitioned by the user
+            if is_readback_buffer {
+                let buffer_barriers = [vk::BufferMemoryBarrier2::builder()
+                    .src_stage_mask(vk::PipelineStageFlags2::COPY)
+                    .src_access_mask(vk::AccessFlags2::TRANSFER_WRITE)
+                    .dst_stage_mask(vk::PipelineStageFlags2::HOST)
+                    .dst_access_mask(vk::AccessFlags2::HOST_READ)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .buffer(dst_buffer.buffer)
+                    .offset(dst_offset_in_bytes)
+                    .size(query_count as u64 * std::mem::size_of::<u64>() as u64)
+                    .build()];
+
+                self.device.device.cmd_pipeline_barrier2(
+                    cmd_list.command_buffer,
+                    &vk::DependencyInfo::builder().buffer_memory_barriers(&buffer_barriers),
+                );
+            }
+        }
+    }
+
+    fn cmd_begin_render_pass(&self, cmd_list: &mut CommandList, desc: &RenderPassDesc) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let mut extent = vk::Extent2D::default();
+        let mut layer_count = 1;
+        let mut color_attachments = Vec::with_capacity(desc.render_targets.len());
+
+        cmd_list.render_pass_sample_count =
+            get_vk_sample_count_from_ze_sample_desc(desc.sample_desc());
+        cmd_list.render_pass_color_count = desc.render_targets.len() as u32;
+        for (i, desc) in desc.render_targets.iter().enumerate() {
+            let rtv = unsafe {
+                desc.render_target_view
+                    .backend_data
+                    .downcast_ref::<VulkanRenderTargetView>()
+                    .unwrap_unchecked()
+            };
+
+            debug_assert!(
+                desc.store_mode != RenderPassTextureStoreMode::Resolve,
+                "Non-implemented"
+            );
+
+            cmd_list.render_pass_color_formats[i] = rtv.format;
+            extent = rtv.extent;
+            layer_count = layer_count.max(rtv.layer_count);
+
+            color_attachments.push(
+                vk::RenderingAttachmentInfo::builder()
+                    .image_view(rtv.view)
+                    .image_layout(vk::ImageLayout::ATTACHMENT_OPTIMAL)
+                    .load_op(get_vk_attachment_load_op_from_ze_load_mode(desc.load_mode))
+                    .store_op(get_vk_attachment_store_op_from_ze_store_mode(
+                        desc.store_mode,
+                    ))
+                    .clear_value(get_vk_clear_value(desc.clear_value))
+                    .build(),
+            );
+        }
+
+        let mut rendering_info = vk::RenderingInfo::builder().color_attachments(&color_attachments);
+
+        let depth_stencil_attachment;
+        cmd_list.render_pass_depth_stencil_format = vk::Format::UNDEFINED;
+        if let Some(depth_stencil_desc) = &desc.depth_stencil {
+            let dsv = unsafe {
+                depth_stencil_desc
+                    .depth_stencil_view
+                    .backend_data
+                    .downcast_ref::<VulkanDepthStencilView>()
+                    .unwrap_unchecked()
+            };
+
+            let vk_texture = depth_stencil_desc
+                .depth_stencil_view
+                .desc
+                .resource
+                .backend_data
+                .downcast_ref::<VulkanTexture>()
+                .unwrap();
+
+            // Read only depth buffers stays in the general layout
+            let layout = match vk_texture.layout() {
+                vk::ImageLayout::GENERAL => vk::ImageLayout::GENERAL,
+                _ => vk::ImageLayout::ATTACHMENT_OPTIMAL,
+            };
+
+            cmd_list.render_pass_depth_stencil_format = dsv.format;
+            extent = dsv.extent;
+            layer_count = layer_count.max(dsv.layer_count);
+
+            depth_stencil_attachment = vk::RenderingAttachmentInfo::builder()
+                .image_view(dsv.view)
+                .image_layout(layout)
+                .load_op(get_vk_attachment_load_op_from_ze_load_mode(
+                    depth_stencil_desc.load_mode,
+                ))
+                .store_op(get_vk_attachment_store_op_from_ze_store_mode(
+                    depth_stencil_desc.store_mode,
+                ))
+                .clear_value(get_vk_clear_value(depth_stencil_desc.clear_value))
+                .build();
+
+            rendering_info = rendering_info.depth_attachment(&depth_stencil_attachment);
+            if vk_texture
+                .aspect_mask
+                .contains(vk::ImageAspectFlags::STENCIL)
+            {
+                rendering_info = rendering_info.stencil_attachment(&depth_stencil_attachment);
+            }
+        }
+
+        rendering_info = rendering_info
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent,
+            })
+            .layer_count(layer_count);
+
+        unsafe {
+            self.device
+                .device
+                .cmd_begin_rendering(cmd_list.command_buffer, &rendering_info);
+        }
+
+        cmd_list.pipeline_state_dirty = true;
+    }
+
+    fn cmd_end_render_pass(&self, cmd_list: &mut CommandList) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            self.device
+                .device
+                .cmd_end_rendering(cmd_list.command_buffer);
+        }
+    }
+
+    fn cmd_resource_barrier(&self, cmd_list: &mut CommandList, barriers: &[ResourceBarrier]) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_ref::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        // Queues without graphics capabilities only understand generic stages and accesses
+        let queue_type = cmd_list.queue_type;
+        let get_barrier_info = |state: ResourceState| {
+            let (stages, access, layout) = get_vk_barrier_info_from_ze_resource_state(state);
+            if queue_type == QueueType::Graphics {
+                (stages, access, layout)
+            } else {
+                let access = if access.is_empty() {
+                    vk::AccessFlags2::NONE
+                } else {
+                    vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE
+                };
+                (vk::PipelineStageFlags2::ALL_COMMANDS, access, layout)
+            }
+        };
+
+        let mut memory_barriers = vec![];
+        let mut buffer_barriers = vec![];
+        let mut image_barriers = vec![];
+        for barrier in barriers {
+            match barrier {
+                ResourceBarrier::Transition(transition) => {
+                    let (src_stage_mask, src_access_mask, src_layout) =
+                        get_barrier_info(transition.source_state);
+                    let (dst_stage_mask, dst_access_mask, dst_layout) =
+                        get_barrier_info(transition.dest_state);
+
+                    match transition.resource {
+                        ResourceTransitionBarrierResource::Buffer(buffer) => {
+                            let buffer =
+                                buffer.backend_data.downcast_ref::<VulkanBuffer>().unwrap();
+
+                            buffer_barriers.push(
+                                vk::BufferMemoryBarrier2::builder()
+                                    .src_stage_mask(src_stage_mask)
+                                    .src_access_mask(src_access_mask)
+                                    .dst_stage_mask(dst_stage_mask)
+                                    .dst_access_mask(dst_access_mask)
+                                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                    .buffer(buffer.buffer)
+                                    .offset(0)
+                                    .size(vk::WHOLE_SIZE)
+                                    .build(),
+                            );
+                        }
+                        ResourceTransitionBarrierResource::Texture(texture)
+                        | ResourceTransitionBarrierResource::TextureSubresource(texture, _) => {
+                            let vk_texture = texture
+                                .backend_data
+                                .downcast_ref::<VulkanTexture>()
+                                .unwrap();
+
+                            let (old_layout, subresource_range) = match transition.resource {
+                                // Only the layout of whole textures is tracked, subresources
+                                // are transitioned back to it before the end of the list
+                                ResourceTransitionBarrierResource::TextureSubresource(
+                                    _,
+                                    subresource,
+                                ) => {
+                                    let mip_levels = texture.desc.mip_levels.max(1);
+                                    (
+                                        src_layout,
+                                        vk::ImageSubresourceRange {
+                                            aspect_mask: vk_texture.aspect_mask,
+                                            base_mip_level: subresource % mip_levels,
+                                            level_count: 1,
+                                            base_array_layer: subresource / mip_levels,
+                                            layer_count: 1,
+                                        },
+                                    )
+                                }
+                                _ => {
+                                    // Contents of never used images are undefined
+                                    let old_layout = match vk_texture.set_layout(dst_layout) {
+                                        vk::ImageLayout::UNDEFINED => vk::ImageLayout::UNDEFINED,
+                                        _ => src_layout,
+                                    };
+                                    (old_layout, full_subresource_range(vk_texture.aspect_mask))
+                                }
+                            };
+
+                            image_barriers.push(
+                                vk::ImageMemoryBarrier2::builder()
+                                    .src_stage_mask(src_stage_mask)
+                                    .src_access_mask(src_access_mask)
+                                    .dst_stage_mask(dst_stage_mask)
+                                    .dst_access_mask(dst_access_mask)
+                                    .old_layout(old_layout)
+                                    .new_layout(dst_layout)
+                                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                                    .image(vk_texture.image)
+                                    .subresource_range(subresource_range)
+                                    .build(),
+                            );
+                        }
+                    }
+                }
+                ResourceBarrier::UnorderedAccess(_) => {
+                    memory_barriers.push(
+                        vk::MemoryBarrier2::builder()
+                            .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                            .src_access_mask(vk::AccessFlags2::MEMORY_WRITE)
+                            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                            .dst_access_mask(
+                                vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE,
+                            )
+                            .build(),
+                    );
+                }
+            }
+        }
+
+        unsafe {
+            self.device.device.cmd_pipeline_barrier2(
+                cmd_list.command_buffer,
+                &vk::DependencyInfo::builder()
+                    .memory_barriers(&memory_barriers)
+                    .buffer_memory_barriers(&buffer_barriers)
+                    .image_memory_barriers(&image_barriers),
+            );
+        }
+    }
+
+    fn cmd_set_viewports(&self, cmd_list: &mut CommandList, viewports: &[Viewport]) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        // Viewports are flipped so clip space matches D3D12
+        let mut vk_viewports = Vec::with_capacity(viewports.len());
+        for viewport in viewports {
+            vk_viewports.push(vk::Viewport {
+                x: viewport.position.x,
+                y: viewport.position.y + viewport.size.y,
+                width: viewport.size.x,
+                height: -viewport.size.y,
+                min_depth: viewport.min_depth,
+                max_depth: viewport.max_depth,
+            });
+        }
+
+        unsafe {
+            self.device
+                .device
+                .cmd_set_viewport(cmd_list.command_buffer, 0, &vk_viewports);
+        }
+    }
+
+    fn cmd_set_scissors(&self, cmd_list: &mut CommandList, scissors: &[RectI32]) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        // Scissors width and height are the right and bottom edges, like in D3D12
+        let mut vk_scissors = Vec::with_capacity(scissors.len());
+        for scissor in scissors {
+            vk_scissors.push(vk::Rect2D {
+                offset: vk::Offset2D {
+                    x: scissor.x.max(0),
+                    y: scissor.y.max(0),
+                },
+                extent: vk::Extent2D {
+                    width: (scissor.width - scissor.x.max(0)).max(0) as u32,
+                    height: (scissor.height - scissor.y.max(0)).max(0) as u32,
+                },
+            });
+        }
+
+        unsafe {
+            self.device
+                .device
+                .cmd_set_scissor(cmd_list.command_buffer, 0, &vk_scissors);
+        }
+    }
+
+    fn cmd_set_shader_stages(&self, cmd_list: &mut CommandList, stages: &[PipelineShaderStage]) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        cmd_list.pipeline_state_dirty = true;
+
+        if stages.len() == 1 && stages[0].stage == ShaderStageFlagBits::Compute {
+            self.set_compute_shader(cmd_list, stages[0].module);
+        } else {
+            let desc = match &mut cmd_list.pipeline {
+                VulkanCommandListPipelineType::Graphics(graphics) => graphics,
+                _ => {
+                    cmd_list.pipeline =
+                        VulkanCommandListPipelineType::Graphics(GraphicsPipelineDesc::default());
+                    cmd_list.pipeline.as_graphics_mut()
+                }
+            };
+
+            for shader in stages {
+                let stage_desc = get_shader_stage_desc(shader.module);
+                match shader.stage {
+                    ShaderStageFlagBits::Vertex => {
+                        desc.vertex_shader = Some(stage_desc);
+                        desc.mesh_shader = None;
+                    }
+                    ShaderStageFlagBits::Fragment => desc.pixel_shader = Some(stage_desc),
+                    ShaderStageFlagBits::Mesh => {
+                        desc.mesh_shader = Some(stage_desc);
+                        desc.vertex_shader = None;
+                    }
+                    ShaderStageFlagBits::Compute => {
+                        panic!("Cannot have a compute stage in a graphics pipeline!")
+                    }
+                }
+            }
+        }
+    }
+
+    fn cmd_set_compute_shader(&self, cmd_list: &mut CommandList, module: &ShaderModule) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.set_compute_shader(cmd_list, module);
+    }
+
+    fn cmd_set_input_assembly_state(
+        &self,
+        cmd_list: &mut CommandList,
+        state: &PipelineInputAssemblyState,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        if let VulkanCommandListPipelineType::Graphics(graphics) = &mut cmd_list.pipeline {
+            graphics.primitive_topology = get_vk_primitive_topology(state.primitive_topology);
+        }
+
+        cmd_list.pipeline_state_dirty = true;
+    }
+
+    fn cmd_set_blend_state(&self, cmd_list: &mut CommandList, state: &PipelineBlendState) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        if let VulkanCommandListPipelineType::Graphics(graphics) = &mut cmd_list.pipeline {
+            for (i, render_target) in state.render_targets.iter().enumerate() {
+                graphics.blend_state[i] = RenderTargetBlendDesc {
+                    enable_blend: render_target.enable_blend,
+                    src_color_blend_factor: get_vk_blend_factor(
+                        render_target.src_color_blend_factor,
+                    ),
+                    dst_color_blend_factor: get_vk_blend_factor(
+                        render_target.dst_color_blend_factor,
+                    ),
+                    color_blend_op: get_vk_blend_op(render_target.color_blend_op),
+                    src_alpha_blend_factor: get_vk_blend_factor(
+                        render_target.src_alpha_blend_factor,
+                    ),
+                    dst_alpha_blend_factor: get_vk_blend_factor(
+                        render_target.dst_alpha_blend_factor,
+                    ),
+                    alpha_blend_op: get_vk_blend_op(render_target.alpha_blend_op),
+                };
+            }
+
+            cmd_list.pipeline_state_dirty = true;
+        }
+    }
+
+    fn cmd_set_depth_stencil_state(
+        &self,
+        cmd_list: &mut CommandList,
+        state: &PipelineDepthStencilState,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let get_stencil_op_desc = |state: &PipelineStencilOpState| StencilOpDesc {
+            fail_op: get_vk_stencil_op(state.fail_op),
+            pass_op: get_vk_stencil_op(state.pass_op),
+            depth_fail_op: get_vk_stencil_op(state.depth_fail_op),
+            compare_op: get_vk_compare_op_from_ze_compare_op(state.compare_op),
+        };
+
+        if let VulkanCommandListPipelineType::Graphics(graphics) = &mut cmd_list.pipeline {
+            graphics.depth_stencil_state = DepthStencilDesc {
+                depth_test_enable: state.depth_test_enable,
+                depth_write_enable: state.depth_write_enable,
+                depth_compare_op: get_vk_compare_op_from_ze_compare_op(state.depth_compare_op),
+                stencil_test_enable: state.stencil_test_enable,
+                stencil_read_mask: state.stencil_read_mask,
+                stencil_write_mask: state.stencil_write_mask,
+                front: get_stencil_op_desc(&state.front),
+                back: get_stencil_op_desc(&state.back),
+            };
+            cmd_list.pipeline_state_dirty = true;
+        }
+    }
+
+    fn cmd_bind_index_buffer(
+        &self,
+        cmd_list: &mut CommandList,
+        index_buffer: &Buffer,
+        format: IndexBufferFormat,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        let buffer = unsafe {
+            index_buffer
+                .backend_data
+                .downcast_ref::<VulkanBuffer>()
+                .unwrap_unchecked()
+        };
+
+        unsafe {
+            self.device.device.cmd_bind_index_buffer(
+                cmd_list.command_buffer,
+                buffer.buffer,
+                0,
+                match format {
+                    IndexBufferFormat::Uint16 => vk::IndexType::UINT16,
+                    IndexBufferFormat::Uint32 => vk::IndexType::UINT32,
+                },
+            );
+        }
+    }
+
+    fn cmd_push_constants(&self, cmd_list: &mut CommandList, offset_in_bytes: u32, data: &[u8]) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        debug_assert!(data.len() <= PUSH_CONSTANTS_SIZE_IN_BYTES as usize);
+
+        unsafe {
+            self.device.device.cmd_push_constants(
+                cmd_list.command_buffer,
+                self.pipeline_layout,
+                vk::ShaderStageFlags::ALL,
+                offset_in_bytes,
+                data,
+            );
+        }
+    }
+
+    fn cmd_draw(
+        &self,
+        cmd_list: &mut CommandList,
+        vertex_count_per_instance: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.flush_pipeline_state(cmd_list);
+        unsafe {
+            self.device.device.cmd_draw(
+                cmd_list.command_buffer,
+                vertex_count_per_instance,
+                instance_count,
+                first_vertex,
+                first_instance,
+            )
+        };
+    }
+
+    fn cmd_draw_indexed(
+        &self,
+        cmd_list: &mut CommandList,
+        index_count_per_instance: u32,
+        instance_count: u32,
+        first_index: u32,
+        first_instance: u32,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.flush_pipeline_state(cmd_list);
+        unsafe {
+            self.device.device.cmd_draw_indexed(
+                cmd_list.command_buffer,
+                index_count_per_instance,
+                instance_count,
+                first_index,
+                0, // Unused as we don't use any vertex buffers
+                first_instance,
+            )
+        };
+    }
+
+    fn cmd_dispatch(
+        &self,
+        cmd_list: &mut CommandList,
+        thread_group_x: u32,
+        thread_group_y: u32,
+        thread_group_z: u32,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.flush_pipeline_state(cmd_list);
+        unsafe {
+            self.device.device.cmd_dispatch(
+                cmd_list.command_buffer,
+                thread_group_x,
+                thread_group_y,
+                thread_group_z,
+            );
+        }
+    }
+
+    fn cmd_dispatch_mesh(
+        &self,
+        cmd_list: &mut CommandList,
+        thread_group_x: u32,
+        thread_group_y: u32,
+        thread_group_z: u32,
+    ) {
+        let cmd_list = unsafe {
+            cmd_list
+                .backend_data
+                .downcast_mut::<VulkanCommandList>()
+                .unwrap_unchecked()
+        };
+
+        self.flush_pipeline_state(cmd_list);
+        unsafe {
+            self.device
+                .mesh_shader
+                .as_ref()
+                .expect("Mesh shaders are not supported by this device")
+                .cmd_draw_mesh_tasks(
+                    cmd_list.command_buffer,
+                    thread_group_x,
+                    thread_group_y,
+                    thread_group_z,
+                );
+        };
+    }
+
+    fn submit(
+        &self,
+        queue_type: QueueType,
+        command_lists: &[&CommandList],
+        wait_fences: &[&Fence],
+        signal_fences: &[&Fence],
+    ) {
+        self.frame_manager.current_frame().command_manager().submit(
+            queue_type,
+            command_lists,
+            wait_fences,
+            signal_fences,
+        );
+    }
+
+    fn wait_idle(&self) {
+        self.frame_manager.wait_for_work();
+    }
+}
+
+fn is_device_extension_supported(
+    instance: &Instance,
+    physical_device: vk::PhysicalDevice,
+    name: &CStr,
+) -> bool {
+    unsafe {
+        instance
+            .instance
+            .enumerate_device_extension_properties(physical_device)
+    }
+    .unwrap_or_default()
+    .iter()
+    .any(|extension| unsafe { CStr::from_ptr(extension.extension_name.as_ptr()) } == name)
+}
+
+fn get_shader_stage_desc(module: &ShaderModule) -> ShaderStageDesc {
+    let module = unsafe {
+        module
+            .backend_data
+            .downcast_ref::<VulkanShaderModule>()
+            .unwrap_unchecked()
+    };
+
+    ShaderStageDesc {
+        module: module.module,
+        entry_point: module.entry_point.clone(),
+    }
+}
+
+fn full_subresource_range(aspect_mask: vk::ImageAspectFlags) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange {
+        aspect_mask,
+        base_mip_level: 0,
+        level_count: vk::REMAINING_MIP_LEVELS,
+        base_array_layer: 0,
+        layer_count: vk::REMAINING_ARRAY_LAYERS,
+    }
+}
+
+fn mip_extent(desc: &TextureDesc, mip_level: u32) -> vk::Extent2D {
+    vk::Extent2D {
+        width: (desc.width >> mip_level).max(1),
+        height: (desc.height >> mip_level).max(1),
+    }
+}
+
+fn get_vk_clear_value(clear_value: ClearValue) -> vk::ClearValue {
+    match clear_value {
+        ClearValue::Color(color) => vk::ClearValue {
+            color: vk::ClearColorValue { float32: color },
+        },
+        ClearValue::DepthStencil((depth, stencil)) => vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue {
+                depth,
+                stencil: stencil as u32,
+            },
+        },
+    }
+}
+
+fn get_vk_buffer_image_copy(
+    texture: &Texture,
+    vk_texture: &VulkanTexture,
+    region: &BufferToTextureCopyRegion,
+    image_extent: vk::Extent3D,
+) -> vk::BufferImageCopy {
+    let mip_levels = texture.desc.mip_levels.max(1);
+
+    // Only the depth aspect of depth stencil textures can be copied
+    let aspect_mask = if vk_texture.aspect_mask.contains(vk::ImageAspectFlags::DEPTH) {
+        vk::ImageAspectFlags::DEPTH
+    } else {
+        vk_texture.aspect_mask
+    };
+
+    // Buffer dimensions are in texels, block compressed formats are addressed by whole blocks
+    let block_size = texture.desc.format.block_size();
+    vk::BufferImageCopy {
+        buffer_offset: region.buffer_offset_in_bytes,
+        buffer_row_length: region.buffer_texture_row_pitch_in_bytes
+            / texture.desc.format.bytes_size() as u32
+            * block_size,
+        buffer_image_height: region.buffer_texture_height.div_ceil(block_size) * block_size,
+        image_subresource: vk::ImageSubresourceLayers {
+            aspect_mask,
+            mip_level: region.texture_subresource_index % mip_levels,
+            base_array_layer: region.texture_subresource_index / mip_levels,
+            layer_count: 1,
+        },
+        image_offset: vk::Offset3D {
+            x: region.texture_subresource_offset.x,
+            y: region.texture_subresource_offset.y,
+            z: region.texture_subresource_offset.z,
+        },
+        image_extent,
+    }
+}
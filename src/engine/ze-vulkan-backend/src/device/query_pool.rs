@@ -0,0 +1,18 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use std::sync::Arc;
+
+pub(crate) struct VulkanQueryPool {
+    pub frame_manager: Arc<FrameManager>,
+    pub pool: vk::QueryPool,
+}
+
+impl Drop for VulkanQueryPool {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::QueryPool(self.pool));
+    }
+}
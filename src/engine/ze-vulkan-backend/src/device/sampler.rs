@@ -0,0 +1,30 @@
+use crate::descriptor_manager::DescriptorManager;
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use std::sync::Arc;
+use ze_gfx::backend::ShaderVisibleResource;
+
+pub(crate) struct VulkanSampler {
+    pub descriptor_manager: Arc<DescriptorManager>,
+    pub frame_manager: Arc<FrameManager>,
+    pub sampler: vk::Sampler,
+    pub index: u32,
+}
+
+impl Drop for VulkanSampler {
+    fn drop(&mut self) {
+        self.descriptor_manager
+            .free_sampler_descriptor_index(self.index);
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::Sampler(self.sampler));
+    }
+}
+
+impl ShaderVisibleResource for VulkanSampler {
+    fn descriptor_index(&self) -> u32 {
+        self.index
+    }
+}
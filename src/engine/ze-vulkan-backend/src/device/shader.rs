@@ -0,0 +1,54 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use std::ffi::CString;
+use std::sync::Arc;
+
+pub(crate) struct VulkanShaderModule {
+    pub frame_manager: Arc<FrameManager>,
+    pub module: vk::ShaderModule,
+    pub entry_point: CString,
+}
+
+impl Drop for VulkanShaderModule {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::ShaderModule(self.module));
+    }
+}
+
+/// Read the name of the first entry point declared in a SPIR-V module
+pub(crate) fn spirv_entry_point_name(words: &[u32]) -> Option<CString> {
+    const SPIRV_MAGIC_NUMBER: u32 = 0x07230203;
+    const SPIRV_HEADER_WORD_COUNT: usize = 5;
+    const OP_ENTRY_POINT: u32 = 15;
+
+    if words.len() < SPIRV_HEADER_WORD_COUNT || words[0] != SPIRV_MAGIC_NUMBER {
+        return None;
+    }
+
+    let mut offset = SPIRV_HEADER_WORD_COUNT;
+    while offset < words.len() {
+        let word_count = (words[offset] >> 16) as usize;
+        let opcode = words[offset] & 0xFFFF;
+        if word_count == 0 || offset + word_count > words.len() {
+            return None;
+        }
+
+        // OpEntryPoint <execution model> <function id> <name literal> ...
+        if opcode == OP_ENTRY_POINT && word_count > 3 {
+            let bytes: Vec<u8> = words[offset + 3..offset + word_count]
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .take_while(|byte| *byte != 0)
+                .collect();
+            return CString::new(bytes).ok();
+        }
+
+        offset += word_count;
+    }
+
+    None
+}
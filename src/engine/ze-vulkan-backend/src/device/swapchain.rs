@@ -0,0 +1,52 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use ze_gfx::backend::Texture;
+
+pub(crate) struct VulkanSwapChain {
+    pub frame_manager: Arc<FrameManager>,
+    pub swapchain: vk::SwapchainKHR,
+
+    /// Surface of the window, taken by the new swapchain when this one is recreated
+    pub surface: Mutex<Option<vk::SurfaceKHR>>,
+    pub textures: Vec<Arc<Texture>>,
+
+    /// Semaphores signaled when an image is acquired, there is one more than images so a free
+    /// semaphore is always available
+    pub acquire_semaphores: Vec<vk::Semaphore>,
+    pub next_acquire_semaphore: AtomicUsize,
+
+    /// Semaphores signaled when an image has been rendered, one per image
+    pub render_finished_semaphores: Vec<vk::Semaphore>,
+
+    /// Index of the image acquired for the current frame
+    pub current_image: Mutex<Option<u32>>,
+}
+
+impl VulkanSwapChain {
+    pub fn next_acquire_semaphore(&self) -> vk::Semaphore {
+        let index = self.next_acquire_semaphore.fetch_add(1, Ordering::SeqCst);
+        self.acquire_semaphores[index % self.acquire_semaphores.len()]
+    }
+}
+
+impl Drop for VulkanSwapChain {
+    fn drop(&mut self) {
+        let resource_queue = self.frame_manager.current_frame().resource_queue();
+        resource_queue.push(Entry::Swapchain(self.swapchain));
+        if let Some(surface) = self.surface.lock().take() {
+            resource_queue.push(Entry::Surface(surface));
+        }
+
+        for semaphore in self
+            .acquire_semaphores
+            .iter()
+            .chain(self.render_finished_semaphores.iter())
+        {
+            resource_queue.push(Entry::Semaphore(*semaphore));
+        }
+    }
+}
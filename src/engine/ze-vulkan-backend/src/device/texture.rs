@@ -0,0 +1,68 @@
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use gpu_allocator::vulkan::Allocation;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+
+pub(crate) struct VulkanTexture {
+    pub frame_manager: Arc<FrameManager>,
+    pub image: vk::Image,
+    pub allocation: Option<Allocation>,
+    pub format: vk::Format,
+    pub aspect_mask: vk::ImageAspectFlags,
+
+    /// Swapchain images are owned by their swapchain and must not be destroyed
+    pub is_swapchain_image: bool,
+
+    /// Layout of the image as of the last recorded barrier
+    /// Images start undefined, D3D12 resources are in the common state instead
+    layout: AtomicI32,
+}
+
+impl VulkanTexture {
+    pub fn new(
+        frame_manager: Arc<FrameManager>,
+        image: vk::Image,
+        allocation: Option<Allocation>,
+        format: vk::Format,
+        aspect_mask: vk::ImageAspectFlags,
+        is_swapchain_image: bool,
+    ) -> Self {
+        Self {
+            frame_manager,
+            image,
+            allocation,
+            format,
+            aspect_mask,
+            is_swapchain_image,
+            layout: AtomicI32::new(vk::ImageLayout::UNDEFINED.as_raw()),
+        }
+    }
+
+    pub fn layout(&self) -> vk::ImageLayout {
+        vk::ImageLayout::from_raw(self.layout.load(Ordering::SeqCst))
+    }
+
+    pub fn set_layout(&self, layout: vk::ImageLayout) -> vk::ImageLayout {
+        vk::ImageLayout::from_raw(self.layout.swap(layout.as_raw(), Ordering::SeqCst))
+    }
+}
+
+impl Drop for VulkanTexture {
+    fn drop(&mut self) {
+        if !self.is_swapchain_image {
+            self.frame_manager
+                .current_frame()
+                .resource_queue()
+                .push(Entry::Image(self.image));
+        }
+
+        if let Some(allocation) = self.allocation.take() {
+            self.frame_manager
+                .current_frame()
+                .resource_queue()
+                .push(Entry::Allocation(allocation));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::descriptor_manager::DescriptorManager;
+use crate::frame_manager::FrameManager;
+use crate::resource_manager::Entry;
+use ash::vk;
+use std::sync::Arc;
+use ze_gfx::backend::ShaderVisibleResource;
+
+pub(crate) struct VulkanRenderTargetView {
+    pub frame_manager: Arc<FrameManager>,
+    pub view: vk::ImageView,
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+    pub layer_count: u32,
+}
+
+impl Drop for VulkanRenderTargetView {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::ImageView(self.view));
+    }
+}
+
+pub(crate) struct VulkanDepthStencilView {
+    pub frame_manager: Arc<FrameManager>,
+    pub view: vk::ImageView,
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+    pub layer_count: u32,
+}
+
+impl Drop for VulkanDepthStencilView {
+    fn drop(&mut self) {
+        self.frame_manager
+            .current_frame()
+            .resource_queue()
+            .push(Entry::ImageView(self.view));
+    }
+}
+
+pub struct VulkanShaderResourceView {
+    pub descriptor_manager: Arc<DescriptorManager>,
+    pub frame_manager: Arc<FrameManager>,
+
+    /// Image view of texture views, buffer views only need a descriptor
+    pub view: Option<vk::ImageView>,
+    pub index: u32,
+}
+
+impl Drop for VulkanShaderResourceView {
+    fn drop(&mut self) {
+        let resource_queue = self.frame_manager.current_frame().resource_queue();
+        resource_queue.push(Entry::ResourceDescriptor(
+            self.descriptor_manager.clone(),
+            self.index,
+        ));
+
+        if let Some(view) = self.view {
+            resource_queue.push(Entry::ImageView(view));
+        }
+    }
+}
+
+impl ShaderVisibleResource for VulkanShaderResourceView {
+    fn descriptor_index(&self) -> u32 {
+        self.index
+    }
+}
+
+pub struct VulkanUnorderedAccessView {
+    pub descriptor_manager: Arc<DescriptorManager>,
+    pub frame_manager: Arc<FrameManager>,
+
+    /// Image view of texture views, buffer views only need a descriptor
+    pub view: Option<vk::ImageView>,
+    pub index: u32,
+}
+
+impl Drop for VulkanUnorderedAccessView {
+    fn drop(&mut self) {
+        let resource_queue = self.frame_manager.current_frame().resource_queue();
+        resource_queue.push(Entry::ResourceDescriptor(
+            self.descriptor_manager.clone(),
+            self.index,
+        ));
+
+        if let Some(view) = self.view {
+            resource_queue.push(Entry::ImageView(view));
+        }
+    }
+}
+
+impl ShaderVisibleResource for VulkanUnorderedAccessView {
+    fn descriptor_index(&self) -> u32 {
+        self.index
+    }
+}
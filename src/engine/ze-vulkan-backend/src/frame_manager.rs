@@ -0,0 +1,106 @@
+use crate::allocator::VulkanAllocator;
+use crate::command_manager::{CommandManager, Queue};
+use crate::device::{RawDevice, VulkanDevice};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::resource_manager::DeferredResourceQueue;
+
+pub(crate) struct Frame {
+    resource_manager: DeferredResourceQueue,
+    command_manager: CommandManager,
+}
+
+impl Frame {
+    pub fn new(
+        device: &Arc<RawDevice>,
+        allocator: &Arc<Mutex<VulkanAllocator>>,
+        graphics_queue: &Arc<Queue>,
+        compute_queue: &Arc<Queue>,
+        transfer_queue: &Arc<Queue>,
+    ) -> Self {
+        Self {
+            resource_manager: DeferredResourceQueue::new(device.clone(), allocator.clone()),
+            command_manager: CommandManager::new(
+                device,
+                graphics_queue,
+                compute_queue,
+                transfer_queue,
+            ),
+        }
+    }
+
+    /// Wait all works issued by this frame has been finished and reset all commands
+    pub fn wait_for_work(&self) {
+        self.command_manager.wait_for_work();
+    }
+
+    pub fn resource_queue(&self) -> &DeferredResourceQueue {
+        &self.resource_manager
+    }
+
+    pub fn command_manager(&self) -> &CommandManager {
+        &self.command_manager
+    }
+}
+
+/// Manage multiple frames that may be processed concurrently without concerns
+pub(crate) struct FrameManager {
+    frames: Vec<Frame>,
+    frame_count: usize,
+    current_frame: AtomicUsize,
+}
+
+impl FrameManager {
+    pub fn new(
+        frame_count: usize,
+        device: &Arc<RawDevice>,
+        allocator: &Arc<Mutex<VulkanAllocator>>,
+        graphics_queue: &Arc<Queue>,
+        compute_queue: &Arc<Queue>,
+        transfer_queue: &Arc<Queue>,
+    ) -> Self {
+        let mut frames = vec![];
+        for _ in 0..frame_count {
+            frames.push(Frame::new(
+                device,
+                allocator,
+                graphics_queue,
+                compute_queue,
+                transfer_queue,
+            ));
+        }
+
+        Self {
+            frames,
+            frame_count,
+            current_frame: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn begin_frame(&self, _: &VulkanDevice) {
+        self.current_frame
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |old_frame| {
+                Some((old_frame + 1) % self.frame_count)
+            })
+            .unwrap();
+
+        self.current_frame().command_manager().new_frame();
+        self.current_frame().resource_queue().flush();
+    }
+
+    pub fn wait_for_work(&self) {
+        for frame in &self.frames {
+            frame.wait_for_work();
+        }
+    }
+
+    pub fn current_frame(&self) -> &Frame {
+        &self.frames[self.current_frame.load(Ordering::SeqCst)]
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+}
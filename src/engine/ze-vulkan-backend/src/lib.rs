@@ -0,0 +1,9 @@
+mod allocator;
+pub mod backend;
+mod command_manager;
+mod descriptor_manager;
+mod device;
+mod frame_manager;
+mod pipeline_manager;
+mod resource_manager;
+mod utils;
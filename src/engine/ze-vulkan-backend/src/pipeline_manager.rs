@@ -0,0 +1,294 @@
+use crate::device::RawDevice;
+use ash::vk;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::Arc;
+use ze_gfx::backend::MAX_RENDER_PASS_RENDER_TARGET_COUNT;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ShaderStageDesc {
+    pub module: vk::ShaderModule,
+    pub entry_point: CString,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderTargetBlendDesc {
+    pub enable_blend: bool,
+    pub src_color_blend_factor: vk::BlendFactor,
+    pub dst_color_blend_factor: vk::BlendFactor,
+    pub color_blend_op: vk::BlendOp,
+    pub src_alpha_blend_factor: vk::BlendFactor,
+    pub dst_alpha_blend_factor: vk::BlendFactor,
+    pub alpha_blend_op: vk::BlendOp,
+}
+
+impl Default for RenderTargetBlendDesc {
+    fn default() -> Self {
+        Self {
+            enable_blend: false,
+            src_color_blend_factor: vk::BlendFactor::ZERO,
+            dst_color_blend_factor: vk::BlendFactor::ZERO,
+            color_blend_op: vk::BlendOp::ADD,
+            src_alpha_blend_factor: vk::BlendFactor::ZERO,
+            dst_alpha_blend_factor: vk::BlendFactor::ZERO,
+            alpha_blend_op: vk::BlendOp::ADD,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StencilOpDesc {
+    pub fail_op: vk::StencilOp,
+    pub pass_op: vk::StencilOp,
+    pub depth_fail_op: vk::StencilOp,
+    pub compare_op: vk::CompareOp,
+}
+
+impl Default for StencilOpDesc {
+    fn default() -> Self {
+        Self {
+            fail_op: vk::StencilOp::KEEP,
+            pass_op: vk::StencilOp::KEEP,
+            depth_fail_op: vk::StencilOp::KEEP,
+            compare_op: vk::CompareOp::ALWAYS,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DepthStencilDesc {
+    pub depth_test_enable: bool,
+    pub depth_write_enable: bool,
+    pub depth_compare_op: vk::CompareOp,
+    pub stencil_test_enable: bool,
+    pub stencil_read_mask: u8,
+    pub stencil_write_mask: u8,
+    pub front: StencilOpDesc,
+    pub back: StencilOpDesc,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GraphicsPipelineDesc {
+    pub vertex_shader: Option<ShaderStageDesc>,
+    pub pixel_shader: Option<ShaderStageDesc>,
+    pub mesh_shader: Option<ShaderStageDesc>,
+    pub primitive_topology: vk::PrimitiveTopology,
+    pub blend_state: [RenderTargetBlendDesc; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
+    pub depth_stencil_state: DepthStencilDesc,
+    pub color_formats: [vk::Format; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
+    pub color_format_count: u32,
+    pub depth_stencil_format: vk::Format,
+    pub sample_count: vk::SampleCountFlags,
+}
+
+impl Default for GraphicsPipelineDesc {
+    fn default() -> Self {
+        Self {
+            vertex_shader: None,
+            pixel_shader: None,
+            mesh_shader: None,
+            primitive_topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            blend_state: Default::default(),
+            depth_stencil_state: Default::default(),
+            color_formats: [vk::Format::UNDEFINED; MAX_RENDER_PASS_RENDER_TARGET_COUNT],
+            color_format_count: 0,
+            depth_stencil_format: vk::Format::UNDEFINED,
+            sample_count: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+}
+
+pub struct PipelineManager {
+    graphics_pipelines: RwLock<HashMap<GraphicsPipelineDesc, vk::Pipeline>>,
+
+    /// Compute pipelines, keyed by their shader stage
+    compute_pipelines: RwLock<HashMap<ShaderStageDesc, vk::Pipeline>>,
+    device: Arc<RawDevice>,
+}
+
+impl PipelineManager {
+    pub fn new(device: Arc<RawDevice>) -> Self {
+        Self {
+            graphics_pipelines: Default::default(),
+            compute_pipelines: Default::default(),
+            device,
+        }
+    }
+
+    pub fn get_or_create_graphics_pipeline(
+        &self,
+        layout: vk::PipelineLayout,
+        desc: &GraphicsPipelineDesc,
+    ) -> vk::Pipeline {
+        if let Some(pipeline) = self.graphics_pipelines.read().get(desc) {
+            return *pipeline;
+        }
+
+        let mut graphics_pipelines = self.graphics_pipelines.write();
+        if let Some(pipeline) = graphics_pipelines.get(desc) {
+            return *pipeline;
+        }
+
+        let mut stages = vec![];
+        let mut add_stage = |stage: vk::ShaderStageFlags, desc: &Option<ShaderStageDesc>| {
+            if let Some(desc) = desc {
+                stages.push(
+                    vk::PipelineShaderStageCreateInfo::builder()
+                        .stage(stage)
+                        .module(desc.module)
+                        .name(&desc.entry_point)
+                        .build(),
+                );
+            }
+        };
+        add_stage(vk::ShaderStageFlags::VERTEX, &desc.vertex_shader);
+        add_stage(vk::ShaderStageFlags::MESH_EXT, &desc.mesh_shader);
+        add_stage(vk::ShaderStageFlags::FRAGMENT, &desc.pixel_shader);
+
+        let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::default();
+        let input_assembly_state =
+            vk::PipelineInputAssemblyStateCreateInfo::builder().topology(desc.primitive_topology);
+        let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+            .viewport_count(1)
+            .scissor_count(1);
+        let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+            .polygon_mode(vk::PolygonMode::FILL)
+            .cull_mode(vk::CullModeFlags::NONE)
+            .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+            .line_width(1.0);
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+            .rasterization_samples(desc.sample_count);
+
+        let stencil_op = |op: &StencilOpDesc| vk::StencilOpState {
+            fail_op: op.fail_op,
+            pass_op: op.pass_op,
+            depth_fail_op: op.depth_fail_op,
+            compare_op: op.compare_op,
+            compare_mask: desc.depth_stencil_state.stencil_read_mask as u32,
+            write_mask: desc.depth_stencil_state.stencil_write_mask as u32,
+            reference: 0,
+        };
+        let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(desc.depth_stencil_state.depth_test_enable)
+            .depth_write_enable(desc.depth_stencil_state.depth_write_enable)
+            .depth_compare_op(desc.depth_stencil_state.depth_compare_op)
+            .stencil_test_enable(desc.depth_stencil_state.stencil_test_enable)
+            .front(stencil_op(&desc.depth_stencil_state.front))
+            .back(stencil_op(&desc.depth_stencil_state.back));
+
+        // Like D3D12 without independent blending, every render target uses the first blend state
+        let blend = &desc.blend_state[0];
+        let blend_attachments = vec![
+            vk::PipelineColorBlendAttachmentState {
+                blend_enable: blend.enable_blend as vk::Bool32,
+                src_color_blend_factor: blend.src_color_blend_factor,
+                dst_color_blend_factor: blend.dst_color_blend_factor,
+                color_blend_op: blend.color_blend_op,
+                src_alpha_blend_factor: blend.src_alpha_blend_factor,
+                dst_alpha_blend_factor: blend.dst_alpha_blend_factor,
+                alpha_blend_op: blend.alpha_blend_op,
+                color_write_mask: vk::ColorComponentFlags::RGBA,
+            };
+            desc.color_format_count as usize
+        ];
+        let color_blend_state =
+            vk::PipelineColorBlendStateCreateInfo::builder().attachments(&blend_attachments);
+
+        let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+        let dynamic_state =
+            vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_states);
+
+        let stencil_format = if desc.depth_stencil_format == vk::Format::D24_UNORM_S8_UINT
+            || desc.depth_stencil_format == vk::Format::D32_SFLOAT_S8_UINT
+        {
+            desc.depth_stencil_format
+        } else {
+            vk::Format::UNDEFINED
+        };
+
+        let mut rendering_info = vk::PipelineRenderingCreateInfo::builder()
+            .color_attachment_formats(&desc.color_formats[..desc.color_format_count as usize])
+            .depth_attachment_format(desc.depth_stencil_format)
+            .stencil_attachment_format(stencil_format);
+
+        let mut create_info = vk::GraphicsPipelineCreateInfo::builder()
+            .stages(&stages)
+            .viewport_state(&viewport_state)
+            .rasterization_state(&rasterization_state)
+            .multisample_state(&multisample_state)
+            .depth_stencil_state(&depth_stencil_state)
+            .color_blend_state(&color_blend_state)
+            .dynamic_state(&dynamic_state)
+            .layout(layout)
+            .push_next(&mut rendering_info);
+
+        // Mesh pipelines have no vertex input
+        if desc.mesh_shader.is_none() {
+            create_info = create_info
+                .vertex_input_state(&vertex_input_state)
+                .input_assembly_state(&input_assembly_state);
+        }
+
+        let pipeline = unsafe {
+            self.device.device.create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &[create_info.build()],
+                None,
+            )
+        }
+        .unwrap()[0];
+
+        graphics_pipelines.insert(desc.clone(), pipeline);
+        pipeline
+    }
+
+    pub fn get_or_create_compute_pipeline(
+        &self,
+        layout: vk::PipelineLayout,
+        compute_shader: &ShaderStageDesc,
+    ) -> vk::Pipeline {
+        if let Some(pipeline) = self.compute_pipelines.read().get(compute_shader) {
+            return *pipeline;
+        }
+
+        let mut compute_pipelines = self.compute_pipelines.write();
+        if let Some(pipeline) = compute_pipelines.get(compute_shader) {
+            return *pipeline;
+        }
+
+        let stage = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::COMPUTE)
+            .module(compute_shader.module)
+            .name(&compute_shader.entry_point);
+
+        let pipeline = unsafe {
+            self.device.device.create_compute_pipelines(
+                vk::PipelineCache::null(),
+                &[vk::ComputePipelineCreateInfo::builder()
+                    .stage(stage.build())
+                    .layout(layout)
+                    .build()],
+                None,
+            )
+        }
+        .unwrap()[0];
+
+        compute_pipelines.insert(compute_shader.clone(), pipeline);
+        pipeline
+    }
+}
+
+impl Drop for PipelineManager {
+    fn drop(&mut self) {
+        unsafe {
+            for pipeline in self.graphics_pipelines.get_mut().values() {
+                self.device.device.destroy_pipeline(*pipeline, None);
+            }
+
+            for pipeline in self.compute_pipelines.get_mut().values() {
+                self.device.device.destroy_pipeline(*pipeline, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::allocator::VulkanAllocator;
+use crate::descriptor_manager::DescriptorManager;
+use crate::device::RawDevice;
+use ash::vk;
+use gpu_allocator::vulkan::Allocation;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+pub enum Entry {
+    Buffer(vk::Buffer),
+    Image(vk::Image),
+    ImageView(vk::ImageView),
+    Sampler(vk::Sampler),
+    ShaderModule(vk::ShaderModule),
+    Swapchain(vk::SwapchainKHR),
+    Surface(vk::SurfaceKHR),
+    Semaphore(vk::Semaphore),
+    QueryPool(vk::QueryPool),
+    Allocation(Allocation),
+
+    /// Shader visible descriptors may still be read by in-flight frames
+    ResourceDescriptor(Arc<DescriptorManager>, u32),
+}
+
+/// This object manage resource lifetimes in a elegant way
+/// Providing a way to defer destruction and managing multiple frames
+///
+/// Unlike D3D12, Vulkan objects are not reference counted so they are destroyed by hand when
+/// flushing, in the order they were pushed
+pub(crate) struct DeferredResourceQueue {
+    queue: Mutex<Vec<Entry>>,
+    allocator: Arc<Mutex<VulkanAllocator>>,
+    device: Arc<RawDevice>,
+}
+
+impl DeferredResourceQueue {
+    pub fn new(device: Arc<RawDevice>, allocator: Arc<Mutex<VulkanAllocator>>) -> Self {
+        Self {
+            queue: Default::default(),
+            allocator,
+            device,
+        }
+    }
+
+    pub fn push(&self, entry: Entry) {
+        self.queue.lock().push(entry)
+    }
+
+    pub fn flush(&self) {
+        let entries: Vec<Entry> = self.queue.lock().drain(..).collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        let device = &self.device.device;
+        let mut allocator = self.allocator.lock();
+        for entry in entries {
+            unsafe {
+                match entry {
+                    Entry::Buffer(buffer) => device.destroy_buffer(buffer, None),
+                    Entry::Image(image) => device.destroy_image(image, None),
+                    Entry::ImageView(view) => device.destroy_image_view(view, None),
+                    Entry::Sampler(sampler) => device.destroy_sampler(sampler, None),
+                    Entry::ShaderModule(module) => device.destroy_shader_module(module, None),
+                    Entry::Swapchain(swapchain) => self
+                        .device
+                        .swapchain_loader
+                        .destroy_swapchain(swapchain, None),
+                    Entry::Surface(surface) => {
+                        self.device.surface_loader.destroy_surface(surface, None)
+                    }
+                    Entry::Semaphore(semaphore) => device.destroy_semaphore(semaphore, None),
+                    Entry::QueryPool(pool) => device.destroy_query_pool(pool, None),
+                    Entry::Allocation(allocation) => {
+                        let _ = allocator.free(allocation);
+                    }
+                    Entry::ResourceDescriptor(descriptor_manager, index) => {
+                        descriptor_manager.free_resource_descriptor_index(index)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for DeferredResourceQueue {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
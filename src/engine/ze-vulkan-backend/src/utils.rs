@@ -0,0 +1,312 @@
+use ash::extensions::ext::DebugUtils;
+use ash::vk;
+use ash::vk::Handle;
+use std::ffi::CString;
+use ze_gfx::backend::{
+    BlendFactor, BlendOp, CompareOp, DeviceError, Filter, MemoryLocation, PrimitiveTopology,
+    RenderPassTextureLoadMode, RenderPassTextureStoreMode, ResourceState, StencilOp,
+    TextureAddressMode,
+};
+use ze_gfx::{PixelFormat, SampleDesc};
+
+// Utils conversion functions
+pub fn get_memory_location_from_ze_memory_location(
+    memory_location: MemoryLocation,
+) -> gpu_allocator::MemoryLocation {
+    match memory_location {
+        MemoryLocation::CpuToGpu => gpu_allocator::MemoryLocation::CpuToGpu,
+        MemoryLocation::GpuOnly => gpu_allocator::MemoryLocation::GpuOnly,
+        MemoryLocation::GpuToCpu => gpu_allocator::MemoryLocation::GpuToCpu,
+    }
+}
+
+pub fn get_vk_format_from_ze_format(format: PixelFormat) -> vk::Format {
+    match format {
+        PixelFormat::Unknown => vk::Format::UNDEFINED,
+        PixelFormat::B8G8R8A8UnormSrgb => vk::Format::B8G8R8A8_SRGB,
+        PixelFormat::B8G8R8A8Unorm => vk::Format::B8G8R8A8_UNORM,
+        PixelFormat::R8Unorm => vk::Format::R8_UNORM,
+        PixelFormat::R32Uint => vk::Format::R32_UINT,
+        PixelFormat::R8G8B8A8Unorm => vk::Format::R8G8B8A8_UNORM,
+        PixelFormat::D24UnormS8Uint => vk::Format::D24_UNORM_S8_UINT,
+        PixelFormat::R16G16B16A16Sfloat => vk::Format::R16G16B16A16_SFLOAT,
+        PixelFormat::Bc1Unorm => vk::Format::BC1_RGBA_UNORM_BLOCK,
+        PixelFormat::Bc1UnormSrgb => vk::Format::BC1_RGBA_SRGB_BLOCK,
+        PixelFormat::Bc2Unorm => vk::Format::BC2_UNORM_BLOCK,
+        PixelFormat::Bc2UnormSrgb => vk::Format::BC2_SRGB_BLOCK,
+        PixelFormat::Bc3Unorm => vk::Format::BC3_UNORM_BLOCK,
+        PixelFormat::Bc3UnormSrgb => vk::Format::BC3_SRGB_BLOCK,
+        PixelFormat::Bc4Unorm => vk::Format::BC4_UNORM_BLOCK,
+        PixelFormat::Bc4Snorm => vk::Format::BC4_SNORM_BLOCK,
+        PixelFormat::Bc5Unorm => vk::Format::BC5_UNORM_BLOCK,
+        PixelFormat::Bc5Snorm => vk::Format::BC5_SNORM_BLOCK,
+        PixelFormat::Bc6hUfloat => vk::Format::BC6H_UFLOAT_BLOCK,
+        PixelFormat::Bc6hSfloat => vk::Format::BC6H_SFLOAT_BLOCK,
+        PixelFormat::Bc7Unorm => vk::Format::BC7_UNORM_BLOCK,
+        PixelFormat::Bc7UnormSrgb => vk::Format::BC7_SRGB_BLOCK,
+        _ => todo!(),
+    }
+}
+
+pub fn get_ze_format_from_vk_format(format: vk::Format) -> PixelFormat {
+    match format {
+        vk::Format::UNDEFINED => PixelFormat::Unknown,
+        vk::Format::B8G8R8A8_SRGB => PixelFormat::B8G8R8A8UnormSrgb,
+        vk::Format::B8G8R8A8_UNORM => PixelFormat::B8G8R8A8Unorm,
+        vk::Format::R8_UNORM => PixelFormat::R8Unorm,
+        vk::Format::R32_UINT => PixelFormat::R32Uint,
+        vk::Format::R8G8B8A8_UNORM => PixelFormat::R8G8B8A8Unorm,
+        vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT => {
+            PixelFormat::D24UnormS8Uint
+        }
+        vk::Format::R16G16B16A16_SFLOAT => PixelFormat::R16G16B16A16Sfloat,
+        vk::Format::BC1_RGBA_UNORM_BLOCK => PixelFormat::Bc1Unorm,
+        vk::Format::BC1_RGBA_SRGB_BLOCK => PixelFormat::Bc1UnormSrgb,
+        vk::Format::BC2_UNORM_BLOCK => PixelFormat::Bc2Unorm,
+        vk::Format::BC2_SRGB_BLOCK => PixelFormat::Bc2UnormSrgb,
+        vk::Format::BC3_UNORM_BLOCK => PixelFormat::Bc3Unorm,
+        vk::Format::BC3_SRGB_BLOCK => PixelFormat::Bc3UnormSrgb,
+        vk::Format::BC4_UNORM_BLOCK => PixelFormat::Bc4Unorm,
+        vk::Format::BC4_SNORM_BLOCK => PixelFormat::Bc4Snorm,
+        vk::Format::BC5_UNORM_BLOCK => PixelFormat::Bc5Unorm,
+        vk::Format::BC5_SNORM_BLOCK => PixelFormat::Bc5Snorm,
+        vk::Format::BC6H_UFLOAT_BLOCK => PixelFormat::Bc6hUfloat,
+        vk::Format::BC6H_SFLOAT_BLOCK => PixelFormat::Bc6hSfloat,
+        vk::Format::BC7_UNORM_BLOCK => PixelFormat::Bc7Unorm,
+        vk::Format::BC7_SRGB_BLOCK => PixelFormat::Bc7UnormSrgb,
+        _ => todo!(),
+    }
+}
+
+pub fn get_vk_aspect_mask_from_vk_format(format: vk::Format) -> vk::ImageAspectFlags {
+    match format {
+        vk::Format::D24_UNORM_S8_UINT | vk::Format::D32_SFLOAT_S8_UINT => {
+            vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+        }
+        vk::Format::D32_SFLOAT | vk::Format::D16_UNORM => vk::ImageAspectFlags::DEPTH,
+        _ => vk::ImageAspectFlags::COLOR,
+    }
+}
+
+pub fn get_vk_sample_count_from_ze_sample_desc(sample_desc: SampleDesc) -> vk::SampleCountFlags {
+    match sample_desc.count {
+        2 => vk::SampleCountFlags::TYPE_2,
+        4 => vk::SampleCountFlags::TYPE_4,
+        8 => vk::SampleCountFlags::TYPE_8,
+        16 => vk::SampleCountFlags::TYPE_16,
+        _ => vk::SampleCountFlags::TYPE_1,
+    }
+}
+
+pub fn convert_vk_error_to_ze_device_error(result: vk::Result) -> DeviceError {
+    match result {
+        vk::Result::ERROR_OUT_OF_HOST_MEMORY | vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+            DeviceError::OutOfMemory
+        }
+        _ => DeviceError::Unknown,
+    }
+}
+
+pub fn convert_allocation_error_to_ze_device_error(
+    error: gpu_allocator::AllocationError,
+) -> DeviceError {
+    match error {
+        gpu_allocator::AllocationError::OutOfMemory => DeviceError::OutOfMemory,
+        gpu_allocator::AllocationError::NoCompatibleMemoryTypeFound => {
+            DeviceError::NoCompatibleMemoryTypeFound
+        }
+        gpu_allocator::AllocationError::InvalidAllocationCreateDesc => {
+            DeviceError::InvalidParameters
+        }
+        _ => DeviceError::Unknown,
+    }
+}
+
+pub fn get_vk_attachment_load_op_from_ze_load_mode(
+    load: RenderPassTextureLoadMode,
+) -> vk::AttachmentLoadOp {
+    match load {
+        RenderPassTextureLoadMode::Discard => vk::AttachmentLoadOp::DONT_CARE,
+        RenderPassTextureLoadMode::Preserve => vk::AttachmentLoadOp::LOAD,
+        RenderPassTextureLoadMode::Clear => vk::AttachmentLoadOp::CLEAR,
+    }
+}
+
+pub fn get_vk_attachment_store_op_from_ze_store_mode(
+    store: RenderPassTextureStoreMode,
+) -> vk::AttachmentStoreOp {
+    match store {
+        RenderPassTextureStoreMode::Discard => vk::AttachmentStoreOp::DONT_CARE,
+        RenderPassTextureStoreMode::Preserve | RenderPassTextureStoreMode::Resolve => {
+            vk::AttachmentStoreOp::STORE
+        }
+    }
+}
+
+/// Stages, accesses and image layout matching a resource state
+///
+/// Shader reads use the general layout as D3D12 lets shaders read resources in the common
+/// state, and shader resource descriptors are written with a single layout
+pub fn get_vk_barrier_info_from_ze_resource_state(
+    state: ResourceState,
+) -> (vk::PipelineStageFlags2, vk::AccessFlags2, vk::ImageLayout) {
+    match state {
+        ResourceState::Common => (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE,
+            vk::ImageLayout::GENERAL,
+        ),
+        ResourceState::IndexBufferRead => (
+            vk::PipelineStageFlags2::INDEX_INPUT,
+            vk::AccessFlags2::INDEX_READ,
+            vk::ImageLayout::GENERAL,
+        ),
+        ResourceState::UnorderedAccessReadWrite => (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::SHADER_STORAGE_READ | vk::AccessFlags2::SHADER_STORAGE_WRITE,
+            vk::ImageLayout::GENERAL,
+        ),
+        ResourceState::RenderTargetWrite => (
+            vk::PipelineStageFlags2::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags2::COLOR_ATTACHMENT_READ | vk::AccessFlags2::COLOR_ATTACHMENT_WRITE,
+            vk::ImageLayout::ATTACHMENT_OPTIMAL,
+        ),
+        ResourceState::DepthRead => (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags2::SHADER_SAMPLED_READ,
+            vk::ImageLayout::GENERAL,
+        ),
+        ResourceState::DepthWrite => (
+            vk::PipelineStageFlags2::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags2::LATE_FRAGMENT_TESTS,
+            vk::AccessFlags2::DEPTH_STENCIL_ATTACHMENT_READ
+                | vk::AccessFlags2::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            vk::ImageLayout::ATTACHMENT_OPTIMAL,
+        ),
+        ResourceState::ShaderRead => (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::SHADER_SAMPLED_READ | vk::AccessFlags2::SHADER_STORAGE_READ,
+            vk::ImageLayout::GENERAL,
+        ),
+        ResourceState::CopyRead => (
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_READ,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ),
+        ResourceState::CopyWrite => (
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        ),
+        ResourceState::ResolveRead => (
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_READ,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ),
+        ResourceState::ResolveWrite => (
+            vk::PipelineStageFlags2::ALL_TRANSFER,
+            vk::AccessFlags2::TRANSFER_WRITE,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        ),
+        ResourceState::Present => (
+            vk::PipelineStageFlags2::ALL_COMMANDS,
+            vk::AccessFlags2::NONE,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+        ),
+    }
+}
+
+pub fn get_vk_filter_from_ze_filter(filter: Filter) -> (vk::Filter, vk::SamplerMipmapMode) {
+    match filter {
+        Filter::Nearest => (vk::Filter::NEAREST, vk::SamplerMipmapMode::NEAREST),
+        Filter::Linear => (vk::Filter::LINEAR, vk::SamplerMipmapMode::LINEAR),
+    }
+}
+
+pub fn get_vk_sampler_address_mode_from_ze_texture_address_mode(
+    address_mode: TextureAddressMode,
+) -> vk::SamplerAddressMode {
+    match address_mode {
+        TextureAddressMode::Repeat => vk::SamplerAddressMode::REPEAT,
+        TextureAddressMode::Mirror => vk::SamplerAddressMode::MIRRORED_REPEAT,
+        TextureAddressMode::Clamp => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+    }
+}
+
+pub fn get_vk_compare_op_from_ze_compare_op(op: CompareOp) -> vk::CompareOp {
+    match op {
+        CompareOp::Never => vk::CompareOp::NEVER,
+        CompareOp::Less => vk::CompareOp::LESS,
+        CompareOp::Equal => vk::CompareOp::EQUAL,
+        CompareOp::LessEqual => vk::CompareOp::LESS_OR_EQUAL,
+        CompareOp::Greater => vk::CompareOp::GREATER,
+        CompareOp::NotEqual => vk::CompareOp::NOT_EQUAL,
+        CompareOp::GreaterEqual => vk::CompareOp::GREATER_OR_EQUAL,
+        CompareOp::Always => vk::CompareOp::ALWAYS,
+    }
+}
+
+pub fn get_vk_stencil_op(op: StencilOp) -> vk::StencilOp {
+    match op {
+        StencilOp::Keep => vk::StencilOp::KEEP,
+        StencilOp::Zero => vk::StencilOp::ZERO,
+        StencilOp::Replace => vk::StencilOp::REPLACE,
+        StencilOp::IncrementAndClamp => vk::StencilOp::INCREMENT_AND_CLAMP,
+        StencilOp::DecrementAndClamp => vk::StencilOp::DECREMENT_AND_CLAMP,
+        StencilOp::Invert => vk::StencilOp::INVERT,
+        StencilOp::IncrementAndWrap => vk::StencilOp::INCREMENT_AND_WRAP,
+        StencilOp::DecrementAndWrap => vk::StencilOp::DECREMENT_AND_WRAP,
+    }
+}
+
+pub fn get_vk_blend_factor(blend: BlendFactor) -> vk::BlendFactor {
+    match blend {
+        BlendFactor::Zero => vk::BlendFactor::ZERO,
+        BlendFactor::One => vk::BlendFactor::ONE,
+        BlendFactor::SrcColor => vk::BlendFactor::SRC_COLOR,
+        BlendFactor::OneMinusSrcColor => vk::BlendFactor::ONE_MINUS_SRC_COLOR,
+        BlendFactor::DstColor => vk::BlendFactor::DST_COLOR,
+        BlendFactor::OneMinusDstColor => vk::BlendFactor::ONE_MINUS_DST_COLOR,
+        BlendFactor::SrcAlpha => vk::BlendFactor::SRC_ALPHA,
+        BlendFactor::OneMinusSrcAlpha => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::DstAlpha => vk::BlendFactor::DST_ALPHA,
+        BlendFactor::OneMinusDstAlpha => vk::BlendFactor::ONE_MINUS_DST_ALPHA,
+    }
+}
+
+pub fn get_vk_blend_op(blend_op: BlendOp) -> vk::BlendOp {
+    match blend_op {
+        BlendOp::Add => vk::BlendOp::ADD,
+        BlendOp::Subtract => vk::BlendOp::SUBTRACT,
+        BlendOp::ReverseSubtract => vk::BlendOp::REVERSE_SUBTRACT,
+        BlendOp::Min => vk::BlendOp::MIN,
+        BlendOp::Max => vk::BlendOp::MAX,
+    }
+}
+
+pub fn get_vk_primitive_topology(topology: PrimitiveTopology) -> vk::PrimitiveTopology {
+    match topology {
+        PrimitiveTopology::Point => vk::PrimitiveTopology::POINT_LIST,
+        PrimitiveTopology::Line => vk::PrimitiveTopology::LINE_LIST,
+        PrimitiveTopology::Triangle => vk::PrimitiveTopology::TRIANGLE_LIST,
+    }
+}
+
+/// Name an object for debuggers and validation messages, does nothing without debug utils
+pub fn set_object_name<T: Handle>(
+    debug_utils: Option<&DebugUtils>,
+    device: vk::Device,
+    object: T,
+    name: &str,
+) {
+    if let Some(debug_utils) = debug_utils {
+        let name = CString::new(name).unwrap_or_default();
+        let info = vk::DebugUtilsObjectNameInfoEXT::builder()
+            .object_type(T::TYPE)
+            .object_handle(object.as_raw())
+            .object_name(&name);
+        unsafe {
+            let _ = debug_utils.set_debug_utils_object_name(device, &info);
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// World of entities and components, implemented by ze-ecs and re-exported as is
+// The Component and SystemId derives expand to `ze_ecs` paths, crates using them also depend on
+// ze-ecs
+pub use ze_ecs::*;
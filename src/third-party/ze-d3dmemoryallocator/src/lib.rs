@@ -11,9 +11,10 @@ use windows::Win32::Graphics::Direct3D12::*;
 use windows::Win32::Graphics::Dxgi::*;
 use ze_d3dmemoryallocator_sys::{
     D3D12MA_Allocation, D3D12MA_Allocation_ReleaseThis, D3D12MA_Allocator,
-    D3D12MA_Allocator_CreatePool, D3D12MA_Allocator_CreateResource, D3D12MA_Allocator_ReleaseThis,
-    D3D12MA_CreateAllocator, D3D12MA_Pool, D3D12MA_Pool_ReleaseThis, D3D12MA_ALLOCATION_CALLBACKS,
-    D3D12MA_ALLOCATION_DESC, D3D12MA_ALLOCATION_FLAGS_ALLOCATION_FLAG_CAN_ALIAS,
+    D3D12MA_Allocator_CreatePool, D3D12MA_Allocator_CreateResource, D3D12MA_Allocator_GetBudget,
+    D3D12MA_Allocator_ReleaseThis, D3D12MA_Budget, D3D12MA_CreateAllocator, D3D12MA_Pool,
+    D3D12MA_Pool_ReleaseThis, D3D12MA_ALLOCATION_CALLBACKS, D3D12MA_ALLOCATION_DESC,
+    D3D12MA_ALLOCATION_FLAGS_ALLOCATION_FLAG_CAN_ALIAS,
     D3D12MA_ALLOCATION_FLAGS_ALLOCATION_FLAG_COMMITTED,
     D3D12MA_ALLOCATION_FLAGS_ALLOCATION_FLAG_NEVER_ALLOCATE,
     D3D12MA_ALLOCATION_FLAGS_ALLOCATION_FLAG_STRATEGY_MIN_MEMORY,
@@ -73,6 +74,36 @@ pub struct PoolDesc {
     pub heap_flags: D3D12_HEAP_FLAGS,
 }
 
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Statistics {
+    pub block_count: u32,
+    pub allocation_count: u32,
+    pub block_bytes: u64,
+    pub allocation_bytes: u64,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Budget {
+    pub stats: Statistics,
+    pub usage_bytes: u64,
+    pub budget_bytes: u64,
+}
+
+impl From<D3D12MA_Budget> for Budget {
+    fn from(budget: D3D12MA_Budget) -> Self {
+        Self {
+            stats: Statistics {
+                block_count: budget.Stats.BlockCount,
+                allocation_count: budget.Stats.AllocationCount,
+                block_bytes: budget.Stats.BlockBytes,
+                allocation_bytes: budget.Stats.AllocationBytes,
+            },
+            usage_bytes: budget.UsageBytes,
+            budget_bytes: budget.BudgetBytes,
+        }
+    }
+}
+
 static MEMORY_LAYOUT_MAP: Lazy<RwLock<FnvHashMap<usize, Layout>>> =
     Lazy::new(|| RwLock::new(FnvHashMap::default()));
 
@@ -127,6 +158,7 @@ impl Allocator {
         &self,
         allocation_desc: &AllocationDesc,
         resource_desc: &D3D12_RESOURCE_DESC,
+        initial_state: D3D12_RESOURCE_STATES,
     ) -> Result<Allocation, HRESULT> {
         let mut allocation = ptr::null_mut();
 
@@ -147,7 +179,7 @@ impl Allocator {
                 self.allocator.as_ptr(),
                 &alloc_desc,
                 resource_desc as *const _ as *mut _,
-                0,
+                mem::transmute(initial_state),
                 ptr::null(),
                 &mut allocation,
                 &IID {
@@ -197,6 +229,21 @@ impl Allocator {
             Err(HRESULT(result))
         }
     }
+
+    /// Return the budget of the local and non-local memory segments
+    /// Usage and budget come from DXGI, statistics only cover memory allocated by this allocator
+    pub fn budget(&self) -> (Budget, Budget) {
+        unsafe {
+            let mut local_budget: D3D12MA_Budget = mem::zeroed();
+            let mut non_local_budget: D3D12MA_Budget = mem::zeroed();
+            D3D12MA_Allocator_GetBudget(
+                self.allocator.as_ptr(),
+                &mut local_budget,
+                &mut non_local_budget,
+            );
+            (local_budget.into(), non_local_budget.into())
+        }
+    }
 }
 
 impl Drop for Allocator {